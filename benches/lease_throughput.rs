@@ -0,0 +1,90 @@
+//! Lease+report throughput against SQLite across event counts and worker
+//! concurrency. Run with `cargo bench --bench lease_throughput`; criterion
+//! keeps the previous run as a baseline and reports throughput changes, so
+//! regressions in the lease eligibility query show up between commits.
+//! Pass `-- --save-baseline <name>` and `-- --baseline <name>` to compare
+//! against a named run instead.
+
+#![allow(clippy::expect_used)]
+
+use std::time::Duration;
+
+use criterion::{
+    BenchmarkId, Criterion, SamplingMode, Throughput, criterion_group, criterion_main,
+};
+use receiver::dispatcher::{LeaseBenchConfig, LeaseBenchReport, run_lease_bench};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use tempfile::NamedTempFile;
+
+const EVENT_COUNTS: [usize; 2] = [1_000, 5_000];
+const WORKER_COUNTS: [usize; 3] = [1, 4, 8];
+
+fn lease_throughput(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("build tokio runtime");
+
+    let mut group = c.benchmark_group("lease_throughput");
+    group
+        .sampling_mode(SamplingMode::Flat)
+        .sample_size(10)
+        .measurement_time(Duration::from_secs(20));
+    for events in EVENT_COUNTS {
+        group.throughput(Throughput::Elements(events as u64));
+        for workers in WORKER_COUNTS {
+            let config = LeaseBenchConfig {
+                events,
+                workers,
+                ..LeaseBenchConfig::default()
+            };
+            group.bench_with_input(
+                BenchmarkId::new(format!("{events}_events"), workers),
+                &config,
+                |b, config| {
+                    // Each iteration drains a freshly seeded database; only
+                    // the drain is timed.
+                    b.to_async(&runtime).iter_custom(|iters| async move {
+                        let mut total = Duration::ZERO;
+                        for _ in 0..iters {
+                            total += drain_time(&run_once(config).await);
+                        }
+                        total
+                    });
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn drain_time(report: &LeaseBenchReport) -> Duration {
+    Duration::from_secs_f64(report.delivered as f64 / report.events_per_sec)
+}
+
+async fn run_once(config: &LeaseBenchConfig) -> LeaseBenchReport {
+    let db_file = NamedTempFile::new().expect("create temp sqlite file");
+    let options = SqliteConnectOptions::new()
+        .filename(db_file.path())
+        .create_if_missing(true)
+        .busy_timeout(Duration::from_secs(30));
+    let pool = SqlitePoolOptions::new()
+        .max_connections(u32::try_from(config.workers).expect("worker count fits u32"))
+        .connect_with(options)
+        .await
+        .expect("connect sqlite");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("run migrations");
+
+    let report = run_lease_bench(&pool, config)
+        .await
+        .expect("run lease bench");
+    assert_eq!(report.delivered, config.events, "bench left events queued");
+    pool.close().await;
+    report
+}
+
+criterion_group!(benches, lease_throughput);
+criterion_main!(benches);
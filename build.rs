@@ -45,4 +45,8 @@ fn main() {
     println!("cargo:rerun-if-changed=src/types/webhook_attempt_log.rs");
     println!("cargo:rerun-if-changed=src/types/target_circuit_state.rs");
     println!("cargo:rerun-if-changed=src/types/dispatcher.rs");
+    println!("cargo:rerun-if-changed=src/types/endpoint.rs");
+    println!("cargo:rerun-if-changed=src/types/ingest.rs");
+    println!("cargo:rerun-if-changed=src/types/scrub.rs");
+    println!("cargo:rerun-if-changed=src/types/payload_schema.rs");
 }
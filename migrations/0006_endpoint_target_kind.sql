@@ -0,0 +1,3 @@
+ALTER TABLE endpoints ADD COLUMN target_kind TEXT NOT NULL DEFAULT 'http';
+
+ALTER TABLE webhook_attempt_logs ADD COLUMN broker_confirmed INTEGER;
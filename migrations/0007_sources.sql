@@ -0,0 +1,12 @@
+CREATE TABLE sources (
+    id TEXT PRIMARY KEY NOT NULL,
+    slug TEXT NOT NULL UNIQUE,
+    provider TEXT NOT NULL,
+    endpoint_id TEXT NOT NULL REFERENCES endpoints(id),
+    secret TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+ALTER TABLE webhook_events ADD COLUMN source_id TEXT REFERENCES sources(id);
+
+CREATE INDEX idx_webhook_events_source_id ON webhook_events (source_id);
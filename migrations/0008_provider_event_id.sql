@@ -0,0 +1,4 @@
+ALTER TABLE webhook_events ADD COLUMN provider_event_id TEXT;
+
+CREATE INDEX idx_webhook_events_provider_event_id
+    ON webhook_events (provider, provider_event_id);
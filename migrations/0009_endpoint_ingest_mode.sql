@@ -0,0 +1 @@
+ALTER TABLE endpoints ADD COLUMN ingest_mode TEXT NOT NULL DEFAULT 'sync';
@@ -0,0 +1,7 @@
+CREATE TABLE dispatch_control (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 1),
+    paused INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT
+);
+
+INSERT INTO dispatch_control (id, paused) VALUES (1, 0);
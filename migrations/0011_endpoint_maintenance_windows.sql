@@ -0,0 +1,10 @@
+CREATE TABLE endpoint_maintenance_windows (
+    id TEXT PRIMARY KEY NOT NULL,
+    endpoint_id TEXT NOT NULL REFERENCES endpoints(id),
+    day_of_week INTEGER,
+    start_minute INTEGER NOT NULL,
+    duration_minutes INTEGER NOT NULL
+);
+
+CREATE INDEX idx_endpoint_maintenance_windows_endpoint_id
+    ON endpoint_maintenance_windows (endpoint_id);
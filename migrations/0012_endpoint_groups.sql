@@ -0,0 +1,11 @@
+CREATE TABLE endpoint_groups (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL UNIQUE,
+    paused INTEGER NOT NULL DEFAULT 0,
+    rate_limit_per_minute INTEGER,
+    created_at TEXT NOT NULL
+);
+
+ALTER TABLE endpoints ADD COLUMN group_id TEXT REFERENCES endpoint_groups(id);
+
+CREATE INDEX idx_endpoints_group_id ON endpoints (group_id);
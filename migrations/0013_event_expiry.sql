@@ -0,0 +1,5 @@
+ALTER TABLE webhook_events ADD COLUMN expires_at TEXT;
+
+ALTER TABLE endpoints ADD COLUMN default_ttl_seconds INTEGER;
+
+CREATE INDEX idx_webhook_events_expires_at ON webhook_events (expires_at);
@@ -0,0 +1 @@
+ALTER TABLE webhook_events ADD COLUMN leased_at TEXT;
@@ -0,0 +1,3 @@
+ALTER TABLE webhook_events ADD COLUMN deleted_at TEXT;
+
+ALTER TABLE webhook_events ADD COLUMN erased_at TEXT;
@@ -0,0 +1,19 @@
+CREATE TABLE scrub_rulesets (
+    id TEXT PRIMARY KEY NOT NULL,
+    provider TEXT,
+    endpoint_id TEXT REFERENCES endpoints(id),
+    version INTEGER NOT NULL,
+    rules TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    CHECK ((provider IS NULL) != (endpoint_id IS NULL))
+);
+
+CREATE UNIQUE INDEX idx_scrub_rulesets_provider
+    ON scrub_rulesets (provider) WHERE provider IS NOT NULL;
+
+CREATE UNIQUE INDEX idx_scrub_rulesets_endpoint_id
+    ON scrub_rulesets (endpoint_id) WHERE endpoint_id IS NOT NULL;
+
+ALTER TABLE webhook_events ADD COLUMN scrub_ruleset_id TEXT;
+
+ALTER TABLE webhook_events ADD COLUMN scrub_rule_version INTEGER;
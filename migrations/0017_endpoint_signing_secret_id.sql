@@ -0,0 +1 @@
+ALTER TABLE endpoints ADD COLUMN signing_secret_id TEXT;
@@ -0,0 +1,6 @@
+CREATE TABLE endpoint_fault_injections (
+    endpoint_id TEXT PRIMARY KEY NOT NULL REFERENCES endpoints(id),
+    failure_rate REAL NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
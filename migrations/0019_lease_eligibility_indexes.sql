@@ -0,0 +1,12 @@
+CREATE INDEX idx_webhook_events_status_next_attempt_received
+    ON webhook_events (status, next_attempt_at, received_at);
+
+CREATE INDEX idx_webhook_events_queued_received_at
+    ON webhook_events (received_at)
+    WHERE status IN ('pending', 'requeued');
+
+CREATE INDEX idx_webhook_events_endpoint_id ON webhook_events (endpoint_id);
+
+CREATE INDEX idx_webhook_attempt_logs_event_id ON webhook_attempt_logs (event_id);
+
+CREATE INDEX idx_webhook_attempt_logs_started_at ON webhook_attempt_logs (started_at);
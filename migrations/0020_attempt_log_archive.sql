@@ -0,0 +1,35 @@
+CREATE TABLE webhook_attempt_logs_archive (
+    id TEXT PRIMARY KEY NOT NULL,
+    event_id TEXT NOT NULL REFERENCES webhook_events(id),
+    attempt_no INTEGER NOT NULL,
+    started_at TEXT NOT NULL,
+    finished_at TEXT NOT NULL,
+    request_headers TEXT NOT NULL,
+    request_body TEXT NOT NULL,
+    response_status INTEGER,
+    response_headers TEXT,
+    response_body TEXT,
+    error_kind TEXT,
+    error_message TEXT,
+    broker_confirmed INTEGER,
+    archived_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_webhook_attempt_logs_archive_event_id
+    ON webhook_attempt_logs_archive (event_id);
+
+CREATE INDEX idx_webhook_attempt_logs_archive_finished_at
+    ON webhook_attempt_logs_archive (finished_at);
+
+CREATE INDEX idx_webhook_attempt_logs_finished_at ON webhook_attempt_logs (finished_at);
+
+CREATE VIEW webhook_attempt_logs_all AS
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed
+    FROM webhook_attempt_logs
+    UNION ALL
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed
+    FROM webhook_attempt_logs_archive;
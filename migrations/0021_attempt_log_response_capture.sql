@@ -0,0 +1,16 @@
+ALTER TABLE webhook_attempt_logs ADD COLUMN response_capture TEXT;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN response_capture TEXT;
+
+DROP VIEW webhook_attempt_logs_all;
+
+CREATE VIEW webhook_attempt_logs_all AS
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture
+    FROM webhook_attempt_logs
+    UNION ALL
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture
+    FROM webhook_attempt_logs_archive;
@@ -0,0 +1,12 @@
+CREATE TABLE audit_log (
+    id TEXT PRIMARY KEY NOT NULL,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    event_id TEXT,
+    endpoint_id TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_audit_log_created_at ON audit_log (created_at);
+
+CREATE INDEX idx_audit_log_event_id ON audit_log (event_id);
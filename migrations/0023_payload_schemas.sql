@@ -0,0 +1,15 @@
+CREATE TABLE payload_schemas (
+    provider TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    schema TEXT NOT NULL,
+    reject_invalid INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (provider, event_type)
+);
+
+ALTER TABLE webhook_events ADD COLUMN event_type TEXT;
+
+ALTER TABLE webhook_events ADD COLUMN schema_errors TEXT;
+
+CREATE INDEX idx_webhook_events_schema_invalid
+    ON webhook_events (received_at) WHERE schema_errors IS NOT NULL;
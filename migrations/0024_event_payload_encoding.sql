@@ -0,0 +1,4 @@
+-- Non-UTF-8 bodies are stored as BLOB values in the existing `payload`
+-- column, which TEXT affinity keeps as they are. This records which
+-- representation a row holds.
+ALTER TABLE webhook_events ADD COLUMN payload_encoding TEXT NOT NULL DEFAULT 'utf8';
@@ -0,0 +1,7 @@
+-- Maintained by every store write to an event. Existing rows start at
+-- their receipt time.
+ALTER TABLE webhook_events ADD COLUMN updated_at TEXT;
+
+UPDATE webhook_events SET updated_at = received_at;
+
+CREATE INDEX idx_webhook_events_updated_at ON webhook_events (updated_at);
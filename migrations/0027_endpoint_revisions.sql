@@ -0,0 +1,37 @@
+-- History of endpoint target URLs. Revision 1 of existing endpoints is
+-- their current target with no recorded author.
+ALTER TABLE endpoints ADD COLUMN target_revision INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE endpoint_revisions (
+    endpoint_id TEXT NOT NULL REFERENCES endpoints(id),
+    revision INTEGER NOT NULL,
+    target_url TEXT NOT NULL,
+    changed_by TEXT,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (endpoint_id, revision)
+);
+
+INSERT INTO endpoint_revisions (endpoint_id, revision, target_url, changed_by, created_at)
+SELECT id, target_revision, target_url, NULL, strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+FROM endpoints;
+
+-- The endpoint revision an event was leased against, copied onto the
+-- attempt its worker reports.
+ALTER TABLE webhook_events ADD COLUMN leased_target_revision INTEGER;
+
+ALTER TABLE webhook_attempt_logs ADD COLUMN target_revision INTEGER;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN target_revision INTEGER;
+
+DROP VIEW webhook_attempt_logs_all;
+
+CREATE VIEW webhook_attempt_logs_all AS
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision
+    FROM webhook_attempt_logs
+    UNION ALL
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision
+    FROM webhook_attempt_logs_archive;
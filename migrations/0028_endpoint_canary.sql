@@ -0,0 +1,27 @@
+-- Canary routing. canary_percent of an endpoint's events are delivered to
+-- canary_target_url instead of target_url, picked by event ID so retries
+-- keep going to the same target.
+ALTER TABLE endpoints ADD COLUMN canary_target_url TEXT;
+
+ALTER TABLE endpoints ADD COLUMN canary_percent INTEGER NOT NULL DEFAULT 0;
+
+-- The URL an event was leased to, copied onto the attempt its worker
+-- reports.
+ALTER TABLE webhook_events ADD COLUMN leased_target_url TEXT;
+
+ALTER TABLE webhook_attempt_logs ADD COLUMN target_url TEXT;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN target_url TEXT;
+
+DROP VIEW webhook_attempt_logs_all;
+
+CREATE VIEW webhook_attempt_logs_all AS
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url
+    FROM webhook_attempt_logs
+    UNION ALL
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url
+    FROM webhook_attempt_logs_archive;
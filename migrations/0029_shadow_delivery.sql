@@ -0,0 +1,19 @@
+-- Shadow delivery. Workers send a best-effort copy of each delivery to
+-- shadow_target_url and report the result separately. Shadow results
+-- never touch the event, its retries or the endpoint circuit.
+ALTER TABLE endpoints ADD COLUMN shadow_target_url TEXT;
+
+CREATE TABLE shadow_attempt_logs (
+    id TEXT PRIMARY KEY,
+    event_id TEXT NOT NULL REFERENCES webhook_events(id),
+    worker_id TEXT NOT NULL,
+    target_url TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    finished_at TEXT NOT NULL,
+    response_status INTEGER,
+    response_body TEXT,
+    error_kind TEXT,
+    error_message TEXT
+);
+
+CREATE INDEX idx_shadow_attempt_logs_event_id ON shadow_attempt_logs (event_id, started_at);
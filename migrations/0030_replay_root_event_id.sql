@@ -0,0 +1,19 @@
+-- First event of a replay chain, NULL for events that are not replays.
+-- Deliveries send it as X-Delivery-Id so consumers can dedupe across
+-- retries and replays.
+ALTER TABLE webhook_events ADD COLUMN replay_root_event_id TEXT;
+
+WITH RECURSIVE lineage(id, root_id) AS (
+    SELECT id, id
+    FROM webhook_events
+    WHERE replayed_from_event_id IS NULL OR replayed_from_event_id = ''
+    UNION ALL
+    SELECT e.id, l.root_id
+    FROM webhook_events e
+    JOIN lineage l ON e.replayed_from_event_id = l.id
+)
+UPDATE webhook_events
+SET replay_root_event_id = lineage.root_id
+FROM lineage
+WHERE lineage.id = webhook_events.id
+    AND lineage.id != lineage.root_id
@@ -0,0 +1,5 @@
+-- Secret rotation. The current signing secret may expire, and the one it
+-- replaced stays valid as the secondary until its own expiry.
+ALTER TABLE endpoints ADD COLUMN signing_secret_expires_at TEXT;
+ALTER TABLE endpoints ADD COLUMN secondary_signing_secret_id TEXT;
+ALTER TABLE endpoints ADD COLUMN secondary_signing_secret_expires_at TEXT;
@@ -0,0 +1,8 @@
+-- Delivery SLOs. slo_objective_percent of an endpoint's events should be
+-- delivered within slo_target_seconds of receipt. slo_breaches counts
+-- deliveries that missed the target and only ever grows.
+ALTER TABLE endpoints ADD COLUMN slo_target_seconds INTEGER;
+
+ALTER TABLE endpoints ADD COLUMN slo_objective_percent REAL;
+
+ALTER TABLE endpoints ADD COLUMN slo_breaches INTEGER NOT NULL DEFAULT 0;
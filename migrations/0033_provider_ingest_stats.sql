@@ -0,0 +1,11 @@
+-- Hourly ingest counters per provider. Rejected requests never become
+-- events, so their counts are only kept here.
+CREATE TABLE provider_ingest_stats (
+    provider TEXT NOT NULL,
+    bucket_start TEXT NOT NULL,
+    accepted INTEGER NOT NULL DEFAULT 0,
+    rejected_signature INTEGER NOT NULL DEFAULT 0,
+    rejected_size INTEGER NOT NULL DEFAULT 0,
+    rejected_schema INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (provider, bucket_start)
+);
@@ -0,0 +1,3 @@
+-- Wall-clock delivery budget. Once delivery_budget_seconds have passed
+-- since an event's first attempt, a retry report marks it dead.
+ALTER TABLE endpoints ADD COLUMN delivery_budget_seconds INTEGER;
@@ -0,0 +1,3 @@
+-- Per-attempt request timeout handed to workers with each lease. NULL
+-- falls back to the dispatcher default.
+ALTER TABLE endpoints ADD COLUMN request_timeout_ms INTEGER;
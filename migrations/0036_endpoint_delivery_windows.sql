@@ -0,0 +1,15 @@
+-- Delivery windows. An endpoint with any only takes deliveries while one
+-- is open. Windows are in local time, delivery_window_utc_offset_minutes
+-- ahead of UTC.
+CREATE TABLE endpoint_delivery_windows (
+    id TEXT PRIMARY KEY NOT NULL,
+    endpoint_id TEXT NOT NULL REFERENCES endpoints(id),
+    day_of_week INTEGER,
+    start_minute INTEGER NOT NULL,
+    duration_minutes INTEGER NOT NULL
+);
+
+CREATE INDEX idx_endpoint_delivery_windows_endpoint_id
+    ON endpoint_delivery_windows (endpoint_id);
+
+ALTER TABLE endpoints ADD COLUMN delivery_window_utc_offset_minutes INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,23 @@
+-- Pre-flight endpoint checks. The receiver hands HTTP endpoints with
+-- recent traffic to workers once per check interval, workers probe them
+-- with check_method against check_url (or target_url) and report the
+-- result here. Checks never touch events or the endpoint circuit.
+ALTER TABLE endpoints ADD COLUMN check_method TEXT NOT NULL DEFAULT 'head';
+ALTER TABLE endpoints ADD COLUMN check_url TEXT;
+ALTER TABLE endpoints ADD COLUMN next_check_at TEXT;
+
+CREATE TABLE endpoint_checks (
+    id TEXT PRIMARY KEY,
+    endpoint_id TEXT NOT NULL REFERENCES endpoints(id),
+    worker_id TEXT NOT NULL,
+    url TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    finished_at TEXT NOT NULL,
+    reachable INTEGER NOT NULL,
+    response_status INTEGER,
+    latency_ms INTEGER NOT NULL,
+    error_message TEXT,
+    tls_expires_at TEXT
+);
+
+CREATE INDEX idx_endpoint_checks_endpoint_id ON endpoint_checks (endpoint_id, finished_at);
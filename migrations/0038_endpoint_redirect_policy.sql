@@ -0,0 +1,24 @@
+-- Redirect policy handed to workers with each lease. redirect_mode is
+-- none, same_host or any, and redirect_max caps the redirects followed.
+ALTER TABLE endpoints ADD COLUMN redirect_mode TEXT NOT NULL DEFAULT 'none';
+
+ALTER TABLE endpoints ADD COLUMN redirect_max INTEGER NOT NULL DEFAULT 0;
+
+-- URL that produced the final response after redirects. NULL when the
+-- worker did not report one.
+ALTER TABLE webhook_attempt_logs ADD COLUMN final_url TEXT;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN final_url TEXT;
+
+DROP VIEW webhook_attempt_logs_all;
+
+CREATE VIEW webhook_attempt_logs_all AS
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url, final_url
+    FROM webhook_attempt_logs
+    UNION ALL
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url, final_url
+    FROM webhook_attempt_logs_archive;
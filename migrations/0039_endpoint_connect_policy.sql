@@ -0,0 +1,28 @@
+-- Connection settings handed to workers with each lease. address_family
+-- is any, ipv4, ipv6, prefer_ipv4 or prefer_ipv6. happy_eyeballs_delay_ms
+-- is how long a connection attempt gets before the other family is
+-- raced, NULL when addresses are tried one at a time.
+ALTER TABLE endpoints ADD COLUMN address_family TEXT NOT NULL DEFAULT 'any';
+
+ALTER TABLE endpoints ADD COLUMN happy_eyeballs_delay_ms INTEGER DEFAULT 250;
+
+-- Address the worker was connected to, e.g. [2001:db8::1]:443. NULL when
+-- the worker did not report one.
+ALTER TABLE webhook_attempt_logs ADD COLUMN peer_address TEXT;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN peer_address TEXT;
+
+DROP VIEW webhook_attempt_logs_all;
+
+CREATE VIEW webhook_attempt_logs_all AS
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url, final_url,
+        peer_address
+    FROM webhook_attempt_logs
+    UNION ALL
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url, final_url,
+        peer_address
+    FROM webhook_attempt_logs_archive;
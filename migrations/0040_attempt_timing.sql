@@ -0,0 +1,36 @@
+-- Phase timings reported by workers, in milliseconds. NULL when the
+-- worker did not measure the phase, e.g. tls_ms for plain HTTP.
+ALTER TABLE webhook_attempt_logs ADD COLUMN dns_ms INTEGER;
+
+ALTER TABLE webhook_attempt_logs ADD COLUMN connect_ms INTEGER;
+
+ALTER TABLE webhook_attempt_logs ADD COLUMN tls_ms INTEGER;
+
+ALTER TABLE webhook_attempt_logs ADD COLUMN ttfb_ms INTEGER;
+
+ALTER TABLE webhook_attempt_logs ADD COLUMN total_ms INTEGER;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN dns_ms INTEGER;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN connect_ms INTEGER;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN tls_ms INTEGER;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN ttfb_ms INTEGER;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN total_ms INTEGER;
+
+DROP VIEW webhook_attempt_logs_all;
+
+CREATE VIEW webhook_attempt_logs_all AS
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url, final_url,
+        peer_address, dns_ms, connect_ms, tls_ms, ttfb_ms, total_ms
+    FROM webhook_attempt_logs
+    UNION ALL
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url, final_url,
+        peer_address, dns_ms, connect_ms, tls_ms, ttfb_ms, total_ms
+    FROM webhook_attempt_logs_archive;
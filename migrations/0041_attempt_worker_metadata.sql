@@ -0,0 +1,34 @@
+-- Worker that made the attempt, so attempts of a misbehaving worker build
+-- or region can be singled out. NULL for attempts logged before workers
+-- reported it.
+ALTER TABLE webhook_attempt_logs ADD COLUMN worker_id TEXT;
+
+ALTER TABLE webhook_attempt_logs ADD COLUMN worker_version TEXT;
+
+ALTER TABLE webhook_attempt_logs ADD COLUMN worker_region TEXT;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN worker_id TEXT;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN worker_version TEXT;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN worker_region TEXT;
+
+CREATE INDEX idx_webhook_attempt_logs_worker_version
+    ON webhook_attempt_logs (worker_version, event_id);
+
+DROP VIEW webhook_attempt_logs_all;
+
+CREATE VIEW webhook_attempt_logs_all AS
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url, final_url,
+        peer_address, dns_ms, connect_ms, tls_ms, ttfb_ms, total_ms, worker_id,
+        worker_version, worker_region
+    FROM webhook_attempt_logs
+    UNION ALL
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url, final_url,
+        peer_address, dns_ms, connect_ms, tls_ms, ttfb_ms, total_ms, worker_id,
+        worker_version, worker_region
+    FROM webhook_attempt_logs_archive;
@@ -0,0 +1,7 @@
+-- Region whose workers deliver to the endpoint. NULL lets any worker
+-- lease its events. region_mode is prefer, where workers of other regions
+-- take over once an event has been due for the fallback delay, or
+-- require, where they never do.
+ALTER TABLE endpoints ADD COLUMN region TEXT;
+
+ALTER TABLE endpoints ADD COLUMN region_mode TEXT NOT NULL DEFAULT 'prefer';
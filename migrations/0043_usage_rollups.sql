@@ -0,0 +1,14 @@
+-- Daily usage per endpoint, kept up to date as events are ingested and
+-- attempts are logged. day is the UTC date, e.g. 2024-01-31. bytes_stored
+-- counts payloads and the request and response bodies kept on attempt
+-- logs, and is not reduced when they are purged.
+CREATE TABLE usage_rollups (
+    endpoint_id TEXT NOT NULL,
+    day TEXT NOT NULL,
+    events_ingested INTEGER NOT NULL DEFAULT 0,
+    deliveries_attempted INTEGER NOT NULL DEFAULT 0,
+    bytes_stored INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (endpoint_id, day)
+);
+
+CREATE INDEX idx_usage_rollups_day ON usage_rollups (day);
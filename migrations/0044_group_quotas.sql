@@ -0,0 +1,12 @@
+-- Ingest quotas per endpoint group. quota_events_per_day caps events
+-- ingested per UTC day and quota_storage_bytes caps payload bytes of the
+-- group's live events. NULL leaves either unlimited. quota_rejections
+-- counts ingest requests refused for either quota, and quota_exceeded_at
+-- is when the latest one was.
+ALTER TABLE endpoint_groups ADD COLUMN quota_events_per_day INTEGER;
+
+ALTER TABLE endpoint_groups ADD COLUMN quota_storage_bytes INTEGER;
+
+ALTER TABLE endpoint_groups ADD COLUMN quota_rejections INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE endpoint_groups ADD COLUMN quota_exceeded_at TEXT;
@@ -0,0 +1,13 @@
+-- History of endpoint circuit state changes, for support bundles. A row
+-- is written whenever a circuit opens or closes, with the state it
+-- changed to.
+CREATE TABLE target_circuit_transitions (
+    endpoint_id TEXT NOT NULL,
+    state TEXT NOT NULL,
+    open_until TEXT,
+    consecutive_failures INTEGER NOT NULL,
+    changed_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_target_circuit_transitions_endpoint
+    ON target_circuit_transitions (endpoint_id, changed_at);
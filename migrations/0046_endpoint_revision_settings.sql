@@ -0,0 +1,4 @@
+-- Delivery settings of the endpoint at each revision, as JSON. Revisions
+-- now also count changes to timeouts, redirects, connection and region
+-- settings. Older revisions only know their target_url.
+ALTER TABLE endpoint_revisions ADD COLUMN settings TEXT;
@@ -0,0 +1,11 @@
+-- Dry-run tokens that confirm a large group replay. A token is bound to
+-- the actor and the replay request it previewed and is used at most once.
+CREATE TABLE replay_confirmations (
+    token TEXT PRIMARY KEY,
+    actor TEXT NOT NULL,
+    request_hash TEXT NOT NULL,
+    matched INTEGER NOT NULL,
+    expires_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_replay_confirmations_expires_at ON replay_confirmations (expires_at);
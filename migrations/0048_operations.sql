@@ -0,0 +1,20 @@
+-- Bulk inspector operations and the events they created, so an operator
+-- can undo them while the new events are still waiting for delivery.
+CREATE TABLE operations (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    group_id TEXT,
+    endpoint_id TEXT,
+    actor TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    undone_at TEXT,
+    undone_by TEXT
+);
+
+CREATE INDEX idx_operations_created_at ON operations (created_at);
+
+CREATE TABLE operation_events (
+    operation_id TEXT NOT NULL REFERENCES operations (id),
+    event_id TEXT NOT NULL,
+    PRIMARY KEY (operation_id, event_id)
+);
@@ -0,0 +1,14 @@
+-- Progress of bulk operations. Operations are recorded when they start,
+-- and `processed` counts the events handled so far out of `total`.
+ALTER TABLE operations ADD COLUMN status TEXT NOT NULL DEFAULT 'completed';
+ALTER TABLE operations ADD COLUMN total INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE operations ADD COLUMN processed INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE operations ADD COLUMN finished_at TEXT;
+ALTER TABLE operations ADD COLUMN error TEXT;
+
+UPDATE operations
+SET total = (SELECT COUNT(*) FROM operation_events WHERE operation_id = operations.id),
+    processed = (SELECT COUNT(*) FROM operation_events WHERE operation_id = operations.id),
+    finished_at = created_at;
+
+CREATE INDEX idx_operations_status ON operations (status);
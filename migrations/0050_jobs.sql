@@ -0,0 +1,24 @@
+-- Background admin jobs. Replay jobs record their events and progress on
+-- the linked operation, and other jobs on the job row itself.
+CREATE TABLE jobs (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    status TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    operation_id TEXT REFERENCES operations (id),
+    total INTEGER NOT NULL DEFAULT 0,
+    processed INTEGER NOT NULL DEFAULT 0,
+    output_path TEXT,
+    error TEXT,
+    created_at TEXT NOT NULL,
+    started_at TEXT,
+    finished_at TEXT,
+    cancel_requested_at TEXT
+);
+
+CREATE INDEX idx_jobs_created_at ON jobs (created_at);
+CREATE INDEX idx_jobs_status ON jobs (status);
+
+-- Set when the job running an operation is cancelled. The replay stops
+-- before its next event.
+ALTER TABLE operations ADD COLUMN cancel_requested_at TEXT;
@@ -0,0 +1,14 @@
+-- Expand step for event priority. The column is added without a default
+-- so existing rows are not rewritten, new rows are written with 0, and the
+-- `event_priority` backfill fills the rest in batches.
+ALTER TABLE webhook_events ADD COLUMN priority INTEGER;
+
+-- Progress of the column backfills run between the expand and contract
+-- steps of a schema change.
+CREATE TABLE schema_backfills (
+    name TEXT PRIMARY KEY,
+    cursor INTEGER NOT NULL DEFAULT 0,
+    job_id TEXT REFERENCES jobs (id),
+    completed_at TEXT,
+    contracted_at TEXT
+);
@@ -0,0 +1,4 @@
+-- Hex SHA-256 of the stored payload bytes, written at ingestion and
+-- checked when payloads are read back. Older events are filled by the
+-- `payload_sha256` backfill.
+ALTER TABLE webhook_events ADD COLUMN payload_sha256 TEXT;
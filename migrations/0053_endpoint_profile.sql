@@ -0,0 +1,4 @@
+-- Human-readable label for an endpoint, shown next to its target URL so
+-- operators can tell which consumer an event belongs to.
+ALTER TABLE endpoints ADD COLUMN name TEXT;
+ALTER TABLE endpoints ADD COLUMN description TEXT;
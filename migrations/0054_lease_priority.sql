@@ -0,0 +1,5 @@
+-- Queued events are leased highest priority first, then oldest first.
+-- Events written before priorities existed count as priority 0.
+CREATE INDEX idx_webhook_events_queued_priority
+    ON webhook_events (COALESCE(priority, 0) DESC, received_at)
+    WHERE status IN ('pending', 'requeued');
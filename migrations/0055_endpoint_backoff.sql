@@ -0,0 +1,4 @@
+-- Retry backoff strategy of an endpoint as JSON, e.g.
+-- {"kind":"intervals","intervals":"1m,5m,30m,2h"}. NULL keeps the default
+-- exponential backoff.
+ALTER TABLE endpoints ADD COLUMN backoff_strategy TEXT;
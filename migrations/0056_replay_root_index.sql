@@ -0,0 +1,3 @@
+-- Lineage lookups gather a replay chain by its root.
+CREATE INDEX idx_webhook_events_replay_root
+    ON webhook_events (replay_root_event_id);
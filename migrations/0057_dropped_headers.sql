@@ -0,0 +1,4 @@
+-- JSON array of inbound header names the storage allowlist left out of
+-- `headers`, so signature requirements stay traceable. NULL when none
+-- were dropped.
+ALTER TABLE webhook_events ADD COLUMN dropped_headers TEXT;
@@ -0,0 +1,3 @@
+-- Provider fields extracted at ingestion as a JSON object, e.g.
+-- {"account_id":"acct_123"} for Stripe. NULL when nothing was extracted.
+ALTER TABLE webhook_events ADD COLUMN metadata TEXT;
@@ -0,0 +1,3 @@
+-- Delivery receipts look up a provider event among its source's events.
+CREATE INDEX idx_webhook_events_source_provider_event
+    ON webhook_events (source_id, provider_event_id);
@@ -0,0 +1,69 @@
+-- Hourly delivery counters per endpoint, kept up to date by reports so
+-- stats, SLO attainment and attempt time series need not scan attempt
+-- logs. Attempts count in the hour they finished, delivered and dead
+-- events in the hour of their final attempt. Latencies are sums so
+-- averages can be taken over any run of hours.
+CREATE TABLE delivery_stats_hourly (
+    endpoint_id TEXT NOT NULL,
+    bucket_start TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    attempt_duration_ms_sum REAL NOT NULL DEFAULT 0,
+    delivered INTEGER NOT NULL DEFAULT 0,
+    dead INTEGER NOT NULL DEFAULT 0,
+    -- From `received_at` to the end of the delivering attempt.
+    delivery_latency_ms_sum REAL NOT NULL DEFAULT 0,
+    -- Delivered within the endpoint's SLO target as set at delivery time.
+    delivered_within_slo INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (endpoint_id, bucket_start)
+);
+
+CREATE INDEX idx_delivery_stats_hourly_bucket ON delivery_stats_hourly (bucket_start);
+
+INSERT INTO delivery_stats_hourly (
+    endpoint_id, bucket_start, attempts, failed_attempts, attempt_duration_ms_sum
+)
+SELECT
+    e.endpoint_id,
+    strftime('%Y-%m-%dT%H:00:00Z', a.finished_at),
+    COUNT(*),
+    SUM(CASE WHEN a.error_kind IS NOT NULL
+        OR a.response_status < 200
+        OR a.response_status >= 300 THEN 1 ELSE 0 END),
+    COALESCE(SUM((julianday(a.finished_at) - julianday(a.started_at)) * 86400000.0), 0)
+FROM webhook_attempt_logs_all a
+JOIN webhook_events e ON e.id = a.event_id
+WHERE strftime('%Y-%m-%dT%H:00:00Z', a.finished_at) IS NOT NULL
+GROUP BY e.endpoint_id, strftime('%Y-%m-%dT%H:00:00Z', a.finished_at);
+
+INSERT INTO delivery_stats_hourly (
+    endpoint_id, bucket_start, delivered, dead, delivery_latency_ms_sum, delivered_within_slo
+)
+SELECT
+    endpoint_id,
+    bucket_start,
+    SUM(status = 'delivered'),
+    SUM(status = 'dead'),
+    COALESCE(SUM(CASE WHEN status = 'delivered' THEN latency_ms ELSE 0 END), 0),
+    SUM(CASE WHEN status = 'delivered' AND latency_ms <= slo_target_seconds * 1000.0
+        THEN 1 ELSE 0 END)
+FROM (
+    SELECT
+        e.endpoint_id,
+        e.status,
+        strftime('%Y-%m-%dT%H:00:00Z', MAX(a.finished_at)) AS bucket_start,
+        (julianday(MAX(a.finished_at)) - julianday(e.received_at)) * 86400000.0 AS latency_ms,
+        ep.slo_target_seconds
+    FROM webhook_events e
+    JOIN webhook_attempt_logs_all a ON a.event_id = e.id
+    JOIN endpoints ep ON ep.id = e.endpoint_id
+    WHERE e.status IN ('delivered', 'dead')
+    GROUP BY e.id
+)
+WHERE bucket_start IS NOT NULL
+GROUP BY endpoint_id, bucket_start
+ON CONFLICT (endpoint_id, bucket_start) DO UPDATE SET
+    delivered = excluded.delivered,
+    dead = excluded.dead,
+    delivery_latency_ms_sum = excluded.delivery_latency_ms_sum,
+    delivered_within_slo = excluded.delivered_within_slo;
@@ -0,0 +1,32 @@
+-- Log only 1 in this many successful attempts of the endpoint. Failed
+-- attempts are always logged. NULL logs every attempt.
+ALTER TABLE endpoints ADD COLUMN success_log_sample_rate INTEGER;
+
+-- Rate a successful attempt was sampled at when it was kept. NULL when
+-- sampling was off.
+ALTER TABLE webhook_attempt_logs ADD COLUMN sample_rate INTEGER;
+
+ALTER TABLE webhook_attempt_logs_archive ADD COLUMN sample_rate INTEGER;
+
+-- Attempts sampling left out of the attempt log, so `attempts` exceeds
+-- the logged count by this much, and the rate they were dropped at.
+ALTER TABLE webhook_events ADD COLUMN unlogged_attempts INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE webhook_events ADD COLUMN unlogged_sample_rate INTEGER;
+
+DROP VIEW webhook_attempt_logs_all;
+
+CREATE VIEW webhook_attempt_logs_all AS
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url, final_url,
+        peer_address, dns_ms, connect_ms, tls_ms, ttfb_ms, total_ms, worker_id,
+        worker_version, worker_region, sample_rate
+    FROM webhook_attempt_logs
+    UNION ALL
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        broker_confirmed, response_capture, target_revision, target_url, final_url,
+        peer_address, dns_ms, connect_ms, tls_ms, ttfb_ms, total_ms, worker_id,
+        worker_version, worker_region, sample_rate
+    FROM webhook_attempt_logs_archive;
@@ -0,0 +1,15 @@
+-- Attempts a replay inherited from its source under the carry-over
+-- budget. They count towards `attempts` but have no attempt logs of
+-- this event, so consistency checks add them to the logged count.
+ALTER TABLE webhook_events ADD COLUMN carried_attempts INTEGER NOT NULL DEFAULT 0;
+
+-- Carry-over replays made before the column existed: whatever their
+-- counter holds beyond their own attempts was inherited.
+UPDATE webhook_events
+SET carried_attempts = attempts - unlogged_attempts - (
+    SELECT COUNT(*) FROM webhook_attempt_logs_all a WHERE a.event_id = webhook_events.id
+)
+WHERE replayed_from_event_id IS NOT NULL
+  AND attempts > unlogged_attempts + (
+    SELECT COUNT(*) FROM webhook_attempt_logs_all a WHERE a.event_id = webhook_events.id
+);
@@ -0,0 +1,8 @@
+-- Dead events a bulk operation left alone, with the reason, so an
+-- operator can see which ones still need attention.
+CREATE TABLE operation_failures (
+    operation_id TEXT NOT NULL REFERENCES operations (id),
+    event_id TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    PRIMARY KEY (operation_id, event_id)
+);
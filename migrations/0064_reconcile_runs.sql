@@ -0,0 +1,18 @@
+-- Reconciliation jobs. A worker lists the source's events in the window
+-- through the provider's API and reports the IDs back, and the ones this
+-- receiver never ingested are kept for the job's report.
+CREATE TABLE reconcile_runs (
+    job_id TEXT PRIMARY KEY REFERENCES jobs (id),
+    source_id TEXT NOT NULL REFERENCES sources (id),
+    window_start TEXT NOT NULL,
+    window_end TEXT NOT NULL,
+    fetch_missing INTEGER NOT NULL DEFAULT 0,
+    leased_by TEXT,
+    lease_expires_at TEXT
+);
+
+CREATE TABLE reconcile_missing (
+    job_id TEXT NOT NULL REFERENCES reconcile_runs (job_id),
+    provider_event_id TEXT NOT NULL,
+    PRIMARY KEY (job_id, provider_event_id)
+);
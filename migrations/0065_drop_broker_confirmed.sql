@@ -0,0 +1,22 @@
+-- AMQP targets were never delivered to, so no attempt carries a broker
+-- confirm. Drop the column until a publisher exists.
+DROP VIEW webhook_attempt_logs_all;
+
+ALTER TABLE webhook_attempt_logs DROP COLUMN broker_confirmed;
+
+ALTER TABLE webhook_attempt_logs_archive DROP COLUMN broker_confirmed;
+
+CREATE VIEW webhook_attempt_logs_all AS
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        response_capture, target_revision, target_url, final_url,
+        peer_address, dns_ms, connect_ms, tls_ms, ttfb_ms, total_ms, worker_id,
+        worker_version, worker_region, sample_rate
+    FROM webhook_attempt_logs
+    UNION ALL
+    SELECT id, event_id, attempt_no, started_at, finished_at, request_headers, request_body,
+        response_status, response_headers, response_body, error_kind, error_message,
+        response_capture, target_revision, target_url, final_url,
+        peer_address, dns_ms, connect_ms, tls_ms, ttfb_ms, total_ms, worker_id,
+        worker_version, worker_region, sample_rate
+    FROM webhook_attempt_logs_archive;
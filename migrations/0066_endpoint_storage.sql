@@ -0,0 +1,13 @@
+-- Running total of the payload bytes each endpoint keeps, so storage
+-- quotas are checked without summing every stored payload. Soft-deleted
+-- events are not counted.
+CREATE TABLE endpoint_storage (
+    endpoint_id TEXT PRIMARY KEY REFERENCES endpoints (id),
+    payload_bytes INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT INTO endpoint_storage (endpoint_id, payload_bytes)
+SELECT endpoint_id, SUM(LENGTH(CAST(payload AS BLOB)))
+FROM webhook_events
+WHERE deleted_at IS NULL
+GROUP BY endpoint_id
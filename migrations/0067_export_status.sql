@@ -0,0 +1,12 @@
+-- Progress of the nightly export, so days missed while the receiver was
+-- down or the sink was failing are backfilled.
+CREATE TABLE export_status (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 1),
+    last_exported_day TEXT,
+    last_success_at TEXT,
+    last_error TEXT,
+    last_error_at TEXT,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT INTO export_status (id) VALUES (1)
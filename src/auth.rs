@@ -1,35 +1,96 @@
 use axum::{
     body::Body,
     extract::State,
-    http::{Request, header::AUTHORIZATION},
+    http::{HeaderMap, Request, header::AUTHORIZATION},
     middleware::Next,
     response::Response,
 };
+use sha2::{Digest, Sha256};
 use subtle::ConstantTimeEq;
+use uuid::Uuid;
 
-use crate::{error::ApiError, state::AppState};
+use crate::{error::ApiError, inspector::EndpointScope, state::AppState};
+
+/// An inspector token that only grants access to `endpoint_ids`.
+#[derive(Debug, Clone)]
+pub struct ScopedToken {
+    pub token: String,
+    pub endpoint_ids: Vec<Uuid>,
+}
+
+/// Who made an inspector request, as recorded in the audit log. Static
+/// tokens are identified by a short SHA-256 fingerprint, never by their
+/// value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InspectorActor(pub String);
+
+impl InspectorActor {
+    pub fn anonymous() -> Self {
+        Self("anonymous".to_string())
+    }
+
+    pub fn share_link() -> Self {
+        Self("share_link".to_string())
+    }
+
+    /// OIDC tokens are short-lived, so they are identified by their
+    /// subject instead of a fingerprint.
+    fn oidc(subject: &str) -> Self {
+        Self(format!("oidc:{subject}"))
+    }
+
+    fn token(kind: &str, token: &str) -> Self {
+        let digest = hex::encode(Sha256::digest(token.as_bytes()));
+        Self(format!("{kind}:{}", &digest[..12]))
+    }
+}
+
+/// Parses `token=endpoint_id,endpoint_id;token=endpoint_id` as used by
+/// `INSPECTOR_SCOPED_TOKENS`.
+pub fn parse_scoped_tokens(raw: &str) -> Result<Vec<ScopedToken>, String> {
+    raw.split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (token, endpoint_ids) = entry.split_once('=').ok_or_else(|| {
+                "scoped token entries must look like token=endpoint_id".to_string()
+            })?;
+            let token = token.trim();
+            if token.is_empty() {
+                return Err("scoped token must be non-empty".to_string());
+            }
+            let endpoint_ids = endpoint_ids
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(|id| {
+                    Uuid::parse_str(id).map_err(|err| format!("invalid endpoint id {id}: {err}"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if endpoint_ids.is_empty() {
+                return Err("scoped token must list at least one endpoint id".to_string());
+            }
+            Ok(ScopedToken {
+                token: token.to_string(),
+                endpoint_ids,
+            })
+        })
+        .collect()
+}
 
 pub async fn inspector_auth(
     State(state): State<AppState>,
-    req: Request<Body>,
+    mut req: Request<Body>,
     next: Next,
 ) -> Result<Response, ApiError> {
-    let Some(expected_token) = &state.inspector_api_token else {
+    if state.inspector_api_token.is_none()
+        && state.inspector_scoped_tokens.is_empty()
+        && state.inspector_oidc.is_none()
+    {
         return Ok(next.run(req).await);
-    };
+    }
 
-    let provided_token = match req
-        .headers()
-        .get(AUTHORIZATION)
-        .and_then(|v| v.to_str().ok())
-        .and_then(|value| {
-            let trimmed = value.trim_start();
-            if trimmed.len() >= 7 && trimmed[..7].eq_ignore_ascii_case("bearer ") {
-                Some(trimmed[7..].trim())
-            } else {
-                None
-            }
-        }) {
+    let provided_token = match bearer_token(req.headers()) {
         Some(token) => token,
         _ => {
             return Err(ApiError::unauthorized(
@@ -38,13 +99,61 @@ pub async fn inspector_auth(
         }
     };
 
-    if !constant_time_eq(expected_token.as_bytes(), provided_token.as_bytes()) {
+    let (scope, actor) = if let Some(scope) = token_scope(&state, provided_token) {
+        let kind = if scope.is_restricted() {
+            "scoped"
+        } else {
+            "admin"
+        };
+        (scope, InspectorActor::token(kind, provided_token))
+    } else if let Some(identity) = match &state.inspector_oidc {
+        Some(oidc) => oidc.verify(provided_token).await,
+        None => None,
+    } {
+        let scope = match identity.endpoint_ids {
+            Some(endpoint_ids) => EndpointScope::Endpoints(endpoint_ids),
+            None => EndpointScope::All,
+        };
+        (scope, InspectorActor::oidc(&identity.subject))
+    } else {
         return Err(ApiError::unauthorized("invalid token"));
-    }
+    };
 
+    req.extensions_mut().insert(actor);
+    req.extensions_mut().insert(scope);
     Ok(next.run(req).await)
 }
 
-fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+/// The full-access token wins over scoped tokens; unknown tokens get `None`.
+fn token_scope(state: &AppState, provided_token: &str) -> Option<EndpointScope> {
+    if let Some(expected) = &state.inspector_api_token
+        && constant_time_eq(expected.as_bytes(), provided_token.as_bytes())
+    {
+        return Some(EndpointScope::All);
+    }
+
+    state
+        .inspector_scoped_tokens
+        .iter()
+        .find(|scoped| constant_time_eq(scoped.token.as_bytes(), provided_token.as_bytes()))
+        .map(|scoped| EndpointScope::Endpoints(scoped.endpoint_ids.clone()))
+}
+
+/// The token of an `Authorization: Bearer` header.
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|value| {
+            let trimmed = value.trim_start();
+            if trimmed.len() >= 7 && trimmed[..7].eq_ignore_ascii_case("bearer ") {
+                Some(trimmed[7..].trim())
+            } else {
+                None
+            }
+        })
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
     a.ct_eq(b).into()
 }
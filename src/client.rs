@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::{Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+use crate::types::{
+    ApiErrorCode, ApiErrorResponse, CheckLeaseRequest, CheckLeaseResponse, CheckReportRequest,
+    CheckReportResponse, EndpointCheckTarget, LeaseRequest, LeaseResponse, LeasedEvent,
+    RenewRequest, RenewResponse, ReportRequest, ReportResponse, ShadowReportRequest,
+    ShadowReportResponse,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The worker no longer holds the lease (`lease_not_owned`,
+    /// `lease_expired` or `lease_missing`). The event must be dropped, not
+    /// retried: another worker may already own it.
+    #[error("lease lost: {0}")]
+    LeaseLost(String),
+
+    #[error("dispatcher returned {status}: {}", error.message)]
+    Api {
+        status: u16,
+        error: ApiErrorResponse,
+    },
+
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+/// How transient failures are retried: transport errors, 429 and 5xx
+/// responses. Delays double from `initial_backoff` up to `max_backoff`.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+/// Client for the `/internal/dispatcher` worker API.
+///
+/// A report whose response is lost in transit is retried like any other
+/// call. If the first attempt was applied, the retry fails with
+/// [`ClientError::LeaseLost`] since the lease is already released.
+#[derive(Debug, Clone)]
+pub struct DispatcherClient {
+    http: reqwest::Client,
+    base_url: String,
+    worker_id: String,
+    region: Option<String>,
+    retry: RetryPolicy,
+}
+
+impl DispatcherClient {
+    /// `base_url` is the receiver's root, e.g. `http://127.0.0.1:3000`.
+    pub fn new(base_url: impl Into<String>, worker_id: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            worker_id: worker_id.into(),
+            region: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Leases prefer events of endpoints pinned to `region`.
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub fn worker_id(&self) -> &str {
+        &self.worker_id
+    }
+
+    pub async fn lease(&self, limit: i64, lease_ms: i64) -> Result<Vec<LeasedEvent>, ClientError> {
+        let req = LeaseRequest {
+            limit,
+            lease_ms,
+            worker_id: self.worker_id.clone(),
+            region: self.region.clone(),
+            endpoint_id: None,
+        };
+        let response: LeaseResponse = self.post("/internal/dispatcher/lease", &req).await?;
+        Ok(response.events)
+    }
+
+    /// Extends the lease on `event_id` and returns its new expiry.
+    pub async fn renew(&self, event_id: Uuid, lease_ms: i64) -> Result<String, ClientError> {
+        let req = RenewRequest {
+            worker_id: self.worker_id.clone(),
+            event_id,
+            lease_ms,
+        };
+        let response: RenewResponse = self.post("/internal/dispatcher/renew", &req).await?;
+        Ok(response.lease_expires_at)
+    }
+
+    /// Reports a delivery attempt. `req.worker_id` must be this client's
+    /// worker ID.
+    pub async fn report(&self, req: &ReportRequest) -> Result<ReportResponse, ClientError> {
+        self.post("/internal/dispatcher/report", req).await
+    }
+
+    /// Logs a shadow delivery. Shadow results never affect the event, so
+    /// callers can ignore failures here.
+    pub async fn report_shadow(
+        &self,
+        req: &ShadowReportRequest,
+    ) -> Result<ShadowReportResponse, ClientError> {
+        self.post("/internal/dispatcher/shadow-report", req).await
+    }
+
+    /// Claims up to `limit` endpoints due for a pre-flight check.
+    pub async fn lease_checks(&self, limit: i64) -> Result<Vec<EndpointCheckTarget>, ClientError> {
+        let req = CheckLeaseRequest {
+            worker_id: self.worker_id.clone(),
+            limit,
+        };
+        let response: CheckLeaseResponse =
+            self.post("/internal/dispatcher/checks/lease", &req).await?;
+        Ok(response.checks)
+    }
+
+    /// Records the result of a pre-flight check.
+    pub async fn report_check(
+        &self,
+        req: &CheckReportRequest,
+    ) -> Result<CheckReportResponse, ClientError> {
+        self.post("/internal/dispatcher/checks/report", req).await
+    }
+
+    async fn post<B, R>(&self, path: &str, body: &B) -> Result<R, ClientError>
+    where
+        B: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let url = format!("{}{path}", self.base_url);
+        let mut retry = 0;
+        loop {
+            match self.send_once(&url, body).await {
+                Err(err) if retry < self.retry.max_retries && is_transient(&err) => {
+                    tokio::time::sleep(self.retry.backoff(retry)).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn send_once<B, R>(&self, url: &str, body: &B) -> Result<R, ClientError>
+    where
+        B: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let response = self.http.post(url).json(body).send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response.json().await?);
+        }
+
+        // Proxies in front of the receiver may answer with non-JSON bodies.
+        let text = response.text().await?;
+        let error = serde_json::from_str::<ApiErrorResponse>(&text).unwrap_or(ApiErrorResponse {
+            code: ApiErrorCode::Internal,
+            message: text,
+        });
+        if status == StatusCode::CONFLICT {
+            return Err(ClientError::LeaseLost(error.message));
+        }
+        Err(ClientError::Api {
+            status: status.as_u16(),
+            error,
+        })
+    }
+}
+
+fn is_transient(err: &ClientError) -> bool {
+    match err {
+        ClientError::Http(err) => !err.is_decode() && !err.is_builder(),
+        ClientError::Api { status, .. } => {
+            *status == StatusCode::TOO_MANY_REQUESTS.as_u16() || *status >= 500
+        }
+        ClientError::LeaseLost(_) => false,
+    }
+}
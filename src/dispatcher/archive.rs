@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+use chrono::{SecondsFormat, Utc};
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+
+use super::store::archive_attempt_logs;
+
+/// Periodically moves attempt logs older than `hot_days` into the archive
+/// table so the hot table stays bounded. Each tick drains full batches
+/// until none are left; errors are retried on the next tick.
+pub fn spawn_attempt_log_archiver(
+    pool: SqlitePool,
+    interval: Duration,
+    hot_days: i64,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let before = (Utc::now() - chrono::Duration::days(hot_days))
+                .to_rfc3339_opts(SecondsFormat::Secs, true);
+            while let Ok(moved) = archive_attempt_logs(&pool, &before).await
+                && moved > 0
+            {}
+        }
+    })
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+
+use super::cron::CronSchedule;
+use crate::types::BackoffStrategy;
+
+/// Retry delays of endpoints without a strategy: doubling from one second,
+/// capped at an hour.
+pub const DEFAULT_BACKOFF: BackoffStrategy = BackoffStrategy::Exponential {
+    base_secs: 1,
+    max_secs: 3600,
+};
+
+/// Longest wait an exponential, linear or intervals strategy may schedule
+/// before a retry. Cron schedules wait for their next firing however far
+/// off it is.
+pub const MAX_BACKOFF_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Most waits an `intervals` strategy may list.
+const MAX_BACKOFF_INTERVALS: usize = 32;
+
+/// Checks that `strategy` only schedules waits between one second and
+/// [`MAX_BACKOFF_SECS`], or that its cron expression parses and fires.
+pub fn validate_backoff(strategy: &BackoffStrategy) -> Result<(), String> {
+    match strategy {
+        BackoffStrategy::Exponential {
+            base_secs: step_secs,
+            max_secs,
+        }
+        | BackoffStrategy::Linear {
+            step_secs,
+            max_secs,
+        } => {
+            if !(1..=MAX_BACKOFF_SECS).contains(max_secs) {
+                return Err(format!("max_secs must be between 1 and {MAX_BACKOFF_SECS}"));
+            }
+            if !(1..=*max_secs).contains(step_secs) {
+                return Err("the initial delay must be between 1 and max_secs".to_string());
+            }
+            Ok(())
+        }
+        BackoffStrategy::Intervals { intervals } => parse_intervals(intervals).map(|_| ()),
+        BackoffStrategy::Cron { expression } => {
+            let schedule = CronSchedule::parse(expression)?;
+            match schedule.next_after(Utc::now()) {
+                Some(_) => Ok(()),
+                None => Err(format!("cron expression {expression:?} never fires")),
+            }
+        }
+    }
+}
+
+/// Seconds failed attempt `attempt_no`, reported at `failed_at`, waits
+/// before its retry. An `intervals` list that runs out repeats its last
+/// wait, and a cron schedule waits for its next firing after `failed_at`,
+/// rounded up to a whole second.
+pub fn retry_delay_secs(
+    strategy: &BackoffStrategy,
+    attempt_no: i64,
+    failed_at: DateTime<Utc>,
+) -> Result<i64, String> {
+    let attempt_no = attempt_no.max(1);
+    Ok(match strategy {
+        BackoffStrategy::Exponential {
+            base_secs,
+            max_secs,
+        } => {
+            let exponent = u32::try_from((attempt_no - 1).min(62)).unwrap_or(62);
+            base_secs
+                .saturating_mul(2_i64.saturating_pow(exponent))
+                .min(*max_secs)
+        }
+        BackoffStrategy::Linear {
+            step_secs,
+            max_secs,
+        } => step_secs.saturating_mul(attempt_no).min(*max_secs),
+        BackoffStrategy::Intervals { intervals } => {
+            let waits = parse_intervals(intervals)?;
+            let index = usize::try_from(attempt_no - 1).unwrap_or(usize::MAX);
+            waits[index.min(waits.len() - 1)]
+        }
+        BackoffStrategy::Cron { expression } => {
+            let next = CronSchedule::parse(expression)?
+                .next_after(failed_at)
+                .ok_or_else(|| format!("cron expression {expression:?} never fires"))?;
+            let delay_ms = (next - failed_at).num_milliseconds();
+            delay_ms.div_euclid(1000) + i64::from(delay_ms.rem_euclid(1000) > 0)
+        }
+    })
+}
+
+/// Parses a comma-separated list of waits such as `1m,5m,30m,2h`. Each
+/// wait is a whole number of `s`, `m`, `h` or `d`.
+fn parse_intervals(raw: &str) -> Result<Vec<i64>, String> {
+    let waits = raw
+        .split(',')
+        .map(str::trim)
+        .map(parse_interval)
+        .collect::<Result<Vec<_>, _>>()?;
+    if waits.len() > MAX_BACKOFF_INTERVALS {
+        return Err(format!(
+            "intervals lists at most {MAX_BACKOFF_INTERVALS} waits"
+        ));
+    }
+    Ok(waits)
+}
+
+fn parse_interval(raw: &str) -> Result<i64, String> {
+    let invalid = || format!("invalid interval {raw:?}, expected e.g. 30s, 5m, 2h or 1d");
+    let split = raw.len().checked_sub(1).ok_or_else(invalid)?;
+    let (count, unit) = raw.split_at_checked(split).ok_or_else(invalid)?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    let secs = count
+        .parse::<i64>()
+        .map_err(|_| invalid())?
+        .saturating_mul(unit_secs);
+    if !(1..=MAX_BACKOFF_SECS).contains(&secs) {
+        return Err(format!(
+            "interval {raw:?} must be between 1s and {MAX_BACKOFF_SECS}s"
+        ));
+    }
+    Ok(secs)
+}
@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+use super::{DispatcherConfig, StoreError, lease_events, report_delivery};
+use crate::types::{LeaseRequest, ReportAttempt, ReportOutcome, ReportRequest};
+
+/// Parameters for [`run_lease_bench`].
+#[derive(Debug, Clone)]
+pub struct LeaseBenchConfig {
+    pub events: usize,
+    pub workers: usize,
+    pub batch_size: i64,
+    pub lease_ms: i64,
+}
+
+impl Default for LeaseBenchConfig {
+    fn default() -> Self {
+        Self {
+            events: 10_000,
+            workers: 4,
+            batch_size: 50,
+            lease_ms: 30_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaseBenchReport {
+    pub events: usize,
+    pub workers: usize,
+    pub batch_size: i64,
+    /// Events leased and reported delivered across all workers.
+    pub delivered: usize,
+    /// Lease or report calls that hit SQLite lock contention and were
+    /// retried, as a worker would after a 5xx.
+    pub busy_retries: usize,
+    pub elapsed_ms: u128,
+    pub events_per_sec: f64,
+}
+
+/// Seeds `config.events` pending events on one endpoint, then drains them
+/// with `config.workers` concurrent lease+report loops and measures the
+/// throughput. Run it against a scratch database: the seeded rows are left
+/// in place.
+pub async fn run_lease_bench(
+    pool: &SqlitePool,
+    config: &LeaseBenchConfig,
+) -> Result<LeaseBenchReport, StoreError> {
+    seed_events(pool, config.events).await?;
+
+    let dispatcher = DispatcherConfig::default();
+    let started = Instant::now();
+    let mut workers = JoinSet::new();
+    for worker in 0..config.workers.max(1) {
+        let pool = pool.clone();
+        let dispatcher = dispatcher.clone();
+        let lease = LeaseRequest {
+            limit: config.batch_size,
+            lease_ms: config.lease_ms,
+            worker_id: format!("bench-worker-{worker}"),
+            region: None,
+            endpoint_id: None,
+        };
+        workers.spawn(async move { drain(&pool, &dispatcher, &lease).await });
+    }
+
+    let mut totals = WorkerTotals::default();
+    while let Some(result) = workers.join_next().await {
+        let worker = result
+            .map_err(|err| StoreError::Conflict(format!("bench worker aborted: {err}")))??;
+        totals.delivered += worker.delivered;
+        totals.busy_retries += worker.busy_retries;
+    }
+    let elapsed = started.elapsed();
+
+    Ok(LeaseBenchReport {
+        events: config.events,
+        workers: config.workers.max(1),
+        batch_size: config.batch_size,
+        delivered: totals.delivered,
+        busy_retries: totals.busy_retries,
+        elapsed_ms: elapsed.as_millis(),
+        events_per_sec: totals.delivered as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+    })
+}
+
+async fn seed_events(pool: &SqlitePool, events: usize) -> Result<(), StoreError> {
+    let endpoint_id = Uuid::new_v4().to_string();
+    let received_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("INSERT INTO endpoints (id, target_url) VALUES (?, ?)")
+        .bind(&endpoint_id)
+        .bind("https://bench.invalid/hook")
+        .execute(&mut *tx)
+        .await?;
+
+    for _ in 0..events {
+        sqlx::query(
+            r"
+            INSERT INTO webhook_events (
+                id, endpoint_id, provider, headers, payload, status, attempts, received_at,
+                priority, updated_at
+            )
+            VALUES (?, ?, 'bench', '{}', '{}', 'pending', 0, ?, 0, ?)
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&endpoint_id)
+        .bind(&received_at)
+        .bind(&received_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct WorkerTotals {
+    delivered: usize,
+    busy_retries: usize,
+}
+
+/// Leases and reports batches until nothing is left to lease.
+async fn drain(
+    pool: &SqlitePool,
+    dispatcher: &DispatcherConfig,
+    lease: &LeaseRequest,
+) -> Result<WorkerTotals, StoreError> {
+    let mut totals = WorkerTotals::default();
+    loop {
+        let leased = match lease_events(pool, dispatcher, lease).await {
+            Ok(leased) => leased,
+            Err(err) if is_busy(&err) => {
+                totals.busy_retries += 1;
+                tokio::task::yield_now().await;
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        if leased.is_empty() {
+            return Ok(totals);
+        }
+        for leased_event in leased {
+            let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+            let report = ReportRequest {
+                worker_id: lease.worker_id.clone(),
+                worker_version: None,
+                worker_region: None,
+                event_id: leased_event.event.id,
+                outcome: ReportOutcome::Delivered,
+                retryable: false,
+                next_attempt_at: None,
+                attempt: ReportAttempt {
+                    started_at: now.clone(),
+                    finished_at: now,
+                    request_headers: BTreeMap::new(),
+                    request_body: String::new(),
+                    response_status: Some(200),
+                    response_headers: None,
+                    response_body: None,
+                    error_kind: None,
+                    error_message: None,
+                    final_url: None,
+                    peer_address: None,
+                    timing: None,
+                },
+            };
+            loop {
+                match report_delivery(pool, dispatcher, &report).await {
+                    Ok(_) => break,
+                    Err(err) if is_busy(&err) => {
+                        totals.busy_retries += 1;
+                        tokio::task::yield_now().await;
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            totals.delivered += 1;
+        }
+    }
+}
+
+/// SQLITE_BUSY and its extended codes: another connection holds the write
+/// lock, so the transaction was rolled back and can be retried as is.
+fn is_busy(err: &StoreError) -> bool {
+    match err {
+        StoreError::Db(sqlx::Error::Database(db)) => db
+            .code()
+            .and_then(|code| code.parse::<i32>().ok())
+            .is_some_and(|code| code & 0xff == 5),
+        _ => false,
+    }
+}
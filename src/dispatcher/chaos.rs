@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use axum::{body::Body, extract::State, http::Request, middleware::Next, response::Response};
+
+use super::fault::random_unit;
+use crate::error::ApiError;
+
+pub const INJECTED_CHAOS_MESSAGE: &str = "injected chaos failure";
+
+/// Staging switch that makes the worker API misbehave so worker retries
+/// and timeouts can be exercised against a real receiver. Each request
+/// rolls separately for latency and for failure. Never enable this in
+/// production.
+#[derive(Debug, Clone, Default)]
+pub struct ChaosConfig {
+    pub latency_ms: u64,
+    /// Share of requests, from 0 to 1, delayed by `latency_ms`.
+    pub latency_rate: f64,
+    /// Share of requests, from 0 to 1, answered with a 500 instead of
+    /// reaching the handler.
+    pub error_rate: f64,
+}
+
+impl ChaosConfig {
+    /// `None` unless `RECEIVER_CHAOS_ENABLED` is set.
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("RECEIVER_CHAOS_ENABLED")
+            .is_ok_and(|value| matches!(value.trim(), "1" | "true"));
+        if !enabled {
+            return None;
+        }
+
+        let mut config = Self::default();
+        if let Ok(value) = std::env::var("RECEIVER_CHAOS_LATENCY_MS")
+            && let Ok(parsed) = value.parse::<u64>()
+        {
+            config.latency_ms = parsed;
+        }
+        if let Ok(value) = std::env::var("RECEIVER_CHAOS_LATENCY_RATE")
+            && let Ok(parsed) = value.parse::<f64>()
+        {
+            config.latency_rate = parsed.clamp(0.0, 1.0);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_CHAOS_ERROR_RATE")
+            && let Ok(parsed) = value.parse::<f64>()
+        {
+            config.error_rate = parsed.clamp(0.0, 1.0);
+        }
+
+        Some(config)
+    }
+}
+
+/// Middleware that applies [`ChaosConfig`] before the handler runs, so an
+/// injected failure never leaves a lease or report half applied.
+pub async fn dispatcher_chaos(
+    State(chaos): State<ChaosConfig>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if chaos.latency_ms > 0 && random_unit() < chaos.latency_rate {
+        tokio::time::sleep(Duration::from_millis(chaos.latency_ms)).await;
+    }
+    if random_unit() < chaos.error_rate {
+        return Err(ApiError::internal(INJECTED_CHAOS_MESSAGE));
+    }
+    Ok(next.run(req).await)
+}
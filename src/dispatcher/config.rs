@@ -1,3 +1,9 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::secrets::SecretStore;
+
 #[derive(Debug, Clone)]
 pub struct DispatcherConfig {
     pub circuit_failure_threshold: u32,
@@ -5,6 +11,47 @@ pub struct DispatcherConfig {
     pub circuit_cooldown_factor: f64,
     pub circuit_cooldown_max_ms: u64,
     pub max_attempts: u32,
+    /// How often queued events past their `expires_at` are marked expired.
+    pub expiry_sweep_interval_ms: u64,
+    /// Staging/test switch that enables per-endpoint simulated failures on
+    /// the report path. Never set this in production.
+    pub fault_injection_enabled: bool,
+    /// Attempt logs older than this stay queryable but move to the archive
+    /// table.
+    pub attempt_log_hot_days: i64,
+    /// Every time an event has waited this long since it was received, it
+    /// leases one priority level higher, up to a small cap. Only the order
+    /// among due events changes; retries still wait for their
+    /// `next_attempt_at`. `None` disables the guard.
+    pub starvation_max_wait_ms: Option<u64>,
+    pub attempt_log_archive_interval_ms: u64,
+    /// Share of successful deliveries, from 0 to 100, whose response body is
+    /// kept on the attempt log. Failed attempts always keep theirs, so 0
+    /// stores bodies for failures only.
+    pub success_response_sample_percent: u8,
+    /// Request timeout handed to workers for endpoints without their own.
+    pub default_request_timeout_ms: i64,
+    /// How often each active HTTP endpoint is handed out for a pre-flight
+    /// check.
+    pub endpoint_check_interval_ms: u64,
+    /// Certificates expiring within this many days are flagged by the
+    /// endpoint health API and `/metrics`.
+    pub tls_expiry_warning_days: i64,
+    /// How long an event of an endpoint that prefers a region must have
+    /// been due before workers of other regions may lease it.
+    pub region_fallback_ms: u64,
+    /// Group replays matching more dead events than this need a dry-run
+    /// confirmation token. `None` disables the check.
+    pub replay_confirm_threshold: Option<i64>,
+    /// How long a dry-run confirmation token stays valid.
+    pub replay_confirm_ttl_minutes: i64,
+    /// Replaying an event whose chain already holds this many replays and
+    /// no delivery is refused as a likely loop unless forced. `None`
+    /// disables the check.
+    pub replay_loop_limit: Option<i64>,
+    /// SMTP server the receiver sends email targets through. Without one
+    /// the events of email endpoints stay queued.
+    pub smtp: Option<SmtpSettings>,
 }
 
 impl DispatcherConfig {
@@ -36,6 +83,69 @@ impl DispatcherConfig {
         {
             config.max_attempts = parsed.max(1);
         }
+        if let Ok(value) = std::env::var("RECEIVER_EXPIRY_SWEEP_INTERVAL_MS")
+            && let Ok(parsed) = value.parse::<u64>()
+        {
+            config.expiry_sweep_interval_ms = parsed.max(1);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_ATTEMPT_LOG_HOT_DAYS")
+            && let Ok(parsed) = value.parse::<i64>()
+        {
+            config.attempt_log_hot_days = parsed.max(1);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_ATTEMPT_LOG_ARCHIVE_INTERVAL_MS")
+            && let Ok(parsed) = value.parse::<u64>()
+        {
+            config.attempt_log_archive_interval_ms = parsed.max(1);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_STARVATION_MAX_WAIT_MS")
+            && let Ok(parsed) = value.parse::<u64>()
+        {
+            config.starvation_max_wait_ms = (parsed > 0).then_some(parsed);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_SUCCESS_RESPONSE_SAMPLE_PERCENT")
+            && let Ok(parsed) = value.parse::<u8>()
+        {
+            config.success_response_sample_percent = parsed.min(100);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_DEFAULT_REQUEST_TIMEOUT_MS")
+            && let Ok(parsed) = value.parse::<i64>()
+        {
+            config.default_request_timeout_ms = parsed.max(1);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_ENDPOINT_CHECK_INTERVAL_MS")
+            && let Ok(parsed) = value.parse::<u64>()
+        {
+            config.endpoint_check_interval_ms = parsed.max(1);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_TLS_EXPIRY_WARNING_DAYS")
+            && let Ok(parsed) = value.parse::<i64>()
+        {
+            config.tls_expiry_warning_days = parsed.max(0);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_REGION_FALLBACK_MS")
+            && let Ok(parsed) = value.parse::<u64>()
+        {
+            config.region_fallback_ms = parsed;
+        }
+        if let Ok(value) = std::env::var("RECEIVER_REPLAY_CONFIRM_THRESHOLD")
+            && let Ok(parsed) = value.parse::<i64>()
+        {
+            config.replay_confirm_threshold = (parsed > 0).then_some(parsed);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_REPLAY_CONFIRM_TTL_MINUTES")
+            && let Ok(parsed) = value.parse::<i64>()
+        {
+            config.replay_confirm_ttl_minutes = parsed.max(1);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_REPLAY_LOOP_LIMIT")
+            && let Ok(parsed) = value.parse::<i64>()
+        {
+            config.replay_loop_limit = (parsed > 0).then_some(parsed);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_FAULT_INJECTION_ENABLED") {
+            config.fault_injection_enabled = matches!(value.trim(), "1" | "true");
+        }
 
         config
     }
@@ -49,6 +159,221 @@ impl Default for DispatcherConfig {
             circuit_cooldown_factor: 2.0,
             circuit_cooldown_max_ms: 600_000,
             max_attempts: 5,
+            expiry_sweep_interval_ms: 30_000,
+            fault_injection_enabled: false,
+            attempt_log_hot_days: 30,
+            starvation_max_wait_ms: None,
+            attempt_log_archive_interval_ms: 3_600_000,
+            success_response_sample_percent: 100,
+            default_request_timeout_ms: 30_000,
+            endpoint_check_interval_ms: 300_000,
+            tls_expiry_warning_days: 14,
+            region_fallback_ms: 30_000,
+            replay_confirm_threshold: Some(100),
+            replay_confirm_ttl_minutes: 10,
+            replay_loop_limit: Some(10),
+            smtp: None,
         }
     }
 }
+
+/// How the receiver secures its connection to the SMTP server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// Plain connection. Only allowed without credentials.
+    None,
+    /// Plain connection upgraded with `STARTTLS`, which the server must
+    /// offer.
+    StartTls,
+    /// TLS from the first byte (SMTPS).
+    Tls,
+}
+
+/// The SMTP server email targets are delivered through.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// `From` address of every message.
+    pub from_address: String,
+}
+
+impl fmt::Debug for SmtpSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmtpSettings")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls", &self.tls)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "[REDACTED]"))
+            .field("from_address", &self.from_address)
+            .finish()
+    }
+}
+
+/// Reads the `RECEIVER_SMTP_*` settings through `var`, which returns a
+/// variable's value if set:
+///
+/// - `RECEIVER_SMTP_HOST`: server host name. Without it the other settings
+///   must be unset too, and email targets are not delivered.
+/// - `RECEIVER_SMTP_TLS`: `starttls` (the default), `tls` or `none`.
+/// - `RECEIVER_SMTP_PORT`: defaults to 587, 465 or 25 for those modes.
+/// - `RECEIVER_SMTP_USERNAME` and `RECEIVER_SMTP_PASSWORD`: set both or
+///   neither. Credentials are refused over a plain connection.
+/// - `RECEIVER_SMTP_FROM`: `From` address of every message; required.
+///
+/// Blank values count as unset.
+pub fn smtp_settings_from_vars(
+    var: impl Fn(&str) -> Option<String>,
+) -> Result<Option<SmtpSettings>, String> {
+    let name = |key: &str| format!("RECEIVER_SMTP_{}", key.to_ascii_uppercase());
+    parse_smtp_settings(|key| var(&name(key)), name)
+}
+
+/// Reads the SMTP settings from the JSON object in the file at `path`. Its
+/// keys are the `RECEIVER_SMTP_*` names in lower case without the prefix
+/// (`host`, `tls`, `port`, `username`, `password`, `from`), validated as
+/// in [`smtp_settings_from_vars`]; `host` is required. Instead of
+/// `password`, `password_secret_id` names a secret to resolve through
+/// `secrets`, which keeps the password out of the file.
+pub fn smtp_settings_from_file(path: &Path, secrets: &SecretStore) -> Result<SmtpSettings, String> {
+    let display = path.display();
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read SMTP config {display}: {err}"))?;
+    let fields: BTreeMap<String, serde_json::Value> = serde_json::from_str(&contents)
+        .map_err(|err| format!("SMTP config {display} must be a JSON object: {err}"))?;
+
+    let mut values = BTreeMap::new();
+    for (key, value) in fields {
+        if !SMTP_KEYS.contains(&key.as_str()) && key != "host" && key != "password_secret_id" {
+            return Err(format!("unknown key {key} in SMTP config {display}"));
+        }
+        let value = match value {
+            serde_json::Value::String(value) => value,
+            serde_json::Value::Number(number) if key == "port" => number.to_string(),
+            _ => return Err(format!("{key} in SMTP config {display} must be a string")),
+        };
+        values.insert(key, value);
+    }
+    if let Some(secret_id) = values.remove("password_secret_id") {
+        if values.contains_key("password") {
+            return Err(format!(
+                "set password or password_secret_id in SMTP config {display}, not both"
+            ));
+        }
+        let password = secrets
+            .resolve(secret_id.trim())
+            .map_err(|err| format!("failed to resolve the SMTP password: {err}"))?;
+        values.insert("password".to_string(), password);
+    }
+
+    parse_smtp_settings(
+        |key| values.get(key).cloned(),
+        |key| format!("{key} in SMTP config {display}"),
+    )?
+    .ok_or_else(|| format!("host is required in SMTP config {display}"))
+}
+
+/// Validates the SMTP settings `value` returns by key, naming each setting
+/// in errors through `name`.
+fn parse_smtp_settings(
+    value: impl Fn(&str) -> Option<String>,
+    name: impl Fn(&str) -> String,
+) -> Result<Option<SmtpSettings>, String> {
+    let value = |key: &str| {
+        value(key)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let Some(host) = value("host") else {
+        return match SMTP_KEYS.iter().find(|key| value(key).is_some()) {
+            Some(key) => Err(format!("{} is set but {} is not", name(key), name("host"))),
+            None => Ok(None),
+        };
+    };
+    if host.contains(|c: char| c.is_whitespace() || c == '/' || c == ':') {
+        return Err(format!(
+            "{} must be a host name without scheme or port, got {host}",
+            name("host")
+        ));
+    }
+
+    let tls = match value("tls").as_deref() {
+        None | Some("starttls") => SmtpTls::StartTls,
+        Some("tls") => SmtpTls::Tls,
+        Some("none") => SmtpTls::None,
+        Some(other) => {
+            return Err(format!(
+                "{} must be starttls, tls or none, got {other}",
+                name("tls")
+            ));
+        }
+    };
+    let port = match value("port") {
+        Some(port) => match port.parse::<u16>() {
+            Ok(port) if port > 0 => port,
+            _ => return Err(format!("{} must be 1-65535, got {port}", name("port"))),
+        },
+        None => match tls {
+            SmtpTls::StartTls => 587,
+            SmtpTls::Tls => 465,
+            SmtpTls::None => 25,
+        },
+    };
+
+    let (username, password) = match (value("username"), value("password")) {
+        (Some(username), Some(password)) => (Some(username), Some(password)),
+        (None, None) => (None, None),
+        _ => {
+            return Err(format!(
+                "{} and {} must be set together",
+                name("username"),
+                name("password")
+            ));
+        }
+    };
+    if username.is_some() && tls == SmtpTls::None {
+        return Err(format!(
+            "{} is none, which would send the SMTP credentials in the clear",
+            name("tls")
+        ));
+    }
+
+    let from_address = value("from")
+        .ok_or_else(|| format!("{} is required with {}", name("from"), name("host")))?;
+    if !is_email_address(&from_address) {
+        return Err(format!(
+            "{} must be a bare address like receiver@example.com, got {from_address}",
+            name("from")
+        ));
+    }
+
+    Ok(Some(SmtpSettings {
+        host,
+        port,
+        tls,
+        username,
+        password,
+        from_address,
+    }))
+}
+
+/// Every SMTP setting other than the host.
+const SMTP_KEYS: [&str; 5] = ["tls", "port", "username", "password", "from"];
+
+/// `local@domain`, without a display name, whitespace or a second `@`.
+fn is_email_address(value: &str) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.is_empty()
+        && !domain.contains('@')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !value.contains(|c: char| c.is_whitespace() || c.is_control() || "<>,;\"".contains(c))
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+
+/// Days searched for the next firing, enough to reach a 29 February.
+const MAX_SEARCH_DAYS: i64 = 8 * 366;
+
+/// A five-field cron expression (`minute hour day-of-month month
+/// day-of-week`) evaluated in UTC. Fields take `*`, numbers, `a-b` ranges,
+/// `/step` and comma lists; day-of-week runs 0-7 with both 0 and 7 for
+/// Sunday. As in cron, a time matches when either day field matches if
+/// both are restricted. `@hourly`, `@daily` and `@weekly` are accepted too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(format!(
+                "cron expression must have 5 fields, got {}",
+                fields.len()
+            ));
+        };
+        let mut days_of_week = parse_field(day_of_week, 0, 7)?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days_of_month: parse_field(day_of_month, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            days_of_week,
+            any_day_of_month: day_of_month == "*",
+            any_day_of_week: day_of_week == "*",
+        })
+    }
+
+    /// First whole minute strictly after `at` that the schedule fires at,
+    /// or `None` if it never fires (e.g. `0 0 30 2 *`).
+    pub fn next_after(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = at.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let first_day = start.date_naive();
+        for offset in 0..MAX_SEARCH_DAYS {
+            let day = first_day.checked_add_signed(Duration::days(offset))?;
+            if !self.matches_day(day) {
+                continue;
+            }
+            for hour in (0..24).filter(|hour| self.hours & (1 << hour) != 0) {
+                for minute in (0..60).filter(|minute| self.minutes & (1 << minute) != 0) {
+                    let fire = day.and_hms_opt(hour, minute, 0)?.and_utc();
+                    if fire >= start {
+                        return Some(fire);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, day: NaiveDate) -> bool {
+        if self.months & (1 << day.month()) == 0 {
+            return false;
+        }
+        let day_of_month = self.days_of_month & (1 << day.day()) != 0;
+        let day_of_week = self.days_of_week & (1 << day.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (false, false) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+}
+
+/// Parses one field into a bit set of the values it matches.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let invalid = || format!("invalid cron field {field:?}, values run {min}-{max}");
+    let mut bits = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (low, high) = if range == "*" {
+            (min, max)
+        } else if let Some((low, high)) = range.split_once('-') {
+            (
+                low.parse().map_err(|_| invalid())?,
+                high.parse().map_err(|_| invalid())?,
+            )
+        } else {
+            let value = range.parse().map_err(|_| invalid())?;
+            // `5/15` runs from 5 to the end of the field.
+            (value, if part.contains('/') { max } else { value })
+        };
+        if low < min || high > max || low > high {
+            return Err(invalid());
+        }
+        for value in (low..=high).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
@@ -0,0 +1,24 @@
+use std::collections::BTreeMap;
+
+use uuid::Uuid;
+
+/// First event of the replay chain. Unchanged across retries and replays,
+/// so consumers can use it as an idempotency key.
+pub const DELIVERY_ID_HEADER: &str = "X-Delivery-Id";
+/// The event being delivered; differs from the delivery ID for replays.
+pub const DELIVERY_EVENT_ID_HEADER: &str = "X-Delivery-Event-Id";
+/// 1-based attempt number of this delivery of the event.
+pub const DELIVERY_ATTEMPT_HEADER: &str = "X-Delivery-Attempt";
+
+/// Headers a worker adds to the outbound request for a leased event.
+pub fn delivery_headers(
+    delivery_id: Uuid,
+    event_id: Uuid,
+    attempt_no: i64,
+) -> BTreeMap<String, String> {
+    BTreeMap::from([
+        (DELIVERY_ID_HEADER.to_string(), delivery_id.to_string()),
+        (DELIVERY_EVENT_ID_HEADER.to_string(), event_id.to_string()),
+        (DELIVERY_ATTEMPT_HEADER.to_string(), attempt_no.to_string()),
+    ])
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Datelike, Duration, Utc};
+
+use super::maintenance::maintenance_window_end;
+use crate::types::{DeliveryWindow, MaintenanceWindow};
+
+/// Maintenance and delivery windows are followed in turn at most this many
+/// times, which bounds the work for schedules that keep excluding each
+/// other.
+const MAX_SCHEDULE_HOPS: usize = 16;
+
+/// Returns when the next of `windows` opens, or `None` if one is open at
+/// `at` or there are no windows. Windows are in local time at
+/// `utc_offset_minutes` from UTC.
+pub fn next_delivery_window_start(
+    windows: &[DeliveryWindow],
+    utc_offset_minutes: i64,
+    at: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let offset = Duration::minutes(utc_offset_minutes);
+    // Local wall-clock time, carried in a UTC value.
+    let local = at + offset;
+    let today = local.date_naive();
+    let mut next: Option<DateTime<Utc>> = None;
+
+    for window in windows.iter().filter(|window| window.duration_minutes > 0) {
+        // A window that started on an earlier day can still be open.
+        let lookback_days = window.duration_minutes.div_euclid(24 * 60) + 1;
+        for days in -lookback_days..=7 {
+            let Some(day) = today.checked_add_signed(Duration::days(days)) else {
+                continue;
+            };
+            if window
+                .day_of_week
+                .is_some_and(|dow| i64::from(day.weekday().num_days_from_monday()) != dow)
+            {
+                continue;
+            }
+            let Some(midnight) = day.and_hms_opt(0, 0, 0) else {
+                continue;
+            };
+            let start = midnight.and_utc() + Duration::minutes(window.start_minute);
+            if start <= local && local < start + Duration::minutes(window.duration_minutes) {
+                return None;
+            }
+            if start > local && next.is_none_or(|next| start < next) {
+                next = Some(start);
+            }
+        }
+    }
+
+    next.map(|start| start - offset)
+}
+
+/// Returns the earliest moment from `at` on when the endpoint is neither
+/// in maintenance nor outside its delivery windows, or `None` if it can
+/// take deliveries at `at`.
+pub fn deliverable_from(
+    maintenance: &[MaintenanceWindow],
+    delivery: &[DeliveryWindow],
+    utc_offset_minutes: i64,
+    at: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let mut cursor = at;
+    for _ in 0..MAX_SCHEDULE_HOPS {
+        if let Some(end) = maintenance_window_end(maintenance, cursor) {
+            cursor = end;
+        } else if let Some(start) = next_delivery_window_start(delivery, utc_offset_minutes, cursor)
+        {
+            cursor = start;
+        } else {
+            break;
+        }
+    }
+    (cursor > at).then_some(cursor)
+}
@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::fault::random_unit;
+use crate::types::EchoDelivery;
+
+pub const INJECTED_ECHO_MESSAGE: &str = "injected echo failure";
+
+const DEFAULT_ECHO_CAPACITY: usize = 100;
+
+/// Demo and test switch for the `/debug/echo` delivery target, which
+/// answers deliveries with their own body so the whole pipeline can run
+/// without an external service. Never enable this in production: the
+/// recorded deliveries are readable without credentials.
+#[derive(Debug, Clone)]
+pub struct EchoConfig {
+    /// Share of deliveries, from 0 to 1, answered with a 500 instead of
+    /// the echo.
+    pub failure_rate: f64,
+    /// How many of the latest deliveries are kept for inspection.
+    pub capacity: usize,
+}
+
+impl Default for EchoConfig {
+    fn default() -> Self {
+        Self {
+            failure_rate: 0.0,
+            capacity: DEFAULT_ECHO_CAPACITY,
+        }
+    }
+}
+
+impl EchoConfig {
+    /// `None` unless `RECEIVER_DEBUG_ECHO_ENABLED` is set.
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("RECEIVER_DEBUG_ECHO_ENABLED")
+            .is_ok_and(|value| matches!(value.trim(), "1" | "true"));
+        if !enabled {
+            return None;
+        }
+
+        let mut config = Self::default();
+        if let Ok(value) = std::env::var("RECEIVER_DEBUG_ECHO_FAILURE_RATE")
+            && let Ok(parsed) = value.parse::<f64>()
+        {
+            config.failure_rate = parsed.clamp(0.0, 1.0);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_DEBUG_ECHO_CAPACITY")
+            && let Ok(parsed) = value.parse::<usize>()
+        {
+            config.capacity = parsed;
+        }
+
+        Some(config)
+    }
+}
+
+/// The echo target's settings and the deliveries it received, oldest
+/// first, shared by its handlers.
+#[derive(Debug, Clone)]
+pub struct EchoTarget {
+    config: EchoConfig,
+    received: Arc<Mutex<VecDeque<EchoDelivery>>>,
+}
+
+impl EchoTarget {
+    pub fn new(config: EchoConfig) -> Self {
+        Self {
+            config,
+            received: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Rolls whether the next delivery gets an injected failure.
+    pub fn roll_failure(&self) -> bool {
+        random_unit() < self.config.failure_rate
+    }
+
+    /// Keeps `delivery`, dropping the oldest once `capacity` is reached.
+    pub fn record(&self, delivery: EchoDelivery) {
+        let mut received = self.received.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.config.capacity == 0 {
+            return;
+        }
+        while received.len() >= self.config.capacity {
+            received.pop_front();
+        }
+        received.push_back(delivery);
+    }
+
+    pub fn received(&self) -> Vec<EchoDelivery> {
+        self.received
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.received
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{SecondsFormat, Utc};
+use lettre::message::header::{ContentType, HeaderName, HeaderValue};
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+
+use super::config::{DispatcherConfig, SmtpSettings, SmtpTls};
+use super::store::{StoreError, lease_email_events, report_delivery};
+use crate::types::{
+    LeaseRequest, LeasedEvent, PayloadEncoding, ReportAttempt, ReportOutcome, ReportRequest,
+    WebhookAttemptErrorKind,
+};
+
+/// Worker ID the receiver's email sender leases and reports under.
+pub const EMAIL_WORKER_ID: &str = "receiver-email";
+
+/// Emails are leased one at a time, and a send is abandoned well before
+/// its lease runs out.
+const EMAIL_LEASE_MS: i64 = 600_000;
+const EMAIL_MAX_SEND_MS: i64 = EMAIL_LEASE_MS / 2;
+
+/// Sends the events of email endpoints through the configured SMTP server.
+/// The credentials stay here; workers never see them.
+pub struct EmailSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    server: String,
+}
+
+impl EmailSender {
+    pub fn new(settings: &SmtpSettings) -> Result<Self, String> {
+        let tls = |host: &str| {
+            TlsParameters::new(host.to_string())
+                .map_err(|err| format!("invalid TLS settings for {host}: {err}"))
+        };
+        let tls = match settings.tls {
+            SmtpTls::None => Tls::None,
+            SmtpTls::StartTls => Tls::Required(tls(&settings.host)?),
+            SmtpTls::Tls => Tls::Wrapper(tls(&settings.host)?),
+        };
+        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+            .port(settings.port)
+            .tls(tls);
+        if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+            transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        let from = settings
+            .from_address
+            .parse()
+            .map_err(|err| format!("invalid SMTP from address: {err}"))?;
+        Ok(Self {
+            transport: transport.build(),
+            from,
+            server: format!("{}:{}", settings.host, settings.port),
+        })
+    }
+
+    /// Sends one leased event and returns how to report it: accepted mail
+    /// is delivered, a permanent (5xx) rejection or a message that cannot
+    /// be rendered is dead, and anything else is retried.
+    pub async fn send(&self, leased: &LeasedEvent) -> (ReportOutcome, ReportAttempt) {
+        let started_at = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let rendered = match render_email(leased, &self.from) {
+            Ok(rendered) => rendered,
+            Err(message) => {
+                let attempt = ReportAttempt {
+                    error_kind: Some(WebhookAttemptErrorKind::Unexpected),
+                    error_message: Some(message),
+                    ..self.attempt(started_at, BTreeMap::new(), String::new())
+                };
+                return (ReportOutcome::Dead, attempt);
+            }
+        };
+        let mut attempt = self.attempt(started_at, rendered.headers, rendered.body);
+
+        let timeout_ms = leased.request_timeout_ms.clamp(1, EMAIL_MAX_SEND_MS);
+        let timeout = Duration::from_millis(timeout_ms as u64);
+        let sent = tokio::time::timeout(timeout, self.transport.send(rendered.message)).await;
+        attempt.finished_at = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let outcome = match sent {
+            Ok(Ok(response)) => {
+                attempt.response_status = Some(i64::from(u16::from(response.code())));
+                attempt.response_body = Some(response.message().collect::<Vec<_>>().join("\n"));
+                ReportOutcome::Delivered
+            }
+            Ok(Err(err)) => {
+                attempt.response_status = err.status().map(|code| i64::from(u16::from(code)));
+                attempt.error_kind = if err.is_timeout() {
+                    Some(WebhookAttemptErrorKind::Timeout)
+                } else if err.is_tls() {
+                    Some(WebhookAttemptErrorKind::Tls)
+                } else if err.status().is_some() {
+                    None
+                } else {
+                    Some(WebhookAttemptErrorKind::Network)
+                };
+                attempt.error_message = Some(err.to_string());
+                if err.is_permanent() {
+                    ReportOutcome::Dead
+                } else {
+                    ReportOutcome::Retry
+                }
+            }
+            Err(_) => {
+                attempt.error_kind = Some(WebhookAttemptErrorKind::Timeout);
+                attempt.error_message = Some(format!(
+                    "SMTP server did not accept the message within {timeout_ms}ms"
+                ));
+                ReportOutcome::Retry
+            }
+        };
+        (outcome, attempt)
+    }
+
+    fn attempt(
+        &self,
+        started_at: String,
+        request_headers: BTreeMap<String, String>,
+        request_body: String,
+    ) -> ReportAttempt {
+        ReportAttempt {
+            finished_at: started_at.clone(),
+            started_at,
+            request_headers,
+            request_body,
+            response_status: None,
+            response_headers: None,
+            response_body: None,
+            error_kind: None,
+            error_message: None,
+            final_url: None,
+            peer_address: Some(self.server.clone()),
+            timing: None,
+        }
+    }
+}
+
+/// A leased event rendered into an email, with the headers and text body
+/// recorded on its attempt log.
+pub struct RenderedEmail {
+    pub message: Message,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+/// Renders a leased event into an email to the addresses of its `mailto:`
+/// target. The body is the payload, pretty-printed when it is JSON; a
+/// binary payload is attached instead. The `X-Delivery-*` headers are set
+/// as on HTTP deliveries.
+pub fn render_email(leased: &LeasedEvent, from: &Mailbox) -> Result<RenderedEmail, String> {
+    let recipients = mailto_recipients(&leased.target_url)?;
+    let event = &leased.event;
+    let subject = match event.event_type.as_deref() {
+        Some(event_type) => format!("{} webhook: {event_type}", event.provider),
+        None => format!("{} webhook {}", event.provider, event.id),
+    };
+
+    let mut headers = BTreeMap::from([
+        ("From".to_string(), from.to_string()),
+        (
+            "To".to_string(),
+            recipients
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        ("Subject".to_string(), subject.clone()),
+    ]);
+    let mut builder = Message::builder().from(from.clone()).subject(subject);
+    for recipient in recipients {
+        builder = builder.to(recipient);
+    }
+    for (name, value) in &leased.delivery_headers {
+        let header = HeaderName::new_from_ascii(name.clone())
+            .map_err(|err| format!("invalid delivery header {name}: {err}"))?;
+        builder = builder.raw_header(HeaderValue::new(header, value.clone()));
+        headers.insert(name.clone(), value.clone());
+    }
+
+    let (message, body) = match event.payload_encoding {
+        PayloadEncoding::Utf8 => {
+            let body = serde_json::from_str::<serde_json::Value>(&event.payload)
+                .ok()
+                .and_then(|value| serde_json::to_string_pretty(&value).ok())
+                .unwrap_or_else(|| event.payload.clone());
+            let message = builder
+                .header(ContentType::TEXT_PLAIN)
+                .body(body.clone())
+                .map_err(|err| format!("failed to build the email: {err}"))?;
+            (message, body)
+        }
+        PayloadEncoding::Base64 => {
+            let bytes = STANDARD
+                .decode(&event.payload)
+                .map_err(|err| format!("stored payload is not valid base64: {err}"))?;
+            let content_type = event
+                .headers
+                .get("content-type")
+                .map_or("application/octet-stream", String::as_str);
+            let content_type = ContentType::parse(content_type)
+                .or_else(|_| ContentType::parse("application/octet-stream"))
+                .map_err(|err| format!("invalid attachment content type: {err}"))?;
+            let body = format!(
+                "The {} payload of event {} is binary and attached ({} bytes).",
+                event.provider,
+                event.id,
+                bytes.len()
+            );
+            let message = builder
+                .multipart(
+                    MultiPart::mixed()
+                        .singlepart(SinglePart::plain(body.clone()))
+                        .singlepart(
+                            Attachment::new("payload".to_string()).body(bytes, content_type),
+                        ),
+                )
+                .map_err(|err| format!("failed to build the email: {err}"))?;
+            (message, body)
+        }
+    };
+    Ok(RenderedEmail {
+        message,
+        headers,
+        body,
+    })
+}
+
+/// Addresses of a `mailto:` URL: comma separated, before any `?`.
+fn mailto_recipients(target_url: &str) -> Result<Vec<Mailbox>, String> {
+    let addresses = target_url
+        .split_once(':')
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("mailto"))
+        .map(|(_, addresses)| addresses.split_once('?').map_or(addresses, |(to, _)| to))
+        .ok_or_else(|| format!("email target {target_url} is not a mailto: URL"))?;
+    let recipients = addresses
+        .split(',')
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(|address| {
+            address
+                .parse::<Mailbox>()
+                .map_err(|err| format!("invalid recipient {address}: {err}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if recipients.is_empty() {
+        return Err(format!("email target {target_url} has no recipient"));
+    }
+    Ok(recipients)
+}
+
+/// Leases the next due email event and sends it, reporting the result
+/// like a worker would. `false` when no email was due.
+pub async fn send_due_email(
+    pool: &SqlitePool,
+    config: &DispatcherConfig,
+    sender: &EmailSender,
+) -> Result<bool, StoreError> {
+    let request = LeaseRequest {
+        limit: 1,
+        lease_ms: EMAIL_LEASE_MS,
+        worker_id: EMAIL_WORKER_ID.to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let Some(leased) = lease_email_events(pool, config, &request).await?.pop() else {
+        return Ok(false);
+    };
+    let (outcome, attempt) = sender.send(&leased).await;
+    let report = ReportRequest {
+        worker_id: EMAIL_WORKER_ID.to_string(),
+        worker_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        worker_region: None,
+        event_id: leased.event.id,
+        retryable: outcome == ReportOutcome::Retry,
+        outcome,
+        next_attempt_at: None,
+        attempt,
+    };
+    report_delivery(pool, config, &report).await?;
+    Ok(true)
+}
+
+/// Every `interval`, sends email events until none is due. Errors end the
+/// round and are retried on the next tick.
+pub fn spawn_email_sender(
+    pool: SqlitePool,
+    config: DispatcherConfig,
+    sender: EmailSender,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            while let Ok(true) = send_due_email(&pool, &config, &sender).await {}
+        }
+    })
+}
@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+
+use super::store::expire_events;
+
+/// Periodically moves queued events past their delivery deadline to
+/// `expired`. Sweep errors are retried on the next tick.
+pub fn spawn_expiry_sweeper(pool: SqlitePool, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let _ = expire_events(&pool).await;
+        }
+    })
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::StoreError;
+use crate::types::{ReportOutcome, ReportRequest, WebhookAttemptErrorKind};
+
+pub const INJECTED_FAILURE_MESSAGE: &str = "injected failure";
+
+/// Applies the endpoint's simulated latency and failure rate to a report
+/// before it is recorded. A delivered report that rolls under the failure
+/// rate is rewritten as a retryable failure so retries, circuit breaking,
+/// and dead-lettering run exactly as they would for a real outage.
+pub async fn inject_faults(pool: &SqlitePool, req: &mut ReportRequest) -> Result<(), StoreError> {
+    let fault: Option<(f64, i64)> = sqlx::query_as(
+        r"
+        SELECT f.failure_rate, f.latency_ms
+        FROM endpoint_fault_injections f
+        JOIN webhook_events e ON e.endpoint_id = f.endpoint_id
+        WHERE e.id = ?
+        ",
+    )
+    .bind(req.event_id.to_string())
+    .fetch_optional(pool)
+    .await?;
+
+    let Some((failure_rate, latency_ms)) = fault else {
+        return Ok(());
+    };
+
+    if latency_ms > 0 {
+        tokio::time::sleep(Duration::from_millis(latency_ms.unsigned_abs())).await;
+    }
+
+    if req.outcome == ReportOutcome::Delivered && random_unit() < failure_rate {
+        req.outcome = ReportOutcome::Retry;
+        req.retryable = true;
+        req.next_attempt_at = None;
+        req.attempt.response_status = None;
+        req.attempt.response_headers = None;
+        req.attempt.response_body = None;
+        req.attempt.error_kind = Some(WebhookAttemptErrorKind::Unexpected);
+        req.attempt.error_message = Some(INJECTED_FAILURE_MESSAGE.to_string());
+    }
+
+    Ok(())
+}
+
+/// Uniform value in `[0, 1)` taken from the random bits of a v4 UUID.
+pub(super) fn random_unit() -> f64 {
+    let bits = Uuid::new_v4().as_u128() & ((1 << 53) - 1);
+    bits as f64 / (1_u64 << 53) as f64
+}
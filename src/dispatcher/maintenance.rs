@@ -0,0 +1,56 @@
+use chrono::{DateTime, Datelike, Days, Duration, Utc};
+
+use crate::types::MaintenanceWindow;
+
+/// Chained windows (one ending inside the next) are followed at most this
+/// many times, which bounds the work for overlapping daily schedules.
+const MAX_CHAINED_WINDOWS: usize = 16;
+
+/// Returns when the maintenance period covering `at` ends, or `None` if no
+/// window is open at `at`. Back-to-back or overlapping windows are merged.
+pub fn maintenance_window_end(
+    windows: &[MaintenanceWindow],
+    at: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let mut end = None;
+    let mut cursor = at;
+
+    for _ in 0..MAX_CHAINED_WINDOWS {
+        let Some(next) = windows
+            .iter()
+            .filter_map(|window| open_window_end(window, cursor))
+            .max()
+        else {
+            break;
+        };
+        end = Some(next);
+        cursor = next;
+    }
+
+    end
+}
+
+fn open_window_end(window: &MaintenanceWindow, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if window.duration_minutes <= 0 {
+        return None;
+    }
+
+    // A window that started on an earlier day can still be open at `at`.
+    let lookback_days = window.duration_minutes.div_euclid(24 * 60) + 1;
+    let today = at.date_naive();
+
+    (0..=lookback_days.unsigned_abs())
+        .filter_map(|back| today.checked_sub_days(Days::new(back)))
+        .filter(|day| {
+            window
+                .day_of_week
+                .is_none_or(|dow| i64::from(day.weekday().num_days_from_monday()) == dow)
+        })
+        .filter_map(|day| {
+            let start =
+                day.and_hms_opt(0, 0, 0)?.and_utc() + Duration::minutes(window.start_minute);
+            let end = start + Duration::minutes(window.duration_minutes);
+            (start <= at && at < end).then_some(end)
+        })
+        .max()
+}
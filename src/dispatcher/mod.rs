@@ -1,5 +1,43 @@
+mod archive;
+mod backoff;
+mod bench;
+mod chaos;
 mod config;
+mod cron;
+mod delivery;
+mod delivery_window;
+mod echo;
+mod email;
+mod expiry;
+mod fault;
+mod maintenance;
+mod selftest;
+mod simulate;
 mod store;
 
-pub use config::DispatcherConfig;
-pub use store::{ReportResult, StoreError, lease_events, report_delivery};
+pub use archive::spawn_attempt_log_archiver;
+pub use backoff::{DEFAULT_BACKOFF, MAX_BACKOFF_SECS, retry_delay_secs, validate_backoff};
+pub use bench::{LeaseBenchConfig, LeaseBenchReport, run_lease_bench};
+pub use chaos::{ChaosConfig, INJECTED_CHAOS_MESSAGE, dispatcher_chaos};
+pub use config::{
+    DispatcherConfig, SmtpSettings, SmtpTls, smtp_settings_from_file, smtp_settings_from_vars,
+};
+pub use cron::CronSchedule;
+pub use delivery::{
+    DELIVERY_ATTEMPT_HEADER, DELIVERY_EVENT_ID_HEADER, DELIVERY_ID_HEADER, delivery_headers,
+};
+pub use delivery_window::{deliverable_from, next_delivery_window_start};
+pub use echo::{EchoConfig, EchoTarget, INJECTED_ECHO_MESSAGE};
+pub use email::{
+    EMAIL_WORKER_ID, EmailSender, RenderedEmail, render_email, send_due_email, spawn_email_sender,
+};
+pub use expiry::spawn_expiry_sweeper;
+pub use fault::{INJECTED_FAILURE_MESSAGE, inject_faults};
+pub use maintenance::maintenance_window_end;
+pub use selftest::{SELFTEST_TARGET_URL, run_selftest};
+pub use simulate::{simulate_backoff, simulate_circuit};
+pub use store::{
+    ReportResult, StoreError, archive_attempt_logs, expire_events, lease_email_events,
+    lease_endpoint_checks, lease_events, record_endpoint_check, record_shadow_attempt, renew_lease,
+    report_delivery,
+};
@@ -0,0 +1,325 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::Instant;
+
+use chrono::{SecondsFormat, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::{DispatcherConfig, lease_events, report_delivery};
+use crate::ingest::{NewEvent, insert_event};
+use crate::types::{
+    LeaseRequest, LeasedEvent, PayloadEncoding, ReportAttempt, ReportOutcome, ReportRequest,
+    SelftestReport, SelftestStep,
+};
+
+/// Target of self-test endpoints. The embedded worker answers it
+/// in-process, echoing the request back, so the run needs no network.
+pub const SELFTEST_TARGET_URL: &str = "internal://selftest/echo";
+
+const SELFTEST_PROVIDER: &str = "selftest";
+const SELFTEST_WORKER_ID: &str = "selftest-worker";
+const SELFTEST_LEASE_MS: i64 = 30_000;
+
+/// Creates a temporary endpoint and source, then ingests one event,
+/// leases it, delivers it with the embedded worker, reports the delivery
+/// and checks the stored result. The temporary rows are deleted
+/// afterwards whether or not the run passed.
+pub async fn run_selftest(pool: &SqlitePool, config: &DispatcherConfig) -> SelftestReport {
+    let started = Instant::now();
+    let started_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let fixture = Fixture {
+        endpoint: Uuid::new_v4(),
+        source: Uuid::new_v4(),
+        event: Uuid::new_v4(),
+    };
+
+    let mut steps = Steps::default();
+    let completed = run_pipeline(pool, config, &fixture, &mut steps)
+        .await
+        .is_some();
+    steps
+        .run("cleanup", async {
+            delete_fixture(pool, &fixture)
+                .await
+                .map(|()| ((), "temporary endpoint removed".to_string()))
+                .map_err(|err| format!("cleanup failed: {err}"))
+        })
+        .await;
+
+    let passed = completed && steps.0.iter().all(|step| step.passed);
+    SelftestReport {
+        passed,
+        started_at,
+        elapsed_ms: started.elapsed().as_millis() as i64,
+        endpoint_id: fixture.endpoint,
+        event_id: fixture.event,
+        steps: steps.0,
+    }
+}
+
+/// IDs of the rows a run creates.
+struct Fixture {
+    endpoint: Uuid,
+    source: Uuid,
+    event: Uuid,
+}
+
+#[derive(Default)]
+struct Steps(Vec<SelftestStep>);
+
+impl Steps {
+    /// Runs one step and records it; `None` when it failed.
+    async fn run<T>(
+        &mut self,
+        name: &str,
+        step: impl Future<Output = Result<(T, String), String>>,
+    ) -> Option<T> {
+        let started = Instant::now();
+        let result = step.await;
+        let elapsed_ms = started.elapsed().as_millis() as i64;
+        let (value, passed, detail) = match result {
+            Ok((value, detail)) => (Some(value), true, detail),
+            Err(detail) => (None, false, detail),
+        };
+        self.0.push(SelftestStep {
+            name: name.to_string(),
+            passed,
+            elapsed_ms,
+            detail,
+        });
+        value
+    }
+}
+
+async fn run_pipeline(
+    pool: &SqlitePool,
+    config: &DispatcherConfig,
+    fixture: &Fixture,
+    steps: &mut Steps,
+) -> Option<()> {
+    steps
+        .run("setup", async {
+            create_fixture(pool, fixture)
+                .await
+                .map(|()| {
+                    let detail = format!(
+                        "endpoint {} targets {SELFTEST_TARGET_URL}",
+                        fixture.endpoint
+                    );
+                    ((), detail)
+                })
+                .map_err(|err| format!("could not create the temporary endpoint: {err}"))
+        })
+        .await?;
+
+    let payload = serde_json::json!({
+        "selftest": true,
+        "event_id": fixture.event,
+    })
+    .to_string();
+    steps
+        .run("ingest", async {
+            let received_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+            let event = NewEvent {
+                id: fixture.event,
+                endpoint_id: fixture.endpoint.to_string(),
+                source_id: fixture.source.to_string(),
+                provider: SELFTEST_PROVIDER.to_string(),
+                provider_event_id: None,
+                headers: BTreeMap::from([(
+                    "content-type".to_string(),
+                    "application/json".to_string(),
+                )]),
+                payload: payload.clone(),
+                payload_encoding: PayloadEncoding::Utf8,
+                received_at,
+                expires_at: None,
+                scrub_ruleset_id: None,
+                scrub_rule_version: None,
+                event_type: None,
+                schema_errors: None,
+                dropped_headers: Vec::new(),
+                metadata: None,
+            };
+            insert_event(pool, &event)
+                .await
+                .map(|()| ((), format!("stored {} payload bytes", payload.len())))
+                .map_err(|err| format!("insert failed: {err:?}"))
+        })
+        .await?;
+
+    let leased = steps
+        .run("lease", async {
+            let request = LeaseRequest {
+                limit: 1,
+                lease_ms: SELFTEST_LEASE_MS,
+                worker_id: SELFTEST_WORKER_ID.to_string(),
+                region: None,
+                endpoint_id: Some(fixture.endpoint.to_string()),
+            };
+            let leased = lease_events(pool, config, &request)
+                .await
+                .map_err(|err| format!("lease failed: {err:?}"))?;
+            let leased = leased
+                .into_iter()
+                .find(|leased| leased.event.id == fixture.event)
+                .ok_or_else(|| {
+                    "event was not leased; dispatch may be paused or the endpoint deferred"
+                        .to_string()
+                })?;
+            let detail = format!("leased until {}", leased.lease_expires_at);
+            Ok((leased, detail))
+        })
+        .await?;
+
+    let attempt = steps
+        .run("deliver", async {
+            let attempt = deliver_to_echo(&leased)?;
+            if attempt.response_body.as_deref() != Some(payload.as_str()) {
+                return Err("echoed body differs from the ingested payload".to_string());
+            }
+            let detail = format!("echo answered {}", attempt.response_status.unwrap_or(0));
+            Ok((attempt, detail))
+        })
+        .await?;
+
+    steps
+        .run("report", async {
+            let request = ReportRequest {
+                worker_id: SELFTEST_WORKER_ID.to_string(),
+                worker_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+                worker_region: None,
+                event_id: fixture.event,
+                outcome: ReportOutcome::Delivered,
+                retryable: false,
+                next_attempt_at: None,
+                attempt,
+            };
+            let result = report_delivery(pool, config, &request)
+                .await
+                .map_err(|err| format!("report failed: {err:?}"))?;
+            if result.final_outcome != ReportOutcome::Delivered {
+                return Err(format!("report was recorded as {:?}", result.final_outcome));
+            }
+            Ok(((), "recorded as delivered".to_string()))
+        })
+        .await?;
+
+    steps
+        .run("verify", async {
+            let (status, attempts, logged): (String, i64, i64) = sqlx::query_as(
+                r"
+                SELECT e.status, e.attempts,
+                    (SELECT COUNT(*) FROM webhook_attempt_logs_all a WHERE a.event_id = e.id)
+                FROM webhook_events e
+                WHERE e.id = ?
+                ",
+            )
+            .bind(fixture.event.to_string())
+            .fetch_one(pool)
+            .await
+            .map_err(|err| format!("could not load the event: {err}"))?;
+            if status != "delivered" || attempts != 1 || logged != 1 {
+                return Err(format!(
+                    "expected delivered with one attempt, found {status} with {attempts} attempts and {logged} attempt logs"
+                ));
+            }
+            Ok(((), "event delivered with one logged attempt".to_string()))
+        })
+        .await
+}
+
+/// The embedded worker: answers deliveries to [`SELFTEST_TARGET_URL`]
+/// with a 200 echoing the request body.
+fn deliver_to_echo(leased: &LeasedEvent) -> Result<ReportAttempt, String> {
+    if leased.target_url != SELFTEST_TARGET_URL {
+        return Err(format!(
+            "leased target is {}, not the self-test echo",
+            leased.target_url
+        ));
+    }
+    let started_at = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+    let mut request_headers = leased.event.headers.clone();
+    request_headers.extend(leased.delivery_headers.clone());
+    let response_headers = BTreeMap::from([(
+        "content-type".to_string(),
+        request_headers
+            .get("content-type")
+            .cloned()
+            .unwrap_or_else(|| "application/octet-stream".to_string()),
+    )]);
+
+    Ok(ReportAttempt {
+        started_at,
+        finished_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        request_headers,
+        request_body: leased.event.payload.clone(),
+        response_status: Some(200),
+        response_headers: Some(response_headers),
+        response_body: Some(leased.event.payload.clone()),
+        error_kind: None,
+        error_message: None,
+        final_url: Some(SELFTEST_TARGET_URL.to_string()),
+        peer_address: None,
+        timing: None,
+    })
+}
+
+async fn create_fixture(pool: &SqlitePool, fixture: &Fixture) -> Result<(), sqlx::Error> {
+    let created_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("INSERT INTO endpoints (id, target_url) VALUES (?, ?)")
+        .bind(fixture.endpoint.to_string())
+        .bind(SELFTEST_TARGET_URL)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        r"
+        INSERT INTO sources (id, slug, provider, endpoint_id, secret, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(fixture.source.to_string())
+    .bind(format!("selftest-{}", fixture.source))
+    .bind(SELFTEST_PROVIDER)
+    .bind(fixture.endpoint.to_string())
+    .bind(Uuid::new_v4().to_string())
+    .bind(&created_at)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Deletes everything the run wrote for the temporary endpoint, including
+/// its usage rollups, so self-tests leave no trace in stats.
+async fn delete_fixture(pool: &SqlitePool, fixture: &Fixture) -> Result<(), sqlx::Error> {
+    let endpoint_id = fixture.endpoint.to_string();
+    let mut tx = pool.begin().await?;
+
+    for statement in [
+        "DELETE FROM webhook_attempt_logs WHERE event_id IN \
+            (SELECT id FROM webhook_events WHERE endpoint_id = ?)",
+        "DELETE FROM webhook_attempt_logs_archive WHERE event_id IN \
+            (SELECT id FROM webhook_events WHERE endpoint_id = ?)",
+        "DELETE FROM webhook_events WHERE endpoint_id = ?",
+        "DELETE FROM usage_rollups WHERE endpoint_id = ?",
+        "DELETE FROM endpoint_storage WHERE endpoint_id = ?",
+        "DELETE FROM delivery_stats_hourly WHERE endpoint_id = ?",
+        "DELETE FROM target_circuit_transitions WHERE endpoint_id = ?",
+        "DELETE FROM target_circuit_states WHERE endpoint_id = ?",
+        "DELETE FROM endpoint_revisions WHERE endpoint_id = ?",
+        "DELETE FROM sources WHERE endpoint_id = ?",
+        "DELETE FROM endpoints WHERE id = ?",
+    ] {
+        sqlx::query(statement)
+            .bind(&endpoint_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await
+}
@@ -0,0 +1,44 @@
+use chrono::Utc;
+
+use super::backoff::{DEFAULT_BACKOFF, retry_delay_secs};
+use super::config::DispatcherConfig;
+use super::store::compute_cooldown_ms;
+use crate::types::{BackoffStep, CircuitStep};
+
+/// Retry schedule of an event whose first `attempts` deliveries all fail
+/// instantly, stopping at `max_attempts`, under [`DEFAULT_BACKOFF`].
+/// Maintenance and delivery windows of the endpoint are not applied.
+pub fn simulate_backoff(config: &DispatcherConfig, attempts: u32) -> Vec<BackoffStep> {
+    let max_attempts = i64::from(config.max_attempts);
+    let mut steps = Vec::new();
+    let mut starts_after_ms = 0;
+    for attempt_no in 1..=i64::from(attempts).min(max_attempts) {
+        let retry_delay_ms = (attempt_no < max_attempts).then(|| {
+            retry_delay_secs(&DEFAULT_BACKOFF, attempt_no, Utc::now())
+                .unwrap_or(0)
+                .saturating_mul(1000)
+        });
+        steps.push(BackoffStep {
+            attempt_no,
+            starts_after_ms,
+            retry_delay_ms,
+        });
+        starts_after_ms = starts_after_ms.saturating_add(retry_delay_ms.unwrap_or(0));
+    }
+    steps
+}
+
+/// State of an endpoint circuit after each of `failures` consecutive
+/// retryable failures.
+pub fn simulate_circuit(config: &DispatcherConfig, failures: u32) -> Vec<CircuitStep> {
+    let threshold = i64::from(config.circuit_failure_threshold);
+    (1..=i64::from(failures))
+        .map(|consecutive_failures| CircuitStep {
+            consecutive_failures,
+            open: consecutive_failures >= threshold,
+            cooldown_ms: (consecutive_failures >= threshold).then(|| {
+                i64::try_from(compute_cooldown_ms(config, consecutive_failures)).unwrap_or(i64::MAX)
+            }),
+        })
+        .collect()
+}
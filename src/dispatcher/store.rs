@@ -1,13 +1,22 @@
 use std::collections::BTreeMap;
 
-use chrono::{Duration, SecondsFormat, Utc};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use sha2::{Digest, Sha256};
 use sqlx::{QueryBuilder, SqlitePool};
 use uuid::Uuid;
 
 use crate::dispatcher::DispatcherConfig;
+use crate::dispatcher::backoff::{DEFAULT_BACKOFF, retry_delay_secs};
+use crate::dispatcher::delivery::delivery_headers;
+use crate::dispatcher::delivery_window::deliverable_from;
 use crate::types::{
-    LeaseRequest, LeasedEvent, ReportOutcome, ReportRequest, TargetCircuitState,
-    TargetCircuitStatus, WebhookAttemptErrorKind, WebhookEvent, WebhookEventStatus,
+    AddressFamily, BackoffStrategy, CheckLeaseRequest, CheckReportRequest, ConnectPolicy,
+    DeliveryWindow, EndpointCheckMethod, EndpointCheckTarget, EndpointStats, EndpointTargetKind,
+    LeaseRequest, LeasedEvent, MaintenanceWindow, PayloadEncoding, RedirectMode, RedirectPolicy,
+    RenewRequest, ReportOutcome, ReportRequest, ResponseCapture, ShadowReportRequest,
+    TargetCircuitState, TargetCircuitStatus, WebhookAttemptErrorKind, WebhookEvent,
+    WebhookEventStatus,
 };
 
 #[derive(Debug)]
@@ -24,9 +33,33 @@ impl From<sqlx::Error> for StoreError {
     }
 }
 
+/// Most priority levels the starvation guard adds to a long-waiting event.
+const STARVATION_MAX_BOOST: i64 = 3;
+
+/// Leases due events of HTTP endpoints to a delivery worker.
 pub async fn lease_events(
     pool: &SqlitePool,
+    config: &DispatcherConfig,
     req: &LeaseRequest,
+) -> Result<Vec<LeasedEvent>, StoreError> {
+    lease_target_kind(pool, config, req, EndpointTargetKind::Http).await
+}
+
+/// Leases due events of email endpoints. Only the receiver's own email
+/// sender takes these, so the SMTP credentials never leave the process.
+pub async fn lease_email_events(
+    pool: &SqlitePool,
+    config: &DispatcherConfig,
+    req: &LeaseRequest,
+) -> Result<Vec<LeasedEvent>, StoreError> {
+    lease_target_kind(pool, config, req, EndpointTargetKind::Email).await
+}
+
+async fn lease_target_kind(
+    pool: &SqlitePool,
+    config: &DispatcherConfig,
+    req: &LeaseRequest,
+    target_kind: EndpointTargetKind,
 ) -> Result<Vec<LeasedEvent>, StoreError> {
     let now = Utc::now();
     let now_str = format_utc(now);
@@ -39,10 +72,27 @@ pub async fn lease_events(
         UPDATE webhook_events
         SET status = 'requeued',
             lease_expires_at = NULL,
-            leased_by = NULL
+            leased_by = NULL,
+            updated_at = ?1
         WHERE status = 'in_flight'
             AND lease_expires_at IS NOT NULL
-            AND lease_expires_at <= ?
+            AND lease_expires_at <= ?1
+        ",
+    )
+    .bind(&now_str)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r"
+        INSERT INTO target_circuit_transitions (
+            endpoint_id, state, open_until, consecutive_failures, changed_at
+        )
+        SELECT endpoint_id, 'closed', NULL, consecutive_failures, ?1
+        FROM target_circuit_states
+        WHERE state = 'open'
+          AND open_until IS NOT NULL
+          AND open_until <= ?1
         ",
     )
     .bind(&now_str)
@@ -63,43 +113,183 @@ pub async fn lease_events(
     .execute(&mut *tx)
     .await?;
 
-    let leased_ids: Vec<String> = sqlx::query_scalar(
+    let paused: Option<bool> =
+        sqlx::query_scalar("SELECT paused FROM dispatch_control WHERE id = 1")
+            .fetch_optional(&mut *tx)
+            .await?;
+    if paused.unwrap_or(false) {
+        tx.commit().await?;
+        return Ok(Vec::new());
+    }
+
+    defer_blocked_endpoints(&mut tx, now).await?;
+    dead_letter_unsupported_targets(&mut tx, now).await?;
+
+    let rate_window_start = format_utc(now - Duration::minutes(1));
+    let region_fallback_before =
+        format_utc(now - Duration::milliseconds(config.region_fallback_ms as i64));
+    // Waiting only reorders events that are already due; it never makes an
+    // event leasable before its `next_attempt_at`. Without the guard the
+    // plain priority keeps the scan on the queued index.
+    let lease_priority = if config.starvation_max_wait_ms.is_some() {
+        "COALESCE(e.priority, 0) + COALESCE(MIN(?10, MAX(0, CAST(\
+            (julianday(?1) - julianday(e.received_at)) * 86400000.0 / ?6 AS INTEGER))), 0)"
+    } else {
+        "COALESCE(e.priority, 0)"
+    };
+    let leasable_kind = match target_kind {
+        EndpointTargetKind::Http => "http",
+        EndpointTargetKind::Email => "email",
+    };
+
+    // Events of ungrouped endpoints and unthrottled groups are taken
+    // highest priority first, then in `received_at` order, straight off the
+    // queued index, so the scan stops after `limit` matches however deep
+    // the backlog is. Only rate-limited
+    // groups need per-group ranking; paused groups lease nothing, and
+    // throttled groups lease at most what is left of their per-minute
+    // budget after recent attempts and deliveries still in flight.
+    //
+    // With the starvation guard on, a due event gains one priority level
+    // for every `starvation_max_wait_ms` it has waited since it was
+    // received, up to STARVATION_MAX_BOOST, so old retries are not parked
+    // behind a steady stream of higher-priority traffic.
+    //
+    // Endpoints pinned to a region go to workers of that region. Workers
+    // elsewhere only take events of `prefer` endpoints that have been due
+    // since the region fallback cutoff.
+    //
+    // Only endpoints of the requested target kind are leased; kinds with
+    // no delivery path were dead-lettered above.
+    //
+    // Parameters: ?1 now, ?2 rate window start, ?3 limit, ?4 lease expiry,
+    // ?5 worker ID, ?6 starvation wait in ms (NULL when disabled), ?7
+    // worker region, ?8 region fallback cutoff, ?9 endpoint filter (NULL
+    // for all endpoints), ?10 starvation boost cap.
+    let leased_ids: Vec<String> = sqlx::query_scalar(&format!(
         r"
-        WITH eligible AS (
-            SELECT e.id
-            FROM webhook_events e
-            LEFT JOIN target_circuit_states c
-                ON c.endpoint_id = e.endpoint_id
-            WHERE (e.status = 'pending' OR e.status = 'requeued')
-                AND (e.next_attempt_at IS NULL OR e.next_attempt_at <= ?)
-                AND (e.lease_expires_at IS NULL OR e.lease_expires_at <= ?)
+        WITH group_budget AS MATERIALIZED (
+            SELECT g.id AS group_id,
+                g.rate_limit_per_minute
+                    - (
+                        SELECT COUNT(*)
+                        FROM webhook_attempt_logs l
+                        CROSS JOIN webhook_events le ON le.id = l.event_id
+                        CROSS JOIN endpoints lep ON lep.id = le.endpoint_id
+                        WHERE lep.group_id = g.id AND l.started_at >= ?2
+                    )
+                    - (
+                        SELECT COUNT(*)
+                        FROM webhook_events fe
+                        JOIN endpoints fep ON fep.id = fe.endpoint_id
+                        WHERE fep.group_id = g.id AND fe.status = 'in_flight'
+                    ) AS remaining
+            FROM endpoint_groups g
+            WHERE g.rate_limit_per_minute IS NOT NULL
+                AND g.paused = 0
+        ),
+        ungoverned AS (
+            SELECT e.id, {lease_priority} AS priority, e.received_at
+            FROM webhook_events e INDEXED BY idx_webhook_events_queued_priority
+            JOIN endpoints ep ON ep.id = e.endpoint_id
+            LEFT JOIN endpoint_groups g ON g.id = ep.group_id
+            LEFT JOIN target_circuit_states c ON c.endpoint_id = e.endpoint_id
+            WHERE e.status IN ('pending', 'requeued')
+                AND (e.next_attempt_at IS NULL OR e.next_attempt_at <= ?1)
+                AND (e.lease_expires_at IS NULL OR e.lease_expires_at <= ?1)
+                AND (e.expires_at IS NULL OR e.expires_at > ?1)
+                AND e.deleted_at IS NULL
                 AND (
                     c.state IS NULL
                     OR c.state = 'closed'
-                    OR (c.state = 'open' AND c.open_until IS NOT NULL AND c.open_until <= ?)
+                    OR (c.state = 'open' AND c.open_until IS NOT NULL AND c.open_until <= ?1)
+                )
+                AND (g.id IS NULL OR (g.paused = 0 AND g.rate_limit_per_minute IS NULL))
+                AND (
+                    ep.region IS NULL
+                    OR ep.region = ?7
+                    OR (ep.region_mode = 'prefer' AND COALESCE(e.next_attempt_at, e.received_at) <= ?8)
                 )
-            ORDER BY e.received_at ASC
-            LIMIT ?
+                AND (?9 IS NULL OR e.endpoint_id = ?9)
+                AND ep.target_kind = '{leasable_kind}'
+            ORDER BY priority DESC, e.received_at ASC
+            LIMIT ?3
+        ),
+        governed AS (
+            SELECT ranked.id, ranked.priority, ranked.received_at
+            FROM (
+                SELECT e.id,
+                    {lease_priority} AS priority,
+                    e.received_at,
+                    b.remaining,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY b.group_id
+                        ORDER BY {lease_priority} DESC, e.received_at ASC
+                    ) AS group_rank
+                FROM group_budget b
+                CROSS JOIN endpoints ep ON ep.group_id = b.group_id
+                CROSS JOIN webhook_events e ON e.endpoint_id = ep.id
+                LEFT JOIN target_circuit_states c ON c.endpoint_id = e.endpoint_id
+                WHERE b.remaining > 0
+                    AND e.status IN ('pending', 'requeued')
+                    AND (e.next_attempt_at IS NULL OR e.next_attempt_at <= ?1)
+                    AND (e.lease_expires_at IS NULL OR e.lease_expires_at <= ?1)
+                    AND (e.expires_at IS NULL OR e.expires_at > ?1)
+                    AND e.deleted_at IS NULL
+                    AND (
+                        c.state IS NULL
+                        OR c.state = 'closed'
+                        OR (c.state = 'open' AND c.open_until IS NOT NULL AND c.open_until <= ?1)
+                    )
+                    AND (
+                        ep.region IS NULL
+                        OR ep.region = ?7
+                        OR (ep.region_mode = 'prefer' AND COALESCE(e.next_attempt_at, e.received_at) <= ?8)
+                    )
+                    AND (?9 IS NULL OR e.endpoint_id = ?9)
+                    AND ep.target_kind = '{leasable_kind}'
+            ) ranked
+            WHERE ranked.group_rank <= ranked.remaining
+        ),
+        eligible AS (
+            SELECT id
+            FROM (
+                SELECT id, priority, received_at FROM ungoverned
+                UNION ALL
+                SELECT id, priority, received_at FROM governed
+            )
+            ORDER BY priority DESC, received_at ASC
+            LIMIT ?3
         )
         UPDATE webhook_events
-        SET lease_expires_at = ?,
-            leased_by = ?,
-            status = 'in_flight'
+        SET lease_expires_at = ?4,
+            leased_by = ?5,
+            leased_at = ?1,
+            status = 'in_flight',
+            updated_at = ?1,
+            leased_target_revision = (
+                SELECT target_revision FROM endpoints WHERE endpoints.id = webhook_events.endpoint_id
+            ),
+            leased_target_url = (
+                SELECT target_url FROM endpoints WHERE endpoints.id = webhook_events.endpoint_id
+            )
         WHERE id IN (SELECT id FROM eligible)
             AND (status = 'pending' OR status = 'requeued')
-            AND (next_attempt_at IS NULL OR next_attempt_at <= ?)
-            AND (lease_expires_at IS NULL OR lease_expires_at <= ?)
+            AND (next_attempt_at IS NULL OR next_attempt_at <= ?1)
+            AND (lease_expires_at IS NULL OR lease_expires_at <= ?1)
         RETURNING id
         ",
-    )
-    .bind(&now_str)
-    .bind(&now_str)
+    ))
     .bind(&now_str)
+    .bind(&rate_window_start)
     .bind(req.limit)
     .bind(&lease_expires_at)
     .bind(&req.worker_id)
-    .bind(&now_str)
-    .bind(&now_str)
+    .bind(config.starvation_max_wait_ms.map(|ms| ms as i64))
+    .bind(req.region.as_deref())
+    .bind(&region_fallback_before)
+    .bind(req.endpoint_id.as_deref())
+    .bind(STARVATION_MAX_BOOST)
     .fetch_all(&mut *tx)
     .await?;
 
@@ -113,17 +303,45 @@ pub async fn lease_events(
             e.id, \
             e.endpoint_id, \
             e.replayed_from_event_id, \
+            COALESCE(e.replay_root_event_id, e.id) AS delivery_id, \
+            e.source_id, \
             e.provider, \
+            e.provider_event_id, \
             e.headers, \
             e.payload, \
+            e.payload_encoding, \
+            e.payload_sha256, \
+            e.scrub_ruleset_id, \
+            e.scrub_rule_version, \
+            e.event_type, \
+            e.schema_errors, \
             e.status, \
             e.attempts, \
             e.received_at, \
             e.next_attempt_at, \
+            e.expires_at, \
             e.lease_expires_at, \
             e.leased_by, \
             e.last_error, \
+            e.updated_at, \
             ep.target_url, \
+            ep.target_kind, \
+            ep.target_revision, \
+            ep.canary_target_url, \
+            ep.canary_percent, \
+            ep.shadow_target_url, \
+            ep.delivery_budget_seconds, \
+            ep.redirect_mode, \
+            ep.redirect_max, \
+            ep.address_family, \
+            ep.happy_eyeballs_delay_ms, \
+            COALESCE(ep.request_timeout_ms, ",
+    );
+    fetch.push_bind(config.default_request_timeout_ms);
+    fetch.push(
+        ") AS request_timeout_ms, \
+            (SELECT MIN(a.started_at) FROM webhook_attempt_logs_all a WHERE a.event_id = e.id) \
+                AS first_attempt_started_at, \
             c.state AS circuit_state, \
             c.open_until AS circuit_open_until, \
             c.consecutive_failures AS circuit_consecutive_failures, \
@@ -140,15 +358,176 @@ pub async fn lease_events(
     fetch_list.push_unseparated(")");
 
     let rows: Vec<LeaseRow> = fetch.build_query_as().fetch_all(&mut *tx).await?;
+    let events = rows
+        .into_iter()
+        .map(LeasedEvent::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // The UPDATE above recorded the primary target; correct it for events
+    // routed to a canary.
+    for leased in events.iter().filter(|leased| leased.canary) {
+        sqlx::query("UPDATE webhook_events SET leased_target_url = ? WHERE id = ?")
+            .bind(&leased.target_url)
+            .bind(leased.event.id.to_string())
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(events)
+}
+
+/// Moves queued events whose `expires_at` has passed to `expired`. Events
+/// currently leased are left to their worker; if the delivery is reported
+/// as a retry they are picked up on a later sweep.
+pub async fn expire_events(pool: &SqlitePool) -> Result<u64, StoreError> {
+    let now_str = format_utc(Utc::now());
+
+    let result = sqlx::query(
+        r"
+        UPDATE webhook_events
+        SET status = 'expired',
+            next_attempt_at = NULL,
+            lease_expires_at = NULL,
+            leased_by = NULL,
+            last_error = 'expired before delivery',
+            updated_at = ?1
+        WHERE (status = 'pending' OR status = 'requeued')
+          AND expires_at IS NOT NULL
+          AND expires_at <= ?1
+        ",
+    )
+    .bind(&now_str)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Rows moved per [`archive_attempt_logs`] call, which keeps each sweep's
+/// write transaction short.
+const ARCHIVE_BATCH_SIZE: i64 = 1000;
+
+/// Moves attempt logs finished before `before` from the hot
+/// `webhook_attempt_logs` table into `webhook_attempt_logs_archive`, one
+/// batch per call. Returns the number of rows moved.
+pub async fn archive_attempt_logs(pool: &SqlitePool, before: &str) -> Result<u64, StoreError> {
+    let now_str = format_utc(Utc::now());
+    let mut tx = pool.begin().await?;
+
+    let ids: Vec<String> = sqlx::query_scalar(
+        r"
+        SELECT id
+        FROM webhook_attempt_logs
+        WHERE finished_at < ?
+        ORDER BY finished_at ASC
+        LIMIT ?
+        ",
+    )
+    .bind(before)
+    .bind(ARCHIVE_BATCH_SIZE)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    if ids.is_empty() {
+        tx.commit().await?;
+        return Ok(0);
+    }
+
+    let mut insert = QueryBuilder::new(
+        "INSERT INTO webhook_attempt_logs_archive ( \
+            id, event_id, attempt_no, started_at, finished_at, request_headers, request_body, \
+            response_status, response_headers, response_body, error_kind, error_message, \
+            response_capture, target_revision, target_url, final_url, \
+            peer_address, dns_ms, connect_ms, tls_ms, ttfb_ms, total_ms, worker_id, \
+            worker_version, worker_region, sample_rate, archived_at \
+        ) \
+        SELECT \
+            id, event_id, attempt_no, started_at, finished_at, request_headers, request_body, \
+            response_status, response_headers, response_body, error_kind, error_message, \
+            response_capture, target_revision, target_url, final_url, \
+            peer_address, dns_ms, connect_ms, tls_ms, ttfb_ms, total_ms, worker_id, \
+            worker_version, worker_region, sample_rate, ",
+    );
+    insert.push_bind(&now_str);
+    insert.push(" FROM webhook_attempt_logs WHERE id IN (");
+    let mut insert_list = insert.separated(", ");
+    for id in &ids {
+        insert_list.push_bind(id);
+    }
+    insert_list.push_unseparated(")");
+    insert.build().execute(&mut *tx).await?;
+
+    let mut delete = QueryBuilder::new("DELETE FROM webhook_attempt_logs WHERE id IN (");
+    let mut delete_list = delete.separated(", ");
+    for id in &ids {
+        delete_list.push_bind(id);
+    }
+    delete_list.push_unseparated(")");
+    let result = delete.build().execute(&mut *tx).await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Extends a live lease held by `req.worker_id` to `req.lease_ms` from now
+/// and returns the new expiry. Expired leases cannot be renewed since the
+/// event may already have been leased again.
+pub async fn renew_lease(pool: &SqlitePool, req: &RenewRequest) -> Result<String, StoreError> {
+    let now = Utc::now();
+    let now_str = format_utc(now);
+    let lease_expires_at = format_utc(now + Duration::milliseconds(req.lease_ms));
+    let event_id = req.event_id.to_string();
+
+    let mut tx = pool.begin().await?;
+
+    let row: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+        r"
+        SELECT leased_by, lease_expires_at
+        FROM webhook_events
+        WHERE id = ? AND status = 'in_flight'
+        ",
+    )
+    .bind(&event_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some((Some(leased_by), Some(current_expiry))) = row else {
+        return Err(StoreError::Conflict("lease_missing".to_string()));
+    };
+    if leased_by != req.worker_id {
+        return Err(StoreError::Conflict("lease_not_owned".to_string()));
+    }
+    if let Ok(expires) = chrono::DateTime::parse_from_rfc3339(&current_expiry)
+        && expires <= now
+    {
+        return Err(StoreError::Conflict("lease_expired".to_string()));
+    }
+
+    sqlx::query(
+        r"
+        UPDATE webhook_events
+        SET lease_expires_at = ?,
+            updated_at = ?
+        WHERE id = ?
+        ",
+    )
+    .bind(&lease_expires_at)
+    .bind(&now_str)
+    .bind(&event_id)
+    .execute(&mut *tx)
+    .await?;
 
     tx.commit().await?;
 
-    rows.into_iter().map(LeaseRow::try_into).collect()
+    Ok(lease_expires_at)
 }
 
 pub struct ReportResult {
     pub circuit: Option<TargetCircuitState>,
     pub final_outcome: ReportOutcome,
+    pub endpoint_stats: EndpointStats,
 }
 
 pub async fn report_delivery(
@@ -165,7 +544,30 @@ pub async fn report_delivery(
 
     let row = sqlx::query_as::<_, ReportEventRow>(
         r"
-        SELECT endpoint_id, attempts, leased_by, lease_expires_at
+        SELECT
+            endpoint_id,
+            attempts,
+            carried_attempts,
+            leased_by,
+            lease_expires_at,
+            leased_target_revision,
+            leased_target_url,
+            (
+                SELECT delivery_budget_seconds FROM endpoints
+                WHERE endpoints.id = webhook_events.endpoint_id
+            ) AS delivery_budget_seconds,
+            (
+                SELECT backoff_strategy FROM endpoints
+                WHERE endpoints.id = webhook_events.endpoint_id
+            ) AS backoff_strategy,
+            (
+                SELECT success_log_sample_rate FROM endpoints
+                WHERE endpoints.id = webhook_events.endpoint_id
+            ) AS success_log_sample_rate,
+            (
+                SELECT MIN(started_at) FROM webhook_attempt_logs_all
+                WHERE event_id = webhook_events.id
+            ) AS first_attempt_started_at
         FROM webhook_events
         WHERE id = ?
         ",
@@ -211,13 +613,28 @@ pub async fn report_delivery(
 
     let attempt_id = Uuid::new_v4().to_string();
     let attempt_no = row.attempts + 1;
+    let timing = req.attempt.timing.unwrap_or_default();
     let endpoint_id = Uuid::parse_str(&row.endpoint_id)
         .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?;
 
     let retryable = req.retryable;
 
-    let exhausted = attempt_no >= i64::from(config.max_attempts);
-    let final_outcome = if exhausted {
+    // A replay that carried over an exhausted budget still gets its one
+    // attempt, so its delivery is kept rather than turned dead.
+    let carried_delivery = req.outcome == ReportOutcome::Delivered && row.carried_attempts > 0;
+    let exhausted = !carried_delivery && attempt_no >= i64::from(config.max_attempts);
+    // The first attempt starts the budget clock when nothing is logged yet.
+    let over_budget = row.delivery_budget_seconds.filter(|budget_seconds| {
+        req.outcome == ReportOutcome::Retry
+            && budget_remaining_ms(
+                *budget_seconds,
+                row.first_attempt_started_at
+                    .as_deref()
+                    .unwrap_or(&req.attempt.started_at),
+                now,
+            ) == 0
+    });
+    let final_outcome = if exhausted || over_budget.is_some() {
         ReportOutcome::Dead
     } else {
         req.outcome
@@ -230,7 +647,12 @@ pub async fn report_delivery(
             req.attempt.error_message.as_deref().unwrap_or("unknown")
         ))
     } else {
-        None
+        over_budget.map(|budget_seconds| {
+            format!(
+                "delivery_budget_exhausted ({budget_seconds}s): {}",
+                req.attempt.error_message.as_deref().unwrap_or("unknown")
+            )
+        })
     };
 
     match final_outcome {
@@ -243,11 +665,13 @@ pub async fn report_delivery(
                     next_attempt_at = NULL,
                     lease_expires_at = NULL,
                     leased_by = NULL,
-                    last_error = NULL
+                    last_error = NULL,
+                    updated_at = ?
                 WHERE id = ?
                   AND leased_by = ?
                 ",
             )
+            .bind(&now_str)
             .bind(&event_id)
             .bind(&req.worker_id)
             .execute(&mut *tx)
@@ -256,6 +680,39 @@ pub async fn report_delivery(
                 return Err(StoreError::Conflict("lease_not_owned".to_string()));
             }
 
+            // A lifetime count, unlike the windowed attainment in stats.
+            sqlx::query(
+                r"
+                UPDATE endpoints
+                SET slo_breaches = slo_breaches + 1
+                WHERE id = ?
+                  AND slo_target_seconds IS NOT NULL
+                  AND (julianday(?) - julianday(
+                        (SELECT received_at FROM webhook_events WHERE id = ?)
+                      )) * 86400 > slo_target_seconds
+                ",
+            )
+            .bind(&row.endpoint_id)
+            .bind(&req.attempt.finished_at)
+            .bind(&event_id)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r"
+                INSERT INTO target_circuit_transitions (
+                    endpoint_id, state, open_until, consecutive_failures, changed_at
+                )
+                SELECT endpoint_id, 'closed', NULL, 0, ?
+                FROM target_circuit_states
+                WHERE endpoint_id = ? AND state = 'open'
+                ",
+            )
+            .bind(&now_str)
+            .bind(&row.endpoint_id)
+            .execute(&mut *tx)
+            .await?;
+
             let updated = sqlx::query(
                 r"
                 UPDATE target_circuit_states
@@ -283,8 +740,10 @@ pub async fn report_delivery(
         ReportOutcome::Retry => {
             let next_attempt_at = match req.next_attempt_at.as_deref() {
                 Some(value) => normalize_rfc3339_utc(value)?,
-                None => compute_next_attempt_at(now, attempt_no),
+                None => compute_next_attempt_at(now, row.backoff_strategy.as_deref(), attempt_no)?,
             };
+            let next_attempt_at =
+                defer_past_blocked_windows(&mut tx, &row.endpoint_id, next_attempt_at).await?;
             let last_error = req
                 .attempt
                 .error_message
@@ -299,13 +758,15 @@ pub async fn report_delivery(
                     next_attempt_at = ?,
                     lease_expires_at = NULL,
                     leased_by = NULL,
-                    last_error = ?
+                    last_error = ?,
+                    updated_at = ?
                 WHERE id = ?
                   AND leased_by = ?
                 ",
             )
             .bind(next_attempt_at)
             .bind(last_error.as_deref())
+            .bind(&now_str)
             .bind(&event_id)
             .bind(&req.worker_id)
             .execute(&mut *tx)
@@ -339,12 +800,14 @@ pub async fn report_delivery(
                     next_attempt_at = NULL,
                     lease_expires_at = NULL,
                     leased_by = NULL,
-                    last_error = ?
+                    last_error = ?,
+                    updated_at = ?
                 WHERE id = ?
                   AND leased_by = ?
                 ",
             )
             .bind(last_error.as_deref())
+            .bind(&now_str)
             .bind(&event_id)
             .bind(&req.worker_id)
             .execute(&mut *tx)
@@ -366,45 +829,415 @@ pub async fn report_delivery(
         }
     }
 
+    let response_capture = response_capture_for(config, req.event_id, final_outcome);
+    let response_body = match response_capture {
+        ResponseCapture::Dropped => None,
+        ResponseCapture::Failure | ResponseCapture::All | ResponseCapture::Sampled => {
+            req.attempt.response_body.as_deref()
+        }
+    };
+
+    // A dropped success still counts towards `attempts`; the event keeps
+    // how many were dropped so the log count can be reconciled.
+    let log_sampling =
+        attempt_log_sampling_for(req.event_id, final_outcome, row.success_log_sample_rate);
+    if let AttemptLogSampling::Dropped(rate) = log_sampling {
+        sqlx::query(
+            r"
+            UPDATE webhook_events
+            SET unlogged_attempts = unlogged_attempts + 1,
+                unlogged_sample_rate = ?
+            WHERE id = ?
+            ",
+        )
+        .bind(rate)
+        .bind(&event_id)
+        .execute(&mut *tx)
+        .await?;
+    } else {
+        let sample_rate = match log_sampling {
+            AttemptLogSampling::Kept(rate) => Some(rate),
+            AttemptLogSampling::Logged | AttemptLogSampling::Dropped(_) => None,
+        };
+        sqlx::query(
+            r"
+            INSERT INTO webhook_attempt_logs (
+                id,
+                event_id,
+                attempt_no,
+                started_at,
+                finished_at,
+                request_headers,
+                request_body,
+                response_status,
+                response_headers,
+                response_body,
+                error_kind,
+                error_message,
+                response_capture,
+                target_revision,
+                target_url,
+                final_url,
+                peer_address,
+                dns_ms,
+                connect_ms,
+                tls_ms,
+                ttfb_ms,
+                total_ms,
+                worker_id,
+                worker_version,
+                worker_region,
+                sample_rate
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&attempt_id)
+        .bind(&event_id)
+        .bind(attempt_no)
+        .bind(&req.attempt.started_at)
+        .bind(&req.attempt.finished_at)
+        .bind(&request_headers)
+        .bind(&req.attempt.request_body)
+        .bind(req.attempt.response_status)
+        .bind(response_headers.as_deref())
+        .bind(response_body)
+        .bind(error_kind.as_deref())
+        .bind(req.attempt.error_message.as_deref())
+        .bind(response_capture_to_str(response_capture))
+        .bind(row.leased_target_revision)
+        .bind(row.leased_target_url.as_deref())
+        .bind(req.attempt.final_url.as_deref())
+        .bind(req.attempt.peer_address.as_deref())
+        .bind(timing.dns_ms)
+        .bind(timing.connect_ms)
+        .bind(timing.tls_ms)
+        .bind(timing.ttfb_ms)
+        .bind(timing.total_ms)
+        .bind(&req.worker_id)
+        .bind(req.worker_version.as_deref())
+        .bind(req.worker_region.as_deref())
+        .bind(sample_rate)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let stored_bytes = match log_sampling {
+        AttemptLogSampling::Dropped(_) => 0,
+        AttemptLogSampling::Logged | AttemptLogSampling::Kept(_) => {
+            req.attempt.request_body.len() + response_body.map_or(0, str::len)
+        }
+    };
+    sqlx::query(
+        r"
+        INSERT INTO usage_rollups (endpoint_id, day, deliveries_attempted, bytes_stored)
+        VALUES (?, ?, 1, ?)
+        ON CONFLICT(endpoint_id, day) DO UPDATE SET
+            deliveries_attempted = deliveries_attempted + 1,
+            bytes_stored = bytes_stored + excluded.bytes_stored
+        ",
+    )
+    .bind(&row.endpoint_id)
+    .bind(now.format("%Y-%m-%d").to_string())
+    .bind(stored_bytes as i64)
+    .execute(&mut *tx)
+    .await?;
+
+    // Counted as failed like the error summary counts it. A finish time
+    // that does not parse lands in the current hour.
+    let failed = req.attempt.error_kind.is_some()
+        || req
+            .attempt
+            .response_status
+            .is_some_and(|status| !(200..300).contains(&status));
+    let delivered = final_outcome == ReportOutcome::Delivered;
+    sqlx::query(
+        r"
+        INSERT INTO delivery_stats_hourly (
+            endpoint_id, bucket_start, attempts, failed_attempts, attempt_duration_ms_sum,
+            delivered, dead, delivery_latency_ms_sum, delivered_within_slo
+        )
+        SELECT
+            ep.id,
+            COALESCE(strftime('%Y-%m-%dT%H:00:00Z', ?1), strftime('%Y-%m-%dT%H:00:00Z', ?2)),
+            1,
+            ?3,
+            COALESCE((julianday(?1) - julianday(?4)) * 86400000.0, 0),
+            ?5,
+            ?6,
+            CASE WHEN ?5 THEN
+                COALESCE((julianday(?1) - julianday(e.received_at)) * 86400000.0, 0)
+            ELSE 0 END,
+            CASE WHEN ?5
+                AND (julianday(?1) - julianday(e.received_at)) * 86400 <= ep.slo_target_seconds
+            THEN 1 ELSE 0 END
+        FROM webhook_events e
+        JOIN endpoints ep ON ep.id = e.endpoint_id
+        WHERE e.id = ?7
+        ON CONFLICT (endpoint_id, bucket_start) DO UPDATE SET
+            attempts = attempts + 1,
+            failed_attempts = failed_attempts + excluded.failed_attempts,
+            attempt_duration_ms_sum = attempt_duration_ms_sum + excluded.attempt_duration_ms_sum,
+            delivered = delivered + excluded.delivered,
+            dead = dead + excluded.dead,
+            delivery_latency_ms_sum = delivery_latency_ms_sum + excluded.delivery_latency_ms_sum,
+            delivered_within_slo = delivered_within_slo + excluded.delivered_within_slo
+        ",
+    )
+    .bind(&req.attempt.finished_at)
+    .bind(&now_str)
+    .bind(i64::from(failed))
+    .bind(&req.attempt.started_at)
+    .bind(i64::from(delivered))
+    .bind(i64::from(final_outcome == ReportOutcome::Dead))
+    .bind(&event_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let endpoint_stats = load_endpoint_stats(
+        &mut tx,
+        config,
+        &row.endpoint_id,
+        attempt_no,
+        final_outcome,
+        now,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(ReportResult {
+        circuit: circuit_state,
+        final_outcome,
+        endpoint_stats,
+    })
+}
+
+/// Logs the result of a shadow delivery. The event, its retry schedule and
+/// the endpoint circuit are left alone, and no lease is required: shadow
+/// copies may finish after the primary delivery was reported.
+pub async fn record_shadow_attempt(
+    pool: &SqlitePool,
+    req: &ShadowReportRequest,
+) -> Result<Uuid, StoreError> {
+    let event_id = req.event_id.to_string();
+    let exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM webhook_events WHERE id = ?")
+        .bind(&event_id)
+        .fetch_optional(pool)
+        .await?;
+    if exists.is_none() {
+        return Err(StoreError::NotFound("event not found".to_string()));
+    }
+
+    let attempt_id = Uuid::new_v4();
     sqlx::query(
         r"
-        INSERT INTO webhook_attempt_logs (
+        INSERT INTO shadow_attempt_logs (
             id,
             event_id,
-            attempt_no,
+            worker_id,
+            target_url,
             started_at,
             finished_at,
-            request_headers,
-            request_body,
             response_status,
-            response_headers,
             response_body,
             error_kind,
             error_message
         )
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ",
     )
-    .bind(&attempt_id)
+    .bind(attempt_id.to_string())
     .bind(&event_id)
-    .bind(attempt_no)
+    .bind(&req.worker_id)
+    .bind(&req.target_url)
     .bind(&req.attempt.started_at)
     .bind(&req.attempt.finished_at)
-    .bind(&request_headers)
-    .bind(&req.attempt.request_body)
     .bind(req.attempt.response_status)
-    .bind(response_headers.as_deref())
     .bind(req.attempt.response_body.as_deref())
-    .bind(error_kind.as_deref())
+    .bind(req.attempt.error_kind.map(error_kind_to_str))
     .bind(req.attempt.error_message.as_deref())
-    .execute(&mut *tx)
+    .execute(pool)
     .await?;
 
+    Ok(attempt_id)
+}
+
+/// Endpoints that received an event within this many days count as
+/// active and get pre-flight checks.
+const CHECK_ACTIVE_DAYS: i64 = 7;
+
+/// Claims up to `req.limit` active HTTP endpoints whose pre-flight check
+/// is due and pushes their next check out by the check interval, so a
+/// worker that dies mid-check leaves the endpoint for the next round.
+pub async fn lease_endpoint_checks(
+    pool: &SqlitePool,
+    config: &DispatcherConfig,
+    req: &CheckLeaseRequest,
+) -> Result<Vec<EndpointCheckTarget>, StoreError> {
+    let now = Utc::now();
+    let now_str = format_utc(now);
+    let active_since = format_utc(now - Duration::days(CHECK_ACTIVE_DAYS));
+    let next_check_at =
+        format_utc(now + Duration::milliseconds(config.endpoint_check_interval_ms as i64));
+
+    let mut tx = pool.begin().await?;
+
+    let rows = sqlx::query_as::<_, CheckTargetRow>(
+        r"
+        SELECT
+            ep.id,
+            ep.check_method,
+            COALESCE(ep.check_url, ep.target_url) AS url,
+            COALESCE(ep.request_timeout_ms, ?) AS request_timeout_ms
+        FROM endpoints ep
+        WHERE ep.target_kind = 'http'
+          AND (ep.next_check_at IS NULL OR ep.next_check_at <= ?)
+          AND EXISTS (
+              SELECT 1 FROM webhook_events e
+              WHERE e.endpoint_id = ep.id AND e.received_at >= ?
+          )
+        ORDER BY ep.next_check_at IS NOT NULL, ep.next_check_at, ep.id
+        LIMIT ?
+        ",
+    )
+    .bind(config.default_request_timeout_ms)
+    .bind(&now_str)
+    .bind(&active_since)
+    .bind(req.limit)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for row in &rows {
+        sqlx::query("UPDATE endpoints SET next_check_at = ? WHERE id = ?")
+            .bind(&next_check_at)
+            .bind(&row.id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
     tx.commit().await?;
 
-    Ok(ReportResult {
-        circuit: circuit_state,
-        final_outcome,
+    rows.into_iter()
+        .map(|row| {
+            Ok(EndpointCheckTarget {
+                endpoint_id: Uuid::parse_str(&row.id)
+                    .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+                method: parse_check_method(&row.check_method)?,
+                url: row.url,
+                request_timeout_ms: row.request_timeout_ms,
+            })
+        })
+        .collect()
+}
+
+/// Records a pre-flight check result. The endpoint counts as reachable
+/// whenever it answered with any status.
+pub async fn record_endpoint_check(
+    pool: &SqlitePool,
+    req: &CheckReportRequest,
+) -> Result<Uuid, StoreError> {
+    let endpoint_id = req.endpoint_id.to_string();
+    let exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM endpoints WHERE id = ?")
+        .bind(&endpoint_id)
+        .fetch_optional(pool)
+        .await?;
+    if exists.is_none() {
+        return Err(StoreError::NotFound("endpoint not found".to_string()));
+    }
+
+    let started_at = parse_check_timestamp(&req.started_at)?;
+    let finished_at = parse_check_timestamp(&req.finished_at)?;
+    let latency_ms = (finished_at - started_at).num_milliseconds().max(0);
+    let tls_expires_at = req
+        .tls_expires_at
+        .as_deref()
+        .map(parse_check_timestamp)
+        .transpose()?
+        .map(format_utc);
+
+    let check_id = Uuid::new_v4();
+    sqlx::query(
+        r"
+        INSERT INTO endpoint_checks (
+            id,
+            endpoint_id,
+            worker_id,
+            url,
+            started_at,
+            finished_at,
+            reachable,
+            response_status,
+            latency_ms,
+            error_message,
+            tls_expires_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(check_id.to_string())
+    .bind(&endpoint_id)
+    .bind(&req.worker_id)
+    .bind(&req.url)
+    .bind(format_utc(started_at))
+    .bind(format_utc(finished_at))
+    .bind(req.response_status.is_some())
+    .bind(req.response_status)
+    .bind(latency_ms)
+    .bind(req.error_message.as_deref())
+    .bind(tls_expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(check_id)
+}
+
+async fn load_endpoint_stats(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    config: &DispatcherConfig,
+    endpoint_id: &str,
+    attempt_no: i64,
+    final_outcome: ReportOutcome,
+    now: chrono::DateTime<Utc>,
+) -> Result<EndpointStats, StoreError> {
+    let circuit = sqlx::query_as::<_, CircuitStatsRow>(
+        r"
+        SELECT state, open_until, consecutive_failures
+        FROM target_circuit_states
+        WHERE endpoint_id = ?
+        ",
+    )
+    .bind(endpoint_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let attempts_remaining = match final_outcome {
+        ReportOutcome::Retry => (i64::from(config.max_attempts) - attempt_no).max(0),
+        ReportOutcome::Delivered | ReportOutcome::Dead => 0,
+    };
+
+    let Some(circuit) = circuit else {
+        return Ok(EndpointStats {
+            consecutive_failures: 0,
+            attempts_remaining,
+            circuit_cooldown_remaining_ms: None,
+        });
+    };
+
+    let circuit_cooldown_remaining_ms = match circuit.open_until.as_deref() {
+        Some(open_until) if circuit.state == "open" => {
+            let open_until = chrono::DateTime::parse_from_rfc3339(open_until)
+                .map_err(|err| StoreError::Parse(format!("invalid open_until: {err}")))?;
+            let remaining = open_until.with_timezone(&Utc) - now;
+            Some(remaining.num_milliseconds().max(0))
+        }
+        _ => None,
+    };
+
+    Ok(EndpointStats {
+        consecutive_failures: circuit.consecutive_failures,
+        attempts_remaining,
+        circuit_cooldown_remaining_ms,
     })
 }
 
@@ -413,17 +1246,40 @@ struct LeaseRow {
     id: String,
     endpoint_id: String,
     replayed_from_event_id: Option<String>,
+    delivery_id: String,
+    source_id: Option<String>,
     provider: String,
+    provider_event_id: Option<String>,
     headers: String,
-    payload: String,
+    payload: Vec<u8>,
+    payload_encoding: String,
+    payload_sha256: Option<String>,
+    scrub_ruleset_id: Option<String>,
+    scrub_rule_version: Option<i64>,
+    event_type: Option<String>,
+    schema_errors: Option<String>,
     status: String,
     attempts: i64,
     received_at: String,
     next_attempt_at: Option<String>,
+    expires_at: Option<String>,
     lease_expires_at: Option<String>,
     leased_by: Option<String>,
     last_error: Option<String>,
+    updated_at: Option<String>,
     target_url: String,
+    target_kind: String,
+    target_revision: i64,
+    canary_target_url: Option<String>,
+    canary_percent: i64,
+    shadow_target_url: Option<String>,
+    delivery_budget_seconds: Option<i64>,
+    redirect_mode: String,
+    redirect_max: i64,
+    address_family: String,
+    happy_eyeballs_delay_ms: Option<i64>,
+    request_timeout_ms: i64,
+    first_attempt_started_at: Option<String>,
     circuit_state: Option<String>,
     circuit_open_until: Option<String>,
     circuit_consecutive_failures: Option<i64>,
@@ -435,6 +1291,7 @@ impl TryFrom<LeaseRow> for LeasedEvent {
 
     fn try_from(row: LeaseRow) -> Result<Self, Self::Error> {
         let status = parse_status(&row.status)?;
+        let target_kind = parse_target_kind(&row.target_kind)?;
         let headers: BTreeMap<String, String> = serde_json::from_str(&row.headers)
             .map_err(|err| StoreError::Parse(format!("invalid headers JSON: {err}")))?;
         let lease_expires_at = row
@@ -442,29 +1299,50 @@ impl TryFrom<LeaseRow> for LeasedEvent {
             .ok_or_else(|| StoreError::Parse("missing lease_expires_at".to_string()))?;
         let replayed_from_event_id = match row.replayed_from_event_id {
             Some(value) if value.is_empty() => None,
-            Some(value) => Some(
-                Uuid::parse_str(&value)
-                    .map_err(|err| StoreError::Parse(format!("invalid replayed_from_event_id: {err}")))?,
-            ),
+            Some(value) => Some(Uuid::parse_str(&value).map_err(|err| {
+                StoreError::Parse(format!("invalid replayed_from_event_id: {err}"))
+            })?),
             None => None,
         };
+        let source_id = row
+            .source_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|err| StoreError::Parse(format!("invalid source id: {err}")))?;
 
+        let (payload, payload_encoding) = decode_payload(row.payload, &row.payload_encoding)?;
         let event = WebhookEvent {
             id: Uuid::parse_str(&row.id)
                 .map_err(|err| StoreError::Parse(format!("invalid event id: {err}")))?,
             endpoint_id: Uuid::parse_str(&row.endpoint_id)
                 .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
             replayed_from_event_id,
+            source_id,
             provider: row.provider,
+            provider_event_id: row.provider_event_id,
             headers,
-            payload: row.payload,
+            payload,
+            payload_encoding,
+            payload_sha256: row.payload_sha256,
+            scrub_ruleset_id: row
+                .scrub_ruleset_id
+                .as_deref()
+                .map(Uuid::parse_str)
+                .transpose()
+                .map_err(|err| StoreError::Parse(format!("invalid scrub ruleset id: {err}")))?,
+            scrub_rule_version: row.scrub_rule_version,
+            event_type: row.event_type,
+            schema_errors: parse_schema_errors(row.schema_errors.as_deref())?,
             status,
             attempts: row.attempts,
             received_at: row.received_at,
             next_attempt_at: row.next_attempt_at,
+            expires_at: row.expires_at,
             lease_expires_at: Some(lease_expires_at.clone()),
             leased_by: row.leased_by,
             last_error: row.last_error,
+            updated_at: row.updated_at,
         };
 
         let circuit = match row.circuit_state.as_deref() {
@@ -485,15 +1363,71 @@ impl TryFrom<LeaseRow> for LeasedEvent {
             None => None,
         };
 
+        let delivery_id = Uuid::parse_str(&row.delivery_id)
+            .map_err(|err| StoreError::Parse(format!("invalid delivery id: {err}")))?;
+        let delivery_headers = delivery_headers(delivery_id, event.id, event.attempts + 1);
+        let canary_url = row
+            .canary_target_url
+            .filter(|_| routes_to_canary(event.id, row.canary_percent));
+
         Ok(LeasedEvent {
             event,
-            target_url: row.target_url,
+            canary: canary_url.is_some(),
+            target_url: canary_url.unwrap_or(row.target_url),
+            target_kind,
+            target_revision: row.target_revision,
+            shadow_target_url: row.shadow_target_url,
+            delivery_headers,
             lease_expires_at,
             circuit,
+            request_timeout_ms: row.request_timeout_ms,
+            redirect_policy: RedirectPolicy {
+                mode: parse_redirect_mode(&row.redirect_mode)?,
+                max_redirects: row.redirect_max,
+            },
+            connect_policy: ConnectPolicy {
+                address_family: parse_address_family(&row.address_family)?,
+                happy_eyeballs_delay_ms: row.happy_eyeballs_delay_ms,
+            },
+            delivery_budget_remaining_ms: row.delivery_budget_seconds.map(|budget_seconds| {
+                row.first_attempt_started_at
+                    .as_deref()
+                    .map_or(budget_seconds * 1000, |started_at| {
+                        budget_remaining_ms(budget_seconds, started_at, Utc::now())
+                    })
+            }),
         })
     }
 }
 
+/// Milliseconds left of a `budget_seconds` delivery budget whose clock
+/// started at `started_at`, never below zero. An unparseable start leaves
+/// the whole budget.
+fn budget_remaining_ms(budget_seconds: i64, started_at: &str, now: DateTime<Utc>) -> i64 {
+    let elapsed_ms = DateTime::parse_from_rfc3339(started_at).map_or(0, |started| {
+        (now - started.with_timezone(&Utc))
+            .num_milliseconds()
+            .max(0)
+    });
+    (budget_seconds * 1000 - elapsed_ms).max(0)
+}
+
+/// Renders a stored payload: text as-is, BLOBs as base64.
+fn decode_payload(
+    payload: Vec<u8>,
+    encoding: &str,
+) -> Result<(String, PayloadEncoding), StoreError> {
+    match encoding {
+        "utf8" => String::from_utf8(payload)
+            .map(|text| (text, PayloadEncoding::Utf8))
+            .map_err(|err| StoreError::Parse(format!("invalid utf8 payload: {err}"))),
+        "base64" => Ok((STANDARD.encode(payload), PayloadEncoding::Base64)),
+        other => Err(StoreError::Parse(format!(
+            "unknown payload encoding: {other}"
+        ))),
+    }
+}
+
 fn parse_status(status: &str) -> Result<WebhookEventStatus, StoreError> {
     match status {
         "pending" => Ok(WebhookEventStatus::Pending),
@@ -502,10 +1436,63 @@ fn parse_status(status: &str) -> Result<WebhookEventStatus, StoreError> {
         "delivered" => Ok(WebhookEventStatus::Delivered),
         "dead" => Ok(WebhookEventStatus::Dead),
         "paused" => Ok(WebhookEventStatus::Paused),
+        "expired" => Ok(WebhookEventStatus::Expired),
+        "cancelled" => Ok(WebhookEventStatus::Cancelled),
         other => Err(StoreError::Parse(format!("unknown status: {other}"))),
     }
 }
 
+fn parse_schema_errors(raw: Option<&str>) -> Result<Option<Vec<String>>, StoreError> {
+    raw.map(serde_json::from_str)
+        .transpose()
+        .map_err(|err| StoreError::Parse(format!("invalid schema errors JSON: {err}")))
+}
+
+fn parse_target_kind(kind: &str) -> Result<EndpointTargetKind, StoreError> {
+    match kind {
+        "http" => Ok(EndpointTargetKind::Http),
+        "email" => Ok(EndpointTargetKind::Email),
+        other => Err(StoreError::Parse(format!("unknown target kind: {other}"))),
+    }
+}
+
+fn parse_redirect_mode(mode: &str) -> Result<RedirectMode, StoreError> {
+    match mode {
+        "none" => Ok(RedirectMode::None),
+        "same_host" => Ok(RedirectMode::SameHost),
+        "any" => Ok(RedirectMode::Any),
+        other => Err(StoreError::Parse(format!("unknown redirect mode: {other}"))),
+    }
+}
+
+fn parse_address_family(family: &str) -> Result<AddressFamily, StoreError> {
+    match family {
+        "any" => Ok(AddressFamily::Any),
+        "ipv4" => Ok(AddressFamily::Ipv4),
+        "ipv6" => Ok(AddressFamily::Ipv6),
+        "prefer_ipv4" => Ok(AddressFamily::PreferIpv4),
+        "prefer_ipv6" => Ok(AddressFamily::PreferIpv6),
+        other => Err(StoreError::Parse(format!(
+            "unknown address family: {other}"
+        ))),
+    }
+}
+
+fn parse_check_method(method: &str) -> Result<EndpointCheckMethod, StoreError> {
+    match method {
+        "head" => Ok(EndpointCheckMethod::Head),
+        "options" => Ok(EndpointCheckMethod::Options),
+        "get" => Ok(EndpointCheckMethod::Get),
+        other => Err(StoreError::Parse(format!("unknown check method: {other}"))),
+    }
+}
+
+fn parse_check_timestamp(raw: &str) -> Result<DateTime<Utc>, StoreError> {
+    DateTime::parse_from_rfc3339(raw.trim())
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|err| StoreError::Parse(format!("invalid check timestamp: {err}")))
+}
+
 fn parse_circuit_status(status: &str) -> Result<TargetCircuitStatus, StoreError> {
     match status {
         "closed" => Ok(TargetCircuitStatus::Closed),
@@ -516,29 +1503,320 @@ fn parse_circuit_status(status: &str) -> Result<TargetCircuitStatus, StoreError>
     }
 }
 
+#[derive(sqlx::FromRow)]
+struct CheckTargetRow {
+    id: String,
+    check_method: String,
+    url: String,
+    request_timeout_ms: i64,
+}
+
 #[derive(sqlx::FromRow)]
 struct ReportEventRow {
     endpoint_id: String,
     attempts: i64,
+    carried_attempts: i64,
     leased_by: Option<String>,
     lease_expires_at: Option<String>,
+    leased_target_revision: Option<i64>,
+    leased_target_url: Option<String>,
+    delivery_budget_seconds: Option<i64>,
+    backoff_strategy: Option<String>,
+    success_log_sample_rate: Option<i64>,
+    first_attempt_started_at: Option<String>,
 }
 
 #[derive(sqlx::FromRow)]
 struct CircuitRow {
+    state: String,
     consecutive_failures: i64,
 }
 
+#[derive(sqlx::FromRow)]
+struct CircuitStatsRow {
+    state: String,
+    open_until: Option<String>,
+    consecutive_failures: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct MaintenanceWindowRow {
+    endpoint_id: String,
+    day_of_week: Option<i64>,
+    start_minute: i64,
+    duration_minutes: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct DeliveryWindowRow {
+    endpoint_id: String,
+    day_of_week: Option<i64>,
+    start_minute: i64,
+    duration_minutes: i64,
+    utc_offset_minutes: i64,
+}
+
+impl From<MaintenanceWindowRow> for MaintenanceWindow {
+    fn from(row: MaintenanceWindowRow) -> Self {
+        Self {
+            day_of_week: row.day_of_week,
+            start_minute: row.start_minute,
+            duration_minutes: row.duration_minutes,
+        }
+    }
+}
+
+/// The recurring windows that hold back an endpoint's deliveries.
+#[derive(Default)]
+struct EndpointSchedule {
+    maintenance: Vec<MaintenanceWindow>,
+    delivery: Vec<DeliveryWindow>,
+    utc_offset_minutes: i64,
+}
+
+impl EndpointSchedule {
+    fn deliverable_from(&self, at: chrono::DateTime<Utc>) -> Option<chrono::DateTime<Utc>> {
+        deliverable_from(
+            &self.maintenance,
+            &self.delivery,
+            self.utc_offset_minutes,
+            at,
+        )
+    }
+}
+
+/// Loads the schedules of `endpoint_id`, or of every endpoint that has one.
+async fn load_schedules(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    endpoint_id: Option<&str>,
+) -> Result<BTreeMap<String, EndpointSchedule>, StoreError> {
+    let maintenance: Vec<MaintenanceWindowRow> = sqlx::query_as(
+        r"
+        SELECT endpoint_id, day_of_week, start_minute, duration_minutes
+        FROM endpoint_maintenance_windows
+        WHERE ?1 IS NULL OR endpoint_id = ?1
+        ",
+    )
+    .bind(endpoint_id)
+    .fetch_all(&mut **tx)
+    .await?;
+    let delivery: Vec<DeliveryWindowRow> = sqlx::query_as(
+        r"
+        SELECT w.endpoint_id, w.day_of_week, w.start_minute, w.duration_minutes,
+            ep.delivery_window_utc_offset_minutes AS utc_offset_minutes
+        FROM endpoint_delivery_windows w
+        JOIN endpoints ep ON ep.id = w.endpoint_id
+        WHERE ?1 IS NULL OR w.endpoint_id = ?1
+        ",
+    )
+    .bind(endpoint_id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let mut schedules: BTreeMap<String, EndpointSchedule> = BTreeMap::new();
+    for row in maintenance {
+        schedules
+            .entry(row.endpoint_id.clone())
+            .or_default()
+            .maintenance
+            .push(row.into());
+    }
+    for row in delivery {
+        let schedule = schedules.entry(row.endpoint_id).or_default();
+        schedule.utc_offset_minutes = row.utc_offset_minutes;
+        schedule.delivery.push(DeliveryWindow {
+            day_of_week: row.day_of_week,
+            start_minute: row.start_minute,
+            duration_minutes: row.duration_minutes,
+        });
+    }
+    Ok(schedules)
+}
+
+/// Pushes `next_attempt_at` of every queued event whose endpoint is inside
+/// a maintenance window or outside its delivery windows to when it can
+/// next take deliveries, which also keeps them out of the current lease.
+async fn defer_blocked_endpoints(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    now: chrono::DateTime<Utc>,
+) -> Result<(), StoreError> {
+    for (endpoint_id, schedule) in load_schedules(tx, None).await? {
+        let Some(deliverable_from) = schedule.deliverable_from(now) else {
+            continue;
+        };
+        let deliverable_from = format_utc(deliverable_from);
+        sqlx::query(
+            r"
+            UPDATE webhook_events
+            SET next_attempt_at = ?,
+                updated_at = ?
+            WHERE endpoint_id = ?
+              AND (status = 'pending' OR status = 'requeued')
+              AND (next_attempt_at IS NULL OR next_attempt_at < ?)
+            ",
+        )
+        .bind(&deliverable_from)
+        .bind(format_utc(now))
+        .bind(&endpoint_id)
+        .bind(&deliverable_from)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Marks queued events of endpoints whose target kind has no delivery path
+/// dead, so they show up with an error instead of waiting forever. AMQP
+/// targets are refused by the API; this covers endpoints set up directly
+/// in the database.
+async fn dead_letter_unsupported_targets(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    now: chrono::DateTime<Utc>,
+) -> Result<(), StoreError> {
+    sqlx::query(
+        r"
+        UPDATE webhook_events
+        SET status = 'dead',
+            next_attempt_at = NULL,
+            last_error = 'target kind ' || (
+                SELECT target_kind FROM endpoints WHERE endpoints.id = webhook_events.endpoint_id
+            ) || ' is not supported',
+            updated_at = ?
+        WHERE (status = 'pending' OR status = 'requeued')
+          AND endpoint_id IN (
+              SELECT id FROM endpoints WHERE target_kind NOT IN ('http', 'email')
+          )
+        ",
+    )
+    .bind(format_utc(now))
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Moves a retry scheduled inside one of the endpoint's maintenance windows
+/// or outside its delivery windows to when the endpoint can next take
+/// deliveries.
+async fn defer_past_blocked_windows(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    endpoint_id: &str,
+    next_attempt_at: String,
+) -> Result<String, StoreError> {
+    let Some(schedule) = load_schedules(tx, Some(endpoint_id))
+        .await?
+        .remove(endpoint_id)
+    else {
+        return Ok(next_attempt_at);
+    };
+
+    let scheduled = chrono::DateTime::parse_from_rfc3339(&next_attempt_at)
+        .map_err(|err| StoreError::Parse(format!("invalid next_attempt_at: {err}")))?
+        .with_timezone(&Utc);
+
+    Ok(schedule
+        .deliverable_from(scheduled)
+        .map_or(next_attempt_at, format_utc))
+}
+
 fn error_kind_to_str(kind: WebhookAttemptErrorKind) -> &'static str {
     match kind {
         WebhookAttemptErrorKind::Timeout => "timeout",
+        WebhookAttemptErrorKind::Dns => "dns",
+        WebhookAttemptErrorKind::Tls => "tls",
+        WebhookAttemptErrorKind::ConnectionRefused => "connection_refused",
+        WebhookAttemptErrorKind::TooManyRedirects => "too_many_redirects",
+        WebhookAttemptErrorKind::PayloadTooLarge => "payload_too_large",
         WebhookAttemptErrorKind::Network => "network",
         WebhookAttemptErrorKind::InvalidResponse => "invalid_response",
         WebhookAttemptErrorKind::Unexpected => "unexpected",
     }
 }
 
-fn compute_cooldown_ms(config: &DispatcherConfig, consecutive_failures: i64) -> u64 {
+/// A stable pseudo-random number for one per-event decision. Hashing the
+/// purpose in keeps decisions independent of each other: canary events are
+/// no more likely than others to have their response captured or their
+/// attempt logged.
+fn sample_bucket(purpose: &str, event_id: Uuid) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(purpose.as_bytes())
+        .chain_update(event_id.as_bytes())
+        .finalize();
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(prefix)
+}
+
+/// Decides whether a reported response body is kept. Sampling is keyed on
+/// the event ID so the decision is stable and needs no RNG.
+fn response_capture_for(
+    config: &DispatcherConfig,
+    event_id: Uuid,
+    outcome: ReportOutcome,
+) -> ResponseCapture {
+    match outcome {
+        ReportOutcome::Retry | ReportOutcome::Dead => ResponseCapture::Failure,
+        ReportOutcome::Delivered if config.success_response_sample_percent >= 100 => {
+            ResponseCapture::All
+        }
+        ReportOutcome::Delivered
+            if sample_bucket("response_capture", event_id) % 100
+                < u64::from(config.success_response_sample_percent) =>
+        {
+            ResponseCapture::Sampled
+        }
+        ReportOutcome::Delivered => ResponseCapture::Dropped,
+    }
+}
+
+/// How attempt log sampling treats a reported attempt.
+#[derive(Debug, Clone, Copy)]
+enum AttemptLogSampling {
+    /// Failures, and successes of endpoints that log every attempt.
+    Logged,
+    /// A success kept by 1-in-N sampling at this rate.
+    Kept(i64),
+    /// A success left out of the log by 1-in-N sampling at this rate.
+    Dropped(i64),
+}
+
+/// Samples successful attempts of endpoints with a `sample_rate` above 1.
+/// Like response capture this is keyed on the event ID, so the decision
+/// is stable and needs no RNG.
+fn attempt_log_sampling_for(
+    event_id: Uuid,
+    outcome: ReportOutcome,
+    sample_rate: Option<i64>,
+) -> AttemptLogSampling {
+    match sample_rate {
+        Some(rate) if rate > 1 && outcome == ReportOutcome::Delivered => {
+            if sample_bucket("attempt_log", event_id).is_multiple_of(rate.unsigned_abs()) {
+                AttemptLogSampling::Kept(rate)
+            } else {
+                AttemptLogSampling::Dropped(rate)
+            }
+        }
+        _ => AttemptLogSampling::Logged,
+    }
+}
+
+/// Whether an event falls in its endpoint's canary share. Like response
+/// sampling this is keyed on the event ID, so retries keep their target.
+fn routes_to_canary(event_id: Uuid, canary_percent: i64) -> bool {
+    sample_bucket("canary", event_id) % 100 < u64::try_from(canary_percent).unwrap_or(0)
+}
+
+fn response_capture_to_str(capture: ResponseCapture) -> &'static str {
+    match capture {
+        ResponseCapture::Failure => "failure",
+        ResponseCapture::All => "all",
+        ResponseCapture::Sampled => "sampled",
+        ResponseCapture::Dropped => "dropped",
+    }
+}
+
+pub(super) fn compute_cooldown_ms(config: &DispatcherConfig, consecutive_failures: i64) -> u64 {
     let threshold = i64::from(config.circuit_failure_threshold);
     if consecutive_failures < threshold {
         return 0;
@@ -568,7 +1846,7 @@ async fn update_circuit_on_failure(
 
     let row = sqlx::query_as::<_, CircuitRow>(
         r"
-        SELECT consecutive_failures
+        SELECT state, consecutive_failures
         FROM target_circuit_states
         WHERE endpoint_id = ?
         ",
@@ -577,6 +1855,7 @@ async fn update_circuit_on_failure(
     .fetch_optional(&mut **tx)
     .await?;
 
+    let was_open = row.as_ref().is_some_and(|row| row.state == "open");
     let current_failures = row.map_or(0, |row| row.consecutive_failures);
     let consecutive_failures = current_failures + 1;
     let cooldown_ms = compute_cooldown_ms(config, consecutive_failures);
@@ -620,6 +1899,23 @@ async fn update_circuit_on_failure(
     .execute(&mut **tx)
     .await?;
 
+    if should_open && !was_open {
+        sqlx::query(
+            r"
+            INSERT INTO target_circuit_transitions (
+                endpoint_id, state, open_until, consecutive_failures, changed_at
+            )
+            VALUES (?, 'open', ?, ?, ?)
+            ",
+        )
+        .bind(endpoint_id)
+        .bind(open_until.as_deref())
+        .bind(consecutive_failures)
+        .bind(now_str)
+        .execute(&mut **tx)
+        .await?;
+    }
+
     Ok(Some(TargetCircuitState {
         endpoint_id: endpoint_uuid,
         state,
@@ -629,11 +1925,21 @@ async fn update_circuit_on_failure(
     }))
 }
 
-fn compute_next_attempt_at(now: chrono::DateTime<Utc>, attempt_no: i64) -> String {
-    let attempt_no = attempt_no.max(1);
-    let exponent = (attempt_no - 1).min(31) as u32;
-    let delay_secs = (1u64 << exponent).min(3600);
-    format_utc(now + Duration::seconds(delay_secs as i64))
+/// Retry time of failed attempt `attempt_no` under the endpoint's stored
+/// backoff strategy, or [`DEFAULT_BACKOFF`] when it has none.
+fn compute_next_attempt_at(
+    now: chrono::DateTime<Utc>,
+    backoff_strategy: Option<&str>,
+    attempt_no: i64,
+) -> Result<String, StoreError> {
+    let strategy = backoff_strategy
+        .map(serde_json::from_str::<BackoffStrategy>)
+        .transpose()
+        .map_err(|err| StoreError::Parse(format!("invalid backoff_strategy: {err}")))?
+        .unwrap_or(DEFAULT_BACKOFF);
+    let delay_secs = retry_delay_secs(&strategy, attempt_no, now)
+        .map_err(|err| StoreError::Parse(format!("invalid backoff_strategy: {err}")))?;
+    Ok(format_utc(now + Duration::seconds(delay_secs)))
 }
 
 fn normalize_rfc3339_utc(value: &str) -> Result<String, StoreError> {
@@ -1,6 +1,6 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header::RETRY_AFTER},
     response::{IntoResponse, Response},
 };
 
@@ -14,15 +14,28 @@ pub enum ApiError {
     #[error("unauthorized: {message}")]
     Unauthorized { message: String },
 
+    #[error("forbidden: {message}")]
+    Forbidden { message: String },
+
     #[error("rate limited: {message}")]
     RateLimited { message: String },
 
+    /// A 429 that tells the client when to retry.
+    #[error("backpressure: {message}")]
+    Backpressure {
+        message: String,
+        retry_after_secs: u64,
+    },
+
     #[error("not found: {message}")]
     NotFound { message: String },
 
     #[error("conflict: {message}")]
     Conflict { message: String },
 
+    #[error("payload too large: {message}")]
+    PayloadTooLarge { message: String },
+
     #[error("database error")]
     Db(#[from] sqlx::Error),
 
@@ -43,12 +56,25 @@ impl ApiError {
         }
     }
 
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::Forbidden {
+            message: message.into(),
+        }
+    }
+
     pub fn rate_limited(message: impl Into<String>) -> Self {
         Self::RateLimited {
             message: message.into(),
         }
     }
 
+    pub fn backpressure(message: impl Into<String>, retry_after_secs: u64) -> Self {
+        Self::Backpressure {
+            message: message.into(),
+            retry_after_secs,
+        }
+    }
+
     pub fn not_found(message: impl Into<String>) -> Self {
         Self::NotFound {
             message: message.into(),
@@ -61,6 +87,12 @@ impl ApiError {
         }
     }
 
+    pub fn payload_too_large(message: impl Into<String>) -> Self {
+        Self::PayloadTooLarge {
+            message: message.into(),
+        }
+    }
+
     pub fn internal(message: impl Into<String>) -> Self {
         Self::Internal {
             message: message.into(),
@@ -77,13 +109,21 @@ impl ApiError {
                 ApiErrorCode::Unauthorized,
                 message,
             ),
-            Self::RateLimited { message } => (
+            Self::Forbidden { message } => {
+                (StatusCode::FORBIDDEN, ApiErrorCode::Forbidden, message)
+            }
+            Self::RateLimited { message } | Self::Backpressure { message, .. } => (
                 StatusCode::TOO_MANY_REQUESTS,
                 ApiErrorCode::RateLimited,
                 message,
             ),
             Self::NotFound { message } => (StatusCode::NOT_FOUND, ApiErrorCode::NotFound, message),
             Self::Conflict { message } => (StatusCode::CONFLICT, ApiErrorCode::Conflict, message),
+            Self::PayloadTooLarge { message } => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                ApiErrorCode::PayloadTooLarge,
+                message,
+            ),
             Self::Db(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 ApiErrorCode::Database,
@@ -100,7 +140,19 @@ impl ApiError {
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        let retry_after = match &self {
+            Self::Backpressure {
+                retry_after_secs, ..
+            } => Some(*retry_after_secs),
+            _ => None,
+        };
         let (status, code, message) = self.into_response_parts();
-        (status, Json(ApiErrorResponse { code, message })).into_response()
+        let mut response = (status, Json(ApiErrorResponse { code, message })).into_response();
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
     }
 }
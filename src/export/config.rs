@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use crate::types::ExportFormat;
+
+/// Where scheduled exports are written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportSink {
+    /// Files under a local directory.
+    Dir(PathBuf),
+    /// Objects in an S3 bucket, with keys under `prefix`. Region,
+    /// credentials and a custom endpoint come from the standard `AWS_*`
+    /// variables.
+    S3 { bucket: String, prefix: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportScheduleConfig {
+    /// Where nightly exports are written; `None` disables them.
+    pub sink: Option<ExportSink>,
+    pub format: ExportFormat,
+    /// UTC hour from which the previous day's events are exported.
+    pub hour_utc: u32,
+    /// How often the job checks whether an export is due.
+    pub check_interval_ms: u64,
+}
+
+impl ExportScheduleConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let mut config = Self::default();
+
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        config.sink = match (var("RECEIVER_EXPORT_DIR"), var("RECEIVER_EXPORT_S3_BUCKET")) {
+            (Some(_), Some(_)) => {
+                return Err(
+                    "set RECEIVER_EXPORT_DIR or RECEIVER_EXPORT_S3_BUCKET, not both".to_string(),
+                );
+            }
+            (Some(dir), None) => Some(ExportSink::Dir(PathBuf::from(dir))),
+            (None, Some(bucket)) => Some(ExportSink::S3 {
+                bucket,
+                prefix: var("RECEIVER_EXPORT_S3_PREFIX").unwrap_or_default(),
+            }),
+            (None, None) => None,
+        };
+        if let Ok(value) = std::env::var("RECEIVER_EXPORT_FORMAT") {
+            match value.trim() {
+                "ndjson" => config.format = ExportFormat::Ndjson,
+                "csv" => config.format = ExportFormat::Csv,
+                _ => {}
+            }
+        }
+        if let Ok(value) = std::env::var("RECEIVER_EXPORT_HOUR_UTC")
+            && let Ok(parsed) = value.parse::<u32>()
+        {
+            config.hour_utc = parsed.min(23);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_EXPORT_CHECK_INTERVAL_MS")
+            && let Ok(parsed) = value.parse::<u64>()
+        {
+            config.check_interval_ms = parsed.max(1_000);
+        }
+
+        Ok(config)
+    }
+}
+
+impl Default for ExportScheduleConfig {
+    fn default() -> Self {
+        Self {
+            sink: None,
+            format: ExportFormat::Ndjson,
+            hour_utc: 1,
+            check_interval_ms: 300_000,
+        }
+    }
+}
@@ -0,0 +1,10 @@
+mod config;
+mod schedule;
+mod store;
+
+pub use config::{ExportScheduleConfig, ExportSink};
+pub use schedule::{
+    ExportError, export_day, export_key, export_path, open_sink, run_scheduled_export,
+    spawn_scheduled_export,
+};
+pub use store::{get_export_status, record_export_failure, record_export_success};
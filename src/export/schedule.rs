@@ -0,0 +1,177 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, SecondsFormat, Timelike, Utc};
+use object_store::aws::AmazonS3Builder;
+use object_store::local::LocalFileSystem;
+use object_store::prefix::PrefixStore;
+use object_store::{ObjectStore, path::Path as ObjectPath};
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+
+use super::store::{get_export_status, record_export_failure, record_export_success};
+use super::{ExportScheduleConfig, ExportSink};
+use crate::inspector::{
+    EndpointScope, ExportEventsParams, StoreError, export_events, render_csv, render_ndjson,
+};
+use crate::types::ExportFormat;
+
+#[derive(Debug)]
+pub enum ExportError {
+    Store(StoreError),
+    Io(std::io::Error),
+    Encode(serde_json::Error),
+    Sink(object_store::Error),
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Store(err) => write!(f, "store error: {err:?}"),
+            Self::Io(err) => write!(f, "io error: {err}"),
+            Self::Encode(err) => write!(f, "encode error: {err}"),
+            Self::Sink(err) => write!(f, "sink error: {err}"),
+        }
+    }
+}
+
+impl From<StoreError> for ExportError {
+    fn from(err: StoreError) -> Self {
+        Self::Store(err)
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<object_store::Error> for ExportError {
+    fn from(err: object_store::Error) -> Self {
+        Self::Sink(err)
+    }
+}
+
+/// Key of the export of events received on `day`, partitioned by date:
+/// `dt=YYYY-MM-DD/events.<ndjson|csv>`.
+pub fn export_key(day: NaiveDate, format: ExportFormat) -> String {
+    let extension = match format {
+        ExportFormat::Ndjson => "ndjson",
+        ExportFormat::Csv => "csv",
+    };
+    format!("dt={}/events.{extension}", day.format("%Y-%m-%d"))
+}
+
+/// Where [`export_key`] lands under a local export directory.
+pub fn export_path(dir: &Path, day: NaiveDate, format: ExportFormat) -> PathBuf {
+    dir.join(export_key(day, format))
+}
+
+/// Opens the store exports are written to. A local directory is created
+/// if missing.
+pub fn open_sink(sink: &ExportSink) -> Result<Arc<dyn ObjectStore>, ExportError> {
+    match sink {
+        ExportSink::Dir(dir) => {
+            std::fs::create_dir_all(dir)?;
+            Ok(Arc::new(LocalFileSystem::new_with_prefix(dir)?))
+        }
+        ExportSink::S3 { bucket, prefix } => {
+            let store = AmazonS3Builder::from_env()
+                .with_bucket_name(bucket)
+                .build()?;
+            Ok(Arc::new(PrefixStore::new(store, prefix.as_str())))
+        }
+    }
+}
+
+/// Exports every event received on `day` (UTC) to `sink` and returns the
+/// key written. Both sinks only make the object visible once it is
+/// complete, so readers never see a partial export.
+pub async fn export_day(
+    pool: &SqlitePool,
+    sink: &dyn ObjectStore,
+    format: ExportFormat,
+    day: NaiveDate,
+) -> Result<String, ExportError> {
+    let start = day.and_time(NaiveTime::MIN).and_utc();
+    let params = ExportEventsParams {
+        received_since: Some(start.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        received_before: Some(
+            (start + chrono::Duration::days(1)).to_rfc3339_opts(SecondsFormat::Secs, true),
+        ),
+        ..ExportEventsParams::default()
+    };
+    let records = export_events(pool, &EndpointScope::All, &params).await?;
+    let body = match format {
+        ExportFormat::Ndjson => render_ndjson(&records).map_err(ExportError::Encode)?,
+        ExportFormat::Csv => render_csv(&records),
+    };
+
+    let key = export_key(day, format);
+    sink.put(&ObjectPath::from(key.as_str()), body.into())
+        .await?;
+    Ok(key)
+}
+
+/// Exports every day that is due at `now` and not yet exported, oldest
+/// first, and returns the days written. The previous day is due once the
+/// configured UTC hour has passed. The first run only exports that day;
+/// afterwards every day since the last export is due, so days missed while
+/// the receiver was down or the sink was failing are backfilled.
+///
+/// The first failure ends the run and is recorded in the export status;
+/// the day is retried on the next run.
+pub async fn run_scheduled_export(
+    pool: &SqlitePool,
+    sink: &dyn ObjectStore,
+    config: &ExportScheduleConfig,
+    now: DateTime<Utc>,
+) -> Result<Vec<NaiveDate>, ExportError> {
+    let yesterday = (now - chrono::Duration::days(1)).date_naive();
+    let latest_due = if now.hour() < config.hour_utc {
+        yesterday - chrono::Duration::days(1)
+    } else {
+        yesterday
+    };
+    let last_exported = get_export_status(pool)
+        .await?
+        .last_exported_day
+        .and_then(|day| NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok());
+    let mut day = last_exported.map_or(yesterday, |day| day + chrono::Duration::days(1));
+
+    let mut exported = Vec::new();
+    while day <= latest_due {
+        if let Err(err) = export_day(pool, sink, config.format, day).await {
+            record_export_failure(pool, &format!("export of {day} failed: {err}")).await?;
+            return Err(err);
+        }
+        record_export_success(pool, &day.format("%Y-%m-%d").to_string()).await?;
+        exported.push(day);
+        day += chrono::Duration::days(1);
+    }
+    Ok(exported)
+}
+
+/// Runs [`run_scheduled_export`] on an interval. Checking on an interval
+/// rather than sleeping until the hour means a restart after the hour
+/// still produces the export. Failures are recorded in the export status
+/// and retried on the next check.
+pub fn spawn_scheduled_export(
+    pool: SqlitePool,
+    config: ExportScheduleConfig,
+) -> Result<Option<JoinHandle<()>>, ExportError> {
+    let Some(sink) = &config.sink else {
+        return Ok(None);
+    };
+    let sink = open_sink(sink)?;
+    Ok(Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_millis(config.check_interval_ms));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let _ = run_scheduled_export(&pool, sink.as_ref(), &config, Utc::now()).await;
+        }
+    })))
+}
@@ -0,0 +1,76 @@
+use chrono::{SecondsFormat, Utc};
+use sqlx::SqlitePool;
+
+use crate::inspector::StoreError;
+use crate::types::ScheduledExportStatus;
+
+#[derive(sqlx::FromRow)]
+struct ExportStatusRow {
+    last_exported_day: Option<String>,
+    last_success_at: Option<String>,
+    last_error: Option<String>,
+    last_error_at: Option<String>,
+    consecutive_failures: i64,
+}
+
+pub async fn get_export_status(pool: &SqlitePool) -> Result<ScheduledExportStatus, StoreError> {
+    let row: Option<ExportStatusRow> = sqlx::query_as(
+        r"
+        SELECT last_exported_day, last_success_at, last_error, last_error_at,
+            consecutive_failures
+        FROM export_status
+        WHERE id = 1
+        ",
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(
+        row.map_or_else(Default::default, |row| ScheduledExportStatus {
+            last_exported_day: row.last_exported_day,
+            last_success_at: row.last_success_at,
+            last_error: row.last_error,
+            last_error_at: row.last_error_at,
+            consecutive_failures: row.consecutive_failures,
+        }),
+    )
+}
+
+/// Records that every day up to `day` (`YYYY-MM-DD`) has been exported.
+pub async fn record_export_success(pool: &SqlitePool, day: &str) -> Result<(), StoreError> {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    sqlx::query(
+        r"
+        INSERT INTO export_status (id, last_exported_day, last_success_at)
+        VALUES (1, ?1, ?2)
+        ON CONFLICT(id) DO UPDATE SET
+            last_exported_day = ?1,
+            last_success_at = ?2,
+            consecutive_failures = 0
+        ",
+    )
+    .bind(day)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn record_export_failure(pool: &SqlitePool, error: &str) -> Result<(), StoreError> {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    sqlx::query(
+        r"
+        INSERT INTO export_status (id, last_error, last_error_at, consecutive_failures)
+        VALUES (1, ?1, ?2, 1)
+        ON CONFLICT(id) DO UPDATE SET
+            last_error = ?1,
+            last_error_at = ?2,
+            consecutive_failures = consecutive_failures + 1
+        ",
+    )
+    .bind(error)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -8,7 +8,7 @@ use axum::{
 };
 use serde::de::DeserializeOwned;
 
-use crate::error::ApiError;
+use crate::{auth::InspectorActor, error::ApiError, inspector::EndpointScope};
 
 pub struct ValidJson<T>(pub T);
 
@@ -69,3 +69,38 @@ where
         }
     }
 }
+
+/// The endpoint scope attached by `inspector_auth`. Routes without the auth
+/// middleware are unauthenticated and therefore unscoped.
+#[async_trait]
+impl<S> FromRequestParts<S> for EndpointScope
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<EndpointScope>()
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+/// The caller identified by `inspector_auth`; anonymous when auth is off.
+#[async_trait]
+impl<S> FromRequestParts<S> for InspectorActor
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<InspectorActor>()
+            .cloned()
+            .unwrap_or_else(InspectorActor::anonymous))
+    }
+}
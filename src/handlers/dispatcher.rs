@@ -1,12 +1,34 @@
-use axum::{Json, extract::State};
-use chrono::DateTime;
+use std::collections::BTreeMap;
+
+use axum::{
+    Json,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{DateTime, SecondsFormat, Utc};
 
 use crate::{
-    dispatcher::{StoreError, lease_events, report_delivery},
+    dispatcher::{
+        EchoTarget, INJECTED_ECHO_MESSAGE, StoreError, inject_faults, lease_endpoint_checks,
+        lease_events, record_endpoint_check, record_shadow_attempt, renew_lease, report_delivery,
+    },
     error::ApiError,
     extractors::ValidJson,
+    jobs::{
+        MAX_RECONCILE_RESULT_IDS, StoreError as JobStoreError, lease_reconcile_runs,
+        record_reconcile_result,
+    },
     state::AppState,
-    types::{LeaseRequest, LeaseResponse, ReportRequest, ReportResponse},
+    types::{
+        CheckLeaseRequest, CheckLeaseResponse, CheckReportRequest, CheckReportResponse,
+        EchoDeliveriesResponse, EchoDelivery, LeaseRequest, LeaseResponse, PayloadEncoding,
+        ReconcileLeaseRequest, ReconcileLeaseResponse, ReconcileResultRequest,
+        ReconcileResultResponse, RenewRequest, RenewResponse, ReportAttempt, ReportRequest,
+        ReportResponse, ShadowReportRequest, ShadowReportResponse,
+    },
 };
 
 pub async fn lease_handler(
@@ -15,7 +37,7 @@ pub async fn lease_handler(
 ) -> Result<Json<LeaseResponse>, ApiError> {
     validate_request(&req)?;
 
-    let events = lease_events(&state.pool, &req)
+    let events = lease_events(&state.pool, &state.dispatcher, &req)
         .await
         .map_err(map_store_error)?;
 
@@ -24,10 +46,16 @@ pub async fn lease_handler(
 
 pub async fn report_handler(
     State(state): State<AppState>,
-    ValidJson(req): ValidJson<ReportRequest>,
+    ValidJson(mut req): ValidJson<ReportRequest>,
 ) -> Result<Json<ReportResponse>, ApiError> {
     validate_report_request(&req)?;
 
+    if state.dispatcher.fault_injection_enabled {
+        inject_faults(&state.pool, &mut req)
+            .await
+            .map_err(map_store_error)?;
+    }
+
     let result = report_delivery(&state.pool, &state.dispatcher, &req)
         .await
         .map_err(map_store_error)?;
@@ -35,9 +63,204 @@ pub async fn report_handler(
     Ok(Json(ReportResponse {
         circuit: result.circuit,
         final_outcome: result.final_outcome,
+        endpoint_stats: result.endpoint_stats,
     }))
 }
 
+/// Debug delivery target: answers with the request's own body and
+/// content type, or an injected 500 at the configured failure rate, and
+/// records what it received either way.
+pub async fn echo_delivery_handler(
+    State(echo): State<EchoTarget>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    let injected_failure = echo.roll_failure();
+    let status = if injected_failure {
+        StatusCode::INTERNAL_SERVER_ERROR
+    } else {
+        StatusCode::OK
+    };
+    let (recorded_body, body_encoding) = match std::str::from_utf8(&body) {
+        Ok(text) => (text.to_string(), PayloadEncoding::Utf8),
+        Err(_) => (STANDARD.encode(&body), PayloadEncoding::Base64),
+    };
+    echo.record(EchoDelivery {
+        received_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        headers: headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect::<BTreeMap<_, _>>(),
+        body: recorded_body,
+        body_encoding,
+        size_bytes: body.len() as i64,
+        status: i64::from(status.as_u16()),
+        injected_failure,
+    });
+
+    if injected_failure {
+        return Err(ApiError::internal(INJECTED_ECHO_MESSAGE));
+    }
+    let mut response = body.into_response();
+    if let Some(content_type) = headers.get(CONTENT_TYPE) {
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, content_type.clone());
+    }
+    Ok(response)
+}
+
+/// Deliveries the echo target received, oldest first.
+pub async fn list_echo_deliveries_handler(
+    State(echo): State<EchoTarget>,
+) -> Json<EchoDeliveriesResponse> {
+    Json(EchoDeliveriesResponse {
+        deliveries: echo.received(),
+    })
+}
+
+pub async fn clear_echo_deliveries_handler(State(echo): State<EchoTarget>) -> StatusCode {
+    echo.clear();
+    StatusCode::NO_CONTENT
+}
+
+pub async fn renew_handler(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<RenewRequest>,
+) -> Result<Json<RenewResponse>, ApiError> {
+    if req.lease_ms <= 0 {
+        return Err(ApiError::validation("lease_ms must be > 0"));
+    }
+    if req.worker_id.trim().is_empty() {
+        return Err(ApiError::validation("worker_id is required"));
+    }
+
+    let lease_expires_at = renew_lease(&state.pool, &req)
+        .await
+        .map_err(map_store_error)?;
+
+    Ok(Json(RenewResponse { lease_expires_at }))
+}
+
+pub async fn shadow_report_handler(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<ShadowReportRequest>,
+) -> Result<Json<ShadowReportResponse>, ApiError> {
+    if req.worker_id.trim().is_empty() {
+        return Err(ApiError::validation("worker_id is required"));
+    }
+    if req.target_url.trim().is_empty() {
+        return Err(ApiError::validation("target_url is required"));
+    }
+    validate_attempt(&req.attempt)?;
+
+    let attempt_id = record_shadow_attempt(&state.pool, &req)
+        .await
+        .map_err(map_store_error)?;
+
+    Ok(Json(ShadowReportResponse { attempt_id }))
+}
+
+pub async fn check_lease_handler(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<CheckLeaseRequest>,
+) -> Result<Json<CheckLeaseResponse>, ApiError> {
+    if req.limit <= 0 {
+        return Err(ApiError::validation("limit must be > 0"));
+    }
+    if req.worker_id.trim().is_empty() {
+        return Err(ApiError::validation("worker_id is required"));
+    }
+
+    let checks = lease_endpoint_checks(&state.pool, &state.dispatcher, &req)
+        .await
+        .map_err(map_store_error)?;
+
+    Ok(Json(CheckLeaseResponse { checks }))
+}
+
+pub async fn check_report_handler(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<CheckReportRequest>,
+) -> Result<Json<CheckReportResponse>, ApiError> {
+    if req.worker_id.trim().is_empty() {
+        return Err(ApiError::validation("worker_id is required"));
+    }
+    if req.url.trim().is_empty() {
+        return Err(ApiError::validation("url is required"));
+    }
+    let started_at = parse_rfc3339("started_at", req.started_at.trim())?;
+    let finished_at = parse_rfc3339("finished_at", req.finished_at.trim())?;
+    if finished_at < started_at {
+        return Err(ApiError::validation("finished_at must be >= started_at"));
+    }
+    if let Some(value) = req.tls_expires_at.as_deref() {
+        parse_rfc3339("tls_expires_at", value)?;
+    }
+
+    let check_id = record_endpoint_check(&state.pool, &req)
+        .await
+        .map_err(map_store_error)?;
+
+    Ok(Json(CheckReportResponse { check_id }))
+}
+
+pub async fn reconcile_lease_handler(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<ReconcileLeaseRequest>,
+) -> Result<Json<ReconcileLeaseResponse>, ApiError> {
+    if req.limit <= 0 {
+        return Err(ApiError::validation("limit must be > 0"));
+    }
+    if req.lease_ms <= 0 {
+        return Err(ApiError::validation("lease_ms must be > 0"));
+    }
+    if req.worker_id.trim().is_empty() {
+        return Err(ApiError::validation("worker_id is required"));
+    }
+
+    let tasks = lease_reconcile_runs(&state.pool, &req)
+        .await
+        .map_err(map_job_error)?;
+
+    Ok(Json(ReconcileLeaseResponse { tasks }))
+}
+
+/// Records the provider events a worker listed for a reconciliation, and
+/// answers with the ones never ingested. 409 `lease_*` when the worker no
+/// longer holds the job.
+pub async fn reconcile_result_handler(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<ReconcileResultRequest>,
+) -> Result<Json<ReconcileResultResponse>, ApiError> {
+    if req.worker_id.trim().is_empty() {
+        return Err(ApiError::validation("worker_id is required"));
+    }
+    if req.provider_event_ids.len() > MAX_RECONCILE_RESULT_IDS {
+        return Err(ApiError::validation(format!(
+            "provider_event_ids must contain at most {MAX_RECONCILE_RESULT_IDS} entries"
+        )));
+    }
+    if req.provider_event_ids.iter().any(|id| id.trim().is_empty()) {
+        return Err(ApiError::validation("provider_event_ids must be non-empty"));
+    }
+    if req
+        .error
+        .as_deref()
+        .is_some_and(|error| error.trim().is_empty())
+    {
+        return Err(ApiError::validation("error must be non-empty"));
+    }
+
+    let result = record_reconcile_result(&state.pool, &req)
+        .await
+        .map_err(map_job_error)?;
+
+    Ok(Json(result))
+}
+
 fn validate_request(req: &LeaseRequest) -> Result<(), ApiError> {
     if req.limit <= 0 {
         return Err(ApiError::validation("limit must be > 0"));
@@ -48,6 +271,13 @@ fn validate_request(req: &LeaseRequest) -> Result<(), ApiError> {
     if req.worker_id.trim().is_empty() {
         return Err(ApiError::validation("worker_id is required"));
     }
+    if req
+        .region
+        .as_deref()
+        .is_some_and(|region| region.trim().is_empty())
+    {
+        return Err(ApiError::validation("region must be non-empty"));
+    }
 
     Ok(())
 }
@@ -56,8 +286,27 @@ fn validate_report_request(req: &ReportRequest) -> Result<(), ApiError> {
     if req.worker_id.trim().is_empty() {
         return Err(ApiError::validation("worker_id is required"));
     }
-    let started_at_raw = req.attempt.started_at.trim();
-    let finished_at_raw = req.attempt.finished_at.trim();
+    for (field, value) in [
+        ("worker_version", &req.worker_version),
+        ("worker_region", &req.worker_region),
+    ] {
+        if value
+            .as_deref()
+            .is_some_and(|value| value.trim().is_empty())
+        {
+            return Err(ApiError::validation(format!("{field} must be non-empty")));
+        }
+    }
+    validate_attempt(&req.attempt)?;
+    if let Some(value) = req.next_attempt_at.as_deref() {
+        parse_rfc3339("next_attempt_at", value)?;
+    }
+    Ok(())
+}
+
+fn validate_attempt(attempt: &ReportAttempt) -> Result<(), ApiError> {
+    let started_at_raw = attempt.started_at.trim();
+    let finished_at_raw = attempt.finished_at.trim();
     if started_at_raw.is_empty() || finished_at_raw.is_empty() {
         return Err(ApiError::validation(
             "attempt started_at and finished_at are required",
@@ -70,8 +319,17 @@ fn validate_report_request(req: &ReportRequest) -> Result<(), ApiError> {
             "attempt finished_at must be >= started_at",
         ));
     }
-    if let Some(value) = req.next_attempt_at.as_deref() {
-        parse_rfc3339("next_attempt_at", value)?;
+    if let Some(timing) = attempt.timing {
+        let phases = [
+            timing.dns_ms,
+            timing.connect_ms,
+            timing.tls_ms,
+            timing.ttfb_ms,
+            timing.total_ms,
+        ];
+        if phases.into_iter().flatten().any(|ms| ms < 0) {
+            return Err(ApiError::validation("attempt timings must be >= 0"));
+        }
     }
     Ok(())
 }
@@ -81,6 +339,15 @@ fn parse_rfc3339(field: &str, value: &str) -> Result<DateTime<chrono::FixedOffse
         .map_err(|_| ApiError::validation(format!("{field} must be RFC3339")))
 }
 
+fn map_job_error(err: JobStoreError) -> ApiError {
+    match err {
+        JobStoreError::Conflict(message) => ApiError::conflict(message),
+        JobStoreError::Db(db) => ApiError::Db(db),
+        JobStoreError::NotFound(message) => ApiError::not_found(message),
+        JobStoreError::Parse(message) => ApiError::internal(message),
+    }
+}
+
 fn map_store_error(err: StoreError) -> ApiError {
     match err {
         StoreError::Conflict(message) => ApiError::conflict(message),
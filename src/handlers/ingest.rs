@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    Json,
+    body::{Body, Bytes},
+    extract::State,
+    http::{HeaderMap, StatusCode, header::CONTENT_ENCODING},
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+
+use crate::{
+    auth::{bearer_token, constant_time_eq},
+    error::ApiError,
+    extractors::ValidPath,
+    ingest::{
+        DecodeError, EnqueueError, IngestOutcome, IngestSource, NewEvent, QuotaExceeded,
+        StoreError, check_group_quota, decode_body, deterministic_event_id, extract_event_type,
+        extract_metadata, extract_provider_event_id, filter_headers, find_delivery_receipt,
+        find_payload_schema, find_scrub_ruleset, find_source_by_slug, find_sources_by_endpoint,
+        ingest_backpressured, ingest_health, insert_event, record_ingest_outcome, scrub_payload,
+        validate_payload, verify_signature,
+    },
+    state::AppState,
+    types::{DeliveryReceipt, IngestHealth, IngestMode, IngestResponse, PayloadEncoding},
+};
+
+pub async fn ingest_source_handler(
+    State(state): State<AppState>,
+    ValidPath(source_slug): ValidPath<String>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<(StatusCode, Json<IngestResponse>), ApiError> {
+    let source = find_source_by_slug(&state.pool, &source_slug)
+        .await
+        .map_err(map_store_error)?;
+
+    // Providers retry on 429, so events wait with them rather than in a
+    // backlog that cannot be delivered in time.
+    if ingest_backpressured(&state.pool, &state.ingest)
+        .await
+        .map_err(map_store_error)?
+    {
+        return Err(ApiError::backpressure(
+            "delivery backlog is over its threshold",
+            state.ingest.backpressure_retry_after_secs,
+        ));
+    }
+
+    let now = Utc::now();
+    let secrets = signing_secrets(&state, &source, now)?;
+
+    // Signatures cover the decompressed body.
+    let limit = state.ingest.max_body_bytes;
+    let content_encoding = headers
+        .get(CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok());
+    let read = read_body(body, limit).await.and_then(|raw| {
+        let decoded = decode_body(content_encoding, &raw, limit)?;
+        Ok((raw, decoded))
+    });
+    let (body, decoded) = match read {
+        Ok(decoded) => decoded,
+        Err(err) => {
+            if matches!(err, DecodeError::TooLarge) {
+                count_outcome(&state, &source.provider, IngestOutcome::RejectedSize).await;
+            }
+            return Err(map_decode_error(err, limit));
+        }
+    };
+    let decompressed = decoded.is_some();
+    let body = decoded.map_or(body, Bytes::from);
+
+    if !secrets
+        .iter()
+        .any(|secret| verify_signature(&source.provider, secret, &headers, &body, now))
+    {
+        count_outcome(&state, &source.provider, IngestOutcome::RejectedSignature).await;
+        return Err(ApiError::unauthorized("invalid webhook signature"));
+    }
+
+    // Binary bodies are stored base64-encoded; only text bodies can carry
+    // IDs, event types, or fields to scrub.
+    let text = std::str::from_utf8(&body).ok();
+    let provider_event_id =
+        extract_provider_event_id(&source.provider, &headers, text.unwrap_or_default());
+    let event_type = extract_event_type(&source.provider, &headers, text.unwrap_or_default());
+    let schema = find_payload_schema(&state.pool, &source.provider, event_type.as_deref())
+        .await
+        .map_err(map_store_error)?;
+    let schema_errors = match schema {
+        Some(schema) => {
+            let errors = validate_payload(text.unwrap_or_default(), &schema.schema);
+            if schema.reject_invalid
+                && let Some(first) = errors.first()
+            {
+                count_outcome(&state, &source.provider, IngestOutcome::RejectedSchema).await;
+                return Err(ApiError::validation(format!(
+                    "payload does not match schema: {first}"
+                )));
+            }
+            Some(errors).filter(|errors| !errors.is_empty())
+        }
+        None => None,
+    };
+    let ruleset = match text {
+        Some(_) => find_scrub_ruleset(&state.pool, &source.endpoint_id, &source.provider)
+            .await
+            .map_err(map_store_error)?,
+        None => None,
+    };
+    let (payload, payload_encoding) = match (text, &ruleset) {
+        (Some(text), Some(ruleset)) => (scrub_payload(text, &ruleset.rules), PayloadEncoding::Utf8),
+        (Some(text), None) => (text.to_string(), PayloadEncoding::Utf8),
+        (None, _) => (STANDARD.encode(&body), PayloadEncoding::Base64),
+    };
+    // Metadata is searchable, so it comes from the scrubbed payload: a
+    // redacted field must not survive there.
+    let metadata = extract_metadata(
+        &source.provider,
+        &headers,
+        text.map_or_else(Default::default, |_| payload.as_str()),
+    );
+    let mut inbound_headers = collect_headers(&headers);
+    if decompressed {
+        inbound_headers.remove(CONTENT_ENCODING.as_str());
+    }
+    let (stored_headers, dropped_headers) =
+        filter_headers(inbound_headers, state.ingest.header_allowlist.as_deref());
+    let mut event = NewEvent::from_source(&source, stored_headers, payload, provider_event_id);
+    event.dropped_headers = dropped_headers;
+    if state.ingest.deterministic_event_ids
+        && let Some(provider_event_id) = event.provider_event_id.as_deref()
+    {
+        event.id = deterministic_event_id(&source.endpoint_id, &source.provider, provider_event_id);
+    }
+    event.payload_encoding = payload_encoding;
+    event.event_type = event_type;
+    event.metadata = metadata;
+    event.schema_errors = schema_errors;
+    if let Some(ruleset) = ruleset {
+        event.scrub_ruleset_id = Some(ruleset.id);
+        event.scrub_rule_version = Some(ruleset.version);
+    }
+    let event_id = event.id;
+
+    let payload_bytes = match event.payload_encoding {
+        PayloadEncoding::Utf8 => event.payload.len(),
+        PayloadEncoding::Base64 => body.len(),
+    };
+    if let Some(exceeded) =
+        check_group_quota(&state.pool, &source.endpoint_id, payload_bytes as i64, now)
+            .await
+            .map_err(map_store_error)?
+    {
+        return Err(ApiError::rate_limited(match exceeded {
+            QuotaExceeded::EventsPerDay => "endpoint group is over its daily event quota",
+            QuotaExceeded::StorageBytes => "endpoint group is over its storage quota",
+        }));
+    }
+
+    if source.ingest_mode == IngestMode::FastAck
+        && let Some(queue) = &state.ingest_queue
+    {
+        queue.enqueue(event).await.map_err(|err| match err {
+            EnqueueError::Full => ApiError::rate_limited("ingest queue is full"),
+            EnqueueError::Journal(_) => ApiError::internal("failed to journal event"),
+        })?;
+        count_outcome(&state, &source.provider, IngestOutcome::Accepted).await;
+        return Ok((StatusCode::ACCEPTED, Json(IngestResponse { event_id })));
+    }
+
+    insert_event(&state.pool, &event)
+        .await
+        .map_err(map_store_error)?;
+    count_outcome(&state, &source.provider, IngestOutcome::Accepted).await;
+
+    Ok((StatusCode::OK, Json(IngestResponse { event_id })))
+}
+
+pub async fn ingest_health_handler(
+    State(state): State<AppState>,
+) -> Result<Json<IngestHealth>, ApiError> {
+    let health = ingest_health(&state.pool, &state.ingest)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(health))
+}
+
+/// Lets the system that sent a webhook confirm whether it was delivered.
+/// The caller presents the signing secret of a source feeding the endpoint
+/// as a bearer token and only sees events ingested through that source.
+pub async fn delivery_receipt_handler(
+    State(state): State<AppState>,
+    ValidPath((endpoint_id, provider_event_id)): ValidPath<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<DeliveryReceipt>, ApiError> {
+    let Some(provided) = bearer_token(&headers) else {
+        return Err(ApiError::unauthorized(
+            "missing or invalid Authorization header",
+        ));
+    };
+
+    let sources = find_sources_by_endpoint(&state.pool, &endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    let now = Utc::now();
+    let mut source_ids = Vec::new();
+    for source in &sources {
+        if signing_secrets(&state, source, now)?
+            .iter()
+            .any(|secret| constant_time_eq(secret.as_bytes(), provided.as_bytes()))
+        {
+            source_ids.push(source.id.clone());
+        }
+    }
+    // Unknown endpoints answer like a wrong secret, so the route cannot
+    // be used to probe for endpoint IDs.
+    if source_ids.is_empty() {
+        return Err(ApiError::unauthorized("invalid source secret"));
+    }
+
+    let receipt = find_delivery_receipt(&state.pool, &source_ids, &provider_event_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(receipt))
+}
+
+/// Secrets a signature may verify against: the endpoint's primary secret,
+/// or the source's plaintext one when it has none, then the secondary.
+/// Expired secrets are skipped, as are expiries that do not parse.
+fn signing_secrets(
+    state: &AppState,
+    source: &IngestSource,
+    now: DateTime<Utc>,
+) -> Result<Vec<String>, ApiError> {
+    let active = |expires_at: Option<&str>| {
+        expires_at.is_none_or(|raw| {
+            DateTime::parse_from_rfc3339(raw).is_ok_and(|expires_at| expires_at > now)
+        })
+    };
+    let resolve = |secret_id: &str| {
+        state
+            .secrets
+            .resolve(secret_id)
+            .map_err(|err| ApiError::internal(format!("failed to resolve signing secret: {err}")))
+    };
+
+    let mut secrets = Vec::with_capacity(2);
+    match &source.signing_secret_id {
+        Some(secret_id) if active(source.signing_secret_expires_at.as_deref()) => {
+            secrets.push(resolve(secret_id)?);
+        }
+        Some(_) => {}
+        None => secrets.push(source.secret.clone()),
+    }
+    if let Some(secret_id) = &source.secondary_signing_secret_id
+        && active(source.secondary_signing_secret_expires_at.as_deref())
+    {
+        secrets.push(resolve(secret_id)?);
+    }
+    Ok(secrets)
+}
+
+/// Buffers the body as received, giving up as soon as it exceeds `limit`
+/// rather than after reading all of it.
+async fn read_body(body: Body, limit: usize) -> Result<Bytes, DecodeError> {
+    let mut buffered = Vec::new();
+    let mut chunks = body.into_data_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(|err| DecodeError::Corrupt(std::io::Error::other(err)))?;
+        if buffered.len() + chunk.len() > limit {
+            return Err(DecodeError::TooLarge);
+        }
+        buffered.extend_from_slice(&chunk);
+    }
+    Ok(Bytes::from(buffered))
+}
+
+/// Provider stats are best effort: a failed update never fails ingestion.
+async fn count_outcome(state: &AppState, provider: &str, outcome: IngestOutcome) {
+    let _ = record_ingest_outcome(&state.pool, provider, outcome, Utc::now()).await;
+}
+
+fn collect_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.as_str().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn map_decode_error(err: DecodeError, limit: usize) -> ApiError {
+    match err {
+        DecodeError::Unsupported(encoding) => {
+            ApiError::validation(format!("unsupported content-encoding: {encoding}"))
+        }
+        DecodeError::TooLarge => ApiError::payload_too_large(format!(
+            "payload exceeds {limit} bytes as received or decompressed"
+        )),
+        DecodeError::Corrupt(err) => ApiError::validation(format!("failed to read payload: {err}")),
+    }
+}
+
+fn map_store_error(err: StoreError) -> ApiError {
+    match err {
+        StoreError::Conflict(message) => ApiError::conflict(message),
+        StoreError::Db(db) => ApiError::Db(db),
+        StoreError::NotFound(message) => ApiError::not_found(message),
+        StoreError::Parse(message) => ApiError::internal(message),
+        StoreError::Io(err) => ApiError::internal(format!("io error: {err}")),
+    }
+}
@@ -1,30 +1,241 @@
-use axum::{Json, extract::State};
+use axum::{
+    Json,
+    body::Body,
+    extract::State,
+    http::{
+        HeaderMap, HeaderName, StatusCode,
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+    },
+    response::{
+        IntoResponse, Response,
+        sse::{Event as SseEvent, KeepAlive, Sse},
+    },
+};
 use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
-use chrono::DateTime;
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Duration, NaiveDate, SecondsFormat, Utc};
+use futures_util::{StreamExt, stream};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+use std::convert::Infallible;
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
 use crate::{
+    auth::InspectorActor,
+    dispatcher::{run_selftest, simulate_backoff, simulate_circuit, validate_backoff},
     error::ApiError,
+    export::get_export_status,
     extractors::{ValidJson, ValidPath, ValidQuery},
+    ingest::check_schema,
     inspector::{
-        InspectorCursor, ListEventsParams, StoreError, get_event, list_attempts, list_events,
-        replay_event,
+        AnomalyConfig, BulkReplay, DeadEventTarget, DeadEventWindow, EndpointScope,
+        ExportEventsParams, IdempotencyClaim, InspectorCursor, ListEventsParams,
+        METRICS_CONTENT_TYPE, QueuedEventFilter, ScrubScope, StoreError, UsageParams,
+        attempt_buckets, claim_idempotency_key, clear_fault_injection, close_circuit,
+        complete_idempotency_key, consume_replay_confirmation, count_group_replay,
+        create_endpoint_group, delete_event, detect_anomalies, event_bundle, export_events,
+        find_missing_provider_events, finish_operation, get_attempt_request,
+        get_endpoint_attempt_log_sampling, get_endpoint_backoff, get_endpoint_canary,
+        get_endpoint_connect_policy, get_endpoint_group, get_endpoint_health, get_endpoint_profile,
+        get_endpoint_redirect_policy, get_endpoint_region, get_endpoint_secrets,
+        get_endpoint_shadow, get_endpoint_slo, get_endpoint_timeouts, get_event, get_event_lineage,
+        get_fault_injection, get_group_quota, get_payload_schema, get_scrub_ruleset, group_quotas,
+        issue_replay_confirmation, list_attempts, list_delivery_windows, list_endpoint_groups,
+        list_endpoint_revisions, list_events, list_maintenance_windows, list_operations,
+        list_shadow_attempts, provider_ingest_stats, record_audit, release_idempotency_key,
+        render_anomaly_metrics, render_csv, render_curl, render_export_metrics, render_ndjson,
+        render_quota_metrics, render_slo_metrics, render_tls_metrics, replay_dead_window,
+        replay_event, replay_group, rotate_endpoint_secret, run_doctor, search_customer_events,
+        set_delivery_windows, set_dispatch_paused, set_endpoint_attempt_log_sampling,
+        set_endpoint_backoff, set_endpoint_canary, set_endpoint_check, set_endpoint_connect_policy,
+        set_endpoint_group, set_endpoint_profile, set_endpoint_redirect_policy,
+        set_endpoint_region, set_endpoint_shadow, set_endpoint_slo, set_endpoint_target,
+        set_endpoint_timeouts, set_fault_injection, set_group_paused, set_group_quota,
+        set_group_rate_limit, set_maintenance_windows, set_payload_schema, set_scrub_rules,
+        sign_bundle, slo_stats, start_operation, storage_report, summarize_errors, tls_expiries,
+        undo_operation, usage_rollups, verify_bundle,
+    },
+    jobs::{
+        GroupReplayJob, JobOutcome, StoreError as JobStoreError, cancel_job, create_job,
+        create_reconcile_job, finish_job, get_job, get_reconcile_report, list_jobs,
+        spawn_backfill_job, spawn_bulk_cancel_job, spawn_export_job, spawn_group_replay_job,
+        spawn_import_job, spawn_purge_job, spawn_reprioritize_job,
+    },
+    migrate::{
+        BackfillError, contract_backfill, ensure_backfill_idle, find_backfill, list_backfills,
     },
     state::AppState,
     types::{
-        GetEventResponse, ListAttemptsResponse, ListEventsResponse, ReplayEventRequest,
-        ReplayEventResponse, WebhookEventStatus,
+        AnomaliesResponse, AttemptCurlResponse, CancelEventsRequest, CloseCircuitResponse,
+        ConnectPolicy, CreateEndpointGroupRequest, CustomerSearchResponse, DeleteEventResponse,
+        DeliveryWindowsResponse, DispatchControlResponse, DispatcherSettings, DoctorReport,
+        EndpointAnomaly, EndpointAttemptLogSampling, EndpointBackoff, EndpointCanary,
+        EndpointConnectPolicy, EndpointGroup, EndpointGroupAssignment, EndpointHealth,
+        EndpointProfile, EndpointRedirectPolicy, EndpointRegion, EndpointRevision,
+        EndpointRevisionsResponse, EndpointSecrets, EndpointShadow, EndpointSlo, EndpointTimeouts,
+        ErrorSummaryResponse, EventLineageResponse, EventListField, ExportFormat, FaultInjection,
+        FeatureFlags, GroupQuota, IngestSettings, Job, JobKind, JobStatus, ListBackfillsResponse,
+        ListEndpointGroupsResponse, ListEventsResponse, ListJobsResponse, ListOperationsResponse,
+        ListShadowAttemptsResponse, MaintenanceWindowsResponse, OperationKind, PayloadSchema,
+        ProviderStatsResponse, PurgeEventsRequest, ReconcileReport, ReconcileRequest,
+        ReconcileResponse, RedirectMode, RedirectPolicy, ReplayEventRequest, ReplayEventResponse,
+        ReplayGroupRequest, ReplayGroupResponse, ReprioritizeEventsRequest, RetentionSettings,
+        RotateEndpointSecretRequest, RuntimeConfigResponse, ScheduledExportStatus, SchemaBackfill,
+        ScrubRuleset, SecretSettings, SelftestReport, SetDeliveryWindowsRequest,
+        SetEndpointAttemptLogSamplingRequest, SetEndpointBackoffRequest, SetEndpointCanaryRequest,
+        SetEndpointCheckRequest, SetEndpointGroupRequest, SetEndpointProfileRequest,
+        SetEndpointRegionRequest, SetEndpointShadowRequest, SetEndpointSloRequest,
+        SetEndpointTargetRequest, SetEndpointTimeoutsRequest, SetFaultInjectionRequest,
+        SetGroupQuotaRequest, SetGroupRateLimitRequest, SetMaintenanceWindowsRequest,
+        SetPayloadSchemaRequest, SetScrubRulesRequest, ShareEventRequest, ShareEventResponse,
+        SignedEventBundle, SimulateBackoffResponse, SimulateCircuitResponse, SloStatsResponse,
+        StartReconcileRequest, StorageReport, TlsExpiryResponse, UndoOperationResponse,
+        UsageResponse, VerifyBundleResponse, WebhookEventListItem, WebhookEventStatus,
     },
 };
 
+const MAX_RECONCILE_IDS: usize = 1000;
+const MINUTES_PER_DAY: i64 = 24 * 60;
+const MAX_MAINTENANCE_WINDOWS: usize = 64;
+const MAX_UTC_OFFSET_MINUTES: i64 = 14 * 60;
+const DEFAULT_STUCK_MINUTES: i64 = 15;
+const MAX_SCRUB_RULES: usize = 100;
+const MAX_INJECTED_LATENCY_MS: i64 = 60_000;
+const DEFAULT_SHARE_TTL_SECONDS: i64 = 24 * 60 * 60;
+const JOB_STREAM_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+const DEFAULT_SECRET_OVERLAP_HOURS: i64 = 24;
+const MAX_SHARE_TTL_SECONDS: i64 = 7 * 24 * 60 * 60;
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+const MAX_AUTO_REPLAY: i64 = 1000;
+const DEFAULT_STATS_WINDOW_MINUTES: i64 = 24 * 60;
+const MAX_STATS_WINDOW_MINUTES: i64 = 30 * 24 * 60;
+const MAX_REQUEST_TIMEOUT_MS: i64 = 10 * 60 * 1000;
+const MAX_REDIRECTS: i64 = 20;
+const MAX_HAPPY_EYEBALLS_DELAY_MS: i64 = 10_000;
+const MAX_REGION_LEN: usize = 64;
+const MAX_ENDPOINT_NAME_LEN: usize = 128;
+const MAX_ENDPOINT_DESCRIPTION_LEN: usize = 1024;
+const MAX_EVENT_PRIORITY: i64 = 1000;
+const MAX_SIMULATED_STEPS: u32 = 100;
+const DEFAULT_USAGE_DAYS: i64 = 30;
+const MAX_USAGE_DAYS: i64 = 366;
+const DEFAULT_ANOMALY_BUCKET_MINUTES: i64 = 60;
+const DEFAULT_ANOMALY_BASELINE_BUCKETS: i64 = 24;
+const MIN_ANOMALY_BASELINE_BUCKETS: i64 = 3;
+const MAX_ANOMALY_BASELINE_BUCKETS: i64 = 7 * 24;
+const DEFAULT_ANOMALY_Z_THRESHOLD: f64 = 3.0;
+const DEFAULT_ANOMALY_MIN_ATTEMPTS: i64 = 10;
+
+const MAX_TLS_EXPIRY_WITHIN_DAYS: i64 = 365;
+const MASKED_SECRET: &str = "[REDACTED]";
+const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
 #[derive(Debug, Deserialize)]
 pub struct ListEventsQuery {
     limit: Option<i64>,
     before: Option<String>,
     status: Option<String>,
     endpoint_id: Option<String>,
+    source_id: Option<String>,
+    group_id: Option<String>,
+    provider: Option<String>,
+    stuck: Option<bool>,
+    stuck_minutes: Option<i64>,
+    schema_invalid: Option<bool>,
+    is_replay: Option<bool>,
+    has_replays: Option<bool>,
+    include_last_attempt: Option<bool>,
+    updated_since: Option<String>,
+    worker_version: Option<String>,
+    fields: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CustomerSearchQuery {
+    customer_ref: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportEventsQuery {
+    format: Option<ExportFormat>,
+    status: Option<String>,
+    endpoint_id: Option<String>,
     provider: Option<String>,
+    received_since: Option<String>,
+    received_before: Option<String>,
+}
+
+/// Optional replay of dead events from an outage window, run after an
+/// endpoint becomes deliverable again.
+#[derive(Debug, Deserialize)]
+pub struct AutoReplayQuery {
+    dead_since: Option<String>,
+    dead_before: Option<String>,
+    replay_limit: Option<i64>,
+    drip_rate_per_minute: Option<i64>,
+}
+
+/// How many failed deliveries to simulate the retry schedule for.
+#[derive(Debug, Deserialize)]
+pub struct SimulateBackoffQuery {
+    attempts: Option<u32>,
+}
+
+/// How many consecutive failures to simulate the circuit breaker for.
+#[derive(Debug, Deserialize)]
+pub struct SimulateCircuitQuery {
+    failures: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsWindowQuery {
+    window_minutes: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnomaliesQuery {
+    bucket_minutes: Option<i64>,
+    baseline_buckets: Option<i64>,
+    z_threshold: Option<f64>,
+    min_attempts: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TlsExpiryQuery {
+    within_days: Option<i64>,
+}
+
+/// Inclusive UTC dates, e.g. `2024-01-31`.
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    from: Option<String>,
+    to: Option<String>,
+    endpoint_id: Option<String>,
+    group_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteEventQuery {
+    hard: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ErrorSummaryQuery {
+    window_minutes: Option<i64>,
+    endpoint_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AttemptCurlQuery {
+    reveal: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShareLinkQuery {
+    expires: i64,
+    sig: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,8 +246,10 @@ struct CursorPayload {
 
 pub async fn list_events_handler(
     State(state): State<AppState>,
+    access: EndpointScope,
     ValidQuery(query): ValidQuery<ListEventsQuery>,
-) -> Result<Json<ListEventsResponse>, ApiError> {
+    ValidQuery(pairs): ValidQuery<Vec<(String, String)>>,
+) -> Result<Response, ApiError> {
     let limit = parse_limit(query.limit)?;
     let before = match query.before {
         Some(raw) => Some(decode_cursor(&raw)?),
@@ -50,6 +263,14 @@ pub async fn list_events_handler(
         Some(raw) => Some(parse_uuid("endpoint_id", &raw)?),
         None => None,
     };
+    let source_id = match query.source_id {
+        Some(raw) => Some(parse_uuid("source_id", &raw)?),
+        None => None,
+    };
+    let group_id = match query.group_id {
+        Some(raw) => Some(parse_uuid("group_id", &raw)?),
+        None => None,
+    };
     let provider = match query.provider {
         Some(raw) => {
             let trimmed = raw.trim();
@@ -60,16 +281,59 @@ pub async fn list_events_handler(
         }
         None => None,
     };
+    let stuck_after_minutes = match (query.stuck, query.stuck_minutes) {
+        (Some(true), minutes) => {
+            let minutes = minutes.unwrap_or(DEFAULT_STUCK_MINUTES);
+            if minutes <= 0 {
+                return Err(ApiError::validation("stuck_minutes must be > 0"));
+            }
+            Some(minutes)
+        }
+        (_, Some(_)) => {
+            return Err(ApiError::validation("stuck_minutes requires stuck=true"));
+        }
+        _ => None,
+    };
+    let updated_since = match query.updated_since {
+        Some(raw) => Some(parse_utc_timestamp("updated_since", &raw)?),
+        None => None,
+    };
+    let worker_version = match query.worker_version {
+        Some(raw) => {
+            let trimmed = raw.trim();
+            if trimmed.is_empty() {
+                return Err(ApiError::validation("worker_version must be non-empty"));
+            }
+            Some(trimmed.to_string())
+        }
+        None => None,
+    };
+    let fields = match query.fields {
+        Some(raw) => Some(parse_list_fields(&raw)?),
+        None => None,
+    };
+    let metadata = parse_metadata_filters(pairs)?;
 
     let params = ListEventsParams {
         limit,
         before,
         status,
         endpoint_id,
+        source_id,
+        group_id,
         provider,
+        stuck_after_minutes,
+        schema_invalid: query.schema_invalid,
+        is_replay: query.is_replay,
+        has_replays: query.has_replays,
+        include_last_attempt: query.include_last_attempt.unwrap_or(false),
+        updated_since,
+        worker_version,
+        fields,
+        metadata,
     };
 
-    let result = list_events(&state.pool, &params)
+    let result = list_events(&state.pool, &access, &params)
         .await
         .map_err(map_store_error)?;
     let next_before = match result.next_before {
@@ -77,95 +341,2779 @@ pub async fn list_events_handler(
         None => None,
     };
 
-    Ok(Json(ListEventsResponse {
-        events: result.events,
-        next_before,
+    let Some(fields) = params.fields else {
+        return Ok(Json(ListEventsResponse {
+            events: result.events,
+            next_before,
+        })
+        .into_response());
+    };
+    let events = result
+        .events
+        .iter()
+        .map(|item| project_list_item(item, &fields, params.include_last_attempt))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Json(serde_json::json!({
+        "events": events,
+        "next_before": next_before,
     }))
+    .into_response())
+}
+
+/// Lists webhook activity for one customer across providers, matching the
+/// reference against each provider's customer metadata fields.
+pub async fn search_customer_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidQuery(query): ValidQuery<CustomerSearchQuery>,
+) -> Result<Json<CustomerSearchResponse>, ApiError> {
+    let customer_ref = query
+        .customer_ref
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| ApiError::validation("customer_ref is required"))?;
+    let limit = parse_limit(query.limit)?;
+    let result = search_customer_events(&state.pool, &access, customer_ref, limit)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
 }
 
 pub async fn get_event_handler(
     State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    headers: HeaderMap,
     ValidPath(event_id): ValidPath<String>,
-) -> Result<Json<GetEventResponse>, ApiError> {
+) -> Result<Response, ApiError> {
     let event_id = parse_uuid("event_id", &event_id)?;
-    let result = get_event(&state.pool, event_id)
+    let result = get_event(&state.pool, &access, event_id)
+        .await
+        .map_err(map_store_error)?;
+    audit_read(
+        &state,
+        &actor,
+        "event.read",
+        event_id,
+        Some(result.event.endpoint_id),
+    )
+    .await?;
+    conditional_json(&headers, &result)
+}
+
+pub async fn delete_event_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(event_id): ValidPath<String>,
+    ValidQuery(query): ValidQuery<DeleteEventQuery>,
+) -> Result<Json<DeleteEventResponse>, ApiError> {
+    let event_id = parse_uuid("event_id", &event_id)?;
+    let hard = query.hard.unwrap_or(false);
+    if hard {
+        require_unscoped(&access)?;
+    }
+    let result = delete_event(&state.pool, &access, event_id, hard)
         .await
         .map_err(map_store_error)?;
     Ok(Json(result))
 }
 
+/// Returns matching events, oldest first, as NDJSON or CSV.
+pub async fn export_events_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidQuery(query): ValidQuery<ExportEventsQuery>,
+) -> Result<Response, ApiError> {
+    let (format, params) = parse_export_query(query)?;
+    let records = export_events(&state.pool, &access, &params)
+        .await
+        .map_err(map_store_error)?;
+
+    let (content_type, body) = match format {
+        ExportFormat::Ndjson => (
+            "application/x-ndjson",
+            render_ndjson(&records).map_err(|_| ApiError::internal("failed to encode export"))?,
+        ),
+        ExportFormat::Csv => ("text/csv; charset=utf-8", render_csv(&records)),
+    };
+    Ok(([(CONTENT_TYPE, content_type)], body).into_response())
+}
+
+/// Progress of the nightly export, which covers every endpoint.
+pub async fn scheduled_export_status_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Json<ScheduledExportStatus>, ApiError> {
+    require_unscoped(&access)?;
+    let status = get_export_status(&state.pool)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(status))
+}
+
+/// Starts a background job exporting the same events as
+/// `export_events_handler` to a file under the job directory.
+pub async fn export_job_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidQuery(query): ValidQuery<ExportEventsQuery>,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    require_unscoped(&access)?;
+    let Some(dir) = state.job_dir.clone() else {
+        return Err(ApiError::conflict("background exports are not configured"));
+    };
+    let (format, params) = parse_export_query(query)?;
+    let job = create_job(&state.pool, JobKind::Export, &actor.0, None)
+        .await
+        .map_err(map_job_error)?;
+    spawn_export_job(state.pool.clone(), job.id, dir, format, params);
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+pub async fn list_backfills_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Json<ListBackfillsResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let backfills = list_backfills(&state.pool)
+        .await
+        .map_err(map_backfill_error)?;
+    Ok(Json(ListBackfillsResponse { backfills }))
+}
+
+/// Starts a background job filling a column added by an expand
+/// migration. 409 `backfill_running` while another job is filling it, and
+/// `backfill_completed` once it is done.
+pub async fn start_backfill_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(name): ValidPath<String>,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    require_unscoped(&access)?;
+    let backfill = find_backfill(&name).map_err(map_backfill_error)?;
+    ensure_backfill_idle(&state.pool, backfill)
+        .await
+        .map_err(map_backfill_error)?;
+    let job = create_job(&state.pool, JobKind::Backfill, &actor.0, None)
+        .await
+        .map_err(map_job_error)?;
+    spawn_backfill_job(state.pool.clone(), job.id, backfill);
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Runs the contract step of a finished backfill.
+pub async fn contract_backfill_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(name): ValidPath<String>,
+) -> Result<Json<SchemaBackfill>, ApiError> {
+    require_unscoped(&access)?;
+    let backfill = find_backfill(&name).map_err(map_backfill_error)?;
+    let contracted = contract_backfill(&state.pool, backfill)
+        .await
+        .map_err(map_backfill_error)?;
+    Ok(Json(contracted))
+}
+
+fn parse_export_query(
+    query: ExportEventsQuery,
+) -> Result<(ExportFormat, ExportEventsParams), ApiError> {
+    let status = match query.status {
+        Some(raw) => Some(parse_status(&raw)?),
+        None => None,
+    };
+    let endpoint_id = match query.endpoint_id {
+        Some(raw) => Some(parse_uuid("endpoint_id", &raw)?),
+        None => None,
+    };
+    let provider = match query.provider {
+        Some(raw) => {
+            let trimmed = raw.trim();
+            if trimmed.is_empty() {
+                return Err(ApiError::validation("provider must be non-empty"));
+            }
+            Some(trimmed.to_string())
+        }
+        None => None,
+    };
+    let received_since = match query.received_since {
+        Some(raw) => Some(parse_utc_timestamp("received_since", &raw)?),
+        None => None,
+    };
+    let received_before = match query.received_before {
+        Some(raw) => Some(parse_utc_timestamp("received_before", &raw)?),
+        None => None,
+    };
+
+    let params = ExportEventsParams {
+        status,
+        endpoint_id,
+        provider,
+        received_since,
+        received_before,
+    };
+    Ok((query.format.unwrap_or_default(), params))
+}
+
+pub async fn list_jobs_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Json<ListJobsResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let jobs = list_jobs(&state.pool).await.map_err(map_job_error)?;
+    Ok(Json(ListJobsResponse { jobs }))
+}
+
+pub async fn get_job_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(job_id): ValidPath<String>,
+) -> Result<Json<Job>, ApiError> {
+    require_unscoped(&access)?;
+    let job_id = parse_uuid("job_id", &job_id)?;
+    let job = get_job(&state.pool, job_id).await.map_err(map_job_error)?;
+    Ok(Json(job))
+}
+
+pub async fn cancel_job_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(job_id): ValidPath<String>,
+) -> Result<Json<Job>, ApiError> {
+    require_unscoped(&access)?;
+    let job_id = parse_uuid("job_id", &job_id)?;
+    let job = cancel_job(&state.pool, job_id)
+        .await
+        .map_err(map_job_error)?;
+    Ok(Json(job))
+}
+
+/// Serves the file a completed export job wrote.
+pub async fn job_output_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(job_id): ValidPath<String>,
+) -> Result<Response, ApiError> {
+    require_unscoped(&access)?;
+    let job_id = parse_uuid("job_id", &job_id)?;
+    let job = get_job(&state.pool, job_id).await.map_err(map_job_error)?;
+    let Some(path) = job
+        .output_path
+        .filter(|_| job.status == JobStatus::Completed)
+    else {
+        return Err(ApiError::conflict("job has no output"));
+    };
+    let body = tokio::fs::read(&path)
+        .await
+        .map_err(|_| ApiError::not_found("job output is no longer available"))?;
+    let content_type = if path.ends_with(".csv") {
+        "text/csv; charset=utf-8"
+    } else {
+        "application/x-ndjson"
+    };
+    Ok(([(CONTENT_TYPE, content_type)], body).into_response())
+}
+
+/// Streams a job's progress as server-sent events: a `progress` event
+/// with the job whenever its status or counts change, then a `done` event
+/// once it finishes, after which the stream closes.
+pub async fn job_stream_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(job_id): ValidPath<String>,
+) -> Result<Response, ApiError> {
+    require_unscoped(&access)?;
+    let job_id = parse_uuid("job_id", &job_id)?;
+    let job = get_job(&state.pool, job_id).await.map_err(map_job_error)?;
+    let events = stream::unfold(Some((state.pool, job, None)), move |cursor| async move {
+        let (pool, mut job, last) = cursor?;
+        if let Some(last) = last {
+            loop {
+                tokio::time::sleep(JOB_STREAM_POLL_INTERVAL).await;
+                job = get_job(&pool, job_id).await.ok()?;
+                if job_progress(&job) != last {
+                    break;
+                }
+            }
+        }
+        let finished = !matches!(job.status, JobStatus::Queued | JobStatus::Running);
+        let event = SseEvent::default()
+            .event(if finished { "done" } else { "progress" })
+            .json_data(&job)
+            .ok()?;
+        let next = (!finished).then(|| {
+            let last = job_progress(&job);
+            (pool, job, Some(last))
+        });
+        Some((Ok::<_, Infallible>(event), next))
+    });
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+fn job_progress(job: &Job) -> (JobStatus, i64, i64) {
+    (job.status, job.processed, job.total)
+}
+
 pub async fn list_attempts_handler(
     State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    headers: HeaderMap,
+    ValidPath(event_id): ValidPath<String>,
+) -> Result<Response, ApiError> {
+    let event_id = parse_uuid("event_id", &event_id)?;
+    let result = list_attempts(&state.pool, &access, event_id)
+        .await
+        .map_err(map_store_error)?;
+    audit_read(&state, &actor, "event.attempts.read", event_id, None).await?;
+    conditional_json(&headers, &result)
+}
+
+pub async fn list_shadow_attempts_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
     ValidPath(event_id): ValidPath<String>,
-) -> Result<Json<ListAttemptsResponse>, ApiError> {
+) -> Result<Json<ListShadowAttemptsResponse>, ApiError> {
     let event_id = parse_uuid("event_id", &event_id)?;
-    let result = list_attempts(&state.pool, event_id)
+    let result = list_shadow_attempts(&state.pool, &access, event_id)
         .await
         .map_err(map_store_error)?;
+    audit_read(&state, &actor, "event.shadow_attempts.read", event_id, None).await?;
     Ok(Json(result))
 }
 
-pub async fn replay_event_handler(
+pub async fn event_lineage_handler(
     State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
     ValidPath(event_id): ValidPath<String>,
-    ValidJson(req): ValidJson<ReplayEventRequest>,
-) -> Result<Json<ReplayEventResponse>, ApiError> {
+) -> Result<Json<EventLineageResponse>, ApiError> {
     let event_id = parse_uuid("event_id", &event_id)?;
-    let reset_circuit = req.reset_circuit.unwrap_or(false);
-    let result = replay_event(&state.pool, event_id, reset_circuit)
+    let result = get_event_lineage(&state.pool, &access, event_id)
         .await
         .map_err(map_store_error)?;
+    audit_read(&state, &actor, "event.lineage.read", event_id, None).await?;
     Ok(Json(result))
 }
 
-fn parse_limit(limit: Option<i64>) -> Result<i64, ApiError> {
-    let limit = limit.unwrap_or(50);
-    if !(1..=200).contains(&limit) {
-        return Err(ApiError::validation("limit must be between 1 and 200"));
+/// Starts a background job cancelling the queued events of an endpoint or
+/// group, for consumers decommissioned with a backlog.
+pub async fn cancel_events_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidJson(req): ValidJson<CancelEventsRequest>,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    require_unscoped(&access)?;
+    let filter = parse_queued_event_filter(
+        req.endpoint_id,
+        req.group_id,
+        req.received_since,
+        req.received_before,
+    )?;
+    let job = create_job(&state.pool, JobKind::BulkCancel, &actor.0, None)
+        .await
+        .map_err(map_job_error)?;
+    spawn_bulk_cancel_job(state.pool.clone(), job.id, filter);
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Starts a background job erasing the finished events of an endpoint or
+/// group, as a hard delete of each would.
+pub async fn purge_events_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidJson(req): ValidJson<PurgeEventsRequest>,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    require_unscoped(&access)?;
+    let filter = parse_queued_event_filter(
+        req.endpoint_id,
+        req.group_id,
+        req.received_since,
+        req.received_before,
+    )?;
+    let job = create_job(&state.pool, JobKind::Purge, &actor.0, None)
+        .await
+        .map_err(map_job_error)?;
+    spawn_purge_job(state.pool.clone(), job.id, filter);
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Starts a background job restoring the snapshot in the request body, as
+/// `receiver restore` does. The upload is kept under the job directory
+/// until the job ends.
+pub async fn import_snapshot_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    body: Body,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    require_unscoped(&access)?;
+    let Some(dir) = state.job_dir.clone() else {
+        return Err(ApiError::conflict("background imports are not configured"));
+    };
+    let job = create_job(&state.pool, JobKind::Import, &actor.0, None)
+        .await
+        .map_err(map_job_error)?;
+    let path = dir.join(format!("{}.snapshot", job.id));
+    if let Err(message) = save_upload(body, &dir, &path).await {
+        let _ = tokio::fs::remove_file(&path).await;
+        let outcome = JobOutcome::Failed(message.clone());
+        let _ = finish_job(&state.pool, job.id, &outcome).await;
+        return Err(ApiError::validation(message));
     }
-    Ok(limit)
+    spawn_import_job(state.pool.clone(), job.id, path);
+    Ok((StatusCode::ACCEPTED, Json(job)))
 }
 
-fn parse_uuid(field: &str, value: &str) -> Result<Uuid, ApiError> {
-    Uuid::parse_str(value).map_err(|_| ApiError::validation(format!("{field} must be a UUID")))
+async fn save_upload(body: Body, dir: &Path, path: &Path) -> Result<(), String> {
+    let write_error = |err: std::io::Error| format!("failed to save the upload: {err}");
+    tokio::fs::create_dir_all(dir).await.map_err(write_error)?;
+    let mut file = tokio::fs::File::create(path).await.map_err(write_error)?;
+    let mut chunks = body.into_data_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(|err| format!("failed to read the upload: {err}"))?;
+        file.write_all(&chunk).await.map_err(write_error)?;
+    }
+    file.flush().await.map_err(write_error)
 }
 
-fn parse_status(value: &str) -> Result<WebhookEventStatus, ApiError> {
-    match value {
-        "pending" => Ok(WebhookEventStatus::Pending),
-        "in_flight" => Ok(WebhookEventStatus::InFlight),
-        "requeued" => Ok(WebhookEventStatus::Requeued),
-        "delivered" => Ok(WebhookEventStatus::Delivered),
-        "dead" => Ok(WebhookEventStatus::Dead),
-        "paused" => Ok(WebhookEventStatus::Paused),
-        _ => Err(ApiError::validation("status is invalid")),
+/// Starts a background job moving the queued events matching the request
+/// to a new priority, to reorder a backlog during recovery.
+pub async fn reprioritize_events_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidJson(req): ValidJson<ReprioritizeEventsRequest>,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    require_unscoped(&access)?;
+    if !(-MAX_EVENT_PRIORITY..=MAX_EVENT_PRIORITY).contains(&req.priority) {
+        return Err(ApiError::validation(format!(
+            "priority must be between -{MAX_EVENT_PRIORITY} and {MAX_EVENT_PRIORITY}"
+        )));
     }
+    let mut filter = parse_queued_event_filter(
+        req.endpoint_id,
+        req.group_id,
+        req.received_since,
+        req.received_before,
+    )?;
+    filter.provider = req.provider.map(|provider| provider.trim().to_string());
+    filter.event_type = req
+        .event_type
+        .map(|event_type| event_type.trim().to_string());
+    if filter.event_type.as_deref() == Some("") {
+        return Err(ApiError::validation("event_type must be non-empty"));
+    }
+    let job = create_job(&state.pool, JobKind::Reprioritize, &actor.0, None)
+        .await
+        .map_err(map_job_error)?;
+    spawn_reprioritize_job(state.pool.clone(), job.id, filter, req.priority);
+    Ok((StatusCode::ACCEPTED, Json(job)))
 }
 
-fn decode_cursor(raw: &str) -> Result<InspectorCursor, ApiError> {
-    let decoded = URL_SAFE_NO_PAD
-        .decode(raw)
-        .map_err(|_| ApiError::validation("before must be a valid cursor"))?;
-    let payload: CursorPayload = serde_json::from_slice(&decoded)
-        .map_err(|_| ApiError::validation("before must be a valid cursor"))?;
-    DateTime::parse_from_rfc3339(&payload.received_at)
-        .map_err(|_| ApiError::validation("before must be a valid cursor"))?;
-    let id = Uuid::parse_str(&payload.id)
-        .map_err(|_| ApiError::validation("before must be a valid cursor"))?;
-    Ok(InspectorCursor {
-        received_at: payload.received_at,
-        id,
+/// Requires exactly one of `endpoint_id` and `group_id`. The window ends no
+/// later than now, so events received once the job runs are left alone.
+fn parse_queued_event_filter(
+    endpoint_id: Option<Uuid>,
+    group_id: Option<Uuid>,
+    received_since: Option<String>,
+    received_before: Option<String>,
+) -> Result<QueuedEventFilter, ApiError> {
+    let target = match (endpoint_id, group_id) {
+        (Some(endpoint_id), None) => DeadEventTarget::Endpoint(endpoint_id),
+        (None, Some(group_id)) => DeadEventTarget::Group(group_id),
+        _ => {
+            return Err(ApiError::validation(
+                "exactly one of endpoint_id and group_id is required",
+            ));
+        }
+    };
+    let received_since = match received_since {
+        Some(raw) => Some(parse_utc_timestamp("received_since", &raw)?),
+        None => None,
+    };
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let received_before = match received_before {
+        Some(raw) => parse_utc_timestamp("received_before", &raw)?.min(now),
+        None => now,
+    };
+    Ok(QueuedEventFilter {
+        target,
+        provider: None,
+        event_type: None,
+        received_since,
+        received_before: Some(received_before),
     })
 }
 
-fn encode_cursor(cursor: &InspectorCursor) -> Result<String, ApiError> {
-    let payload = CursorPayload {
-        received_at: cursor.received_at.clone(),
-        id: cursor.id.to_string(),
+pub async fn replay_event_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    headers: HeaderMap,
+    ValidPath(event_id): ValidPath<String>,
+    ValidJson(req): ValidJson<ReplayEventRequest>,
+) -> Result<Json<ReplayEventResponse>, ApiError> {
+    let event_id = parse_uuid("event_id", &event_id)?;
+    let reset_circuit = req.reset_circuit.unwrap_or(false);
+    let skip_if_pending = req.skip_if_pending.unwrap_or(false);
+    let attempt_budget = req.attempt_budget.unwrap_or_default();
+    let loop_limit = if req.force.unwrap_or(false) {
+        None
+    } else {
+        state.dispatcher.replay_loop_limit
     };
-    let encoded =
-        serde_json::to_vec(&payload).map_err(|_| ApiError::internal("failed to encode cursor"))?;
-    Ok(URL_SAFE_NO_PAD.encode(encoded))
+    let operation = format!("event.replay:{event_id}");
+    idempotent(&state, &actor, &headers, &operation, &req, || async {
+        replay_event(
+            &state.pool,
+            &access,
+            event_id,
+            reset_circuit,
+            skip_if_pending,
+            attempt_budget,
+            loop_limit,
+        )
+        .await
+        .map_err(map_store_error)
+    })
+    .await
+}
+
+/// Renders an attempt's recorded request as a curl command. Credential and
+/// signature headers are masked unless an unscoped token asks for
+/// `reveal=true`.
+pub async fn attempt_curl_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(attempt_id): ValidPath<String>,
+    ValidQuery(query): ValidQuery<AttemptCurlQuery>,
+) -> Result<Json<AttemptCurlResponse>, ApiError> {
+    let attempt_id = parse_uuid("attempt_id", &attempt_id)?;
+    let reveal = query.reveal.unwrap_or(false);
+    if reveal {
+        require_unscoped(&access)?;
+    }
+    let request = get_attempt_request(&state.pool, &access, attempt_id)
+        .await
+        .map_err(map_store_error)?;
+    audit_read(
+        &state,
+        &actor,
+        "attempt.curl.read",
+        request.event_id,
+        Some(request.endpoint_id),
+    )
+    .await?;
+
+    let curl = render_curl(
+        &request.target_url,
+        &request.request_headers,
+        &request.request_body,
+        reveal,
+    );
+    Ok(Json(AttemptCurlResponse {
+        attempt_id,
+        event_id: request.event_id,
+        target_url: request.target_url,
+        command: curl.command,
+        masked_headers: curl.masked_headers,
+    }))
+}
+
+/// Mints a signed link that reads this event and its attempts without a
+/// token until it expires.
+pub async fn share_event_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(event_id): ValidPath<String>,
+    ValidJson(req): ValidJson<ShareEventRequest>,
+) -> Result<Json<ShareEventResponse>, ApiError> {
+    let Some(share_links) = &state.share_links else {
+        return Err(ApiError::conflict("share links are disabled"));
+    };
+    let event_id = parse_uuid("event_id", &event_id)?;
+    let ttl_seconds = req.ttl_seconds.unwrap_or(DEFAULT_SHARE_TTL_SECONDS);
+    if !(1..=MAX_SHARE_TTL_SECONDS).contains(&ttl_seconds) {
+        return Err(ApiError::validation(format!(
+            "ttl_seconds must be between 1 and {MAX_SHARE_TTL_SECONDS}"
+        )));
+    }
+    get_event(&state.pool, &access, event_id)
+        .await
+        .map_err(map_store_error)?;
+
+    let expires_at = Utc::now() + Duration::seconds(ttl_seconds);
+    let url = share_links
+        .share_url(event_id, expires_at.timestamp())
+        .ok_or_else(|| ApiError::internal("failed to sign share link"))?;
+    Ok(Json(ShareEventResponse {
+        url,
+        expires_at: expires_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+    }))
+}
+
+pub async fn event_bundle_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(event_id): ValidPath<String>,
+) -> Result<Response, ApiError> {
+    let Some(key) = &state.bundle_signing_key else {
+        return Err(ApiError::conflict("bundle signing is disabled"));
+    };
+    let event_id = parse_uuid("event_id", &event_id)?;
+    let bundle = event_bundle(&state.pool, &access, event_id)
+        .await
+        .map_err(map_store_error)?;
+    audit_read(
+        &state,
+        &actor,
+        "event.bundle.read",
+        event_id,
+        Some(bundle.event.endpoint_id),
+    )
+    .await?;
+    let signature =
+        sign_bundle(key, &bundle).ok_or_else(|| ApiError::internal("failed to sign bundle"))?;
+    let disposition = format!("attachment; filename=\"event-{event_id}-bundle.json\"");
+    Ok((
+        [(CONTENT_DISPOSITION, disposition)],
+        Json(SignedEventBundle { bundle, signature }),
+    )
+        .into_response())
+}
+
+/// Checks a bundle against its signature. Any edit to the bundle after it
+/// was produced fails verification.
+pub async fn verify_bundle_handler(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<SignedEventBundle>,
+) -> Result<Json<VerifyBundleResponse>, ApiError> {
+    let Some(key) = &state.bundle_signing_key else {
+        return Err(ApiError::conflict("bundle signing is disabled"));
+    };
+    Ok(Json(VerifyBundleResponse {
+        valid: verify_bundle(key, &req.bundle, &req.signature),
+    }))
+}
+
+pub async fn shared_event_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ValidPath(event_id): ValidPath<String>,
+    ValidQuery(query): ValidQuery<ShareLinkQuery>,
+) -> Result<Response, ApiError> {
+    let event_id = verify_share_link(&state, &event_id, &query)?;
+    let result = get_event(&state.pool, &EndpointScope::All, event_id)
+        .await
+        .map_err(map_store_error)?;
+    audit_read(
+        &state,
+        &InspectorActor::share_link(),
+        "event.read",
+        event_id,
+        Some(result.event.endpoint_id),
+    )
+    .await?;
+    conditional_json(&headers, &result)
+}
+
+pub async fn shared_attempts_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ValidPath(event_id): ValidPath<String>,
+    ValidQuery(query): ValidQuery<ShareLinkQuery>,
+) -> Result<Response, ApiError> {
+    let event_id = verify_share_link(&state, &event_id, &query)?;
+    let result = list_attempts(&state.pool, &EndpointScope::All, event_id)
+        .await
+        .map_err(map_store_error)?;
+    audit_read(
+        &state,
+        &InspectorActor::share_link(),
+        "event.attempts.read",
+        event_id,
+        None,
+    )
+    .await?;
+    conditional_json(&headers, &result)
+}
+
+/// Audits a payload read when `audit_reads` is on. A failed audit write
+/// fails the request so no read goes unrecorded.
+async fn audit_read(
+    state: &AppState,
+    actor: &InspectorActor,
+    action: &str,
+    event_id: Uuid,
+    endpoint_id: Option<Uuid>,
+) -> Result<(), ApiError> {
+    if !state.audit_reads {
+        return Ok(());
+    }
+    record_audit(&state.pool, &actor.0, action, Some(event_id), endpoint_id)
+        .await
+        .map_err(map_store_error)
+}
+
+/// Runs `mutation` once per `Idempotency-Key` and actor, answering retries
+/// with the stored response. `operation` and `request` identify the
+/// request, so a key reused for anything else is refused. Without the
+/// header the mutation simply runs.
+async fn idempotent<T, F, Fut>(
+    state: &AppState,
+    actor: &InspectorActor,
+    headers: &HeaderMap,
+    operation: &str,
+    request: &impl Serialize,
+    mutation: F,
+) -> Result<Json<T>, ApiError>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, ApiError>>,
+{
+    let Some(key) = headers.get(IDEMPOTENCY_KEY) else {
+        return mutation().await.map(Json);
+    };
+    let key = key
+        .to_str()
+        .ok()
+        .filter(|key| !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN)
+        .ok_or_else(|| {
+            ApiError::validation(format!(
+                "Idempotency-Key must be 1 to {MAX_IDEMPOTENCY_KEY_LEN} visible ASCII characters"
+            ))
+        })?;
+
+    let request_hash = request_hash(operation, request)?;
+    let claim = claim_idempotency_key(&state.pool, &actor.0, key, &request_hash)
+        .await
+        .map_err(map_store_error)?;
+    if let IdempotencyClaim::Completed(response) = claim {
+        return serde_json::from_str(&response)
+            .map(Json)
+            .map_err(|_| ApiError::internal("stored idempotent response is invalid"));
+    }
+
+    match mutation().await {
+        Ok(result) => {
+            let response = serde_json::to_string(&result)
+                .map_err(|_| ApiError::internal("failed to encode response"))?;
+            complete_idempotency_key(&state.pool, &actor.0, key, &response)
+                .await
+                .map_err(map_store_error)?;
+            Ok(Json(result))
+        }
+        Err(err) => {
+            release_idempotency_key(&state.pool, &actor.0, key)
+                .await
+                .map_err(map_store_error)?;
+            Err(err)
+        }
+    }
+}
+
+/// SHA-256 of `operation` and the JSON encoding of `request`.
+fn request_hash(operation: &str, request: &impl Serialize) -> Result<String, ApiError> {
+    let body =
+        serde_json::to_vec(request).map_err(|_| ApiError::internal("failed to encode request"))?;
+    let mut hasher = Sha256::new();
+    hasher.update(operation.as_bytes());
+    hasher.update([0]);
+    hasher.update(&body);
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Sends `value` as JSON with a weak ETag over its bytes, or an empty 304
+/// when the client's `If-None-Match` already holds that ETag.
+fn conditional_json(headers: &HeaderMap, value: &impl Serialize) -> Result<Response, ApiError> {
+    let body =
+        serde_json::to_vec(value).map_err(|_| ApiError::internal("failed to encode response"))?;
+    let etag = format!("W/\"{}\"", &hex::encode(Sha256::digest(&body))[..32]);
+    let not_modified = headers
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| etag_matches(value, &etag));
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, [(ETAG, etag)]).into_response());
+    }
+    Ok((
+        [(CONTENT_TYPE, "application/json".to_string()), (ETAG, etag)],
+        body,
+    )
+        .into_response())
+}
+
+/// Weak comparison, as `If-None-Match` requires.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    if_none_match
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == opaque(etag))
+}
+
+fn verify_share_link(
+    state: &AppState,
+    event_id: &str,
+    query: &ShareLinkQuery,
+) -> Result<Uuid, ApiError> {
+    let event_id = parse_uuid("event_id", event_id)?;
+    let valid = state.share_links.as_ref().is_some_and(|share_links| {
+        share_links.verify(event_id, query.expires, &query.sig, Utc::now())
+    });
+    if !valid {
+        return Err(ApiError::unauthorized("invalid or expired share link"));
+    }
+    Ok(event_id)
+}
+
+pub async fn reconcile_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidJson(req): ValidJson<ReconcileRequest>,
+) -> Result<Json<ReconcileResponse>, ApiError> {
+    let provider = req.provider.trim();
+    if provider.is_empty() {
+        return Err(ApiError::validation("provider must be non-empty"));
+    }
+    if req.provider_event_ids.len() > MAX_RECONCILE_IDS {
+        return Err(ApiError::validation(format!(
+            "provider_event_ids must contain at most {MAX_RECONCILE_IDS} entries"
+        )));
+    }
+
+    let missing =
+        find_missing_provider_events(&state.pool, &access, provider, &req.provider_event_ids)
+            .await
+            .map_err(map_store_error)?;
+
+    Ok(Json(ReconcileResponse {
+        checked: req.provider_event_ids.len() as i64,
+        missing,
+    }))
+}
+
+/// Queues a reconciliation of a Stripe or GitHub source over a window, for
+/// a worker to run against the provider's API. 409 for sources of other
+/// providers.
+pub async fn start_reconcile_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidJson(mut req): ValidJson<StartReconcileRequest>,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    require_unscoped(&access)?;
+    req.since = parse_utc_timestamp("since", &req.since)?;
+    req.until = parse_utc_timestamp("until", &req.until)?;
+    if req.until <= req.since {
+        return Err(ApiError::validation("until must be after since"));
+    }
+
+    let job = create_reconcile_job(&state.pool, &actor.0, &req)
+        .await
+        .map_err(map_job_error)?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+pub async fn get_reconcile_report_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(job_id): ValidPath<String>,
+) -> Result<Json<ReconcileReport>, ApiError> {
+    require_unscoped(&access)?;
+    let job_id = parse_uuid("job_id", &job_id)?;
+    let report = get_reconcile_report(&state.pool, job_id)
+        .await
+        .map_err(map_job_error)?;
+    Ok(Json(report))
+}
+
+pub async fn pause_dispatch_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Json<DispatchControlResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let result = set_dispatch_paused(&state.pool, true)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+/// Retry schedule the dispatcher config gives an event failing its first
+/// `attempts` deliveries, defaulting to `max_attempts`.
+pub async fn simulate_backoff_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidQuery(query): ValidQuery<SimulateBackoffQuery>,
+) -> Result<Json<SimulateBackoffResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let max_attempts = state.dispatcher.max_attempts;
+    let attempts = query.attempts.unwrap_or(max_attempts);
+    if !(1..=MAX_SIMULATED_STEPS).contains(&attempts) {
+        return Err(ApiError::validation(format!(
+            "attempts must be between 1 and {MAX_SIMULATED_STEPS}"
+        )));
+    }
+    Ok(Json(SimulateBackoffResponse {
+        max_attempts,
+        steps: simulate_backoff(&state.dispatcher, attempts),
+    }))
+}
+
+/// Circuit states the dispatcher config gives an endpoint after each of
+/// `failures` consecutive failures, defaulting to a few past the
+/// threshold.
+pub async fn simulate_circuit_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidQuery(query): ValidQuery<SimulateCircuitQuery>,
+) -> Result<Json<SimulateCircuitResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let threshold = state.dispatcher.circuit_failure_threshold;
+    let failures = query
+        .failures
+        .unwrap_or_else(|| threshold.saturating_add(4).min(MAX_SIMULATED_STEPS));
+    if !(1..=MAX_SIMULATED_STEPS).contains(&failures) {
+        return Err(ApiError::validation(format!(
+            "failures must be between 1 and {MAX_SIMULATED_STEPS}"
+        )));
+    }
+    Ok(Json(SimulateCircuitResponse {
+        circuit_failure_threshold: threshold,
+        steps: simulate_circuit(&state.dispatcher, failures),
+    }))
+}
+
+/// Effective dispatcher, ingest and retention settings of this process,
+/// with credentials masked.
+pub async fn runtime_config_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Json<RuntimeConfigResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let dispatcher = &state.dispatcher;
+    let ingest = &state.ingest;
+    let masked = |secret: Option<&String>| secret.map(|_| MASKED_SECRET.to_string());
+    Ok(Json(RuntimeConfigResponse {
+        dispatcher: DispatcherSettings {
+            circuit_failure_threshold: dispatcher.circuit_failure_threshold,
+            circuit_cooldown_base_ms: dispatcher.circuit_cooldown_base_ms,
+            circuit_cooldown_factor: dispatcher.circuit_cooldown_factor,
+            circuit_cooldown_max_ms: dispatcher.circuit_cooldown_max_ms,
+            max_attempts: dispatcher.max_attempts,
+            expiry_sweep_interval_ms: dispatcher.expiry_sweep_interval_ms,
+            starvation_max_wait_ms: dispatcher.starvation_max_wait_ms,
+            success_response_sample_percent: dispatcher.success_response_sample_percent,
+            default_request_timeout_ms: dispatcher.default_request_timeout_ms,
+            endpoint_check_interval_ms: dispatcher.endpoint_check_interval_ms,
+            tls_expiry_warning_days: dispatcher.tls_expiry_warning_days,
+            region_fallback_ms: dispatcher.region_fallback_ms,
+            replay_confirm_threshold: dispatcher.replay_confirm_threshold,
+            replay_confirm_ttl_minutes: dispatcher.replay_confirm_ttl_minutes,
+            replay_loop_limit: dispatcher.replay_loop_limit,
+        },
+        ingest: IngestSettings {
+            queue_capacity: ingest.queue_capacity,
+            journal_path: ingest
+                .journal_path
+                .as_ref()
+                .map(|path| path.display().to_string()),
+            max_pending_events: ingest.max_pending_events,
+            max_db_bytes: ingest.max_db_bytes,
+            backpressure_retry_after_secs: ingest.backpressure_retry_after_secs,
+            deterministic_event_ids: ingest.deterministic_event_ids,
+            header_allowlist: ingest.header_allowlist.clone(),
+            max_body_bytes: ingest.max_body_bytes,
+        },
+        retention: RetentionSettings {
+            attempt_log_hot_days: dispatcher.attempt_log_hot_days,
+            attempt_log_archive_interval_ms: dispatcher.attempt_log_archive_interval_ms,
+        },
+        features: FeatureFlags {
+            fault_injection: dispatcher.fault_injection_enabled,
+            share_links: state.share_links.is_some(),
+            bundle_signing: state.bundle_signing_key.is_some(),
+            audit_reads: state.audit_reads,
+            ingest_queue: state.ingest_queue.is_some(),
+            background_exports: state.job_dir.is_some(),
+        },
+        secrets: SecretSettings {
+            inspector_api_token: masked(state.inspector_api_token.as_ref()),
+            share_link_secret: masked(state.share_links.as_ref().map(|links| &links.secret)),
+            share_link_base_url: state
+                .share_links
+                .as_ref()
+                .and_then(|links| links.base_url.clone()),
+            bundle_signing_key: masked(state.bundle_signing_key.as_ref()),
+            scoped_token_endpoints: state
+                .inspector_scoped_tokens
+                .iter()
+                .map(|token| token.endpoint_ids.clone())
+                .collect(),
+        },
+    }))
+}
+
+pub async fn resume_dispatch_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Json<DispatchControlResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let result = set_dispatch_paused(&state.pool, false)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn list_maintenance_windows_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<MaintenanceWindowsResponse>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = list_maintenance_windows(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_maintenance_windows_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetMaintenanceWindowsRequest>,
+) -> Result<Json<MaintenanceWindowsResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    if req.windows.len() > MAX_MAINTENANCE_WINDOWS {
+        return Err(ApiError::validation(format!(
+            "windows must contain at most {MAX_MAINTENANCE_WINDOWS} entries"
+        )));
+    }
+    for window in &req.windows {
+        validate_recurring_window(
+            window.day_of_week,
+            window.start_minute,
+            window.duration_minutes,
+        )?;
+    }
+    let result = set_maintenance_windows(&state.pool, &access, endpoint_id, &req.windows)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn list_delivery_windows_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<DeliveryWindowsResponse>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = list_delivery_windows(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_delivery_windows_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetDeliveryWindowsRequest>,
+) -> Result<Json<DeliveryWindowsResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    if !(-MAX_UTC_OFFSET_MINUTES..=MAX_UTC_OFFSET_MINUTES).contains(&req.utc_offset_minutes) {
+        return Err(ApiError::validation(format!(
+            "utc_offset_minutes must be between -{MAX_UTC_OFFSET_MINUTES} and \
+             {MAX_UTC_OFFSET_MINUTES}"
+        )));
+    }
+    if req.windows.len() > MAX_MAINTENANCE_WINDOWS {
+        return Err(ApiError::validation(format!(
+            "windows must contain at most {MAX_MAINTENANCE_WINDOWS} entries"
+        )));
+    }
+    for window in &req.windows {
+        validate_recurring_window(
+            window.day_of_week,
+            window.start_minute,
+            window.duration_minutes,
+        )?;
+    }
+    let result = set_delivery_windows(
+        &state.pool,
+        &access,
+        endpoint_id,
+        req.utc_offset_minutes,
+        &req.windows,
+    )
+    .await
+    .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_target_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetEndpointTargetRequest>,
+) -> Result<Json<EndpointRevision>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let target_url = req.target_url.trim();
+    if !target_url.contains("://") {
+        return Err(ApiError::validation("target_url must be an absolute URL"));
+    }
+    let scheme = target_url
+        .split_once("://")
+        .map_or("", |(scheme, _)| scheme)
+        .to_ascii_lowercase();
+    if scheme == "amqp" || scheme == "amqps" {
+        return Err(ApiError::validation("AMQP targets are not supported"));
+    }
+    let revision = set_endpoint_target(&state.pool, &access, endpoint_id, target_url, &actor.0)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(revision))
+}
+
+pub async fn list_endpoint_revisions_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointRevisionsResponse>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = list_endpoint_revisions(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_endpoint_canary_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointCanary>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_canary(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_canary_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetEndpointCanaryRequest>,
+) -> Result<Json<EndpointCanary>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    if !(0..=100).contains(&req.canary_percent) {
+        return Err(ApiError::validation(
+            "canary_percent must be between 0 and 100",
+        ));
+    }
+    let canary_target_url = req.canary_target_url.as_deref().map(str::trim);
+    if canary_target_url.is_some_and(|url| !url.contains("://")) {
+        return Err(ApiError::validation(
+            "canary_target_url must be an absolute URL",
+        ));
+    }
+    let result = set_endpoint_canary(
+        &state.pool,
+        &access,
+        endpoint_id,
+        canary_target_url,
+        req.canary_percent,
+    )
+    .await
+    .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_endpoint_profile_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointProfile>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_profile(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_profile_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetEndpointProfileRequest>,
+) -> Result<Json<EndpointProfile>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let name = req.name.as_deref().map(str::trim);
+    if name.is_some_and(|name| name.is_empty() || name.len() > MAX_ENDPOINT_NAME_LEN) {
+        return Err(ApiError::validation(format!(
+            "name must be 1 to {MAX_ENDPOINT_NAME_LEN} characters"
+        )));
+    }
+    let description = req.description.as_deref().map(str::trim);
+    if description.is_some_and(|description| {
+        description.is_empty() || description.len() > MAX_ENDPOINT_DESCRIPTION_LEN
+    }) {
+        return Err(ApiError::validation(format!(
+            "description must be 1 to {MAX_ENDPOINT_DESCRIPTION_LEN} characters"
+        )));
+    }
+    let result = set_endpoint_profile(&state.pool, &access, endpoint_id, name, description)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_endpoint_shadow_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointShadow>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_shadow(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_shadow_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetEndpointShadowRequest>,
+) -> Result<Json<EndpointShadow>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let shadow_target_url = req.shadow_target_url.as_deref().map(str::trim);
+    if shadow_target_url.is_some_and(|url| !url.contains("://")) {
+        return Err(ApiError::validation(
+            "shadow_target_url must be an absolute URL",
+        ));
+    }
+    let result = set_endpoint_shadow(&state.pool, &access, endpoint_id, shadow_target_url)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_endpoint_slo_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointSlo>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_slo(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_slo_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetEndpointSloRequest>,
+) -> Result<Json<EndpointSlo>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let slo = match (req.target_seconds, req.objective_percent) {
+        (Some(target_seconds), Some(objective_percent)) => {
+            if target_seconds <= 0 {
+                return Err(ApiError::validation("target_seconds must be > 0"));
+            }
+            if !(objective_percent > 0.0 && objective_percent <= 100.0) {
+                return Err(ApiError::validation(
+                    "objective_percent must be > 0 and <= 100",
+                ));
+            }
+            Some((target_seconds, objective_percent))
+        }
+        (None, None) => None,
+        _ => {
+            return Err(ApiError::validation(
+                "target_seconds and objective_percent must be set together",
+            ));
+        }
+    };
+    let result = set_endpoint_slo(&state.pool, &access, endpoint_id, slo)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_endpoint_attempt_log_sampling_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointAttemptLogSampling>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_attempt_log_sampling(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_attempt_log_sampling_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetEndpointAttemptLogSamplingRequest>,
+) -> Result<Json<EndpointAttemptLogSampling>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    if req.success_sample_rate.is_some_and(|rate| rate < 1) {
+        return Err(ApiError::validation("success_sample_rate must be >= 1"));
+    }
+    // A rate of 1 logs every attempt, the same as no sampling.
+    let success_sample_rate = req.success_sample_rate.filter(|rate| *rate > 1);
+    let result =
+        set_endpoint_attempt_log_sampling(&state.pool, &access, endpoint_id, success_sample_rate)
+            .await
+            .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_endpoint_timeouts_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointTimeouts>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_timeouts(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_timeouts_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetEndpointTimeoutsRequest>,
+) -> Result<Json<EndpointTimeouts>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    if req
+        .request_timeout_ms
+        .is_some_and(|timeout_ms| !(1..=MAX_REQUEST_TIMEOUT_MS).contains(&timeout_ms))
+    {
+        return Err(ApiError::validation(format!(
+            "request_timeout_ms must be between 1 and {MAX_REQUEST_TIMEOUT_MS}"
+        )));
+    }
+    if req
+        .delivery_budget_seconds
+        .is_some_and(|seconds| seconds <= 0)
+    {
+        return Err(ApiError::validation("delivery_budget_seconds must be > 0"));
+    }
+    let result = set_endpoint_timeouts(
+        &state.pool,
+        &access,
+        endpoint_id,
+        req.request_timeout_ms,
+        req.delivery_budget_seconds,
+        &actor.0,
+    )
+    .await
+    .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_endpoint_backoff_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointBackoff>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_backoff(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_backoff_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetEndpointBackoffRequest>,
+) -> Result<Json<EndpointBackoff>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    if let Some(strategy) = &req.strategy {
+        validate_backoff(strategy).map_err(ApiError::validation)?;
+    }
+    let result = set_endpoint_backoff(
+        &state.pool,
+        &access,
+        endpoint_id,
+        req.strategy.as_ref(),
+        &actor.0,
+    )
+    .await
+    .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_endpoint_redirect_policy_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointRedirectPolicy>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_redirect_policy(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_redirect_policy_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(policy): ValidJson<RedirectPolicy>,
+) -> Result<Json<EndpointRedirectPolicy>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let min_redirects = i64::from(policy.mode != RedirectMode::None);
+    if !(min_redirects..=MAX_REDIRECTS).contains(&policy.max_redirects) {
+        return Err(ApiError::validation(format!(
+            "max_redirects must be between {min_redirects} and {MAX_REDIRECTS}"
+        )));
+    }
+    let result = set_endpoint_redirect_policy(&state.pool, &access, endpoint_id, policy, &actor.0)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_endpoint_region_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointRegion>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_region(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_region_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetEndpointRegionRequest>,
+) -> Result<Json<EndpointRegion>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let region = req.region.as_deref().map(str::trim);
+    if region.is_some_and(|region| region.is_empty() || region.len() > MAX_REGION_LEN) {
+        return Err(ApiError::validation(format!(
+            "region must be 1 to {MAX_REGION_LEN} characters"
+        )));
+    }
+    let result = set_endpoint_region(
+        &state.pool,
+        &access,
+        endpoint_id,
+        region,
+        req.mode,
+        &actor.0,
+    )
+    .await
+    .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_endpoint_connect_policy_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointConnectPolicy>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_connect_policy(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_connect_policy_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(policy): ValidJson<ConnectPolicy>,
+) -> Result<Json<EndpointConnectPolicy>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    if policy
+        .happy_eyeballs_delay_ms
+        .is_some_and(|delay| !(1..=MAX_HAPPY_EYEBALLS_DELAY_MS).contains(&delay))
+    {
+        return Err(ApiError::validation(format!(
+            "happy_eyeballs_delay_ms must be between 1 and {MAX_HAPPY_EYEBALLS_DELAY_MS}"
+        )));
+    }
+    let result = set_endpoint_connect_policy(&state.pool, &access, endpoint_id, policy, &actor.0)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_endpoint_health_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointHealth>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_health(
+        &state.pool,
+        &access,
+        endpoint_id,
+        state.dispatcher.tls_expiry_warning_days,
+    )
+    .await
+    .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_endpoint_check_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetEndpointCheckRequest>,
+) -> Result<Json<EndpointHealth>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let url = req.url.as_deref().map(str::trim);
+    if url.is_some_and(|url| !url.starts_with("http://") && !url.starts_with("https://")) {
+        return Err(ApiError::validation("url must be an http or https URL"));
+    }
+    let result = set_endpoint_check(
+        &state.pool,
+        &access,
+        endpoint_id,
+        req.method,
+        url,
+        state.dispatcher.tls_expiry_warning_days,
+    )
+    .await
+    .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+/// Endpoints whose certificate expires within `within_days`, defaulting
+/// to the configured TLS warning window.
+pub async fn tls_expiry_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidQuery(query): ValidQuery<TlsExpiryQuery>,
+) -> Result<Json<TlsExpiryResponse>, ApiError> {
+    let within_days = query
+        .within_days
+        .unwrap_or(state.dispatcher.tls_expiry_warning_days);
+    if !(0..=MAX_TLS_EXPIRY_WITHIN_DAYS).contains(&within_days) {
+        return Err(ApiError::validation(format!(
+            "within_days must be between 0 and {MAX_TLS_EXPIRY_WITHIN_DAYS}"
+        )));
+    }
+    let endpoints = tls_expiries(&state.pool, &access)
+        .await
+        .map_err(map_store_error)?
+        .into_iter()
+        .filter(|expiry| expiry.days_remaining < within_days)
+        .collect();
+    Ok(Json(TlsExpiryResponse {
+        within_days,
+        endpoints,
+    }))
+}
+
+/// Daily usage per endpoint, by default over the last 30 days. Endpoint
+/// groups stand in for tenants: filter by `group_id` or sum the rows by
+/// their `group_id` for chargeback.
+pub async fn usage_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidQuery(query): ValidQuery<UsageQuery>,
+) -> Result<Json<UsageResponse>, ApiError> {
+    let to = match query.to.as_deref() {
+        Some(raw) => parse_day("to", raw)?,
+        None => Utc::now().date_naive(),
+    };
+    let from = match query.from.as_deref() {
+        Some(raw) => parse_day("from", raw)?,
+        None => to - Duration::days(DEFAULT_USAGE_DAYS - 1),
+    };
+    let days = (to - from).num_days() + 1;
+    if !(1..=MAX_USAGE_DAYS).contains(&days) {
+        return Err(ApiError::validation(format!(
+            "from must be on or before to and span at most {MAX_USAGE_DAYS} days"
+        )));
+    }
+    let params = UsageParams {
+        from: from.to_string(),
+        to: to.to_string(),
+        endpoint_id: match query.endpoint_id {
+            Some(raw) => Some(parse_uuid("endpoint_id", &raw)?),
+            None => None,
+        },
+        group_id: match query.group_id {
+            Some(raw) => Some(parse_uuid("group_id", &raw)?),
+            None => None,
+        },
+    };
+    let rollups = usage_rollups(&state.pool, &access, &params)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(UsageResponse {
+        from: params.from,
+        to: params.to,
+        rollups,
+    }))
+}
+
+fn parse_day(field: &str, raw: &str) -> Result<NaiveDate, ApiError> {
+    NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d")
+        .map_err(|_| ApiError::validation(format!("{field} must be a YYYY-MM-DD date")))
+}
+
+/// Endpoints whose failure rate or latency in the last bucket spikes
+/// above the preceding buckets.
+pub async fn anomalies_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidQuery(query): ValidQuery<AnomaliesQuery>,
+) -> Result<Json<AnomaliesResponse>, ApiError> {
+    let bucket_minutes = query
+        .bucket_minutes
+        .unwrap_or(DEFAULT_ANOMALY_BUCKET_MINUTES);
+    if !(1..=MINUTES_PER_DAY).contains(&bucket_minutes) {
+        return Err(ApiError::validation(format!(
+            "bucket_minutes must be between 1 and {MINUTES_PER_DAY}"
+        )));
+    }
+    let baseline_buckets = query
+        .baseline_buckets
+        .unwrap_or(DEFAULT_ANOMALY_BASELINE_BUCKETS);
+    if !(MIN_ANOMALY_BASELINE_BUCKETS..=MAX_ANOMALY_BASELINE_BUCKETS).contains(&baseline_buckets) {
+        return Err(ApiError::validation(format!(
+            "baseline_buckets must be between {MIN_ANOMALY_BASELINE_BUCKETS} and \
+             {MAX_ANOMALY_BASELINE_BUCKETS}"
+        )));
+    }
+    let z_threshold = query.z_threshold.unwrap_or(DEFAULT_ANOMALY_Z_THRESHOLD);
+    if !z_threshold.is_finite() || z_threshold <= 0.0 {
+        return Err(ApiError::validation("z_threshold must be positive"));
+    }
+    let min_attempts = query.min_attempts.unwrap_or(DEFAULT_ANOMALY_MIN_ATTEMPTS);
+    if min_attempts < 1 {
+        return Err(ApiError::validation("min_attempts must be at least 1"));
+    }
+
+    let config = AnomalyConfig {
+        baseline_buckets,
+        z_threshold,
+        min_attempts,
+    };
+    let anomalies = find_anomalies(&state, &access, bucket_minutes, config).await?;
+    Ok(Json(AnomaliesResponse {
+        bucket_minutes,
+        baseline_buckets,
+        z_threshold,
+        anomalies,
+    }))
+}
+
+async fn find_anomalies(
+    state: &AppState,
+    access: &EndpointScope,
+    bucket_minutes: i64,
+    config: AnomalyConfig,
+) -> Result<Vec<EndpointAnomaly>, ApiError> {
+    let buckets = attempt_buckets(&state.pool, access, bucket_minutes, config.baseline_buckets)
+        .await
+        .map_err(map_store_error)?;
+    Ok(detect_anomalies(&buckets, config))
+}
+
+pub async fn slo_stats_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidQuery(query): ValidQuery<StatsWindowQuery>,
+) -> Result<Json<SloStatsResponse>, ApiError> {
+    let window_minutes = parse_stats_window(query.window_minutes)?;
+    let endpoints = slo_stats(&state.pool, &access, window_minutes)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(SloStatsResponse {
+        window_minutes,
+        endpoints,
+    }))
+}
+
+pub async fn provider_stats_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidQuery(query): ValidQuery<StatsWindowQuery>,
+) -> Result<Json<ProviderStatsResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let window_minutes = parse_stats_window(query.window_minutes)?;
+    let providers = provider_ingest_stats(&state.pool, window_minutes)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(ProviderStatsResponse {
+        window_minutes,
+        providers,
+    }))
+}
+
+fn parse_stats_window(window_minutes: Option<i64>) -> Result<i64, ApiError> {
+    let window_minutes = window_minutes.unwrap_or(DEFAULT_STATS_WINDOW_MINUTES);
+    if !(1..=MAX_STATS_WINDOW_MINUTES).contains(&window_minutes) {
+        return Err(ApiError::validation(format!(
+            "window_minutes must be between 1 and {MAX_STATS_WINDOW_MINUTES}"
+        )));
+    }
+    Ok(window_minutes)
+}
+
+/// Prometheus scrape target. SLO series cover the default stats window
+/// and anomalies use the default detector settings.
+pub async fn metrics_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Response, ApiError> {
+    let stats = slo_stats(&state.pool, &access, DEFAULT_STATS_WINDOW_MINUTES)
+        .await
+        .map_err(map_store_error)?;
+    let anomalies = find_anomalies(
+        &state,
+        &access,
+        DEFAULT_ANOMALY_BUCKET_MINUTES,
+        AnomalyConfig {
+            baseline_buckets: DEFAULT_ANOMALY_BASELINE_BUCKETS,
+            z_threshold: DEFAULT_ANOMALY_Z_THRESHOLD,
+            min_attempts: DEFAULT_ANOMALY_MIN_ATTEMPTS,
+        },
+    )
+    .await?;
+    let expiries = tls_expiries(&state.pool, &access)
+        .await
+        .map_err(map_store_error)?;
+    // Groups span endpoints, so scoped tokens do not see their quotas or
+    // the export, which covers every endpoint.
+    let (quotas, export) = if access.is_restricted() {
+        (Vec::new(), String::new())
+    } else {
+        let quotas = group_quotas(&state.pool, None)
+            .await
+            .map_err(map_store_error)?;
+        let export = get_export_status(&state.pool)
+            .await
+            .map_err(map_store_error)?;
+        (quotas, render_export_metrics(&export))
+    };
+    let body = [
+        render_slo_metrics(&stats),
+        render_anomaly_metrics(&anomalies),
+        render_tls_metrics(&expiries, state.dispatcher.tls_expiry_warning_days),
+        render_quota_metrics(&quotas),
+        export,
+    ]
+    .concat();
+    Ok(([(CONTENT_TYPE, METRICS_CONTENT_TYPE)], body).into_response())
+}
+
+pub async fn get_endpoint_secrets_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<EndpointSecrets>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_endpoint_secrets(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn rotate_endpoint_secret_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<RotateEndpointSecretRequest>,
+) -> Result<Json<EndpointSecrets>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let secret_id = req.secret_id.trim();
+    // An unresolvable primary would reject every webhook once rotated in.
+    state
+        .secrets
+        .resolve(secret_id)
+        .map_err(|err| ApiError::validation(format!("secret_id cannot be resolved: {err}")))?;
+    let expires_at = match req.expires_at {
+        Some(raw) => Some(parse_utc_timestamp("expires_at", &raw)?),
+        None => None,
+    };
+    let previous_expires_at = match req.previous_expires_at {
+        Some(raw) => parse_utc_timestamp("previous_expires_at", &raw)?,
+        None => (Utc::now() + Duration::hours(DEFAULT_SECRET_OVERLAP_HOURS))
+            .to_rfc3339_opts(SecondsFormat::Secs, true),
+    };
+    let result = rotate_endpoint_secret(
+        &state.pool,
+        &access,
+        endpoint_id,
+        secret_id,
+        expires_at.as_deref(),
+        &previous_expires_at,
+    )
+    .await
+    .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn get_fault_injection_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<FaultInjection>, ApiError> {
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = get_fault_injection(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_fault_injection_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetFaultInjectionRequest>,
+) -> Result<Json<FaultInjection>, ApiError> {
+    require_unscoped(&access)?;
+    if !state.dispatcher.fault_injection_enabled {
+        return Err(ApiError::conflict("fault injection is disabled"));
+    }
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    if !(0.0..=1.0).contains(&req.failure_rate) {
+        return Err(ApiError::validation("failure_rate must be between 0 and 1"));
+    }
+    if !(0..=MAX_INJECTED_LATENCY_MS).contains(&req.latency_ms) {
+        return Err(ApiError::validation(format!(
+            "latency_ms must be between 0 and {MAX_INJECTED_LATENCY_MS}"
+        )));
+    }
+    let result = set_fault_injection(
+        &state.pool,
+        &access,
+        endpoint_id,
+        req.failure_rate,
+        req.latency_ms,
+    )
+    .await
+    .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn clear_fault_injection_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<StatusCode, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    clear_fault_injection(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn error_summary_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidQuery(query): ValidQuery<ErrorSummaryQuery>,
+) -> Result<Json<ErrorSummaryResponse>, ApiError> {
+    let window_minutes = query.window_minutes.unwrap_or(60);
+    if !(1..=7 * MINUTES_PER_DAY).contains(&window_minutes) {
+        return Err(ApiError::validation(
+            "window_minutes must be between 1 and 10080",
+        ));
+    }
+    let endpoint_id = match query.endpoint_id {
+        Some(raw) => Some(parse_uuid("endpoint_id", &raw)?),
+        None => None,
+    };
+
+    let since =
+        (Utc::now() - Duration::minutes(window_minutes)).to_rfc3339_opts(SecondsFormat::Secs, true);
+    let buckets = summarize_errors(&state.pool, &access, &since, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+
+    Ok(Json(ErrorSummaryResponse {
+        since,
+        total: buckets.iter().map(|bucket| bucket.count).sum(),
+        buckets,
+    }))
+}
+
+/// Reports inconsistent event state without changing anything.
+pub async fn doctor_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Json<DoctorReport>, ApiError> {
+    require_unscoped(&access)?;
+    let report = run_doctor(&state.pool, false)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(report))
+}
+
+pub async fn storage_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Json<StorageReport>, ApiError> {
+    require_unscoped(&access)?;
+    let report = storage_report(
+        &state.pool,
+        state.dispatcher.attempt_log_hot_days,
+        state.ingest.max_db_bytes,
+    )
+    .await
+    .map_err(map_store_error)?;
+    Ok(Json(report))
+}
+
+pub async fn repair_doctor_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Json<DoctorReport>, ApiError> {
+    require_unscoped(&access)?;
+    let report = run_doctor(&state.pool, true)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(report))
+}
+
+/// Pushes one event through the whole delivery pipeline against a
+/// temporary endpoint. Answers 503 with the same report when a step
+/// fails, so a post-deploy check can rely on the status alone.
+pub async fn selftest_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<(StatusCode, Json<SelftestReport>), ApiError> {
+    require_unscoped(&access)?;
+    let report = run_selftest(&state.pool, &state.dispatcher).await;
+    let status = if report.passed {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok((status, Json(report)))
+}
+
+pub async fn create_group_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidJson(req): ValidJson<CreateEndpointGroupRequest>,
+) -> Result<Json<EndpointGroup>, ApiError> {
+    require_unscoped(&access)?;
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::validation("name must be non-empty"));
+    }
+    validate_rate_limit(req.rate_limit_per_minute)?;
+    let group = create_endpoint_group(&state.pool, name, req.rate_limit_per_minute)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(group))
+}
+
+pub async fn list_groups_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Json<ListEndpointGroupsResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let groups = list_endpoint_groups(&state.pool)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(ListEndpointGroupsResponse { groups }))
+}
+
+pub async fn get_group_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(group_id): ValidPath<String>,
+) -> Result<Json<EndpointGroup>, ApiError> {
+    require_unscoped(&access)?;
+    let group_id = parse_uuid("group_id", &group_id)?;
+    let group = get_endpoint_group(&state.pool, group_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(group))
+}
+
+pub async fn set_endpoint_group_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetEndpointGroupRequest>,
+) -> Result<Json<EndpointGroupAssignment>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let result = set_endpoint_group(&state.pool, endpoint_id, req.group_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn pause_group_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(group_id): ValidPath<String>,
+) -> Result<Json<EndpointGroup>, ApiError> {
+    require_unscoped(&access)?;
+    let group_id = parse_uuid("group_id", &group_id)?;
+    let group = set_group_paused(&state.pool, group_id, true)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(group))
+}
+
+pub async fn resume_group_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(group_id): ValidPath<String>,
+    ValidQuery(query): ValidQuery<AutoReplayQuery>,
+) -> Result<Json<EndpointGroup>, ApiError> {
+    require_unscoped(&access)?;
+    let group_id = parse_uuid("group_id", &group_id)?;
+    let window = parse_auto_replay(query)?;
+    let group = set_group_paused(&state.pool, group_id, false)
+        .await
+        .map_err(map_store_error)?;
+    if let Some(window) = window {
+        let target = DeadEventTarget::Group(group_id);
+        run_operation(
+            &state,
+            OperationKind::OutageReplay,
+            target,
+            &actor,
+            |operation_id| {
+                replay_dead_window(
+                    &state.pool,
+                    target,
+                    &window,
+                    state.dispatcher.replay_loop_limit,
+                    Some(operation_id),
+                )
+            },
+        )
+        .await?;
+    }
+    Ok(Json(group))
+}
+
+pub async fn close_circuit_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidQuery(query): ValidQuery<AutoReplayQuery>,
+) -> Result<Json<CloseCircuitResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let endpoint_id = parse_uuid("endpoint_id", &endpoint_id)?;
+    let window = parse_auto_replay(query)?;
+    close_circuit(&state.pool, &access, endpoint_id)
+        .await
+        .map_err(map_store_error)?;
+    let target = DeadEventTarget::Endpoint(endpoint_id);
+    let (replayed, operation_id) = match window {
+        Some(window) => {
+            let (replayed, operation_id) = run_operation(
+                &state,
+                OperationKind::OutageReplay,
+                target,
+                &actor,
+                |operation_id| {
+                    replay_dead_window(
+                        &state.pool,
+                        target,
+                        &window,
+                        state.dispatcher.replay_loop_limit,
+                        Some(operation_id),
+                    )
+                },
+            )
+            .await?;
+            (replayed, Some(operation_id))
+        }
+        None => (BulkReplay::default(), None),
+    };
+    Ok(Json(CloseCircuitResponse {
+        endpoint_id,
+        replayed_event_ids: replayed.replayed_event_ids,
+        failures: replayed.failures,
+        operation_id,
+    }))
+}
+
+/// Runs `replay` as a tracked operation, so its progress shows in the
+/// operations history and it can be undone. Returns the replay outcome
+/// and the operation ID.
+async fn run_operation<F, Fut>(
+    state: &AppState,
+    kind: OperationKind,
+    target: DeadEventTarget,
+    actor: &InspectorActor,
+    replay: F,
+) -> Result<(BulkReplay, Uuid), ApiError>
+where
+    F: FnOnce(Uuid) -> Fut,
+    Fut: Future<Output = Result<BulkReplay, StoreError>>,
+{
+    let operation_id = start_operation(&state.pool, kind, target, &actor.0)
+        .await
+        .map_err(map_store_error)?;
+    let result = replay(operation_id).await;
+    finish_operation(&state.pool, operation_id, &result)
+        .await
+        .map_err(map_store_error)?;
+    let replayed = result.map_err(map_store_error)?;
+    Ok((replayed, operation_id))
+}
+
+pub async fn list_operations_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+) -> Result<Json<ListOperationsResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let operations = list_operations(&state.pool)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(ListOperationsResponse { operations }))
+}
+
+pub async fn undo_operation_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    ValidPath(operation_id): ValidPath<String>,
+) -> Result<Json<UndoOperationResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let operation_id = parse_uuid("operation_id", &operation_id)?;
+    let result = undo_operation(&state.pool, operation_id, &actor.0)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(result))
+}
+
+pub async fn set_group_rate_limit_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(group_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetGroupRateLimitRequest>,
+) -> Result<Json<EndpointGroup>, ApiError> {
+    require_unscoped(&access)?;
+    let group_id = parse_uuid("group_id", &group_id)?;
+    validate_rate_limit(req.rate_limit_per_minute)?;
+    let group = set_group_rate_limit(&state.pool, group_id, req.rate_limit_per_minute)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(group))
+}
+
+pub async fn get_group_quota_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(group_id): ValidPath<String>,
+) -> Result<Json<GroupQuota>, ApiError> {
+    require_unscoped(&access)?;
+    let group_id = parse_uuid("group_id", &group_id)?;
+    let quota = get_group_quota(&state.pool, group_id)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(quota))
+}
+
+pub async fn set_group_quota_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(group_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetGroupQuotaRequest>,
+) -> Result<Json<GroupQuota>, ApiError> {
+    require_unscoped(&access)?;
+    let group_id = parse_uuid("group_id", &group_id)?;
+    if req.events_per_day.is_some_and(|limit| limit <= 0) {
+        return Err(ApiError::validation("events_per_day must be > 0"));
+    }
+    if req.storage_bytes.is_some_and(|limit| limit <= 0) {
+        return Err(ApiError::validation("storage_bytes must be > 0"));
+    }
+    let quota = set_group_quota(&state.pool, group_id, req.events_per_day, req.storage_bytes)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(quota))
+}
+
+pub async fn replay_group_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    actor: InspectorActor,
+    headers: HeaderMap,
+    ValidPath(group_id): ValidPath<String>,
+    ValidJson(req): ValidJson<ReplayGroupRequest>,
+) -> Result<Json<ReplayGroupResponse>, ApiError> {
+    require_unscoped(&access)?;
+    let group_id = parse_uuid("group_id", &group_id)?;
+    let reset_circuit = req.reset_circuit.unwrap_or(false);
+    let skip_if_pending = req.skip_if_pending.unwrap_or(false);
+    if req.drip_rate_per_minute.is_some_and(|rate| rate <= 0) {
+        return Err(ApiError::validation("drip_rate_per_minute must be > 0"));
+    }
+    let drip_rate_per_minute = req.drip_rate_per_minute;
+    let dry_run = req.dry_run.unwrap_or(false);
+    let background = req.background.unwrap_or(false);
+    let operation = format!("group.replay:{group_id}");
+    // Dry runs and their confirmed replay share a hash.
+    let confirm_hash = request_hash(
+        &operation,
+        &ReplayGroupRequest {
+            dry_run: None,
+            confirm_token: None,
+            background: None,
+            ..req.clone()
+        },
+    )?;
+    idempotent(&state, &actor, &headers, &operation, &req, || async {
+        let matched = count_group_replay(&state.pool, group_id)
+            .await
+            .map_err(map_store_error)?;
+        if dry_run {
+            let (token, expires_at) = issue_replay_confirmation(
+                &state.pool,
+                &actor.0,
+                &confirm_hash,
+                matched,
+                state.dispatcher.replay_confirm_ttl_minutes,
+            )
+            .await
+            .map_err(map_store_error)?;
+            return Ok(ReplayGroupResponse {
+                replayed_event_ids: Vec::new(),
+                failures: Vec::new(),
+                matched,
+                confirm_token: Some(token),
+                confirm_expires_at: Some(expires_at),
+                operation_id: None,
+                job_id: None,
+            });
+        }
+        if let Some(threshold) = state.dispatcher.replay_confirm_threshold
+            && matched > threshold
+        {
+            let Some(token) = req.confirm_token.as_deref() else {
+                return Err(ApiError::conflict(format!(
+                    "replay matches {matched} dead events, more than {threshold}; \
+                    send it with dry_run first and pass back its confirm_token"
+                )));
+            };
+            let confirmed =
+                consume_replay_confirmation(&state.pool, &actor.0, token, &confirm_hash)
+                    .await
+                    .map_err(map_store_error)?;
+            if matched > confirmed {
+                return Err(ApiError::conflict(format!(
+                    "{matched} dead events match now but the dry run confirmed {confirmed}; \
+                    run a new dry run"
+                )));
+            }
+        }
+        if background {
+            let operation_id = start_operation(
+                &state.pool,
+                OperationKind::GroupReplay,
+                DeadEventTarget::Group(group_id),
+                &actor.0,
+            )
+            .await
+            .map_err(map_store_error)?;
+            let job = create_job(
+                &state.pool,
+                JobKind::GroupReplay,
+                &actor.0,
+                Some(operation_id),
+            )
+            .await
+            .map_err(map_job_error)?;
+            spawn_group_replay_job(
+                state.pool.clone(),
+                job.id,
+                operation_id,
+                GroupReplayJob {
+                    group_id,
+                    reset_circuit,
+                    skip_if_pending,
+                    drip_rate_per_minute,
+                    loop_limit: state.dispatcher.replay_loop_limit,
+                },
+            );
+            return Ok(ReplayGroupResponse {
+                replayed_event_ids: Vec::new(),
+                failures: Vec::new(),
+                matched,
+                confirm_token: None,
+                confirm_expires_at: None,
+                operation_id: Some(operation_id),
+                job_id: Some(job.id),
+            });
+        }
+        let (replayed, operation_id) = run_operation(
+            &state,
+            OperationKind::GroupReplay,
+            DeadEventTarget::Group(group_id),
+            &actor,
+            |operation_id| {
+                replay_group(
+                    &state.pool,
+                    group_id,
+                    reset_circuit,
+                    skip_if_pending,
+                    drip_rate_per_minute,
+                    state.dispatcher.replay_loop_limit,
+                    Some(operation_id),
+                )
+            },
+        )
+        .await?;
+        Ok(ReplayGroupResponse {
+            replayed_event_ids: replayed.replayed_event_ids,
+            failures: replayed.failures,
+            matched,
+            confirm_token: None,
+            confirm_expires_at: None,
+            operation_id: Some(operation_id),
+            job_id: None,
+        })
+    })
+    .await
+}
+
+pub async fn get_provider_scrub_rules_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(provider): ValidPath<String>,
+) -> Result<Json<ScrubRuleset>, ApiError> {
+    require_unscoped(&access)?;
+    let scope = ScrubScope::Provider(parse_provider(&provider)?);
+    let ruleset = get_scrub_ruleset(&state.pool, &access, &scope)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(ruleset))
+}
+
+pub async fn set_provider_scrub_rules_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(provider): ValidPath<String>,
+    ValidJson(req): ValidJson<SetScrubRulesRequest>,
+) -> Result<Json<ScrubRuleset>, ApiError> {
+    require_unscoped(&access)?;
+    let scope = ScrubScope::Provider(parse_provider(&provider)?);
+    validate_scrub_rules(&req)?;
+    let ruleset = set_scrub_rules(&state.pool, &access, &scope, &req.rules)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(ruleset))
+}
+
+pub async fn get_endpoint_scrub_rules_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+) -> Result<Json<ScrubRuleset>, ApiError> {
+    let scope = ScrubScope::Endpoint(parse_uuid("endpoint_id", &endpoint_id)?);
+    let ruleset = get_scrub_ruleset(&state.pool, &access, &scope)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(ruleset))
+}
+
+pub async fn set_endpoint_scrub_rules_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath(endpoint_id): ValidPath<String>,
+    ValidJson(req): ValidJson<SetScrubRulesRequest>,
+) -> Result<Json<ScrubRuleset>, ApiError> {
+    require_unscoped(&access)?;
+    let scope = ScrubScope::Endpoint(parse_uuid("endpoint_id", &endpoint_id)?);
+    validate_scrub_rules(&req)?;
+    let ruleset = set_scrub_rules(&state.pool, &access, &scope, &req.rules)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(ruleset))
+}
+
+pub async fn get_payload_schema_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath((provider, event_type)): ValidPath<(String, String)>,
+) -> Result<Json<PayloadSchema>, ApiError> {
+    require_unscoped(&access)?;
+    let provider = parse_provider(&provider)?;
+    let event_type = parse_event_type(&event_type)?;
+    let schema = get_payload_schema(&state.pool, &provider, &event_type)
+        .await
+        .map_err(map_store_error)?;
+    Ok(Json(schema))
+}
+
+/// Events keep the schema errors they were ingested with; changing the
+/// schema does not revalidate them.
+pub async fn set_payload_schema_handler(
+    State(state): State<AppState>,
+    access: EndpointScope,
+    ValidPath((provider, event_type)): ValidPath<(String, String)>,
+    ValidJson(req): ValidJson<SetPayloadSchemaRequest>,
+) -> Result<Json<PayloadSchema>, ApiError> {
+    require_unscoped(&access)?;
+    let provider = parse_provider(&provider)?;
+    let event_type = parse_event_type(&event_type)?;
+    let schema = match serde_json::from_str::<serde_json::Value>(&req.schema) {
+        Ok(value @ (serde_json::Value::Object(_) | serde_json::Value::Bool(_))) => value,
+        _ => {
+            return Err(ApiError::validation(
+                "schema must be a JSON object or boolean",
+            ));
+        }
+    };
+    check_schema(&schema).map_err(|err| ApiError::validation(format!("invalid schema: {err}")))?;
+    let schema = set_payload_schema(
+        &state.pool,
+        &provider,
+        &event_type,
+        &schema.to_string(),
+        req.reject_invalid.unwrap_or(false),
+    )
+    .await
+    .map_err(map_store_error)?;
+    Ok(Json(schema))
+}
+
+/// Global operations affect every endpoint, so endpoint-scoped tokens may
+/// not use them.
+fn require_unscoped(access: &EndpointScope) -> Result<(), ApiError> {
+    if access.is_restricted() {
+        return Err(ApiError::forbidden("token is scoped to specific endpoints"));
+    }
+    Ok(())
+}
+
+fn parse_provider(value: &str) -> Result<String, ApiError> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err(ApiError::validation("provider must be non-empty"));
+    }
+    Ok(trimmed.to_string())
+}
+
+fn parse_event_type(value: &str) -> Result<String, ApiError> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err(ApiError::validation("event_type must be non-empty"));
+    }
+    Ok(trimmed.to_string())
+}
+
+fn validate_scrub_rules(req: &SetScrubRulesRequest) -> Result<(), ApiError> {
+    if req.rules.len() > MAX_SCRUB_RULES {
+        return Err(ApiError::validation(format!(
+            "rules must contain at most {MAX_SCRUB_RULES} entries"
+        )));
+    }
+    if req.rules.iter().any(|rule| {
+        rule.path
+            .split('.')
+            .any(|segment| segment.trim().is_empty())
+    }) {
+        return Err(ApiError::validation(
+            "rule path must be dot-separated non-empty segments",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_rate_limit(rate_limit_per_minute: Option<i64>) -> Result<(), ApiError> {
+    if let Some(limit) = rate_limit_per_minute
+        && limit <= 0
+    {
+        return Err(ApiError::validation("rate_limit_per_minute must be > 0"));
+    }
+    Ok(())
+}
+
+fn validate_recurring_window(
+    day_of_week: Option<i64>,
+    start_minute: i64,
+    duration_minutes: i64,
+) -> Result<(), ApiError> {
+    if let Some(day) = day_of_week
+        && !(0..=6).contains(&day)
+    {
+        return Err(ApiError::validation("day_of_week must be between 0 and 6"));
+    }
+    if !(0..MINUTES_PER_DAY).contains(&start_minute) {
+        return Err(ApiError::validation(
+            "start_minute must be between 0 and 1439",
+        ));
+    }
+    if !(1..=7 * MINUTES_PER_DAY).contains(&duration_minutes) {
+        return Err(ApiError::validation(
+            "duration_minutes must be between 1 and 10080",
+        ));
+    }
+    Ok(())
+}
+
+fn parse_limit(limit: Option<i64>) -> Result<i64, ApiError> {
+    let limit = limit.unwrap_or(50);
+    if !(1..=200).contains(&limit) {
+        return Err(ApiError::validation("limit must be between 1 and 200"));
+    }
+    Ok(limit)
+}
+
+fn parse_uuid(field: &str, value: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(value).map_err(|_| ApiError::validation(format!("{field} must be a UUID")))
+}
+
+/// Parses the optional outage window replay. Replay is requested by setting
+/// `dead_since`; the other bounds are only valid alongside it.
+fn parse_auto_replay(query: AutoReplayQuery) -> Result<Option<DeadEventWindow>, ApiError> {
+    let Some(since) = query.dead_since else {
+        if query.dead_before.is_some()
+            || query.replay_limit.is_some()
+            || query.drip_rate_per_minute.is_some()
+        {
+            return Err(ApiError::validation(
+                "dead_before, replay_limit and drip_rate_per_minute require dead_since",
+            ));
+        }
+        return Ok(None);
+    };
+    if query.drip_rate_per_minute.is_some_and(|rate| rate <= 0) {
+        return Err(ApiError::validation("drip_rate_per_minute must be > 0"));
+    }
+    let limit = query.replay_limit.unwrap_or(MAX_AUTO_REPLAY);
+    if !(1..=MAX_AUTO_REPLAY).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "replay_limit must be between 1 and {MAX_AUTO_REPLAY}"
+        )));
+    }
+    Ok(Some(DeadEventWindow {
+        received_since: parse_utc_timestamp("dead_since", &since)?,
+        received_before: match query.dead_before {
+            Some(raw) => Some(parse_utc_timestamp("dead_before", &raw)?),
+            None => None,
+        },
+        limit,
+        drip_rate_per_minute: query.drip_rate_per_minute,
+    }))
+}
+
+/// Parses an RFC 3339 timestamp and normalizes it to UTC seconds, the form
+/// event timestamps are stored in.
+fn parse_utc_timestamp(field: &str, raw: &str) -> Result<String, ApiError> {
+    Ok(DateTime::parse_from_rfc3339(raw)
+        .map_err(|_| ApiError::validation(format!("{field} must be an RFC 3339 timestamp")))?
+        .with_timezone(&Utc)
+        .to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn parse_status(value: &str) -> Result<WebhookEventStatus, ApiError> {
+    match value {
+        "pending" => Ok(WebhookEventStatus::Pending),
+        "in_flight" => Ok(WebhookEventStatus::InFlight),
+        "requeued" => Ok(WebhookEventStatus::Requeued),
+        "delivered" => Ok(WebhookEventStatus::Delivered),
+        "dead" => Ok(WebhookEventStatus::Dead),
+        "paused" => Ok(WebhookEventStatus::Paused),
+        "expired" => Ok(WebhookEventStatus::Expired),
+        "cancelled" => Ok(WebhookEventStatus::Cancelled),
+        _ => Err(ApiError::validation("status is invalid")),
+    }
+}
+
+/// Query names of each `EventListField`.
+const EVENT_LIST_FIELDS: [(&str, EventListField); 16] = [
+    ("id", EventListField::Id),
+    ("endpoint_id", EventListField::EndpointId),
+    (
+        "replayed_from_event_id",
+        EventListField::ReplayedFromEventId,
+    ),
+    ("source_id", EventListField::SourceId),
+    ("provider", EventListField::Provider),
+    ("status", EventListField::Status),
+    ("attempts", EventListField::Attempts),
+    ("received_at", EventListField::ReceivedAt),
+    ("next_attempt_at", EventListField::NextAttemptAt),
+    ("last_error", EventListField::LastError),
+    ("updated_at", EventListField::UpdatedAt),
+    ("payload_preview", EventListField::PayloadPreview),
+    ("target_url", EventListField::TargetUrl),
+    ("endpoint_name", EventListField::EndpointName),
+    ("endpoint_description", EventListField::EndpointDescription),
+    ("circuit", EventListField::Circuit),
+];
+
+/// Collects `metadata.<key>=<value>` query parameters. Keys are limited to
+/// letters, digits and underscores, as the extractors produce.
+fn parse_metadata_filters(pairs: Vec<(String, String)>) -> Result<Vec<(String, String)>, ApiError> {
+    let mut filters = Vec::new();
+    for (name, value) in pairs {
+        let Some(key) = name.strip_prefix("metadata.") else {
+            continue;
+        };
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        {
+            return Err(ApiError::validation(format!(
+                "invalid metadata filter key: {key}"
+            )));
+        }
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(ApiError::validation(format!(
+                "metadata.{key} must be non-empty"
+            )));
+        }
+        filters.push((key.to_string(), value.to_string()));
+    }
+    Ok(filters)
+}
+
+/// Parses a comma-separated `fields` list, ignoring duplicates.
+fn parse_list_fields(raw: &str) -> Result<Vec<EventListField>, ApiError> {
+    let mut fields = Vec::new();
+    for name in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let field = EVENT_LIST_FIELDS
+            .iter()
+            .find(|(candidate, _)| *candidate == name)
+            .map(|(_, field)| *field)
+            .ok_or_else(|| ApiError::validation(format!("fields has unknown field: {name}")))?;
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+    if fields.is_empty() {
+        return Err(ApiError::validation("fields must be non-empty"));
+    }
+    Ok(fields)
+}
+
+/// Serializes `item` keeping only the requested fields. The `event`
+/// object is always present, even when none of its fields are, and
+/// `last_attempt` whenever it was asked for.
+fn project_list_item(
+    item: &WebhookEventListItem,
+    fields: &[EventListField],
+    include_last_attempt: bool,
+) -> Result<serde_json::Value, ApiError> {
+    let requested = |key: &str| {
+        EVENT_LIST_FIELDS
+            .iter()
+            .any(|(name, field)| *name == key && fields.contains(field))
+    };
+    let mut value =
+        serde_json::to_value(item).map_err(|_| ApiError::internal("failed to encode event"))?;
+    if let serde_json::Value::Object(object) = &mut value {
+        if let Some(serde_json::Value::Object(event)) = object.get_mut("event") {
+            event.retain(|key, _| requested(key));
+        }
+        object.retain(|key, _| {
+            key == "event" || (include_last_attempt && key == "last_attempt") || requested(key)
+        });
+    }
+    Ok(value)
+}
+
+fn decode_cursor(raw: &str) -> Result<InspectorCursor, ApiError> {
+    let decoded = URL_SAFE_NO_PAD
+        .decode(raw)
+        .map_err(|_| ApiError::validation("before must be a valid cursor"))?;
+    let payload: CursorPayload = serde_json::from_slice(&decoded)
+        .map_err(|_| ApiError::validation("before must be a valid cursor"))?;
+    DateTime::parse_from_rfc3339(&payload.received_at)
+        .map_err(|_| ApiError::validation("before must be a valid cursor"))?;
+    let id = Uuid::parse_str(&payload.id)
+        .map_err(|_| ApiError::validation("before must be a valid cursor"))?;
+    Ok(InspectorCursor {
+        received_at: payload.received_at,
+        id,
+    })
+}
+
+fn encode_cursor(cursor: &InspectorCursor) -> Result<String, ApiError> {
+    let payload = CursorPayload {
+        received_at: cursor.received_at.clone(),
+        id: cursor.id.to_string(),
+    };
+    let encoded =
+        serde_json::to_vec(&payload).map_err(|_| ApiError::internal("failed to encode cursor"))?;
+    Ok(URL_SAFE_NO_PAD.encode(encoded))
+}
+
+fn map_backfill_error(err: BackfillError) -> ApiError {
+    match err {
+        BackfillError::Conflict(message) => ApiError::conflict(message),
+        BackfillError::Db(db) => ApiError::Db(db),
+        BackfillError::NotFound(message) => ApiError::not_found(message),
+        BackfillError::Parse(message) => ApiError::internal(message),
+    }
+}
+
+fn map_job_error(err: JobStoreError) -> ApiError {
+    match err {
+        JobStoreError::Conflict(message) => ApiError::conflict(message),
+        JobStoreError::Db(db) => ApiError::Db(db),
+        JobStoreError::NotFound(message) => ApiError::not_found(message),
+        JobStoreError::Parse(message) => ApiError::internal(message),
+    }
 }
 
 fn map_store_error(err: StoreError) -> ApiError {
@@ -1,2 +1,3 @@
 pub mod dispatcher;
+pub mod ingest;
 pub mod inspector;
@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct IngestConfig {
+    pub queue_capacity: usize,
+    /// Write-ahead journal for fast-ack events; `None` disables it.
+    pub journal_path: Option<PathBuf>,
+    /// Undelivered events above which ingestion answers 429; `None`
+    /// disables the check.
+    pub max_pending_events: Option<i64>,
+    /// Database file size above which ingestion answers 429; `None`
+    /// disables the check.
+    pub max_db_bytes: Option<i64>,
+    /// `Retry-After` sent with those 429s.
+    pub backpressure_retry_after_secs: u64,
+    /// Derive event IDs from the provider's event ID instead of picking
+    /// random ones, so ingesting the same provider event twice stores it
+    /// once, whichever instance receives it. Events without a provider
+    /// event ID keep random IDs.
+    pub deterministic_event_ids: bool,
+    /// Inbound headers to store, lowercased. `None` stores every header
+    /// except hop-by-hop and credential ones.
+    pub header_allowlist: Option<Vec<String>>,
+    /// Largest body accepted, both as received and after decompression,
+    /// so a payload is never accepted in one form and refused in the
+    /// other. The decompressed check guards against gzip bombs.
+    pub max_body_bytes: usize,
+}
+
+impl IngestConfig {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        if let Ok(value) = std::env::var("RECEIVER_INGEST_QUEUE_CAPACITY")
+            && let Ok(parsed) = value.parse::<usize>()
+        {
+            config.queue_capacity = parsed.max(1);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_INGEST_JOURNAL_PATH") {
+            let trimmed = value.trim();
+            config.journal_path = (!trimmed.is_empty()).then(|| PathBuf::from(trimmed));
+        }
+        if let Ok(value) = std::env::var("RECEIVER_INGEST_MAX_PENDING_EVENTS")
+            && let Ok(parsed) = value.parse::<i64>()
+        {
+            config.max_pending_events = Some(parsed.max(1));
+        }
+        if let Ok(value) = std::env::var("RECEIVER_INGEST_MAX_DB_BYTES")
+            && let Ok(parsed) = value.parse::<i64>()
+        {
+            config.max_db_bytes = Some(parsed.max(1));
+        }
+        if let Ok(value) = std::env::var("RECEIVER_INGEST_RETRY_AFTER_SECS")
+            && let Ok(parsed) = value.parse::<u64>()
+        {
+            config.backpressure_retry_after_secs = parsed.max(1);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_INGEST_MAX_BODY_BYTES")
+            && let Ok(parsed) = value.parse::<usize>()
+        {
+            config.max_body_bytes = parsed.max(1);
+        }
+        if let Ok(value) = std::env::var("RECEIVER_DETERMINISTIC_EVENT_IDS") {
+            config.deterministic_event_ids = matches!(value.trim(), "1" | "true");
+        }
+        if let Ok(value) = std::env::var("RECEIVER_INGEST_HEADER_ALLOWLIST") {
+            let names: Vec<String> = value
+                .split(',')
+                .map(|name| name.trim().to_ascii_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect();
+            config.header_allowlist = (!names.is_empty()).then_some(names);
+        }
+
+        config
+    }
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            queue_capacity: 1024,
+            journal_path: Some(PathBuf::from("receiver-ingest.journal")),
+            max_pending_events: None,
+            max_db_bytes: None,
+            backpressure_retry_after_secs: 30,
+            deterministic_event_ids: false,
+            header_allowlist: None,
+            max_body_bytes: 8 * 1024 * 1024,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::io::Read;
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+
+#[derive(Debug)]
+pub enum DecodeError {
+    Unsupported(String),
+    /// Over the configured body limit, as received or once decompressed.
+    TooLarge,
+    /// The body could not be read or decompressed.
+    Corrupt(std::io::Error),
+}
+
+/// Undoes a `Content-Encoding` of `gzip` or `deflate` (zlib-wrapped, as
+/// HTTP specifies). `None` and `identity` return the body unchanged.
+pub fn decode_body(
+    content_encoding: Option<&str>,
+    body: &[u8],
+    limit: usize,
+) -> Result<Option<Vec<u8>>, DecodeError> {
+    let encoding = content_encoding.map(|value| value.trim().to_ascii_lowercase());
+    match encoding.as_deref() {
+        None | Some("" | "identity") => Ok(None),
+        Some("gzip" | "x-gzip") => read_limited(GzDecoder::new(body), limit).map(Some),
+        Some("deflate") => read_limited(ZlibDecoder::new(body), limit).map(Some),
+        Some(other) => Err(DecodeError::Unsupported(other.to_string())),
+    }
+}
+
+fn read_limited(reader: impl Read, limit: usize) -> Result<Vec<u8>, DecodeError> {
+    let mut decoded = Vec::new();
+    reader
+        .take(u64::try_from(limit).unwrap_or(u64::MAX).saturating_add(1))
+        .read_to_end(&mut decoded)
+        .map_err(DecodeError::Corrupt)?;
+    if decoded.len() > limit {
+        return Err(DecodeError::TooLarge);
+    }
+    Ok(decoded)
+}
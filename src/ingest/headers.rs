@@ -0,0 +1,47 @@
+use std::collections::BTreeMap;
+
+/// Headers never stored unless an allowlist names them: hop-by-hop
+/// headers, which describe the connection rather than the webhook, and
+/// credentials the provider or a proxy sent to this receiver.
+pub const DEFAULT_DROPPED_HEADERS: [&str; 12] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "x-api-key",
+    "x-auth-token",
+];
+
+/// Splits inbound headers into those to store and the sorted names of
+/// those dropped. With an `allowlist`, only the headers it names are kept;
+/// without one, [`DEFAULT_DROPPED_HEADERS`] are dropped. Names compare
+/// case-insensitively.
+pub fn filter_headers(
+    headers: BTreeMap<String, String>,
+    allowlist: Option<&[String]>,
+) -> (BTreeMap<String, String>, Vec<String>) {
+    let mut kept = BTreeMap::new();
+    let mut dropped = Vec::new();
+    for (name, value) in headers {
+        let keep = match allowlist {
+            Some(allowlist) => allowlist
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(&name)),
+            None => !DEFAULT_DROPPED_HEADERS
+                .iter()
+                .any(|denied| denied.eq_ignore_ascii_case(&name)),
+        };
+        if keep {
+            kept.insert(name, value);
+        } else {
+            dropped.push(name);
+        }
+    }
+    (kept, dropped)
+}
@@ -0,0 +1,7 @@
+use sha2::{Digest, Sha256};
+
+/// Hex SHA-256 of a payload exactly as stored: the text bytes of a UTF-8
+/// payload, or the raw bytes of a binary one.
+pub fn payload_sha256(payload: &[u8]) -> String {
+    hex::encode(Sha256::digest(payload))
+}
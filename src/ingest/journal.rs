@@ -0,0 +1,101 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use sqlx::SqlitePool;
+
+use super::store::{NewEvent, StoreError, insert_event};
+
+/// Append-only write-ahead log for fast-ack ingestion. Each accepted event
+/// is written and synced here before the 202 goes out; the file is
+/// truncated once every journaled event has reached SQLite, and replayed
+/// on startup to recover events still in the queue when the process died.
+/// File writes and syncs run on the blocking pool.
+pub struct IngestJournal {
+    inner: Arc<Mutex<JournalFile>>,
+}
+
+struct JournalFile {
+    file: File,
+    pending: usize,
+}
+
+impl IngestJournal {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(JournalFile { file, pending: 0 })),
+        })
+    }
+
+    /// Appends `event` and syncs it to disk.
+    pub(crate) async fn append(&self, event: &NewEvent) -> io::Result<()> {
+        let mut line = serde_json::to_vec(event).map_err(io::Error::other)?;
+        line.push(b'\n');
+        self.with_file(move |journal| {
+            journal.file.write_all(&line)?;
+            journal.file.sync_data()?;
+            journal.pending += 1;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Records that one journaled event reached SQLite, and empties the
+    /// journal once none is left.
+    pub(crate) async fn mark_persisted(&self) -> io::Result<()> {
+        self.with_file(|journal| {
+            journal.pending = journal.pending.saturating_sub(1);
+            if journal.pending == 0 {
+                journal.file.set_len(0)?;
+                journal.file.sync_data()?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn with_file(
+        &self,
+        f: impl FnOnce(&mut JournalFile) -> io::Result<()> + Send + 'static,
+    ) -> io::Result<()> {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut journal = inner
+                .lock()
+                .map_err(|_| io::Error::other("ingest journal lock poisoned"))?;
+            f(&mut journal)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+/// Writes every event recorded in the journal at `path` to SQLite and then
+/// empties the journal. A torn final line from a crash mid-append is
+/// skipped; that event was never acknowledged.
+pub async fn replay_journal(
+    pool: &SqlitePool,
+    path: impl AsRef<Path>,
+) -> Result<usize, StoreError> {
+    let path = path.as_ref();
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut replayed = 0;
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let Ok(event) = serde_json::from_str::<NewEvent>(line) else {
+            continue;
+        };
+        insert_event(pool, &event).await?;
+        replayed += 1;
+    }
+
+    OpenOptions::new().write(true).open(path)?.set_len(0)?;
+
+    Ok(replayed)
+}
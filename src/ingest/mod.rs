@@ -0,0 +1,32 @@
+mod config;
+mod decode;
+mod headers;
+mod integrity;
+mod journal;
+mod provider;
+mod queue;
+mod schema;
+mod scrub;
+mod signature;
+mod store;
+
+pub use config::IngestConfig;
+pub use decode::{DecodeError, decode_body};
+pub use headers::{DEFAULT_DROPPED_HEADERS, filter_headers};
+pub use integrity::payload_sha256;
+pub use journal::{IngestJournal, replay_journal};
+pub use provider::{
+    CUSTOMER_REF_FIELDS, deterministic_event_id, extract_event_type, extract_metadata,
+    extract_provider_event_id,
+};
+pub use queue::{EnqueueError, IngestQueue};
+pub use schema::{check_schema, validate_payload};
+pub use scrub::scrub_payload;
+pub use signature::verify_signature;
+pub use store::{
+    IngestOutcome, IngestSchema, IngestSource, NewEvent, QuotaExceeded, StoreError,
+    check_group_quota, find_delivery_receipt, find_payload_schema, find_scrub_ruleset,
+    find_source_by_slug, find_sources_by_endpoint, ingest_backpressured, ingest_health,
+    insert_event, record_ingest_outcome,
+};
+pub(crate) use store::{add_stored_payload_bytes, rebuild_stored_payload_bytes};
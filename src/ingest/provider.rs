@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use axum::http::HeaderMap;
+use serde_json::Value;
+use uuid::Uuid;
+
+/// UUIDv5 namespace of event IDs derived by [`deterministic_event_id`].
+const EVENT_ID_NAMESPACE: Uuid = Uuid::from_u128(0x6f1c_2d0e_8a4b_5c39_9e27_41d8_b3f0_a6c5);
+
+/// Extracts the provider's own identifier for an inbound webhook so it can
+/// be reconciled against the provider's delivery log later.
+pub fn extract_provider_event_id(
+    provider: &str,
+    headers: &HeaderMap,
+    payload: &str,
+) -> Option<String> {
+    match provider {
+        "stripe" => serde_json::from_str::<serde_json::Value>(payload)
+            .ok()?
+            .get("id")?
+            .as_str()
+            .map(str::to_string),
+        "github" => header_string(headers, "x-github-delivery"),
+        _ => {
+            header_string(headers, "webhook-id").or_else(|| header_string(headers, "x-webhook-id"))
+        }
+    }
+}
+
+/// Extracts the event type used to pick a payload schema: Stripe's `type`
+/// field, GitHub's `X-GitHub-Event` header, or a top-level `type` field.
+pub fn extract_event_type(provider: &str, headers: &HeaderMap, payload: &str) -> Option<String> {
+    if provider == "github" {
+        return header_string(headers, "x-github-event");
+    }
+    serde_json::from_str::<serde_json::Value>(payload)
+        .ok()?
+        .get("type")?
+        .as_str()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Metadata fields that hold a customer reference, per provider. Customer
+/// search matches a reference against each of them.
+pub const CUSTOMER_REF_FIELDS: [(&str, &str); 5] = [
+    ("stripe", "customer_id"),
+    ("stripe", "account_id"),
+    ("shopify", "customer_id"),
+    ("shopify", "shop_domain"),
+    ("github", "organization"),
+];
+
+/// Pulls the provider fields investigations filter on, such as the Stripe
+/// account and customer, the GitHub repository, or the Shopify shop
+/// domain. `None` when the provider has no extractor or nothing matched.
+pub fn extract_metadata(
+    provider: &str,
+    headers: &HeaderMap,
+    payload: &str,
+) -> Option<BTreeMap<String, String>> {
+    let value = serde_json::from_str::<Value>(payload).unwrap_or(Value::Null);
+    let fields: Vec<(&str, Option<String>)> = match provider {
+        "stripe" => vec![
+            ("account_id", json_string(&value, &["account"])),
+            (
+                "customer_id",
+                json_string(&value, &["data", "object", "customer"]),
+            ),
+        ],
+        "github" => vec![
+            (
+                "repository",
+                json_string(&value, &["repository", "full_name"]),
+            ),
+            (
+                "organization",
+                json_string(&value, &["organization", "login"]),
+            ),
+            ("sender", json_string(&value, &["sender", "login"])),
+        ],
+        "shopify" => vec![
+            (
+                "shop_domain",
+                header_string(headers, "x-shopify-shop-domain"),
+            ),
+            ("customer_id", json_string(&value, &["customer", "id"])),
+        ],
+        _ => Vec::new(),
+    };
+    let metadata: BTreeMap<String, String> = fields
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.to_string(), value?)))
+        .collect();
+    (!metadata.is_empty()).then_some(metadata)
+}
+
+/// Derives an event ID from the provider's own event ID. The endpoint is
+/// part of the name so a provider event routed to two endpoints is still
+/// stored once for each.
+pub fn deterministic_event_id(endpoint_id: &str, provider: &str, provider_event_id: &str) -> Uuid {
+    let name = [endpoint_id, "/", provider, "/", provider_event_id].concat();
+    Uuid::new_v5(&EVENT_ID_NAMESPACE, name.as_bytes())
+}
+
+/// The string or number at `path`, as text.
+fn json_string(value: &Value, path: &[&str]) -> Option<String> {
+    let found = path
+        .iter()
+        .try_fold(value, |current, segment| current.get(segment))?;
+    match found {
+        Value::String(text) => Some(text.trim().to_string()).filter(|text| !text.is_empty()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn header_string(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tokio::sync::mpsc;
+
+use super::journal::IngestJournal;
+use super::store::{NewEvent, insert_event};
+
+const WRITE_RETRY_BASE_MS: u64 = 50;
+const WRITE_RETRY_MAX_MS: u64 = 5_000;
+
+/// Bounded in-process queue backing fast-ack ingestion. A single writer task
+/// drains it into SQLite; when the writer falls behind the queue fills and
+/// `enqueue` fails, which the handler surfaces as backpressure.
+#[derive(Clone)]
+pub struct IngestQueue {
+    tx: mpsc::Sender<NewEvent>,
+    journal: Option<Arc<IngestJournal>>,
+}
+
+#[derive(Debug)]
+pub enum EnqueueError {
+    Full,
+    Journal(std::io::Error),
+}
+
+impl IngestQueue {
+    pub fn spawn(pool: SqlitePool, capacity: usize, journal: Option<IngestJournal>) -> Self {
+        let (tx, mut rx) = mpsc::channel::<NewEvent>(capacity.max(1));
+        let journal = journal.map(Arc::new);
+
+        let writer_journal = journal.clone();
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                write_until_stored(&pool, &event).await;
+                if let Some(journal) = &writer_journal {
+                    // A failed truncate leaves already-persisted records in
+                    // the journal; replay skips them, so this is harmless.
+                    let _ = journal.mark_persisted().await;
+                }
+            }
+        });
+
+        Self { tx, journal }
+    }
+
+    pub async fn enqueue(&self, event: NewEvent) -> Result<(), EnqueueError> {
+        // Reserving first keeps the slot while the journal syncs, and a
+        // rejected event is never journaled.
+        let permit = self
+            .tx
+            .clone()
+            .try_reserve_owned()
+            .map_err(|_| EnqueueError::Full)?;
+        let Some(journal) = self.journal.clone() else {
+            permit.send(event);
+            return Ok(());
+        };
+
+        // Journaled events must reach the writer even if the request is
+        // dropped mid-append, or the journal would never be emptied.
+        tokio::spawn(async move {
+            journal
+                .append(&event)
+                .await
+                .map_err(EnqueueError::Journal)?;
+            permit.send(event);
+            Ok(())
+        })
+        .await
+        .map_err(|err| EnqueueError::Journal(std::io::Error::other(err)))?
+    }
+}
+
+/// Inserts `event`, retrying with capped backoff until it is stored. Events
+/// queued behind it wait, so while the database is failing the queue fills
+/// and ingest reports backpressure instead of acknowledging events that
+/// would be lost.
+async fn write_until_stored(pool: &SqlitePool, event: &NewEvent) {
+    let mut delay_ms = WRITE_RETRY_BASE_MS;
+    while insert_event(pool, event).await.is_err() {
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        delay_ms = (delay_ms * 2).min(WRITE_RETRY_MAX_MS);
+    }
+}
@@ -0,0 +1,244 @@
+use serde_json::{Map, Value};
+
+/// Errors past this many are dropped; the first few are enough to spot the
+/// drift.
+const MAX_ERRORS: usize = 20;
+
+/// Validates a payload against a JSON Schema and returns one message per
+/// violation, prefixed with the offending path (`$.data.object`). Supports
+/// the commonly used subset: `type`, `enum`, `const`, `required`,
+/// `properties`, `additionalProperties`, `items`, `minLength`, `maxLength`,
+/// `minimum` and `maximum`. Schemas are checked with [`check_schema`] when
+/// they are registered.
+pub fn validate_payload(payload: &str, schema: &Value) -> Vec<String> {
+    let Ok(value) = serde_json::from_str::<Value>(payload) else {
+        return vec!["$: payload is not valid JSON".to_string()];
+    };
+    let mut errors = Vec::new();
+    validate(&value, schema, "$", &mut errors);
+    errors.truncate(MAX_ERRORS);
+    errors
+}
+
+/// Keywords that only describe a schema and never reject a payload.
+const ANNOTATION_KEYWORDS: [&str; 7] = [
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+];
+
+/// Checks that `schema` only uses the keywords [`validate_payload`]
+/// enforces, so a registered schema never silently accepts payloads it
+/// was meant to reject. Returns the first problem, with its schema path.
+pub fn check_schema(schema: &Value) -> Result<(), String> {
+    check(schema, "$")
+}
+
+fn check(schema: &Value, path: &str) -> Result<(), String> {
+    let schema = match schema {
+        Value::Bool(_) => return Ok(()),
+        Value::Object(schema) => schema,
+        _ => return Err(format!("{path}: a schema must be an object or boolean")),
+    };
+    for (keyword, value) in schema {
+        let keyword_path = format!("{path}.{keyword}");
+        let valid = match keyword.as_str() {
+            "type" => match value {
+                Value::String(name) => is_type_name(name),
+                Value::Array(names) => names
+                    .iter()
+                    .all(|name| name.as_str().is_some_and(is_type_name)),
+                _ => false,
+            },
+            "enum" => value.is_array(),
+            "const" => true,
+            "required" => value
+                .as_array()
+                .is_some_and(|names| names.iter().all(Value::is_string)),
+            "properties" => {
+                let Value::Object(properties) = value else {
+                    return Err(format!("{keyword_path}: must be an object"));
+                };
+                for (name, child) in properties {
+                    check(child, &format!("{keyword_path}.{name}"))?;
+                }
+                true
+            }
+            "additionalProperties" | "items" => {
+                check(value, &keyword_path)?;
+                true
+            }
+            "minLength" | "maxLength" => value.is_u64(),
+            "minimum" | "maximum" => value.is_number(),
+            other if ANNOTATION_KEYWORDS.contains(&other) => true,
+            other => return Err(format!("{path}: unsupported keyword {other}")),
+        };
+        if !valid {
+            return Err(format!("{keyword_path}: invalid value"));
+        }
+    }
+    Ok(())
+}
+
+fn is_type_name(name: &str) -> bool {
+    matches!(
+        name,
+        "object" | "array" | "string" | "number" | "integer" | "boolean" | "null"
+    )
+}
+
+fn validate(value: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    if errors.len() >= MAX_ERRORS {
+        return;
+    }
+    let Value::Object(schema) = schema else {
+        if *schema == Value::Bool(false) {
+            errors.push(format!("{path}: no value is allowed here"));
+        }
+        return;
+    };
+
+    if let Some(expected) = schema.get("type")
+        && !matches_type(value, expected)
+    {
+        errors.push(format!(
+            "{path}: expected {}, got {}",
+            describe_type(expected),
+            type_name(value)
+        ));
+        return;
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum")
+        && !allowed.contains(value)
+    {
+        errors.push(format!("{path}: value is not one of the allowed values"));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        errors.push(format!(
+            "{path}: value does not match the expected constant"
+        ));
+    }
+
+    match value {
+        Value::Object(map) => validate_object(map, schema, path, errors),
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate(item, item_schema, &format!("{path}[{index}]"), errors);
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+                && length < min
+            {
+                errors.push(format!("{path}: shorter than {min} characters"));
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+                && length > max
+            {
+                errors.push(format!("{path}: longer than {max} characters"));
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or(f64::NAN);
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64)
+                && number < min
+            {
+                errors.push(format!("{path}: less than {min}"));
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64)
+                && number > max
+            {
+                errors.push(format!("{path}: greater than {max}"));
+            }
+        }
+        Value::Bool(_) | Value::Null => {}
+    }
+}
+
+fn validate_object(
+    map: &Map<String, Value>,
+    schema: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    if let Some(Value::Array(required)) = schema.get("required") {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !map.contains_key(name) {
+                errors.push(format!("{path}: missing required property {name}"));
+            }
+        }
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (name, child) in map {
+        let child_path = format!("{path}.{name}");
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(child_schema) => validate(child, child_schema, &child_path, errors),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    errors.push(format!("{child_path}: property is not allowed"));
+                }
+                Some(extra_schema) => validate(child, extra_schema, &child_path, errors),
+                None => {}
+            },
+        }
+    }
+}
+
+fn matches_type(value: &Value, expected: &Value) -> bool {
+    match expected {
+        Value::String(name) => matches_type_name(value, name),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|name| matches_type_name(value, name)),
+        _ => true,
+    }
+}
+
+fn matches_type_name(value: &Value, name: &str) -> bool {
+    match (name, value) {
+        ("object", Value::Object(_))
+        | ("array", Value::Array(_))
+        | ("string", Value::String(_))
+        | ("number", Value::Number(_))
+        | ("boolean", Value::Bool(_))
+        | ("null", Value::Null) => true,
+        ("integer", Value::Number(number)) => {
+            number.is_i64() || number.is_u64() || number.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+fn describe_type(expected: &Value) -> String {
+    match expected {
+        Value::String(name) => name.clone(),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        other => other.to_string(),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
@@ -0,0 +1,130 @@
+use serde_json::Value;
+
+use crate::types::{ScrubAction, ScrubRule};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Applies `rules` to a JSON payload and returns the re-serialized result.
+/// Payloads that are not JSON are returned unchanged since field paths
+/// cannot be resolved in them.
+pub fn scrub_payload(payload: &str, rules: &[ScrubRule]) -> String {
+    if rules.is_empty() {
+        return payload.to_string();
+    }
+    let Ok(mut value) = serde_json::from_str::<Value>(payload) else {
+        return payload.to_string();
+    };
+
+    for rule in rules {
+        let segments: Vec<&str> = rule.path.split('.').collect();
+        apply_rule(&mut value, &segments, rule.action);
+    }
+
+    serde_json::to_string(&value).unwrap_or_else(|_| payload.to_string())
+}
+
+fn apply_rule(value: &mut Value, segments: &[&str], action: ScrubAction) {
+    let Some((segment, rest)) = segments.split_first() else {
+        return;
+    };
+
+    if let Value::Array(items) = value
+        && *segment != "*"
+        && segment.parse::<usize>().is_err()
+    {
+        for item in items {
+            apply_rule(item, segments, action);
+        }
+        return;
+    }
+
+    if rest.is_empty() {
+        apply_leaf(value, segment, action);
+        return;
+    }
+
+    match value {
+        Value::Object(map) if *segment == "*" => {
+            for child in map.values_mut() {
+                apply_rule(child, rest, action);
+            }
+        }
+        Value::Object(map) => {
+            if let Some(child) = map.get_mut(*segment) {
+                apply_rule(child, rest, action);
+            }
+        }
+        Value::Array(items) if *segment == "*" => {
+            for item in items {
+                apply_rule(item, rest, action);
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item) = segment.parse::<usize>().ok().and_then(|i| items.get_mut(i)) {
+                apply_rule(item, rest, action);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn apply_leaf(value: &mut Value, key: &str, action: ScrubAction) {
+    match value {
+        Value::Object(map) if action == ScrubAction::Remove => {
+            if key == "*" {
+                map.clear();
+            } else {
+                map.remove(key);
+            }
+        }
+        Value::Object(map) if key == "*" => {
+            for child in map.values_mut() {
+                mask_value(child, action);
+            }
+        }
+        Value::Object(map) => {
+            if let Some(child) = map.get_mut(key) {
+                mask_value(child, action);
+            }
+        }
+        Value::Array(items) if key == "*" => {
+            if action == ScrubAction::Remove {
+                items.clear();
+            } else {
+                for item in items {
+                    mask_value(item, action);
+                }
+            }
+        }
+        Value::Array(items) => {
+            let Ok(index) = key.parse::<usize>() else {
+                return;
+            };
+            if action == ScrubAction::Remove {
+                if index < items.len() {
+                    items.remove(index);
+                }
+            } else if let Some(item) = items.get_mut(index) {
+                mask_value(item, action);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn mask_value(value: &mut Value, action: ScrubAction) {
+    *value = match (action, &*value) {
+        (ScrubAction::MaskEmail, Value::String(email)) => Value::String(mask_email(email)),
+        _ => Value::String(REDACTED.to_string()),
+    };
+}
+
+fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) if !local.is_empty() => {
+            let first: String = local.chars().take(1).collect();
+            format!("{first}***@{domain}")
+        }
+        _ => REDACTED.to_string(),
+    }
+}
@@ -0,0 +1,755 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+
+use crate::ingest::{IngestConfig, payload_sha256};
+use crate::types::{
+    DeliveryReceipt, IngestHealth, IngestHealthStatus, IngestMode, PayloadEncoding, ScrubRule,
+    ScrubRuleset, WebhookEventStatus,
+};
+
+#[derive(Debug)]
+pub enum StoreError {
+    Db(sqlx::Error),
+    Conflict(String),
+    NotFound(String),
+    Parse(String),
+    Io(std::io::Error),
+}
+
+impl From<sqlx::Error> for StoreError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Db(err)
+    }
+}
+
+impl From<std::io::Error> for StoreError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IngestSource {
+    pub id: String,
+    pub slug: String,
+    pub provider: String,
+    pub endpoint_id: String,
+    pub secret: String,
+    /// Endpoint secret ID that, when set, replaces the plaintext `secret`.
+    pub signing_secret_id: Option<String>,
+    pub signing_secret_expires_at: Option<String>,
+    /// The secret `signing_secret_id` replaced, still accepted until
+    /// `secondary_signing_secret_expires_at`.
+    pub secondary_signing_secret_id: Option<String>,
+    pub secondary_signing_secret_expires_at: Option<String>,
+    pub ingest_mode: IngestMode,
+    /// Endpoint default delivery TTL applied to events from this source.
+    pub default_ttl_seconds: Option<i64>,
+}
+
+const SOURCE_COLUMNS: &str = "\
+    s.id, \
+    s.slug, \
+    s.provider, \
+    s.endpoint_id, \
+    s.secret, \
+    ep.signing_secret_id, \
+    ep.signing_secret_expires_at, \
+    ep.secondary_signing_secret_id, \
+    ep.secondary_signing_secret_expires_at, \
+    ep.ingest_mode, \
+    ep.default_ttl_seconds";
+
+pub async fn find_source_by_slug(
+    pool: &SqlitePool,
+    slug: &str,
+) -> Result<IngestSource, StoreError> {
+    let row = sqlx::query_as::<_, SourceRow>(&format!(
+        "SELECT {SOURCE_COLUMNS} \
+        FROM sources s \
+        JOIN endpoints ep ON ep.id = s.endpoint_id \
+        WHERE s.slug = ?"
+    ))
+    .bind(slug)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| StoreError::NotFound("source not found".to_string()))?;
+
+    source_from_row(row)
+}
+
+/// Every source feeding `endpoint_id`; empty when the endpoint is unknown.
+pub async fn find_sources_by_endpoint(
+    pool: &SqlitePool,
+    endpoint_id: &str,
+) -> Result<Vec<IngestSource>, StoreError> {
+    let rows = sqlx::query_as::<_, SourceRow>(&format!(
+        "SELECT {SOURCE_COLUMNS} \
+        FROM sources s \
+        JOIN endpoints ep ON ep.id = s.endpoint_id \
+        WHERE s.endpoint_id = ? \
+        ORDER BY s.id"
+    ))
+    .bind(endpoint_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(source_from_row).collect()
+}
+
+fn source_from_row(row: SourceRow) -> Result<IngestSource, StoreError> {
+    Ok(IngestSource {
+        ingest_mode: parse_ingest_mode(&row.ingest_mode)?,
+        id: row.id,
+        slug: row.slug,
+        provider: row.provider,
+        endpoint_id: row.endpoint_id,
+        secret: row.secret,
+        signing_secret_id: row.signing_secret_id,
+        signing_secret_expires_at: row.signing_secret_expires_at,
+        secondary_signing_secret_id: row.secondary_signing_secret_id,
+        secondary_signing_secret_expires_at: row.secondary_signing_secret_expires_at,
+        default_ttl_seconds: row.default_ttl_seconds,
+    })
+}
+
+/// An accepted webhook ready to be written to `webhook_events`. This is
+/// also the record format of the fast-ack ingest journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewEvent {
+    pub id: Uuid,
+    pub endpoint_id: String,
+    pub source_id: String,
+    pub provider: String,
+    pub provider_event_id: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    /// Text body, or the base64 of a binary body per `payload_encoding`.
+    pub payload: String,
+    #[serde(default)]
+    pub payload_encoding: PayloadEncoding,
+    pub received_at: String,
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    #[serde(default)]
+    pub scrub_ruleset_id: Option<Uuid>,
+    #[serde(default)]
+    pub scrub_rule_version: Option<i64>,
+    #[serde(default)]
+    pub event_type: Option<String>,
+    /// Payload schema violations; `None` when valid or unchecked.
+    #[serde(default)]
+    pub schema_errors: Option<Vec<String>>,
+    /// Names of inbound headers left out of `headers` by the storage
+    /// allowlist.
+    #[serde(default)]
+    pub dropped_headers: Vec<String>,
+    /// Provider fields extracted for filtering; `None` when none matched.
+    #[serde(default)]
+    pub metadata: Option<BTreeMap<String, String>>,
+}
+
+impl NewEvent {
+    pub fn from_source(
+        source: &IngestSource,
+        headers: BTreeMap<String, String>,
+        payload: String,
+        provider_event_id: Option<String>,
+    ) -> Self {
+        let now = Utc::now();
+        let expires_at = source
+            .default_ttl_seconds
+            .filter(|ttl| *ttl > 0)
+            .map(|ttl| (now + Duration::seconds(ttl)).to_rfc3339_opts(SecondsFormat::Secs, true));
+
+        Self {
+            id: Uuid::new_v4(),
+            endpoint_id: source.endpoint_id.clone(),
+            source_id: source.id.clone(),
+            provider: source.provider.clone(),
+            provider_event_id,
+            headers,
+            payload,
+            payload_encoding: PayloadEncoding::Utf8,
+            received_at: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+            expires_at,
+            scrub_ruleset_id: None,
+            scrub_rule_version: None,
+            event_type: None,
+            schema_errors: None,
+            dropped_headers: Vec::new(),
+            metadata: None,
+        }
+    }
+}
+
+/// Inserts the event unless a row with the same ID already exists, so
+/// retried writes and journal replays are idempotent. A new event counts
+/// towards its endpoint's daily usage.
+pub async fn insert_event(pool: &SqlitePool, event: &NewEvent) -> Result<(), StoreError> {
+    let headers = serde_json::to_string(&event.headers)
+        .map_err(|err| StoreError::Parse(format!("invalid headers JSON: {err}")))?;
+    let schema_errors = event
+        .schema_errors
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|err| StoreError::Parse(format!("invalid schema errors JSON: {err}")))?;
+    let dropped_headers = (!event.dropped_headers.is_empty())
+        .then(|| serde_json::to_string(&event.dropped_headers))
+        .transpose()
+        .map_err(|err| StoreError::Parse(format!("invalid dropped headers JSON: {err}")))?;
+    let metadata = event
+        .metadata
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|err| StoreError::Parse(format!("invalid metadata JSON: {err}")))?;
+    let mut tx = pool.begin().await?;
+    let binary_payload = match event.payload_encoding {
+        PayloadEncoding::Utf8 => None,
+        PayloadEncoding::Base64 => Some(
+            STANDARD
+                .decode(&event.payload)
+                .map_err(|err| StoreError::Parse(format!("invalid base64 payload: {err}")))?,
+        ),
+    };
+
+    let query = sqlx::query(
+        r"
+        INSERT INTO webhook_events (
+            id,
+            endpoint_id,
+            source_id,
+            provider,
+            provider_event_id,
+            headers,
+            payload,
+            payload_encoding,
+            status,
+            attempts,
+            received_at,
+            expires_at,
+            scrub_ruleset_id,
+            scrub_rule_version,
+            event_type,
+            schema_errors,
+            priority,
+            payload_sha256,
+            dropped_headers,
+            metadata,
+            updated_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'pending', 0, ?, ?, ?, ?, ?, ?, 0, ?, ?, ?, ?)
+        ON CONFLICT(id) DO NOTHING
+        ",
+    )
+    .bind(event.id.to_string())
+    .bind(&event.endpoint_id)
+    .bind(&event.source_id)
+    .bind(&event.provider)
+    .bind(event.provider_event_id.as_deref())
+    .bind(&headers);
+    let payload_hash = payload_sha256(
+        binary_payload
+            .as_deref()
+            .unwrap_or(event.payload.as_bytes()),
+    );
+    let payload_bytes = binary_payload
+        .as_ref()
+        .map_or(event.payload.len(), Vec::len);
+    let query = match binary_payload {
+        Some(bytes) => query.bind(bytes),
+        None => query.bind(&event.payload),
+    };
+    let inserted = query
+        .bind(match event.payload_encoding {
+            PayloadEncoding::Utf8 => "utf8",
+            PayloadEncoding::Base64 => "base64",
+        })
+        .bind(&event.received_at)
+        .bind(event.expires_at.as_deref())
+        .bind(event.scrub_ruleset_id.map(|id| id.to_string()))
+        .bind(event.scrub_rule_version)
+        .bind(event.event_type.as_deref())
+        .bind(schema_errors)
+        .bind(payload_hash)
+        .bind(dropped_headers)
+        .bind(metadata)
+        .bind(&event.received_at)
+        .execute(&mut *tx)
+        .await?;
+
+    if inserted.rows_affected() > 0 {
+        sqlx::query(
+            r"
+            INSERT INTO usage_rollups (endpoint_id, day, events_ingested, bytes_stored)
+            VALUES (?, ?, 1, ?)
+            ON CONFLICT(endpoint_id, day) DO UPDATE SET
+                events_ingested = events_ingested + 1,
+                bytes_stored = bytes_stored + excluded.bytes_stored
+            ",
+        )
+        .bind(&event.endpoint_id)
+        .bind(event.received_at.get(..10).unwrap_or_default())
+        .bind(payload_bytes as i64)
+        .execute(&mut *tx)
+        .await?;
+        add_stored_payload_bytes(&mut tx, &event.id.to_string(), 1).await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[derive(sqlx::FromRow)]
+struct ReceiptRow {
+    id: String,
+    status: String,
+    attempts: i64,
+    received_at: String,
+    last_attempt_at: Option<String>,
+}
+
+/// Delivery state of `provider_event_id` across the copies ingested
+/// through `source_ids`, replays included.
+pub async fn find_delivery_receipt(
+    pool: &SqlitePool,
+    source_ids: &[String],
+    provider_event_id: &str,
+) -> Result<DeliveryReceipt, StoreError> {
+    let source_ids = serde_json::to_string(source_ids)
+        .map_err(|err| StoreError::Parse(format!("invalid source list: {err}")))?;
+    let rows = sqlx::query_as::<_, ReceiptRow>(
+        r"
+        SELECT
+            e.id,
+            e.status,
+            e.attempts,
+            e.received_at,
+            (
+                SELECT MAX(a.finished_at)
+                FROM webhook_attempt_logs_all a
+                WHERE a.event_id = e.id
+            ) AS last_attempt_at
+        FROM webhook_events e
+        WHERE e.source_id IN (SELECT value FROM json_each(?))
+            AND e.provider_event_id = ?
+            AND e.deleted_at IS NULL
+        ORDER BY e.received_at ASC, e.rowid ASC
+        ",
+    )
+    .bind(&source_ids)
+    .bind(provider_event_id)
+    .fetch_all(pool)
+    .await?;
+
+    let (Some(first), Some(latest)) = (rows.first(), rows.last()) else {
+        return Err(StoreError::NotFound("event not found".to_string()));
+    };
+    // A delivered event's last attempt is the one that succeeded.
+    let delivered_at = rows
+        .iter()
+        .filter(|row| row.status == "delivered")
+        .find_map(|row| row.last_attempt_at.clone());
+
+    Ok(DeliveryReceipt {
+        provider_event_id: provider_event_id.to_string(),
+        event_id: Uuid::parse_str(&latest.id)
+            .map_err(|err| StoreError::Parse(format!("invalid event id: {err}")))?,
+        status: parse_event_status(&latest.status)?,
+        delivered: rows.iter().any(|row| row.status == "delivered"),
+        delivered_at,
+        attempts: rows.iter().map(|row| row.attempts).sum(),
+        received_at: first.received_at.clone(),
+        last_attempt_at: rows
+            .iter()
+            .filter_map(|row| row.last_attempt_at.clone())
+            .max(),
+    })
+}
+
+/// Whether ingest should be refused under the backpressure thresholds of
+/// `config`. Runs on every ingest request, so undelivered events are only
+/// counted up to the threshold.
+pub async fn ingest_backpressured(
+    pool: &SqlitePool,
+    config: &IngestConfig,
+) -> Result<bool, StoreError> {
+    if let Some(max) = config.max_pending_events {
+        let pending_events: i64 = sqlx::query_scalar(
+            r"
+            SELECT COUNT(*) FROM (
+                SELECT 1
+                FROM webhook_events
+                WHERE status IN ('pending', 'in_flight', 'requeued') AND deleted_at IS NULL
+                LIMIT ?
+            )
+            ",
+        )
+        .bind(max)
+        .fetch_one(pool)
+        .await?;
+        if pending_events >= max {
+            return Ok(true);
+        }
+    }
+    if let Some(max) = config.max_db_bytes {
+        return Ok(db_bytes(pool).await? >= max);
+    }
+    Ok(false)
+}
+
+async fn db_bytes(pool: &SqlitePool) -> Result<i64, StoreError> {
+    let db_bytes = sqlx::query_scalar(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(db_bytes)
+}
+
+/// Undelivered events and database size against the backpressure
+/// thresholds of `config`. Counts every undelivered event; ingest itself
+/// checks with [`ingest_backpressured`].
+pub async fn ingest_health(
+    pool: &SqlitePool,
+    config: &IngestConfig,
+) -> Result<IngestHealth, StoreError> {
+    let pending_events: i64 = sqlx::query_scalar(
+        r"
+        SELECT COUNT(*)
+        FROM webhook_events
+        WHERE status IN ('pending', 'in_flight', 'requeued') AND deleted_at IS NULL
+        ",
+    )
+    .fetch_one(pool)
+    .await?;
+    let db_bytes = db_bytes(pool).await?;
+
+    let over = |value: i64, max: Option<i64>| max.is_some_and(|max| value >= max);
+    let status =
+        if over(pending_events, config.max_pending_events) || over(db_bytes, config.max_db_bytes) {
+            IngestHealthStatus::Backpressure
+        } else {
+            IngestHealthStatus::Ok
+        };
+    Ok(IngestHealth {
+        status,
+        pending_events,
+        max_pending_events: config.max_pending_events,
+        db_bytes,
+        max_db_bytes: config.max_db_bytes,
+    })
+}
+
+/// Quota of the endpoint's group that an ingest request would exceed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaExceeded {
+    EventsPerDay,
+    StorageBytes,
+}
+
+#[derive(sqlx::FromRow)]
+struct GroupQuotaRow {
+    group_id: String,
+    quota_events_per_day: Option<i64>,
+    quota_storage_bytes: Option<i64>,
+}
+
+/// Adds the stored payload size of `event_id`, times `sign`, to its
+/// endpoint's running total. Called with `1` once an event is stored and
+/// `-1` just before it is soft-deleted.
+pub(crate) async fn add_stored_payload_bytes(
+    conn: &mut SqliteConnection,
+    event_id: &str,
+    sign: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r"
+        INSERT INTO endpoint_storage (endpoint_id, payload_bytes)
+        SELECT endpoint_id, ? * LENGTH(CAST(payload AS BLOB))
+        FROM webhook_events
+        WHERE id = ?
+        ON CONFLICT(endpoint_id) DO UPDATE SET
+            payload_bytes = payload_bytes + excluded.payload_bytes
+        ",
+    )
+    .bind(sign)
+    .bind(event_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Recomputes every endpoint's running payload total from the stored
+/// events, for writes that bypass [`add_stored_payload_bytes`].
+pub(crate) async fn rebuild_stored_payload_bytes(
+    conn: &mut SqliteConnection,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM endpoint_storage")
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        r"
+        INSERT INTO endpoint_storage (endpoint_id, payload_bytes)
+        SELECT endpoint_id, SUM(LENGTH(CAST(payload AS BLOB)))
+        FROM webhook_events
+        WHERE deleted_at IS NULL
+        GROUP BY endpoint_id
+        ",
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Checks an event of `payload_bytes` for `endpoint_id` against its
+/// group's quotas. A refusal is counted on the group, which
+/// `/metrics` reports. Endpoints outside a group have no quota.
+pub async fn check_group_quota(
+    pool: &SqlitePool,
+    endpoint_id: &str,
+    payload_bytes: i64,
+    now: DateTime<Utc>,
+) -> Result<Option<QuotaExceeded>, StoreError> {
+    let Some(quota) = sqlx::query_as::<_, GroupQuotaRow>(
+        r"
+        SELECT g.id AS group_id, g.quota_events_per_day, g.quota_storage_bytes
+        FROM endpoints ep
+        JOIN endpoint_groups g ON g.id = ep.group_id
+        WHERE ep.id = ?
+        ",
+    )
+    .bind(endpoint_id)
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let mut exceeded = None;
+    if let Some(limit) = quota.quota_events_per_day {
+        let events_today: i64 = sqlx::query_scalar(
+            r"
+            SELECT COALESCE(SUM(u.events_ingested), 0)
+            FROM usage_rollups u
+            JOIN endpoints ep ON ep.id = u.endpoint_id
+            WHERE ep.group_id = ? AND u.day = ?
+            ",
+        )
+        .bind(&quota.group_id)
+        .bind(now.format("%Y-%m-%d").to_string())
+        .fetch_one(pool)
+        .await?;
+        if events_today >= limit {
+            exceeded = Some(QuotaExceeded::EventsPerDay);
+        }
+    }
+    if exceeded.is_none()
+        && let Some(limit) = quota.quota_storage_bytes
+    {
+        let stored_bytes: i64 = sqlx::query_scalar(
+            r"
+            SELECT COALESCE(SUM(s.payload_bytes), 0)
+            FROM endpoint_storage s
+            JOIN endpoints ep ON ep.id = s.endpoint_id
+            WHERE ep.group_id = ?
+            ",
+        )
+        .bind(&quota.group_id)
+        .fetch_one(pool)
+        .await?;
+        if stored_bytes + payload_bytes > limit {
+            exceeded = Some(QuotaExceeded::StorageBytes);
+        }
+    }
+
+    if exceeded.is_some() {
+        sqlx::query(
+            r"
+            UPDATE endpoint_groups
+            SET quota_rejections = quota_rejections + 1, quota_exceeded_at = ?
+            WHERE id = ?
+            ",
+        )
+        .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .bind(&quota.group_id)
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(exceeded)
+}
+
+/// How an ingest request for a known source ended, for provider stats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestOutcome {
+    Accepted,
+    RejectedSignature,
+    RejectedSize,
+    RejectedSchema,
+}
+
+/// Counts `outcome` for `provider` in the hour containing `at`.
+pub async fn record_ingest_outcome(
+    pool: &SqlitePool,
+    provider: &str,
+    outcome: IngestOutcome,
+    at: DateTime<Utc>,
+) -> Result<(), StoreError> {
+    let column = match outcome {
+        IngestOutcome::Accepted => "accepted",
+        IngestOutcome::RejectedSignature => "rejected_signature",
+        IngestOutcome::RejectedSize => "rejected_size",
+        IngestOutcome::RejectedSchema => "rejected_schema",
+    };
+    let sql = format!(
+        "INSERT INTO provider_ingest_stats (provider, bucket_start, {column}) VALUES (?, ?, 1) \
+        ON CONFLICT(provider, bucket_start) DO UPDATE SET {column} = {column} + 1"
+    );
+    sqlx::query(&sql)
+        .bind(provider)
+        .bind(at.format("%Y-%m-%dT%H:00:00Z").to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the scrub rules that apply to events for `endpoint_id` from
+/// `provider`: the endpoint's ruleset if it has one, else the provider's.
+pub async fn find_scrub_ruleset(
+    pool: &SqlitePool,
+    endpoint_id: &str,
+    provider: &str,
+) -> Result<Option<ScrubRuleset>, StoreError> {
+    let row = sqlx::query_as::<_, ScrubRulesetRow>(
+        r"
+        SELECT id, provider, endpoint_id, version, rules, updated_at
+        FROM scrub_rulesets
+        WHERE endpoint_id = ? OR provider = ?
+        ORDER BY endpoint_id IS NULL
+        LIMIT 1
+        ",
+    )
+    .bind(endpoint_id)
+    .bind(provider)
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(ScrubRuleset::try_from).transpose()
+}
+
+/// A payload schema as applied at ingestion.
+#[derive(Debug, Clone)]
+pub struct IngestSchema {
+    pub schema: serde_json::Value,
+    pub reject_invalid: bool,
+}
+
+/// Returns the schema for `provider`'s `event_type`, falling back to the
+/// provider's `*` schema.
+pub async fn find_payload_schema(
+    pool: &SqlitePool,
+    provider: &str,
+    event_type: Option<&str>,
+) -> Result<Option<IngestSchema>, StoreError> {
+    let row: Option<(String, bool)> = sqlx::query_as(
+        r"
+        SELECT schema, reject_invalid
+        FROM payload_schemas
+        WHERE provider = ? AND event_type IN (?, '*')
+        ORDER BY event_type = '*'
+        LIMIT 1
+        ",
+    )
+    .bind(provider)
+    .bind(event_type.unwrap_or("*"))
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(|(schema, reject_invalid)| {
+        Ok(IngestSchema {
+            schema: serde_json::from_str(&schema)
+                .map_err(|err| StoreError::Parse(format!("invalid payload schema JSON: {err}")))?,
+            reject_invalid,
+        })
+    })
+    .transpose()
+}
+
+#[derive(sqlx::FromRow)]
+struct ScrubRulesetRow {
+    id: String,
+    provider: Option<String>,
+    endpoint_id: Option<String>,
+    version: i64,
+    rules: String,
+    updated_at: String,
+}
+
+impl TryFrom<ScrubRulesetRow> for ScrubRuleset {
+    type Error = StoreError;
+
+    fn try_from(row: ScrubRulesetRow) -> Result<Self, Self::Error> {
+        let rules: Vec<ScrubRule> = serde_json::from_str(&row.rules)
+            .map_err(|err| StoreError::Parse(format!("invalid scrub rules JSON: {err}")))?;
+        Ok(Self {
+            id: Uuid::parse_str(&row.id)
+                .map_err(|err| StoreError::Parse(format!("invalid ruleset id: {err}")))?,
+            provider: row.provider,
+            endpoint_id: row
+                .endpoint_id
+                .as_deref()
+                .map(Uuid::parse_str)
+                .transpose()
+                .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+            version: row.version,
+            rules,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct SourceRow {
+    id: String,
+    slug: String,
+    provider: String,
+    endpoint_id: String,
+    secret: String,
+    signing_secret_id: Option<String>,
+    signing_secret_expires_at: Option<String>,
+    secondary_signing_secret_id: Option<String>,
+    secondary_signing_secret_expires_at: Option<String>,
+    ingest_mode: String,
+    default_ttl_seconds: Option<i64>,
+}
+
+fn parse_event_status(status: &str) -> Result<WebhookEventStatus, StoreError> {
+    match status {
+        "pending" => Ok(WebhookEventStatus::Pending),
+        "in_flight" => Ok(WebhookEventStatus::InFlight),
+        "requeued" => Ok(WebhookEventStatus::Requeued),
+        "delivered" => Ok(WebhookEventStatus::Delivered),
+        "dead" => Ok(WebhookEventStatus::Dead),
+        "paused" => Ok(WebhookEventStatus::Paused),
+        "expired" => Ok(WebhookEventStatus::Expired),
+        "cancelled" => Ok(WebhookEventStatus::Cancelled),
+        other => Err(StoreError::Parse(format!("unknown status: {other}"))),
+    }
+}
+
+fn parse_ingest_mode(mode: &str) -> Result<IngestMode, StoreError> {
+    match mode {
+        "sync" => Ok(IngestMode::Sync),
+        "fast_ack" => Ok(IngestMode::FastAck),
+        other => Err(StoreError::Parse(format!("unknown ingest mode: {other}"))),
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use uuid::Uuid;
+
+use crate::types::{AnomalyMetric, EndpointAnomaly};
+
+/// Baseline buckets with attempts needed before an endpoint is judged.
+const MIN_BASELINE_SAMPLES: usize = 3;
+/// Lower bound on the failure rate deviation, so a baseline of constant
+/// rates does not turn a single failure into an infinite z-score.
+const MIN_FAILURE_RATE_STDDEV: f64 = 0.05;
+/// Lower bound on the latency deviation, as a share of the mean.
+const MIN_LATENCY_STDDEV_RATIO: f64 = 0.1;
+const MIN_LATENCY_STDDEV_MS: f64 = 1.0;
+
+/// Delivery attempts of one endpoint in one bucket. Bucket `0` is the
+/// oldest baseline bucket and the highest index is the current one.
+#[derive(Debug, Clone)]
+pub struct AttemptBucket {
+    pub endpoint_id: Uuid,
+    pub bucket: i64,
+    pub attempts: i64,
+    pub failures: i64,
+    pub avg_latency_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AnomalyConfig {
+    /// Buckets before the current one that form the baseline.
+    pub baseline_buckets: i64,
+    pub z_threshold: f64,
+    /// Attempts the current bucket needs before it is judged.
+    pub min_attempts: i64,
+}
+
+/// Flags endpoints whose failure rate or mean latency in the current
+/// bucket sits at least `z_threshold` standard deviations above the
+/// baseline. Only spikes are reported, never improvements.
+pub fn detect_anomalies(buckets: &[AttemptBucket], config: AnomalyConfig) -> Vec<EndpointAnomaly> {
+    let mut by_endpoint: BTreeMap<Uuid, Vec<&AttemptBucket>> = BTreeMap::new();
+    for bucket in buckets {
+        by_endpoint
+            .entry(bucket.endpoint_id)
+            .or_default()
+            .push(bucket);
+    }
+
+    let mut anomalies = Vec::new();
+    for (endpoint_id, buckets) in by_endpoint {
+        let Some(current) = buckets
+            .iter()
+            .find(|bucket| bucket.bucket == config.baseline_buckets)
+        else {
+            continue;
+        };
+        if current.attempts < config.min_attempts {
+            continue;
+        }
+        let baseline: Vec<&AttemptBucket> = buckets
+            .iter()
+            .copied()
+            .filter(|bucket| bucket.bucket < config.baseline_buckets && bucket.attempts > 0)
+            .collect();
+        if baseline.len() < MIN_BASELINE_SAMPLES {
+            continue;
+        }
+
+        let failure_rates: Vec<f64> = baseline.iter().map(|bucket| failure_rate(bucket)).collect();
+        let (mean, stddev) = mean_stddev(&failure_rates);
+        let stddev = stddev.max(MIN_FAILURE_RATE_STDDEV);
+        let rate = failure_rate(current);
+        let z_score = (rate - mean) / stddev;
+        if z_score >= config.z_threshold {
+            anomalies.push(EndpointAnomaly {
+                endpoint_id,
+                metric: AnomalyMetric::FailureRate,
+                attempts: current.attempts,
+                current: rate,
+                baseline_mean: mean,
+                baseline_stddev: stddev,
+                z_score,
+            });
+        }
+
+        let latencies: Vec<f64> = baseline
+            .iter()
+            .map(|bucket| bucket.avg_latency_ms)
+            .collect();
+        let (mean, stddev) = mean_stddev(&latencies);
+        let stddev = stddev
+            .max(mean * MIN_LATENCY_STDDEV_RATIO)
+            .max(MIN_LATENCY_STDDEV_MS);
+        let z_score = (current.avg_latency_ms - mean) / stddev;
+        if z_score >= config.z_threshold {
+            anomalies.push(EndpointAnomaly {
+                endpoint_id,
+                metric: AnomalyMetric::LatencyMs,
+                attempts: current.attempts,
+                current: current.avg_latency_ms,
+                baseline_mean: mean,
+                baseline_stddev: stddev,
+                z_score,
+            });
+        }
+    }
+    anomalies
+}
+
+fn failure_rate(bucket: &AttemptBucket) -> f64 {
+    bucket.failures as f64 / bucket.attempts as f64
+}
+
+fn mean_stddev(samples: &[f64]) -> (f64, f64) {
+    let count = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / count;
+    let variance = samples
+        .iter()
+        .map(|sample| (sample - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    (mean, variance.sqrt())
+}
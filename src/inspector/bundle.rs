@@ -0,0 +1,29 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::types::EventBundle;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Hex HMAC-SHA256 of `bundle` under `key`. The MAC covers the bundle as
+/// a JSON value, whose object keys serialize sorted, so a bundle verifies
+/// no matter how its fields were ordered on the way back in.
+pub fn sign_bundle(key: &str, bundle: &EventBundle) -> Option<String> {
+    let mac = bundle_mac(key, bundle)?;
+    Some(hex::encode(mac.finalize().into_bytes()))
+}
+
+pub fn verify_bundle(key: &str, bundle: &EventBundle, signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    bundle_mac(key, bundle).is_some_and(|mac| mac.verify_slice(&signature).is_ok())
+}
+
+fn bundle_mac(key: &str, bundle: &EventBundle) -> Option<HmacSha256> {
+    let value = serde_json::to_value(bundle).ok()?;
+    let bytes = serde_json::to_vec(&value).ok()?;
+    let mut mac = HmacSha256::new_from_slice(key.as_bytes()).ok()?;
+    mac.update(&bytes);
+    Some(mac)
+}
@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Header name fragments whose values are credentials or signatures.
+const SENSITIVE_HEADER_PARTS: &[&str] = &[
+    "authorization",
+    "cookie",
+    "signature",
+    "secret",
+    "token",
+    "api-key",
+    "apikey",
+    "password",
+];
+
+/// A recorded attempt request rendered as a shell command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurlCommand {
+    pub command: String,
+    /// Names of the headers whose values were replaced, in header order.
+    pub masked_headers: Vec<String>,
+}
+
+pub fn is_sensitive_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_HEADER_PARTS
+        .iter()
+        .any(|part| name.contains(part))
+}
+
+/// Renders a POST of `body` with `headers` to `target_url`. Sensitive header
+/// values are masked unless `reveal` is set.
+pub fn render_curl(
+    target_url: &str,
+    headers: &BTreeMap<String, String>,
+    body: &str,
+    reveal: bool,
+) -> CurlCommand {
+    let mut command = format!("curl -X POST {}", shell_quote(target_url));
+    let mut masked_headers = Vec::new();
+
+    for (name, value) in headers {
+        let value = if !reveal && is_sensitive_header(name) {
+            masked_headers.push(name.clone());
+            REDACTED
+        } else {
+            value.as_str()
+        };
+        command.push_str(" \\\n  -H ");
+        command.push_str(&shell_quote(&format!("{name}: {value}")));
+    }
+    command.push_str(" \\\n  --data-raw ");
+    command.push_str(&shell_quote(body));
+
+    CurlCommand {
+        command,
+        masked_headers,
+    }
+}
+
+/// Single-quotes `value` for POSIX shells.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
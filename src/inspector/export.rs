@@ -0,0 +1,82 @@
+use crate::types::EventExportRecord;
+
+use super::store::status_to_str;
+
+/// CSV header row, in `EventExportRecord` field order.
+pub const CSV_COLUMNS: &[&str] = &[
+    "id",
+    "endpoint_id",
+    "provider",
+    "provider_event_id",
+    "event_type",
+    "status",
+    "attempts",
+    "received_at",
+    "updated_at",
+    "last_attempt_at",
+    "last_response_status",
+    "last_duration_ms",
+    "total_duration_ms",
+    "last_error",
+];
+
+pub fn render_ndjson(records: &[EventExportRecord]) -> Result<String, serde_json::Error> {
+    let mut out = String::new();
+    for record in records {
+        out.push_str(&serde_json::to_string(record)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Renders RFC 4180 CSV with CRLF line endings. Missing values are empty
+/// fields.
+pub fn render_csv(records: &[EventExportRecord]) -> String {
+    [
+        CSV_COLUMNS.join(",").as_str(),
+        "\r\n",
+        &render_csv_rows(records),
+    ]
+    .concat()
+}
+
+/// CSV rows of `render_csv` without the header, for writing an export in
+/// chunks.
+pub fn render_csv_rows(records: &[EventExportRecord]) -> String {
+    let mut out = String::new();
+    for record in records {
+        let fields = [
+            record.id.to_string(),
+            record.endpoint_id.to_string(),
+            record.provider.clone(),
+            record.provider_event_id.clone().unwrap_or_default(),
+            record.event_type.clone().unwrap_or_default(),
+            status_to_str(record.status).to_string(),
+            record.attempts.to_string(),
+            record.received_at.clone(),
+            record.updated_at.clone().unwrap_or_default(),
+            record.last_attempt_at.clone().unwrap_or_default(),
+            optional_number(record.last_response_status),
+            optional_number(record.last_duration_ms),
+            optional_number(record.total_duration_ms),
+            record.last_error.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_quote(field)).collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn optional_number(value: Option<i64>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Quotes a field that contains a delimiter, quote, or line break.
+fn csv_quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
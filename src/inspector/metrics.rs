@@ -0,0 +1,243 @@
+use chrono::NaiveDate;
+
+use crate::types::{
+    AnomalyMetric, EndpointAnomaly, GroupQuota, ScheduledExportStatus, SloAttainment, TlsExpiry,
+};
+
+/// Prometheus text exposition content type.
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Renders SLO attainment in the Prometheus text format, one series per
+/// endpoint. Endpoints without deliveries in the window have no
+/// attainment sample.
+pub fn render_slo_metrics(stats: &[SloAttainment]) -> String {
+    let mut out = String::new();
+    push_family(
+        &mut out,
+        "receiver_slo_objective_ratio",
+        "gauge",
+        "Share of events that should be delivered within the SLO target.",
+        stats
+            .iter()
+            .map(|slo| (slo, Some(slo.objective_percent / 100.0))),
+    );
+    push_family(
+        &mut out,
+        "receiver_slo_attainment_ratio",
+        "gauge",
+        "Share of events in the stats window delivered within the SLO target.",
+        stats
+            .iter()
+            .map(|slo| (slo, slo.attainment_percent.map(|percent| percent / 100.0))),
+    );
+    push_family(
+        &mut out,
+        "receiver_slo_overdue_events",
+        "gauge",
+        "Undelivered events already older than the SLO target.",
+        stats.iter().map(|slo| (slo, Some(slo.overdue as f64))),
+    );
+    push_family(
+        &mut out,
+        "receiver_slo_breaches_total",
+        "counter",
+        "Deliveries that missed the SLO target.",
+        stats
+            .iter()
+            .map(|slo| (slo, Some(slo.breaches_total as f64))),
+    );
+    out
+}
+
+/// Renders flagged anomalies as z-scores, one series per endpoint and
+/// metric. Endpoints without an anomaly have no sample.
+pub fn render_anomaly_metrics(anomalies: &[EndpointAnomaly]) -> String {
+    let name = "receiver_anomaly_z_score";
+    let mut out = [
+        "# HELP ",
+        name,
+        " Deviation of an endpoint metric above its baseline, in standard deviations.\n",
+        "# TYPE ",
+        name,
+        " gauge\n",
+    ]
+    .concat();
+    for anomaly in anomalies {
+        let endpoint_id = anomaly.endpoint_id.to_string();
+        let metric = match anomaly.metric {
+            AnomalyMetric::FailureRate => "failure_rate",
+            AnomalyMetric::LatencyMs => "latency_ms",
+        };
+        let value = anomaly.z_score.to_string();
+        let line = [
+            name,
+            "{endpoint_id=\"",
+            &endpoint_id,
+            "\",metric=\"",
+            metric,
+            "\"} ",
+            &value,
+            "\n",
+        ];
+        out.push_str(&line.concat());
+    }
+    out
+}
+
+/// Renders certificate expiry per endpoint, plus a 0/1 series that is 1
+/// once the certificate is within `warning_days` of expiring.
+pub fn render_tls_metrics(expiries: &[TlsExpiry], warning_days: i64) -> String {
+    let days_name = "receiver_endpoint_tls_expiry_days";
+    let expiring_name = "receiver_endpoint_tls_expiring";
+    let mut days = [
+        "# HELP ",
+        days_name,
+        " Whole days until the endpoint certificate expires, negative once expired.\n",
+        "# TYPE ",
+        days_name,
+        " gauge\n",
+    ]
+    .concat();
+    let mut expiring = [
+        "# HELP ",
+        expiring_name,
+        " Whether the endpoint certificate expires within the TLS warning window.\n",
+        "# TYPE ",
+        expiring_name,
+        " gauge\n",
+    ]
+    .concat();
+    for expiry in expiries {
+        let endpoint_id = expiry.endpoint_id.to_string();
+        let remaining = expiry.days_remaining.to_string();
+        let flag = if expiry.days_remaining < warning_days {
+            "1"
+        } else {
+            "0"
+        };
+        days.push_str(
+            &[
+                days_name,
+                "{endpoint_id=\"",
+                &endpoint_id,
+                "\"} ",
+                &remaining,
+                "\n",
+            ]
+            .concat(),
+        );
+        expiring.push_str(
+            &[
+                expiring_name,
+                "{endpoint_id=\"",
+                &endpoint_id,
+                "\"} ",
+                flag,
+                "\n",
+            ]
+            .concat(),
+        );
+    }
+    days + &expiring
+}
+
+/// Renders ingest quota refusals per group, plus a 0/1 series that is 1
+/// once the group was refused today, to alert on.
+pub fn render_quota_metrics(quotas: &[GroupQuota]) -> String {
+    let rejections_name = "receiver_group_quota_rejections_total";
+    let exceeded_name = "receiver_group_quota_exceeded";
+    let mut rejections = [
+        "# HELP ",
+        rejections_name,
+        " Ingest requests refused because the group was over a quota.\n",
+        "# TYPE ",
+        rejections_name,
+        " counter\n",
+    ]
+    .concat();
+    let mut exceeded = [
+        "# HELP ",
+        exceeded_name,
+        " Whether the group was refused ingest for a quota today (UTC).\n",
+        "# TYPE ",
+        exceeded_name,
+        " gauge\n",
+    ]
+    .concat();
+    for quota in quotas {
+        let group_id = quota.group_id.to_string();
+        let total = quota.rejections_total.to_string();
+        let flag = if quota.exceeded_today { "1" } else { "0" };
+        rejections.push_str(
+            &[
+                rejections_name,
+                "{group_id=\"",
+                &group_id,
+                "\"} ",
+                &total,
+                "\n",
+            ]
+            .concat(),
+        );
+        exceeded.push_str(&[exceeded_name, "{group_id=\"", &group_id, "\"} ", flag, "\n"].concat());
+    }
+    rejections + &exceeded
+}
+
+/// Renders the nightly export's progress: failed checks since the last
+/// success, and the start of the last exported day so a stalled export
+/// can be alerted on by age.
+pub fn render_export_metrics(status: &ScheduledExportStatus) -> String {
+    let failures_name = "receiver_scheduled_export_consecutive_failures";
+    let last_day_name = "receiver_scheduled_export_last_day_timestamp_seconds";
+    let failures = status.consecutive_failures.to_string();
+    let mut out = [
+        "# HELP ",
+        failures_name,
+        " Scheduled export checks that failed since the last successful export.\n",
+        "# TYPE ",
+        failures_name,
+        " gauge\n",
+        failures_name,
+        " ",
+        &failures,
+        "\n",
+        "# HELP ",
+        last_day_name,
+        " Start of the last UTC day the scheduled export wrote, as a Unix timestamp.\n",
+        "# TYPE ",
+        last_day_name,
+        " gauge\n",
+    ]
+    .concat();
+    if let Some(day) = status
+        .last_exported_day
+        .as_deref()
+        .and_then(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+    {
+        let timestamp = day.and_time(chrono::NaiveTime::MIN).and_utc().timestamp();
+        out.push_str(&[last_day_name, " ", &timestamp.to_string(), "\n"].concat());
+    }
+    out
+}
+
+fn push_family<'a>(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    samples: impl Iterator<Item = (&'a SloAttainment, Option<f64>)>,
+) {
+    let header = [
+        "# HELP ", name, " ", help, "\n", "# TYPE ", name, " ", kind, "\n",
+    ];
+    out.push_str(&header.concat());
+    for (slo, value) in samples {
+        if let Some(value) = value {
+            let endpoint_id = slo.endpoint_id.to_string();
+            let value = value.to_string();
+            let line = [name, "{endpoint_id=\"", &endpoint_id, "\"} ", &value, "\n"];
+            out.push_str(&line.concat());
+        }
+    }
+}
@@ -1,6 +1,45 @@
+pub mod anomaly;
+pub mod bundle;
+pub mod curl;
+pub mod export;
+pub mod metrics;
+pub mod scope;
+pub mod share;
 pub mod store;
 
+pub use anomaly::{AnomalyConfig, AttemptBucket, detect_anomalies};
+pub use bundle::{sign_bundle, verify_bundle};
+pub use curl::{CurlCommand, is_sensitive_header, render_curl};
+pub use export::{CSV_COLUMNS, render_csv, render_csv_rows, render_ndjson};
+pub use metrics::{
+    METRICS_CONTENT_TYPE, render_anomaly_metrics, render_export_metrics, render_quota_metrics,
+    render_slo_metrics, render_tls_metrics,
+};
+pub use scope::EndpointScope;
+pub use share::ShareLinkConfig;
 pub use store::{
-    InspectorCursor, ListEventsParams, ListEventsResult, StoreError, get_event, list_attempts,
-    list_events, replay_event,
+    AttemptRequest, BulkReplay, DeadEventTarget, DeadEventWindow, ExportEventsParams,
+    IdempotencyClaim, InspectorCursor, ListEventsParams, ListEventsResult, QueuedEventFilter,
+    ScrubScope, StoreError, UsageParams, attempt_buckets, cancel_events_batch,
+    claim_idempotency_key, clear_fault_injection, close_circuit, complete_idempotency_key,
+    consume_replay_confirmation, count_events_to_reprioritize, count_group_replay,
+    count_purgeable_events, count_queued_events, create_endpoint_group, delete_event, event_bundle,
+    export_events, find_missing_provider_events, finish_operation, get_attempt_request,
+    get_endpoint_attempt_log_sampling, get_endpoint_backoff, get_endpoint_canary,
+    get_endpoint_connect_policy, get_endpoint_group, get_endpoint_health, get_endpoint_profile,
+    get_endpoint_redirect_policy, get_endpoint_region, get_endpoint_secrets, get_endpoint_shadow,
+    get_endpoint_slo, get_endpoint_timeouts, get_event, get_event_lineage, get_fault_injection,
+    get_group_quota, get_payload_schema, get_scrub_ruleset, group_quotas,
+    issue_replay_confirmation, list_attempts, list_delivery_windows, list_endpoint_groups,
+    list_endpoint_revisions, list_endpoint_secret_ids, list_events, list_maintenance_windows,
+    list_operations, list_shadow_attempts, provider_ingest_stats, purge_events_batch, record_audit,
+    release_idempotency_key, replay_dead_window, replay_event, replay_group,
+    reprioritize_events_batch, rotate_endpoint_secret, run_doctor, search_customer_events,
+    set_delivery_windows, set_dispatch_paused, set_endpoint_attempt_log_sampling,
+    set_endpoint_backoff, set_endpoint_canary, set_endpoint_check, set_endpoint_connect_policy,
+    set_endpoint_group, set_endpoint_profile, set_endpoint_redirect_policy, set_endpoint_region,
+    set_endpoint_shadow, set_endpoint_slo, set_endpoint_target, set_endpoint_timeouts,
+    set_fault_injection, set_group_paused, set_group_quota, set_group_rate_limit,
+    set_maintenance_windows, set_payload_schema, set_scrub_rules, slo_stats, start_operation,
+    storage_report, summarize_errors, tls_expiries, undo_operation, usage_rollups,
 };
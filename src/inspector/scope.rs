@@ -0,0 +1,40 @@
+use sqlx::{QueryBuilder, Sqlite};
+use uuid::Uuid;
+
+/// Endpoints an inspector token may read and act on. Scoped tokens have
+/// every store query narrowed to their endpoints, so out-of-scope events
+/// and endpoints look as if they do not exist.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EndpointScope {
+    #[default]
+    All,
+    Endpoints(Vec<Uuid>),
+}
+
+impl EndpointScope {
+    pub fn is_restricted(&self) -> bool {
+        matches!(self, Self::Endpoints(_))
+    }
+
+    pub fn allows(&self, endpoint_id: Uuid) -> bool {
+        match self {
+            Self::All => true,
+            Self::Endpoints(ids) => ids.contains(&endpoint_id),
+        }
+    }
+
+    /// Appends ` AND <column> IN (...)` when the scope is restricted.
+    pub(crate) fn push_predicate(&self, query: &mut QueryBuilder<'_, Sqlite>, column: &str) {
+        let Self::Endpoints(ids) = self else {
+            return;
+        };
+        query.push(" AND ");
+        query.push(column);
+        query.push(" IN (");
+        let mut list = query.separated(", ");
+        for id in ids {
+            list.push_bind(id.to_string());
+        }
+        list.push_unseparated(")");
+    }
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Key and public address used to mint read-only event share links.
+#[derive(Debug, Clone)]
+pub struct ShareLinkConfig {
+    pub secret: String,
+    /// Prepended to minted share paths, e.g. `https://receiver.example.com`.
+    pub base_url: Option<String>,
+}
+
+impl ShareLinkConfig {
+    /// Public path that serves the shared event until `expires` (Unix
+    /// seconds).
+    pub fn share_path(&self, event_id: Uuid, expires: i64) -> Option<String> {
+        let mac = share_mac(&self.secret, event_id, expires)?;
+        let signature = hex::encode(mac.finalize().into_bytes());
+        Some(format!(
+            "/share/events/{event_id}?expires={expires}&sig={signature}"
+        ))
+    }
+
+    pub fn share_url(&self, event_id: Uuid, expires: i64) -> Option<String> {
+        let path = self.share_path(event_id, expires)?;
+        Some(match &self.base_url {
+            Some(base_url) => format!("{}{path}", base_url.trim_end_matches('/')),
+            None => path,
+        })
+    }
+
+    /// Checks the HMAC over event ID and expiry, and that the link has not
+    /// expired at `now`.
+    pub fn verify(
+        &self,
+        event_id: Uuid,
+        expires: i64,
+        signature: &str,
+        now: DateTime<Utc>,
+    ) -> bool {
+        if expires <= now.timestamp() {
+            return false;
+        }
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        share_mac(&self.secret, event_id, expires)
+            .is_some_and(|mac| mac.verify_slice(&signature).is_ok())
+    }
+}
+
+fn share_mac(secret: &str, event_id: Uuid, expires: i64) -> Option<HmacSha256> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).ok()?;
+    mac.update(format!("{event_id}.{expires}").as_bytes());
+    Some(mac)
+}
@@ -1,13 +1,31 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 
-use chrono::Utc;
-use sqlx::{QueryBuilder, SqlitePool};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{SecondsFormat, Utc};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
 use uuid::Uuid;
 
+use crate::ingest::{CUSTOMER_REF_FIELDS, add_stored_payload_bytes, payload_sha256};
+use crate::inspector::{AttemptBucket, EndpointScope};
 use crate::types::{
-    GetEventResponse, ListAttemptsResponse, ReplayEventResponse, TargetCircuitState,
-    TargetCircuitStatus, WebhookAttemptErrorKind, WebhookAttemptLog, WebhookEvent,
-    WebhookEventListItem, WebhookEventStatus, WebhookEventSummary,
+    AddressFamily, AttemptTiming, BackoffStrategy, BundleEndpoint, CircuitTransition,
+    ConnectPolicy, CustomerEventMatch, CustomerSearchResponse, DeleteEventResponse, DeliveryWindow,
+    DeliveryWindowsResponse, DispatchControlResponse, DoctorIssue, DoctorIssueKind, DoctorReport,
+    EndpointAttemptLogSampling, EndpointBackoff, EndpointCanary, EndpointCheck,
+    EndpointCheckMethod, EndpointConnectPolicy, EndpointGroup, EndpointGroupAssignment,
+    EndpointHealth, EndpointProfile, EndpointRedirectPolicy, EndpointRegion, EndpointRevision,
+    EndpointRevisionChange, EndpointRevisionsResponse, EndpointSecrets, EndpointShadow,
+    EndpointSlo, EndpointTimeouts, ErrorSummaryBucket, EventBundle, EventExportRecord,
+    EventLineageResponse, EventListField, FaultInjection, GetEventResponse, GroupQuota,
+    InspectorOperation, LastAttemptSummary, LineageEvent, ListAttemptsResponse,
+    ListShadowAttemptsResponse, MaintenanceWindow, MaintenanceWindowsResponse, OperationFailure,
+    OperationKind, OperationStatus, PayloadEncoding, PayloadIntegrity, PayloadPreview,
+    PayloadSchema, ProviderIngestStats, RedirectMode, RedirectPolicy, RegionMode,
+    ReplayAttemptBudget, ReplayEventResponse, ResponseCapture, ScrubRule, ScrubRuleset,
+    ShadowAttemptLog, SloAttainment, StorageProjection, StorageReport, TableStorage,
+    TargetCircuitState, TargetCircuitStatus, TlsExpiry, UndoOperationResponse, UsageRollup,
+    WebhookAttemptErrorKind, WebhookAttemptLog, WebhookEvent, WebhookEventListItem,
+    WebhookEventStatus, WebhookEventSummary,
 };
 
 #[derive(Debug)]
@@ -36,9 +54,61 @@ pub struct ListEventsParams {
     pub before: Option<InspectorCursor>,
     pub status: Option<WebhookEventStatus>,
     pub endpoint_id: Option<Uuid>,
+    pub source_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
     pub provider: Option<String>,
+    /// Only events stuck `in_flight`: lease already expired, or leased more
+    /// than this many minutes ago.
+    pub stuck_after_minutes: Option<i64>,
+    /// `Some(true)` keeps only events that failed payload schema
+    /// validation, `Some(false)` only those that did not.
+    pub schema_invalid: Option<bool>,
+    /// `Some(true)` keeps only replays, `Some(false)` only original events.
+    pub is_replay: Option<bool>,
+    /// `Some(true)` keeps only events that have been replayed at least once,
+    /// `Some(false)` only those that have not.
+    pub has_replays: Option<bool>,
+    /// Joins each event's latest attempt to fill `last_attempt`.
+    pub include_last_attempt: bool,
+    /// Only events whose `updated_at` is at or after this UTC timestamp.
+    pub updated_since: Option<String>,
+    /// Only events with an attempt made by this worker build.
+    pub worker_version: Option<String>,
+    /// Fields the caller will read; `None` means all of them. The endpoint
+    /// join is skipped unless `TargetUrl`, `EndpointName` or
+    /// `EndpointDescription` is listed, leaving those fields empty, and the
+    /// circuit join unless `Circuit` is, leaving `circuit` unset.
+    pub fields: Option<Vec<EventListField>>,
+    /// Only events whose extracted `metadata` has each key set to the
+    /// paired value.
+    pub metadata: Vec<(String, String)>,
 }
 
+/// Filters for `export_events`. Time bounds are UTC RFC 3339 timestamps
+/// on `received_at`; `received_before` is exclusive.
+#[derive(Debug, Clone, Default)]
+pub struct ExportEventsParams {
+    pub status: Option<WebhookEventStatus>,
+    pub endpoint_id: Option<Uuid>,
+    pub provider: Option<String>,
+    pub received_since: Option<String>,
+    pub received_before: Option<String>,
+}
+
+/// Filters for `usage_rollups`. `from` and `to` are inclusive UTC dates
+/// such as `2024-01-31`.
+#[derive(Debug, Clone, Default)]
+pub struct UsageParams {
+    pub from: String,
+    pub to: String,
+    pub endpoint_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+}
+
+/// Characters of the payload, or bytes of a binary one, kept in a list
+/// item's `payload_preview`.
+const PAYLOAD_PREVIEW_CHARS: i64 = 200;
+
 #[derive(Debug, Clone)]
 pub struct ListEventsResult {
     pub events: Vec<WebhookEventListItem>,
@@ -47,29 +117,124 @@ pub struct ListEventsResult {
 
 pub async fn list_events(
     pool: &SqlitePool,
+    access: &EndpointScope,
     params: &ListEventsParams,
 ) -> Result<ListEventsResult, StoreError> {
+    let wants = |field| {
+        params
+            .fields
+            .as_ref()
+            .is_none_or(|fields| fields.contains(&field))
+    };
+    let join_endpoint = wants(EventListField::TargetUrl)
+        || wants(EventListField::EndpointName)
+        || wants(EventListField::EndpointDescription)
+        || params.group_id.is_some();
+    let join_circuit = wants(EventListField::Circuit);
+    let preview = wants(EventListField::PayloadPreview);
+
     let mut query = QueryBuilder::new(
         "SELECT \
             e.id, \
             e.endpoint_id, \
             e.replayed_from_event_id, \
+            e.source_id, \
             e.provider, \
             e.status, \
             e.attempts, \
             e.received_at, \
             e.next_attempt_at, \
             e.last_error, \
-            ep.target_url, \
-            c.state AS circuit_state, \
+            e.updated_at, ",
+    );
+    if join_endpoint {
+        query.push(
+            "ep.target_url, \
+            ep.name AS endpoint_name, \
+            ep.description AS endpoint_description, ",
+        );
+    } else {
+        query.push(
+            "NULL AS target_url, \
+            NULL AS endpoint_name, \
+            NULL AS endpoint_description, ",
+        );
+    }
+    if join_circuit {
+        query.push(
+            "c.state AS circuit_state, \
             c.open_until AS circuit_open_until, \
             c.consecutive_failures AS circuit_consecutive_failures, \
-            c.last_failure_at AS circuit_last_failure_at \
-        FROM webhook_events e \
-        JOIN endpoints ep ON ep.id = e.endpoint_id \
-        LEFT JOIN target_circuit_states c ON c.endpoint_id = e.endpoint_id \
-        WHERE 1 = 1",
-    );
+            c.last_failure_at AS circuit_last_failure_at",
+        );
+    } else {
+        query.push(
+            "NULL AS circuit_state, \
+            NULL AS circuit_open_until, \
+            NULL AS circuit_consecutive_failures, \
+            NULL AS circuit_last_failure_at",
+        );
+    }
+    if preview {
+        // Only the head of the payload leaves the database.
+        query.push(", CAST(substr(e.payload, 1, ");
+        query.push_bind(PAYLOAD_PREVIEW_CHARS);
+        query.push(
+            ") AS BLOB) AS preview_head, \
+            length(e.payload) > ",
+        );
+        query.push_bind(PAYLOAD_PREVIEW_CHARS);
+        query.push(
+            " AS preview_truncated, \
+            length(CAST(e.payload AS BLOB)) AS preview_size_bytes, \
+            e.payload_encoding AS preview_encoding, \
+            CASE WHEN e.payload_encoding = 'utf8' THEN json_valid(e.payload) ELSE 0 END \
+                AS preview_is_json, \
+            json_extract(e.headers, '$.\"content-type\"') AS preview_content_type",
+        );
+    } else {
+        query.push(
+            ", NULL AS preview_head, \
+            NULL AS preview_truncated, \
+            NULL AS preview_size_bytes, \
+            NULL AS preview_encoding, \
+            NULL AS preview_is_json, \
+            NULL AS preview_content_type",
+        );
+    }
+    if params.include_last_attempt {
+        query.push(
+            ", la.response_status AS last_attempt_status, \
+            la.error_kind AS last_attempt_error_kind, \
+            la.finished_at AS last_attempt_finished_at",
+        );
+    } else {
+        query.push(
+            ", NULL AS last_attempt_status, \
+            NULL AS last_attempt_error_kind, \
+            NULL AS last_attempt_finished_at",
+        );
+    }
+    query.push(" FROM webhook_events e");
+    if join_endpoint {
+        query.push(" JOIN endpoints ep ON ep.id = e.endpoint_id");
+    }
+    if join_circuit {
+        query.push(" LEFT JOIN target_circuit_states c ON c.endpoint_id = e.endpoint_id");
+    }
+    if params.include_last_attempt {
+        query.push(
+            " LEFT JOIN webhook_attempt_logs_all la ON la.id = ( \
+                SELECT a.id \
+                FROM webhook_attempt_logs_all a \
+                WHERE a.event_id = e.id \
+                ORDER BY a.attempt_no DESC, a.started_at DESC \
+                LIMIT 1 \
+            )",
+        );
+    }
+    query.push(" WHERE e.deleted_at IS NULL");
+    access.push_predicate(&mut query, "e.endpoint_id");
 
     if let Some(status) = params.status {
         query.push(" AND e.status = ");
@@ -81,11 +246,85 @@ pub async fn list_events(
         query.push_bind(endpoint_id.to_string());
     }
 
+    if let Some(source_id) = params.source_id {
+        query.push(" AND e.source_id = ");
+        query.push_bind(source_id.to_string());
+    }
+
+    if let Some(group_id) = params.group_id {
+        query.push(" AND ep.group_id = ");
+        query.push_bind(group_id.to_string());
+    }
+
     if let Some(provider) = params.provider.as_deref() {
         query.push(" AND e.provider = ");
         query.push_bind(provider);
     }
 
+    if let Some(minutes) = params.stuck_after_minutes {
+        let now = Utc::now();
+        query.push(" AND e.status = 'in_flight' AND (e.lease_expires_at <= ");
+        query.push_bind(now.to_rfc3339_opts(SecondsFormat::Secs, true));
+        query.push(" OR e.leased_at <= ");
+        query.push_bind(
+            (now - chrono::Duration::minutes(minutes)).to_rfc3339_opts(SecondsFormat::Secs, true),
+        );
+        query.push(")");
+    }
+
+    match params.schema_invalid {
+        Some(true) => {
+            query.push(" AND e.schema_errors IS NOT NULL");
+        }
+        Some(false) => {
+            query.push(" AND e.schema_errors IS NULL");
+        }
+        None => {}
+    }
+
+    match params.is_replay {
+        Some(true) => {
+            query.push(" AND e.replayed_from_event_id IS NOT NULL");
+        }
+        Some(false) => {
+            query.push(" AND e.replayed_from_event_id IS NULL");
+        }
+        None => {}
+    }
+
+    if let Some(has_replays) = params.has_replays {
+        query.push(if has_replays {
+            " AND EXISTS"
+        } else {
+            " AND NOT EXISTS"
+        });
+        query.push(
+            " (SELECT 1 FROM webhook_events r \
+                WHERE r.replayed_from_event_id = e.id AND r.deleted_at IS NULL)",
+        );
+    }
+
+    if let Some(updated_since) = params.updated_since.as_deref() {
+        query.push(" AND e.updated_at >= ");
+        query.push_bind(updated_since);
+    }
+
+    if let Some(worker_version) = params.worker_version.as_deref() {
+        query.push(
+            " AND EXISTS (SELECT 1 FROM webhook_attempt_logs_all a \
+                WHERE a.event_id = e.id AND a.worker_version = ",
+        );
+        query.push_bind(worker_version);
+        query.push(")");
+    }
+
+    for (key, value) in &params.metadata {
+        query.push(" AND CAST(json_extract(e.metadata, ");
+        query.push_bind(format!("$.\"{key}\""));
+        query.push(") AS TEXT) = ");
+        query.push_bind(value);
+    }
+
     if let Some(cursor) = &params.before {
         query.push(" AND (e.received_at < ");
         query.push_bind(&cursor.received_at);
@@ -125,50 +364,223 @@ pub async fn list_events(
     })
 }
 
-pub async fn get_event(pool: &SqlitePool, event_id: Uuid) -> Result<GetEventResponse, StoreError> {
-    let row = sqlx::query_as::<_, GetEventRow>(
-        r"
-        SELECT
-            e.id,
-            e.endpoint_id,
-            e.provider,
-            e.headers,
-            e.payload,
-            e.status,
-            e.attempts,
-            e.received_at,
-            e.next_attempt_at,
-            e.replayed_from_event_id,
-            e.lease_expires_at,
-            e.leased_by,
-            e.last_error,
-            ep.target_url,
-            c.state AS circuit_state,
-            c.open_until AS circuit_open_until,
-            c.consecutive_failures AS circuit_consecutive_failures,
-            c.last_failure_at AS circuit_last_failure_at
-        FROM webhook_events e
-        JOIN endpoints ep ON ep.id = e.endpoint_id
-        LEFT JOIN target_circuit_states c ON c.endpoint_id = e.endpoint_id
-        WHERE e.id = ?
-        ",
-    )
-    .bind(event_id.to_string())
-    .fetch_optional(pool)
-    .await?
-    .ok_or_else(|| StoreError::NotFound("event not found".to_string()))?;
+/// Finds the most recent events, newest first, whose extracted metadata
+/// holds `customer_ref` in one of the provider's customer fields.
+pub async fn search_customer_events(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    customer_ref: &str,
+    limit: i64,
+) -> Result<CustomerSearchResponse, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT \
+            e.id, \
+            e.endpoint_id, \
+            e.replayed_from_event_id, \
+            e.source_id, \
+            e.provider, \
+            e.status, \
+            e.attempts, \
+            e.received_at, \
+            e.next_attempt_at, \
+            e.last_error, \
+            e.updated_at, \
+            e.metadata \
+        FROM webhook_events e \
+        WHERE e.deleted_at IS NULL \
+          AND e.metadata IS NOT NULL",
+    );
+    access.push_predicate(&mut query, "e.endpoint_id");
+    query.push(" AND (");
+    for (index, (provider, field)) in CUSTOMER_REF_FIELDS.iter().enumerate() {
+        if index > 0 {
+            query.push(" OR ");
+        }
+        query.push("(e.provider = ");
+        query.push_bind(*provider);
+        query.push(" AND CAST(json_extract(e.metadata, ");
+        query.push_bind(format!("$.\"{field}\""));
+        query.push(") AS TEXT) = ");
+        query.push_bind(customer_ref);
+        query.push(")");
+    }
+    query.push(") ORDER BY e.received_at DESC, e.id DESC LIMIT ");
+    query.push_bind(limit);
+
+    let rows: Vec<CustomerEventRow> = query.build_query_as().fetch_all(pool).await?;
+    let mut events = Vec::with_capacity(rows.len());
+    for row in rows {
+        let metadata: BTreeMap<String, String> = serde_json::from_str(&row.metadata)
+            .map_err(|err| StoreError::Parse(format!("invalid metadata JSON: {err}")))?;
+        let matched_field = CUSTOMER_REF_FIELDS
+            .iter()
+            .find(|(provider, field)| {
+                *provider == row.provider
+                    && metadata.get(*field).map(String::as_str) == Some(customer_ref)
+            })
+            .map(|(_, field)| (*field).to_string())
+            .unwrap_or_default();
+        events.push(CustomerEventMatch {
+            event: WebhookEventSummary {
+                id: parse_event_id(&row.id)?,
+                endpoint_id: Uuid::parse_str(&row.endpoint_id)
+                    .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+                replayed_from_event_id: parse_optional_uuid(
+                    "replayed_from_event_id",
+                    row.replayed_from_event_id.as_deref(),
+                )?,
+                source_id: parse_optional_uuid("source id", row.source_id.as_deref())?,
+                provider: row.provider,
+                status: parse_status(&row.status)?,
+                attempts: row.attempts,
+                received_at: row.received_at,
+                next_attempt_at: row.next_attempt_at,
+                last_error: row.last_error,
+                updated_at: row.updated_at,
+                payload_preview: None,
+            },
+            matched_field,
+        });
+    }
+
+    Ok(CustomerSearchResponse {
+        customer_ref: customer_ref.to_string(),
+        events,
+    })
+}
+
+/// Returns every matching event, oldest first, flattened for export.
+/// Durations are measured from each attempt's reported start and finish.
+pub async fn export_events(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    params: &ExportEventsParams,
+) -> Result<Vec<EventExportRecord>, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT \
+            e.id, \
+            e.endpoint_id, \
+            e.provider, \
+            e.provider_event_id, \
+            e.event_type, \
+            e.status, \
+            e.attempts, \
+            e.received_at, \
+            e.updated_at, \
+            e.last_error, \
+            la.started_at AS last_attempt_at, \
+            la.response_status AS last_response_status, \
+            CAST(ROUND((julianday(la.finished_at) - julianday(la.started_at)) * 86400000) AS INTEGER) \
+                AS last_duration_ms, \
+            ( \
+                SELECT CAST(ROUND(SUM(julianday(a.finished_at) - julianday(a.started_at)) * 86400000) AS INTEGER) \
+                FROM webhook_attempt_logs_all a \
+                WHERE a.event_id = e.id \
+            ) AS total_duration_ms \
+        FROM webhook_events e \
+        LEFT JOIN webhook_attempt_logs_all la ON la.id = ( \
+            SELECT a.id \
+            FROM webhook_attempt_logs_all a \
+            WHERE a.event_id = e.id \
+            ORDER BY a.attempt_no DESC, a.started_at DESC \
+            LIMIT 1 \
+        ) \
+        WHERE e.deleted_at IS NULL",
+    );
+    access.push_predicate(&mut query, "e.endpoint_id");
+
+    if let Some(status) = params.status {
+        query.push(" AND e.status = ");
+        query.push_bind(status_to_str(status));
+    }
+    if let Some(endpoint_id) = params.endpoint_id {
+        query.push(" AND e.endpoint_id = ");
+        query.push_bind(endpoint_id.to_string());
+    }
+    if let Some(provider) = params.provider.as_deref() {
+        query.push(" AND e.provider = ");
+        query.push_bind(provider);
+    }
+    if let Some(since) = params.received_since.as_deref() {
+        query.push(" AND e.received_at >= ");
+        query.push_bind(since);
+    }
+    if let Some(before) = params.received_before.as_deref() {
+        query.push(" AND e.received_at < ");
+        query.push_bind(before);
+    }
+    query.push(" ORDER BY e.received_at ASC, e.id ASC");
+
+    let rows: Vec<ExportEventRow> = query.build_query_as().fetch_all(pool).await?;
+    rows.into_iter().map(EventExportRecord::try_from).collect()
+}
+
+pub async fn get_event(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    event_id: Uuid,
+) -> Result<GetEventResponse, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT \
+            e.id, \
+            e.endpoint_id, \
+            e.provider, \
+            e.provider_event_id, \
+            e.headers, \
+            e.payload, \
+            e.payload_encoding, \
+            e.payload_sha256, \
+            e.scrub_ruleset_id, \
+            e.scrub_rule_version, \
+            e.event_type, \
+            e.schema_errors, \
+            e.dropped_headers, \
+            e.metadata, \
+            e.status, \
+            e.attempts, \
+            e.received_at, \
+            e.next_attempt_at, \
+            e.expires_at, \
+            e.replayed_from_event_id, \
+            e.source_id, \
+            e.lease_expires_at, \
+            e.leased_by, \
+            e.last_error, \
+            e.updated_at, \
+            ep.target_url, \
+            ep.name AS endpoint_name, \
+            ep.description AS endpoint_description, \
+            c.state AS circuit_state, \
+            c.open_until AS circuit_open_until, \
+            c.consecutive_failures AS circuit_consecutive_failures, \
+            c.last_failure_at AS circuit_last_failure_at \
+        FROM webhook_events e \
+        JOIN endpoints ep ON ep.id = e.endpoint_id \
+        LEFT JOIN target_circuit_states c ON c.endpoint_id = e.endpoint_id \
+        WHERE e.deleted_at IS NULL \
+          AND e.id = ",
+    );
+    query.push_bind(event_id.to_string());
+    access.push_predicate(&mut query, "e.endpoint_id");
+
+    let row = query
+        .build_query_as::<GetEventRow>()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("event not found".to_string()))?;
 
     get_event_from_row(row)
 }
 
 pub async fn list_attempts(
     pool: &SqlitePool,
+    access: &EndpointScope,
     event_id: Uuid,
 ) -> Result<ListAttemptsResponse, StoreError> {
-    let rows = sqlx::query_as::<_, ListAttemptsRow>(
-        r"
-        SELECT \
+    let mut query = QueryBuilder::new(
+        "SELECT \
             e.id AS event_id, \
+            COALESCE(e.replay_root_event_id, e.id) AS delivery_id, \
             a.id AS attempt_id, \
             a.attempt_no AS attempt_no, \
             a.started_at AS started_at, \
@@ -179,21 +591,40 @@ pub async fn list_attempts(
             a.response_headers AS response_headers, \
             a.response_body AS response_body, \
             a.error_kind AS error_kind, \
-            a.error_message AS error_message \
-        FROM webhook_events e
-        LEFT JOIN webhook_attempt_logs a ON a.event_id = e.id
-        WHERE e.id = ?
-        ORDER BY a.started_at ASC, a.attempt_no ASC
-        ",
-    )
-    .bind(event_id.to_string())
-    .fetch_all(pool)
-    .await?;
+            a.error_message AS error_message, \
+            a.response_capture AS response_capture, \
+            a.target_revision AS target_revision, \
+            a.target_url AS target_url, \
+            a.final_url AS final_url, \
+            a.peer_address AS peer_address, \
+            a.dns_ms AS dns_ms, \
+            a.connect_ms AS connect_ms, \
+            a.tls_ms AS tls_ms, \
+            a.ttfb_ms AS ttfb_ms, \
+            a.total_ms AS total_ms, \
+            a.worker_id AS worker_id, \
+            a.worker_version AS worker_version, \
+            a.worker_region AS worker_region, \
+            a.sample_rate AS sample_rate, \
+            e.unlogged_attempts AS unlogged_attempts, \
+            e.unlogged_sample_rate AS unlogged_sample_rate \
+        FROM webhook_events e \
+        LEFT JOIN webhook_attempt_logs_all a ON a.event_id = e.id \
+        WHERE e.deleted_at IS NULL \
+          AND e.id = ",
+    );
+    query.push_bind(event_id.to_string());
+    access.push_predicate(&mut query, "e.endpoint_id");
+    query.push(" ORDER BY a.started_at ASC, a.attempt_no ASC");
+
+    let rows: Vec<ListAttemptsRow> = query.build_query_as().fetch_all(pool).await?;
 
     if rows.is_empty() {
         return Err(StoreError::NotFound("event not found".to_string()));
     }
 
+    let unlogged_attempts = rows[0].unlogged_attempts;
+    let unlogged_sample_rate = rows[0].unlogged_sample_rate;
     let mut attempts = Vec::with_capacity(rows.len());
     for row in rows {
         if let Some(attempt) = attempt_from_optional_row(row)? {
@@ -201,37 +632,189 @@ pub async fn list_attempts(
         }
     }
 
-    Ok(ListAttemptsResponse { attempts })
+    Ok(ListAttemptsResponse {
+        attempts,
+        unlogged_attempts,
+        unlogged_sample_rate,
+    })
+}
+
+pub async fn list_shadow_attempts(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    event_id: Uuid,
+) -> Result<ListShadowAttemptsResponse, StoreError> {
+    let mut query =
+        QueryBuilder::new("SELECT 1 FROM webhook_events WHERE deleted_at IS NULL AND id = ");
+    query.push_bind(event_id.to_string());
+    access.push_predicate(&mut query, "endpoint_id");
+    let exists: Option<i64> = query.build_query_scalar().fetch_optional(pool).await?;
+    if exists.is_none() {
+        return Err(StoreError::NotFound("event not found".to_string()));
+    }
+
+    let rows = sqlx::query_as::<_, ShadowAttemptRow>(
+        r"
+        SELECT
+            id,
+            event_id,
+            worker_id,
+            target_url,
+            started_at,
+            finished_at,
+            response_status,
+            response_body,
+            error_kind,
+            error_message
+        FROM shadow_attempt_logs
+        WHERE event_id = ?
+        ORDER BY started_at ASC
+        ",
+    )
+    .bind(event_id.to_string())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ListShadowAttemptsResponse {
+        attempts: rows
+            .into_iter()
+            .map(ShadowAttemptLog::try_from)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// The request an attempt sent and where it went. Attempts logged before
+/// endpoint revisions existed fall back to the endpoint's current target.
+#[derive(Debug, Clone)]
+pub struct AttemptRequest {
+    pub attempt_id: Uuid,
+    pub event_id: Uuid,
+    pub endpoint_id: Uuid,
+    pub target_url: String,
+    pub request_headers: BTreeMap<String, String>,
+    pub request_body: String,
+}
+
+pub async fn get_attempt_request(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    attempt_id: Uuid,
+) -> Result<AttemptRequest, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT \
+            a.event_id AS event_id, \
+            e.endpoint_id AS endpoint_id, \
+            COALESCE(a.target_url, r.target_url, ep.target_url) AS target_url, \
+            ep.target_kind AS target_kind, \
+            a.request_headers AS request_headers, \
+            a.request_body AS request_body \
+        FROM webhook_attempt_logs_all a \
+        JOIN webhook_events e ON e.id = a.event_id \
+        JOIN endpoints ep ON ep.id = e.endpoint_id \
+        LEFT JOIN endpoint_revisions r \
+            ON r.endpoint_id = e.endpoint_id AND r.revision = a.target_revision \
+        WHERE e.deleted_at IS NULL \
+          AND a.id = ",
+    );
+    query.push_bind(attempt_id.to_string());
+    access.push_predicate(&mut query, "e.endpoint_id");
+
+    let row: AttemptRequestRow = query
+        .build_query_as()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("attempt not found".to_string()))?;
+
+    if row.target_kind != "http" {
+        return Err(StoreError::Conflict(format!(
+            "curl export is not available for {} endpoints",
+            row.target_kind
+        )));
+    }
+
+    Ok(AttemptRequest {
+        attempt_id,
+        event_id: Uuid::parse_str(&row.event_id)
+            .map_err(|err| StoreError::Parse(format!("invalid event id: {err}")))?,
+        endpoint_id: Uuid::parse_str(&row.endpoint_id)
+            .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+        target_url: row.target_url,
+        request_headers: serde_json::from_str(&row.request_headers)
+            .map_err(|err| StoreError::Parse(format!("invalid request headers JSON: {err}")))?,
+        request_body: row.request_body,
+    })
 }
 
+/// Copies the event into a new pending event. With `skip_if_pending`, an
+/// existing undelivered copy of the same event is a `pending_replay_exists`
+/// conflict instead. With [`ReplayAttemptBudget::CarryOver`] the copy starts
+/// from the source's attempt counter, so it only gets the attempts left of
+/// `max_attempts`. With `loop_limit`, a chain that already holds that many
+/// undeleted replays without any delivery is a `replay_loop_detected`
+/// conflict.
 pub async fn replay_event(
     pool: &SqlitePool,
+    access: &EndpointScope,
     event_id: Uuid,
     reset_circuit: bool,
+    skip_if_pending: bool,
+    attempt_budget: ReplayAttemptBudget,
+    loop_limit: Option<i64>,
+) -> Result<ReplayEventResponse, StoreError> {
+    let options = ReplayOptions {
+        reset_circuit,
+        skip_if_pending,
+        attempt_budget,
+        loop_limit,
+        next_attempt_at: None,
+    };
+    replay_copy(pool, access, event_id, &options).await
+}
+
+/// How `replay_copy` copies an event. `next_attempt_at` holds the copy
+/// back until then, so drip-fed bulk replays are never due early.
+struct ReplayOptions {
+    reset_circuit: bool,
+    skip_if_pending: bool,
+    attempt_budget: ReplayAttemptBudget,
+    loop_limit: Option<i64>,
+    next_attempt_at: Option<String>,
+}
+
+async fn replay_copy(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    event_id: Uuid,
+    options: &ReplayOptions,
 ) -> Result<ReplayEventResponse, StoreError> {
     let now = Utc::now();
+    let now_str = now.to_rfc3339_opts(SecondsFormat::Secs, true);
 
     let mut tx = pool.begin().await?;
 
-    let row = sqlx::query_as::<_, ReplaySourceRow>(
-        r"
-        SELECT \
+    let mut query = QueryBuilder::new(
+        "SELECT \
             id, \
             endpoint_id, \
+            source_id, \
             provider, \
-            headers, \
-            payload, \
             status, \
+            attempts, \
             received_at, \
-            lease_expires_at \
-        FROM webhook_events
-        WHERE id = ?
-        ",
-    )
-    .bind(event_id.to_string())
-    .fetch_optional(&mut *tx)
-    .await?
-    .ok_or_else(|| StoreError::NotFound("event not found".to_string()))?;
+            lease_expires_at, \
+            COALESCE(replay_root_event_id, id) AS root_event_id \
+        FROM webhook_events \
+        WHERE deleted_at IS NULL \
+          AND id = ",
+    );
+    query.push_bind(event_id.to_string());
+    access.push_predicate(&mut query, "endpoint_id");
+
+    let row = query
+        .build_query_as::<ReplaySourceRow>()
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("event not found".to_string()))?;
 
     let status = parse_status(&row.status)?;
     if status == WebhookEventStatus::InFlight {
@@ -246,6 +829,50 @@ pub async fn replay_event(
         }
     }
 
+    if options.skip_if_pending {
+        let pending_replay: Option<String> = sqlx::query_scalar(
+            r"
+            SELECT id
+            FROM webhook_events
+            WHERE replayed_from_event_id = ?
+              AND status IN ('pending', 'requeued', 'in_flight')
+              AND deleted_at IS NULL
+            LIMIT 1
+            ",
+        )
+        .bind(event_id.to_string())
+        .fetch_optional(&mut *tx)
+        .await?;
+        if pending_replay.is_some() {
+            return Err(StoreError::Conflict("pending_replay_exists".to_string()));
+        }
+    }
+
+    if let Some(limit) = options.loop_limit {
+        // Deleted events leave the chain, as they do in its lineage.
+        let (replays, delivered): (i64, i64) = sqlx::query_as(
+            r"
+            SELECT COUNT(replayed_from_event_id),
+                   COALESCE(SUM(status = 'delivered'), 0)
+            FROM webhook_events
+            WHERE (replay_root_event_id = ?1 OR id = ?1)
+              AND deleted_at IS NULL
+            ",
+        )
+        .bind(&row.root_event_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if delivered == 0 && replays >= limit {
+            return Err(StoreError::Conflict("replay_loop_detected".to_string()));
+        }
+    }
+
+    let initial_attempts = match options.attempt_budget {
+        ReplayAttemptBudget::Reset => 0,
+        ReplayAttemptBudget::CarryOver => row.attempts,
+    };
+
+    // Copied in SQL so binary payloads keep their BLOB storage.
     let new_event_id = Uuid::new_v4();
     sqlx::query(
         r"
@@ -253,90 +880,3827 @@ pub async fn replay_event(
             id,
             endpoint_id,
             replayed_from_event_id,
+            replay_root_event_id,
+            source_id,
             provider,
+            provider_event_id,
             headers,
             payload,
+            payload_encoding,
+            payload_sha256,
+            dropped_headers,
+            metadata,
+            event_type,
+            priority,
             status,
             attempts,
+            carried_attempts,
             received_at,
             next_attempt_at,
             lease_expires_at,
             leased_by,
-            last_error
+            last_error,
+            updated_at
+        )
+        SELECT
+            ?,
+            endpoint_id,
+            id,
+            COALESCE(replay_root_event_id, id),
+            source_id,
+            provider,
+            provider_event_id,
+            headers,
+            payload,
+            payload_encoding,
+            payload_sha256,
+            dropped_headers,
+            metadata,
+            event_type,
+            COALESCE(priority, 0),
+            'pending',
+            ?,
+            ?,
+            received_at,
+            ?,
+            NULL,
+            NULL,
+            NULL,
+            ?
+        FROM webhook_events
+        WHERE id = ?
+        ",
+    )
+    .bind(new_event_id.to_string())
+    .bind(initial_attempts)
+    .bind(initial_attempts)
+    .bind(&options.next_attempt_at)
+    .bind(&now_str)
+    .bind(event_id.to_string())
+    .execute(&mut *tx)
+    .await?;
+    add_stored_payload_bytes(&mut tx, &new_event_id.to_string(), 1).await?;
+
+    if options.reset_circuit {
+        sqlx::query(
+            r"
+            UPDATE target_circuit_states
+            SET state = 'closed',
+                open_until = NULL,
+                consecutive_failures = 0,
+                last_failure_at = NULL
+            WHERE endpoint_id = ?
+            ",
         )
-        VALUES (?, ?, ?, ?, ?, ?, 'pending', 0, ?, NULL, NULL, NULL, NULL)
+        .bind(&row.endpoint_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let endpoint_row = sqlx::query_as::<_, ReplayEndpointRow>(
+        r"
+        SELECT ep.target_url,
+               c.state AS circuit_state,
+               c.open_until AS circuit_open_until,
+               c.consecutive_failures AS circuit_consecutive_failures,
+               c.last_failure_at AS circuit_last_failure_at
+        FROM endpoints ep
+        LEFT JOIN target_circuit_states c ON c.endpoint_id = ep.id
+        WHERE ep.id = ?
+        ",
+    )
+    .bind(&row.endpoint_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    tx.commit().await?;
+
+    let summary = WebhookEventSummary {
+        id: new_event_id,
+        endpoint_id: Uuid::parse_str(&row.endpoint_id)
+            .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+        replayed_from_event_id: Some(event_id),
+        source_id: parse_optional_uuid("source id", row.source_id.as_deref())?,
+        provider: row.provider,
+        status: WebhookEventStatus::Pending,
+        attempts: initial_attempts,
+        received_at: row.received_at,
+        next_attempt_at: options.next_attempt_at.clone(),
+        last_error: None,
+        updated_at: Some(now_str),
+        payload_preview: None,
+    };
+
+    let circuit = map_circuit(
+        &row.endpoint_id,
+        endpoint_row.circuit_state.as_deref(),
+        endpoint_row.circuit_open_until.as_deref(),
+        endpoint_row.circuit_consecutive_failures,
+        endpoint_row.circuit_last_failure_at.as_deref(),
+    )?;
+
+    Ok(ReplayEventResponse {
+        event: summary,
+        circuit,
+    })
+}
+
+/// Lists the replay chain the event belongs to, root first, with the
+/// attempts logged for each event and their sum across the chain. Deleted
+/// events are left out of both.
+pub async fn get_event_lineage(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    event_id: Uuid,
+) -> Result<EventLineageResponse, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT COALESCE(replay_root_event_id, id) \
+        FROM webhook_events \
+        WHERE deleted_at IS NULL \
+          AND id = ",
+    );
+    query.push_bind(event_id.to_string());
+    access.push_predicate(&mut query, "endpoint_id");
+    let root_id: String = query
+        .build_query_scalar()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("event not found".to_string()))?;
+
+    let rows = sqlx::query_as::<_, LineageRow>(
+        r"
+        SELECT e.id,
+               e.replayed_from_event_id,
+               e.status,
+               e.attempts,
+               e.unlogged_attempts
+                   + (SELECT COUNT(*) FROM webhook_attempt_logs_all a WHERE a.event_id = e.id)
+                   AS attempts_made,
+               e.updated_at
+        FROM webhook_events e
+        WHERE (e.replay_root_event_id = ?1 OR e.id = ?1)
+          AND e.deleted_at IS NULL
+        ",
+    )
+    .bind(&root_id)
+    .fetch_all(pool)
+    .await?;
+
+    let parents: BTreeMap<&str, Option<&str>> = rows
+        .iter()
+        .map(|row| (row.id.as_str(), row.replayed_from_event_id.as_deref()))
+        .collect();
+    let mut events = Vec::with_capacity(rows.len());
+    for row in &rows {
+        // Deleted events break the chain; depth counts the hops still visible.
+        let mut depth = 0;
+        let mut parent = row.replayed_from_event_id.as_deref();
+        while let Some(id) = parent
+            && let Some(next) = parents.get(id)
+        {
+            depth += 1;
+            parent = *next;
+        }
+        events.push(LineageEvent {
+            id: parse_event_id(&row.id)?,
+            replayed_from_event_id: parse_optional_uuid(
+                "replayed_from_event_id",
+                row.replayed_from_event_id.as_deref(),
+            )?,
+            depth,
+            status: parse_status(&row.status)?,
+            attempts: row.attempts,
+            attempts_made: row.attempts_made,
+            updated_at: row.updated_at.clone(),
+        });
+    }
+    events.sort_by(|a, b| {
+        a.depth
+            .cmp(&b.depth)
+            .then_with(|| a.updated_at.cmp(&b.updated_at))
+    });
+    let cumulative_attempts = events.iter().map(|event| event.attempts_made).sum();
+
+    Ok(EventLineageResponse {
+        root_event_id: parse_event_id(&root_id)?,
+        events,
+        cumulative_attempts,
+    })
+}
+
+/// Appends one row to the audit log.
+pub async fn record_audit(
+    pool: &SqlitePool,
+    actor: &str,
+    action: &str,
+    event_id: Option<Uuid>,
+    endpoint_id: Option<Uuid>,
+) -> Result<(), StoreError> {
+    sqlx::query(
+        r"
+        INSERT INTO audit_log (id, actor, action, event_id, endpoint_id, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(actor)
+    .bind(action)
+    .bind(event_id.map(|id| id.to_string()))
+    .bind(endpoint_id.map(|id| id.to_string()))
+    .bind(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// How long an idempotency key is remembered before it may be reused.
+const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+
+/// Outcome of [`claim_idempotency_key`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyClaim {
+    /// First use of the key: run the request, then store its response.
+    New,
+    /// The request already completed; holds its JSON response.
+    Completed(String),
+}
+
+/// Claims `key` for `actor`'s request identified by `request_hash`. A key
+/// still in progress, or already used for a different request, is a
+/// conflict.
+pub async fn claim_idempotency_key(
+    pool: &SqlitePool,
+    actor: &str,
+    key: &str,
+    request_hash: &str,
+) -> Result<IdempotencyClaim, StoreError> {
+    let now = Utc::now();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM idempotency_keys WHERE created_at < ?")
+        .bind(
+            (now - chrono::Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS))
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+        )
+        .execute(&mut *tx)
+        .await?;
+
+    let inserted = sqlx::query(
+        r"
+        INSERT INTO idempotency_keys (actor, key, request_hash, response, created_at)
+        VALUES (?, ?, ?, NULL, ?)
+        ON CONFLICT(actor, key) DO NOTHING
+        ",
+    )
+    .bind(actor)
+    .bind(key)
+    .bind(request_hash)
+    .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+    .execute(&mut *tx)
+    .await?;
+
+    let claim = if inserted.rows_affected() == 1 {
+        IdempotencyClaim::New
+    } else {
+        let (stored_hash, response): (String, Option<String>) = sqlx::query_as(
+            "SELECT request_hash, response FROM idempotency_keys WHERE actor = ? AND key = ?",
+        )
+        .bind(actor)
+        .bind(key)
+        .fetch_one(&mut *tx)
+        .await?;
+        if stored_hash != request_hash {
+            return Err(StoreError::Conflict(
+                "idempotency key was already used for a different request".to_string(),
+            ));
+        }
+        let Some(response) = response else {
+            return Err(StoreError::Conflict(
+                "a request with this idempotency key is still in progress".to_string(),
+            ));
+        };
+        IdempotencyClaim::Completed(response)
+    };
+
+    tx.commit().await?;
+    Ok(claim)
+}
+
+/// Stores the response of the request that claimed `key`.
+pub async fn complete_idempotency_key(
+    pool: &SqlitePool,
+    actor: &str,
+    key: &str,
+    response: &str,
+) -> Result<(), StoreError> {
+    sqlx::query("UPDATE idempotency_keys SET response = ? WHERE actor = ? AND key = ?")
+        .bind(response)
+        .bind(actor)
+        .bind(key)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Drops an unfinished claim so a failed request can be retried with the
+/// same key.
+pub async fn release_idempotency_key(
+    pool: &SqlitePool,
+    actor: &str,
+    key: &str,
+) -> Result<(), StoreError> {
+    sqlx::query("DELETE FROM idempotency_keys WHERE actor = ? AND key = ? AND response IS NULL")
+        .bind(actor)
+        .bind(key)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Issues a single-use token confirming `actor`'s replay request
+/// identified by `request_hash`, which matched `matched` events. Returns
+/// the token and its expiry.
+pub async fn issue_replay_confirmation(
+    pool: &SqlitePool,
+    actor: &str,
+    request_hash: &str,
+    matched: i64,
+    ttl_minutes: i64,
+) -> Result<(String, String), StoreError> {
+    let now = Utc::now();
+    let token = Uuid::new_v4().simple().to_string();
+    let expires_at =
+        (now + chrono::Duration::minutes(ttl_minutes)).to_rfc3339_opts(SecondsFormat::Secs, true);
+    sqlx::query("DELETE FROM replay_confirmations WHERE expires_at <= ?")
+        .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        r"
+        INSERT INTO replay_confirmations (token, actor, request_hash, matched, expires_at)
+        VALUES (?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&token)
+    .bind(actor)
+    .bind(request_hash)
+    .bind(matched)
+    .bind(&expires_at)
+    .execute(pool)
+    .await?;
+    Ok((token, expires_at))
+}
+
+/// Uses up a confirmation token issued to `actor` for the same request and
+/// returns how many events the dry run matched.
+pub async fn consume_replay_confirmation(
+    pool: &SqlitePool,
+    actor: &str,
+    token: &str,
+    request_hash: &str,
+) -> Result<i64, StoreError> {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let matched: Option<i64> = sqlx::query_scalar(
+        r"
+        DELETE FROM replay_confirmations
+        WHERE token = ? AND actor = ? AND request_hash = ? AND expires_at > ?
+        RETURNING matched
+        ",
+    )
+    .bind(token)
+    .bind(actor)
+    .bind(request_hash)
+    .bind(now)
+    .fetch_optional(pool)
+    .await?;
+    matched.ok_or_else(|| {
+        StoreError::Conflict(
+            "confirm_token is unknown, expired, or was issued for a different replay".to_string(),
+        )
+    })
+}
+
+/// Returns the provider event IDs from `provider_event_ids` that have no
+/// matching ingested event for `provider`, preserving the input order.
+pub async fn find_missing_provider_events(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    provider: &str,
+    provider_event_ids: &[String],
+) -> Result<Vec<String>, StoreError> {
+    if provider_event_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query = QueryBuilder::new(
+        "SELECT DISTINCT provider_event_id \
+        FROM webhook_events \
+        WHERE provider = ",
+    );
+    query.push_bind(provider);
+    query.push(" AND provider_event_id IN (");
+    let mut ids = query.separated(", ");
+    for id in provider_event_ids {
+        ids.push_bind(id);
+    }
+    ids.push_unseparated(")");
+    access.push_predicate(&mut query, "endpoint_id");
+
+    let found: HashSet<String> = query
+        .build_query_scalar::<String>()
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+
+    Ok(provider_event_ids
+        .iter()
+        .filter(|id| !found.contains(*id))
+        .cloned()
+        .collect())
+}
+
+/// Flips the global dispatch pause flag checked by `lease_events`.
+pub async fn set_dispatch_paused(
+    pool: &SqlitePool,
+    paused: bool,
+) -> Result<DispatchControlResponse, StoreError> {
+    let updated_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    sqlx::query(
+        r"
+        INSERT INTO dispatch_control (id, paused, updated_at)
+        VALUES (1, ?, ?)
+        ON CONFLICT(id) DO UPDATE SET
+            paused = excluded.paused,
+            updated_at = excluded.updated_at
+        ",
+    )
+    .bind(paused)
+    .bind(&updated_at)
+    .execute(pool)
+    .await?;
+
+    Ok(DispatchControlResponse {
+        paused,
+        updated_at: Some(updated_at),
+    })
+}
+
+pub async fn list_maintenance_windows(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<MaintenanceWindowsResponse, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let windows = sqlx::query_as::<_, MaintenanceWindowRow>(
+        r"
+        SELECT day_of_week, start_minute, duration_minutes
+        FROM endpoint_maintenance_windows
+        WHERE endpoint_id = ?
+        ORDER BY day_of_week, start_minute
+        ",
+    )
+    .bind(endpoint_id.to_string())
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| MaintenanceWindow {
+        day_of_week: row.day_of_week,
+        start_minute: row.start_minute,
+        duration_minutes: row.duration_minutes,
+    })
+    .collect();
+
+    Ok(MaintenanceWindowsResponse {
+        endpoint_id,
+        windows,
+    })
+}
+
+/// Replaces the endpoint's maintenance schedule with `windows`.
+pub async fn set_maintenance_windows(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    windows: &[MaintenanceWindow],
+) -> Result<MaintenanceWindowsResponse, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM endpoint_maintenance_windows WHERE endpoint_id = ?")
+        .bind(endpoint_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+    for window in windows {
+        sqlx::query(
+            r"
+            INSERT INTO endpoint_maintenance_windows (
+                id,
+                endpoint_id,
+                day_of_week,
+                start_minute,
+                duration_minutes
+            )
+            VALUES (?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(endpoint_id.to_string())
+        .bind(window.day_of_week)
+        .bind(window.start_minute)
+        .bind(window.duration_minutes)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    list_maintenance_windows(pool, access, endpoint_id).await
+}
+
+pub async fn list_delivery_windows(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<DeliveryWindowsResponse, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let utc_offset_minutes: i64 =
+        sqlx::query_scalar("SELECT delivery_window_utc_offset_minutes FROM endpoints WHERE id = ?")
+            .bind(endpoint_id.to_string())
+            .fetch_one(pool)
+            .await?;
+    let windows = sqlx::query_as::<_, MaintenanceWindowRow>(
+        r"
+        SELECT day_of_week, start_minute, duration_minutes
+        FROM endpoint_delivery_windows
+        WHERE endpoint_id = ?
+        ORDER BY day_of_week, start_minute
+        ",
+    )
+    .bind(endpoint_id.to_string())
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| DeliveryWindow {
+        day_of_week: row.day_of_week,
+        start_minute: row.start_minute,
+        duration_minutes: row.duration_minutes,
+    })
+    .collect();
+
+    Ok(DeliveryWindowsResponse {
+        endpoint_id,
+        utc_offset_minutes,
+        windows,
+    })
+}
+
+/// Replaces the endpoint's delivery windows and their UTC offset.
+pub async fn set_delivery_windows(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    utc_offset_minutes: i64,
+    windows: &[DeliveryWindow],
+) -> Result<DeliveryWindowsResponse, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE endpoints SET delivery_window_utc_offset_minutes = ? WHERE id = ?")
+        .bind(utc_offset_minutes)
+        .bind(endpoint_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM endpoint_delivery_windows WHERE endpoint_id = ?")
+        .bind(endpoint_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+    for window in windows {
+        sqlx::query(
+            r"
+            INSERT INTO endpoint_delivery_windows (
+                id,
+                endpoint_id,
+                day_of_week,
+                start_minute,
+                duration_minutes
+            )
+            VALUES (?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(endpoint_id.to_string())
+        .bind(window.day_of_week)
+        .bind(window.start_minute)
+        .bind(window.duration_minutes)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    list_delivery_windows(pool, access, endpoint_id).await
+}
+
+pub async fn get_fault_injection(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<FaultInjection, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let (failure_rate, latency_ms, updated_at): (f64, i64, String) = sqlx::query_as(
+        r"
+        SELECT failure_rate, latency_ms, updated_at
+        FROM endpoint_fault_injections
+        WHERE endpoint_id = ?
+        ",
+    )
+    .bind(endpoint_id.to_string())
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| StoreError::NotFound("fault injection not configured".to_string()))?;
+
+    Ok(FaultInjection {
+        endpoint_id,
+        failure_rate,
+        latency_ms,
+        updated_at,
+    })
+}
+
+pub async fn set_fault_injection(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    failure_rate: f64,
+    latency_ms: i64,
+) -> Result<FaultInjection, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let now_str = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    sqlx::query(
+        r"
+        INSERT INTO endpoint_fault_injections (endpoint_id, failure_rate, latency_ms, updated_at)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(endpoint_id) DO UPDATE SET
+            failure_rate = excluded.failure_rate,
+            latency_ms = excluded.latency_ms,
+            updated_at = excluded.updated_at
+        ",
+    )
+    .bind(endpoint_id.to_string())
+    .bind(failure_rate)
+    .bind(latency_ms)
+    .bind(&now_str)
+    .execute(pool)
+    .await?;
+
+    Ok(FaultInjection {
+        endpoint_id,
+        failure_rate,
+        latency_ms,
+        updated_at: now_str,
+    })
+}
+
+pub async fn clear_fault_injection(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<(), StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    sqlx::query("DELETE FROM endpoint_fault_injections WHERE endpoint_id = ?")
+        .bind(endpoint_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_endpoint_canary(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointCanary, StoreError> {
+    let mut query =
+        QueryBuilder::new("SELECT canary_target_url, canary_percent FROM endpoints WHERE id = ");
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let (canary_target_url, canary_percent): (Option<String>, i64) = query
+        .build_query_as()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    Ok(EndpointCanary {
+        endpoint_id,
+        canary_target_url,
+        canary_percent,
+    })
+}
+
+/// Sets the endpoint's canary split. Without a canary URL the percentage is
+/// stored as 0. Events already leased keep the target they were given.
+pub async fn set_endpoint_canary(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    canary_target_url: Option<&str>,
+    canary_percent: i64,
+) -> Result<EndpointCanary, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let canary_percent = if canary_target_url.is_some() {
+        canary_percent
+    } else {
+        0
+    };
+    sqlx::query("UPDATE endpoints SET canary_target_url = ?, canary_percent = ? WHERE id = ?")
+        .bind(canary_target_url)
+        .bind(canary_percent)
+        .bind(endpoint_id.to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(EndpointCanary {
+        endpoint_id,
+        canary_target_url: canary_target_url.map(str::to_string),
+        canary_percent,
+    })
+}
+
+pub async fn get_endpoint_profile(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointProfile, StoreError> {
+    let mut query = QueryBuilder::new("SELECT name, description FROM endpoints WHERE id = ");
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let (name, description): (Option<String>, Option<String>) = query
+        .build_query_as()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    Ok(EndpointProfile {
+        endpoint_id,
+        name,
+        description,
+    })
+}
+
+pub async fn set_endpoint_profile(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    name: Option<&str>,
+    description: Option<&str>,
+) -> Result<EndpointProfile, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    sqlx::query("UPDATE endpoints SET name = ?, description = ? WHERE id = ?")
+        .bind(name)
+        .bind(description)
+        .bind(endpoint_id.to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(EndpointProfile {
+        endpoint_id,
+        name: name.map(str::to_string),
+        description: description.map(str::to_string),
+    })
+}
+
+pub async fn get_endpoint_shadow(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointShadow, StoreError> {
+    let mut query = QueryBuilder::new("SELECT shadow_target_url FROM endpoints WHERE id = ");
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let shadow_target_url: Option<String> = query
+        .build_query_scalar()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    Ok(EndpointShadow {
+        endpoint_id,
+        shadow_target_url,
+    })
+}
+
+pub async fn set_endpoint_shadow(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    shadow_target_url: Option<&str>,
+) -> Result<EndpointShadow, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    sqlx::query("UPDATE endpoints SET shadow_target_url = ? WHERE id = ?")
+        .bind(shadow_target_url)
+        .bind(endpoint_id.to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(EndpointShadow {
+        endpoint_id,
+        shadow_target_url: shadow_target_url.map(str::to_string),
+    })
+}
+
+pub async fn get_endpoint_slo(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointSlo, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT slo_target_seconds, slo_objective_percent FROM endpoints WHERE id = ",
+    );
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let (target_seconds, objective_percent) = query
+        .build_query_as()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    Ok(EndpointSlo {
+        endpoint_id,
+        target_seconds,
+        objective_percent,
+    })
+}
+
+/// Sets or, with `None`, removes the endpoint's SLO. The breach counter
+/// is kept either way.
+pub async fn set_endpoint_slo(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    slo: Option<(i64, f64)>,
+) -> Result<EndpointSlo, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let (target_seconds, objective_percent) = slo.unzip();
+    sqlx::query(
+        "UPDATE endpoints SET slo_target_seconds = ?, slo_objective_percent = ? WHERE id = ?",
+    )
+    .bind(target_seconds)
+    .bind(objective_percent)
+    .bind(endpoint_id.to_string())
+    .execute(pool)
+    .await?;
+
+    Ok(EndpointSlo {
+        endpoint_id,
+        target_seconds,
+        objective_percent,
+    })
+}
+
+pub async fn get_endpoint_attempt_log_sampling(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointAttemptLogSampling, StoreError> {
+    let mut query = QueryBuilder::new("SELECT success_log_sample_rate FROM endpoints WHERE id = ");
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let success_sample_rate = query
+        .build_query_scalar()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    Ok(EndpointAttemptLogSampling {
+        endpoint_id,
+        success_sample_rate,
+    })
+}
+
+/// Sets the 1-in-N rate successful attempts are logged at, or with `None`
+/// logs every attempt again. Applies to attempts reported from now on.
+pub async fn set_endpoint_attempt_log_sampling(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    success_sample_rate: Option<i64>,
+) -> Result<EndpointAttemptLogSampling, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    sqlx::query("UPDATE endpoints SET success_log_sample_rate = ? WHERE id = ?")
+        .bind(success_sample_rate)
+        .bind(endpoint_id.to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(EndpointAttemptLogSampling {
+        endpoint_id,
+        success_sample_rate,
+    })
+}
+
+pub async fn get_endpoint_timeouts(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointTimeouts, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT request_timeout_ms, delivery_budget_seconds FROM endpoints WHERE id = ",
+    );
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let (request_timeout_ms, delivery_budget_seconds) = query
+        .build_query_as()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    Ok(EndpointTimeouts {
+        endpoint_id,
+        request_timeout_ms,
+        delivery_budget_seconds,
+    })
+}
+
+/// Replaces the endpoint's timeouts. Events already in flight keep the
+/// request timeout they were leased with but are judged against the new
+/// budget when their attempt is reported.
+pub async fn set_endpoint_timeouts(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    request_timeout_ms: Option<i64>,
+    delivery_budget_seconds: Option<i64>,
+    changed_by: &str,
+) -> Result<EndpointTimeouts, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let now_str = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut tx = pool.begin().await?;
+    record_endpoint_revision(&mut tx, endpoint_id, None, &now_str).await?;
+    sqlx::query(
+        "UPDATE endpoints SET request_timeout_ms = ?, delivery_budget_seconds = ? WHERE id = ?",
+    )
+    .bind(request_timeout_ms)
+    .bind(delivery_budget_seconds)
+    .bind(endpoint_id.to_string())
+    .execute(&mut *tx)
+    .await?;
+    record_endpoint_revision(&mut tx, endpoint_id, Some(changed_by), &now_str).await?;
+    tx.commit().await?;
+
+    Ok(EndpointTimeouts {
+        endpoint_id,
+        request_timeout_ms,
+        delivery_budget_seconds,
+    })
+}
+
+pub async fn get_endpoint_backoff(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointBackoff, StoreError> {
+    let mut query = QueryBuilder::new("SELECT backoff_strategy FROM endpoints WHERE id = ");
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let strategy: Option<String> = query
+        .build_query_scalar()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+    let strategy = strategy
+        .as_deref()
+        .map(serde_json::from_str)
+        .transpose()
+        .map_err(|err| StoreError::Parse(format!("invalid backoff_strategy: {err}")))?;
+
+    Ok(EndpointBackoff {
+        endpoint_id,
+        strategy,
+    })
+}
+
+/// Replaces the endpoint's retry backoff strategy. Retries already
+/// scheduled keep their `next_attempt_at`.
+pub async fn set_endpoint_backoff(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    strategy: Option<&BackoffStrategy>,
+    changed_by: &str,
+) -> Result<EndpointBackoff, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+    let strategy_json = strategy
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|err| StoreError::Parse(format!("invalid backoff_strategy: {err}")))?;
+
+    let now_str = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut tx = pool.begin().await?;
+    record_endpoint_revision(&mut tx, endpoint_id, None, &now_str).await?;
+    sqlx::query("UPDATE endpoints SET backoff_strategy = ? WHERE id = ?")
+        .bind(strategy_json)
+        .bind(endpoint_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    record_endpoint_revision(&mut tx, endpoint_id, Some(changed_by), &now_str).await?;
+    tx.commit().await?;
+
+    Ok(EndpointBackoff {
+        endpoint_id,
+        strategy: strategy.cloned(),
+    })
+}
+
+pub async fn get_endpoint_redirect_policy(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointRedirectPolicy, StoreError> {
+    let mut query =
+        QueryBuilder::new("SELECT redirect_mode, redirect_max FROM endpoints WHERE id = ");
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let (mode, max_redirects): (String, i64) =
+        query
+            .build_query_as()
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    Ok(EndpointRedirectPolicy {
+        endpoint_id,
+        policy: RedirectPolicy {
+            mode: parse_redirect_mode(&mode)?,
+            max_redirects,
+        },
+    })
+}
+
+/// Replaces the endpoint's redirect policy. Events already in flight keep
+/// the policy they were leased with.
+pub async fn set_endpoint_redirect_policy(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    policy: RedirectPolicy,
+    changed_by: &str,
+) -> Result<EndpointRedirectPolicy, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let now_str = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut tx = pool.begin().await?;
+    record_endpoint_revision(&mut tx, endpoint_id, None, &now_str).await?;
+    sqlx::query("UPDATE endpoints SET redirect_mode = ?, redirect_max = ? WHERE id = ?")
+        .bind(redirect_mode_to_str(policy.mode))
+        .bind(policy.max_redirects)
+        .bind(endpoint_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    record_endpoint_revision(&mut tx, endpoint_id, Some(changed_by), &now_str).await?;
+    tx.commit().await?;
+
+    Ok(EndpointRedirectPolicy {
+        endpoint_id,
+        policy,
+    })
+}
+
+pub async fn get_endpoint_connect_policy(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointConnectPolicy, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT address_family, happy_eyeballs_delay_ms FROM endpoints WHERE id = ",
+    );
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let (address_family, happy_eyeballs_delay_ms): (String, Option<i64>) = query
+        .build_query_as()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    Ok(EndpointConnectPolicy {
+        endpoint_id,
+        policy: ConnectPolicy {
+            address_family: parse_address_family(&address_family)?,
+            happy_eyeballs_delay_ms,
+        },
+    })
+}
+
+pub async fn get_endpoint_region(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointRegion, StoreError> {
+    let mut query = QueryBuilder::new("SELECT region, region_mode FROM endpoints WHERE id = ");
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let (region, mode): (Option<String>, String) = query
+        .build_query_as()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    Ok(EndpointRegion {
+        endpoint_id,
+        region,
+        mode: parse_region_mode(&mode)?,
+    })
+}
+
+/// Pins the endpoint's deliveries to a region, or unpins them with
+/// `None`. Events already in flight stay with their worker.
+pub async fn set_endpoint_region(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    region: Option<&str>,
+    mode: RegionMode,
+    changed_by: &str,
+) -> Result<EndpointRegion, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let now_str = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut tx = pool.begin().await?;
+    record_endpoint_revision(&mut tx, endpoint_id, None, &now_str).await?;
+    sqlx::query("UPDATE endpoints SET region = ?, region_mode = ? WHERE id = ?")
+        .bind(region)
+        .bind(region_mode_to_str(mode))
+        .bind(endpoint_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    record_endpoint_revision(&mut tx, endpoint_id, Some(changed_by), &now_str).await?;
+    tx.commit().await?;
+
+    Ok(EndpointRegion {
+        endpoint_id,
+        region: region.map(str::to_string),
+        mode,
+    })
+}
+
+/// Replaces the endpoint's connection settings. Events already in flight
+/// keep the settings they were leased with.
+pub async fn set_endpoint_connect_policy(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    policy: ConnectPolicy,
+    changed_by: &str,
+) -> Result<EndpointConnectPolicy, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let now_str = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut tx = pool.begin().await?;
+    record_endpoint_revision(&mut tx, endpoint_id, None, &now_str).await?;
+    sqlx::query(
+        "UPDATE endpoints SET address_family = ?, happy_eyeballs_delay_ms = ? WHERE id = ?",
+    )
+    .bind(address_family_to_str(policy.address_family))
+    .bind(policy.happy_eyeballs_delay_ms)
+    .bind(endpoint_id.to_string())
+    .execute(&mut *tx)
+    .await?;
+    record_endpoint_revision(&mut tx, endpoint_id, Some(changed_by), &now_str).await?;
+    tx.commit().await?;
+
+    Ok(EndpointConnectPolicy {
+        endpoint_id,
+        policy,
+    })
+}
+
+/// Pre-flight check settings, circuit and latest check of an endpoint.
+/// Its certificate counts as expiring within `tls_warning_days` of now.
+pub async fn get_endpoint_health(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    tls_warning_days: i64,
+) -> Result<EndpointHealth, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT ep.check_method, ep.check_url, c.state, c.open_until, \
+            c.consecutive_failures, c.last_failure_at \
+        FROM endpoints ep \
+        LEFT JOIN target_circuit_states c ON c.endpoint_id = ep.id \
+        WHERE ep.id = ",
+    );
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "ep.id");
+    let row: EndpointHealthRow = query
+        .build_query_as()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    let latest_check = sqlx::query_as::<_, EndpointCheckRow>(
+        r"
+        SELECT id, endpoint_id, worker_id, url, finished_at, reachable, response_status,
+            latency_ms, error_message, tls_expires_at
+        FROM endpoint_checks
+        WHERE endpoint_id = ?
+        ORDER BY finished_at DESC, id DESC
+        LIMIT 1
+        ",
+    )
+    .bind(endpoint_id.to_string())
+    .fetch_optional(pool)
+    .await?
+    .map(EndpointCheck::try_from)
+    .transpose()?;
+    let last_reachable_at: Option<String> = sqlx::query_scalar(
+        "SELECT MAX(finished_at) FROM endpoint_checks WHERE endpoint_id = ? AND reachable = 1",
+    )
+    .bind(endpoint_id.to_string())
+    .fetch_one(pool)
+    .await?;
+    let tls_expires_at: Option<String> = sqlx::query_scalar(
+        r"
+        SELECT tls_expires_at
+        FROM endpoint_checks
+        WHERE endpoint_id = ? AND tls_expires_at IS NOT NULL
+        ORDER BY finished_at DESC, id DESC
+        LIMIT 1
+        ",
+    )
+    .bind(endpoint_id.to_string())
+    .fetch_optional(pool)
+    .await?;
+    let tls_expiring = match tls_expires_at.as_deref() {
+        Some(raw) => {
+            parse_tls_expires_at(raw)? < Utc::now() + chrono::Duration::days(tls_warning_days)
+        }
+        None => false,
+    };
+
+    Ok(EndpointHealth {
+        endpoint_id,
+        check_method: parse_check_method(&row.check_method)?,
+        check_url: row.check_url,
+        circuit: map_circuit(
+            &endpoint_id.to_string(),
+            row.state.as_deref(),
+            row.open_until.as_deref(),
+            row.consecutive_failures,
+            row.last_failure_at.as_deref(),
+        )?,
+        latest_check,
+        last_reachable_at,
+        tls_expires_at,
+        tls_expiring,
+    })
+}
+
+/// Certificate expiry from each endpoint's latest check that reported
+/// one, soonest first.
+pub async fn tls_expiries(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+) -> Result<Vec<TlsExpiry>, StoreError> {
+    // SQLite takes the bare columns from the row holding MAX(finished_at).
+    let mut query = QueryBuilder::new(
+        "SELECT endpoint_id, url, tls_expires_at, MAX(finished_at) AS checked_at \
+        FROM endpoint_checks \
+        WHERE tls_expires_at IS NOT NULL",
+    );
+    access.push_predicate(&mut query, "endpoint_id");
+    query.push(" GROUP BY endpoint_id ORDER BY tls_expires_at, endpoint_id");
+    let rows: Vec<TlsExpiryRow> = query.build_query_as().fetch_all(pool).await?;
+
+    let now = Utc::now();
+    rows.into_iter()
+        .map(|row| {
+            let expires_at = parse_tls_expires_at(&row.tls_expires_at)?;
+            Ok(TlsExpiry {
+                endpoint_id: Uuid::parse_str(&row.endpoint_id)
+                    .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+                url: row.url,
+                tls_expires_at: row.tls_expires_at,
+                checked_at: row.checked_at,
+                days_remaining: (expires_at - now).num_seconds().div_euclid(24 * 60 * 60),
+            })
+        })
+        .collect()
+}
+
+/// Replaces how the endpoint is probed and makes it due for a check, so
+/// the new settings are tried on the next check lease.
+pub async fn set_endpoint_check(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    method: EndpointCheckMethod,
+    url: Option<&str>,
+    tls_warning_days: i64,
+) -> Result<EndpointHealth, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    sqlx::query(
+        "UPDATE endpoints SET check_method = ?, check_url = ?, next_check_at = NULL WHERE id = ?",
+    )
+    .bind(check_method_to_str(method))
+    .bind(url)
+    .bind(endpoint_id.to_string())
+    .execute(pool)
+    .await?;
+
+    get_endpoint_health(pool, access, endpoint_id, tls_warning_days).await
+}
+
+/// Daily usage rows between `params.from` and `params.to`, oldest day
+/// first.
+pub async fn usage_rollups(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    params: &UsageParams,
+) -> Result<Vec<UsageRollup>, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT u.endpoint_id, ep.group_id, u.day, u.events_ingested, \
+            u.deliveries_attempted, u.bytes_stored \
+        FROM usage_rollups u \
+        LEFT JOIN endpoints ep ON ep.id = u.endpoint_id \
+        WHERE u.day >= ",
+    );
+    query.push_bind(&params.from);
+    query.push(" AND u.day <= ");
+    query.push_bind(&params.to);
+    access.push_predicate(&mut query, "u.endpoint_id");
+    if let Some(endpoint_id) = params.endpoint_id {
+        query.push(" AND u.endpoint_id = ");
+        query.push_bind(endpoint_id.to_string());
+    }
+    if let Some(group_id) = params.group_id {
+        query.push(" AND ep.group_id = ");
+        query.push_bind(group_id.to_string());
+    }
+    query.push(" ORDER BY u.day ASC, u.endpoint_id ASC");
+
+    let rows: Vec<UsageRow> = query.build_query_as().fetch_all(pool).await?;
+    rows.into_iter()
+        .map(|row| {
+            Ok(UsageRollup {
+                endpoint_id: Uuid::parse_str(&row.endpoint_id)
+                    .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+                group_id: row
+                    .group_id
+                    .as_deref()
+                    .map(Uuid::parse_str)
+                    .transpose()
+                    .map_err(|err| StoreError::Parse(format!("invalid group id: {err}")))?,
+                day: row.day,
+                events_ingested: row.events_ingested,
+                deliveries_attempted: row.deliveries_attempted,
+                bytes_stored: row.bytes_stored,
+            })
+        })
+        .collect()
+}
+
+/// SLO attainment of every endpoint with an SLO, over events delivered in
+/// the last `window_minutes`. Deliveries come from the hourly rollups, so
+/// the window is widened to whole hours, and each delivery was judged
+/// against the SLO target in force when it was reported.
+pub async fn slo_stats(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    window_minutes: i64,
+) -> Result<Vec<SloAttainment>, StoreError> {
+    let now = Utc::now();
+    let since = now - chrono::Duration::minutes(window_minutes);
+
+    let mut query = QueryBuilder::new(
+        "WITH delivered AS ( \
+            SELECT endpoint_id, \
+                SUM(delivered) AS delivered, \
+                SUM(delivered_within_slo) AS delivered_within_target \
+            FROM delivery_stats_hourly \
+            WHERE bucket_start >= ",
+    );
+    query.push_bind(since.format("%Y-%m-%dT%H:00:00Z").to_string());
+    query.push(
+        " \
+            GROUP BY endpoint_id \
+        ) \
+        SELECT \
+            ep.id AS endpoint_id, \
+            ep.slo_target_seconds AS target_seconds, \
+            ep.slo_objective_percent AS objective_percent, \
+            ep.slo_breaches AS breaches_total, \
+            COALESCE(d.delivered, 0) AS delivered, \
+            COALESCE(d.delivered_within_target, 0) AS delivered_within_target, \
+            (SELECT COUNT(*) FROM webhook_events e \
+                WHERE e.endpoint_id = ep.id \
+                  AND e.deleted_at IS NULL \
+                  AND e.status IN ('pending', 'requeued', 'in_flight', 'paused') \
+                  AND (julianday(",
+    );
+    query.push_bind(now.to_rfc3339_opts(SecondsFormat::Secs, true));
+    query.push(
+        ") - julianday(e.received_at)) * 86400 > ep.slo_target_seconds) AS overdue \
+        FROM endpoints ep \
+        LEFT JOIN delivered d ON d.endpoint_id = ep.id \
+        WHERE ep.slo_target_seconds IS NOT NULL \
+          AND ep.slo_objective_percent IS NOT NULL",
+    );
+    access.push_predicate(&mut query, "ep.id");
+    query.push(" ORDER BY ep.id");
+
+    let rows: Vec<SloRow> = query.build_query_as().fetch_all(pool).await?;
+    rows.into_iter().map(SloAttainment::try_from).collect()
+}
+
+/// Attempt counts, failures and mean duration per endpoint in
+/// `baseline_buckets + 1` buckets of `bucket_minutes` ending now. Failures
+/// are counted as in the error summary. Empty buckets are omitted.
+///
+/// Buckets of whole hours are summed from the hourly rollups and end with
+/// the current hour; other sizes scan the attempt logs.
+pub async fn attempt_buckets(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    bucket_minutes: i64,
+    baseline_buckets: i64,
+) -> Result<Vec<AttemptBucket>, StoreError> {
+    if bucket_minutes % 60 == 0 {
+        return hourly_attempt_buckets(pool, access, bucket_minutes, baseline_buckets).await;
+    }
+
+    let since = Utc::now() - chrono::Duration::minutes(bucket_minutes * (baseline_buckets + 1));
+    let since = since.to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    let mut query = QueryBuilder::new(
+        "SELECT \
+            e.endpoint_id, \
+            CAST((julianday(a.finished_at) - julianday(",
+    );
+    query.push_bind(since.clone());
+    query.push(")) * 1440 / ");
+    query.push_bind(bucket_minutes);
+    query.push(
+        " AS INTEGER) AS bucket, \
+            COUNT(*) AS attempts, \
+            SUM(CASE WHEN a.error_kind IS NOT NULL \
+                OR a.response_status < 200 \
+                OR a.response_status >= 300 THEN 1 ELSE 0 END) AS failures, \
+            AVG((julianday(a.finished_at) - julianday(a.started_at)) * 86400000.0) \
+                AS avg_latency_ms \
+        FROM webhook_attempt_logs_all a \
+        JOIN webhook_events e ON e.id = a.event_id \
+        WHERE julianday(a.finished_at) >= julianday(",
+    );
+    query.push_bind(since);
+    query.push(")");
+    access.push_predicate(&mut query, "e.endpoint_id");
+    query.push(" GROUP BY e.endpoint_id, bucket HAVING bucket <= ");
+    query.push_bind(baseline_buckets);
+    query.push(" ORDER BY e.endpoint_id, bucket");
+
+    let rows: Vec<AttemptBucketRow> = query.build_query_as().fetch_all(pool).await?;
+    rows.into_iter()
+        .map(|row| {
+            Ok(AttemptBucket {
+                endpoint_id: Uuid::parse_str(&row.endpoint_id)
+                    .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+                bucket: row.bucket,
+                attempts: row.attempts,
+                failures: row.failures,
+                avg_latency_ms: row.avg_latency_ms,
+            })
+        })
+        .collect()
+}
+
+async fn hourly_attempt_buckets(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    bucket_minutes: i64,
+    baseline_buckets: i64,
+) -> Result<Vec<AttemptBucket>, StoreError> {
+    // Truncating to the hour makes the last bucket end with the current
+    // hour.
+    let since = Utc::now() + chrono::Duration::hours(1)
+        - chrono::Duration::minutes(bucket_minutes * (baseline_buckets + 1));
+    let since = since.format("%Y-%m-%dT%H:00:00Z").to_string();
+
+    let mut query = QueryBuilder::new(
+        "SELECT \
+            s.endpoint_id, \
+            CAST(ROUND((julianday(s.bucket_start) - julianday(",
+    );
+    query.push_bind(since.clone());
+    query.push(")) * 1440) / ");
+    query.push_bind(bucket_minutes);
+    query.push(
+        " AS INTEGER) AS bucket, \
+            SUM(s.attempts) AS attempts, \
+            SUM(s.failed_attempts) AS failures, \
+            SUM(s.attempt_duration_ms_sum) / SUM(s.attempts) AS avg_latency_ms \
+        FROM delivery_stats_hourly s \
+        WHERE s.attempts > 0 AND s.bucket_start >= ",
+    );
+    query.push_bind(since);
+    access.push_predicate(&mut query, "s.endpoint_id");
+    query.push(" GROUP BY s.endpoint_id, bucket HAVING bucket <= ");
+    query.push_bind(baseline_buckets);
+    query.push(" ORDER BY s.endpoint_id, bucket");
+
+    let rows: Vec<AttemptBucketRow> = query.build_query_as().fetch_all(pool).await?;
+    rows.into_iter()
+        .map(|row| {
+            Ok(AttemptBucket {
+                endpoint_id: Uuid::parse_str(&row.endpoint_id)
+                    .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+                bucket: row.bucket,
+                attempts: row.attempts,
+                failures: row.failures,
+                avg_latency_ms: row.avg_latency_ms,
+            })
+        })
+        .collect()
+}
+
+/// Per-provider ingest counters and payload sizes for the last
+/// `window_minutes`, ordered by provider.
+pub async fn provider_ingest_stats(
+    pool: &SqlitePool,
+    window_minutes: i64,
+) -> Result<Vec<ProviderIngestStats>, StoreError> {
+    let since = Utc::now() - chrono::Duration::minutes(window_minutes);
+
+    let counters: Vec<ProviderCounterRow> = sqlx::query_as(
+        r"
+        SELECT provider,
+               SUM(accepted) AS accepted,
+               SUM(rejected_signature) AS rejected_signature,
+               SUM(rejected_size) AS rejected_size,
+               SUM(rejected_schema) AS rejected_schema
+        FROM provider_ingest_stats
+        WHERE bucket_start >= ?
+        GROUP BY provider
+        ",
+    )
+    .bind(since.format("%Y-%m-%dT%H:00:00Z").to_string())
+    .fetch_all(pool)
+    .await?;
+
+    let sizes: Vec<(String, i64)> = sqlx::query_as(
+        r"
+        SELECT provider, length(CAST(payload AS BLOB)) AS payload_bytes
+        FROM webhook_events
+        WHERE replayed_from_event_id IS NULL
+          AND deleted_at IS NULL
+          AND julianday(received_at) >= julianday(?)
+        ORDER BY provider, payload_bytes
+        ",
+    )
+    .bind(since.to_rfc3339_opts(SecondsFormat::Secs, true))
+    .fetch_all(pool)
+    .await?;
+    let mut sizes_by_provider: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+    for (provider, bytes) in sizes {
+        sizes_by_provider.entry(provider).or_default().push(bytes);
+    }
+
+    let mut stats: BTreeMap<String, ProviderIngestStats> = BTreeMap::new();
+    for row in counters {
+        stats.insert(
+            row.provider.clone(),
+            ProviderIngestStats {
+                provider: row.provider,
+                accepted: row.accepted,
+                rejected_signature: row.rejected_signature,
+                rejected_size: row.rejected_size,
+                rejected_schema: row.rejected_schema,
+                payload_bytes_p50: None,
+                payload_bytes_p95: None,
+                payload_bytes_p99: None,
+                payload_bytes_max: None,
+            },
+        );
+    }
+    for (provider, sorted) in sizes_by_provider {
+        let entry = stats
+            .entry(provider.clone())
+            .or_insert_with(|| ProviderIngestStats {
+                provider,
+                accepted: 0,
+                rejected_signature: 0,
+                rejected_size: 0,
+                rejected_schema: 0,
+                payload_bytes_p50: None,
+                payload_bytes_p95: None,
+                payload_bytes_p99: None,
+                payload_bytes_max: None,
+            });
+        entry.payload_bytes_p50 = percentile(&sorted, 50);
+        entry.payload_bytes_p95 = percentile(&sorted, 95);
+        entry.payload_bytes_p99 = percentile(&sorted, 99);
+        entry.payload_bytes_max = sorted.last().copied();
+    }
+
+    Ok(stats.into_values().collect())
+}
+
+/// Nearest-rank percentile of an ascending slice.
+fn percentile(sorted: &[i64], percent: usize) -> Option<i64> {
+    let rank = (sorted.len() * percent).div_ceil(100);
+    sorted.get(rank.saturating_sub(1)).copied()
+}
+
+pub async fn get_endpoint_secrets(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointSecrets, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT signing_secret_id, signing_secret_expires_at, \
+            secondary_signing_secret_id, secondary_signing_secret_expires_at \
+        FROM endpoints WHERE id = ",
+    );
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let (primary_secret_id, primary_expires_at, secondary_secret_id, secondary_expires_at) = query
+        .build_query_as()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    Ok(EndpointSecrets {
+        endpoint_id,
+        primary_secret_id,
+        primary_expires_at,
+        secondary_secret_id,
+        secondary_expires_at,
+    })
+}
+
+/// Every signing secret ID stored on an endpoint, primary and secondary,
+/// as `(endpoint_id, secret_id)` pairs.
+pub async fn list_endpoint_secret_ids(
+    pool: &SqlitePool,
+) -> Result<Vec<(String, String)>, StoreError> {
+    let rows = sqlx::query_as(
+        r"
+        SELECT id, signing_secret_id FROM endpoints WHERE signing_secret_id IS NOT NULL
+        UNION ALL
+        SELECT id, secondary_signing_secret_id FROM endpoints
+        WHERE secondary_signing_secret_id IS NOT NULL
+        ORDER BY 1, 2
+        ",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Shown in support bundles instead of secret IDs.
+const MASKED_SECRET: &str = "[REDACTED]";
+
+/// Cap on the circuit transitions a support bundle carries, newest kept.
+const MAX_BUNDLE_CIRCUIT_TRANSITIONS: i64 = 100;
+
+/// Collects the event, its attempts, the endpoint circuit with its recent
+/// history, and the endpoint configuration with secret IDs masked.
+pub async fn event_bundle(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    event_id: Uuid,
+) -> Result<EventBundle, StoreError> {
+    let GetEventResponse {
+        event,
+        target_url,
+        circuit,
+        ..
+    } = get_event(pool, access, event_id).await?;
+    let attempts = list_attempts(pool, access, event_id).await?.attempts;
+    let endpoint_id = event.endpoint_id;
+
+    let group_id: Option<String> =
+        sqlx::query_scalar("SELECT group_id FROM endpoints WHERE id = ?")
+            .bind(endpoint_id.to_string())
+            .fetch_optional(pool)
+            .await?
+            .flatten();
+    let mut secrets = get_endpoint_secrets(pool, access, endpoint_id).await?;
+    let mask = |secret_id: Option<String>| secret_id.map(|_| MASKED_SECRET.to_string());
+    secrets.primary_secret_id = mask(secrets.primary_secret_id);
+    secrets.secondary_secret_id = mask(secrets.secondary_secret_id);
+    let endpoint = BundleEndpoint {
+        endpoint_id,
+        target_url,
+        group_id: parse_optional_uuid("group_id", group_id.as_deref())?,
+        secrets,
+        timeouts: get_endpoint_timeouts(pool, access, endpoint_id).await?,
+        redirects: get_endpoint_redirect_policy(pool, access, endpoint_id).await?,
+        connect: get_endpoint_connect_policy(pool, access, endpoint_id).await?,
+        region: get_endpoint_region(pool, access, endpoint_id).await?,
+        slo: get_endpoint_slo(pool, access, endpoint_id).await?,
+        canary: get_endpoint_canary(pool, access, endpoint_id).await?,
+        shadow: get_endpoint_shadow(pool, access, endpoint_id).await?,
+    };
+
+    let rows: Vec<CircuitTransitionRow> = sqlx::query_as(
+        r"
+        SELECT state, open_until, consecutive_failures, changed_at
+        FROM (
+            SELECT rowid, state, open_until, consecutive_failures, changed_at
+            FROM target_circuit_transitions
+            WHERE endpoint_id = ?
+            ORDER BY changed_at DESC, rowid DESC
+            LIMIT ?
+        )
+        ORDER BY changed_at ASC, rowid ASC
+        ",
+    )
+    .bind(endpoint_id.to_string())
+    .bind(MAX_BUNDLE_CIRCUIT_TRANSITIONS)
+    .fetch_all(pool)
+    .await?;
+    let circuit_history = rows
+        .into_iter()
+        .map(|row| {
+            Ok(CircuitTransition {
+                state: parse_circuit_status(&row.state)?,
+                open_until: row.open_until,
+                consecutive_failures: row.consecutive_failures,
+                changed_at: row.changed_at,
+            })
+        })
+        .collect::<Result<_, StoreError>>()?;
+
+    Ok(EventBundle {
+        generated_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        event,
+        attempts,
+        circuit,
+        circuit_history,
+        endpoint,
+    })
+}
+
+/// Makes `secret_id` the endpoint's primary secret. The current primary
+/// becomes the secondary, valid until `previous_expires_at`. An endpoint
+/// without a primary has no secondary afterwards.
+pub async fn rotate_endpoint_secret(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    secret_id: &str,
+    expires_at: Option<&str>,
+    previous_expires_at: &str,
+) -> Result<EndpointSecrets, StoreError> {
+    let mut tx = pool.begin().await?;
+
+    let mut query = QueryBuilder::new("SELECT signing_secret_id FROM endpoints WHERE id = ");
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let previous_secret_id: Option<String> = query
+        .build_query_scalar()
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+    let secondary_expires_at = previous_secret_id.as_ref().map(|_| previous_expires_at);
+
+    sqlx::query(
+        r"
+        UPDATE endpoints
+        SET signing_secret_id = ?,
+            signing_secret_expires_at = ?,
+            secondary_signing_secret_id = ?,
+            secondary_signing_secret_expires_at = ?
+        WHERE id = ?
+        ",
+    )
+    .bind(secret_id)
+    .bind(expires_at)
+    .bind(previous_secret_id.as_deref())
+    .bind(secondary_expires_at)
+    .bind(endpoint_id.to_string())
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(EndpointSecrets {
+        endpoint_id,
+        primary_secret_id: Some(secret_id.to_string()),
+        primary_expires_at: expires_at.map(str::to_string),
+        secondary_secret_id: previous_secret_id,
+        secondary_expires_at: secondary_expires_at.map(str::to_string),
+    })
+}
+
+/// Points the endpoint at `target_url` as a new revision authored by
+/// `changed_by`. Setting the current URL again is a no-op.
+pub async fn set_endpoint_target(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+    target_url: &str,
+    changed_by: &str,
+) -> Result<EndpointRevision, StoreError> {
+    let now_str = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut tx = pool.begin().await?;
+
+    let mut query = QueryBuilder::new("SELECT target_url FROM endpoints WHERE id = ");
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let current_url: String = query
+        .build_query_scalar()
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+
+    record_endpoint_revision(&mut tx, endpoint_id, None, &now_str).await?;
+    if current_url != target_url {
+        sqlx::query("UPDATE endpoints SET target_url = ? WHERE id = ?")
+            .bind(target_url)
+            .bind(endpoint_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+    }
+    let revision =
+        record_endpoint_revision(&mut tx, endpoint_id, Some(changed_by), &now_str).await?;
+
+    let mut rows = sqlx::query_as::<_, EndpointRevisionRow>(
+        r"
+        SELECT endpoint_id, revision, target_url, changed_by, created_at, settings
+        FROM endpoint_revisions
+        WHERE endpoint_id = ? AND revision <= ?
+        ORDER BY revision DESC
+        LIMIT 2
+        ",
+    )
+    .bind(endpoint_id.to_string())
+    .bind(revision)
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    rows.reverse();
+    revisions_with_changes(rows)?
+        .pop()
+        .ok_or_else(|| StoreError::NotFound("revision not found".to_string()))
+}
+
+/// Snapshots the endpoint's delivery settings as a new revision authored
+/// by `changed_by` when they differ from its latest revision, and returns
+/// the latest revision number. A missing latest revision is written, and
+/// one without settings takes the current ones, without counting as a
+/// change.
+async fn record_endpoint_revision(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    endpoint_id: Uuid,
+    changed_by: Option<&str>,
+    now_str: &str,
+) -> Result<i64, StoreError> {
+    let settings = sqlx::query_as::<_, EndpointSettingsRow>(
+        r"
+        SELECT target_url, request_timeout_ms, delivery_budget_seconds, redirect_mode,
+            redirect_max, address_family, happy_eyeballs_delay_ms, region, region_mode,
+            backoff_strategy
+        FROM endpoints
+        WHERE id = ?
+        ",
+    )
+    .bind(endpoint_id.to_string())
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+    let settings_json = serde_json::to_string(&settings)
+        .map_err(|err| StoreError::Parse(format!("invalid endpoint settings: {err}")))?;
+    let current_revision: i64 =
+        sqlx::query_scalar("SELECT target_revision FROM endpoints WHERE id = ?")
+            .bind(endpoint_id.to_string())
+            .fetch_one(&mut **tx)
+            .await?;
+    let latest: Option<Option<String>> = sqlx::query_scalar(
+        "SELECT settings FROM endpoint_revisions WHERE endpoint_id = ? AND revision = ?",
+    )
+    .bind(endpoint_id.to_string())
+    .bind(current_revision)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let revision = match latest {
+        Some(Some(latest)) if latest == settings_json => return Ok(current_revision),
+        Some(None) => {
+            sqlx::query(
+                "UPDATE endpoint_revisions SET settings = ? WHERE endpoint_id = ? AND revision = ?",
+            )
+            .bind(&settings_json)
+            .bind(endpoint_id.to_string())
+            .bind(current_revision)
+            .execute(&mut **tx)
+            .await?;
+            return Ok(current_revision);
+        }
+        Some(Some(_)) => current_revision + 1,
+        None => current_revision,
+    };
+    let changed_by = changed_by.filter(|_| revision > current_revision);
+
+    sqlx::query(
+        r"
+        INSERT INTO endpoint_revisions (
+            endpoint_id, revision, target_url, changed_by, created_at, settings
+        )
+        VALUES (?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(endpoint_id.to_string())
+    .bind(revision)
+    .bind(&settings.target_url)
+    .bind(changed_by)
+    .bind(now_str)
+    .bind(&settings_json)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("UPDATE endpoints SET target_revision = ? WHERE id = ?")
+        .bind(revision)
+        .bind(endpoint_id.to_string())
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(revision)
+}
+
+pub async fn list_endpoint_revisions(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<EndpointRevisionsResponse, StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let rows = sqlx::query_as::<_, EndpointRevisionRow>(
+        r"
+        SELECT endpoint_id, revision, target_url, changed_by, created_at, settings
+        FROM endpoint_revisions
+        WHERE endpoint_id = ?
+        ORDER BY revision ASC
+        ",
+    )
+    .bind(endpoint_id.to_string())
+    .fetch_all(pool)
+    .await?;
+
+    let mut revisions = revisions_with_changes(rows)?;
+    revisions.reverse();
+    Ok(EndpointRevisionsResponse {
+        endpoint_id,
+        revisions,
+    })
+}
+
+/// Builds revisions from `rows`, oldest first, each with its changes
+/// from the row before it. The first row has no changes.
+fn revisions_with_changes(
+    rows: Vec<EndpointRevisionRow>,
+) -> Result<Vec<EndpointRevision>, StoreError> {
+    let mut revisions = Vec::with_capacity(rows.len());
+    let mut previous: Option<serde_json::Map<String, serde_json::Value>> = None;
+    for row in rows {
+        let settings = revision_settings(&row)?;
+        let changes = match &previous {
+            Some(previous) => settings
+                .iter()
+                .filter_map(|(field, value)| {
+                    let before = previous.get(field)?;
+                    (before != value).then(|| EndpointRevisionChange {
+                        field: field.clone(),
+                        from: settings_value(before),
+                        to: settings_value(value),
+                    })
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        revisions.push(EndpointRevision {
+            endpoint_id: Uuid::parse_str(&row.endpoint_id)
+                .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+            revision: row.revision,
+            target_url: row.target_url,
+            changed_by: row.changed_by,
+            created_at: row.created_at,
+            changes,
+        });
+        previous = Some(settings);
+    }
+    Ok(revisions)
+}
+
+/// Recorded settings of a revision. Revisions from before settings were
+/// recorded only know their target URL.
+fn revision_settings(
+    row: &EndpointRevisionRow,
+) -> Result<serde_json::Map<String, serde_json::Value>, StoreError> {
+    match row.settings.as_deref() {
+        Some(raw) => serde_json::from_str(raw)
+            .map_err(|err| StoreError::Parse(format!("invalid revision settings: {err}"))),
+        None => Ok(serde_json::Map::from_iter([(
+            "target_url".to_string(),
+            serde_json::Value::String(row.target_url.clone()),
+        )])),
+    }
+}
+
+fn settings_value(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(value) => Some(value.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Delivery settings recorded with each endpoint revision.
+#[derive(sqlx::FromRow, serde::Serialize)]
+struct EndpointSettingsRow {
+    target_url: String,
+    request_timeout_ms: Option<i64>,
+    delivery_budget_seconds: Option<i64>,
+    redirect_mode: String,
+    redirect_max: i64,
+    address_family: String,
+    happy_eyeballs_delay_ms: Option<i64>,
+    region: Option<String>,
+    region_mode: String,
+    backoff_strategy: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct EndpointRevisionRow {
+    endpoint_id: String,
+    revision: i64,
+    target_url: String,
+    changed_by: Option<String>,
+    created_at: String,
+    settings: Option<String>,
+}
+
+async fn ensure_endpoint_exists(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<(), StoreError> {
+    let mut query = QueryBuilder::new("SELECT 1 FROM endpoints WHERE id = ");
+    query.push_bind(endpoint_id.to_string());
+    access.push_predicate(&mut query, "id");
+    let exists: Option<i64> = query.build_query_scalar().fetch_optional(pool).await?;
+    exists
+        .map(|_| ())
+        .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))
+}
+
+/// Which events a scrub ruleset applies to.
+#[derive(Debug, Clone)]
+pub enum ScrubScope {
+    Provider(String),
+    Endpoint(Uuid),
+}
+
+/// Provider rulesets span endpoints, so they are invisible to restricted
+/// access.
+pub async fn get_scrub_ruleset(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    scope: &ScrubScope,
+) -> Result<ScrubRuleset, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT id, provider, endpoint_id, version, rules, updated_at \
+        FROM scrub_rulesets WHERE ",
+    );
+    match scope {
+        ScrubScope::Provider(provider) => {
+            query.push("provider = ");
+            query.push_bind(provider.as_str());
+        }
+        ScrubScope::Endpoint(endpoint_id) => {
+            query.push("endpoint_id = ");
+            query.push_bind(endpoint_id.to_string());
+        }
+    }
+    access.push_predicate(&mut query, "endpoint_id");
+
+    query
+        .build_query_as::<ScrubRulesetRow>()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("scrub ruleset not found".to_string()))?
+        .try_into()
+}
+
+/// Replaces the scope's scrub rules, bumping the ruleset version so events
+/// record which rules were applied to them.
+pub async fn set_scrub_rules(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    scope: &ScrubScope,
+    rules: &[ScrubRule],
+) -> Result<ScrubRuleset, StoreError> {
+    match scope {
+        ScrubScope::Endpoint(endpoint_id) => {
+            ensure_endpoint_exists(pool, access, *endpoint_id).await?;
+        }
+        ScrubScope::Provider(_) if access.is_restricted() => {
+            return Err(StoreError::NotFound("scrub ruleset not found".to_string()));
+        }
+        ScrubScope::Provider(_) => {}
+    }
+
+    let rules_json = serde_json::to_string(rules)
+        .map_err(|err| StoreError::Parse(format!("invalid scrub rules JSON: {err}")))?;
+    let updated_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let (provider, endpoint_id) = match scope {
+        ScrubScope::Provider(provider) => (Some(provider.clone()), None),
+        ScrubScope::Endpoint(endpoint_id) => (None, Some(endpoint_id.to_string())),
+    };
+
+    let updated = sqlx::query(
+        r"
+        UPDATE scrub_rulesets
+        SET version = version + 1,
+            rules = ?,
+            updated_at = ?
+        WHERE provider IS ? AND endpoint_id IS ?
+        ",
+    )
+    .bind(&rules_json)
+    .bind(&updated_at)
+    .bind(provider.as_deref())
+    .bind(endpoint_id.as_deref())
+    .execute(pool)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        sqlx::query(
+            r"
+            INSERT INTO scrub_rulesets (id, provider, endpoint_id, version, rules, updated_at)
+            VALUES (?, ?, ?, 1, ?, ?)
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(provider.as_deref())
+        .bind(endpoint_id.as_deref())
+        .bind(&rules_json)
+        .bind(&updated_at)
+        .execute(pool)
+        .await?;
+    }
+
+    get_scrub_ruleset(pool, access, scope).await
+}
+
+#[derive(sqlx::FromRow)]
+struct ScrubRulesetRow {
+    id: String,
+    provider: Option<String>,
+    endpoint_id: Option<String>,
+    version: i64,
+    rules: String,
+    updated_at: String,
+}
+
+impl TryFrom<ScrubRulesetRow> for ScrubRuleset {
+    type Error = StoreError;
+
+    fn try_from(row: ScrubRulesetRow) -> Result<Self, Self::Error> {
+        let rules: Vec<ScrubRule> = serde_json::from_str(&row.rules)
+            .map_err(|err| StoreError::Parse(format!("invalid scrub rules JSON: {err}")))?;
+        Ok(Self {
+            id: Uuid::parse_str(&row.id)
+                .map_err(|err| StoreError::Parse(format!("invalid ruleset id: {err}")))?,
+            provider: row.provider,
+            endpoint_id: parse_optional_uuid("endpoint id", row.endpoint_id.as_deref())?,
+            version: row.version,
+            rules,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+/// Soft-deletes an event so it is no longer listed, fetched, replayed, or
+/// leased. With `hard`, payload, headers, and attempt request/response
+/// bodies are also scrubbed; the event and attempt rows stay so attempt
+/// history counts remain intact for audit.
+pub async fn get_payload_schema(
+    pool: &SqlitePool,
+    provider: &str,
+    event_type: &str,
+) -> Result<PayloadSchema, StoreError> {
+    sqlx::query_as::<_, PayloadSchemaRow>(
+        r"
+        SELECT provider, event_type, schema, reject_invalid, updated_at
+        FROM payload_schemas
+        WHERE provider = ? AND event_type = ?
+        ",
+    )
+    .bind(provider)
+    .bind(event_type)
+    .fetch_optional(pool)
+    .await?
+    .map(PayloadSchema::from)
+    .ok_or_else(|| StoreError::NotFound("payload schema not found".to_string()))
+}
+
+pub async fn set_payload_schema(
+    pool: &SqlitePool,
+    provider: &str,
+    event_type: &str,
+    schema: &str,
+    reject_invalid: bool,
+) -> Result<PayloadSchema, StoreError> {
+    let row = sqlx::query_as::<_, PayloadSchemaRow>(
+        r"
+        INSERT INTO payload_schemas (provider, event_type, schema, reject_invalid, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(provider, event_type) DO UPDATE SET
+            schema = excluded.schema,
+            reject_invalid = excluded.reject_invalid,
+            updated_at = excluded.updated_at
+        RETURNING provider, event_type, schema, reject_invalid, updated_at
+        ",
+    )
+    .bind(provider)
+    .bind(event_type)
+    .bind(schema)
+    .bind(reject_invalid)
+    .bind(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.into())
+}
+
+pub async fn delete_event(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    event_id: Uuid,
+    hard: bool,
+) -> Result<DeleteEventResponse, StoreError> {
+    let now = Utc::now();
+    let now_str = now.to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    let mut tx = pool.begin().await?;
+
+    let mut query = QueryBuilder::new(
+        "SELECT status, lease_expires_at, deleted_at, erased_at \
+        FROM webhook_events \
+        WHERE id = ",
+    );
+    query.push_bind(event_id.to_string());
+    access.push_predicate(&mut query, "endpoint_id");
+
+    let row = query
+        .build_query_as::<DeleteEventRow>()
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("event not found".to_string()))?;
+
+    if parse_status(&row.status)? == WebhookEventStatus::InFlight
+        && let Some(lease_expires_at) = row.lease_expires_at.as_deref()
+    {
+        let expires = chrono::DateTime::parse_from_rfc3339(lease_expires_at)
+            .map_err(|_| StoreError::Parse("invalid lease_expires_at".to_string()))?;
+        if expires > now {
+            return Err(StoreError::Conflict("lease_active".to_string()));
+        }
+    }
+
+    if row.deleted_at.is_none() {
+        add_stored_payload_bytes(&mut tx, &event_id.to_string(), -1).await?;
+    }
+    let deleted_at = row.deleted_at.unwrap_or_else(|| now_str.clone());
+    let mut erased_at = row.erased_at;
+
+    sqlx::query("UPDATE webhook_events SET deleted_at = ?, updated_at = ? WHERE id = ?")
+        .bind(&deleted_at)
+        .bind(&now_str)
+        .bind(event_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+    if hard && erased_at.is_none() {
+        // Metadata is pulled from the payload, and so is the event type for
+        // every provider but GitHub, which sends it in a header.
+        sqlx::query(
+            r"
+            UPDATE webhook_events
+            SET payload = '',
+                payload_encoding = 'utf8',
+                payload_sha256 = NULL,
+                headers = '{}',
+                last_error = NULL,
+                metadata = NULL,
+                event_type = CASE WHEN provider = 'github' THEN event_type END,
+                erased_at = ?1,
+                updated_at = ?1
+            WHERE id = ?2
+            ",
+        )
+        .bind(&now_str)
+        .bind(event_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        for table in ["webhook_attempt_logs", "webhook_attempt_logs_archive"] {
+            sqlx::query(&format!(
+                "UPDATE {table} \
+                SET request_headers = '{{}}', \
+                    request_body = '', \
+                    response_headers = NULL, \
+                    response_body = NULL, \
+                    error_message = NULL \
+                WHERE event_id = ?"
+            ))
+            .bind(event_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query(
+            r"
+            UPDATE shadow_attempt_logs
+            SET response_body = NULL,
+                error_message = NULL
+            WHERE event_id = ?
+            ",
+        )
+        .bind(event_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        erased_at = Some(now_str);
+    }
+
+    tx.commit().await?;
+
+    Ok(DeleteEventResponse {
+        event_id,
+        deleted_at,
+        erased_at,
+    })
+}
+
+#[derive(sqlx::FromRow)]
+struct DeleteEventRow {
+    status: String,
+    lease_expires_at: Option<String>,
+    deleted_at: Option<String>,
+    erased_at: Option<String>,
+}
+
+/// Groups failed attempts finished at or after `since` by endpoint, error
+/// kind, and response status class, most frequent first. An attempt counts
+/// as failed if it has an error kind or a non-2xx response.
+pub async fn summarize_errors(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    since: &str,
+    endpoint_id: Option<Uuid>,
+) -> Result<Vec<ErrorSummaryBucket>, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT \
+            e.endpoint_id, \
+            ep.target_url, \
+            a.error_kind, \
+            CASE \
+                WHEN a.response_status IS NULL THEN NULL \
+                ELSE (a.response_status / 100) || 'xx' \
+            END AS status_class, \
+            COUNT(*) AS count, \
+            MAX(a.finished_at) AS last_seen_at \
+        FROM webhook_attempt_logs_all a \
+        JOIN webhook_events e ON e.id = a.event_id \
+        JOIN endpoints ep ON ep.id = e.endpoint_id \
+        WHERE (a.error_kind IS NOT NULL \
+            OR a.response_status < 200 \
+            OR a.response_status >= 300) \
+          AND a.finished_at >= ",
+    );
+    query.push_bind(since);
+    access.push_predicate(&mut query, "e.endpoint_id");
+
+    if let Some(endpoint_id) = endpoint_id {
+        query.push(" AND e.endpoint_id = ");
+        query.push_bind(endpoint_id.to_string());
+    }
+
+    query.push(
+        " GROUP BY e.endpoint_id, ep.target_url, a.error_kind, status_class \
+        ORDER BY count DESC, last_seen_at DESC",
+    );
+
+    let rows: Vec<ErrorSummaryRow> = query.build_query_as().fetch_all(pool).await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(ErrorSummaryBucket {
+                endpoint_id: Uuid::parse_str(&row.endpoint_id)
+                    .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+                target_url: row.target_url,
+                error_kind: row
+                    .error_kind
+                    .as_deref()
+                    .map(parse_error_kind)
+                    .transpose()?,
+                status_class: row.status_class,
+                count: row.count,
+                last_seen_at: row.last_seen_at,
+            })
+        })
+        .collect()
+}
+
+#[derive(sqlx::FromRow)]
+struct ErrorSummaryRow {
+    endpoint_id: String,
+    target_url: String,
+    error_kind: Option<String>,
+    status_class: Option<String>,
+    count: i64,
+    last_seen_at: String,
+}
+
+const IN_FLIGHT_WITHOUT_LEASE: &str =
+    "status = 'in_flight' AND (lease_expires_at IS NULL OR leased_by IS NULL)";
+const DELIVERED_WITH_NEXT_ATTEMPT: &str =
+    "status = 'delivered' AND next_attempt_at IS NOT NULL AND next_attempt_at > ?";
+
+/// Finds events in states the dispatcher can never produce and, with
+/// `repair`, fixes them: lease-less in-flight events are requeued,
+/// delivered events lose their `next_attempt_at`, and `attempts` is reset
+/// to the number of attempt log rows plus the attempts sampled out of the
+/// log and those a replay carried over from its source.
+pub async fn run_doctor(pool: &SqlitePool, repair: bool) -> Result<DoctorReport, StoreError> {
+    let now_str = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut tx = pool.begin().await?;
+    let mut issues = Vec::new();
+
+    let ids: Vec<String> = sqlx::query_scalar(&format!(
+        "SELECT id FROM webhook_events WHERE {IN_FLIGHT_WITHOUT_LEASE} ORDER BY id"
+    ))
+    .fetch_all(&mut *tx)
+    .await?;
+    for id in ids {
+        issues.push(DoctorIssue {
+            event_id: parse_event_id(&id)?,
+            kind: DoctorIssueKind::InFlightWithoutLease,
+            detail: "in_flight without lease_expires_at or leased_by".to_string(),
+        });
+    }
+
+    let rows: Vec<(String, String)> = sqlx::query_as(&format!(
+        "SELECT id, next_attempt_at FROM webhook_events \
+        WHERE {DELIVERED_WITH_NEXT_ATTEMPT} ORDER BY id"
+    ))
+    .bind(&now_str)
+    .fetch_all(&mut *tx)
+    .await?;
+    for (id, next_attempt_at) in rows {
+        issues.push(DoctorIssue {
+            event_id: parse_event_id(&id)?,
+            kind: DoctorIssueKind::DeliveredWithNextAttempt,
+            detail: format!("delivered with next_attempt_at {next_attempt_at}"),
+        });
+    }
+
+    // Attempts left out by log sampling and attempts carried over from a
+    // replay's source are counted on the event.
+    let rows: Vec<(String, i64, i64, i64, i64)> = sqlx::query_as(
+        r"
+        SELECT e.id, e.attempts, COUNT(a.id), e.unlogged_attempts, e.carried_attempts
+        FROM webhook_events e
+        LEFT JOIN webhook_attempt_logs_all a ON a.event_id = e.id
+        GROUP BY e.id
+        HAVING e.attempts != COUNT(a.id) + e.unlogged_attempts + e.carried_attempts
+        ORDER BY e.id
+        ",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    for (id, attempts, logged, unlogged, carried) in rows {
+        let detail = if unlogged == 0 && carried == 0 {
+            format!("attempts is {attempts} but {logged} attempt logs exist")
+        } else {
+            format!(
+                "attempts is {attempts} but {logged} attempt logs exist, {unlogged} were sampled out and {carried} were carried over"
+            )
+        };
+        issues.push(DoctorIssue {
+            event_id: parse_event_id(&id)?,
+            kind: DoctorIssueKind::AttemptCountMismatch,
+            detail,
+        });
+    }
+
+    if repair && !issues.is_empty() {
+        sqlx::query(&format!(
+            "UPDATE webhook_events \
+            SET status = 'requeued', lease_expires_at = NULL, leased_by = NULL, updated_at = ? \
+            WHERE {IN_FLIGHT_WITHOUT_LEASE}"
+        ))
+        .bind(&now_str)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(&format!(
+            "UPDATE webhook_events SET next_attempt_at = NULL, updated_at = ? \
+            WHERE {DELIVERED_WITH_NEXT_ATTEMPT}"
+        ))
+        .bind(&now_str)
+        .bind(&now_str)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r"
+            UPDATE webhook_events
+            SET attempts = unlogged_attempts + carried_attempts + (
+                SELECT COUNT(*) FROM webhook_attempt_logs_all a WHERE a.event_id = webhook_events.id
+            ),
+                updated_at = ?
+            WHERE attempts != unlogged_attempts + carried_attempts + (
+                SELECT COUNT(*) FROM webhook_attempt_logs_all a WHERE a.event_id = webhook_events.id
+            )
+            ",
+        )
+        .bind(&now_str)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(DoctorReport {
+        checked_at: now_str,
+        repaired: repair,
+        issues,
+    })
+}
+
+/// Days of usage rollups averaged for the storage growth projection.
+const STORAGE_GROWTH_WINDOW_DAYS: i64 = 7;
+
+/// Database and per-table sizes with a growth projection. Table sizes
+/// come from `dbstat`, so every page is read; meant for occasional
+/// capacity checks, not dashboards polling it.
+pub async fn storage_report(
+    pool: &SqlitePool,
+    attempt_log_hot_days: i64,
+    max_db_bytes: Option<i64>,
+) -> Result<StorageReport, StoreError> {
+    let now = Utc::now();
+    let (db_bytes, free_bytes): (i64, i64) = sqlx::query_as(
+        r"
+        SELECT page_count * page_size, freelist_count * page_size
+        FROM pragma_page_count(), pragma_freelist_count(), pragma_page_size()
+        ",
+    )
+    .fetch_one(pool)
+    .await?;
+    let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+        .fetch_one(pool)
+        .await?;
+    let file: Option<String> =
+        sqlx::query_scalar("SELECT file FROM pragma_database_list WHERE name = 'main'")
+            .fetch_optional(pool)
+            .await?;
+    let (file_bytes, wal_bytes) = match file.filter(|file| !file.is_empty()) {
+        Some(file) => (
+            file_len(&file).await,
+            file_len(&format!("{file}-wal")).await,
+        ),
+        None => (None, None),
+    };
+
+    let names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_schema WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+    )
+    .fetch_all(pool)
+    .await?;
+    let sizes: BTreeMap<String, i64> = sqlx::query_as(
+        r"
+        SELECT s.tbl_name, SUM(d.pgsize)
+        FROM dbstat AS d
+        JOIN sqlite_schema AS s ON s.name = d.name
+        WHERE d.aggregate = TRUE
+        GROUP BY s.tbl_name
+        ",
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+    let mut tables = Vec::with_capacity(names.len());
+    for name in names {
+        let rows: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM \"{}\"",
+            name.replace('"', "\"\"")
+        ))
+        .fetch_one(pool)
+        .await?;
+        let bytes = sizes.get(&name).copied().unwrap_or(0);
+        tables.push(TableStorage { name, rows, bytes });
+    }
+    tables.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+
+    let archive_before = (now - chrono::Duration::days(attempt_log_hot_days))
+        .to_rfc3339_opts(SecondsFormat::Secs, true);
+    let attempt_logs_due_for_archive: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM webhook_attempt_logs WHERE finished_at < ?")
+            .bind(&archive_before)
+            .fetch_one(pool)
+            .await?;
+    let today = now.date_naive();
+    let window_start = today - chrono::Duration::days(STORAGE_GROWTH_WINDOW_DAYS);
+    let window_bytes: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(bytes_stored), 0) FROM usage_rollups WHERE day >= ? AND day < ?",
+    )
+    .bind(window_start.format("%Y-%m-%d").to_string())
+    .bind(today.format("%Y-%m-%d").to_string())
+    .fetch_one(pool)
+    .await?;
+    let daily_growth_bytes = window_bytes / STORAGE_GROWTH_WINDOW_DAYS;
+    let days_until_full = max_db_bytes
+        .filter(|_| daily_growth_bytes > 0)
+        .map(|max| (max - db_bytes).max(0) / daily_growth_bytes);
+
+    Ok(StorageReport {
+        generated_at: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        db_bytes,
+        free_bytes,
+        file_bytes,
+        wal_bytes,
+        journal_mode,
+        tables,
+        projection: StorageProjection {
+            attempt_log_hot_days,
+            attempt_logs_due_for_archive,
+            growth_window_days: STORAGE_GROWTH_WINDOW_DAYS,
+            daily_growth_bytes,
+            projected_db_bytes_30d: db_bytes + daily_growth_bytes * 30,
+            max_db_bytes,
+            days_until_full,
+        },
+    })
+}
+
+/// Size of the file at `path`; `None` when it does not exist.
+async fn file_len(path: &str) -> Option<i64> {
+    let metadata = tokio::fs::metadata(path).await.ok()?;
+    i64::try_from(metadata.len()).ok()
+}
+
+fn parse_event_id(id: &str) -> Result<Uuid, StoreError> {
+    Uuid::parse_str(id).map_err(|err| StoreError::Parse(format!("invalid event id: {err}")))
+}
+
+/// Cap on how many dead events a single group replay re-enqueues.
+const MAX_GROUP_REPLAY: i64 = 1000;
+
+/// Endpoints whose dead events `replay_dead_window` replays, or whose
+/// queued events a bulk cancel withdraws.
+#[derive(Debug, Clone, Copy)]
+pub enum DeadEventTarget {
+    Group(Uuid),
+    Endpoint(Uuid),
+}
+
+/// Dead events received at or after `received_since` and, if set, before
+/// `received_before`. At most `limit` are replayed, oldest first, and with
+/// `drip_rate_per_minute` only that many of them become due per minute.
+#[derive(Debug, Clone)]
+pub struct DeadEventWindow {
+    pub received_since: String,
+    pub received_before: Option<String>,
+    pub limit: i64,
+    pub drip_rate_per_minute: Option<i64>,
+}
+
+/// Outcome of `replay_group` or `replay_dead_window`: the events created,
+/// and the dead events skipped because their replay chain already hit the
+/// loop limit.
+#[derive(Debug, Clone, Default)]
+pub struct BulkReplay {
+    pub replayed_event_ids: Vec<Uuid>,
+    pub failures: Vec<OperationFailure>,
+}
+
+/// Queued events of `target` a bulk cancel or re-prioritization covers:
+/// `pending` or `requeued` events received at or after `received_since`
+/// and before `received_before`, when set. `event_type` is a glob such as
+/// `invoice.*`. Leased events are left to their worker.
+#[derive(Debug, Clone)]
+pub struct QueuedEventFilter {
+    pub target: DeadEventTarget,
+    pub provider: Option<String>,
+    pub event_type: Option<String>,
+    pub received_since: Option<String>,
+    pub received_before: Option<String>,
+}
+
+/// Selects the IDs of the events matching `filter`, leaving out those
+/// already at `except_priority` if set.
+fn push_queued_events(
+    query: &mut QueryBuilder<'_, Sqlite>,
+    filter: &QueuedEventFilter,
+    except_priority: Option<i64>,
+) {
+    query.push(
+        "SELECT e.id \
+        FROM webhook_events e \
+        JOIN endpoints ep ON ep.id = e.endpoint_id \
+        WHERE e.status IN ('pending', 'requeued') AND e.deleted_at IS NULL",
+    );
+    push_event_filter(query, filter);
+    if let Some(priority) = except_priority {
+        query.push(" AND COALESCE(e.priority, 0) != ");
+        query.push_bind(priority);
+    }
+}
+
+/// Narrows a query over `webhook_events e JOIN endpoints ep` to the events
+/// matching `filter`.
+fn push_event_filter(query: &mut QueryBuilder<'_, Sqlite>, filter: &QueuedEventFilter) {
+    match filter.target {
+        DeadEventTarget::Group(group_id) => {
+            query.push(" AND ep.group_id = ");
+            query.push_bind(group_id.to_string());
+        }
+        DeadEventTarget::Endpoint(endpoint_id) => {
+            query.push(" AND e.endpoint_id = ");
+            query.push_bind(endpoint_id.to_string());
+        }
+    }
+    if let Some(provider) = &filter.provider {
+        query.push(" AND e.provider = ");
+        query.push_bind(provider.clone());
+    }
+    if let Some(event_type) = &filter.event_type {
+        query.push(" AND e.event_type GLOB ");
+        query.push_bind(event_type.clone());
+    }
+    if let Some(received_since) = &filter.received_since {
+        query.push(" AND e.received_at >= ");
+        query.push_bind(received_since.clone());
+    }
+    if let Some(received_before) = &filter.received_before {
+        query.push(" AND e.received_at < ");
+        query.push_bind(received_before.clone());
+    }
+}
+
+pub async fn count_queued_events(
+    pool: &SqlitePool,
+    filter: &QueuedEventFilter,
+) -> Result<i64, StoreError> {
+    let mut query = QueryBuilder::new("SELECT COUNT(*) FROM (");
+    push_queued_events(&mut query, filter, None);
+    query.push(")");
+    Ok(query.build_query_scalar().fetch_one(pool).await?)
+}
+
+/// Cancels up to `limit` of the oldest events matching `filter` and
+/// returns how many were cancelled.
+pub async fn cancel_events_batch(
+    pool: &SqlitePool,
+    filter: &QueuedEventFilter,
+    limit: i64,
+) -> Result<i64, StoreError> {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut query = QueryBuilder::new(
+        "UPDATE webhook_events \
+        SET status = 'cancelled', \
+            next_attempt_at = NULL, \
+            last_error = 'cancelled in bulk', \
+            updated_at = ",
+    );
+    query.push_bind(now);
+    query.push(" WHERE status IN ('pending', 'requeued') AND id IN (");
+    push_queued_events(&mut query, filter, None);
+    query.push(" ORDER BY e.received_at ASC LIMIT ");
+    query.push_bind(limit);
+    query.push(")");
+    let result = query.build().execute(pool).await?;
+    Ok(i64::try_from(result.rows_affected()).unwrap_or(i64::MAX))
+}
+
+/// Counts the events matching `filter` not yet at `priority`.
+pub async fn count_events_to_reprioritize(
+    pool: &SqlitePool,
+    filter: &QueuedEventFilter,
+    priority: i64,
+) -> Result<i64, StoreError> {
+    let mut query = QueryBuilder::new("SELECT COUNT(*) FROM (");
+    push_queued_events(&mut query, filter, Some(priority));
+    query.push(")");
+    Ok(query.build_query_scalar().fetch_one(pool).await?)
+}
+
+/// Moves up to `limit` of the oldest events matching `filter` to
+/// `priority` and returns how many were changed.
+pub async fn reprioritize_events_batch(
+    pool: &SqlitePool,
+    filter: &QueuedEventFilter,
+    priority: i64,
+    limit: i64,
+) -> Result<i64, StoreError> {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut query = QueryBuilder::new("UPDATE webhook_events SET priority = ");
+    query.push_bind(priority);
+    query.push(", updated_at = ");
+    query.push_bind(now);
+    query.push(" WHERE status IN ('pending', 'requeued') AND id IN (");
+    push_queued_events(&mut query, filter, Some(priority));
+    query.push(" ORDER BY e.received_at ASC LIMIT ");
+    query.push_bind(limit);
+    query.push(")");
+    let result = query.build().execute(pool).await?;
+    Ok(i64::try_from(result.rows_affected()).unwrap_or(i64::MAX))
+}
+
+/// Selects the IDs of the finished events matching `filter` that still
+/// hold their payload.
+fn push_purgeable_events(query: &mut QueryBuilder<'_, Sqlite>, filter: &QueuedEventFilter) {
+    query.push(
+        "SELECT e.id \
+        FROM webhook_events e \
+        JOIN endpoints ep ON ep.id = e.endpoint_id \
+        WHERE e.status IN ('delivered', 'dead', 'expired', 'cancelled') \
+            AND e.erased_at IS NULL",
+    );
+    push_event_filter(query, filter);
+}
+
+pub async fn count_purgeable_events(
+    pool: &SqlitePool,
+    filter: &QueuedEventFilter,
+) -> Result<i64, StoreError> {
+    let mut query = QueryBuilder::new("SELECT COUNT(*) FROM (");
+    push_purgeable_events(&mut query, filter);
+    query.push(")");
+    Ok(query.build_query_scalar().fetch_one(pool).await?)
+}
+
+/// Hard deletes up to `limit` of the oldest finished events matching
+/// `filter`, as `delete_event` does, and returns how many were purged.
+pub async fn purge_events_batch(
+    pool: &SqlitePool,
+    filter: &QueuedEventFilter,
+    limit: i64,
+) -> Result<i64, StoreError> {
+    let mut query = QueryBuilder::new("");
+    push_purgeable_events(&mut query, filter);
+    query.push(" ORDER BY e.received_at ASC LIMIT ");
+    query.push_bind(limit);
+    let event_ids: Vec<String> = query.build_query_scalar().fetch_all(pool).await?;
+    for event_id in &event_ids {
+        let event_id = Uuid::parse_str(event_id)
+            .map_err(|_| StoreError::Parse("invalid event id".to_string()))?;
+        delete_event(pool, &EndpointScope::All, event_id, true).await?;
+    }
+    Ok(i64::try_from(event_ids.len()).unwrap_or(i64::MAX))
+}
+
+pub async fn create_endpoint_group(
+    pool: &SqlitePool,
+    name: &str,
+    rate_limit_per_minute: Option<i64>,
+) -> Result<EndpointGroup, StoreError> {
+    let id = Uuid::new_v4();
+    let created_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    let result = sqlx::query(
+        r"
+        INSERT INTO endpoint_groups (id, name, paused, rate_limit_per_minute, created_at)
+        VALUES (?, ?, 0, ?, ?)
+        ",
+    )
+    .bind(id.to_string())
+    .bind(name)
+    .bind(rate_limit_per_minute)
+    .bind(&created_at)
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(_) => get_endpoint_group(pool, id).await,
+        Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {
+            Err(StoreError::Conflict("group_name_taken".to_string()))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn list_endpoint_groups(pool: &SqlitePool) -> Result<Vec<EndpointGroup>, StoreError> {
+    sqlx::query_as::<_, EndpointGroupRow>(
+        r"
+        SELECT id, name, paused, rate_limit_per_minute, created_at
+        FROM endpoint_groups
+        ORDER BY name ASC
+        ",
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(EndpointGroup::try_from)
+    .collect()
+}
+
+pub async fn get_endpoint_group(
+    pool: &SqlitePool,
+    group_id: Uuid,
+) -> Result<EndpointGroup, StoreError> {
+    sqlx::query_as::<_, EndpointGroupRow>(
+        r"
+        SELECT id, name, paused, rate_limit_per_minute, created_at
+        FROM endpoint_groups
+        WHERE id = ?
+        ",
+    )
+    .bind(group_id.to_string())
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| StoreError::NotFound("group not found".to_string()))?
+    .try_into()
+}
+
+pub async fn set_endpoint_group(
+    pool: &SqlitePool,
+    endpoint_id: Uuid,
+    group_id: Option<Uuid>,
+) -> Result<EndpointGroupAssignment, StoreError> {
+    if let Some(group_id) = group_id {
+        get_endpoint_group(pool, group_id).await?;
+    }
+
+    let result = sqlx::query("UPDATE endpoints SET group_id = ? WHERE id = ?")
+        .bind(group_id.map(|id| id.to_string()))
+        .bind(endpoint_id.to_string())
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(StoreError::NotFound("endpoint not found".to_string()));
+    }
+
+    Ok(EndpointGroupAssignment {
+        endpoint_id,
+        group_id,
+    })
+}
+
+/// Pausing a group keeps `lease_events` from handing out any of its
+/// endpoints' events until it is resumed.
+pub async fn set_group_paused(
+    pool: &SqlitePool,
+    group_id: Uuid,
+    paused: bool,
+) -> Result<EndpointGroup, StoreError> {
+    let result = sqlx::query("UPDATE endpoint_groups SET paused = ? WHERE id = ?")
+        .bind(paused)
+        .bind(group_id.to_string())
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(StoreError::NotFound("group not found".to_string()));
+    }
+
+    get_endpoint_group(pool, group_id).await
+}
+
+pub async fn set_group_rate_limit(
+    pool: &SqlitePool,
+    group_id: Uuid,
+    rate_limit_per_minute: Option<i64>,
+) -> Result<EndpointGroup, StoreError> {
+    let result = sqlx::query("UPDATE endpoint_groups SET rate_limit_per_minute = ? WHERE id = ?")
+        .bind(rate_limit_per_minute)
+        .bind(group_id.to_string())
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(StoreError::NotFound("group not found".to_string()));
+    }
+
+    get_endpoint_group(pool, group_id).await
+}
+
+/// Quotas and current usage of every group, or only of `group_id`.
+/// Usage counts today's events (UTC) and the payload bytes of events that
+/// are not deleted.
+pub async fn group_quotas(
+    pool: &SqlitePool,
+    group_id: Option<Uuid>,
+) -> Result<Vec<GroupQuota>, StoreError> {
+    let today = Utc::now().format("%Y-%m-%d").to_string();
+    let mut query = QueryBuilder::new(
+        "SELECT g.id AS group_id, g.quota_events_per_day, g.quota_storage_bytes, \
+            g.quota_rejections, g.quota_exceeded_at, \
+            COALESCE((SELECT SUM(u.events_ingested) FROM usage_rollups u \
+                JOIN endpoints ep ON ep.id = u.endpoint_id \
+                WHERE ep.group_id = g.id AND u.day = ",
+    );
+    query.push_bind(&today);
+    query.push(
+        "), 0) AS events_today, \
+            COALESCE((SELECT SUM(s.payload_bytes) FROM endpoint_storage s \
+                JOIN endpoints ep ON ep.id = s.endpoint_id \
+                WHERE ep.group_id = g.id), 0) AS stored_bytes \
+        FROM endpoint_groups g WHERE 1 = 1",
+    );
+    if let Some(group_id) = group_id {
+        query.push(" AND g.id = ");
+        query.push_bind(group_id.to_string());
+    }
+    query.push(" ORDER BY g.name ASC");
+
+    let rows: Vec<GroupQuotaRow> = query.build_query_as().fetch_all(pool).await?;
+    rows.into_iter()
+        .map(|row| {
+            let exceeded_today = row
+                .quota_exceeded_at
+                .as_deref()
+                .is_some_and(|at| at.starts_with(&today));
+            Ok(GroupQuota {
+                group_id: Uuid::parse_str(&row.group_id)
+                    .map_err(|err| StoreError::Parse(format!("invalid group id: {err}")))?,
+                events_per_day: row.quota_events_per_day,
+                storage_bytes: row.quota_storage_bytes,
+                events_today: row.events_today,
+                stored_bytes: row.stored_bytes,
+                rejections_total: row.quota_rejections,
+                last_exceeded_at: row.quota_exceeded_at,
+                exceeded_today,
+            })
+        })
+        .collect()
+}
+
+pub async fn get_group_quota(pool: &SqlitePool, group_id: Uuid) -> Result<GroupQuota, StoreError> {
+    group_quotas(pool, Some(group_id))
+        .await?
+        .pop()
+        .ok_or_else(|| StoreError::NotFound("group not found".to_string()))
+}
+
+pub async fn set_group_quota(
+    pool: &SqlitePool,
+    group_id: Uuid,
+    events_per_day: Option<i64>,
+    storage_bytes: Option<i64>,
+) -> Result<GroupQuota, StoreError> {
+    let result = sqlx::query(
+        "UPDATE endpoint_groups SET quota_events_per_day = ?, quota_storage_bytes = ? WHERE id = ?",
+    )
+    .bind(events_per_day)
+    .bind(storage_bytes)
+    .bind(group_id.to_string())
+    .execute(pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(StoreError::NotFound("group not found".to_string()));
+    }
+
+    get_group_quota(pool, group_id).await
+}
+
+/// Replays every dead event of the group's endpoints, oldest first, and
+/// returns the IDs of the new events. With `skip_if_pending`, events that
+/// already have an undelivered copy are left out. With
+/// `drip_rate_per_minute`, the new events become due one after another at
+/// that rate, starting now. With `loop_limit`, events whose replay chain
+/// already holds that many undelivered replays are skipped and reported
+/// as failures. Progress is recorded on `operation_id` if set.
+pub async fn replay_group(
+    pool: &SqlitePool,
+    group_id: Uuid,
+    reset_circuit: bool,
+    skip_if_pending: bool,
+    drip_rate_per_minute: Option<i64>,
+    loop_limit: Option<i64>,
+    operation_id: Option<Uuid>,
+) -> Result<BulkReplay, StoreError> {
+    get_endpoint_group(pool, group_id).await?;
+
+    let dead_ids: Vec<String> = sqlx::query_scalar(
+        r"
+        SELECT e.id
+        FROM webhook_events e
+        JOIN endpoints ep ON ep.id = e.endpoint_id
+        WHERE ep.group_id = ?
+          AND e.status = 'dead'
+          AND e.deleted_at IS NULL
+        ORDER BY e.received_at ASC
+        LIMIT ?
+        ",
+    )
+    .bind(group_id.to_string())
+    .bind(MAX_GROUP_REPLAY)
+    .fetch_all(pool)
+    .await?;
+
+    replay_dead_events(
+        pool,
+        dead_ids,
+        reset_circuit,
+        skip_if_pending,
+        drip_rate_per_minute,
+        loop_limit,
+        operation_id,
+    )
+    .await
+}
+
+/// Number of dead events a `replay_group` call would currently pick up.
+pub async fn count_group_replay(pool: &SqlitePool, group_id: Uuid) -> Result<i64, StoreError> {
+    get_endpoint_group(pool, group_id).await?;
+    let matched: i64 = sqlx::query_scalar(
+        r"
+        SELECT COUNT(*) FROM (
+            SELECT 1
+            FROM webhook_events e
+            JOIN endpoints ep ON ep.id = e.endpoint_id
+            WHERE ep.group_id = ?
+              AND e.status = 'dead'
+              AND e.deleted_at IS NULL
+            LIMIT ?
+        )
+        ",
+    )
+    .bind(group_id.to_string())
+    .bind(MAX_GROUP_REPLAY)
+    .fetch_one(pool)
+    .await?;
+    Ok(matched)
+}
+
+/// Replays the target's dead events from an outage window, skipping any
+/// that already have an undelivered copy so repeated calls are harmless.
+/// With `loop_limit`, events whose replay chain already holds that many
+/// undelivered replays are skipped and reported as failures. Progress is
+/// recorded on `operation_id` if set.
+pub async fn replay_dead_window(
+    pool: &SqlitePool,
+    target: DeadEventTarget,
+    window: &DeadEventWindow,
+    loop_limit: Option<i64>,
+    operation_id: Option<Uuid>,
+) -> Result<BulkReplay, StoreError> {
+    let mut query = QueryBuilder::new(
+        "SELECT e.id \
+        FROM webhook_events e \
+        JOIN endpoints ep ON ep.id = e.endpoint_id \
+        WHERE e.status = 'dead' AND e.deleted_at IS NULL",
+    );
+    match target {
+        DeadEventTarget::Group(group_id) => {
+            query.push(" AND ep.group_id = ");
+            query.push_bind(group_id.to_string());
+        }
+        DeadEventTarget::Endpoint(endpoint_id) => {
+            query.push(" AND e.endpoint_id = ");
+            query.push_bind(endpoint_id.to_string());
+        }
+    }
+    query.push(" AND e.received_at >= ");
+    query.push_bind(&window.received_since);
+    if let Some(received_before) = &window.received_before {
+        query.push(" AND e.received_at < ");
+        query.push_bind(received_before);
+    }
+    query.push(" ORDER BY e.received_at ASC LIMIT ");
+    query.push_bind(window.limit.min(MAX_GROUP_REPLAY));
+    let dead_ids: Vec<String> = query.build_query_scalar().fetch_all(pool).await?;
+
+    replay_dead_events(
+        pool,
+        dead_ids,
+        false,
+        true,
+        window.drip_rate_per_minute,
+        loop_limit,
+        operation_id,
+    )
+    .await
+}
+
+/// Replays `dead_ids` in order for `replay_group` and `replay_dead_window`.
+/// With `drip_rate_per_minute`, each new event is inserted already due one
+/// interval after the previous one, starting now.
+async fn replay_dead_events(
+    pool: &SqlitePool,
+    dead_ids: Vec<String>,
+    reset_circuit: bool,
+    skip_if_pending: bool,
+    drip_rate_per_minute: Option<i64>,
+    loop_limit: Option<i64>,
+    operation_id: Option<Uuid>,
+) -> Result<BulkReplay, StoreError> {
+    let drip_interval_ms = drip_rate_per_minute
+        .filter(|rate| *rate > 0)
+        .map(|rate| 60_000 / rate);
+    let started_at = Utc::now();
+
+    if let Some(operation_id) = operation_id {
+        set_operation_total(pool, operation_id, dead_ids.len()).await?;
+    }
+    let mut replayed = BulkReplay {
+        replayed_event_ids: Vec::with_capacity(dead_ids.len()),
+        failures: Vec::new(),
+    };
+    for id in dead_ids {
+        let event_id = parse_event_id(&id)?;
+        let next_attempt_at = drip_interval_ms.map(|interval_ms| {
+            let offset_ms = interval_ms.saturating_mul(replayed.replayed_event_ids.len() as i64);
+            (started_at + chrono::Duration::milliseconds(offset_ms))
+                .to_rfc3339_opts(SecondsFormat::Secs, true)
+        });
+        let options = ReplayOptions {
+            reset_circuit,
+            skip_if_pending,
+            attempt_budget: ReplayAttemptBudget::Reset,
+            loop_limit,
+            next_attempt_at,
+        };
+        let mut failure = None;
+        let created = match replay_copy(pool, &EndpointScope::All, event_id, &options).await {
+            Ok(result) => Some(result.event.id),
+            Err(StoreError::Conflict(reason)) if reason == "pending_replay_exists" => None,
+            Err(StoreError::Conflict(reason)) if reason == "replay_loop_detected" => {
+                failure = Some(OperationFailure { event_id, reason });
+                None
+            }
+            Err(err) => return Err(err),
+        };
+        replayed.replayed_event_ids.extend(created);
+        let cancelled = match operation_id {
+            Some(operation_id) => {
+                advance_operation(pool, operation_id, created, failure.as_ref()).await?
+            }
+            None => false,
+        };
+        replayed.failures.extend(failure);
+        if cancelled {
+            break;
+        }
+    }
+
+    Ok(replayed)
+}
+
+/// Cap on how many operations `list_operations` returns.
+const MAX_LISTED_OPERATIONS: i64 = 100;
+
+/// Records a bulk operation started by `actor` as running and returns its
+/// ID. Replays given the ID fill in its total and progress.
+pub async fn start_operation(
+    pool: &SqlitePool,
+    kind: OperationKind,
+    target: DeadEventTarget,
+    actor: &str,
+) -> Result<Uuid, StoreError> {
+    let id = Uuid::new_v4();
+    let (group_id, endpoint_id) = match target {
+        DeadEventTarget::Group(group_id) => (Some(group_id), None),
+        DeadEventTarget::Endpoint(endpoint_id) => (None, Some(endpoint_id)),
+    };
+    sqlx::query(
+        r"
+        INSERT INTO operations (id, kind, status, group_id, endpoint_id, actor, created_at)
+        VALUES (?, ?, 'running', ?, ?, ?, ?)
+        ",
+    )
+    .bind(id.to_string())
+    .bind(operation_kind_to_str(kind))
+    .bind(group_id.map(|id| id.to_string()))
+    .bind(endpoint_id.map(|id| id.to_string()))
+    .bind(actor)
+    .bind(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
+
+/// Marks a running operation completed, or cancelled if that was
+/// requested, or failed with the error it stopped on.
+pub async fn finish_operation<T>(
+    pool: &SqlitePool,
+    operation_id: Uuid,
+    result: &Result<T, StoreError>,
+) -> Result<(), StoreError> {
+    let (status, error) = match result {
+        Ok(_) => ("completed", None),
+        Err(StoreError::Db(err)) => ("failed", Some(err.to_string())),
+        Err(
+            StoreError::Conflict(message)
+            | StoreError::NotFound(message)
+            | StoreError::Parse(message),
+        ) => ("failed", Some(message.clone())),
+    };
+    sqlx::query(
+        r"
+        UPDATE operations
+        SET status = CASE
+                WHEN ?1 = 'completed' AND cancel_requested_at IS NOT NULL THEN 'cancelled'
+                ELSE ?1
+            END,
+            error = ?2,
+            finished_at = ?3
+        WHERE id = ?4
+        ",
+    )
+    .bind(status)
+    .bind(error)
+    .bind(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+    .bind(operation_id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn set_operation_total(
+    pool: &SqlitePool,
+    operation_id: Uuid,
+    total: usize,
+) -> Result<(), StoreError> {
+    sqlx::query("UPDATE operations SET total = ? WHERE id = ?")
+        .bind(i64::try_from(total).unwrap_or(i64::MAX))
+        .bind(operation_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Counts one more handled event, remembering the event it created or the
+/// reason it was skipped, if any. Returns whether the operation should stop
+/// because cancellation was requested.
+async fn advance_operation(
+    pool: &SqlitePool,
+    operation_id: Uuid,
+    created: Option<Uuid>,
+    failure: Option<&OperationFailure>,
+) -> Result<bool, StoreError> {
+    let mut tx = pool.begin().await?;
+    if let Some(event_id) = created {
+        sqlx::query("INSERT INTO operation_events (operation_id, event_id) VALUES (?, ?)")
+            .bind(operation_id.to_string())
+            .bind(event_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+    }
+    if let Some(failure) = failure {
+        sqlx::query(
+            "INSERT INTO operation_failures (operation_id, event_id, reason) VALUES (?, ?, ?)",
+        )
+        .bind(operation_id.to_string())
+        .bind(failure.event_id.to_string())
+        .bind(&failure.reason)
+        .execute(&mut *tx)
+        .await?;
+    }
+    let cancelled: bool = sqlx::query_scalar(
+        r"
+        UPDATE operations SET processed = processed + 1
+        WHERE id = ?
+        RETURNING cancel_requested_at IS NOT NULL
+        ",
+    )
+    .bind(operation_id.to_string())
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(cancelled)
+}
+
+/// Most recent bulk operations, newest first.
+pub async fn list_operations(pool: &SqlitePool) -> Result<Vec<InspectorOperation>, StoreError> {
+    let rows: Vec<OperationRow> = sqlx::query_as(
+        r"
+        SELECT id, kind, status, group_id, endpoint_id, actor, total, processed, error,
+            created_at, finished_at, undone_at, undone_by
+        FROM operations
+        ORDER BY created_at DESC, rowid DESC
+        LIMIT ?
+        ",
+    )
+    .bind(MAX_LISTED_OPERATIONS)
+    .fetch_all(pool)
+    .await?;
+
+    let mut operations = Vec::with_capacity(rows.len());
+    for row in rows {
+        let event_ids = operation_event_ids(pool, &row.id).await?;
+        let failures = operation_failures(pool, &row.id).await?;
+        operations.push(map_operation(row, event_ids, failures)?);
+    }
+    Ok(operations)
+}
+
+/// Cancels the events created by an operation that are still waiting for
+/// delivery by soft-deleting them, and marks the operation undone. Leased,
+/// delivered, and dead events are skipped. An operation can be undone once.
+pub async fn undo_operation(
+    pool: &SqlitePool,
+    operation_id: Uuid,
+    actor: &str,
+) -> Result<UndoOperationResponse, StoreError> {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut tx = pool.begin().await?;
+
+    let row: OperationRow = sqlx::query_as(
+        r"
+        SELECT id, kind, status, group_id, endpoint_id, actor, total, processed, error,
+            created_at, finished_at, undone_at, undone_by
+        FROM operations
+        WHERE id = ?
+        ",
+    )
+    .bind(operation_id.to_string())
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| StoreError::NotFound("operation not found".to_string()))?;
+    if row.undone_at.is_some() {
+        return Err(StoreError::Conflict("operation_already_undone".to_string()));
+    }
+    if row.status == "running" {
+        return Err(StoreError::Conflict("operation_running".to_string()));
+    }
+
+    sqlx::query(
+        r"
+        INSERT INTO endpoint_storage (endpoint_id, payload_bytes)
+        SELECT endpoint_id, -SUM(LENGTH(CAST(payload AS BLOB)))
+        FROM webhook_events
+        WHERE id IN (SELECT event_id FROM operation_events WHERE operation_id = ?)
+          AND status IN ('pending', 'requeued', 'paused')
+          AND deleted_at IS NULL
+        GROUP BY endpoint_id
+        ON CONFLICT(endpoint_id) DO UPDATE SET
+            payload_bytes = payload_bytes + excluded.payload_bytes
+        ",
+    )
+    .bind(&row.id)
+    .execute(&mut *tx)
+    .await?;
+    let cancelled: Vec<String> = sqlx::query_scalar(
+        r"
+        UPDATE webhook_events
+        SET deleted_at = ?, updated_at = ?
+        WHERE id IN (SELECT event_id FROM operation_events WHERE operation_id = ?)
+          AND status IN ('pending', 'requeued', 'paused')
+          AND deleted_at IS NULL
+        RETURNING id
+        ",
+    )
+    .bind(&now)
+    .bind(&now)
+    .bind(&row.id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE operations SET undone_at = ?, undone_by = ? WHERE id = ?")
+        .bind(&now)
+        .bind(actor)
+        .bind(&row.id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    let event_ids = operation_event_ids(pool, &row.id).await?;
+    let failures = operation_failures(pool, &row.id).await?;
+    let cancelled: HashSet<String> = cancelled.into_iter().collect();
+    let mut cancelled_event_ids = Vec::new();
+    let mut skipped_event_ids = Vec::new();
+    for event_id in &event_ids {
+        if cancelled.contains(&event_id.to_string()) {
+            cancelled_event_ids.push(*event_id);
+        } else {
+            skipped_event_ids.push(*event_id);
+        }
+    }
+    let operation = map_operation(
+        OperationRow {
+            undone_at: Some(now),
+            undone_by: Some(actor.to_string()),
+            ..row
+        },
+        event_ids,
+        failures,
+    )?;
+    Ok(UndoOperationResponse {
+        operation,
+        cancelled_event_ids,
+        skipped_event_ids,
+    })
+}
+
+async fn operation_event_ids(
+    pool: &SqlitePool,
+    operation_id: &str,
+) -> Result<Vec<Uuid>, StoreError> {
+    let ids: Vec<String> = sqlx::query_scalar(
+        "SELECT event_id FROM operation_events WHERE operation_id = ? ORDER BY rowid",
+    )
+    .bind(operation_id)
+    .fetch_all(pool)
+    .await?;
+    ids.iter().map(|id| parse_event_id(id)).collect()
+}
+
+async fn operation_failures(
+    pool: &SqlitePool,
+    operation_id: &str,
+) -> Result<Vec<OperationFailure>, StoreError> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT event_id, reason FROM operation_failures WHERE operation_id = ? ORDER BY rowid",
+    )
+    .bind(operation_id)
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter()
+        .map(|(event_id, reason)| {
+            Ok(OperationFailure {
+                event_id: parse_event_id(&event_id)?,
+                reason,
+            })
+        })
+        .collect()
+}
+
+fn map_operation(
+    row: OperationRow,
+    event_ids: Vec<Uuid>,
+    failures: Vec<OperationFailure>,
+) -> Result<InspectorOperation, StoreError> {
+    Ok(InspectorOperation {
+        id: Uuid::parse_str(&row.id)
+            .map_err(|err| StoreError::Parse(format!("invalid operation id: {err}")))?,
+        kind: parse_operation_kind(&row.kind)?,
+        status: parse_operation_status(&row.status)?,
+        group_id: parse_optional_uuid("group_id", row.group_id.as_deref())?,
+        endpoint_id: parse_optional_uuid("endpoint_id", row.endpoint_id.as_deref())?,
+        actor: row.actor,
+        total: row.total,
+        processed: row.processed,
+        event_ids,
+        failures,
+        error: row.error,
+        created_at: row.created_at,
+        finished_at: row.finished_at,
+        undone_at: row.undone_at,
+        undone_by: row.undone_by,
+    })
+}
+
+/// Closes the endpoint's circuit and clears its failure count.
+pub async fn close_circuit(
+    pool: &SqlitePool,
+    access: &EndpointScope,
+    endpoint_id: Uuid,
+) -> Result<(), StoreError> {
+    ensure_endpoint_exists(pool, access, endpoint_id).await?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        r"
+        INSERT INTO target_circuit_transitions (
+            endpoint_id, state, open_until, consecutive_failures, changed_at
+        )
+        SELECT endpoint_id, 'closed', NULL, 0, ?
+        FROM target_circuit_states
+        WHERE endpoint_id = ? AND state = 'open'
+        ",
+    )
+    .bind(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+    .bind(endpoint_id.to_string())
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        r"
+        UPDATE target_circuit_states
+        SET state = 'closed',
+            open_until = NULL,
+            consecutive_failures = 0,
+            last_failure_at = NULL
+        WHERE endpoint_id = ?
         ",
     )
-    .bind(new_event_id.to_string())
-    .bind(&row.endpoint_id)
-    .bind(event_id.to_string())
-    .bind(&row.provider)
-    .bind(&row.headers)
-    .bind(&row.payload)
-    .bind(&row.received_at)
+    .bind(endpoint_id.to_string())
     .execute(&mut *tx)
     .await?;
+    tx.commit().await?;
 
-    if reset_circuit {
-        sqlx::query(
-            r"
-            UPDATE target_circuit_states
-            SET state = 'closed',
-                open_until = NULL,
-                consecutive_failures = 0,
-                last_failure_at = NULL
-            WHERE endpoint_id = ?
-            ",
-        )
-        .bind(&row.endpoint_id)
-        .execute(&mut *tx)
-        .await?;
+    Ok(())
+}
+
+#[derive(sqlx::FromRow)]
+struct TlsExpiryRow {
+    endpoint_id: String,
+    url: String,
+    tls_expires_at: String,
+    checked_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct EndpointHealthRow {
+    check_method: String,
+    check_url: Option<String>,
+    state: Option<String>,
+    open_until: Option<String>,
+    consecutive_failures: Option<i64>,
+    last_failure_at: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct EndpointCheckRow {
+    id: String,
+    endpoint_id: String,
+    worker_id: String,
+    url: String,
+    finished_at: String,
+    reachable: bool,
+    response_status: Option<i64>,
+    latency_ms: i64,
+    error_message: Option<String>,
+    tls_expires_at: Option<String>,
+}
+
+impl TryFrom<EndpointCheckRow> for EndpointCheck {
+    type Error = StoreError;
+
+    fn try_from(row: EndpointCheckRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Uuid::parse_str(&row.id)
+                .map_err(|err| StoreError::Parse(format!("invalid check id: {err}")))?,
+            endpoint_id: Uuid::parse_str(&row.endpoint_id)
+                .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+            worker_id: row.worker_id,
+            url: row.url,
+            checked_at: row.finished_at,
+            reachable: row.reachable,
+            response_status: row.response_status,
+            latency_ms: row.latency_ms,
+            error_message: row.error_message,
+            tls_expires_at: row.tls_expires_at,
+        })
     }
+}
 
-    let endpoint_row = sqlx::query_as::<_, ReplayEndpointRow>(
-        r"
-        SELECT ep.target_url,
-               c.state AS circuit_state,
-               c.open_until AS circuit_open_until,
-               c.consecutive_failures AS circuit_consecutive_failures,
-               c.last_failure_at AS circuit_last_failure_at
-        FROM endpoints ep
-        LEFT JOIN target_circuit_states c ON c.endpoint_id = ep.id
-        WHERE ep.id = ?
-        ",
-    )
-    .bind(&row.endpoint_id)
-    .fetch_optional(&mut *tx)
-    .await?
-    .ok_or_else(|| StoreError::NotFound("endpoint not found".to_string()))?;
+#[derive(sqlx::FromRow)]
+struct ProviderCounterRow {
+    provider: String,
+    accepted: i64,
+    rejected_signature: i64,
+    rejected_size: i64,
+    rejected_schema: i64,
+}
 
-    tx.commit().await?;
+#[derive(sqlx::FromRow)]
+struct AttemptBucketRow {
+    endpoint_id: String,
+    bucket: i64,
+    attempts: i64,
+    failures: i64,
+    avg_latency_ms: f64,
+}
 
-    let summary = WebhookEventSummary {
-        id: new_event_id,
-        endpoint_id: Uuid::parse_str(&row.endpoint_id)
-            .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
-        replayed_from_event_id: Some(event_id),
-        provider: row.provider,
-        status: WebhookEventStatus::Pending,
-        attempts: 0,
-        received_at: row.received_at,
-        next_attempt_at: None,
-        last_error: None,
-    };
+#[derive(sqlx::FromRow)]
+struct SloRow {
+    endpoint_id: String,
+    target_seconds: i64,
+    objective_percent: f64,
+    breaches_total: i64,
+    delivered: i64,
+    delivered_within_target: i64,
+    overdue: i64,
+}
 
-    let circuit = map_circuit(
-        &row.endpoint_id,
-        endpoint_row.circuit_state.as_deref(),
-        endpoint_row.circuit_open_until.as_deref(),
-        endpoint_row.circuit_consecutive_failures,
-        endpoint_row.circuit_last_failure_at.as_deref(),
-    )?;
+impl TryFrom<SloRow> for SloAttainment {
+    type Error = StoreError;
 
-    Ok(ReplayEventResponse {
-        event: summary,
-        circuit,
-    })
+    fn try_from(row: SloRow) -> Result<Self, Self::Error> {
+        let attainment_percent = (row.delivered > 0)
+            .then(|| row.delivered_within_target as f64 * 100.0 / row.delivered as f64);
+        Ok(Self {
+            endpoint_id: Uuid::parse_str(&row.endpoint_id)
+                .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+            target_seconds: row.target_seconds,
+            objective_percent: row.objective_percent,
+            delivered: row.delivered,
+            delivered_within_target: row.delivered_within_target,
+            overdue: row.overdue,
+            attainment_percent,
+            met: attainment_percent.is_none_or(|percent| percent >= row.objective_percent),
+            breaches_total: row.breaches_total,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct EndpointGroupRow {
+    id: String,
+    name: String,
+    paused: bool,
+    rate_limit_per_minute: Option<i64>,
+    created_at: String,
+}
+
+impl TryFrom<EndpointGroupRow> for EndpointGroup {
+    type Error = StoreError;
+
+    fn try_from(row: EndpointGroupRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Uuid::parse_str(&row.id)
+                .map_err(|err| StoreError::Parse(format!("invalid group id: {err}")))?,
+            name: row.name,
+            paused: row.paused,
+            rate_limit_per_minute: row.rate_limit_per_minute,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CircuitTransitionRow {
+    state: String,
+    open_until: Option<String>,
+    consecutive_failures: i64,
+    changed_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct OperationRow {
+    id: String,
+    kind: String,
+    status: String,
+    group_id: Option<String>,
+    endpoint_id: Option<String>,
+    actor: String,
+    total: i64,
+    processed: i64,
+    error: Option<String>,
+    created_at: String,
+    finished_at: Option<String>,
+    undone_at: Option<String>,
+    undone_by: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct GroupQuotaRow {
+    group_id: String,
+    quota_events_per_day: Option<i64>,
+    quota_storage_bytes: Option<i64>,
+    quota_rejections: i64,
+    quota_exceeded_at: Option<String>,
+    events_today: i64,
+    stored_bytes: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct MaintenanceWindowRow {
+    day_of_week: Option<i64>,
+    start_minute: i64,
+    duration_minutes: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct PayloadSchemaRow {
+    provider: String,
+    event_type: String,
+    schema: String,
+    reject_invalid: bool,
+    updated_at: String,
+}
+
+impl From<PayloadSchemaRow> for PayloadSchema {
+    fn from(row: PayloadSchemaRow) -> Self {
+        Self {
+            provider: row.provider,
+            event_type: row.event_type,
+            schema: row.schema,
+            reject_invalid: row.reject_invalid,
+            updated_at: row.updated_at,
+        }
+    }
 }
 
 #[derive(sqlx::FromRow)]
@@ -344,17 +4708,46 @@ struct ListEventRow {
     id: String,
     endpoint_id: String,
     replayed_from_event_id: Option<String>,
+    source_id: Option<String>,
     provider: String,
     status: String,
     attempts: i64,
     received_at: String,
     next_attempt_at: Option<String>,
     last_error: Option<String>,
-    target_url: String,
+    updated_at: Option<String>,
+    target_url: Option<String>,
+    endpoint_name: Option<String>,
+    endpoint_description: Option<String>,
     circuit_state: Option<String>,
     circuit_open_until: Option<String>,
     circuit_consecutive_failures: Option<i64>,
     circuit_last_failure_at: Option<String>,
+    preview_head: Option<Vec<u8>>,
+    preview_truncated: Option<bool>,
+    preview_size_bytes: Option<i64>,
+    preview_encoding: Option<String>,
+    preview_is_json: Option<bool>,
+    preview_content_type: Option<String>,
+    last_attempt_status: Option<i64>,
+    last_attempt_error_kind: Option<String>,
+    last_attempt_finished_at: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct CustomerEventRow {
+    id: String,
+    endpoint_id: String,
+    replayed_from_event_id: Option<String>,
+    source_id: Option<String>,
+    provider: String,
+    status: String,
+    attempts: i64,
+    received_at: String,
+    next_attempt_at: Option<String>,
+    last_error: Option<String>,
+    updated_at: Option<String>,
+    metadata: String,
 }
 
 #[derive(sqlx::FromRow)]
@@ -362,23 +4755,119 @@ struct GetEventRow {
     id: String,
     endpoint_id: String,
     replayed_from_event_id: Option<String>,
+    source_id: Option<String>,
     provider: String,
+    provider_event_id: Option<String>,
     headers: String,
-    payload: String,
+    payload: Vec<u8>,
+    payload_encoding: String,
+    payload_sha256: Option<String>,
+    scrub_ruleset_id: Option<String>,
+    scrub_rule_version: Option<i64>,
+    event_type: Option<String>,
+    schema_errors: Option<String>,
+    dropped_headers: Option<String>,
+    metadata: Option<String>,
     status: String,
     attempts: i64,
     received_at: String,
     next_attempt_at: Option<String>,
+    expires_at: Option<String>,
     lease_expires_at: Option<String>,
     leased_by: Option<String>,
     last_error: Option<String>,
+    updated_at: Option<String>,
     target_url: String,
+    endpoint_name: Option<String>,
+    endpoint_description: Option<String>,
     circuit_state: Option<String>,
     circuit_open_until: Option<String>,
     circuit_consecutive_failures: Option<i64>,
     circuit_last_failure_at: Option<String>,
 }
 
+#[derive(sqlx::FromRow)]
+struct ExportEventRow {
+    id: String,
+    endpoint_id: String,
+    provider: String,
+    provider_event_id: Option<String>,
+    event_type: Option<String>,
+    status: String,
+    attempts: i64,
+    received_at: String,
+    updated_at: Option<String>,
+    last_error: Option<String>,
+    last_attempt_at: Option<String>,
+    last_response_status: Option<i64>,
+    last_duration_ms: Option<i64>,
+    total_duration_ms: Option<i64>,
+}
+
+impl TryFrom<ExportEventRow> for EventExportRecord {
+    type Error = StoreError;
+
+    fn try_from(row: ExportEventRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Uuid::parse_str(&row.id)
+                .map_err(|err| StoreError::Parse(format!("invalid event id: {err}")))?,
+            endpoint_id: Uuid::parse_str(&row.endpoint_id)
+                .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
+            provider: row.provider,
+            provider_event_id: row.provider_event_id,
+            event_type: row.event_type,
+            status: parse_status(&row.status)?,
+            attempts: row.attempts,
+            received_at: row.received_at,
+            updated_at: row.updated_at,
+            last_attempt_at: row.last_attempt_at,
+            last_response_status: row.last_response_status,
+            last_duration_ms: row.last_duration_ms,
+            total_duration_ms: row.total_duration_ms,
+            last_error: row.last_error,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ShadowAttemptRow {
+    id: String,
+    event_id: String,
+    worker_id: String,
+    target_url: String,
+    started_at: String,
+    finished_at: String,
+    response_status: Option<i64>,
+    response_body: Option<String>,
+    error_kind: Option<String>,
+    error_message: Option<String>,
+}
+
+impl TryFrom<ShadowAttemptRow> for ShadowAttemptLog {
+    type Error = StoreError;
+
+    fn try_from(row: ShadowAttemptRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Uuid::parse_str(&row.id)
+                .map_err(|err| StoreError::Parse(format!("invalid attempt id: {err}")))?,
+            event_id: Uuid::parse_str(&row.event_id)
+                .map_err(|err| StoreError::Parse(format!("invalid event id: {err}")))?,
+            worker_id: row.worker_id,
+            target_url: row.target_url,
+            started_at: row.started_at,
+            finished_at: row.finished_at,
+            response_status: row.response_status,
+            response_body: row.response_body,
+            error_kind: row
+                .error_kind
+                .as_deref()
+                .map(parse_error_kind)
+                .transpose()?,
+            error_message: row.error_message,
+        })
+    }
+}
+
 #[derive(sqlx::FromRow)]
 struct ListAttemptsRow {
     event_id: String,
@@ -393,6 +4882,23 @@ struct ListAttemptsRow {
     response_body: Option<String>,
     error_kind: Option<String>,
     error_message: Option<String>,
+    response_capture: Option<String>,
+    target_revision: Option<i64>,
+    target_url: Option<String>,
+    final_url: Option<String>,
+    peer_address: Option<String>,
+    dns_ms: Option<i64>,
+    connect_ms: Option<i64>,
+    tls_ms: Option<i64>,
+    ttfb_ms: Option<i64>,
+    total_ms: Option<i64>,
+    worker_id: Option<String>,
+    worker_version: Option<String>,
+    worker_region: Option<String>,
+    sample_rate: Option<i64>,
+    unlogged_attempts: i64,
+    unlogged_sample_rate: Option<i64>,
+    delivery_id: String,
 }
 
 #[derive(sqlx::FromRow)]
@@ -400,12 +4906,23 @@ struct ListAttemptsRow {
 struct ReplaySourceRow {
     id: String,
     endpoint_id: String,
+    source_id: Option<String>,
     provider: String,
-    headers: String,
-    payload: String,
     status: String,
+    attempts: i64,
     received_at: String,
     lease_expires_at: Option<String>,
+    root_event_id: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct LineageRow {
+    id: String,
+    replayed_from_event_id: Option<String>,
+    status: String,
+    attempts: i64,
+    attempts_made: i64,
+    updated_at: Option<String>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -426,25 +4943,47 @@ fn list_item_from_row(
         .map_err(|err| StoreError::Parse(format!("invalid event id: {err}")))?;
     let endpoint_id = Uuid::parse_str(&row.endpoint_id)
         .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?;
-    let replayed_from_event_id = match row.replayed_from_event_id {
-        Some(value) if value.is_empty() => None,
-        Some(value) => Some(
-            Uuid::parse_str(&value)
-                .map_err(|err| StoreError::Parse(format!("invalid replayed_from_event_id: {err}")))?,
-        ),
-        None => None,
+    let replayed_from_event_id =
+        match row.replayed_from_event_id {
+            Some(value) if value.is_empty() => None,
+            Some(value) => Some(Uuid::parse_str(&value).map_err(|err| {
+                StoreError::Parse(format!("invalid replayed_from_event_id: {err}"))
+            })?),
+            None => None,
+        };
+    let payload_preview = match (row.preview_head, row.preview_encoding) {
+        (Some(head), Some(encoding)) => {
+            let (snippet, encoding) = decode_payload(head, &encoding)?;
+            let is_json = row.preview_is_json.unwrap_or(false);
+            Some(PayloadPreview {
+                content_type: detect_content_type(
+                    row.preview_content_type.as_deref(),
+                    is_json,
+                    encoding,
+                ),
+                snippet,
+                encoding,
+                truncated: row.preview_truncated.unwrap_or(false),
+                is_json,
+                size_bytes: row.preview_size_bytes.unwrap_or(0),
+            })
+        }
+        _ => None,
     };
 
     let event = WebhookEventSummary {
         id: event_id,
         endpoint_id,
         replayed_from_event_id,
+        source_id: parse_optional_uuid("source id", row.source_id.as_deref())?,
         provider: row.provider,
         status,
         attempts: row.attempts,
         received_at: row.received_at.clone(),
         next_attempt_at: row.next_attempt_at,
         last_error: row.last_error,
+        updated_at: row.updated_at,
+        payload_preview,
     };
 
     let circuit = map_circuit(
@@ -455,11 +4994,27 @@ fn list_item_from_row(
         row.circuit_last_failure_at.as_deref(),
     )?;
 
+    let last_attempt = match row.last_attempt_finished_at {
+        Some(finished_at) => Some(LastAttemptSummary {
+            response_status: row.last_attempt_status,
+            error_kind: row
+                .last_attempt_error_kind
+                .as_deref()
+                .map(parse_error_kind)
+                .transpose()?,
+            finished_at,
+        }),
+        None => None,
+    };
+
     Ok((
         WebhookEventListItem {
             event,
-            target_url: row.target_url,
+            target_url: row.target_url.unwrap_or_default(),
+            endpoint_name: row.endpoint_name,
+            endpoint_description: row.endpoint_description,
             circuit,
+            last_attempt,
         },
         InspectorCursor {
             received_at: row.received_at,
@@ -473,6 +5028,12 @@ fn get_event_from_row(row: GetEventRow) -> Result<GetEventResponse, StoreError>
     let headers: BTreeMap<String, String> = serde_json::from_str(&row.headers)
         .map_err(|err| StoreError::Parse(format!("invalid headers JSON: {err}")))?;
 
+    let payload_integrity = match row.payload_sha256.as_deref() {
+        None => PayloadIntegrity::Unrecorded,
+        Some(expected) if expected == payload_sha256(&row.payload) => PayloadIntegrity::Verified,
+        Some(_) => PayloadIntegrity::Mismatch,
+    };
+    let (payload, payload_encoding) = decode_payload(row.payload, &row.payload_encoding)?;
     let event = WebhookEvent {
         id: Uuid::parse_str(&row.id)
             .map_err(|err| StoreError::Parse(format!("invalid event id: {err}")))?,
@@ -480,22 +5041,36 @@ fn get_event_from_row(row: GetEventRow) -> Result<GetEventResponse, StoreError>
             .map_err(|err| StoreError::Parse(format!("invalid endpoint id: {err}")))?,
         replayed_from_event_id: match row.replayed_from_event_id {
             Some(value) if value.is_empty() => None,
-            Some(value) => Some(
-                Uuid::parse_str(&value)
-                    .map_err(|err| StoreError::Parse(format!("invalid replayed_from_event_id: {err}")))?,
-            ),
+            Some(value) => Some(Uuid::parse_str(&value).map_err(|err| {
+                StoreError::Parse(format!("invalid replayed_from_event_id: {err}"))
+            })?),
             None => None,
         },
+        source_id: parse_optional_uuid("source id", row.source_id.as_deref())?,
         provider: row.provider,
+        provider_event_id: row.provider_event_id,
         headers,
-        payload: row.payload,
+        payload,
+        payload_encoding,
+        payload_sha256: row.payload_sha256,
+        scrub_ruleset_id: parse_optional_uuid("scrub ruleset id", row.scrub_ruleset_id.as_deref())?,
+        scrub_rule_version: row.scrub_rule_version,
+        event_type: row.event_type,
+        schema_errors: row
+            .schema_errors
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(|err| StoreError::Parse(format!("invalid schema errors JSON: {err}")))?,
         status,
         attempts: row.attempts,
         received_at: row.received_at,
         next_attempt_at: row.next_attempt_at,
+        expires_at: row.expires_at,
         lease_expires_at: row.lease_expires_at,
         leased_by: row.leased_by,
         last_error: row.last_error,
+        updated_at: row.updated_at,
     };
 
     let circuit = map_circuit(
@@ -506,13 +5081,52 @@ fn get_event_from_row(row: GetEventRow) -> Result<GetEventResponse, StoreError>
         row.circuit_last_failure_at.as_deref(),
     )?;
 
+    let dropped_headers = row
+        .dropped_headers
+        .as_deref()
+        .map(serde_json::from_str)
+        .transpose()
+        .map_err(|err| StoreError::Parse(format!("invalid dropped headers JSON: {err}")))?
+        .unwrap_or_default();
+    let metadata = row
+        .metadata
+        .as_deref()
+        .map(serde_json::from_str)
+        .transpose()
+        .map_err(|err| StoreError::Parse(format!("invalid metadata JSON: {err}")))?;
+
     Ok(GetEventResponse {
         event,
+        payload_integrity,
+        dropped_headers,
+        metadata,
         target_url: row.target_url,
+        endpoint_name: row.endpoint_name,
+        endpoint_description: row.endpoint_description,
         circuit,
     })
 }
 
+#[derive(sqlx::FromRow)]
+struct UsageRow {
+    endpoint_id: String,
+    group_id: Option<String>,
+    day: String,
+    events_ingested: i64,
+    deliveries_attempted: i64,
+    bytes_stored: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct AttemptRequestRow {
+    event_id: String,
+    endpoint_id: String,
+    target_url: String,
+    target_kind: String,
+    request_headers: String,
+    request_body: String,
+}
+
 fn attempt_from_optional_row(
     row: ListAttemptsRow,
 ) -> Result<Option<WebhookAttemptLog>, StoreError> {
@@ -536,6 +5150,14 @@ fn attempt_from_optional_row(
         .request_body
         .ok_or_else(|| StoreError::Parse("attempt row missing request_body".to_string()))?;
 
+    let timing = AttemptTiming {
+        dns_ms: row.dns_ms,
+        connect_ms: row.connect_ms,
+        tls_ms: row.tls_ms,
+        ttfb_ms: row.ttfb_ms,
+        total_ms: row.total_ms,
+    };
+
     let request_headers: BTreeMap<String, String> = serde_json::from_str(&request_headers)
         .map_err(|err| StoreError::Parse(format!("invalid request headers JSON: {err}")))?;
     let response_headers = match row.response_headers {
@@ -551,6 +5173,11 @@ fn attempt_from_optional_row(
         Some(kind) => Some(parse_error_kind(kind)?),
         None => None,
     };
+    let response_capture = row
+        .response_capture
+        .as_deref()
+        .map(parse_response_capture)
+        .transpose()?;
 
     Ok(Some(WebhookAttemptLog {
         id: Uuid::parse_str(&attempt_id)
@@ -567,9 +5194,28 @@ fn attempt_from_optional_row(
         response_body: row.response_body,
         error_kind,
         error_message: row.error_message,
+        response_capture,
+        target_revision: row.target_revision,
+        target_url: row.target_url,
+        final_url: row.final_url,
+        peer_address: row.peer_address,
+        timing: Some(timing).filter(|timing| *timing != AttemptTiming::default()),
+        worker_id: row.worker_id,
+        worker_version: row.worker_version,
+        worker_region: row.worker_region,
+        sample_rate: row.sample_rate,
+        delivery_id: Uuid::parse_str(&row.delivery_id)
+            .map_err(|err| StoreError::Parse(format!("invalid delivery id: {err}")))?,
     }))
 }
 
+fn parse_optional_uuid(field: &str, value: Option<&str>) -> Result<Option<Uuid>, StoreError> {
+    value
+        .map(Uuid::parse_str)
+        .transpose()
+        .map_err(|err| StoreError::Parse(format!("invalid {field}: {err}")))
+}
+
 fn map_circuit(
     endpoint_id: &str,
     state: Option<&str>,
@@ -592,6 +5238,37 @@ fn map_circuit(
     }))
 }
 
+/// Renders a stored payload: text as-is, BLOBs as base64.
+fn decode_payload(
+    payload: Vec<u8>,
+    encoding: &str,
+) -> Result<(String, PayloadEncoding), StoreError> {
+    match encoding {
+        "utf8" => String::from_utf8(payload)
+            .map(|text| (text, PayloadEncoding::Utf8))
+            .map_err(|err| StoreError::Parse(format!("invalid utf8 payload: {err}"))),
+        "base64" => Ok((STANDARD.encode(payload), PayloadEncoding::Base64)),
+        other => Err(StoreError::Parse(format!(
+            "unknown payload encoding: {other}"
+        ))),
+    }
+}
+
+/// Media type of the `content-type` header without its parameters, or a
+/// guess from the payload when the header is missing.
+fn detect_content_type(header: Option<&str>, is_json: bool, encoding: PayloadEncoding) -> String {
+    let declared = header
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase())
+        .filter(|value| !value.is_empty());
+    match (declared, encoding) {
+        (Some(declared), _) => declared,
+        (None, _) if is_json => "application/json".to_string(),
+        (None, PayloadEncoding::Utf8) => "text/plain".to_string(),
+        (None, PayloadEncoding::Base64) => "application/octet-stream".to_string(),
+    }
+}
+
 fn parse_status(status: &str) -> Result<WebhookEventStatus, StoreError> {
     match status {
         "pending" => Ok(WebhookEventStatus::Pending),
@@ -600,11 +5277,13 @@ fn parse_status(status: &str) -> Result<WebhookEventStatus, StoreError> {
         "delivered" => Ok(WebhookEventStatus::Delivered),
         "dead" => Ok(WebhookEventStatus::Dead),
         "paused" => Ok(WebhookEventStatus::Paused),
+        "expired" => Ok(WebhookEventStatus::Expired),
+        "cancelled" => Ok(WebhookEventStatus::Cancelled),
         other => Err(StoreError::Parse(format!("unknown status: {other}"))),
     }
 }
 
-fn status_to_str(status: WebhookEventStatus) -> &'static str {
+pub(crate) fn status_to_str(status: WebhookEventStatus) -> &'static str {
     match status {
         WebhookEventStatus::Pending => "pending",
         WebhookEventStatus::InFlight => "in_flight",
@@ -612,6 +5291,115 @@ fn status_to_str(status: WebhookEventStatus) -> &'static str {
         WebhookEventStatus::Delivered => "delivered",
         WebhookEventStatus::Dead => "dead",
         WebhookEventStatus::Paused => "paused",
+        WebhookEventStatus::Expired => "expired",
+        WebhookEventStatus::Cancelled => "cancelled",
+    }
+}
+
+fn parse_tls_expires_at(raw: &str) -> Result<chrono::DateTime<Utc>, StoreError> {
+    chrono::DateTime::parse_from_rfc3339(raw)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| StoreError::Parse("invalid tls_expires_at".to_string()))
+}
+
+fn parse_operation_kind(kind: &str) -> Result<OperationKind, StoreError> {
+    match kind {
+        "group_replay" => Ok(OperationKind::GroupReplay),
+        "outage_replay" => Ok(OperationKind::OutageReplay),
+        other => Err(StoreError::Parse(format!(
+            "unknown operation kind: {other}"
+        ))),
+    }
+}
+
+fn parse_operation_status(status: &str) -> Result<OperationStatus, StoreError> {
+    match status {
+        "running" => Ok(OperationStatus::Running),
+        "completed" => Ok(OperationStatus::Completed),
+        "failed" => Ok(OperationStatus::Failed),
+        "cancelled" => Ok(OperationStatus::Cancelled),
+        other => Err(StoreError::Parse(format!(
+            "unknown operation status: {other}"
+        ))),
+    }
+}
+
+fn operation_kind_to_str(kind: OperationKind) -> &'static str {
+    match kind {
+        OperationKind::GroupReplay => "group_replay",
+        OperationKind::OutageReplay => "outage_replay",
+    }
+}
+
+fn parse_redirect_mode(mode: &str) -> Result<RedirectMode, StoreError> {
+    match mode {
+        "none" => Ok(RedirectMode::None),
+        "same_host" => Ok(RedirectMode::SameHost),
+        "any" => Ok(RedirectMode::Any),
+        other => Err(StoreError::Parse(format!("unknown redirect mode: {other}"))),
+    }
+}
+
+fn redirect_mode_to_str(mode: RedirectMode) -> &'static str {
+    match mode {
+        RedirectMode::None => "none",
+        RedirectMode::SameHost => "same_host",
+        RedirectMode::Any => "any",
+    }
+}
+
+fn parse_region_mode(mode: &str) -> Result<RegionMode, StoreError> {
+    match mode {
+        "prefer" => Ok(RegionMode::Prefer),
+        "require" => Ok(RegionMode::Require),
+        other => Err(StoreError::Parse(format!("unknown region mode: {other}"))),
+    }
+}
+
+fn region_mode_to_str(mode: RegionMode) -> &'static str {
+    match mode {
+        RegionMode::Prefer => "prefer",
+        RegionMode::Require => "require",
+    }
+}
+
+fn parse_address_family(family: &str) -> Result<AddressFamily, StoreError> {
+    match family {
+        "any" => Ok(AddressFamily::Any),
+        "ipv4" => Ok(AddressFamily::Ipv4),
+        "ipv6" => Ok(AddressFamily::Ipv6),
+        "prefer_ipv4" => Ok(AddressFamily::PreferIpv4),
+        "prefer_ipv6" => Ok(AddressFamily::PreferIpv6),
+        other => Err(StoreError::Parse(format!(
+            "unknown address family: {other}"
+        ))),
+    }
+}
+
+fn address_family_to_str(family: AddressFamily) -> &'static str {
+    match family {
+        AddressFamily::Any => "any",
+        AddressFamily::Ipv4 => "ipv4",
+        AddressFamily::Ipv6 => "ipv6",
+        AddressFamily::PreferIpv4 => "prefer_ipv4",
+        AddressFamily::PreferIpv6 => "prefer_ipv6",
+    }
+}
+
+fn parse_check_method(method: &str) -> Result<EndpointCheckMethod, StoreError> {
+    match method {
+        "head" => Ok(EndpointCheckMethod::Head),
+        "options" => Ok(EndpointCheckMethod::Options),
+        "get" => Ok(EndpointCheckMethod::Get),
+        other => Err(StoreError::Parse(format!("unknown check method: {other}"))),
+    }
+}
+
+fn check_method_to_str(method: EndpointCheckMethod) -> &'static str {
+    match method {
+        EndpointCheckMethod::Head => "head",
+        EndpointCheckMethod::Options => "options",
+        EndpointCheckMethod::Get => "get",
     }
 }
 
@@ -628,9 +5416,26 @@ fn parse_circuit_status(status: &str) -> Result<TargetCircuitStatus, StoreError>
 fn parse_error_kind(kind: &str) -> Result<WebhookAttemptErrorKind, StoreError> {
     match kind {
         "timeout" => Ok(WebhookAttemptErrorKind::Timeout),
+        "dns" => Ok(WebhookAttemptErrorKind::Dns),
+        "tls" => Ok(WebhookAttemptErrorKind::Tls),
+        "connection_refused" => Ok(WebhookAttemptErrorKind::ConnectionRefused),
+        "too_many_redirects" => Ok(WebhookAttemptErrorKind::TooManyRedirects),
+        "payload_too_large" => Ok(WebhookAttemptErrorKind::PayloadTooLarge),
         "network" => Ok(WebhookAttemptErrorKind::Network),
         "invalid_response" => Ok(WebhookAttemptErrorKind::InvalidResponse),
         "unexpected" => Ok(WebhookAttemptErrorKind::Unexpected),
         other => Err(StoreError::Parse(format!("unknown error kind: {other}"))),
     }
 }
+
+fn parse_response_capture(capture: &str) -> Result<ResponseCapture, StoreError> {
+    match capture {
+        "failure" => Ok(ResponseCapture::Failure),
+        "all" => Ok(ResponseCapture::All),
+        "sampled" => Ok(ResponseCapture::Sampled),
+        "dropped" => Ok(ResponseCapture::Dropped),
+        other => Err(StoreError::Parse(format!(
+            "unknown response capture: {other}"
+        ))),
+    }
+}
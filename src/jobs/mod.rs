@@ -0,0 +1,16 @@
+mod reconcile;
+mod runner;
+mod store;
+
+pub use reconcile::{
+    MAX_RECONCILE_RESULT_IDS, RECONCILE_PROVIDERS, create_reconcile_job, get_reconcile_report,
+    lease_reconcile_runs, record_reconcile_result,
+};
+pub use runner::{
+    GroupReplayJob, spawn_backfill_job, spawn_bulk_cancel_job, spawn_export_job,
+    spawn_group_replay_job, spawn_import_job, spawn_purge_job, spawn_reprioritize_job,
+};
+pub use store::{
+    JobOutcome, StoreError, advance_job, cancel_job, create_job, fail_interrupted_jobs, finish_job,
+    get_job, list_jobs, set_job_total, start_job,
+};
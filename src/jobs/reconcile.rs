@@ -0,0 +1,395 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use sqlx::{QueryBuilder, SqlitePool};
+use uuid::Uuid;
+
+use super::store::{StoreError, get_job};
+use crate::types::{
+    Job, ReconcileLeaseRequest, ReconcileMissingEvent, ReconcileReport, ReconcileResultRequest,
+    ReconcileResultResponse, ReconcileTask, StartReconcileRequest,
+};
+
+/// Providers with a list-deliveries API a worker can reconcile against.
+pub const RECONCILE_PROVIDERS: [&str; 2] = ["stripe", "github"];
+
+/// Cap on the provider event IDs one reconciliation result may carry.
+pub const MAX_RECONCILE_RESULT_IDS: usize = 10_000;
+
+#[derive(sqlx::FromRow)]
+struct RunRow {
+    source_id: String,
+    provider: String,
+    endpoint_id: String,
+    window_start: String,
+    window_end: String,
+    fetch_missing: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct TaskRow {
+    job_id: String,
+    source_id: String,
+    slug: String,
+    provider: String,
+    window_start: String,
+    window_end: String,
+    fetch_missing: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct ResultRow {
+    status: String,
+    leased_by: Option<String>,
+    lease_expires_at: Option<String>,
+    provider: String,
+    endpoint_id: String,
+    fetch_missing: bool,
+}
+
+/// Queues a reconciliation job for a worker to pick up. `since` and
+/// `until` are expected normalized to UTC.
+pub async fn create_reconcile_job(
+    pool: &SqlitePool,
+    actor: &str,
+    req: &StartReconcileRequest,
+) -> Result<Job, StoreError> {
+    let source_id = req.source_id.to_string();
+    let mut tx = pool.begin().await?;
+
+    let provider: String = sqlx::query_scalar("SELECT provider FROM sources WHERE id = ?")
+        .bind(&source_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| StoreError::NotFound("source not found".to_string()))?;
+    if !RECONCILE_PROVIDERS.contains(&provider.as_str()) {
+        return Err(StoreError::Conflict(format!(
+            "provider {provider} has no deliveries API to reconcile against"
+        )));
+    }
+
+    let job_id = Uuid::new_v4();
+    sqlx::query(
+        r"
+        INSERT INTO jobs (id, kind, status, actor, created_at)
+        VALUES (?, 'reconcile', 'queued', ?, ?)
+        ",
+    )
+    .bind(job_id.to_string())
+    .bind(actor)
+    .bind(now())
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        r"
+        INSERT INTO reconcile_runs (job_id, source_id, window_start, window_end, fetch_missing)
+        VALUES (?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(job_id.to_string())
+    .bind(&source_id)
+    .bind(&req.since)
+    .bind(&req.until)
+    .bind(req.fetch_missing)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    get_job(pool, job_id).await
+}
+
+/// The job and the provider events it found missing, each with the event
+/// that has been ingested for it since, if any.
+pub async fn get_reconcile_report(
+    pool: &SqlitePool,
+    job_id: Uuid,
+) -> Result<ReconcileReport, StoreError> {
+    let job = get_job(pool, job_id).await?;
+    let run: RunRow = sqlx::query_as(
+        r"
+        SELECT r.source_id, s.provider, s.endpoint_id, r.window_start, r.window_end,
+            r.fetch_missing
+        FROM reconcile_runs r
+        JOIN sources s ON s.id = r.source_id
+        WHERE r.job_id = ?
+        ",
+    )
+    .bind(job_id.to_string())
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| StoreError::NotFound("reconciliation not found".to_string()))?;
+
+    let rows: Vec<(String, Option<String>)> = sqlx::query_as(
+        r"
+        SELECT m.provider_event_id, (
+            SELECT e.id FROM webhook_events e
+            WHERE e.provider = ?1
+              AND e.provider_event_id = m.provider_event_id
+              AND e.endpoint_id = ?2
+            ORDER BY e.received_at
+            LIMIT 1
+        )
+        FROM reconcile_missing m
+        WHERE m.job_id = ?3
+        ORDER BY m.provider_event_id
+        ",
+    )
+    .bind(&run.provider)
+    .bind(&run.endpoint_id)
+    .bind(job_id.to_string())
+    .fetch_all(pool)
+    .await?;
+    let missing = rows
+        .into_iter()
+        .map(|(provider_event_id, event_id)| {
+            Ok(ReconcileMissingEvent {
+                provider_event_id,
+                event_id: event_id
+                    .as_deref()
+                    .map(|id| parse_uuid("event id", id))
+                    .transpose()?,
+            })
+        })
+        .collect::<Result<_, StoreError>>()?;
+
+    Ok(ReconcileReport {
+        job,
+        source_id: parse_uuid("source id", &run.source_id)?,
+        provider: run.provider,
+        since: run.window_start,
+        until: run.window_end,
+        fetch_missing: run.fetch_missing,
+        missing,
+    })
+}
+
+/// Claims up to `req.limit` queued reconciliations, and ones whose worker
+/// let the lease run out, and marks their jobs running. A cancelled job
+/// that lost its worker is closed instead of handed out again.
+pub async fn lease_reconcile_runs(
+    pool: &SqlitePool,
+    req: &ReconcileLeaseRequest,
+) -> Result<Vec<ReconcileTask>, StoreError> {
+    let now = Utc::now();
+    let now_str = format_utc(now);
+    let lease_expires_at = format_utc(now + Duration::milliseconds(req.lease_ms));
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r"
+        UPDATE jobs
+        SET status = 'cancelled', finished_at = ?1
+        WHERE kind = 'reconcile'
+          AND status = 'running'
+          AND cancel_requested_at IS NOT NULL
+          AND id IN (SELECT job_id FROM reconcile_runs WHERE lease_expires_at <= ?1)
+        ",
+    )
+    .bind(&now_str)
+    .execute(&mut *tx)
+    .await?;
+
+    let rows: Vec<TaskRow> = sqlx::query_as(
+        r"
+        SELECT r.job_id, r.source_id, s.slug, s.provider, r.window_start, r.window_end,
+            r.fetch_missing
+        FROM reconcile_runs r
+        JOIN jobs j ON j.id = r.job_id
+        JOIN sources s ON s.id = r.source_id
+        WHERE j.status = 'queued'
+           OR (j.status = 'running' AND r.lease_expires_at <= ?)
+        ORDER BY j.created_at, j.rowid
+        LIMIT ?
+        ",
+    )
+    .bind(&now_str)
+    .bind(req.limit)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for row in &rows {
+        sqlx::query(
+            "UPDATE jobs SET status = 'running', started_at = COALESCE(started_at, ?) \
+            WHERE id = ?",
+        )
+        .bind(&now_str)
+        .bind(&row.job_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE reconcile_runs SET leased_by = ?, lease_expires_at = ? WHERE job_id = ?",
+        )
+        .bind(&req.worker_id)
+        .bind(&lease_expires_at)
+        .bind(&row.job_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(ReconcileTask {
+                job_id: parse_uuid("job id", &row.job_id)?,
+                source_id: parse_uuid("source id", &row.source_id)?,
+                source_slug: row.slug,
+                provider: row.provider,
+                since: row.window_start,
+                until: row.window_end,
+                fetch_missing: row.fetch_missing,
+            })
+        })
+        .collect()
+}
+
+/// Records what the worker holding the lease found, and finishes the job:
+/// failed when the worker reports an error, completed otherwise. The
+/// listed events never ingested for the source's endpoint are kept for the
+/// job's report.
+pub async fn record_reconcile_result(
+    pool: &SqlitePool,
+    req: &ReconcileResultRequest,
+) -> Result<ReconcileResultResponse, StoreError> {
+    let now = Utc::now();
+    let now_str = format_utc(now);
+    let job_id = req.job_id.to_string();
+
+    let mut tx = pool.begin().await?;
+
+    let run: ResultRow = sqlx::query_as(
+        r"
+        SELECT j.status, r.leased_by, r.lease_expires_at, s.provider, s.endpoint_id,
+            r.fetch_missing
+        FROM reconcile_runs r
+        JOIN jobs j ON j.id = r.job_id
+        JOIN sources s ON s.id = r.source_id
+        WHERE r.job_id = ?
+        ",
+    )
+    .bind(&job_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| StoreError::NotFound("reconciliation not found".to_string()))?;
+    let (Some(leased_by), Some(lease_expires_at)) = (run.leased_by, run.lease_expires_at) else {
+        return Err(StoreError::Conflict("lease_missing".to_string()));
+    };
+    if run.status != "running" {
+        return Err(StoreError::Conflict("lease_missing".to_string()));
+    }
+    if leased_by != req.worker_id {
+        return Err(StoreError::Conflict("lease_not_owned".to_string()));
+    }
+    if let Ok(expires) = DateTime::parse_from_rfc3339(&lease_expires_at)
+        && expires <= now
+    {
+        return Err(StoreError::Conflict("lease_expired".to_string()));
+    }
+
+    sqlx::query(
+        "UPDATE reconcile_runs SET leased_by = NULL, lease_expires_at = NULL WHERE job_id = ?",
+    )
+    .bind(&job_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if let Some(error) = req.error.as_deref() {
+        sqlx::query("UPDATE jobs SET status = 'failed', error = ?, finished_at = ? WHERE id = ?")
+            .bind(error)
+            .bind(&now_str)
+            .bind(&job_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        return Ok(ReconcileResultResponse {
+            checked: 0,
+            missing: Vec::new(),
+            fetch_missing: run.fetch_missing,
+        });
+    }
+
+    let mut seen = HashSet::new();
+    let listed: Vec<&String> = req
+        .provider_event_ids
+        .iter()
+        .filter(|id| seen.insert(id.as_str()))
+        .collect();
+
+    let found: HashSet<String> = if listed.is_empty() {
+        HashSet::new()
+    } else {
+        let mut query = QueryBuilder::new(
+            "SELECT DISTINCT provider_event_id \
+            FROM webhook_events \
+            WHERE provider = ",
+        );
+        query.push_bind(&run.provider);
+        query.push(" AND endpoint_id = ");
+        query.push_bind(&run.endpoint_id);
+        query.push(" AND provider_event_id IN (");
+        let mut ids = query.separated(", ");
+        for id in &listed {
+            ids.push_bind(id.as_str());
+        }
+        ids.push_unseparated(")");
+        query
+            .build_query_scalar::<String>()
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .collect()
+    };
+    let missing: Vec<String> = listed
+        .iter()
+        .filter(|id| !found.contains(id.as_str()))
+        .map(|id| (*id).clone())
+        .collect();
+
+    for provider_event_id in &missing {
+        sqlx::query(
+            "INSERT OR IGNORE INTO reconcile_missing (job_id, provider_event_id) VALUES (?, ?)",
+        )
+        .bind(&job_id)
+        .bind(provider_event_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let checked = listed.len() as i64;
+    sqlx::query(
+        r"
+        UPDATE jobs
+        SET status = CASE WHEN cancel_requested_at IS NOT NULL THEN 'cancelled' ELSE 'completed' END,
+            total = ?1,
+            processed = ?1,
+            finished_at = ?2
+        WHERE id = ?3
+        ",
+    )
+    .bind(checked)
+    .bind(&now_str)
+    .bind(&job_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(ReconcileResultResponse {
+        checked,
+        missing,
+        fetch_missing: run.fetch_missing,
+    })
+}
+
+fn now() -> String {
+    format_utc(Utc::now())
+}
+
+fn format_utc(dt: DateTime<Utc>) -> String {
+    dt.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn parse_uuid(field: &str, value: &str) -> Result<Uuid, StoreError> {
+    Uuid::parse_str(value).map_err(|err| StoreError::Parse(format!("invalid {field}: {err}")))
+}
@@ -0,0 +1,382 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::store::{JobOutcome, StoreError, advance_job, finish_job, set_job_total, start_job};
+use crate::inspector::{
+    self, CSV_COLUMNS, EndpointScope, ExportEventsParams, QueuedEventFilter, cancel_events_batch,
+    count_events_to_reprioritize, count_purgeable_events, count_queued_events, export_events,
+    finish_operation, purge_events_batch, render_csv_rows, render_ndjson, replay_group,
+    reprioritize_events_batch,
+};
+use crate::migrate::{Backfill, BackfillError, backfill_batch, claim_backfill, complete_backfill};
+use crate::snapshot::{SnapshotError, check_snapshot_header, restore_snapshot_records};
+use crate::types::ExportFormat;
+
+/// Events written between progress updates of an export job.
+const EXPORT_CHUNK: usize = 500;
+/// Rowids filled per transaction by a backfill job.
+const BACKFILL_BATCH: i64 = 1000;
+/// Pause between the batches of background jobs, so writers
+/// are not starved.
+const BATCH_PAUSE: Duration = Duration::from_millis(50);
+/// Events changed per statement by bulk cancel and re-prioritization jobs.
+const EVENT_BATCH: i64 = 500;
+/// Events erased between progress updates of a purge job.
+const PURGE_BATCH: i64 = 100;
+/// Snapshot records restored per transaction by an import job.
+const IMPORT_CHUNK: usize = 500;
+
+#[derive(Debug, thiserror::Error)]
+enum JobError {
+    #[error("job store: {0:?}")]
+    Jobs(StoreError),
+    #[error("event store: {0:?}")]
+    Inspector(inspector::StoreError),
+    #[error("backfill: {0:?}")]
+    Backfill(BackfillError),
+    #[error("snapshot: {0:?}")]
+    Snapshot(SnapshotError),
+    #[error("writing output: {0}")]
+    Io(std::io::Error),
+    #[error("encoding output: {0}")]
+    Encode(serde_json::Error),
+}
+
+impl From<StoreError> for JobError {
+    fn from(err: StoreError) -> Self {
+        Self::Jobs(err)
+    }
+}
+
+impl From<inspector::StoreError> for JobError {
+    fn from(err: inspector::StoreError) -> Self {
+        Self::Inspector(err)
+    }
+}
+
+impl From<BackfillError> for JobError {
+    fn from(err: BackfillError) -> Self {
+        Self::Backfill(err)
+    }
+}
+
+impl From<SnapshotError> for JobError {
+    fn from(err: SnapshotError) -> Self {
+        Self::Snapshot(err)
+    }
+}
+
+impl From<std::io::Error> for JobError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Arguments of `replay_group` for a background group replay.
+#[derive(Debug, Clone, Copy)]
+pub struct GroupReplayJob {
+    pub group_id: Uuid,
+    pub reset_circuit: bool,
+    pub skip_if_pending: bool,
+    pub drip_rate_per_minute: Option<i64>,
+    pub loop_limit: Option<i64>,
+}
+
+/// Runs a group replay for `job_id`, recording its events and progress on
+/// `operation_id`.
+pub fn spawn_group_replay_job(
+    pool: SqlitePool,
+    job_id: Uuid,
+    operation_id: Uuid,
+    replay: GroupReplayJob,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let outcome = match start_job(&pool, job_id).await {
+            Ok(()) => {
+                let result = replay_group(
+                    &pool,
+                    replay.group_id,
+                    replay.reset_circuit,
+                    replay.skip_if_pending,
+                    replay.drip_rate_per_minute,
+                    replay.loop_limit,
+                    Some(operation_id),
+                )
+                .await;
+                let _ = finish_operation(&pool, operation_id, &result).await;
+                match result {
+                    Ok(_) => JobOutcome::Completed { output_path: None },
+                    Err(err) => JobOutcome::Failed(JobError::from(err).to_string()),
+                }
+            }
+            Err(err) => JobOutcome::Failed(JobError::from(err).to_string()),
+        };
+        let _ = finish_job(&pool, job_id, &outcome).await;
+    })
+}
+
+/// Exports the events matching `params` to `<dir>/<job_id>.<ndjson|csv>`.
+/// The file is renamed into place once complete, and removed if the job
+/// is cancelled.
+pub fn spawn_export_job(
+    pool: SqlitePool,
+    job_id: Uuid,
+    dir: PathBuf,
+    format: ExportFormat,
+    params: ExportEventsParams,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let outcome = match run_export(&pool, job_id, &dir, format, &params).await {
+            Ok(path) => JobOutcome::Completed {
+                output_path: path.map(|path| path.display().to_string()),
+            },
+            Err(err) => JobOutcome::Failed(err.to_string()),
+        };
+        let _ = finish_job(&pool, job_id, &outcome).await;
+    })
+}
+
+/// Fills `backfill` for the rows that existed when the job started, one
+/// batch of rowids at a time. A cancelled job keeps its cursor, so the next
+/// job resumes where it stopped.
+pub fn spawn_backfill_job(
+    pool: SqlitePool,
+    job_id: Uuid,
+    backfill: &'static Backfill,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let outcome = match run_backfill(&pool, job_id, backfill).await {
+            Ok(()) => JobOutcome::Completed { output_path: None },
+            Err(err) => JobOutcome::Failed(err.to_string()),
+        };
+        let _ = finish_job(&pool, job_id, &outcome).await;
+    })
+}
+
+/// Cancels the queued events matching `filter`, oldest first, one batch
+/// at a time. A cancelled job keeps the events it already cancelled.
+pub fn spawn_bulk_cancel_job(
+    pool: SqlitePool,
+    job_id: Uuid,
+    filter: QueuedEventFilter,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let outcome = match run_bulk_cancel(&pool, job_id, &filter).await {
+            Ok(()) => JobOutcome::Completed { output_path: None },
+            Err(err) => JobOutcome::Failed(err.to_string()),
+        };
+        let _ = finish_job(&pool, job_id, &outcome).await;
+    })
+}
+
+async fn run_bulk_cancel(
+    pool: &SqlitePool,
+    job_id: Uuid,
+    filter: &QueuedEventFilter,
+) -> Result<(), JobError> {
+    start_job(pool, job_id).await?;
+    set_job_total(pool, job_id, count_queued_events(pool, filter).await?).await?;
+    loop {
+        let cancelled = cancel_events_batch(pool, filter, EVENT_BATCH).await?;
+        if cancelled == 0 || advance_job(pool, job_id, cancelled).await? {
+            return Ok(());
+        }
+        tokio::time::sleep(BATCH_PAUSE).await;
+    }
+}
+
+/// Moves the queued events matching `filter` to `priority`, oldest first,
+/// one batch at a time.
+pub fn spawn_reprioritize_job(
+    pool: SqlitePool,
+    job_id: Uuid,
+    filter: QueuedEventFilter,
+    priority: i64,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let outcome = match run_reprioritize(&pool, job_id, &filter, priority).await {
+            Ok(()) => JobOutcome::Completed { output_path: None },
+            Err(err) => JobOutcome::Failed(err.to_string()),
+        };
+        let _ = finish_job(&pool, job_id, &outcome).await;
+    })
+}
+
+async fn run_reprioritize(
+    pool: &SqlitePool,
+    job_id: Uuid,
+    filter: &QueuedEventFilter,
+    priority: i64,
+) -> Result<(), JobError> {
+    start_job(pool, job_id).await?;
+    let total = count_events_to_reprioritize(pool, filter, priority).await?;
+    set_job_total(pool, job_id, total).await?;
+    loop {
+        let changed = reprioritize_events_batch(pool, filter, priority, EVENT_BATCH).await?;
+        if changed == 0 || advance_job(pool, job_id, changed).await? {
+            return Ok(());
+        }
+        tokio::time::sleep(BATCH_PAUSE).await;
+    }
+}
+
+/// Erases the finished events matching `filter`, oldest first, one batch
+/// at a time. A cancelled job keeps the events it already erased.
+pub fn spawn_purge_job(
+    pool: SqlitePool,
+    job_id: Uuid,
+    filter: QueuedEventFilter,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let outcome = match run_purge(&pool, job_id, &filter).await {
+            Ok(()) => JobOutcome::Completed { output_path: None },
+            Err(err) => JobOutcome::Failed(err.to_string()),
+        };
+        let _ = finish_job(&pool, job_id, &outcome).await;
+    })
+}
+
+async fn run_purge(
+    pool: &SqlitePool,
+    job_id: Uuid,
+    filter: &QueuedEventFilter,
+) -> Result<(), JobError> {
+    start_job(pool, job_id).await?;
+    set_job_total(pool, job_id, count_purgeable_events(pool, filter).await?).await?;
+    loop {
+        let purged = purge_events_batch(pool, filter, PURGE_BATCH).await?;
+        if purged == 0 || advance_job(pool, job_id, purged).await? {
+            return Ok(());
+        }
+        tokio::time::sleep(BATCH_PAUSE).await;
+    }
+}
+
+/// Restores the snapshot at `path`, one chunk of records per transaction,
+/// and removes the file once the job ends. Rows that already exist are
+/// skipped, so a failed or cancelled import can be started again with the
+/// same snapshot.
+pub fn spawn_import_job(pool: SqlitePool, job_id: Uuid, path: PathBuf) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let outcome = match run_import(&pool, job_id, &path).await {
+            Ok(()) => JobOutcome::Completed { output_path: None },
+            Err(err) => JobOutcome::Failed(err.to_string()),
+        };
+        let _ = tokio::fs::remove_file(&path).await;
+        let _ = finish_job(&pool, job_id, &outcome).await;
+    })
+}
+
+async fn run_import(pool: &SqlitePool, job_id: Uuid, path: &Path) -> Result<(), JobError> {
+    start_job(pool, job_id).await?;
+    let mut lines = BufReader::new(tokio::fs::File::open(path).await?).lines();
+    check_snapshot_header(&lines.next_line().await?.unwrap_or_default())?;
+    let mut total = 0;
+    while let Some(line) = lines.next_line().await? {
+        if !line.trim().is_empty() {
+            total += 1;
+        }
+    }
+    set_job_total(pool, job_id, total).await?;
+
+    let mut lines = BufReader::new(tokio::fs::File::open(path).await?).lines();
+    lines.next_line().await?;
+    let mut done = false;
+    while !done {
+        let mut chunk = Vec::with_capacity(IMPORT_CHUNK);
+        while chunk.len() < IMPORT_CHUNK {
+            match lines.next_line().await? {
+                Some(line) if line.trim().is_empty() => {}
+                Some(line) => chunk.push(line),
+                None => {
+                    done = true;
+                    break;
+                }
+            }
+        }
+        if chunk.is_empty() {
+            break;
+        }
+        restore_snapshot_records(pool, &chunk).await?;
+        let restored = i64::try_from(chunk.len()).unwrap_or(i64::MAX);
+        if advance_job(pool, job_id, restored).await? {
+            return Ok(());
+        }
+        tokio::time::sleep(BATCH_PAUSE).await;
+    }
+    Ok(())
+}
+
+async fn run_backfill(
+    pool: &SqlitePool,
+    job_id: Uuid,
+    backfill: &'static Backfill,
+) -> Result<(), JobError> {
+    start_job(pool, job_id).await?;
+    let (mut cursor, last) = claim_backfill(pool, backfill, job_id).await?;
+    set_job_total(pool, job_id, (last - cursor).max(0)).await?;
+    while cursor < last {
+        let end = (cursor + BACKFILL_BATCH).min(last);
+        backfill_batch(pool, backfill, cursor, end).await?;
+        if advance_job(pool, job_id, end - cursor).await? {
+            return Ok(());
+        }
+        cursor = end;
+        tokio::time::sleep(BATCH_PAUSE).await;
+    }
+    complete_backfill(pool, backfill).await?;
+    Ok(())
+}
+
+/// Returns the file written, or `None` if the job was cancelled.
+async fn run_export(
+    pool: &SqlitePool,
+    job_id: Uuid,
+    dir: &Path,
+    format: ExportFormat,
+    params: &ExportEventsParams,
+) -> Result<Option<PathBuf>, JobError> {
+    start_job(pool, job_id).await?;
+    let records = export_events(pool, &EndpointScope::All, params).await?;
+    set_job_total(
+        pool,
+        job_id,
+        i64::try_from(records.len()).unwrap_or(i64::MAX),
+    )
+    .await?;
+
+    let extension = match format {
+        ExportFormat::Ndjson => "ndjson",
+        ExportFormat::Csv => "csv",
+    };
+    tokio::fs::create_dir_all(dir).await?;
+    let path = dir.join(format!("{job_id}.{extension}"));
+    let tmp_path = path.with_extension("tmp");
+    let mut file = tokio::fs::File::create(&tmp_path).await?;
+    if format == ExportFormat::Csv {
+        file.write_all([CSV_COLUMNS.join(",").as_str(), "\r\n"].concat().as_bytes())
+            .await?;
+    }
+    for chunk in records.chunks(EXPORT_CHUNK) {
+        let body = match format {
+            ExportFormat::Ndjson => render_ndjson(chunk).map_err(JobError::Encode)?,
+            ExportFormat::Csv => render_csv_rows(chunk),
+        };
+        file.write_all(body.as_bytes()).await?;
+        let written = i64::try_from(chunk.len()).unwrap_or(i64::MAX);
+        if advance_job(pool, job_id, written).await? {
+            drop(file);
+            tokio::fs::remove_file(&tmp_path).await?;
+            return Ok(None);
+        }
+    }
+    file.flush().await?;
+    drop(file);
+    tokio::fs::rename(&tmp_path, &path).await?;
+    Ok(Some(path))
+}
@@ -0,0 +1,317 @@
+use chrono::{SecondsFormat, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::types::{Job, JobKind, JobStatus};
+
+#[derive(Debug)]
+pub enum StoreError {
+    Db(sqlx::Error),
+    Conflict(String),
+    NotFound(String),
+    Parse(String),
+}
+
+impl From<sqlx::Error> for StoreError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Db(err)
+    }
+}
+
+/// How a job ended, as reported by its runner. A job that stopped early
+/// because it was cancelled reports `Completed`, and is recorded as
+/// cancelled.
+#[derive(Debug, Clone)]
+pub enum JobOutcome {
+    Completed { output_path: Option<String> },
+    Failed(String),
+}
+
+/// Cap on how many jobs `list_jobs` returns.
+const MAX_LISTED_JOBS: i64 = 100;
+
+/// Replay jobs report the progress of their operation.
+const JOB_COLUMNS: &str = "j.id, j.kind, j.status, j.actor, j.operation_id, \
+    COALESCE(o.total, j.total) AS total, COALESCE(o.processed, j.processed) AS processed, \
+    j.output_path, j.error, j.created_at, j.started_at, j.finished_at, j.cancel_requested_at";
+
+#[derive(sqlx::FromRow)]
+struct JobRow {
+    id: String,
+    kind: String,
+    status: String,
+    actor: String,
+    operation_id: Option<String>,
+    total: i64,
+    processed: i64,
+    output_path: Option<String>,
+    error: Option<String>,
+    created_at: String,
+    started_at: Option<String>,
+    finished_at: Option<String>,
+    cancel_requested_at: Option<String>,
+}
+
+/// Records a queued job started by `actor`.
+pub async fn create_job(
+    pool: &SqlitePool,
+    kind: JobKind,
+    actor: &str,
+    operation_id: Option<Uuid>,
+) -> Result<Job, StoreError> {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r"
+        INSERT INTO jobs (id, kind, status, actor, operation_id, created_at)
+        VALUES (?, ?, 'queued', ?, ?, ?)
+        ",
+    )
+    .bind(id.to_string())
+    .bind(kind_to_str(kind))
+    .bind(actor)
+    .bind(operation_id.map(|id| id.to_string()))
+    .bind(now())
+    .execute(pool)
+    .await?;
+    get_job(pool, id).await
+}
+
+pub async fn get_job(pool: &SqlitePool, job_id: Uuid) -> Result<Job, StoreError> {
+    let row: JobRow = sqlx::query_as(&format!(
+        "SELECT {JOB_COLUMNS} FROM jobs j \
+        LEFT JOIN operations o ON o.id = j.operation_id \
+        WHERE j.id = ?"
+    ))
+    .bind(job_id.to_string())
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| StoreError::NotFound("job not found".to_string()))?;
+    map_job(row)
+}
+
+/// Most recent jobs, newest first.
+pub async fn list_jobs(pool: &SqlitePool) -> Result<Vec<Job>, StoreError> {
+    let rows: Vec<JobRow> = sqlx::query_as(&format!(
+        "SELECT {JOB_COLUMNS} FROM jobs j \
+        LEFT JOIN operations o ON o.id = j.operation_id \
+        ORDER BY j.created_at DESC, j.rowid DESC \
+        LIMIT ?"
+    ))
+    .bind(MAX_LISTED_JOBS)
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter().map(map_job).collect()
+}
+
+pub async fn start_job(pool: &SqlitePool, job_id: Uuid) -> Result<(), StoreError> {
+    sqlx::query("UPDATE jobs SET status = 'running', started_at = ? WHERE id = ?")
+        .bind(now())
+        .bind(job_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn set_job_total(pool: &SqlitePool, job_id: Uuid, total: i64) -> Result<(), StoreError> {
+    sqlx::query("UPDATE jobs SET total = ? WHERE id = ?")
+        .bind(total)
+        .bind(job_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Adds `processed` items to the job's progress and returns whether it
+/// should stop because cancellation was requested.
+pub async fn advance_job(
+    pool: &SqlitePool,
+    job_id: Uuid,
+    processed: i64,
+) -> Result<bool, StoreError> {
+    let cancelled: bool = sqlx::query_scalar(
+        r"
+        UPDATE jobs SET processed = processed + ?
+        WHERE id = ?
+        RETURNING cancel_requested_at IS NOT NULL
+        ",
+    )
+    .bind(processed)
+    .bind(job_id.to_string())
+    .fetch_one(pool)
+    .await?;
+    Ok(cancelled)
+}
+
+pub async fn finish_job(
+    pool: &SqlitePool,
+    job_id: Uuid,
+    outcome: &JobOutcome,
+) -> Result<(), StoreError> {
+    let (status, output_path, error) = match outcome {
+        JobOutcome::Completed { output_path } => ("completed", output_path.as_deref(), None),
+        JobOutcome::Failed(error) => ("failed", None, Some(error.as_str())),
+    };
+    sqlx::query(
+        r"
+        UPDATE jobs
+        SET status = CASE
+                WHEN ?1 = 'completed' AND cancel_requested_at IS NOT NULL THEN 'cancelled'
+                ELSE ?1
+            END,
+            output_path = ?2,
+            error = ?3,
+            finished_at = ?4
+        WHERE id = ?5
+        ",
+    )
+    .bind(status)
+    .bind(output_path)
+    .bind(error)
+    .bind(now())
+    .bind(job_id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Asks a queued or running job to stop. The runner stops at its next
+/// progress update. A reconciliation no worker has leased yet is
+/// cancelled right away.
+pub async fn cancel_job(pool: &SqlitePool, job_id: Uuid) -> Result<Job, StoreError> {
+    let now = now();
+    let mut tx = pool.begin().await?;
+    let row: Option<(String, Option<String>)> =
+        sqlx::query_as("SELECT status, operation_id FROM jobs WHERE id = ?")
+            .bind(job_id.to_string())
+            .fetch_optional(&mut *tx)
+            .await?;
+    let (status, operation_id) =
+        row.ok_or_else(|| StoreError::NotFound("job not found".to_string()))?;
+    if !matches!(status.as_str(), "queued" | "running") {
+        return Err(StoreError::Conflict("job_finished".to_string()));
+    }
+    sqlx::query(
+        "UPDATE jobs SET cancel_requested_at = COALESCE(cancel_requested_at, ?) WHERE id = ?",
+    )
+    .bind(&now)
+    .bind(job_id.to_string())
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "UPDATE jobs SET status = 'cancelled', finished_at = ? \
+        WHERE id = ? AND kind = 'reconcile' AND status = 'queued'",
+    )
+    .bind(&now)
+    .bind(job_id.to_string())
+    .execute(&mut *tx)
+    .await?;
+    if let Some(operation_id) = operation_id {
+        sqlx::query(
+            "UPDATE operations SET cancel_requested_at = COALESCE(cancel_requested_at, ?) \
+            WHERE id = ?",
+        )
+        .bind(&now)
+        .bind(operation_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    get_job(pool, job_id).await
+}
+
+/// Marks jobs and operations left queued or running by a previous process
+/// as failed. Call once at startup, before any job is spawned.
+/// Reconciliations are run by workers rather than this process, so they
+/// are left for the next worker to lease.
+pub async fn fail_interrupted_jobs(pool: &SqlitePool) -> Result<u64, StoreError> {
+    let now = now();
+    let mut tx = pool.begin().await?;
+    let jobs = sqlx::query(
+        r"
+        UPDATE jobs SET status = 'failed', error = 'interrupted by restart', finished_at = ?
+        WHERE status IN ('queued', 'running') AND kind <> 'reconcile'
+        ",
+    )
+    .bind(&now)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        r"
+        UPDATE operations SET status = 'failed', error = 'interrupted by restart', finished_at = ?
+        WHERE status = 'running'
+        ",
+    )
+    .bind(&now)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(jobs.rows_affected())
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn map_job(row: JobRow) -> Result<Job, StoreError> {
+    Ok(Job {
+        id: parse_uuid("job id", &row.id)?,
+        kind: parse_kind(&row.kind)?,
+        status: parse_status(&row.status)?,
+        actor: row.actor,
+        operation_id: row
+            .operation_id
+            .as_deref()
+            .map(|id| parse_uuid("operation id", id))
+            .transpose()?,
+        total: row.total,
+        processed: row.processed,
+        output_path: row.output_path,
+        error: row.error,
+        created_at: row.created_at,
+        started_at: row.started_at,
+        finished_at: row.finished_at,
+        cancel_requested_at: row.cancel_requested_at,
+    })
+}
+
+fn parse_uuid(field: &str, value: &str) -> Result<Uuid, StoreError> {
+    Uuid::parse_str(value).map_err(|err| StoreError::Parse(format!("invalid {field}: {err}")))
+}
+
+fn parse_kind(kind: &str) -> Result<JobKind, StoreError> {
+    match kind {
+        "group_replay" => Ok(JobKind::GroupReplay),
+        "export" => Ok(JobKind::Export),
+        "backfill" => Ok(JobKind::Backfill),
+        "bulk_cancel" => Ok(JobKind::BulkCancel),
+        "reprioritize" => Ok(JobKind::Reprioritize),
+        "purge" => Ok(JobKind::Purge),
+        "import" => Ok(JobKind::Import),
+        "reconcile" => Ok(JobKind::Reconcile),
+        other => Err(StoreError::Parse(format!("unknown job kind: {other}"))),
+    }
+}
+
+fn kind_to_str(kind: JobKind) -> &'static str {
+    match kind {
+        JobKind::GroupReplay => "group_replay",
+        JobKind::Export => "export",
+        JobKind::Backfill => "backfill",
+        JobKind::BulkCancel => "bulk_cancel",
+        JobKind::Reprioritize => "reprioritize",
+        JobKind::Purge => "purge",
+        JobKind::Import => "import",
+        JobKind::Reconcile => "reconcile",
+    }
+}
+
+fn parse_status(status: &str) -> Result<JobStatus, StoreError> {
+    match status {
+        "queued" => Ok(JobStatus::Queued),
+        "running" => Ok(JobStatus::Running),
+        "completed" => Ok(JobStatus::Completed),
+        "failed" => Ok(JobStatus::Failed),
+        "cancelled" => Ok(JobStatus::Cancelled),
+        other => Err(StoreError::Parse(format!("unknown job status: {other}"))),
+    }
+}
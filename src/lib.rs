@@ -1,8 +1,23 @@
 pub mod auth;
+#[cfg(feature = "client")]
+pub mod client;
 pub mod dispatcher;
 pub mod error;
+pub mod export;
 pub mod extractors;
 pub mod handlers;
+pub mod ingest;
 pub mod inspector;
+pub mod jobs;
+#[cfg(feature = "remote")]
+pub mod login;
+pub mod migrate;
+pub mod oidc;
+#[cfg(feature = "client")]
+pub mod redirect;
+#[cfg(feature = "client")]
+pub mod resolver;
+pub mod secrets;
+pub mod snapshot;
 pub mod state;
 pub mod types;
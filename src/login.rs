@@ -0,0 +1,346 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Grant type of RFC 8628 token requests.
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Polling interval when the provider does not name one (RFC 8628 §3.2).
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+
+/// Added to the polling interval on every `slow_down` (RFC 8628 §3.5).
+const SLOW_DOWN_STEP_SECS: u64 = 5;
+
+/// Access tokens this close to expiry are refreshed before use.
+const EXPIRY_MARGIN_SECS: i64 = 30;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoginError {
+    #[error("OIDC discovery failed: {0}")]
+    Discovery(String),
+
+    #[error("the login request was denied")]
+    Denied,
+
+    #[error("the device code expired before the login was approved")]
+    Expired,
+
+    #[error("identity provider returned {error}: {}", description.as_deref().unwrap_or("no description"))]
+    Provider {
+        error: String,
+        description: Option<String>,
+    },
+
+    #[error("not logged in; run `receiver login` first")]
+    NotLoggedIn,
+
+    #[error("login expired; run `receiver login` again")]
+    SessionExpired,
+
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("login file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("login file: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// The OIDC client `receiver login` authenticates as. The client must be
+/// registered with the issuer for the device authorization grant.
+#[derive(Debug, Clone)]
+pub struct DeviceLoginConfig {
+    pub issuer: String,
+    pub client_id: String,
+    /// `offline_access` asks for a refresh token, so a login outlives its
+    /// first access token.
+    pub scope: String,
+    /// Sent as `audience` for providers that pick the token's audience
+    /// from the request rather than the client.
+    pub audience: Option<String>,
+}
+
+impl DeviceLoginConfig {
+    pub fn new(issuer: impl Into<String>, client_id: impl Into<String>) -> Self {
+        Self {
+            issuer: issuer.into().trim_end_matches('/').to_string(),
+            client_id: client_id.into(),
+            scope: "openid offline_access".to_string(),
+            audience: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    device_authorization_endpoint: Option<String>,
+    token_endpoint: String,
+}
+
+/// What the operator is asked to do: open `verification_uri` and enter
+/// `user_code`, or open `verification_uri_complete` directly.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    /// Some providers still use the draft's `verification_url`.
+    #[serde(alias = "verification_url")]
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    pub interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+/// A login saved by `receiver login`: the receiver it is for, and the
+/// tokens with what is needed to refresh them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StoredLogin {
+    pub receiver_url: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub token_endpoint: String,
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<String>,
+}
+
+/// The OIDC device authorization flow (RFC 8628) against one issuer.
+pub struct DeviceLogin {
+    http: reqwest::Client,
+    config: DeviceLoginConfig,
+    device_authorization_endpoint: String,
+    token_endpoint: String,
+}
+
+impl DeviceLogin {
+    /// Looks up the issuer's endpoints from its discovery document.
+    pub async fn discover(config: DeviceLoginConfig) -> Result<Self, LoginError> {
+        let http = reqwest::Client::new();
+        let url = format!("{}/.well-known/openid-configuration", config.issuer);
+        let response = http.get(&url).send().await?;
+        if !response.status().is_success() {
+            return Err(LoginError::Discovery(format!(
+                "{url} returned {}",
+                response.status()
+            )));
+        }
+        let metadata: ProviderMetadata = response
+            .json()
+            .await
+            .map_err(|err| LoginError::Discovery(format!("invalid document at {url}: {err}")))?;
+        let device_authorization_endpoint =
+            metadata.device_authorization_endpoint.ok_or_else(|| {
+                LoginError::Discovery(format!(
+                    "{} does not support the device authorization grant",
+                    config.issuer
+                ))
+            })?;
+        Ok(Self {
+            http,
+            config,
+            device_authorization_endpoint,
+            token_endpoint: metadata.token_endpoint,
+        })
+    }
+
+    /// Asks for a device code and the user code to show the operator.
+    pub async fn start(&self) -> Result<DeviceAuthorization, LoginError> {
+        let mut form = vec![
+            ("client_id", self.config.client_id.as_str()),
+            ("scope", self.config.scope.as_str()),
+        ];
+        if let Some(audience) = &self.config.audience {
+            form.push(("audience", audience));
+        }
+        let response = self
+            .http
+            .post(&self.device_authorization_endpoint)
+            .form(&form)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(provider_error(response).await);
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Polls the token endpoint until the operator approves or denies the
+    /// login, or the device code expires.
+    pub async fn wait_for_approval(
+        &self,
+        authorization: &DeviceAuthorization,
+        receiver_url: &str,
+    ) -> Result<StoredLogin, LoginError> {
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(authorization.expires_in);
+        let mut interval = authorization.interval.unwrap_or(DEFAULT_INTERVAL_SECS);
+        loop {
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            if tokio::time::Instant::now() >= deadline {
+                return Err(LoginError::Expired);
+            }
+            let response = self
+                .http
+                .post(&self.token_endpoint)
+                .form(&[
+                    ("grant_type", DEVICE_CODE_GRANT),
+                    ("device_code", authorization.device_code.as_str()),
+                    ("client_id", self.config.client_id.as_str()),
+                ])
+                .send()
+                .await?;
+            if response.status().is_success() {
+                let tokens: TokenResponse = response.json().await?;
+                return Ok(StoredLogin {
+                    receiver_url: receiver_url.trim_end_matches('/').to_string(),
+                    issuer: self.config.issuer.clone(),
+                    client_id: self.config.client_id.clone(),
+                    token_endpoint: self.token_endpoint.clone(),
+                    access_token: tokens.access_token,
+                    refresh_token: tokens.refresh_token,
+                    expires_at: expires_at(tokens.expires_in),
+                });
+            }
+            match provider_error(response).await {
+                LoginError::Provider { error, .. } if error == "authorization_pending" => {}
+                LoginError::Provider { error, .. } if error == "slow_down" => {
+                    interval += SLOW_DOWN_STEP_SECS;
+                }
+                LoginError::Provider { error, .. } if error == "access_denied" => {
+                    return Err(LoginError::Denied);
+                }
+                LoginError::Provider { error, .. } if error == "expired_token" => {
+                    return Err(LoginError::Expired);
+                }
+                other => return Err(other),
+            }
+        }
+    }
+}
+
+/// Where logins are saved: `RECEIVER_LOGIN_FILE`, or
+/// `$HOME/.config/receiver/login.json`.
+pub fn login_file() -> PathBuf {
+    match std::env::var("RECEIVER_LOGIN_FILE") {
+        Ok(path) if !path.trim().is_empty() => PathBuf::from(path.trim()),
+        _ => std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(".config/receiver/login.json"),
+    }
+}
+
+/// Writes the login readable by its owner only, since it holds tokens.
+pub fn save_login(path: &Path, login: &StoredLogin) -> Result<(), LoginError> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // `mode` only applies to new files.
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    let mut file = options.open(path)?;
+    file.write_all(&serde_json::to_vec_pretty(login)?)?;
+    Ok(())
+}
+
+pub fn load_login(path: &Path) -> Result<StoredLogin, LoginError> {
+    let raw = match std::fs::read(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(LoginError::NotLoggedIn);
+        }
+        Err(err) => return Err(err.into()),
+    };
+    Ok(serde_json::from_slice(&raw)?)
+}
+
+/// The saved login, with its access token refreshed and saved again first
+/// if it is about to expire.
+pub async fn current_login(path: &Path) -> Result<StoredLogin, LoginError> {
+    let login = load_login(path)?;
+    let expiring = login
+        .expires_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .is_some_and(|at| {
+            at.with_timezone(&Utc) <= Utc::now() + chrono::Duration::seconds(EXPIRY_MARGIN_SECS)
+        });
+    if !expiring {
+        return Ok(login);
+    }
+    let Some(refresh_token) = login.refresh_token.clone() else {
+        return Err(LoginError::SessionExpired);
+    };
+
+    let response = reqwest::Client::new()
+        .post(&login.token_endpoint)
+        .form(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+            ("client_id", login.client_id.as_str()),
+        ])
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(match provider_error(response).await {
+            LoginError::Provider { error, .. } if error == "invalid_grant" => {
+                LoginError::SessionExpired
+            }
+            other => other,
+        });
+    }
+    let tokens: TokenResponse = response.json().await?;
+    let refreshed = StoredLogin {
+        access_token: tokens.access_token,
+        // Providers that do not rotate refresh tokens leave it out.
+        refresh_token: tokens.refresh_token.or(Some(refresh_token)),
+        expires_at: expires_at(tokens.expires_in),
+        ..login
+    };
+    save_login(path, &refreshed)?;
+    Ok(refreshed)
+}
+
+fn expires_at(expires_in: Option<i64>) -> Option<String> {
+    expires_in.map(|secs| {
+        (Utc::now() + chrono::Duration::seconds(secs)).to_rfc3339_opts(SecondsFormat::Secs, true)
+    })
+}
+
+async fn provider_error(response: reqwest::Response) -> LoginError {
+    let status = response.status();
+    match response.json::<TokenErrorResponse>().await {
+        Ok(body) => LoginError::Provider {
+            error: body.error,
+            description: body.error_description,
+        },
+        Err(_) => LoginError::Provider {
+            error: status.to_string(),
+            description: None,
+        },
+    }
+}
@@ -1,32 +1,136 @@
 use axum::{
     Router, middleware,
-    routing::{get, post},
+    routing::{get, post, put},
 };
+#[cfg(feature = "remote")]
+use receiver::login::{DeviceLogin, DeviceLoginConfig, current_login, login_file, save_login};
 use receiver::{
-    auth::inspector_auth,
-    dispatcher::DispatcherConfig,
+    auth::{inspector_auth, parse_scoped_tokens},
+    dispatcher::{
+        ChaosConfig, DispatcherConfig, EchoConfig, EchoTarget, EmailSender, LeaseBenchConfig,
+        SmtpSettings, dispatcher_chaos, run_lease_bench, smtp_settings_from_file,
+        smtp_settings_from_vars, spawn_attempt_log_archiver, spawn_email_sender,
+        spawn_expiry_sweeper,
+    },
+    export::{ExportScheduleConfig, spawn_scheduled_export},
     handlers::{
-        dispatcher::{lease_handler, report_handler},
+        dispatcher::{
+            check_lease_handler, check_report_handler, clear_echo_deliveries_handler,
+            echo_delivery_handler, lease_handler, list_echo_deliveries_handler,
+            reconcile_lease_handler, reconcile_result_handler, renew_handler, report_handler,
+            shadow_report_handler,
+        },
+        ingest::{delivery_receipt_handler, ingest_health_handler, ingest_source_handler},
         inspector::{
-            get_event_handler, list_attempts_handler, list_events_handler, replay_event_handler,
+            anomalies_handler, attempt_curl_handler, cancel_events_handler, cancel_job_handler,
+            clear_fault_injection_handler, close_circuit_handler, contract_backfill_handler,
+            create_group_handler, delete_event_handler, doctor_handler, error_summary_handler,
+            event_bundle_handler, event_lineage_handler, export_events_handler, export_job_handler,
+            get_endpoint_attempt_log_sampling_handler, get_endpoint_backoff_handler,
+            get_endpoint_canary_handler, get_endpoint_connect_policy_handler,
+            get_endpoint_health_handler, get_endpoint_profile_handler,
+            get_endpoint_redirect_policy_handler, get_endpoint_region_handler,
+            get_endpoint_scrub_rules_handler, get_endpoint_secrets_handler,
+            get_endpoint_shadow_handler, get_endpoint_slo_handler, get_endpoint_timeouts_handler,
+            get_event_handler, get_fault_injection_handler, get_group_handler,
+            get_group_quota_handler, get_job_handler, get_payload_schema_handler,
+            get_provider_scrub_rules_handler, get_reconcile_report_handler,
+            import_snapshot_handler, job_output_handler, job_stream_handler, list_attempts_handler,
+            list_backfills_handler, list_delivery_windows_handler, list_endpoint_revisions_handler,
+            list_events_handler, list_groups_handler, list_jobs_handler,
+            list_maintenance_windows_handler, list_operations_handler,
+            list_shadow_attempts_handler, metrics_handler, pause_dispatch_handler,
+            pause_group_handler, provider_stats_handler, purge_events_handler, reconcile_handler,
+            repair_doctor_handler, replay_event_handler, replay_group_handler,
+            reprioritize_events_handler, resume_dispatch_handler, resume_group_handler,
+            rotate_endpoint_secret_handler, runtime_config_handler,
+            scheduled_export_status_handler, search_customer_handler, selftest_handler,
+            set_delivery_windows_handler, set_endpoint_attempt_log_sampling_handler,
+            set_endpoint_backoff_handler, set_endpoint_canary_handler, set_endpoint_check_handler,
+            set_endpoint_connect_policy_handler, set_endpoint_group_handler,
+            set_endpoint_profile_handler, set_endpoint_redirect_policy_handler,
+            set_endpoint_region_handler, set_endpoint_scrub_rules_handler,
+            set_endpoint_shadow_handler, set_endpoint_slo_handler, set_endpoint_target_handler,
+            set_endpoint_timeouts_handler, set_fault_injection_handler, set_group_quota_handler,
+            set_group_rate_limit_handler, set_maintenance_windows_handler,
+            set_payload_schema_handler, set_provider_scrub_rules_handler, share_event_handler,
+            shared_attempts_handler, shared_event_handler, simulate_backoff_handler,
+            simulate_circuit_handler, slo_stats_handler, start_backfill_handler,
+            start_reconcile_handler, storage_handler, tls_expiry_handler, undo_operation_handler,
+            usage_handler, verify_bundle_handler,
         },
     },
+    ingest::{IngestConfig, IngestJournal, IngestQueue, replay_journal},
+    inspector::{ShareLinkConfig, list_endpoint_secret_ids, run_doctor},
+    jobs::{create_job, fail_interrupted_jobs, get_job, spawn_backfill_job},
+    migrate::{
+        ensure_backfill_idle, find_backfill, list_backfills, repair_migrations, run_migrations,
+    },
+    oidc::OidcVerifier,
+    secrets::SecretStore,
+    snapshot::{restore_snapshot, write_snapshot},
     state::AppState,
+    types::{DoctorReport, JobKind, JobStatus},
 };
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
+use tower_http::compression::CompressionLayer;
+
+const BACKFILL_PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+/// How often due email events are looked for.
+const EMAIL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[cfg(not(feature = "remote"))]
+const REMOTE_DISABLED: &str = "receiver was built without the remote feature";
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("bench") {
+        return run_bench_command(args.get(1..).unwrap_or_default()).await;
+    }
+    match args.first().map(String::as_str) {
+        Some("login") => return run_login_command(args.get(1..).unwrap_or_default()).await,
+        Some("logout") => return run_logout_command(),
+        Some("doctor") if args.iter().any(|arg| arg == "--remote") => {
+            let repair = args.iter().any(|arg| arg == "--repair");
+            return run_remote_doctor_command(repair).await;
+        }
+        _ => {}
+    }
+
     let database_url =
         std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:receiver.db".to_string());
     let bind_addr = std::env::var("RECEIVER_INTERNAL_BIND_ADDR")
         .unwrap_or_else(|_| "127.0.0.1:3001".to_string());
-    let inspector_api_token = std::env::var("INSPECTOR_API_TOKEN")
-        .ok()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty());
+    let secrets = SecretStore::from_env();
+    let inspector_api_token = secret_from_env(&secrets, "INSPECTOR_API_TOKEN")?;
+    let inspector_scoped_tokens = match std::env::var("INSPECTOR_SCOPED_TOKENS") {
+        Ok(raw) => parse_scoped_tokens(&raw)
+            .map_err(|err| format!("invalid INSPECTOR_SCOPED_TOKENS: {err}"))?,
+        Err(_) => Vec::new(),
+    };
+    let inspector_oidc = oidc_from_env()?;
+    let audit_reads = std::env::var("INSPECTOR_AUDIT_READS")
+        .is_ok_and(|value| matches!(value.trim(), "1" | "true"));
+    let compress_inspector = !std::env::var("INSPECTOR_COMPRESSION")
+        .is_ok_and(|value| matches!(value.trim(), "0" | "false" | "off"));
+    let share_links =
+        secret_from_env(&secrets, "INSPECTOR_SHARE_LINK_SECRET")?.map(|secret| ShareLinkConfig {
+            secret,
+            base_url: std::env::var("RECEIVER_PUBLIC_BASE_URL")
+                .ok()
+                .filter(|url| !url.trim().is_empty()),
+        });
+    let bundle_signing_key = secret_from_env(&secrets, "INSPECTOR_BUNDLE_SIGNING_KEY")?;
+    let job_dir = match std::env::var("RECEIVER_JOB_DIR") {
+        Ok(dir) => Some(dir.trim().to_string()).filter(|dir| !dir.is_empty()),
+        Err(_) => Some("receiver-jobs".to_string()),
+    }
+    .map(PathBuf::from);
 
     let connect_options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true);
 
@@ -35,29 +139,332 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .connect_with(connect_options)
         .await?;
 
-    sqlx::migrate!("./migrations").run(&pool).await?;
+    if args.first().map(String::as_str) == Some("migrate") {
+        let repair = args.iter().any(|arg| arg == "--repair");
+        return run_migrate_command(&pool, repair).await;
+    }
+    run_migrations(&pool)
+        .await
+        .map_err(|err| format!("migrations failed: {err}"))?;
 
-    let dispatcher = DispatcherConfig::from_env();
+    match args.first().map(String::as_str) {
+        Some("doctor") => {
+            let repair = args.iter().any(|arg| arg == "--repair");
+            return run_doctor_command(&pool, repair).await;
+        }
+        Some("backfill") => return run_backfill_command(&pool, args.get(1)).await,
+        Some("snapshot") => return run_snapshot_command(&pool, args.get(1)).await,
+        Some("restore") => return run_restore_command(&pool, args.get(1)).await,
+        _ => {}
+    }
+    run_doctor(&pool, true)
+        .await
+        .map_err(|err| format!("startup consistency check failed: {err:?}"))?;
+    fail_interrupted_jobs(&pool)
+        .await
+        .map_err(|err| format!("failed to close interrupted jobs: {err:?}"))?;
+    check_endpoint_secret_schemes(&pool, &secrets).await?;
+
+    let mut dispatcher = DispatcherConfig::from_env();
+    dispatcher.smtp = smtp_from_env(&secrets)?;
+    let chaos = ChaosConfig::from_env();
+    let echo = EchoConfig::from_env();
+    spawn_expiry_sweeper(
+        pool.clone(),
+        Duration::from_millis(dispatcher.expiry_sweep_interval_ms),
+    );
+    spawn_attempt_log_archiver(
+        pool.clone(),
+        Duration::from_millis(dispatcher.attempt_log_archive_interval_ms),
+        dispatcher.attempt_log_hot_days,
+    );
+    spawn_scheduled_export(pool.clone(), ExportScheduleConfig::from_env()?)
+        .map_err(|err| format!("failed to open the export sink: {err}"))?;
+    if let Some(smtp) = &dispatcher.smtp {
+        spawn_email_sender(
+            pool.clone(),
+            dispatcher.clone(),
+            EmailSender::new(smtp)?,
+            EMAIL_POLL_INTERVAL,
+        );
+    }
+    let ingest = IngestConfig::from_env();
+    let journal = match &ingest.journal_path {
+        Some(path) => {
+            replay_journal(&pool, path)
+                .await
+                .map_err(|err| format!("failed to replay ingest journal: {err:?}"))?;
+            Some(IngestJournal::open(path)?)
+        }
+        None => None,
+    };
+    let ingest_queue = Some(IngestQueue::spawn(
+        pool.clone(),
+        ingest.queue_capacity,
+        journal,
+    ));
     let state = AppState {
         pool,
         dispatcher,
         inspector_api_token,
+        inspector_scoped_tokens,
+        inspector_oidc,
+        share_links,
+        bundle_signing_key,
+        audit_reads,
+        ingest,
+        ingest_queue,
+        secrets,
+        job_dir,
     };
 
     let inspector_router = Router::new()
         .route("/events", get(list_events_handler))
-        .route("/events/:event_id", get(get_event_handler))
+        .route(
+            "/events/export",
+            get(export_events_handler).post(export_job_handler),
+        )
+        .route("/exports/schedule", get(scheduled_export_status_handler))
+        .route("/events/cancel-bulk", post(cancel_events_handler))
+        .route("/events/purge-bulk", post(purge_events_handler))
+        .route(
+            "/events/reprioritize-bulk",
+            post(reprioritize_events_handler),
+        )
+        .route(
+            "/events/:event_id",
+            get(get_event_handler).delete(delete_event_handler),
+        )
         .route("/events/:event_id/attempts", get(list_attempts_handler))
+        .route(
+            "/events/:event_id/shadow-attempts",
+            get(list_shadow_attempts_handler),
+        )
+        .route("/events/:event_id/lineage", get(event_lineage_handler))
         .route("/events/:event_id/replay", post(replay_event_handler))
+        .route("/events/:event_id/share", post(share_event_handler))
+        .route("/events/:event_id/bundle", post(event_bundle_handler))
+        .route("/bundles/verify", post(verify_bundle_handler))
+        .route("/attempts/:attempt_id/curl", get(attempt_curl_handler))
+        .route("/search", get(search_customer_handler))
+        .route("/reconcile", post(reconcile_handler))
+        .route("/reconcile/jobs", post(start_reconcile_handler))
+        .route("/reconcile/jobs/:job_id", get(get_reconcile_report_handler))
+        .route("/errors/summary", get(error_summary_handler))
+        .route("/slo/stats", get(slo_stats_handler))
+        .route("/providers/stats", get(provider_stats_handler))
+        .route("/anomalies", get(anomalies_handler))
+        .route("/tls/expiring", get(tls_expiry_handler))
+        .route("/usage", get(usage_handler))
+        .route("/dispatch/pause", post(pause_dispatch_handler))
+        .route("/dispatch/resume", post(resume_dispatch_handler))
+        .route("/config", get(runtime_config_handler))
+        .route("/simulate/backoff", get(simulate_backoff_handler))
+        .route("/simulate/circuit", get(simulate_circuit_handler))
+        .route("/operations", get(list_operations_handler))
+        .route("/backfills", get(list_backfills_handler))
+        .route("/backfills/:name", post(start_backfill_handler))
+        .route("/backfills/:name/contract", post(contract_backfill_handler))
+        .route("/snapshots/import", post(import_snapshot_handler))
+        .route("/jobs", get(list_jobs_handler))
+        .route("/jobs/:job_id", get(get_job_handler))
+        .route("/jobs/:job_id/cancel", post(cancel_job_handler))
+        .route("/jobs/:job_id/output", get(job_output_handler))
+        .route("/jobs/:job_id/stream", get(job_stream_handler))
+        .route(
+            "/operations/:operation_id/undo",
+            post(undo_operation_handler),
+        )
+        .route("/doctor", get(doctor_handler).post(repair_doctor_handler))
+        .route("/storage", get(storage_handler))
+        .route("/selftest", post(selftest_handler))
+        .route(
+            "/endpoints/:endpoint_id/maintenance-windows",
+            get(list_maintenance_windows_handler).put(set_maintenance_windows_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/delivery-windows",
+            get(list_delivery_windows_handler).put(set_delivery_windows_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/fault-injection",
+            get(get_fault_injection_handler)
+                .put(set_fault_injection_handler)
+                .delete(clear_fault_injection_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/target",
+            put(set_endpoint_target_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/canary",
+            get(get_endpoint_canary_handler).put(set_endpoint_canary_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/profile",
+            get(get_endpoint_profile_handler).put(set_endpoint_profile_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/shadow",
+            get(get_endpoint_shadow_handler).put(set_endpoint_shadow_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/secrets",
+            get(get_endpoint_secrets_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/slo",
+            get(get_endpoint_slo_handler).put(set_endpoint_slo_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/attempt-log-sampling",
+            get(get_endpoint_attempt_log_sampling_handler)
+                .put(set_endpoint_attempt_log_sampling_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/timeouts",
+            get(get_endpoint_timeouts_handler).put(set_endpoint_timeouts_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/backoff",
+            get(get_endpoint_backoff_handler).put(set_endpoint_backoff_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/health",
+            get(get_endpoint_health_handler).put(set_endpoint_check_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/redirects",
+            get(get_endpoint_redirect_policy_handler).put(set_endpoint_redirect_policy_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/connect",
+            get(get_endpoint_connect_policy_handler).put(set_endpoint_connect_policy_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/region",
+            get(get_endpoint_region_handler).put(set_endpoint_region_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/secrets/rotate",
+            post(rotate_endpoint_secret_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/revisions",
+            get(list_endpoint_revisions_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/circuit/close",
+            post(close_circuit_handler),
+        )
+        .route(
+            "/endpoints/:endpoint_id/group",
+            put(set_endpoint_group_handler),
+        )
+        .route(
+            "/groups",
+            get(list_groups_handler).post(create_group_handler),
+        )
+        .route("/groups/:group_id", get(get_group_handler))
+        .route("/groups/:group_id/pause", post(pause_group_handler))
+        .route("/groups/:group_id/resume", post(resume_group_handler))
+        .route(
+            "/groups/:group_id/rate-limit",
+            put(set_group_rate_limit_handler),
+        )
+        .route(
+            "/groups/:group_id/quota",
+            get(get_group_quota_handler).put(set_group_quota_handler),
+        )
+        .route("/groups/:group_id/replay", post(replay_group_handler))
+        .route(
+            "/scrub-rules/providers/:provider",
+            get(get_provider_scrub_rules_handler).put(set_provider_scrub_rules_handler),
+        )
+        .route(
+            "/scrub-rules/endpoints/:endpoint_id",
+            get(get_endpoint_scrub_rules_handler).put(set_endpoint_scrub_rules_handler),
+        )
+        .route(
+            "/schemas/:provider/:event_type",
+            get(get_payload_schema_handler).put(set_payload_schema_handler),
+        )
         .layer(middleware::from_fn_with_state(
             state.clone(),
             inspector_auth,
         ));
+    // gzip or deflate, whichever the client accepts; tiny bodies are left
+    // uncompressed.
+    let inspector_router = if compress_inspector {
+        inspector_router.layer(CompressionLayer::new())
+    } else {
+        inspector_router
+    };
 
-    let app = Router::new()
+    // Scrapers authenticate like inspector clients.
+    let metrics_router = Router::new().route("/metrics", get(metrics_handler)).layer(
+        middleware::from_fn_with_state(state.clone(), inspector_auth),
+    );
+
+    let worker_router = Router::new()
         .route("/internal/dispatcher/lease", post(lease_handler))
-        .route("/internal/dispatcher/report", post(report_handler))
+        .route("/internal/dispatcher/report", post(report_handler));
+    let worker_router = match chaos {
+        Some(chaos) => {
+            worker_router.route_layer(middleware::from_fn_with_state(chaos, dispatcher_chaos))
+        }
+        None => worker_router,
+    };
+
+    let echo_router = match echo {
+        Some(echo) => Router::new()
+            .route(
+                "/debug/echo",
+                post(echo_delivery_handler)
+                    .get(list_echo_deliveries_handler)
+                    .delete(clear_echo_deliveries_handler),
+            )
+            .with_state(EchoTarget::new(echo)),
+        None => Router::new(),
+    };
+
+    let app = Router::new()
+        .merge(worker_router)
+        .merge(echo_router)
+        .route("/internal/dispatcher/renew", post(renew_handler))
+        .route(
+            "/internal/dispatcher/shadow-report",
+            post(shadow_report_handler),
+        )
+        .route(
+            "/internal/dispatcher/checks/lease",
+            post(check_lease_handler),
+        )
+        .route(
+            "/internal/dispatcher/checks/report",
+            post(check_report_handler),
+        )
+        .route(
+            "/internal/dispatcher/reconcile/lease",
+            post(reconcile_lease_handler),
+        )
+        .route(
+            "/internal/dispatcher/reconcile/report",
+            post(reconcile_result_handler),
+        )
+        .route("/ingest/s/:source_slug", post(ingest_source_handler))
+        .route(
+            "/ingest/:endpoint_id/events/:provider_event_id/status",
+            get(delivery_receipt_handler),
+        )
+        .route("/health", get(ingest_health_handler))
+        .route("/share/events/:event_id", get(shared_event_handler))
+        .route(
+            "/share/events/:event_id/attempts",
+            get(shared_attempts_handler),
+        )
         .nest("/api/inspector", inspector_router)
+        .merge(metrics_router)
         .with_state(state);
 
     let addr: SocketAddr = bind_addr.parse()?;
@@ -66,3 +473,332 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Reads `<name>_SECRET_ID` through the secret store, falling back to the
+/// plaintext `<name>` variable. Blank values count as unset.
+fn secret_from_env(secrets: &SecretStore, name: &str) -> Result<Option<String>, String> {
+    let value = match std::env::var(format!("{name}_SECRET_ID")) {
+        Ok(secret_id) if !secret_id.trim().is_empty() => Some(
+            secrets
+                .resolve(secret_id.trim())
+                .map_err(|err| format!("failed to resolve {name}: {err}"))?,
+        ),
+        _ => std::env::var(name).ok(),
+    };
+    Ok(value
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty()))
+}
+
+/// Refuses to start when an endpoint's signing secret names a scheme no
+/// provider is registered for, rather than rejecting its webhooks later.
+async fn check_endpoint_secret_schemes(
+    pool: &sqlx::SqlitePool,
+    secrets: &SecretStore,
+) -> Result<(), String> {
+    let secret_ids = list_endpoint_secret_ids(pool)
+        .await
+        .map_err(|err| format!("failed to read endpoint secrets: {err:?}"))?;
+    for (endpoint_id, secret_id) in secret_ids {
+        secrets.check_scheme(&secret_id).map_err(|err| {
+            format!("endpoint {endpoint_id} has an unusable signing secret {secret_id}: {err}")
+        })?;
+    }
+    Ok(())
+}
+
+/// Reads the SMTP settings from the JSON file named by
+/// `RECEIVER_SMTP_CONFIG`, or else from the `RECEIVER_SMTP_*` variables,
+/// where `RECEIVER_SMTP_PASSWORD_SECRET_ID` may stand in for the password.
+fn smtp_from_env(secrets: &SecretStore) -> Result<Option<SmtpSettings>, String> {
+    let Some(path) = std::env::var("RECEIVER_SMTP_CONFIG")
+        .ok()
+        .filter(|path| !path.trim().is_empty())
+    else {
+        let password = secret_from_env(secrets, "RECEIVER_SMTP_PASSWORD")?;
+        return smtp_settings_from_vars(|name| match name {
+            "RECEIVER_SMTP_PASSWORD" => password.clone(),
+            _ => std::env::var(name).ok(),
+        });
+    };
+    if let Some((name, _)) = std::env::vars()
+        .find(|(name, _)| name.starts_with("RECEIVER_SMTP_") && name != "RECEIVER_SMTP_CONFIG")
+    {
+        return Err(format!(
+            "{name} is set, but RECEIVER_SMTP_CONFIG already holds the SMTP settings"
+        ));
+    }
+    smtp_settings_from_file(std::path::Path::new(path.trim()), secrets).map(Some)
+}
+
+/// Reads `INSPECTOR_OIDC_ISSUER`, `INSPECTOR_OIDC_AUDIENCE` and
+/// `INSPECTOR_OIDC_JWKS_PATH`, which enable OIDC access tokens together.
+fn oidc_from_env() -> Result<Option<OidcVerifier>, String> {
+    let var = |name| {
+        std::env::var(name)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    match (
+        var("INSPECTOR_OIDC_ISSUER"),
+        var("INSPECTOR_OIDC_AUDIENCE"),
+        var("INSPECTOR_OIDC_JWKS_PATH"),
+    ) {
+        (None, None, None) => Ok(None),
+        (Some(issuer), Some(audience), Some(jwks_path)) => {
+            OidcVerifier::from_jwks_file(issuer, audience, jwks_path).map(Some)
+        }
+        _ => Err("INSPECTOR_OIDC_ISSUER, INSPECTOR_OIDC_AUDIENCE and \
+            INSPECTOR_OIDC_JWKS_PATH must be set together"
+            .to_string()),
+    }
+}
+
+/// `receiver login <receiver-url> --issuer <url> --client-id <id>
+/// [--scope <scope>] [--audience <audience>]`: logs in through the OIDC
+/// device flow and saves the tokens for `--remote` subcommands.
+#[cfg(feature = "remote")]
+#[allow(clippy::print_stderr)]
+async fn run_login_command(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    const USAGE: &str = "usage: receiver login <receiver-url> --issuer <url> --client-id <id>";
+    let (receiver_url, options) = args.split_first().ok_or(USAGE)?;
+    let (mut issuer, mut client_id, mut scope, mut audience) = (None, None, None, None);
+    for pair in options.chunks(2) {
+        let [flag, value] = pair else {
+            return Err(format!("missing value for {}", pair.join(" ")).into());
+        };
+        match flag.as_str() {
+            "--issuer" => issuer = Some(value.clone()),
+            "--client-id" => client_id = Some(value.clone()),
+            "--scope" => scope = Some(value.clone()),
+            "--audience" => audience = Some(value.clone()),
+            other => return Err(format!("unknown login option: {other}").into()),
+        }
+    }
+    let mut config = DeviceLoginConfig::new(issuer.ok_or(USAGE)?, client_id.ok_or(USAGE)?);
+    if let Some(scope) = scope {
+        config.scope = scope;
+    }
+    config.audience = audience;
+
+    let login = DeviceLogin::discover(config).await?;
+    let authorization = login.start().await?;
+    match &authorization.verification_uri_complete {
+        Some(uri) => eprintln!("Open {uri} to approve the login."),
+        None => eprintln!(
+            "Open {} and enter the code {}.",
+            authorization.verification_uri, authorization.user_code
+        ),
+    }
+    let stored = login
+        .wait_for_approval(&authorization, receiver_url)
+        .await?;
+    let path = login_file();
+    save_login(&path, &stored)?;
+    eprintln!("Logged in to {}.", stored.receiver_url);
+    Ok(())
+}
+
+#[cfg(not(feature = "remote"))]
+async fn run_login_command(_args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    Err(REMOTE_DISABLED.into())
+}
+
+/// `receiver logout`: forgets the saved login.
+#[cfg(feature = "remote")]
+fn run_logout_command() -> Result<(), Box<dyn std::error::Error>> {
+    match std::fs::remove_file(login_file()) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(feature = "remote"))]
+fn run_logout_command() -> Result<(), Box<dyn std::error::Error>> {
+    Err(REMOTE_DISABLED.into())
+}
+
+/// `receiver doctor --remote [--repair]`: runs `doctor` on the receiver
+/// saved by `receiver login`.
+#[cfg(feature = "remote")]
+async fn run_remote_doctor_command(repair: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let login = current_login(&login_file()).await?;
+    let url = format!("{}/api/inspector/doctor", login.receiver_url);
+    let http = reqwest::Client::new();
+    let request = if repair {
+        http.post(url)
+    } else {
+        http.get(url)
+    };
+    let response = request.bearer_auth(&login.access_token).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("receiver returned {status}: {body}").into());
+    }
+    let report: DoctorReport = response.json().await?;
+    print_doctor_report(&report, repair)
+}
+
+#[cfg(not(feature = "remote"))]
+async fn run_remote_doctor_command(_repair: bool) -> Result<(), Box<dyn std::error::Error>> {
+    Err(REMOTE_DISABLED.into())
+}
+
+/// `receiver doctor [--repair]`: prints the consistency report as JSON and
+/// exits non-zero if issues were found and left unrepaired.
+async fn run_doctor_command(
+    pool: &sqlx::SqlitePool,
+    repair: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = run_doctor(pool, repair)
+        .await
+        .map_err(|err| format!("consistency check failed: {err:?}"))?;
+    print_doctor_report(&report, repair)
+}
+
+#[allow(clippy::print_stdout)]
+fn print_doctor_report(
+    report: &DoctorReport,
+    repair: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", serde_json::to_string_pretty(report)?);
+    if !repair && !report.issues.is_empty() {
+        return Err(format!("{} inconsistent events found", report.issues.len()).into());
+    }
+    Ok(())
+}
+
+/// `receiver migrate [--repair]`: applies pending migrations. With
+/// `--repair`, first re-applies migrations a crash left unfinished.
+#[allow(clippy::print_stdout)]
+async fn run_migrate_command(
+    pool: &sqlx::SqlitePool,
+    repair: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = if repair {
+        repair_migrations(pool).await
+    } else {
+        run_migrations(pool).await
+    }
+    .map_err(|err| format!("migrations failed: {err}"))?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+/// `receiver backfill [name]`: without a name, lists the column backfills
+/// and their progress. With one, runs that backfill as a job in this
+/// process, printing its progress every few seconds.
+#[allow(clippy::print_stdout)]
+async fn run_backfill_command(
+    pool: &sqlx::SqlitePool,
+    name: Option<&String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(name) = name else {
+        let backfills = list_backfills(pool)
+            .await
+            .map_err(|err| format!("listing backfills failed: {err:?}"))?;
+        println!("{}", serde_json::to_string_pretty(&backfills)?);
+        return Ok(());
+    };
+    let backfill = find_backfill(name).map_err(|err| format!("{err:?}"))?;
+    ensure_backfill_idle(pool, backfill)
+        .await
+        .map_err(|err| format!("cannot start backfill: {err:?}"))?;
+    let job = create_job(pool, JobKind::Backfill, "cli", None)
+        .await
+        .map_err(|err| format!("creating backfill job failed: {err:?}"))?;
+    let mut runner = spawn_backfill_job(pool.clone(), job.id, backfill);
+    loop {
+        tokio::select! {
+            result = &mut runner => {
+                result?;
+                break;
+            }
+            () = tokio::time::sleep(BACKFILL_PROGRESS_INTERVAL) => {
+                if let Ok(job) = get_job(pool, job.id).await {
+                    println!("{}: {}/{} rows", backfill.name, job.processed, job.total);
+                }
+            }
+        }
+    }
+    let job = get_job(pool, job.id)
+        .await
+        .map_err(|err| format!("reading backfill job failed: {err:?}"))?;
+    println!("{}", serde_json::to_string_pretty(&job)?);
+    if job.status == JobStatus::Failed {
+        return Err(format!("backfill failed: {}", job.error.unwrap_or_default()).into());
+    }
+    Ok(())
+}
+
+/// `receiver snapshot <path>`: writes queued and dead events, their
+/// attempts, endpoints, and sources to a portable JSON lines archive.
+#[allow(clippy::print_stdout)]
+async fn run_snapshot_command(
+    pool: &sqlx::SqlitePool,
+    path: Option<&String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.ok_or("usage: receiver snapshot <path>")?;
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let summary = write_snapshot(pool, &mut out)
+        .await
+        .map_err(|err| format!("snapshot failed: {err:?}"))?;
+    println!("{}", serde_json::to_string_pretty(&summary)?);
+    Ok(())
+}
+
+/// `receiver restore <path>`: loads a snapshot, skipping rows that already
+/// exist.
+#[allow(clippy::print_stdout)]
+async fn run_restore_command(
+    pool: &sqlx::SqlitePool,
+    path: Option<&String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.ok_or("usage: receiver restore <path>")?;
+    let input = std::io::BufReader::new(std::fs::File::open(path)?);
+    let summary = restore_snapshot(pool, input)
+        .await
+        .map_err(|err| format!("restore failed: {err:?}"))?;
+    println!("{}", serde_json::to_string_pretty(&summary)?);
+    Ok(())
+}
+
+/// `receiver bench [--events N] [--workers N] [--batch N]`: measures lease
+/// and report throughput against a scratch SQLite database that is removed
+/// afterwards, then prints the report as JSON.
+#[allow(clippy::print_stdout)]
+async fn run_bench_command(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = LeaseBenchConfig::default();
+    for pair in args.chunks(2) {
+        let [flag, value] = pair else {
+            return Err(format!("missing value for {}", pair.join(" ")).into());
+        };
+        match flag.as_str() {
+            "--events" => config.events = value.parse()?,
+            "--workers" => config.workers = value.parse()?,
+            "--batch" => config.batch_size = value.parse()?,
+            other => return Err(format!("unknown bench option: {other}").into()),
+        }
+    }
+
+    let db_path = std::env::temp_dir().join(format!("receiver-bench-{}.db", uuid::Uuid::new_v4()));
+    let connect_options = SqliteConnectOptions::new()
+        .filename(&db_path)
+        .create_if_missing(true)
+        .busy_timeout(Duration::from_secs(30));
+    let pool = SqlitePoolOptions::new()
+        .max_connections(u32::try_from(config.workers.max(1)).unwrap_or(u32::MAX))
+        .connect_with(connect_options)
+        .await?;
+    sqlx::migrate!("./migrations").run(&pool).await?;
+
+    let result = run_lease_bench(&pool, &config).await;
+    pool.close().await;
+    let _ = std::fs::remove_file(&db_path);
+
+    let report = result.map_err(|err| format!("bench failed: {err:?}"))?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
@@ -0,0 +1,388 @@
+use std::collections::BTreeMap;
+
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use chrono::{SecondsFormat, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::ingest::{extract_event_type, extract_provider_event_id, payload_sha256};
+use crate::types::SchemaBackfill;
+
+/// A column changed with the expand/contract pattern, so large tables are
+/// never rewritten while the service is running:
+///
+/// 1. Expand: a migration adds the column as nullable, which SQLite does
+///    without touching existing rows, and every insert starts writing it.
+/// 2. Backfill: a background job fills the older rows in small batches,
+///    leaving room for writers between them.
+/// 3. Contract: once no row is left to fill, `contract` statements that
+///    rely on the column (indexes, for example) are run on request.
+#[derive(Debug)]
+pub struct Backfill {
+    pub name: &'static str,
+    pub table: &'static str,
+    pub column: &'static str,
+    pub description: &'static str,
+    pub value: BackfillValue,
+    /// Limits the backfill to rows the value applies to.
+    pub filter: Option<&'static str>,
+    pub contract: &'static [&'static str],
+}
+
+#[derive(Debug)]
+pub enum BackfillValue {
+    /// SQL expression evaluated against the row it fills.
+    Sql(&'static str),
+    /// Derived in Rust from a stored webhook event, for columns filled at
+    /// ingestion from headers and payload. Rows it returns `None` for are
+    /// left NULL.
+    Derived(fn(&StoredEvent) -> Option<String>),
+}
+
+/// The parts of a stored `webhook_events` row derived columns come from.
+#[derive(Debug)]
+pub struct StoredEvent {
+    pub provider: String,
+    pub headers: HeaderMap,
+    pub payload: Vec<u8>,
+}
+
+impl StoredEvent {
+    /// The payload as text, or empty for binary payloads, as at ingestion.
+    pub fn text(&self) -> &str {
+        std::str::from_utf8(&self.payload).unwrap_or_default()
+    }
+}
+
+pub const BACKFILLS: [Backfill; 5] = [
+    Backfill {
+        name: "event_priority",
+        table: "webhook_events",
+        column: "priority",
+        description: "Sets priority 0 on events received before priorities existed.",
+        value: BackfillValue::Sql("0"),
+        filter: None,
+        contract: &["CREATE INDEX IF NOT EXISTS idx_webhook_events_priority \
+            ON webhook_events (status, priority)"],
+    },
+    Backfill {
+        name: "replay_event_type",
+        table: "webhook_events",
+        column: "event_type",
+        description: "Copies the event type onto replays created before replays carried it.",
+        value: BackfillValue::Sql(
+            "(SELECT original.event_type FROM webhook_events original \
+            WHERE original.id = webhook_events.replayed_from_event_id)",
+        ),
+        filter: Some("replayed_from_event_id IS NOT NULL"),
+        contract: &[],
+    },
+    Backfill {
+        name: "event_type",
+        table: "webhook_events",
+        column: "event_type",
+        description: "Extracts the event type of events received before it was recorded. \
+            Run before replay_event_type, which copies it onto replays.",
+        value: BackfillValue::Derived(|event| {
+            extract_event_type(&event.provider, &event.headers, event.text())
+        }),
+        filter: Some("replayed_from_event_id IS NULL"),
+        contract: &[],
+    },
+    Backfill {
+        name: "provider_event_id",
+        table: "webhook_events",
+        column: "provider_event_id",
+        description: "Extracts the provider's event ID of events received before it was recorded.",
+        value: BackfillValue::Derived(|event| {
+            extract_provider_event_id(&event.provider, &event.headers, event.text())
+        }),
+        filter: None,
+        contract: &[],
+    },
+    Backfill {
+        name: "payload_sha256",
+        table: "webhook_events",
+        column: "payload_sha256",
+        description: "Records the payload hash of events stored before hashes were recorded.",
+        value: BackfillValue::Derived(|event| Some(payload_sha256(&event.payload))),
+        filter: Some("erased_at IS NULL"),
+        contract: &[],
+    },
+];
+
+impl Backfill {
+    fn filter_clause(&self) -> String {
+        self.filter
+            .map_or(String::new(), |filter| [" AND (", filter, ")"].concat())
+    }
+}
+
+#[derive(Debug)]
+pub enum BackfillError {
+    Db(sqlx::Error),
+    Conflict(String),
+    NotFound(String),
+    Parse(String),
+}
+
+impl From<sqlx::Error> for BackfillError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Db(err)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct BackfillStateRow {
+    cursor: i64,
+    job_id: Option<String>,
+    completed_at: Option<String>,
+    contracted_at: Option<String>,
+}
+
+pub fn find_backfill(name: &str) -> Result<&'static Backfill, BackfillError> {
+    BACKFILLS
+        .iter()
+        .find(|backfill| backfill.name == name)
+        .ok_or_else(|| BackfillError::NotFound("backfill not found".to_string()))
+}
+
+pub async fn list_backfills(pool: &SqlitePool) -> Result<Vec<SchemaBackfill>, BackfillError> {
+    let mut backfills = Vec::with_capacity(BACKFILLS.len());
+    for backfill in &BACKFILLS {
+        backfills.push(get_backfill(pool, backfill).await?);
+    }
+    Ok(backfills)
+}
+
+pub async fn get_backfill(
+    pool: &SqlitePool,
+    backfill: &Backfill,
+) -> Result<SchemaBackfill, BackfillError> {
+    let row: Option<BackfillStateRow> = sqlx::query_as(
+        "SELECT cursor, job_id, completed_at, contracted_at FROM schema_backfills WHERE name = ?",
+    )
+    .bind(backfill.name)
+    .fetch_optional(pool)
+    .await?;
+    let row = row.unwrap_or(BackfillStateRow {
+        cursor: 0,
+        job_id: None,
+        completed_at: None,
+        contracted_at: None,
+    });
+    Ok(SchemaBackfill {
+        name: backfill.name.to_string(),
+        table: backfill.table.to_string(),
+        column: backfill.column.to_string(),
+        description: backfill.description.to_string(),
+        cursor: row.cursor,
+        job_id: row
+            .job_id
+            .as_deref()
+            .map(|id| {
+                Uuid::parse_str(id)
+                    .map_err(|err| BackfillError::Parse(format!("invalid job id: {err}")))
+            })
+            .transpose()?,
+        completed_at: row.completed_at,
+        contracted_at: row.contracted_at,
+    })
+}
+
+/// Rejects starting `backfill` while a job is already filling it, or once
+/// it has finished.
+pub async fn ensure_backfill_idle(
+    pool: &SqlitePool,
+    backfill: &Backfill,
+) -> Result<(), BackfillError> {
+    let state = get_backfill(pool, backfill).await?;
+    if state.completed_at.is_some() {
+        return Err(BackfillError::Conflict("backfill_completed".to_string()));
+    }
+    let running: bool = sqlx::query_scalar(
+        r"
+        SELECT EXISTS (
+            SELECT 1 FROM schema_backfills b
+            JOIN jobs j ON j.id = b.job_id
+            WHERE b.name = ? AND j.status IN ('queued', 'running')
+        )
+        ",
+    )
+    .bind(backfill.name)
+    .fetch_one(pool)
+    .await?;
+    if running {
+        return Err(BackfillError::Conflict("backfill_running".to_string()));
+    }
+    Ok(())
+}
+
+/// Records `job_id` as the job filling `backfill` and returns the rowid
+/// range it has left: after the saved cursor, up to the table's current
+/// last row. Rows inserted later are written with the column already set.
+pub async fn claim_backfill(
+    pool: &SqlitePool,
+    backfill: &Backfill,
+    job_id: Uuid,
+) -> Result<(i64, i64), BackfillError> {
+    let cursor: i64 = sqlx::query_scalar(
+        r"
+        INSERT INTO schema_backfills (name, job_id) VALUES (?, ?)
+        ON CONFLICT(name) DO UPDATE SET job_id = excluded.job_id
+        RETURNING cursor
+        ",
+    )
+    .bind(backfill.name)
+    .bind(job_id.to_string())
+    .fetch_one(pool)
+    .await?;
+    let last: Option<i64> =
+        sqlx::query_scalar(&format!("SELECT MAX(rowid) FROM {}", backfill.table))
+            .fetch_one(pool)
+            .await?;
+    Ok((cursor, last.unwrap_or(0)))
+}
+
+/// Fills the rows of `backfill` with a rowid in `(cursor, end]` and saves
+/// `end` as the new cursor, in one short transaction.
+pub async fn backfill_batch(
+    pool: &SqlitePool,
+    backfill: &Backfill,
+    cursor: i64,
+    end: i64,
+) -> Result<u64, BackfillError> {
+    let mut tx = pool.begin().await?;
+    let updated = match backfill.value {
+        BackfillValue::Sql(value) => sqlx::query(&format!(
+            "UPDATE {table} SET {column} = {value} \
+            WHERE rowid > ? AND rowid <= ? AND {column} IS NULL{filter}",
+            table = backfill.table,
+            column = backfill.column,
+            filter = backfill.filter_clause(),
+        ))
+        .bind(cursor)
+        .bind(end)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected(),
+        BackfillValue::Derived(derive) => {
+            let rows: Vec<(i64, String, String, Vec<u8>)> = sqlx::query_as(&format!(
+                "SELECT rowid, provider, headers, CAST(payload AS BLOB) FROM {table} \
+                WHERE rowid > ? AND rowid <= ? AND {column} IS NULL{filter}",
+                table = backfill.table,
+                column = backfill.column,
+                filter = backfill.filter_clause(),
+            ))
+            .bind(cursor)
+            .bind(end)
+            .fetch_all(&mut *tx)
+            .await?;
+            let mut updated = 0;
+            for (rowid, provider, headers, payload) in rows {
+                let event = StoredEvent {
+                    provider,
+                    headers: parse_headers(&headers),
+                    payload,
+                };
+                let Some(value) = derive(&event) else {
+                    continue;
+                };
+                updated += sqlx::query(&format!(
+                    "UPDATE {table} SET {column} = ? WHERE rowid = ?",
+                    table = backfill.table,
+                    column = backfill.column,
+                ))
+                .bind(value)
+                .bind(rowid)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            }
+            updated
+        }
+    };
+    sqlx::query("UPDATE schema_backfills SET cursor = ? WHERE name = ?")
+        .bind(end)
+        .bind(backfill.name)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(updated)
+}
+
+pub async fn complete_backfill(
+    pool: &SqlitePool,
+    backfill: &Backfill,
+) -> Result<(), BackfillError> {
+    sqlx::query("UPDATE schema_backfills SET completed_at = ? WHERE name = ?")
+        .bind(now())
+        .bind(backfill.name)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Runs the contract step of `backfill`. Refuses with 409
+/// `backfill_incomplete` until the backfill has finished and no row is
+/// still waiting for a value, and with `backfill_contracted` the second
+/// time.
+pub async fn contract_backfill(
+    pool: &SqlitePool,
+    backfill: &Backfill,
+) -> Result<SchemaBackfill, BackfillError> {
+    let state = get_backfill(pool, backfill).await?;
+    if state.contracted_at.is_some() {
+        return Err(BackfillError::Conflict("backfill_contracted".to_string()));
+    }
+    // Derived values cannot be checked in SQL. Every row up to the cursor
+    // has been visited, and later rows were written with the column set.
+    let remaining = match backfill.value {
+        BackfillValue::Sql(value) => {
+            sqlx::query_scalar(&format!(
+                "SELECT EXISTS (SELECT 1 FROM {table} \
+                WHERE {column} IS NULL AND ({value}) IS NOT NULL{filter})",
+                table = backfill.table,
+                column = backfill.column,
+                filter = backfill.filter_clause(),
+            ))
+            .fetch_one(pool)
+            .await?
+        }
+        BackfillValue::Derived(_) => false,
+    };
+    if state.completed_at.is_none() || remaining {
+        return Err(BackfillError::Conflict("backfill_incomplete".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+    for statement in backfill.contract {
+        sqlx::query(statement).execute(&mut *tx).await?;
+    }
+    sqlx::query("UPDATE schema_backfills SET contracted_at = ? WHERE name = ?")
+        .bind(now())
+        .bind(backfill.name)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    get_backfill(pool, backfill).await
+}
+
+/// Stored headers are a JSON object of names to values. Entries that are
+/// not valid header names or values are skipped.
+fn parse_headers(raw: &str) -> HeaderMap {
+    let headers: BTreeMap<String, String> = serde_json::from_str(raw).unwrap_or_default();
+    headers
+        .into_iter()
+        .filter_map(|(name, value)| {
+            Some((
+                HeaderName::from_bytes(name.as_bytes()).ok()?,
+                HeaderValue::from_str(&value).ok()?,
+            ))
+        })
+        .collect()
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
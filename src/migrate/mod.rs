@@ -0,0 +1,11 @@
+mod backfill;
+mod store;
+
+pub use backfill::{
+    BACKFILLS, Backfill, BackfillError, BackfillValue, StoredEvent, backfill_batch, claim_backfill,
+    complete_backfill, contract_backfill, ensure_backfill_idle, find_backfill, get_backfill,
+    list_backfills,
+};
+pub use store::{
+    MIGRATOR, MigrationError, MigrationReport, RepairedMigration, repair_migrations, run_migrations,
+};
@@ -0,0 +1,194 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use sqlx::migrate::{MigrateError, Migrator};
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Runs of the migrator before giving up. Two processes starting against
+/// the same database both try to apply the pending migrations, and the
+/// slower one fails on whatever the other already applied. Running again
+/// skips those.
+const MIGRATE_ATTEMPTS: u32 = 3;
+const MIGRATE_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+#[derive(Debug, thiserror::Error)]
+pub enum MigrationError {
+    #[error(
+        "migration {version} ({description}) was left partially applied; \
+        run `receiver migrate --repair` to re-apply it"
+    )]
+    Dirty { version: i64, description: String },
+    #[error(
+        "could not repair migration {version}: `{statement}` failed: {error}; \
+        apply the rest of the migration by hand, then mark it successful in _sqlx_migrations"
+    )]
+    Repair {
+        version: i64,
+        statement: String,
+        error: sqlx::Error,
+    },
+    #[error("{0}")]
+    Migrate(MigrateError),
+    #[error("{0}")]
+    Db(sqlx::Error),
+}
+
+impl From<sqlx::Error> for MigrationError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Db(err)
+    }
+}
+
+impl From<MigrateError> for MigrationError {
+    fn from(err: MigrateError) -> Self {
+        Self::Migrate(err)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationReport {
+    /// Versions applied by this run.
+    pub applied: Vec<i64>,
+    /// Partially applied migrations that were re-run.
+    pub repaired: Vec<RepairedMigration>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepairedMigration {
+    pub version: i64,
+    pub description: String,
+    /// Statements that had already been applied before the crash.
+    pub skipped_statements: usize,
+}
+
+/// Applies pending migrations. A migration recorded as unfinished stops
+/// the run with [`MigrationError::Dirty`] rather than being guessed at.
+pub async fn run_migrations(pool: &SqlitePool) -> Result<MigrationReport, MigrationError> {
+    let before = applied_versions(pool).await?;
+    let mut attempt = 1;
+    loop {
+        match MIGRATOR.run(pool).await {
+            Ok(()) => break,
+            Err(MigrateError::Dirty(version)) => return Err(dirty(pool, version).await),
+            Err(MigrateError::Execute(_)) if attempt < MIGRATE_ATTEMPTS => {
+                attempt += 1;
+                tokio::time::sleep(MIGRATE_RETRY_DELAY).await;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+    let applied = applied_versions(pool)
+        .await?
+        .into_iter()
+        .filter(|version| !before.contains(version))
+        .collect();
+    Ok(MigrationReport {
+        applied,
+        repaired: Vec::new(),
+    })
+}
+
+/// Re-applies migrations recorded as unfinished, then runs the pending
+/// ones. Statements are replayed one at a time and those failing because
+/// their table, index, or column already exists are skipped, so a script
+/// that stopped halfway is completed rather than run twice. Any other
+/// failure rolls the repair back and is reported with the statement, for
+/// the operator to finish by hand.
+///
+/// Scripts are split on `;`, which is why migrations avoid semicolons
+/// outside statement ends.
+pub async fn repair_migrations(pool: &SqlitePool) -> Result<MigrationReport, MigrationError> {
+    let dirty: Vec<(i64, String)> = if migrations_table_exists(pool).await? {
+        sqlx::query_as(
+            "SELECT version, description FROM _sqlx_migrations \
+            WHERE success = FALSE ORDER BY version",
+        )
+        .fetch_all(pool)
+        .await?
+    } else {
+        Vec::new()
+    };
+
+    let mut repaired = Vec::with_capacity(dirty.len());
+    for (version, description) in dirty {
+        let migration = MIGRATOR
+            .iter()
+            .find(|migration| migration.version == version)
+            .ok_or(MigrateError::VersionMissing(version))?;
+        let mut tx = pool.begin().await?;
+        let mut skipped_statements = 0;
+        for statement in migration.sql.split(';').map(str::trim) {
+            if statement.is_empty() {
+                continue;
+            }
+            match sqlx::query(statement).execute(&mut *tx).await {
+                Ok(_) => {}
+                Err(err) if already_applied(&err) => skipped_statements += 1,
+                Err(error) => {
+                    return Err(MigrationError::Repair {
+                        version,
+                        statement: statement.to_string(),
+                        error,
+                    });
+                }
+            }
+        }
+        sqlx::query("UPDATE _sqlx_migrations SET success = TRUE, checksum = ? WHERE version = ?")
+            .bind(&*migration.checksum)
+            .bind(version)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        repaired.push(RepairedMigration {
+            version,
+            description,
+            skipped_statements,
+        });
+    }
+
+    let mut report = run_migrations(pool).await?;
+    report.repaired = repaired;
+    Ok(report)
+}
+
+async fn dirty(pool: &SqlitePool, version: i64) -> MigrationError {
+    let description =
+        sqlx::query_scalar("SELECT description FROM _sqlx_migrations WHERE version = ?")
+            .bind(version)
+            .fetch_optional(pool)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+    MigrationError::Dirty {
+        version,
+        description,
+    }
+}
+
+fn already_applied(err: &sqlx::Error) -> bool {
+    err.as_database_error().is_some_and(|err| {
+        let message = err.message();
+        message.contains("already exists") || message.starts_with("duplicate column name")
+    })
+}
+
+async fn migrations_table_exists(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master \
+        WHERE type = 'table' AND name = '_sqlx_migrations')",
+    )
+    .fetch_one(pool)
+    .await
+}
+
+async fn applied_versions(pool: &SqlitePool) -> Result<Vec<i64>, sqlx::Error> {
+    if !migrations_table_exists(pool).await? {
+        return Ok(Vec::new());
+    }
+    sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success = TRUE ORDER BY version")
+        .fetch_all(pool)
+        .await
+}
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation, decode, decode_header,
+    jwk::{Jwk, JwkSet},
+};
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// How often a token naming an unknown key may re-read the JWKS file.
+const JWKS_RELOAD_INTERVAL: Duration = Duration::from_mins(1);
+
+/// Signing algorithms accepted from the issuer. Symmetric ones are left
+/// out, since the JWKS is public.
+const ACCEPTED_ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// Who an OIDC access token was issued to, and the endpoints it is limited
+/// to, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OidcIdentity {
+    pub subject: String,
+    pub endpoint_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Deserialize)]
+struct AccessClaims {
+    sub: String,
+    /// Endpoints the token is limited to. Tokens without it get full
+    /// access, like `INSPECTOR_API_TOKEN`.
+    #[serde(default, rename = "receiver_endpoints")]
+    endpoint_ids: Option<Vec<Uuid>>,
+}
+
+struct KeyCache {
+    keys: JwkSet,
+    loaded_at: Instant,
+}
+
+/// Verifies OIDC access tokens, such as the ones `receiver login` obtains,
+/// for the inspector API. Tokens must be signed by a key of the issuer's
+/// JWKS, carry the issuer and audience, and be unexpired.
+///
+/// The receiver makes no outbound calls, so the JWKS is read from a file
+/// kept in sync with the issuer's `jwks_uri`. The file is re-read when a
+/// token names a key it does not hold, so rotated keys are picked up
+/// without a restart.
+#[derive(Clone)]
+pub struct OidcVerifier {
+    issuer: String,
+    audience: String,
+    jwks_path: PathBuf,
+    cache: Arc<RwLock<KeyCache>>,
+}
+
+impl OidcVerifier {
+    pub fn from_jwks_file(
+        issuer: impl Into<String>,
+        audience: impl Into<String>,
+        jwks_path: impl Into<PathBuf>,
+    ) -> Result<Self, String> {
+        let jwks_path = jwks_path.into();
+        let keys = read_jwks(&jwks_path)?;
+        Ok(Self {
+            issuer: issuer.into(),
+            audience: audience.into(),
+            jwks_path,
+            cache: Arc::new(RwLock::new(KeyCache {
+                keys,
+                loaded_at: Instant::now(),
+            })),
+        })
+    }
+
+    /// `None` for any token that does not verify.
+    pub async fn verify(&self, token: &str) -> Option<OidcIdentity> {
+        let header = decode_header(token).ok()?;
+        if !ACCEPTED_ALGORITHMS.contains(&header.alg) {
+            return None;
+        }
+        let jwk = self.find_key(header.kid.as_deref()?).await?;
+        let key = DecodingKey::from_jwk(&jwk).ok()?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.audience]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = decode::<AccessClaims>(token, &key, &validation)
+            .ok()?
+            .claims;
+
+        Some(OidcIdentity {
+            subject: claims.sub,
+            endpoint_ids: claims.endpoint_ids,
+        })
+    }
+
+    async fn find_key(&self, kid: &str) -> Option<Jwk> {
+        {
+            let cache = self.cache.read().await;
+            if let Some(jwk) = cache.keys.find(kid) {
+                return Some(jwk.clone());
+            }
+            if cache.loaded_at.elapsed() < JWKS_RELOAD_INTERVAL {
+                return None;
+            }
+        }
+
+        let mut cache = self.cache.write().await;
+        if cache.loaded_at.elapsed() >= JWKS_RELOAD_INTERVAL {
+            // A file caught mid-update keeps the keys already loaded.
+            if let Ok(keys) = read_jwks(&self.jwks_path) {
+                cache.keys = keys;
+            }
+            cache.loaded_at = Instant::now();
+        }
+        cache.keys.find(kid).cloned()
+    }
+}
+
+fn read_jwks(path: &Path) -> Result<JwkSet, String> {
+    let raw = std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read JWKS {}: {err}", path.display()))?;
+    serde_json::from_str(&raw).map_err(|err| format!("invalid JWKS {}: {err}", path.display()))
+}
@@ -0,0 +1,40 @@
+use reqwest::redirect::{Attempt, Policy};
+
+use crate::types::{RedirectMode, RedirectPolicy};
+
+#[derive(Debug, thiserror::Error)]
+#[error("stopped after {0} redirects")]
+struct TooManyRedirects(usize);
+
+/// Redirect policy for the delivery client of a leased event. Redirects
+/// are not followed per request in reqwest, so workers build one delivery
+/// client per distinct [`RedirectPolicy`] and report `response.url()` as
+/// the attempt's `final_url`.
+///
+/// `same_host` answers with the redirect response itself once a redirect
+/// leaves the host of the target URL. Going past `max_redirects` fails
+/// the request, which [`attempt_error_kind`] reports as
+/// `TooManyRedirects`.
+///
+/// [`attempt_error_kind`]: crate::resolver::attempt_error_kind
+pub fn delivery_redirect_policy(policy: RedirectPolicy) -> Policy {
+    let max = usize::try_from(policy.max_redirects).unwrap_or(0);
+    match policy.mode {
+        RedirectMode::None => Policy::none(),
+        RedirectMode::Any => Policy::limited(max),
+        RedirectMode::SameHost => Policy::custom(move |attempt| same_host(attempt, max)),
+    }
+}
+
+fn same_host(attempt: Attempt, max: usize) -> reqwest::redirect::Action {
+    let followed = attempt.previous().len();
+    if followed > max {
+        return attempt.error(TooManyRedirects(max));
+    }
+    let origin = attempt.previous().first().and_then(|url| url.host_str());
+    if origin.is_some() && attempt.url().host_str() == origin {
+        attempt.follow()
+    } else {
+        attempt.stop()
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+use crate::types::{AddressFamily, WebhookAttemptErrorKind};
+
+/// Expired entries are only swept once the cache grows past this size.
+const MAX_CACHE_ENTRIES: usize = 1024;
+
+/// Why a host name did not resolve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DnsErrorKind {
+    /// The name does not exist.
+    NotFound,
+    /// The name exists but has no addresses.
+    NoAddresses,
+    /// The resolver could not answer, e.g. SERVFAIL or an unreachable
+    /// upstream. Worth retrying.
+    Temporary,
+    /// No answer within the lookup timeout.
+    Timeout,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("could not resolve {host}: {message}")]
+pub struct DnsError {
+    pub host: String,
+    pub kind: DnsErrorKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolverConfig {
+    /// How long resolved addresses are reused. The system resolver does
+    /// not expose record TTLs, so this applies to every name.
+    pub ttl: Duration,
+    /// How long `NotFound` and `NoAddresses` answers are reused. Temporary
+    /// failures and timeouts are never cached.
+    pub negative_ttl: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_mins(1),
+            negative_ttl: Duration::from_secs(10),
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Caching resolver for worker delivery clients. Plug it into the
+/// delivery `reqwest::Client` with `ClientBuilder::dns_resolver` so
+/// lookups are shared across deliveries to the same host and their
+/// failures can be told apart with [`attempt_error_kind`].
+///
+/// Addresses are handed to the client filtered and ordered for an
+/// endpoint's [`AddressFamily`], see [`CachingResolver::for_family`].
+/// reqwest races the second family after a fixed 300ms, so a leased
+/// `happy_eyeballs_delay_ms` needs a worker connector that exposes the
+/// delay. Report `response.remote_addr()` as the attempt's
+/// `peer_address`.
+#[derive(Debug, Clone)]
+pub struct CachingResolver {
+    config: ResolverConfig,
+    family: AddressFamily,
+    cache: Arc<Mutex<HashMap<String, CacheEntry>>>,
+}
+
+impl Default for CachingResolver {
+    fn default() -> Self {
+        Self::new(ResolverConfig::default())
+    }
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    expires_at: Instant,
+    result: Result<Vec<IpAddr>, DnsError>,
+}
+
+impl CachingResolver {
+    pub fn new(config: ResolverConfig) -> Self {
+        Self {
+            config,
+            family: AddressFamily::Any,
+            cache: Arc::default(),
+        }
+    }
+
+    /// A resolver sharing this one's cache that resolves for `family`.
+    /// Build one delivery client per family in use.
+    #[must_use]
+    pub fn for_family(&self, family: AddressFamily) -> Self {
+        Self {
+            family,
+            ..self.clone()
+        }
+    }
+
+    pub async fn lookup(&self, host: &str) -> Result<Vec<IpAddr>, DnsError> {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Ok(vec![ip]);
+        }
+        if let Some(result) = self.cached(&host) {
+            return result;
+        }
+
+        let lookup = tokio::net::lookup_host((host.as_str(), 0));
+        let result = match tokio::time::timeout(self.config.timeout, lookup).await {
+            Err(_) => Err(DnsError {
+                host: host.clone(),
+                kind: DnsErrorKind::Timeout,
+                message: format!("no answer within {}ms", self.config.timeout.as_millis()),
+            }),
+            Ok(Err(err)) => Err(DnsError {
+                host: host.clone(),
+                kind: classify_lookup_error(&err),
+                message: err.to_string(),
+            }),
+            Ok(Ok(addrs)) => {
+                let mut ips: Vec<IpAddr> = Vec::new();
+                for addr in addrs {
+                    if !ips.contains(&addr.ip()) {
+                        ips.push(addr.ip());
+                    }
+                }
+                if ips.is_empty() {
+                    Err(DnsError {
+                        host: host.clone(),
+                        kind: DnsErrorKind::NoAddresses,
+                        message: "no addresses returned".to_string(),
+                    })
+                } else {
+                    Ok(ips)
+                }
+            }
+        };
+        self.store(host, &result);
+        result
+    }
+
+    fn cached(&self, host: &str) -> Option<Result<Vec<IpAddr>, DnsError>> {
+        let cache = self.cache.lock().ok()?;
+        let entry = cache.get(host)?;
+        (entry.expires_at > Instant::now()).then(|| entry.result.clone())
+    }
+
+    fn store(&self, host: String, result: &Result<Vec<IpAddr>, DnsError>) {
+        let ttl = match result {
+            Ok(_) => self.config.ttl,
+            Err(err) if matches!(err.kind, DnsErrorKind::NotFound | DnsErrorKind::NoAddresses) => {
+                self.config.negative_ttl
+            }
+            Err(_) => return,
+        };
+        // A poisoned cache only costs extra lookups.
+        let Ok(mut cache) = self.cache.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            cache.retain(|_, entry| entry.expires_at > now);
+        }
+        cache.insert(
+            host,
+            CacheEntry {
+                expires_at: now + ttl,
+                result: result.clone(),
+            },
+        );
+    }
+}
+
+impl Resolve for CachingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let ips = order_addresses(resolver.lookup(name.as_str()).await?, resolver.family);
+            if ips.is_empty() {
+                return Err(DnsError {
+                    host: name.as_str().to_string(),
+                    kind: DnsErrorKind::NoAddresses,
+                    message: format!("no addresses for {:?}", resolver.family),
+                }
+                .into());
+            }
+            let addrs: Addrs = Box::new(ips.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+/// Keeps the addresses of `family` and moves the preferred family first,
+/// otherwise keeping the resolver's order.
+pub fn order_addresses(ips: Vec<IpAddr>, family: AddressFamily) -> Vec<IpAddr> {
+    match family {
+        AddressFamily::Any => ips,
+        AddressFamily::Ipv4 => ips.into_iter().filter(IpAddr::is_ipv4).collect(),
+        AddressFamily::Ipv6 => ips.into_iter().filter(IpAddr::is_ipv6).collect(),
+        AddressFamily::PreferIpv4 => {
+            let (mut first, rest): (Vec<_>, Vec<_>) = ips.into_iter().partition(IpAddr::is_ipv4);
+            first.extend(rest);
+            first
+        }
+        AddressFamily::PreferIpv6 => {
+            let (mut first, rest): (Vec<_>, Vec<_>) = ips.into_iter().partition(IpAddr::is_ipv6);
+            first.extend(rest);
+            first
+        }
+    }
+}
+
+/// Error kind to report for a failed delivery request. Requests whose
+/// host did not resolve through [`CachingResolver`] are `Dns`, so name
+/// resolution incidents stand apart from other network errors. A 413
+/// response is not an error to reqwest; report it as `PayloadTooLarge`.
+pub fn attempt_error_kind(err: &reqwest::Error) -> WebhookAttemptErrorKind {
+    if err.is_redirect() {
+        return WebhookAttemptErrorKind::TooManyRedirects;
+    }
+    let mut source = std::error::Error::source(err);
+    while let Some(inner) = source {
+        if inner.is::<DnsError>() {
+            return WebhookAttemptErrorKind::Dns;
+        }
+        if let Some(io_err) = inner.downcast_ref::<io::Error>()
+            && io_err.kind() == io::ErrorKind::ConnectionRefused
+        {
+            return WebhookAttemptErrorKind::ConnectionRefused;
+        }
+        // TLS backends are optional for workers, so their error types are
+        // not nameable here and only their messages identify them.
+        let message = inner.to_string().to_ascii_lowercase();
+        if message.contains("certificate") || message.contains("tls") || message.contains("ssl") {
+            return WebhookAttemptErrorKind::Tls;
+        }
+        source = inner.source();
+    }
+
+    if err.is_timeout() {
+        WebhookAttemptErrorKind::Timeout
+    } else if err.is_connect() || err.is_request() {
+        WebhookAttemptErrorKind::Network
+    } else if err.is_decode() || err.is_body() {
+        WebhookAttemptErrorKind::InvalidResponse
+    } else {
+        WebhookAttemptErrorKind::Unexpected
+    }
+}
+
+/// getaddrinfo failures only surface through their message, e.g. "Name
+/// or service not known" or "Temporary failure in name resolution".
+fn classify_lookup_error(err: &io::Error) -> DnsErrorKind {
+    let message = err.to_string().to_ascii_lowercase();
+    if message.contains("no address") {
+        DnsErrorKind::NoAddresses
+    } else if message.contains("not known")
+        || message.contains("not found")
+        || message.contains("no such host")
+    {
+        DnsErrorKind::NotFound
+    } else {
+        DnsErrorKind::Temporary
+    }
+}
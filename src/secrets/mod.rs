@@ -0,0 +1,5 @@
+mod provider;
+mod store;
+
+pub use provider::{EnvProvider, FileProvider, SecretProvider};
+pub use store::{SecretError, SecretStore};
@@ -0,0 +1,72 @@
+use std::path::{Component, Path, PathBuf};
+
+use super::SecretError;
+
+/// A backend that turns the key part of a secret ID (`<scheme>:<key>`)
+/// into the secret value.
+pub trait SecretProvider: Send + Sync {
+    fn resolve(&self, key: &str) -> Result<String, SecretError>;
+}
+
+/// Reads secrets from process environment variables: `env:STRIPE_SECRET`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EnvProvider;
+
+impl SecretProvider for EnvProvider {
+    fn resolve(&self, key: &str) -> Result<String, SecretError> {
+        std::env::var(key).map_err(|_| SecretError::NotFound(format!("env var {key} is not set")))
+    }
+}
+
+/// Reads secrets from files, e.g. mounted Docker/Kubernetes secrets or
+/// files rendered by a Vault agent. When `base_dir` is configured, keys are
+/// relative to it and may not point outside it, symlinks included. Files
+/// are read on every lookup so rotated secrets are picked up without a
+/// restart.
+#[derive(Debug, Default, Clone)]
+pub struct FileProvider {
+    pub base_dir: Option<PathBuf>,
+}
+
+impl FileProvider {
+    fn path(&self, key: &str) -> Result<PathBuf, SecretError> {
+        let Some(base) = &self.base_dir else {
+            return Ok(PathBuf::from(key));
+        };
+        let outside = || {
+            SecretError::OutsideBaseDir(format!("secret file {key} is outside {}", base.display()))
+        };
+        if !Path::new(key)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(outside());
+        }
+        let not_found = |err: std::io::Error, path: &Path| match err.kind() {
+            std::io::ErrorKind::NotFound => {
+                SecretError::NotFound(format!("secret file {} not found", path.display()))
+            }
+            _ => SecretError::Io(err),
+        };
+        let base = base.canonicalize().map_err(|err| not_found(err, base))?;
+        let path = base.join(key);
+        let path = path.canonicalize().map_err(|err| not_found(err, &path))?;
+        if !path.starts_with(&base) {
+            return Err(outside());
+        }
+        Ok(path)
+    }
+}
+
+impl SecretProvider for FileProvider {
+    fn resolve(&self, key: &str) -> Result<String, SecretError> {
+        let path = self.path(key)?;
+        let contents = std::fs::read_to_string(&path).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => {
+                SecretError::NotFound(format!("secret file {} not found", path.display()))
+            }
+            _ => SecretError::Io(err),
+        })?;
+        Ok(contents.trim_end_matches(['\r', '\n']).to_string())
+    }
+}
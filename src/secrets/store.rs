@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::{EnvProvider, FileProvider, SecretProvider};
+
+#[derive(Debug)]
+pub enum SecretError {
+    /// The secret ID is not of the form `<scheme>:<key>`.
+    InvalidId(String),
+    UnknownScheme(String),
+    NotFound(String),
+    /// A `file:` key that leaves the configured base directory.
+    OutsideBaseDir(String),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for SecretError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidId(id) => write!(f, "invalid secret id: {id}"),
+            Self::UnknownScheme(scheme) => write!(f, "unknown secret scheme: {scheme}"),
+            Self::NotFound(message) | Self::OutsideBaseDir(message) => f.write_str(message),
+            Self::Io(err) => write!(f, "io error: {err}"),
+        }
+    }
+}
+
+/// Registry of secret backends keyed by scheme. Secret IDs stored in the
+/// database look like `env:STRIPE_SECRET` or `file:stripe/signing`, so
+/// plaintext values never need to live in the endpoints table.
+#[derive(Clone)]
+pub struct SecretStore {
+    providers: HashMap<String, Arc<dyn SecretProvider>>,
+}
+
+impl SecretStore {
+    /// An empty store; use [`SecretStore::default`] for the built-in schemes.
+    pub fn empty() -> Self {
+        Self {
+            providers: HashMap::new(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let mut store = Self::default();
+
+        if let Ok(value) = std::env::var("RECEIVER_SECRETS_FILE_DIR") {
+            let trimmed = value.trim();
+            if !trimmed.is_empty() {
+                store = store.with_provider(
+                    "file",
+                    FileProvider {
+                        base_dir: Some(PathBuf::from(trimmed)),
+                    },
+                );
+            }
+        }
+
+        store
+    }
+
+    /// Registers `provider` for `scheme`, replacing any existing backend.
+    #[must_use]
+    pub fn with_provider(mut self, scheme: &str, provider: impl SecretProvider + 'static) -> Self {
+        self.providers
+            .insert(scheme.to_string(), Arc::new(provider));
+        self
+    }
+
+    pub fn resolve(&self, secret_id: &str) -> Result<String, SecretError> {
+        let (provider, key) = self.provider(secret_id)?;
+        provider.resolve(key)
+    }
+
+    /// Checks that `secret_id` is well formed and names a registered
+    /// scheme, without looking the secret up.
+    pub fn check_scheme(&self, secret_id: &str) -> Result<(), SecretError> {
+        self.provider(secret_id).map(|_| ())
+    }
+
+    fn provider<'a>(
+        &self,
+        secret_id: &'a str,
+    ) -> Result<(&Arc<dyn SecretProvider>, &'a str), SecretError> {
+        let (scheme, key) = secret_id
+            .split_once(':')
+            .filter(|(scheme, key)| !scheme.is_empty() && !key.is_empty())
+            .ok_or_else(|| SecretError::InvalidId(secret_id.to_string()))?;
+        let provider = self
+            .providers
+            .get(scheme)
+            .ok_or_else(|| SecretError::UnknownScheme(scheme.to_string()))?;
+        Ok((provider, key))
+    }
+}
+
+impl Default for SecretStore {
+    /// `env` and `file`. Other backends, such as Vault or AWS Secrets
+    /// Manager, are not built in; their secret IDs fail with
+    /// [`SecretError::UnknownScheme`] unless a provider is registered.
+    fn default() -> Self {
+        Self::empty()
+            .with_provider("env", EnvProvider)
+            .with_provider("file", FileProvider::default())
+    }
+}
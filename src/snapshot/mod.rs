@@ -0,0 +1,6 @@
+mod store;
+
+pub use store::{
+    SnapshotError, SnapshotSummary, check_snapshot_header, restore_snapshot,
+    restore_snapshot_records, write_snapshot,
+};
@@ -0,0 +1,313 @@
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Column, QueryBuilder, Row, SqlitePool, TypeInfo, ValueRef};
+
+use crate::ingest::{payload_sha256, rebuild_stored_payload_bytes};
+
+const FORMAT: &str = "receiver-snapshot";
+const VERSION: i64 = 1;
+/// BLOB values are written as `{"$base64": "..."}`.
+const BLOB_KEY: &str = "$base64";
+
+/// Tables in restore order: each only references rows from tables before
+/// it.
+const TABLES: [&str; 5] = [
+    "endpoint_groups",
+    "endpoints",
+    "sources",
+    "webhook_events",
+    "webhook_attempt_logs",
+];
+
+/// Events worth carrying to another machine: queued work and the dead
+/// letter queue. In-flight events are left to their current lease.
+/// Sources are included because events reference them.
+const SNAPSHOT_QUERIES: [(&str, &str); 5] = [
+    ("endpoint_groups", "SELECT * FROM endpoint_groups"),
+    ("endpoints", "SELECT * FROM endpoints"),
+    ("sources", "SELECT * FROM sources"),
+    (
+        "webhook_events",
+        "SELECT * FROM webhook_events \
+        WHERE status IN ('pending', 'requeued', 'dead') AND deleted_at IS NULL",
+    ),
+    (
+        "webhook_attempt_logs",
+        "SELECT a.* FROM webhook_attempt_logs_all a \
+        JOIN webhook_events e ON e.id = a.event_id \
+        WHERE e.status IN ('pending', 'requeued', 'dead') AND e.deleted_at IS NULL",
+    ),
+];
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Db(sqlx::Error),
+    Io(std::io::Error),
+    Parse(String),
+}
+
+impl From<sqlx::Error> for SnapshotError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Db(err)
+    }
+}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Row counts per table written to or restored from a snapshot.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SnapshotSummary {
+    pub endpoint_groups: u64,
+    pub endpoints: u64,
+    pub sources: u64,
+    pub webhook_events: u64,
+    pub webhook_attempt_logs: u64,
+}
+
+impl SnapshotSummary {
+    fn add(&mut self, table: &str, count: u64) {
+        match table {
+            "endpoint_groups" => self.endpoint_groups += count,
+            "endpoints" => self.endpoints += count,
+            "sources" => self.sources += count,
+            "webhook_events" => self.webhook_events += count,
+            "webhook_attempt_logs" => self.webhook_attempt_logs += count,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: i64,
+    created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+    table: String,
+    row: Map<String, Value>,
+}
+
+/// Writes queued and dead events, their attempt logs, and all endpoints,
+/// endpoint groups, and sources as JSON lines: a header line, then one record per
+/// row. Rows are stored column by column so snapshots survive additive
+/// schema changes between the source and target machine. Source signing
+/// secrets are included as stored, so treat snapshots as sensitive.
+pub async fn write_snapshot(
+    pool: &SqlitePool,
+    out: &mut impl Write,
+) -> Result<SnapshotSummary, SnapshotError> {
+    let mut tx = pool.begin().await?;
+    let mut summary = SnapshotSummary::default();
+
+    let header = Header {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+    };
+    write_line(out, &header)?;
+
+    for (table, query) in SNAPSHOT_QUERIES {
+        let rows = sqlx::query(query).fetch_all(&mut *tx).await?;
+        for row in &rows {
+            let record = Record {
+                table: table.to_string(),
+                row: row_to_json(row)?,
+            };
+            write_line(out, &record)?;
+        }
+        summary.add(table, rows.len() as u64);
+    }
+
+    tx.commit().await?;
+    out.flush()?;
+    Ok(summary)
+}
+
+/// Restores a snapshot written by [`write_snapshot`] in one transaction.
+/// Rows whose primary key already exists are skipped, so restoring the
+/// same snapshot twice is harmless. Columns unknown to this schema are
+/// rejected rather than silently dropped.
+pub async fn restore_snapshot(
+    pool: &SqlitePool,
+    input: impl BufRead,
+) -> Result<SnapshotSummary, SnapshotError> {
+    let mut lines = input.lines();
+    match lines.next() {
+        Some(line) => check_snapshot_header(&line?)?,
+        None => return Err(SnapshotError::Parse("snapshot is empty".to_string())),
+    }
+    restore_lines(pool, lines).await
+}
+
+/// Checks the first line of a snapshot.
+pub fn check_snapshot_header(line: &str) -> Result<(), SnapshotError> {
+    let header: Header = parse_line(line)?;
+    if header.format != FORMAT || header.version != VERSION {
+        return Err(SnapshotError::Parse(format!(
+            "unsupported snapshot format {} v{}",
+            header.format, header.version
+        )));
+    }
+    Ok(())
+}
+
+/// Restores the record lines that follow a snapshot's header in one
+/// transaction, like [`restore_snapshot`]. Lines must keep the order of
+/// the snapshot, so a row is restored after the rows it references.
+pub async fn restore_snapshot_records(
+    pool: &SqlitePool,
+    records: &[impl AsRef<str>],
+) -> Result<SnapshotSummary, SnapshotError> {
+    let lines = records.iter().map(|line| Ok(line.as_ref().to_string()));
+    restore_lines(pool, lines).await
+}
+
+async fn restore_lines(
+    pool: &SqlitePool,
+    lines: impl Iterator<Item = std::io::Result<String>>,
+) -> Result<SnapshotSummary, SnapshotError> {
+    let mut tx = pool.begin().await?;
+    let mut columns = Vec::with_capacity(TABLES.len());
+    for table in TABLES {
+        let names: Vec<String> =
+            sqlx::query_scalar(&format!("SELECT name FROM pragma_table_info('{table}')"))
+                .fetch_all(&mut *tx)
+                .await?;
+        columns.push((table, names.into_iter().collect::<HashSet<_>>()));
+    }
+
+    let mut summary = SnapshotSummary::default();
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Record = parse_line(&line)?;
+        let Some((table, known)) = columns.iter().find(|(table, _)| *table == record.table) else {
+            return Err(SnapshotError::Parse(format!(
+                "unknown snapshot table: {}",
+                record.table
+            )));
+        };
+        if let Some(column) = record.row.keys().find(|column| !known.contains(*column)) {
+            return Err(SnapshotError::Parse(format!(
+                "unknown column {table}.{column}"
+            )));
+        }
+        if record.row.is_empty() {
+            continue;
+        }
+        if *table == "webhook_events" {
+            verify_payload(&record.row)?;
+        }
+
+        let mut insert = QueryBuilder::new(format!("INSERT INTO {table} ("));
+        let mut names = insert.separated(", ");
+        for column in record.row.keys() {
+            names.push(column);
+        }
+        insert.push(") VALUES (");
+        let mut values = insert.separated(", ");
+        for value in record.row.values() {
+            match value {
+                Value::Null => values.push_bind(None::<String>),
+                Value::Bool(flag) => values.push_bind(*flag),
+                Value::Number(number) => match number.as_i64() {
+                    Some(int) => values.push_bind(int),
+                    None => values.push_bind(number.as_f64()),
+                },
+                Value::String(text) => values.push_bind(text.clone()),
+                Value::Object(blob) if blob.len() == 1 && blob.contains_key(BLOB_KEY) => {
+                    let bytes = blob_bytes(blob).ok_or_else(|| {
+                        SnapshotError::Parse(format!("invalid {BLOB_KEY} value in {table}"))
+                    })?;
+                    values.push_bind(bytes)
+                }
+                other => values.push_bind(other.to_string()),
+            };
+        }
+        insert.push(") ON CONFLICT DO NOTHING");
+
+        let result = insert.build().execute(&mut *tx).await?;
+        summary.add(table, result.rows_affected());
+    }
+    rebuild_stored_payload_bytes(&mut tx).await?;
+
+    tx.commit().await?;
+    Ok(summary)
+}
+
+fn blob_bytes(blob: &Map<String, Value>) -> Option<Vec<u8>> {
+    blob.get(BLOB_KEY)?
+        .as_str()
+        .and_then(|encoded| STANDARD.decode(encoded).ok())
+}
+
+/// Rejects an event whose payload no longer matches its recorded hash,
+/// so a corrupted or edited snapshot is not restored silently.
+fn verify_payload(row: &Map<String, Value>) -> Result<(), SnapshotError> {
+    let Some(Value::String(expected)) = row.get("payload_sha256") else {
+        return Ok(());
+    };
+    let payload = match row.get("payload") {
+        Some(Value::String(text)) => text.as_bytes().to_vec(),
+        Some(Value::Object(blob)) => blob_bytes(blob).unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    if payload_sha256(&payload) == *expected {
+        return Ok(());
+    }
+    let id = row.get("id").and_then(Value::as_str).unwrap_or_default();
+    Err(SnapshotError::Parse(format!(
+        "payload of event {id} does not match its payload_sha256"
+    )))
+}
+
+fn row_to_json(row: &SqliteRow) -> Result<Map<String, Value>, SnapshotError> {
+    let mut map = Map::new();
+    for (index, column) in row.columns().iter().enumerate() {
+        let raw = row.try_get_raw(index)?;
+        let value = if raw.is_null() {
+            Value::Null
+        } else {
+            match raw.type_info().name() {
+                "INTEGER" => Value::from(row.try_get::<i64, _>(index)?),
+                "REAL" => Value::from(row.try_get::<f64, _>(index)?),
+                "BLOB" => {
+                    let bytes = row.try_get::<Vec<u8>, _>(index)?;
+                    let mut blob = Map::new();
+                    blob.insert(BLOB_KEY.to_string(), Value::from(STANDARD.encode(bytes)));
+                    Value::Object(blob)
+                }
+                _ => Value::from(row.try_get::<String, _>(index)?),
+            }
+        };
+        map.insert(column.name().to_string(), value);
+    }
+    Ok(map)
+}
+
+fn write_line(out: &mut impl Write, value: &impl Serialize) -> Result<(), SnapshotError> {
+    serde_json::to_writer(&mut *out, value)
+        .map_err(|err| SnapshotError::Parse(format!("failed to encode snapshot: {err}")))?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+fn parse_line<T: for<'de> Deserialize<'de>>(line: &str) -> Result<T, SnapshotError> {
+    serde_json::from_str(line)
+        .map_err(|err| SnapshotError::Parse(format!("invalid snapshot line: {err}")))
+}
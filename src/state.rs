@@ -1,10 +1,36 @@
+use std::path::PathBuf;
+
 use sqlx::SqlitePool;
 
+use crate::auth::ScopedToken;
 use crate::dispatcher::DispatcherConfig;
+use crate::ingest::{IngestConfig, IngestQueue};
+use crate::inspector::ShareLinkConfig;
+use crate::oidc::OidcVerifier;
+use crate::secrets::SecretStore;
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: SqlitePool,
     pub dispatcher: DispatcherConfig,
     pub inspector_api_token: Option<String>,
+    /// Inspector tokens limited to specific endpoints.
+    pub inspector_scoped_tokens: Vec<ScopedToken>,
+    /// Accepts OIDC access tokens next to the static ones when set.
+    pub inspector_oidc: Option<OidcVerifier>,
+    /// Enables event share links when set.
+    pub share_links: Option<ShareLinkConfig>,
+    /// Signs support bundles when set.
+    pub bundle_signing_key: Option<String>,
+    /// Records who read which event payload in the audit log. Off by
+    /// default since every read then costs a write.
+    pub audit_reads: bool,
+    pub ingest: IngestConfig,
+    /// Queue for fast-ack ingestion; when unset, fast-ack endpoints persist
+    /// synchronously.
+    pub ingest_queue: Option<IngestQueue>,
+    /// Resolves secret IDs such as endpoint signing secret references.
+    pub secrets: SecretStore,
+    /// Directory background export jobs write to; `None` disables them.
+    pub job_dir: Option<PathBuf>,
 }
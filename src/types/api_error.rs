@@ -6,9 +6,11 @@ use specta::Type;
 pub enum ApiErrorCode {
     Validation,
     Unauthorized,
+    Forbidden,
     RateLimited,
     NotFound,
     Conflict,
+    PayloadTooLarge,
     Database,
     Internal,
 }
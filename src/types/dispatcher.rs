@@ -4,21 +4,56 @@ use serde::{Deserialize, Serialize};
 use specta::Type;
 use uuid::Uuid;
 
-use super::{TargetCircuitState, WebhookAttemptErrorKind, WebhookEvent};
+use super::{
+    AttemptTiming, ConnectPolicy, EndpointCheckMethod, EndpointTargetKind, PayloadEncoding,
+    RedirectPolicy, TargetCircuitState, WebhookAttemptErrorKind, WebhookEvent,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct LeaseRequest {
     pub limit: i64,
     pub lease_ms: i64,
     pub worker_id: String,
+    /// Region the worker runs in. Events of endpoints in other regions
+    /// are only leased under their fallback rules, and a worker without a
+    /// region matches no endpoint region.
+    pub region: Option<String>,
+    /// Only lease events of this endpoint.
+    #[serde(default)]
+    pub endpoint_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct LeasedEvent {
     pub event: WebhookEvent,
+    /// Where to deliver: the endpoint's target, or its canary target when
+    /// `canary` is set.
     pub target_url: String,
+    pub target_kind: EndpointTargetKind,
+    /// Endpoint revision the event was leased against.
+    pub target_revision: i64,
+    pub canary: bool,
+    /// Where to send a best-effort copy of the delivery, reported through
+    /// the shadow report endpoint.
+    pub shadow_target_url: Option<String>,
+    /// `X-Delivery-*` headers to send with the request. `X-Delivery-Id`
+    /// stays the same across retries and replays of an event.
+    pub delivery_headers: BTreeMap<String, String>,
     pub lease_expires_at: String,
     pub circuit: Option<TargetCircuitState>,
+    /// How long a single delivery request may take before the worker
+    /// abandons it and reports a timeout.
+    pub request_timeout_ms: i64,
+    /// Which redirects to follow. Report the URL of the final response as
+    /// the attempt's `final_url`.
+    pub redirect_policy: RedirectPolicy,
+    /// Which addresses to connect to and when to race the other family.
+    /// Report the address connected to as the attempt's `peer_address`.
+    pub connect_policy: ConnectPolicy,
+    /// Milliseconds left of the endpoint's delivery budget, counted from
+    /// the event's first attempt. A retry reported once it reaches zero
+    /// marks the event dead. `None` when the endpoint has no budget.
+    pub delivery_budget_remaining_ms: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -26,9 +61,25 @@ pub struct LeaseResponse {
     pub events: Vec<LeasedEvent>,
 }
 
+/// Extends the caller's lease on `event_id` to `lease_ms` from now.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct RenewRequest {
+    pub worker_id: String,
+    pub event_id: Uuid,
+    pub lease_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct RenewResponse {
+    pub lease_expires_at: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct ReportRequest {
     pub worker_id: String,
+    /// Build of the worker, e.g. its release tag or commit.
+    pub worker_version: Option<String>,
+    pub worker_region: Option<String>,
     pub event_id: Uuid,
     pub outcome: ReportOutcome,
     pub retryable: bool,
@@ -50,6 +101,30 @@ pub struct ReportAttempt {
 
     pub error_kind: Option<WebhookAttemptErrorKind>,
     pub error_message: Option<String>,
+
+    /// URL that produced the final response once redirects were followed.
+    pub final_url: Option<String>,
+
+    /// Address the request was sent to, e.g. `[2001:db8::1]:443`.
+    pub peer_address: Option<String>,
+
+    /// Phase timings of the request, when the worker measured them.
+    pub timing: Option<AttemptTiming>,
+}
+
+/// Result of a shadow delivery. Only the timing, response status and body
+/// and error fields of `attempt` are kept.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ShadowReportRequest {
+    pub worker_id: String,
+    pub event_id: Uuid,
+    pub target_url: String,
+    pub attempt: ReportAttempt,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ShadowReportResponse {
+    pub attempt_id: Uuid,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
@@ -60,8 +135,133 @@ pub enum ReportOutcome {
     Dead,
 }
 
+/// Endpoint and event context after a report, for worker-side logging and
+/// local throttling.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointStats {
+    pub consecutive_failures: i64,
+    /// Attempts left for this event before it is marked dead; 0 once the
+    /// event is delivered or dead.
+    pub attempts_remaining: i64,
+    /// Time until the endpoint's open circuit allows leases again.
+    pub circuit_cooldown_remaining_ms: Option<i64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct ReportResponse {
     pub circuit: Option<TargetCircuitState>,
     pub final_outcome: ReportOutcome,
+    pub endpoint_stats: EndpointStats,
+}
+
+/// Claims up to `limit` endpoints whose pre-flight check is due.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CheckLeaseRequest {
+    pub worker_id: String,
+    pub limit: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CheckLeaseResponse {
+    pub checks: Vec<EndpointCheckTarget>,
+}
+
+/// An endpoint a worker should probe with `method` against `url`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointCheckTarget {
+    pub endpoint_id: Uuid,
+    pub method: EndpointCheckMethod,
+    pub url: String,
+    pub request_timeout_ms: i64,
+}
+
+/// Result of a pre-flight probe. Leave `response_status` unset when no
+/// response arrived and describe why in `error_message`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CheckReportRequest {
+    pub worker_id: String,
+    pub endpoint_id: Uuid,
+    pub url: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub response_status: Option<i64>,
+    pub error_message: Option<String>,
+    /// `notAfter` of the certificate the endpoint presented, for HTTPS.
+    pub tls_expires_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CheckReportResponse {
+    pub check_id: Uuid,
+}
+
+/// Claims up to `limit` queued reconciliation jobs for `lease_ms`. A job
+/// whose lease runs out before its result is reported goes back to the
+/// queue.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReconcileLeaseRequest {
+    pub worker_id: String,
+    pub limit: i64,
+    pub lease_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReconcileLeaseResponse {
+    pub tasks: Vec<ReconcileTask>,
+}
+
+/// A source whose provider-side events created in `[since, until)` a
+/// worker should list through the provider's API (Stripe's events list,
+/// GitHub's hook deliveries).
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReconcileTask {
+    pub job_id: Uuid,
+    pub source_id: Uuid,
+    pub source_slug: String,
+    pub provider: String,
+    pub since: String,
+    pub until: String,
+    /// Whether to ask the provider to redeliver the events the result
+    /// reports missing.
+    pub fetch_missing: bool,
+}
+
+/// The provider event IDs a worker listed for a leased reconciliation, or
+/// `error` when listing them failed.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReconcileResultRequest {
+    pub worker_id: String,
+    pub job_id: Uuid,
+    #[serde(default)]
+    pub provider_event_ids: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReconcileResultResponse {
+    pub checked: i64,
+    /// Listed events never ingested, for the worker to have redelivered
+    /// when `fetch_missing` is set.
+    pub missing: Vec<String>,
+    pub fetch_missing: bool,
+}
+
+/// A request received by the debug echo target.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EchoDelivery {
+    pub received_at: String,
+    pub headers: BTreeMap<String, String>,
+    /// The body as text, or its base64 when `body_encoding` is `base64`.
+    pub body: String,
+    pub body_encoding: PayloadEncoding,
+    pub size_bytes: i64,
+    /// Status the echo answered with.
+    pub status: i64,
+    /// Whether the answer was an injected failure.
+    pub injected_failure: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EchoDeliveriesResponse {
+    pub deliveries: Vec<EchoDelivery>,
 }
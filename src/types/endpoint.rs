@@ -0,0 +1,549 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use uuid::Uuid;
+
+use super::{OperationFailure, TargetCircuitState};
+
+/// How an endpoint's `target_url` is delivered to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointTargetKind {
+    /// `target_url` is an HTTP(S) URL; workers POST deliveries to it.
+    Http,
+    /// `target_url` is a `mailto:` address. The receiver renders the
+    /// payload into an email and sends it itself; workers never lease
+    /// these events. An SMTP accept counts as delivered, a temporary
+    /// rejection as a retry and a permanent one as dead.
+    Email,
+}
+
+/// Which redirects a worker follows when delivering to an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RedirectMode {
+    /// A redirect response is the attempt's final response.
+    None,
+    /// Redirects are followed while they stay on the target's host.
+    SameHost,
+    Any,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct RedirectPolicy {
+    pub mode: RedirectMode,
+    /// Redirects followed at most before the attempt fails as
+    /// `too_many_redirects`. Ignored when `mode` is `none`.
+    pub max_redirects: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointRedirectPolicy {
+    pub endpoint_id: Uuid,
+    pub policy: RedirectPolicy,
+}
+
+/// Which resolved addresses a worker connects to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFamily {
+    /// Addresses in the order the resolver returned them.
+    Any,
+    Ipv4,
+    Ipv6,
+    /// Both families, IPv4 addresses first.
+    PreferIpv4,
+    /// Both families, IPv6 addresses first.
+    PreferIpv6,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct ConnectPolicy {
+    pub address_family: AddressFamily,
+    /// How long a connection to the first family gets before one to the
+    /// other family is raced against it. `None` tries the addresses one at
+    /// a time.
+    pub happy_eyeballs_delay_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointConnectPolicy {
+    pub endpoint_id: Uuid,
+    pub policy: ConnectPolicy,
+}
+
+/// When ingestion acknowledges a webhook to the provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestMode {
+    /// Persist the event before responding 200.
+    Sync,
+    /// Respond 202 immediately and persist via the in-process ingest queue.
+    FastAck,
+}
+
+/// A recurring UTC window during which an endpoint receives no deliveries.
+/// Events due inside the window are deferred until it ends.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct MaintenanceWindow {
+    /// 0 = Monday through 6 = Sunday; `None` repeats the window daily.
+    pub day_of_week: Option<i64>,
+    /// Minutes after midnight UTC at which the window opens.
+    pub start_minute: i64,
+    pub duration_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetMaintenanceWindowsRequest {
+    pub windows: Vec<MaintenanceWindow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct MaintenanceWindowsResponse {
+    pub endpoint_id: Uuid,
+    pub windows: Vec<MaintenanceWindow>,
+}
+
+/// A recurring window during which an endpoint takes deliveries, in the
+/// endpoint's local time. Once an endpoint has any, events due outside
+/// them are deferred until the next one opens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct DeliveryWindow {
+    /// 0 = Monday through 6 = Sunday; `None` repeats the window daily.
+    pub day_of_week: Option<i64>,
+    /// Minutes after local midnight at which the window opens.
+    pub start_minute: i64,
+    pub duration_minutes: i64,
+}
+
+/// Replaces the endpoint's delivery windows; an empty list lifts them.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetDeliveryWindowsRequest {
+    /// Local time's offset from UTC, e.g. `-300` for UTC-05:00. Fixed, so
+    /// daylight saving changes need a new offset.
+    pub utc_offset_minutes: i64,
+    pub windows: Vec<DeliveryWindow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DeliveryWindowsResponse {
+    pub endpoint_id: Uuid,
+    pub utc_offset_minutes: i64,
+    pub windows: Vec<DeliveryWindow>,
+}
+
+/// A set of related endpoints (e.g. all endpoints of one customer) that can
+/// be paused, replayed, or rate-limited together.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointGroup {
+    pub id: Uuid,
+    pub name: String,
+    pub paused: bool,
+    /// Deliveries per minute across all endpoints in the group.
+    pub rate_limit_per_minute: Option<i64>,
+    pub created_at: String,
+}
+
+/// Ingest quotas of a group and how much of them is used. Endpoint groups
+/// stand in for tenants.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GroupQuota {
+    pub group_id: Uuid,
+    /// Events ingested per UTC day. `None` is unlimited.
+    pub events_per_day: Option<i64>,
+    /// Payload bytes of the group's events that are not deleted. `None`
+    /// is unlimited.
+    pub storage_bytes: Option<i64>,
+    pub events_today: i64,
+    pub stored_bytes: i64,
+    /// Ingest requests refused with a 429 for either quota.
+    pub rejections_total: i64,
+    pub last_exceeded_at: Option<String>,
+    /// Whether a request was refused today (UTC).
+    pub exceeded_today: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetGroupQuotaRequest {
+    pub events_per_day: Option<i64>,
+    pub storage_bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CreateEndpointGroupRequest {
+    pub name: String,
+    pub rate_limit_per_minute: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ListEndpointGroupsResponse {
+    pub groups: Vec<EndpointGroup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetEndpointGroupRequest {
+    /// `None` removes the endpoint from its group.
+    pub group_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointGroupAssignment {
+    pub endpoint_id: Uuid,
+    pub group_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetGroupRateLimitRequest {
+    /// `None` lifts the limit.
+    pub rate_limit_per_minute: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Default)]
+pub struct ReplayGroupRequest {
+    pub reset_circuit: Option<bool>,
+    /// Skip events that already have a pending or in-flight replay.
+    pub skip_if_pending: Option<bool>,
+    /// Staggers the new events' `next_attempt_at` so that only this many
+    /// become due per minute. `None` makes them all due at once.
+    pub drip_rate_per_minute: Option<i64>,
+    /// Count the matching dead events and issue a `confirm_token` without
+    /// replaying anything.
+    pub dry_run: Option<bool>,
+    /// Token from a dry run of the same request. Required when more dead
+    /// events match than the replay confirmation threshold.
+    pub confirm_token: Option<String>,
+    /// Run the replay as a background job and answer right away.
+    pub background: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReplayGroupResponse {
+    /// IDs of the new events created by replaying the group's dead events.
+    pub replayed_event_ids: Vec<Uuid>,
+    /// Dead events the replay covers.
+    pub matched: i64,
+    /// Set on dry runs; pass it back as `confirm_token` before
+    /// `confirm_expires_at` to run the replay.
+    pub confirm_token: Option<String>,
+    pub confirm_expires_at: Option<String>,
+    /// Dead events left alone because their replay chain already hit the
+    /// loop limit.
+    pub failures: Vec<OperationFailure>,
+    /// Operation tracking the replay; `None` on dry runs.
+    pub operation_id: Option<Uuid>,
+    /// Job running a background replay. Its events show on the operation
+    /// as they are created.
+    pub job_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CloseCircuitResponse {
+    pub endpoint_id: Uuid,
+    /// IDs of the new events created by replaying dead events from the
+    /// requested outage window.
+    pub replayed_event_ids: Vec<Uuid>,
+    /// Dead events from the window left alone because their replay chain
+    /// already hit the loop limit.
+    pub failures: Vec<OperationFailure>,
+    /// Operation tracking the outage window replay, if one was requested.
+    pub operation_id: Option<Uuid>,
+}
+
+/// Simulated failures applied to reports for an endpoint. Only honoured
+/// when the receiver runs with `RECEIVER_FAULT_INJECTION_ENABLED`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct FaultInjection {
+    pub endpoint_id: Uuid,
+    /// Probability in `[0, 1]` that a delivered report is turned into a
+    /// retryable failure.
+    pub failure_rate: f64,
+    /// Delay added before each report for the endpoint is processed.
+    pub latency_ms: i64,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetFaultInjectionRequest {
+    pub failure_rate: f64,
+    pub latency_ms: i64,
+}
+
+/// One state of an endpoint's delivery settings: its target, timeouts,
+/// redirect, connection and region settings. Revisions count up from 1;
+/// `changed_by` is `None` for settings changed outside the inspector.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointRevision {
+    pub endpoint_id: Uuid,
+    pub revision: i64,
+    pub target_url: String,
+    pub changed_by: Option<String>,
+    pub created_at: String,
+    /// Differences from the previous revision, by field name. Revisions
+    /// recorded before settings were tracked only compare `target_url`.
+    pub changes: Vec<EndpointRevisionChange>,
+}
+
+/// A setting that changed between two revisions. `None` is unset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct EndpointRevisionChange {
+    pub field: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetEndpointTargetRequest {
+    pub target_url: String,
+}
+
+/// Canary routing: `canary_percent` of the endpoint's events go to
+/// `canary_target_url` instead of its target. Events are assigned by ID, so
+/// retries stay on the same side of the split.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointCanary {
+    pub endpoint_id: Uuid,
+    pub canary_target_url: Option<String>,
+    pub canary_percent: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetEndpointCanaryRequest {
+    /// `None` turns canary routing off.
+    pub canary_target_url: Option<String>,
+    /// Between 0 and 100.
+    pub canary_percent: i64,
+}
+
+/// Operator-facing label of an endpoint, returned with its events.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointProfile {
+    pub endpoint_id: Uuid,
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetEndpointProfileRequest {
+    /// `None` clears the name.
+    pub name: Option<String>,
+    /// `None` clears the description.
+    pub description: Option<String>,
+}
+
+/// Shadow delivery: workers also send each delivery to
+/// `shadow_target_url` and log the result without affecting the event.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointShadow {
+    pub endpoint_id: Uuid,
+    pub shadow_target_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetEndpointShadowRequest {
+    /// `None` turns shadow delivery off.
+    pub shadow_target_url: Option<String>,
+}
+
+/// Secrets that verify inbound webhooks for an endpoint's sources, as
+/// secret IDs (`<scheme>:<key>`). A signature is accepted if it verifies
+/// against either secret before its expiry. Without a primary secret the
+/// sources' plaintext secrets are used instead.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointSecrets {
+    pub endpoint_id: Uuid,
+    pub primary_secret_id: Option<String>,
+    pub primary_expires_at: Option<String>,
+    pub secondary_secret_id: Option<String>,
+    pub secondary_expires_at: Option<String>,
+}
+
+/// Makes `secret_id` the primary secret and demotes the current primary
+/// to secondary, replacing any previous secondary.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct RotateEndpointSecretRequest {
+    pub secret_id: String,
+    /// `None` keeps the new primary valid until it is rotated out.
+    pub expires_at: Option<String>,
+    /// When the demoted primary stops verifying. Defaults to 24 hours
+    /// from now.
+    pub previous_expires_at: Option<String>,
+}
+
+/// Delivery SLO: `objective_percent` of events should be delivered within
+/// `target_seconds` of receipt. Both are `None` when no SLO is set.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointSlo {
+    pub endpoint_id: Uuid,
+    pub target_seconds: Option<i64>,
+    pub objective_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetEndpointSloRequest {
+    /// `None` removes the SLO; `objective_percent` must then be `None` too.
+    pub target_seconds: Option<i64>,
+    pub objective_percent: Option<f64>,
+}
+
+/// 1-in-N logging of successful attempts for high-volume endpoints.
+/// Failed attempts are always logged. `None` logs every attempt.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointAttemptLogSampling {
+    pub endpoint_id: Uuid,
+    pub success_sample_rate: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetEndpointAttemptLogSamplingRequest {
+    /// Log one in this many successful attempts; `None` or 1 logs them all.
+    pub success_sample_rate: Option<i64>,
+}
+
+/// Delivery time limits for an endpoint. `None` means no limit.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointTimeouts {
+    pub endpoint_id: Uuid,
+    /// Per-attempt request timeout handed to workers with each lease.
+    /// `None` uses the receiver's default.
+    pub request_timeout_ms: Option<i64>,
+    /// Wall-clock budget across all attempts of an event, counted from its
+    /// first attempt. A retry reported after it runs out marks the event
+    /// dead even if attempts remain.
+    pub delivery_budget_seconds: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetEndpointTimeoutsRequest {
+    pub request_timeout_ms: Option<i64>,
+    pub delivery_budget_seconds: Option<i64>,
+}
+
+/// How long a failed delivery waits before its retry. `Exponential` waits
+/// `base_secs * 2^(n-1)` after attempt `n` and `Linear` waits
+/// `step_secs * n`, both capped at `max_secs`. `Intervals` lists the waits
+/// in order, e.g. `"1m,5m,30m,2h"`, and repeats the last one once the list
+/// runs out. `Cron` retries at the next firing of a five-field cron
+/// expression in UTC, e.g. `"0 * * * *"` for the top of each hour.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BackoffStrategy {
+    Exponential { base_secs: i64, max_secs: i64 },
+    Linear { step_secs: i64, max_secs: i64 },
+    Intervals { intervals: String },
+    Cron { expression: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointBackoff {
+    pub endpoint_id: Uuid,
+    /// `None` uses the default exponential backoff: doubling from one
+    /// second, capped at an hour.
+    pub strategy: Option<BackoffStrategy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetEndpointBackoffRequest {
+    /// `None` restores the default backoff.
+    pub strategy: Option<BackoffStrategy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointRevisionsResponse {
+    pub endpoint_id: Uuid,
+    /// Newest first.
+    pub revisions: Vec<EndpointRevision>,
+}
+
+/// Request a worker sends when probing an endpoint before real events
+/// reach it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointCheckMethod {
+    Head,
+    Options,
+    Get,
+}
+
+/// One pre-flight probe of an endpoint, as reported by a worker.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointCheck {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub worker_id: String,
+    pub url: String,
+    pub checked_at: String,
+    /// Whether the endpoint answered at all. Any HTTP status counts, since
+    /// many receivers reject HEAD or OPTIONS but still prove the route.
+    pub reachable: bool,
+    pub response_status: Option<i64>,
+    pub latency_ms: i64,
+    pub error_message: Option<String>,
+    /// Expiry of the certificate presented, for HTTPS URLs.
+    pub tls_expires_at: Option<String>,
+}
+
+/// What is known about an endpoint's health ahead of and during delivery.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointHealth {
+    pub endpoint_id: Uuid,
+    pub check_method: EndpointCheckMethod,
+    /// Probed instead of `target_url` when set.
+    pub check_url: Option<String>,
+    pub circuit: Option<TargetCircuitState>,
+    pub latest_check: Option<EndpointCheck>,
+    pub last_reachable_at: Option<String>,
+    /// Expiry of the certificate seen by the latest check that reported
+    /// one.
+    pub tls_expires_at: Option<String>,
+    /// Whether `tls_expires_at` falls within the TLS warning window.
+    pub tls_expiring: bool,
+}
+
+/// How strictly an endpoint's events stay with workers of its region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionMode {
+    /// Workers of other regions lease events that have waited past the
+    /// dispatcher's region fallback delay.
+    Prefer,
+    /// Only workers of the endpoint's region lease its events.
+    Require,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointRegion {
+    pub endpoint_id: Uuid,
+    /// `None` lets workers of any region deliver.
+    pub region: Option<String>,
+    pub mode: RegionMode,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetEndpointRegionRequest {
+    pub region: Option<String>,
+    pub mode: RegionMode,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetEndpointCheckRequest {
+    pub method: EndpointCheckMethod,
+    /// `None` probes `target_url`.
+    pub url: Option<String>,
+}
+
+/// Certificate expiry from an endpoint's latest check that reported one.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct TlsExpiry {
+    pub endpoint_id: Uuid,
+    pub url: String,
+    pub tls_expires_at: String,
+    pub checked_at: String,
+    /// Whole days left, negative once the certificate has expired.
+    pub days_remaining: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct TlsExpiryResponse {
+    pub within_days: i64,
+    /// Soonest expiry first.
+    pub endpoints: Vec<TlsExpiry>,
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use uuid::Uuid;
+
+use super::WebhookEventStatus;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct IngestResponse {
+    pub event_id: Uuid,
+}
+
+/// Delivery state of a provider event, for the system that sent it. When
+/// the provider event was ingested more than once, or replayed, `status`
+/// and `event_id` are those of the latest copy and `delivered` holds if
+/// any copy was delivered.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DeliveryReceipt {
+    pub provider_event_id: String,
+    pub event_id: Uuid,
+    pub status: WebhookEventStatus,
+    pub delivered: bool,
+    /// When the first delivered copy finished its successful attempt.
+    pub delivered_at: Option<String>,
+    /// Delivery attempts across all copies.
+    pub attempts: i64,
+    /// When the provider event was first received.
+    pub received_at: String,
+    pub last_attempt_at: Option<String>,
+}
+
+/// Ingestion figures for one provider over the stats window. Counters are
+/// kept per hour, so the window is widened to whole hours. Requests over
+/// the HTTP body limit are rejected before the source is known and are
+/// not counted.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ProviderIngestStats {
+    pub provider: String,
+    pub accepted: i64,
+    pub rejected_signature: i64,
+    /// Bodies that exceeded the decompressed size limit.
+    pub rejected_size: i64,
+    pub rejected_schema: i64,
+    /// Nearest-rank percentiles of stored payload sizes of ingested (not
+    /// replayed) events, after scrubbing. `None` without events.
+    pub payload_bytes_p50: Option<i64>,
+    pub payload_bytes_p95: Option<i64>,
+    pub payload_bytes_p99: Option<i64>,
+    pub payload_bytes_max: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestHealthStatus {
+    Ok,
+    /// A backlog threshold is crossed and ingestion answers 429.
+    Backpressure,
+}
+
+/// Backlog against the ingest backpressure thresholds. `None` thresholds
+/// are disabled.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct IngestHealth {
+    pub status: IngestHealthStatus,
+    /// Events that are pending, in flight, or requeued.
+    pub pending_events: i64,
+    pub max_pending_events: Option<i64>,
+    pub db_bytes: i64,
+    pub max_db_bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ProviderStatsResponse {
+    pub window_minutes: i64,
+    pub providers: Vec<ProviderIngestStats>,
+}
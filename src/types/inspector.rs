@@ -1,7 +1,14 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::types::{TargetCircuitState, WebhookAttemptLog, WebhookEvent, WebhookEventStatus};
+use crate::types::{
+    EndpointCanary, EndpointConnectPolicy, EndpointRedirectPolicy, EndpointRegion, EndpointSecrets,
+    EndpointShadow, EndpointSlo, EndpointTimeouts, PayloadEncoding, PayloadIntegrity,
+    ShadowAttemptLog, TargetCircuitState, TargetCircuitStatus, WebhookAttemptErrorKind,
+    WebhookAttemptLog, WebhookEvent, WebhookEventStatus,
+};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -9,19 +16,68 @@ pub struct WebhookEventSummary {
     pub id: Uuid,
     pub endpoint_id: Uuid,
     pub replayed_from_event_id: Option<Uuid>,
+    pub source_id: Option<Uuid>,
     pub provider: String,
     pub status: WebhookEventStatus,
     pub attempts: i64,
     pub received_at: String,
     pub next_attempt_at: Option<String>,
     pub last_error: Option<String>,
+    pub updated_at: Option<String>,
+    /// Set by the events list; the full payload is on `get_event`.
+    pub payload_preview: Option<PayloadPreview>,
+}
+
+/// The start of an event's payload and what it looks like, so lists can
+/// show a snippet without shipping the whole body.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct PayloadPreview {
+    /// Up to the first 200 characters of the payload, or the base64 of its
+    /// first 200 bytes when `encoding` is `base64`.
+    pub snippet: String,
+    pub encoding: PayloadEncoding,
+    /// Whether the payload continues past `snippet`.
+    pub truncated: bool,
+    /// Whether the whole payload parses as JSON.
+    pub is_json: bool,
+    /// Media type from the stored `content-type` header, or sniffed from
+    /// the payload when there is none.
+    pub content_type: String,
+    pub size_bytes: i64,
+}
+
+/// An event whose extracted metadata references the searched customer.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CustomerEventMatch {
+    pub event: WebhookEventSummary,
+    /// The metadata field that matched, e.g. `customer_id`.
+    pub matched_field: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CustomerSearchResponse {
+    pub customer_ref: String,
+    pub events: Vec<CustomerEventMatch>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct WebhookEventListItem {
     pub event: WebhookEventSummary,
     pub target_url: String,
+    pub endpoint_name: Option<String>,
+    pub endpoint_description: Option<String>,
     pub circuit: Option<TargetCircuitState>,
+    /// Set only when the list was asked to `include_last_attempt` and the
+    /// event has been attempted.
+    pub last_attempt: Option<LastAttemptSummary>,
+}
+
+/// The outcome of an event's most recent delivery attempt.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct LastAttemptSummary {
+    pub response_status: Option<i64>,
+    pub error_kind: Option<WebhookAttemptErrorKind>,
+    pub finished_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -30,21 +86,165 @@ pub struct ListEventsResponse {
     pub next_before: Option<String>,
 }
 
+/// SLO attainment of one endpoint over events delivered in the stats
+/// window, widened to whole hours. Delivery time is when the delivering
+/// attempt finished.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SloAttainment {
+    pub endpoint_id: Uuid,
+    pub target_seconds: i64,
+    pub objective_percent: f64,
+    pub delivered: i64,
+    pub delivered_within_target: i64,
+    /// Undelivered events already older than the target.
+    pub overdue: i64,
+    /// `None` when nothing was delivered in the window.
+    pub attainment_percent: Option<f64>,
+    /// Whether attainment meets the objective; `true` with no deliveries.
+    pub met: bool,
+    /// Late deliveries since the SLO was first set, across all time.
+    pub breaches_total: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SloStatsResponse {
+    pub window_minutes: i64,
+    pub endpoints: Vec<SloAttainment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyMetric {
+    /// Share of attempts that failed, from 0 to 1.
+    FailureRate,
+    /// Mean attempt duration in milliseconds.
+    LatencyMs,
+}
+
+/// An endpoint metric whose value in the current bucket sits far above
+/// its baseline.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EndpointAnomaly {
+    pub endpoint_id: Uuid,
+    pub metric: AnomalyMetric,
+    /// Attempts in the current bucket.
+    pub attempts: i64,
+    pub current: f64,
+    pub baseline_mean: f64,
+    /// Deviation used for the z-score, after the detector's floor.
+    pub baseline_stddev: f64,
+    pub z_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AnomaliesResponse {
+    pub bucket_minutes: i64,
+    pub baseline_buckets: i64,
+    pub z_threshold: f64,
+    pub anomalies: Vec<EndpointAnomaly>,
+}
+
+/// A field of `WebhookEventListItem` that can be requested through the
+/// `fields` parameter of the events list. Every variant but `TargetUrl`,
+/// `EndpointName`, `EndpointDescription` and `Circuit` names a field of
+/// the nested `event` summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum EventListField {
+    Id,
+    EndpointId,
+    ReplayedFromEventId,
+    SourceId,
+    Provider,
+    Status,
+    Attempts,
+    ReceivedAt,
+    NextAttemptAt,
+    LastError,
+    UpdatedAt,
+    PayloadPreview,
+    TargetUrl,
+    EndpointName,
+    EndpointDescription,
+    Circuit,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct GetEventResponse {
     pub event: WebhookEvent,
+    pub payload_integrity: PayloadIntegrity,
+    /// Inbound headers that were received but not stored, e.g. the
+    /// `authorization` header a provider authenticated with.
+    pub dropped_headers: Vec<String>,
+    /// Provider fields extracted at ingestion, e.g. `account_id` for
+    /// Stripe or `repository` for GitHub.
+    pub metadata: Option<BTreeMap<String, String>>,
     pub target_url: String,
+    pub endpoint_name: Option<String>,
+    pub endpoint_description: Option<String>,
     pub circuit: Option<TargetCircuitState>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct ListAttemptsResponse {
     pub attempts: Vec<WebhookAttemptLog>,
+    /// Successful attempts left out of `attempts` by the endpoint's log
+    /// sampling, and the 1-in-N rate they were dropped at.
+    pub unlogged_attempts: i64,
+    pub unlogged_sample_rate: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ListShadowAttemptsResponse {
+    pub attempts: Vec<ShadowAttemptLog>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type, Default)]
 pub struct ReplayEventRequest {
     pub reset_circuit: Option<bool>,
+    /// Refuse with a conflict when a pending or in-flight replay of the
+    /// event already exists.
+    pub skip_if_pending: Option<bool>,
+    /// Defaults to `reset`.
+    pub attempt_budget: Option<ReplayAttemptBudget>,
+    /// Replay even when the event's chain looks like a replay loop.
+    pub force: Option<bool>,
+}
+
+/// Whether a replay starts with a fresh `max_attempts` budget or carries
+/// over the attempts its source already used, so a chain of replays
+/// shares one budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayAttemptBudget {
+    #[default]
+    Reset,
+    CarryOver,
+}
+
+/// One event of a replay chain.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct LineageEvent {
+    pub id: Uuid,
+    pub replayed_from_event_id: Option<Uuid>,
+    /// Replays between this event and the root, which has depth 0.
+    pub depth: i64,
+    pub status: WebhookEventStatus,
+    /// The event's attempt counter, including attempts carried over from
+    /// its source.
+    pub attempts: i64,
+    /// Attempts logged for this event itself.
+    pub attempts_made: i64,
+    pub updated_at: Option<String>,
+}
+
+/// Every event replayed from the same original, root first, and the
+/// delivery attempts made across all of them.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EventLineageResponse {
+    pub root_event_id: Uuid,
+    pub events: Vec<LineageEvent>,
+    pub cumulative_attempts: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -52,3 +252,507 @@ pub struct ReplayEventResponse {
     pub event: WebhookEventSummary,
     pub circuit: Option<TargetCircuitState>,
 }
+
+/// Queued events to cancel, chosen like the dead events of an outage
+/// replay: exactly one of `endpoint_id` and `group_id`, optionally
+/// narrowed to a `received_at` window. Events received after the request
+/// are left queued.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Default)]
+pub struct CancelEventsRequest {
+    pub endpoint_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub received_since: Option<String>,
+    pub received_before: Option<String>,
+}
+
+/// Finished events (delivered, dead, expired or cancelled) to erase as a
+/// hard delete does, chosen like the queued events of a bulk cancel.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Default)]
+pub struct PurgeEventsRequest {
+    pub endpoint_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub received_since: Option<String>,
+    pub received_before: Option<String>,
+}
+
+/// Queued events to move to `priority`, chosen like those of a bulk
+/// cancel and optionally narrowed by provider and by `event_type`, a glob
+/// such as `invoice.*`. Higher priorities are leased first.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Default)]
+pub struct ReprioritizeEventsRequest {
+    pub endpoint_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub provider: Option<String>,
+    pub event_type: Option<String>,
+    pub received_since: Option<String>,
+    pub received_before: Option<String>,
+    /// Between -1000 and 1000; events start at 0.
+    pub priority: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Default)]
+pub struct ShareEventRequest {
+    /// Link lifetime; defaults to 24 hours, at most 7 days.
+    pub ttl_seconds: Option<i64>,
+}
+
+/// Unauthenticated read-only link to one event and its attempts.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ShareEventResponse {
+    pub url: String,
+    pub expires_at: String,
+}
+
+/// A change of an endpoint circuit to `state`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CircuitTransition {
+    pub state: TargetCircuitStatus,
+    pub open_until: Option<String>,
+    pub consecutive_failures: i64,
+    pub changed_at: String,
+}
+
+/// Endpoint configuration as of a support bundle. Secret IDs are masked,
+/// so only whether a secret is set shows.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct BundleEndpoint {
+    pub endpoint_id: Uuid,
+    pub target_url: String,
+    pub group_id: Option<Uuid>,
+    pub secrets: EndpointSecrets,
+    pub timeouts: EndpointTimeouts,
+    pub redirects: EndpointRedirectPolicy,
+    pub connect: EndpointConnectPolicy,
+    pub region: EndpointRegion,
+    pub slo: EndpointSlo,
+    pub canary: EndpointCanary,
+    pub shadow: EndpointShadow,
+}
+
+/// Everything known about one event, for attaching to a support ticket.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EventBundle {
+    pub generated_at: String,
+    pub event: WebhookEvent,
+    pub attempts: Vec<WebhookAttemptLog>,
+    pub circuit: Option<TargetCircuitState>,
+    /// Oldest first.
+    pub circuit_history: Vec<CircuitTransition>,
+    pub endpoint: BundleEndpoint,
+}
+
+/// An [`EventBundle`] and the hex HMAC-SHA256 of its JSON with object keys
+/// sorted, keyed with the bundle signing key.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SignedEventBundle {
+    pub bundle: EventBundle,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct VerifyBundleResponse {
+    pub valid: bool,
+}
+
+/// Body format of the events export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// One JSON `EventExportRecord` per line.
+    #[default]
+    Ndjson,
+    /// A header row, then one row per event with the record's fields as
+    /// columns.
+    Csv,
+}
+
+/// Progress of the nightly export. Every day up to `last_exported_day`
+/// has been written; later days are retried on each check until they
+/// succeed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct ScheduledExportStatus {
+    /// `YYYY-MM-DD`, UTC.
+    pub last_exported_day: Option<String>,
+    pub last_success_at: Option<String>,
+    /// The most recent failure, kept after later successes for reference.
+    pub last_error: Option<String>,
+    pub last_error_at: Option<String>,
+    /// Failed checks since the last successful export.
+    pub consecutive_failures: i64,
+}
+
+/// One event flattened for export, with figures from its latest attempt
+/// and totals across all attempts.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EventExportRecord {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub provider: String,
+    pub provider_event_id: Option<String>,
+    pub event_type: Option<String>,
+    pub status: WebhookEventStatus,
+    pub attempts: i64,
+    pub received_at: String,
+    pub updated_at: Option<String>,
+    pub last_attempt_at: Option<String>,
+    pub last_response_status: Option<i64>,
+    pub last_duration_ms: Option<i64>,
+    /// Time spent across all logged attempts.
+    pub total_duration_ms: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// A recorded attempt request as a ready-to-run curl command.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AttemptCurlResponse {
+    pub attempt_id: Uuid,
+    pub event_id: Uuid,
+    pub target_url: String,
+    pub command: String,
+    /// Headers whose values were replaced with `[REDACTED]`.
+    pub masked_headers: Vec<String>,
+}
+
+/// Provider-side event IDs (e.g. from Stripe's events list or GitHub's
+/// deliveries API) to check against what this receiver has ingested.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReconcileRequest {
+    pub provider: String,
+    pub provider_event_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DeleteEventResponse {
+    pub event_id: Uuid,
+    pub deleted_at: String,
+    /// Set once payload, headers, and attempt bodies have been scrubbed.
+    pub erased_at: Option<String>,
+}
+
+/// Failed attempts in one (endpoint, error kind, status class) bucket.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ErrorSummaryBucket {
+    pub endpoint_id: Uuid,
+    pub target_url: String,
+    pub error_kind: Option<WebhookAttemptErrorKind>,
+    /// Response status class such as `"5xx"`; `None` when no response
+    /// was received.
+    pub status_class: Option<String>,
+    pub count: i64,
+    pub last_seen_at: String,
+}
+
+/// Usage of one endpoint on one UTC day. `group_id` is the endpoint's
+/// current group, so moving an endpoint moves its past usage too.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct UsageRollup {
+    pub endpoint_id: Uuid,
+    pub group_id: Option<Uuid>,
+    /// UTC date, e.g. `2024-01-31`.
+    pub day: String,
+    pub events_ingested: i64,
+    pub deliveries_attempted: i64,
+    /// Payload bytes of ingested events plus request and response body
+    /// bytes kept on attempt logs.
+    pub bytes_stored: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct UsageResponse {
+    pub from: String,
+    pub to: String,
+    pub rollups: Vec<UsageRollup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ErrorSummaryResponse {
+    pub since: String,
+    pub total: i64,
+    pub buckets: Vec<ErrorSummaryBucket>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DispatcherSettings {
+    pub circuit_failure_threshold: u32,
+    pub circuit_cooldown_base_ms: u64,
+    pub circuit_cooldown_factor: f64,
+    pub circuit_cooldown_max_ms: u64,
+    pub max_attempts: u32,
+    pub expiry_sweep_interval_ms: u64,
+    pub starvation_max_wait_ms: Option<u64>,
+    pub success_response_sample_percent: u8,
+    pub default_request_timeout_ms: i64,
+    pub endpoint_check_interval_ms: u64,
+    pub tls_expiry_warning_days: i64,
+    pub region_fallback_ms: u64,
+    pub replay_confirm_threshold: Option<i64>,
+    pub replay_confirm_ttl_minutes: i64,
+    pub replay_loop_limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct IngestSettings {
+    pub queue_capacity: usize,
+    pub journal_path: Option<String>,
+    pub max_pending_events: Option<i64>,
+    pub max_db_bytes: Option<i64>,
+    pub backpressure_retry_after_secs: u64,
+    pub deterministic_event_ids: bool,
+    pub header_allowlist: Option<Vec<String>>,
+    pub max_body_bytes: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct RetentionSettings {
+    pub attempt_log_hot_days: i64,
+    pub attempt_log_archive_interval_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct FeatureFlags {
+    pub fault_injection: bool,
+    pub share_links: bool,
+    pub bundle_signing: bool,
+    pub audit_reads: bool,
+    /// Fast-ack ingestion through the in-memory queue.
+    pub ingest_queue: bool,
+    pub background_exports: bool,
+}
+
+/// Credentials the deployment was started with. Set values read
+/// `[REDACTED]`, unset ones are `None`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SecretSettings {
+    pub inspector_api_token: Option<String>,
+    pub share_link_secret: Option<String>,
+    pub share_link_base_url: Option<String>,
+    pub bundle_signing_key: Option<String>,
+    /// Endpoints covered by each scoped inspector token, in configured
+    /// order.
+    pub scoped_token_endpoints: Vec<Vec<Uuid>>,
+}
+
+/// One attempt of a simulated retry schedule.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct BackoffStep {
+    pub attempt_no: i64,
+    /// Since the first attempt started.
+    pub starts_after_ms: i64,
+    /// Wait before the next attempt; `None` when this attempt is the last
+    /// and its failure makes the event dead.
+    pub retry_delay_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SimulateBackoffResponse {
+    pub max_attempts: u32,
+    pub steps: Vec<BackoffStep>,
+}
+
+/// An endpoint circuit after a run of consecutive failures.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CircuitStep {
+    pub consecutive_failures: i64,
+    pub open: bool,
+    /// How long the circuit stays open; `None` while it is closed.
+    pub cooldown_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SimulateCircuitResponse {
+    pub circuit_failure_threshold: u32,
+    pub steps: Vec<CircuitStep>,
+}
+
+/// Effective configuration of the running process, after environment
+/// overrides and clamping.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct RuntimeConfigResponse {
+    pub dispatcher: DispatcherSettings,
+    pub ingest: IngestSettings,
+    pub retention: RetentionSettings,
+    pub features: FeatureFlags,
+    pub secrets: SecretSettings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    /// Dead events of a group replayed through the group replay API.
+    GroupReplay,
+    /// Dead events from an outage window replayed when a group resumed or
+    /// a circuit was closed.
+    OutageReplay,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus {
+    Running,
+    Completed,
+    /// Stopped part way; see `error`. Events created until then stay.
+    Failed,
+    /// Stopped early because its job was cancelled.
+    Cancelled,
+}
+
+/// A bulk inspector operation and the events it created.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct InspectorOperation {
+    pub id: Uuid,
+    pub kind: OperationKind,
+    pub status: OperationStatus,
+    pub group_id: Option<Uuid>,
+    pub endpoint_id: Option<Uuid>,
+    /// Who started the operation.
+    pub actor: String,
+    /// Dead events the operation covers.
+    pub total: i64,
+    /// Dead events handled so far, replayed or skipped.
+    pub processed: i64,
+    pub event_ids: Vec<Uuid>,
+    /// Dead events the operation did not replay, such as ones whose
+    /// replay chain hit the loop limit.
+    pub failures: Vec<OperationFailure>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+    pub undone_at: Option<String>,
+    pub undone_by: Option<String>,
+}
+
+/// A dead event a bulk replay skipped. `reason` is the replay conflict,
+/// e.g. `replay_loop_detected`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct OperationFailure {
+    pub event_id: Uuid,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ListOperationsResponse {
+    /// Newest first.
+    pub operations: Vec<InspectorOperation>,
+}
+
+/// Result of undoing an operation. Events still waiting for delivery are
+/// cancelled; ones already leased or finished are left alone.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct UndoOperationResponse {
+    pub operation: InspectorOperation,
+    pub cancelled_event_ids: Vec<Uuid>,
+    pub skipped_event_ids: Vec<Uuid>,
+}
+
+/// Global dispatch kill switch. While `paused` is set, `lease_events`
+/// hands out nothing; events keep accumulating and in-flight leases
+/// expire back to `requeued` as usual.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DispatchControlResponse {
+    pub paused: bool,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum DoctorIssueKind {
+    /// `in_flight` without `lease_expires_at` or `leased_by`; the lease
+    /// can never expire, so the event would never be retried.
+    InFlightWithoutLease,
+    /// `delivered` but still scheduled for a future attempt.
+    DeliveredWithNextAttempt,
+    /// `attempts` differs from the number of attempt log rows.
+    AttemptCountMismatch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DoctorIssue {
+    pub event_id: Uuid,
+    pub kind: DoctorIssueKind,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DoctorReport {
+    pub checked_at: String,
+    /// Whether the listed issues were repaired or only reported.
+    pub repaired: bool,
+    pub issues: Vec<DoctorIssue>,
+}
+
+/// Size of the SQLite database and where it goes.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct StorageReport {
+    pub generated_at: String,
+    /// Pages in use and free, as SQLite counts them.
+    pub db_bytes: i64,
+    /// Free pages, reused before the file grows.
+    pub free_bytes: i64,
+    /// Sizes on disk. `None` for in-memory databases or when the file
+    /// does not exist, e.g. no `-wal` file outside WAL mode.
+    pub file_bytes: Option<i64>,
+    pub wal_bytes: Option<i64>,
+    pub journal_mode: String,
+    /// Largest first.
+    pub tables: Vec<TableStorage>,
+    pub projection: StorageProjection,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct TableStorage {
+    pub name: String,
+    pub rows: i64,
+    /// Pages of the table and its indexes.
+    pub bytes: i64,
+}
+
+/// Where the retention policy takes storage. Attempt logs are archived,
+/// not deleted, so the database only grows; growth is the average of the
+/// bytes stored per day in usage rollups, which leaves out headers and
+/// indexes.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct StorageProjection {
+    pub attempt_log_hot_days: i64,
+    /// Hot attempt logs older than `attempt_log_hot_days`, moved to the
+    /// archive on the archiver's next run.
+    pub attempt_logs_due_for_archive: i64,
+    pub growth_window_days: i64,
+    pub daily_growth_bytes: i64,
+    pub projected_db_bytes_30d: i64,
+    /// Ingest backpressure limit. `None` when unset.
+    pub max_db_bytes: Option<i64>,
+    /// Days of growth left before `max_db_bytes` is reached. `None`
+    /// without a limit or without growth.
+    pub days_until_full: Option<i64>,
+}
+
+/// One stage of a self-test run, in pipeline order.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SelftestStep {
+    /// `setup`, `ingest`, `lease`, `deliver`, `report`, `verify` or
+    /// `cleanup`.
+    pub name: String,
+    pub passed: bool,
+    pub elapsed_ms: i64,
+    /// What went wrong, or what was observed when the step passed.
+    pub detail: String,
+}
+
+/// Outcome of pushing one event through ingest, lease, delivery and
+/// report against a temporary endpoint. Steps after the first failure are
+/// skipped, except cleanup.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SelftestReport {
+    pub passed: bool,
+    pub started_at: String,
+    pub elapsed_ms: i64,
+    pub endpoint_id: Uuid,
+    pub event_id: Uuid,
+    pub steps: Vec<SelftestStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReconcileResponse {
+    pub checked: i64,
+    pub missing: Vec<String>,
+}
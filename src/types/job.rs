@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    GroupReplay,
+    Export,
+    Backfill,
+    BulkCancel,
+    Reprioritize,
+    /// Erases finished events, as a hard delete does.
+    Purge,
+    /// Restores a snapshot uploaded to the job directory.
+    Import,
+    /// Run by a worker: compares a source's provider-side events against
+    /// what was ingested.
+    Reconcile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    /// Stopped on an error, or interrupted by a restart.
+    Failed,
+    /// Stopped early on request. Work done until then is kept.
+    Cancelled,
+}
+
+/// An admin task running in the background instead of inside the request
+/// that started it.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// Who started the job.
+    pub actor: String,
+    /// Operation recording the events a replay job created.
+    pub operation_id: Option<Uuid>,
+    /// Items the job covers: dead events for replays, events for exports,
+    /// queued events for bulk cancels and re-prioritizations, finished
+    /// events for purges, snapshot records for imports, and provider-side
+    /// events for reconciliations.
+    pub total: i64,
+    pub processed: i64,
+    /// File written by an export job, served by the job output route.
+    pub output_path: Option<String>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub cancel_requested_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ListJobsResponse {
+    /// Newest first.
+    pub jobs: Vec<Job>,
+}
+
+/// Starts a reconciliation of `source_id` over the provider events created
+/// in `[since, until)`. With `fetch_missing`, the worker also asks the
+/// provider to redeliver the events this receiver never ingested.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct StartReconcileRequest {
+    pub source_id: Uuid,
+    pub since: String,
+    pub until: String,
+    #[serde(default)]
+    pub fetch_missing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReconcileMissingEvent {
+    pub provider_event_id: String,
+    /// Set once the event has been ingested since, e.g. after the provider
+    /// redelivered it.
+    pub event_id: Option<Uuid>,
+}
+
+/// What a reconciliation job found. `missing` stays empty until a worker
+/// reports the provider's events.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ReconcileReport {
+    pub job: Job,
+    pub source_id: Uuid,
+    pub provider: String,
+    pub since: String,
+    pub until: String,
+    pub fetch_missing: bool,
+    pub missing: Vec<ReconcileMissingEvent>,
+}
+
+/// A column added by an expand migration, and how far filling it for
+/// existing rows has got.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SchemaBackfill {
+    pub name: String,
+    pub table: String,
+    pub column: String,
+    pub description: String,
+    /// Highest rowid filled so far.
+    pub cursor: i64,
+    /// Most recent job run for this backfill.
+    pub job_id: Option<Uuid>,
+    /// Set once every existing row has been visited.
+    pub completed_at: Option<String>,
+    /// Set once the contract step has run.
+    pub contracted_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ListBackfillsResponse {
+    pub backfills: Vec<SchemaBackfill>,
+}
@@ -1,6 +1,11 @@
 pub mod api_error;
 pub mod dispatcher;
+pub mod endpoint;
+pub mod ingest;
 pub mod inspector;
+pub mod job;
+pub mod payload_schema;
+pub mod scrub;
 pub mod target_circuit_state;
 pub mod webhook_attempt_log;
 pub mod webhook_event;
@@ -9,17 +14,68 @@ pub mod webhook_event;
 pub use api_error::{ApiErrorCode, ApiErrorResponse};
 #[allow(unused_imports)]
 pub use dispatcher::{
-    LeaseRequest, LeaseResponse, LeasedEvent, ReportAttempt, ReportOutcome, ReportRequest,
-    ReportResponse,
+    CheckLeaseRequest, CheckLeaseResponse, CheckReportRequest, CheckReportResponse,
+    EchoDeliveriesResponse, EchoDelivery, EndpointCheckTarget, EndpointStats, LeaseRequest,
+    LeaseResponse, LeasedEvent, ReconcileLeaseRequest, ReconcileLeaseResponse,
+    ReconcileResultRequest, ReconcileResultResponse, ReconcileTask, RenewRequest, RenewResponse,
+    ReportAttempt, ReportOutcome, ReportRequest, ReportResponse, ShadowReportRequest,
+    ShadowReportResponse,
+};
+#[allow(unused_imports)]
+pub use endpoint::{
+    AddressFamily, BackoffStrategy, CloseCircuitResponse, ConnectPolicy,
+    CreateEndpointGroupRequest, DeliveryWindow, DeliveryWindowsResponse,
+    EndpointAttemptLogSampling, EndpointBackoff, EndpointCanary, EndpointCheck,
+    EndpointCheckMethod, EndpointConnectPolicy, EndpointGroup, EndpointGroupAssignment,
+    EndpointHealth, EndpointProfile, EndpointRedirectPolicy, EndpointRegion, EndpointRevision,
+    EndpointRevisionChange, EndpointRevisionsResponse, EndpointSecrets, EndpointShadow,
+    EndpointSlo, EndpointTargetKind, EndpointTimeouts, FaultInjection, GroupQuota, IngestMode,
+    ListEndpointGroupsResponse, MaintenanceWindow, MaintenanceWindowsResponse, RedirectMode,
+    RedirectPolicy, RegionMode, ReplayGroupRequest, ReplayGroupResponse,
+    RotateEndpointSecretRequest, SetDeliveryWindowsRequest, SetEndpointAttemptLogSamplingRequest,
+    SetEndpointBackoffRequest, SetEndpointCanaryRequest, SetEndpointCheckRequest,
+    SetEndpointGroupRequest, SetEndpointProfileRequest, SetEndpointRegionRequest,
+    SetEndpointShadowRequest, SetEndpointSloRequest, SetEndpointTargetRequest,
+    SetEndpointTimeoutsRequest, SetFaultInjectionRequest, SetGroupQuotaRequest,
+    SetGroupRateLimitRequest, SetMaintenanceWindowsRequest, TlsExpiry, TlsExpiryResponse,
+};
+#[allow(unused_imports)]
+pub use ingest::{
+    DeliveryReceipt, IngestHealth, IngestHealthStatus, IngestResponse, ProviderIngestStats,
+    ProviderStatsResponse,
 };
 #[allow(unused_imports)]
 pub use inspector::{
-    GetEventResponse, ListAttemptsResponse, ListEventsResponse, ReplayEventRequest,
-    ReplayEventResponse, WebhookEventListItem, WebhookEventSummary,
+    AnomaliesResponse, AnomalyMetric, AttemptCurlResponse, BackoffStep, BundleEndpoint,
+    CancelEventsRequest, CircuitStep, CircuitTransition, CustomerEventMatch,
+    CustomerSearchResponse, DeleteEventResponse, DispatchControlResponse, DispatcherSettings,
+    DoctorIssue, DoctorIssueKind, DoctorReport, EndpointAnomaly, ErrorSummaryBucket,
+    ErrorSummaryResponse, EventBundle, EventExportRecord, EventLineageResponse, EventListField,
+    ExportFormat, FeatureFlags, GetEventResponse, IngestSettings, InspectorOperation,
+    LastAttemptSummary, LineageEvent, ListAttemptsResponse, ListEventsResponse,
+    ListOperationsResponse, ListShadowAttemptsResponse, OperationFailure, OperationKind,
+    OperationStatus, PayloadPreview, PurgeEventsRequest, ReconcileRequest, ReconcileResponse,
+    ReplayAttemptBudget, ReplayEventRequest, ReplayEventResponse, ReprioritizeEventsRequest,
+    RetentionSettings, RuntimeConfigResponse, ScheduledExportStatus, SecretSettings,
+    SelftestReport, SelftestStep, ShareEventRequest, ShareEventResponse, SignedEventBundle,
+    SimulateBackoffResponse, SimulateCircuitResponse, SloAttainment, SloStatsResponse,
+    StorageProjection, StorageReport, TableStorage, UndoOperationResponse, UsageResponse,
+    UsageRollup, VerifyBundleResponse, WebhookEventListItem, WebhookEventSummary,
 };
 #[allow(unused_imports)]
+pub use job::{
+    Job, JobKind, JobStatus, ListBackfillsResponse, ListJobsResponse, ReconcileMissingEvent,
+    ReconcileReport, SchemaBackfill, StartReconcileRequest,
+};
+#[allow(unused_imports)]
+pub use payload_schema::{PayloadSchema, SetPayloadSchemaRequest};
+#[allow(unused_imports)]
+pub use scrub::{ScrubAction, ScrubRule, ScrubRuleset, SetScrubRulesRequest};
+#[allow(unused_imports)]
 pub use target_circuit_state::{TargetCircuitState, TargetCircuitStatus};
 #[allow(unused_imports)]
-pub use webhook_attempt_log::{WebhookAttemptErrorKind, WebhookAttemptLog};
+pub use webhook_attempt_log::{
+    AttemptTiming, ResponseCapture, ShadowAttemptLog, WebhookAttemptErrorKind, WebhookAttemptLog,
+};
 #[allow(unused_imports)]
-pub use webhook_event::{WebhookEvent, WebhookEventStatus};
+pub use webhook_event::{PayloadEncoding, PayloadIntegrity, WebhookEvent, WebhookEventStatus};
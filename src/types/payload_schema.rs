@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// JSON Schema that ingested payloads of `provider`'s `event_type` are
+/// checked against. `event_type` `*` applies to every type of the provider
+/// without a schema of its own.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct PayloadSchema {
+    pub provider: String,
+    pub event_type: String,
+    /// The JSON Schema document, serialized.
+    pub schema: String,
+    /// Reject non-conforming payloads instead of only recording the errors.
+    pub reject_invalid: bool,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetPayloadSchemaRequest {
+    pub schema: String,
+    pub reject_invalid: Option<bool>,
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrubAction {
+    /// Drop the field from the payload.
+    Remove,
+    /// Replace the value with `"[REDACTED]"`.
+    Mask,
+    /// Keep the first character and domain of an email address.
+    MaskEmail,
+}
+
+/// A field to scrub from JSON payloads before they are stored. `path` is
+/// dot-separated (`card.number`); `*` matches any key or array element, and
+/// a key applied to an array applies to each of its elements.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct ScrubRule {
+    pub path: String,
+    pub action: ScrubAction,
+}
+
+/// Scrub rules for one provider or one endpoint. Endpoint rules take
+/// precedence over provider rules; `version` increments on every change and
+/// is recorded on each event it was applied to.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ScrubRuleset {
+    pub id: Uuid,
+    pub provider: Option<String>,
+    pub endpoint_id: Option<Uuid>,
+    pub version: i64,
+    pub rules: Vec<ScrubRule>,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetScrubRulesRequest {
+    pub rules: Vec<ScrubRule>,
+}
@@ -20,13 +20,101 @@ pub struct WebhookAttemptLog {
 
     pub error_kind: Option<WebhookAttemptErrorKind>,
     pub error_message: Option<String>,
+
+    /// How the response capture policy treated this attempt's body. `None`
+    /// for attempts logged before the policy existed.
+    pub response_capture: Option<ResponseCapture>,
+    /// Endpoint revision the event was leased against, and so the target
+    /// URL this attempt was sent to. `None` for older attempts.
+    pub target_revision: Option<i64>,
+    /// URL the event was leased to, which differs from the revision's
+    /// target for canary deliveries. `None` for older attempts.
+    pub target_url: Option<String>,
+    /// URL that answered after redirects were followed. `None` when the
+    /// worker did not report one.
+    pub final_url: Option<String>,
+    /// Address the worker was connected to. `None` when the worker did
+    /// not report one.
+    pub peer_address: Option<String>,
+    /// Where the attempt's time went. `None` when the worker did not
+    /// report timings.
+    pub timing: Option<AttemptTiming>,
+    /// Worker that made the attempt. `None` for older attempts.
+    pub worker_id: Option<String>,
+    pub worker_version: Option<String>,
+    pub worker_region: Option<String>,
+    /// Rate of the endpoint's 1-in-N success sampling when this attempt
+    /// was kept by it. `None` when sampling was off.
+    pub sample_rate: Option<i64>,
+    /// `X-Delivery-Id` the attempt was sent with: the first event of the
+    /// replay chain, shared by every retry and replay.
+    pub delivery_id: Uuid,
+}
+
+/// Phases of a delivery request in milliseconds, as measured by the
+/// worker. A phase it did not measure or skipped is `None`, e.g. `dns_ms`
+/// for a reused connection or `tls_ms` for plain HTTP. Network time is
+/// `dns_ms + connect_ms + tls_ms`; most of the rest of `ttfb_ms` is the
+/// target processing the webhook.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[allow(clippy::struct_field_names)]
+pub struct AttemptTiming {
+    pub dns_ms: Option<i64>,
+    pub connect_ms: Option<i64>,
+    pub tls_ms: Option<i64>,
+    /// Until the first response byte, counted from the request start.
+    pub ttfb_ms: Option<i64>,
+    pub total_ms: Option<i64>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+/// A best-effort copy of a delivery sent to the endpoint's shadow target.
+/// Shadow attempts have no effect on the event they copy.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ShadowAttemptLog {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub worker_id: String,
+    pub target_url: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub response_status: Option<i64>,
+    pub response_body: Option<String>,
+    pub error_kind: Option<WebhookAttemptErrorKind>,
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
 #[serde(rename_all = "snake_case")]
 pub enum WebhookAttemptErrorKind {
     Timeout,
+    /// The target's host name did not resolve. Connection failures after
+    /// resolution stay `Network`.
+    Dns,
+    /// The TLS handshake failed, e.g. an expired or untrusted certificate.
+    Tls,
+    /// The target host actively refused the connection.
+    ConnectionRefused,
+    /// The target kept redirecting past the worker's redirect limit.
+    TooManyRedirects,
+    /// The target answered 413 or the payload exceeded a limit the worker
+    /// knows the target enforces. Retrying the same payload cannot
+    /// succeed.
+    PayloadTooLarge,
+    /// Any other connection or transport failure.
     Network,
     InvalidResponse,
     Unexpected,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseCapture {
+    /// Failed attempts always keep their response body.
+    Failure,
+    /// Every successful response body is kept.
+    All,
+    /// The successful attempt was picked by success sampling.
+    Sampled,
+    /// The successful attempt's body was discarded by the policy.
+    Dropped,
+}
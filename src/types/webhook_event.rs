@@ -8,20 +8,64 @@ pub struct WebhookEvent {
     pub id: Uuid,
     pub endpoint_id: Uuid,
     pub replayed_from_event_id: Option<Uuid>,
+    pub source_id: Option<Uuid>,
     pub provider: String,
+    pub provider_event_id: Option<String>,
     pub headers: BTreeMap<String, String>,
+    /// The body as text, or its base64 when `payload_encoding` is `base64`.
     pub payload: String,
+    pub payload_encoding: PayloadEncoding,
+    /// Hex SHA-256 of the stored payload bytes, recorded at ingestion.
+    /// `None` for events stored before hashes were recorded, and once the
+    /// payload is erased.
+    pub payload_sha256: Option<String>,
+    /// Scrub ruleset and version applied to `payload` at ingestion.
+    pub scrub_ruleset_id: Option<Uuid>,
+    pub scrub_rule_version: Option<i64>,
+    pub event_type: Option<String>,
+    /// Payload schema violations recorded at ingestion; `None` when the
+    /// payload conformed or no schema applied.
+    pub schema_errors: Option<Vec<String>>,
 
     pub status: WebhookEventStatus,
     pub attempts: i64,
 
     pub received_at: String,
     pub next_attempt_at: Option<String>,
+    /// Delivery deadline; past it the event is not leased and moves to
+    /// `expired`.
+    pub expires_at: Option<String>,
 
     pub lease_expires_at: Option<String>,
     pub leased_by: Option<String>,
 
     pub last_error: Option<String>,
+    /// When the row last changed; `None` for rows written outside the
+    /// stores.
+    pub updated_at: Option<String>,
+}
+
+/// Whether a payload read back from storage still matches the hash
+/// recorded when it was stored.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadIntegrity {
+    Verified,
+    /// The payload changed after it was stored: corruption or tampering.
+    Mismatch,
+    /// No hash was recorded for the event.
+    Unrecorded,
+}
+
+/// How an event's `payload` string represents the body that was received.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadEncoding {
+    /// The body was valid UTF-8 and is returned as-is.
+    #[default]
+    Utf8,
+    /// The body was binary and is returned base64-encoded.
+    Base64,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
@@ -33,4 +77,8 @@ pub enum WebhookEventStatus {
     Delivered,
     Dead,
     Paused,
+    /// Passed its `expires_at` before it could be delivered.
+    Expired,
+    /// Withdrawn from the queue by a bulk cancel before it was delivered.
+    Cancelled,
 }
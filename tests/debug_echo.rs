@@ -0,0 +1,100 @@
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header::CONTENT_TYPE},
+    routing::post,
+};
+use http_body_util::BodyExt;
+use receiver::{
+    dispatcher::{EchoConfig, EchoTarget},
+    handlers::dispatcher::{
+        clear_echo_deliveries_handler, echo_delivery_handler, list_echo_deliveries_handler,
+    },
+    types::{EchoDeliveriesResponse, PayloadEncoding},
+};
+use tower::ServiceExt;
+
+fn echo_app(config: EchoConfig) -> Router {
+    Router::new()
+        .route(
+            "/debug/echo",
+            post(echo_delivery_handler)
+                .get(list_echo_deliveries_handler)
+                .delete(clear_echo_deliveries_handler),
+        )
+        .with_state(EchoTarget::new(config))
+}
+
+fn delivery(body: impl Into<Body>) -> Request<Body> {
+    Request::post("/debug/echo")
+        .header(CONTENT_TYPE, "application/json")
+        .header("x-delivery-id", "delivery-1")
+        .body(body.into())
+        .unwrap()
+}
+
+async fn recorded(app: &Router) -> EchoDeliveriesResponse {
+    let response = app
+        .clone()
+        .oneshot(Request::get("/debug/echo").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn echo_answers_with_the_delivery_and_records_it() {
+    let app = echo_app(EchoConfig {
+        failure_rate: 0.0,
+        capacity: 2,
+    });
+
+    let response = app.clone().oneshot(delivery(r#"{"id":1}"#)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], br#"{"id":1}"#);
+
+    app.clone()
+        .oneshot(delivery(vec![0xff, 0x00]))
+        .await
+        .unwrap();
+    app.clone().oneshot(delivery(r#"{"id":3}"#)).await.unwrap();
+
+    let deliveries = recorded(&app).await.deliveries;
+    assert_eq!(deliveries.len(), 2, "capacity keeps the latest deliveries");
+    assert_eq!(deliveries[0].body, "/wA=");
+    assert_eq!(deliveries[0].body_encoding, PayloadEncoding::Base64);
+    assert_eq!(deliveries[0].size_bytes, 2);
+    assert_eq!(deliveries[1].body, r#"{"id":3}"#);
+    assert_eq!(deliveries[1].headers["x-delivery-id"], "delivery-1");
+    assert_eq!(deliveries[1].status, 200);
+
+    let response = app
+        .clone()
+        .oneshot(Request::delete("/debug/echo").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(recorded(&app).await.deliveries.is_empty());
+}
+
+#[tokio::test]
+async fn echo_failure_rate_injects_recorded_500s() {
+    let app = echo_app(EchoConfig {
+        failure_rate: 1.0,
+        ..EchoConfig::default()
+    });
+
+    let response = app.clone().oneshot(delivery("{}")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let deliveries = recorded(&app).await.deliveries;
+    assert_eq!(deliveries.len(), 1);
+    assert!(deliveries[0].injected_failure);
+    assert_eq!(deliveries[0].status, 500);
+}
@@ -0,0 +1,218 @@
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::collections::HashMap;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{
+    Form, Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use receiver::login::{
+    DeviceLogin, DeviceLoginConfig, LoginError, StoredLogin, current_login, load_login, save_login,
+};
+use serde_json::json;
+use tempfile::TempDir;
+
+/// Answers token polls with these errors in turn, then with tokens.
+#[derive(Clone)]
+struct MockIdp {
+    base_url: Arc<Mutex<String>>,
+    poll_errors: Arc<Vec<&'static str>>,
+    polls: Arc<AtomicUsize>,
+    refreshes: Arc<AtomicUsize>,
+}
+
+async fn discovery(State(idp): State<MockIdp>) -> Json<serde_json::Value> {
+    let base_url = idp.base_url.lock().unwrap().clone();
+    Json(json!({
+        "issuer": base_url,
+        "device_authorization_endpoint": format!("{base_url}/device"),
+        "token_endpoint": format!("{base_url}/token"),
+    }))
+}
+
+async fn device_authorization(
+    State(idp): State<MockIdp>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Json<serde_json::Value> {
+    assert_eq!(
+        form.get("client_id").map(String::as_str),
+        Some("receiver-cli")
+    );
+    assert_eq!(
+        form.get("audience").map(String::as_str),
+        Some("receiver-api")
+    );
+    let base_url = idp.base_url.lock().unwrap().clone();
+    Json(json!({
+        "device_code": "device-1",
+        "user_code": "WDJB-MJHT",
+        "verification_uri": format!("{base_url}/activate"),
+        "expires_in": 60,
+        "interval": 0,
+    }))
+}
+
+async fn token(State(idp): State<MockIdp>, Form(form): Form<HashMap<String, String>>) -> Response {
+    match form.get("grant_type").map(String::as_str) {
+        Some("refresh_token") => {
+            assert_eq!(
+                form.get("refresh_token").map(String::as_str),
+                Some("refresh-1")
+            );
+            idp.refreshes.fetch_add(1, Ordering::SeqCst);
+            Json(json!({
+                "access_token": "access-2",
+                "token_type": "Bearer",
+                "expires_in": 3600,
+            }))
+            .into_response()
+        }
+        Some("urn:ietf:params:oauth:grant-type:device_code") => {
+            assert_eq!(
+                form.get("device_code").map(String::as_str),
+                Some("device-1")
+            );
+            let poll = idp.polls.fetch_add(1, Ordering::SeqCst);
+            match idp.poll_errors.get(poll) {
+                Some(error) => {
+                    (StatusCode::BAD_REQUEST, Json(json!({ "error": error }))).into_response()
+                }
+                None => Json(json!({
+                    "access_token": "access-1",
+                    "refresh_token": "refresh-1",
+                    "token_type": "Bearer",
+                    "expires_in": 3600,
+                }))
+                .into_response(),
+            }
+        }
+        _ => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "unsupported_grant_type" })),
+        )
+            .into_response(),
+    }
+}
+
+async fn spawn_idp(poll_errors: Vec<&'static str>) -> (MockIdp, String) {
+    let idp = MockIdp {
+        base_url: Arc::new(Mutex::new(String::new())),
+        poll_errors: Arc::new(poll_errors),
+        polls: Arc::new(AtomicUsize::new(0)),
+        refreshes: Arc::new(AtomicUsize::new(0)),
+    };
+    let app = Router::new()
+        .route("/.well-known/openid-configuration", get(discovery))
+        .route("/device", post(device_authorization))
+        .route("/token", post(token))
+        .with_state(idp.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    idp.base_url.lock().unwrap().clone_from(&base_url);
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (idp, base_url)
+}
+
+fn login_config(issuer: &str) -> DeviceLoginConfig {
+    let mut config = DeviceLoginConfig::new(issuer, "receiver-cli");
+    config.audience = Some("receiver-api".to_string());
+    config
+}
+
+#[tokio::test]
+async fn device_login_polls_until_approved_and_saves_the_login() {
+    let (idp, issuer) = spawn_idp(vec!["authorization_pending", "authorization_pending"]).await;
+
+    let login = DeviceLogin::discover(login_config(&issuer))
+        .await
+        .expect("discover");
+    let authorization = login.start().await.expect("start");
+    assert_eq!(authorization.user_code, "WDJB-MJHT");
+    assert_eq!(authorization.verification_uri, format!("{issuer}/activate"));
+
+    let stored = login
+        .wait_for_approval(&authorization, "https://receiver.example.com/")
+        .await
+        .expect("approved");
+    assert_eq!(idp.polls.load(Ordering::SeqCst), 3);
+    assert_eq!(stored.receiver_url, "https://receiver.example.com");
+    assert_eq!(stored.access_token, "access-1");
+    assert_eq!(stored.refresh_token.as_deref(), Some("refresh-1"));
+    assert_eq!(stored.token_endpoint, format!("{issuer}/token"));
+    assert!(stored.expires_at.is_some());
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("receiver/login.json");
+    save_login(&path, &stored).expect("save login");
+    assert_eq!(load_login(&path).expect("load login"), stored);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600, "the login holds tokens");
+    }
+}
+
+#[tokio::test]
+async fn device_login_backs_off_on_slow_down_and_stops_when_denied() {
+    let (idp, issuer) = spawn_idp(vec!["slow_down", "access_denied"]).await;
+
+    let login = DeviceLogin::discover(login_config(&issuer))
+        .await
+        .expect("discover");
+    let authorization = login.start().await.expect("start");
+    let started = std::time::Instant::now();
+    let err = login
+        .wait_for_approval(&authorization, "https://receiver.example.com")
+        .await
+        .expect_err("denied");
+    assert!(matches!(err, LoginError::Denied), "got {err:?}");
+    assert_eq!(idp.polls.load(Ordering::SeqCst), 2);
+    assert!(
+        started.elapsed() >= std::time::Duration::from_secs(5),
+        "slow_down adds five seconds to the polling interval"
+    );
+}
+
+#[tokio::test]
+async fn current_login_refreshes_an_expiring_access_token() {
+    let (idp, issuer) = spawn_idp(Vec::new()).await;
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("login.json");
+
+    let err = current_login(&path).await.expect_err("nothing saved yet");
+    assert!(matches!(err, LoginError::NotLoggedIn), "got {err:?}");
+
+    let expiring = StoredLogin {
+        receiver_url: "https://receiver.example.com".to_string(),
+        issuer: issuer.clone(),
+        client_id: "receiver-cli".to_string(),
+        token_endpoint: format!("{issuer}/token"),
+        access_token: "access-1".to_string(),
+        refresh_token: Some("refresh-1".to_string()),
+        expires_at: Some("2020-01-01T00:00:00Z".to_string()),
+    };
+    save_login(&path, &expiring).expect("save login");
+
+    let refreshed = current_login(&path).await.expect("refresh");
+    assert_eq!(refreshed.access_token, "access-2");
+    assert_eq!(
+        refreshed.refresh_token.as_deref(),
+        Some("refresh-1"),
+        "a refresh token the provider did not rotate is kept"
+    );
+    assert_eq!(load_login(&path).expect("reload"), refreshed);
+
+    let fresh = current_login(&path).await.expect("still fresh");
+    assert_eq!(fresh.access_token, "access-2");
+    assert_eq!(idp.refreshes.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,407 @@
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::collections::BTreeMap;
+
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
+use chrono::Utc;
+use receiver::{
+    client::{ClientError, DispatcherClient, RetryPolicy},
+    dispatcher::{ChaosConfig, DispatcherConfig, INJECTED_CHAOS_MESSAGE, dispatcher_chaos},
+    handlers::dispatcher::{lease_handler, renew_handler, report_handler},
+    ingest::IngestConfig,
+    redirect::delivery_redirect_policy,
+    resolver::{
+        CachingResolver, DnsErrorKind, ResolverConfig, attempt_error_kind, order_addresses,
+    },
+    secrets::SecretStore,
+    state::AppState,
+    types::{
+        AddressFamily, RedirectMode, RedirectPolicy, ReportAttempt, ReportOutcome, ReportRequest,
+        WebhookAttemptErrorKind,
+    },
+};
+use sqlx::{
+    Connection,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+use std::fs;
+use std::time::Duration;
+use tempfile::NamedTempFile;
+use uuid::Uuid;
+
+struct TestDb {
+    pool: sqlx::SqlitePool,
+    _db_file: NamedTempFile,
+}
+
+async fn setup_db() -> TestDb {
+    let db_file = NamedTempFile::new().expect("create temp sqlite file");
+    let options = SqliteConnectOptions::new()
+        .filename(db_file.path())
+        .create_if_missing(true)
+        .busy_timeout(std::time::Duration::from_millis(500));
+
+    let mut conn = sqlx::SqliteConnection::connect_with(&options)
+        .await
+        .expect("connect sqlite");
+    let mut entries: Vec<_> = fs::read_dir("migrations")
+        .expect("read migrations dir")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("sql"))
+        .collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let contents = fs::read_to_string(entry.path()).expect("read migration");
+        for stmt in contents.split(';') {
+            let stmt = stmt.trim();
+            if !stmt.is_empty() {
+                sqlx::query(stmt)
+                    .execute(&mut conn)
+                    .await
+                    .expect("run migration");
+            }
+        }
+    }
+    conn.close().await.expect("close migration conn");
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .expect("connect pool");
+
+    TestDb {
+        pool,
+        _db_file: db_file,
+    }
+}
+
+/// Serves the dispatcher routes on an ephemeral port and returns its URL.
+async fn spawn_dispatcher(pool: sqlx::SqlitePool) -> String {
+    spawn_dispatcher_with_chaos(pool, None).await
+}
+
+async fn spawn_dispatcher_with_chaos(pool: sqlx::SqlitePool, chaos: Option<ChaosConfig>) -> String {
+    let state = AppState {
+        pool,
+        dispatcher: DispatcherConfig::default(),
+        inspector_api_token: None,
+        inspector_scoped_tokens: Vec::new(),
+        inspector_oidc: None,
+        share_links: None,
+        bundle_signing_key: None,
+        audit_reads: false,
+        ingest: IngestConfig::default(),
+        ingest_queue: None,
+        secrets: SecretStore::default(),
+        job_dir: None,
+    };
+    let mut app = Router::new()
+        .route("/internal/dispatcher/lease", post(lease_handler))
+        .route("/internal/dispatcher/report", post(report_handler))
+        .route("/internal/dispatcher/renew", post(renew_handler));
+    if let Some(chaos) = chaos {
+        app = app.route_layer(middleware::from_fn_with_state(chaos, dispatcher_chaos));
+    }
+    let app = app.with_state(state);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn client_leases_renews_and_reports() {
+    let db = setup_db().await;
+    let endpoint_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO endpoints (id, target_url) VALUES (?, ?)")
+        .bind(endpoint_id.to_string())
+        .bind("https://example.com/hook")
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let event_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO webhook_events (id, endpoint_id, provider, headers, payload, status, attempts, received_at) \
+         VALUES (?, ?, 'stripe', '{}', '{}', 'pending', 0, ?)",
+    )
+    .bind(event_id.to_string())
+    .bind(endpoint_id.to_string())
+    .bind(Utc::now().to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    let base_url = spawn_dispatcher(db.pool.clone()).await;
+    let client = DispatcherClient::new(format!("{base_url}/"), "worker-a");
+
+    let leased = client.lease(10, 30_000).await.expect("lease");
+    assert_eq!(leased.len(), 1);
+    assert_eq!(leased[0].event.id, event_id);
+
+    let renewed = client.renew(event_id, 600_000).await.expect("renew");
+    assert!(renewed > leased[0].lease_expires_at);
+
+    let now = Utc::now().to_rfc3339();
+    let report = ReportRequest {
+        worker_id: client.worker_id().to_string(),
+        worker_version: None,
+        worker_region: None,
+        event_id,
+        outcome: ReportOutcome::Delivered,
+        retryable: false,
+        next_attempt_at: None,
+        attempt: ReportAttempt {
+            started_at: now.clone(),
+            finished_at: now,
+            request_headers: BTreeMap::new(),
+            request_body: "{}".to_string(),
+            response_status: Some(200),
+            response_headers: None,
+            response_body: None,
+            error_kind: None,
+            error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
+        },
+    };
+    let response = client.report(&report).await.expect("report");
+    assert_eq!(response.final_outcome, ReportOutcome::Delivered);
+
+    let err = client.renew(event_id, 600_000).await.unwrap_err();
+    assert!(matches!(&err, ClientError::LeaseLost(message) if message == "lease_missing"));
+    let err = client.report(&report).await.unwrap_err();
+    assert!(matches!(err, ClientError::LeaseLost(_)));
+}
+
+#[tokio::test]
+async fn client_gives_up_after_retrying_transport_errors() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let client = DispatcherClient::new(format!("http://{addr}"), "worker-a").with_retry_policy(
+        RetryPolicy {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+        },
+    );
+    let err = client.lease(1, 1_000).await.unwrap_err();
+    assert!(matches!(err, ClientError::Http(_)));
+}
+
+#[tokio::test]
+async fn chaos_middleware_delays_and_fails_worker_requests() {
+    let db = setup_db().await;
+    let retry = RetryPolicy {
+        max_retries: 1,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(1),
+    };
+
+    let slow = ChaosConfig {
+        latency_ms: 50,
+        latency_rate: 1.0,
+        error_rate: 0.0,
+    };
+    let base_url = spawn_dispatcher_with_chaos(db.pool.clone(), Some(slow)).await;
+    let client = DispatcherClient::new(base_url, "worker-a").with_retry_policy(retry.clone());
+    let started = std::time::Instant::now();
+    let leased = client.lease(1, 1_000).await.expect("lease");
+    assert!(leased.is_empty());
+    assert!(started.elapsed() >= Duration::from_millis(50));
+
+    let failing = ChaosConfig {
+        latency_ms: 0,
+        latency_rate: 0.0,
+        error_rate: 1.0,
+    };
+    let base_url = spawn_dispatcher_with_chaos(db.pool.clone(), Some(failing)).await;
+    let client = DispatcherClient::new(base_url, "worker-a").with_retry_policy(retry);
+    let err = client.lease(1, 1_000).await.unwrap_err();
+    assert!(matches!(
+        &err,
+        ClientError::Api { status: 500, error } if error.message == INJECTED_CHAOS_MESSAGE
+    ));
+}
+
+#[tokio::test]
+async fn caching_resolver_reports_unresolvable_hosts_as_dns_errors() {
+    let resolver = CachingResolver::new(ResolverConfig {
+        timeout: Duration::from_secs(2),
+        ..ResolverConfig::default()
+    });
+    let ips = resolver.lookup("127.0.0.1").await.expect("ip literal");
+    assert!(ips.iter().all(std::net::IpAddr::is_loopback));
+    let ips = resolver
+        .lookup("localhost")
+        .await
+        .expect("resolve localhost");
+    assert!(ips.iter().any(std::net::IpAddr::is_loopback));
+
+    // `.invalid` never resolves. Without network access the lookup may
+    // fail as temporary instead of not found, but it is a DNS error either
+    // way.
+    let err = resolver
+        .lookup("receiver-test.invalid")
+        .await
+        .expect_err("invalid host must not resolve");
+    assert_eq!(err.host, "receiver-test.invalid");
+    assert_ne!(err.kind, DnsErrorKind::NoAddresses);
+
+    let http = reqwest::Client::builder()
+        .dns_resolver(std::sync::Arc::new(resolver))
+        .build()
+        .expect("build client");
+    let err = http
+        .post("http://receiver-test.invalid/webhook")
+        .send()
+        .await
+        .expect_err("delivery must fail");
+    assert_eq!(attempt_error_kind(&err), WebhookAttemptErrorKind::Dns);
+}
+
+#[tokio::test]
+async fn refused_connections_and_redirect_loops_get_their_own_error_kinds() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let closed_addr = listener.local_addr().expect("local addr");
+    drop(listener);
+    let err = reqwest::Client::new()
+        .post(format!("http://{closed_addr}/webhook"))
+        .send()
+        .await
+        .expect_err("nothing listens on the port");
+    assert_eq!(
+        attempt_error_kind(&err),
+        WebhookAttemptErrorKind::ConnectionRefused
+    );
+
+    let app = Router::new().route(
+        "/loop",
+        post(|| async { axum::response::Redirect::temporary("/loop") }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("local addr");
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    let err = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::limited(3))
+        .build()
+        .expect("build client")
+        .post(format!("http://{addr}/loop"))
+        .send()
+        .await
+        .expect_err("redirect loop");
+    assert_eq!(
+        attempt_error_kind(&err),
+        WebhookAttemptErrorKind::TooManyRedirects
+    );
+}
+
+#[tokio::test]
+async fn delivery_redirect_policies_stop_at_the_host_and_limit() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("local addr");
+    let other_host = format!("http://localhost:{}/c", addr.port());
+    let app = Router::new()
+        .route(
+            "/a",
+            get(|| async { axum::response::Redirect::temporary("/b") }),
+        )
+        .route(
+            "/b",
+            get(move || async move { axum::response::Redirect::temporary(&other_host) }),
+        )
+        .route("/c", get(|| async { "ok" }));
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let deliver = |mode, max_redirects| {
+        let client = reqwest::Client::builder()
+            .redirect(delivery_redirect_policy(RedirectPolicy {
+                mode,
+                max_redirects,
+            }))
+            .build()
+            .expect("build client");
+        let url = format!("http://{addr}/a");
+        async move { client.get(url).send().await }
+    };
+
+    let response = deliver(RedirectMode::None, 0).await.expect("send");
+    assert_eq!(response.status(), 307);
+    assert_eq!(response.url().path(), "/a");
+
+    let response = deliver(RedirectMode::SameHost, 5).await.expect("send");
+    assert_eq!(response.status(), 307);
+    assert_eq!(response.url().path(), "/b");
+
+    let err = deliver(RedirectMode::SameHost, 0)
+        .await
+        .expect_err("redirect limit");
+    assert_eq!(
+        attempt_error_kind(&err),
+        WebhookAttemptErrorKind::TooManyRedirects
+    );
+
+    let response = deliver(RedirectMode::Any, 2).await.expect("send");
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.url().host_str(), Some("localhost"));
+
+    let err = deliver(RedirectMode::Any, 1)
+        .await
+        .expect_err("redirect limit");
+    assert_eq!(
+        attempt_error_kind(&err),
+        WebhookAttemptErrorKind::TooManyRedirects
+    );
+}
+
+#[tokio::test]
+async fn address_family_orders_resolved_addresses_and_peer_is_reported() {
+    let v4: std::net::IpAddr = "192.0.2.1".parse().unwrap();
+    let v6: std::net::IpAddr = "2001:db8::1".parse().unwrap();
+    let ips = vec![v4, v6];
+    assert_eq!(
+        order_addresses(ips.clone(), AddressFamily::Any),
+        vec![v4, v6]
+    );
+    assert_eq!(order_addresses(ips.clone(), AddressFamily::Ipv4), vec![v4]);
+    assert_eq!(order_addresses(ips.clone(), AddressFamily::Ipv6), vec![v6]);
+    assert_eq!(
+        order_addresses(ips.clone(), AddressFamily::PreferIpv6),
+        vec![v6, v4]
+    );
+    assert_eq!(
+        order_addresses(ips, AddressFamily::PreferIpv4),
+        vec![v4, v6]
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("local addr");
+    let app = Router::new().route("/webhook", post(|| async { "ok" }));
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let resolver = CachingResolver::default().for_family(AddressFamily::Ipv4);
+    let response = reqwest::Client::builder()
+        .dns_resolver(std::sync::Arc::new(resolver))
+        .build()
+        .expect("build client")
+        .post(format!("http://localhost:{}/webhook", addr.port()))
+        .send()
+        .await
+        .expect("deliver over ipv4");
+    assert_eq!(response.remote_addr(), Some(addr));
+}
@@ -0,0 +1,308 @@
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use receiver::{
+    dispatcher::{
+        DELIVERY_ID_HEADER, DispatcherConfig, EMAIL_WORKER_ID, EmailSender, SmtpSettings, SmtpTls,
+        send_due_email, smtp_settings_from_file,
+    },
+    secrets::{FileProvider, SecretStore},
+};
+use sqlx::{
+    Connection, SqliteConnection, SqlitePool,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+use tempfile::{NamedTempFile, TempDir};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+struct TestDb {
+    pool: SqlitePool,
+    _db_file: NamedTempFile,
+}
+
+async fn setup_db() -> TestDb {
+    let db_file = NamedTempFile::new().expect("create temp sqlite file");
+    let options = SqliteConnectOptions::new()
+        .filename(db_file.path())
+        .create_if_missing(true);
+
+    let mut conn = SqliteConnection::connect_with(&options)
+        .await
+        .expect("connect sqlite for migrations");
+    run_migrations(&mut conn).await.expect("run migrations");
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .expect("connect sqlite file");
+
+    TestDb {
+        pool,
+        _db_file: db_file,
+    }
+}
+
+async fn run_migrations(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    let mut entries: Vec<_> = fs::read_dir("migrations")
+        .map_err(sqlx::Error::Io)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().and_then(|ext| ext.to_str()) == Some("sql"))
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let contents = fs::read_to_string(entry.path()).map_err(sqlx::Error::Io)?;
+        for statement in contents.split(';') {
+            let statement = statement.trim();
+            if !statement.is_empty() {
+                sqlx::query(statement).execute(&mut *conn).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn seed_email_event(pool: &SqlitePool, target_url: &str, payload: &str) -> Uuid {
+    let endpoint_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO endpoints (id, target_url, target_kind) VALUES (?, ?, 'email')")
+        .bind(endpoint_id.to_string())
+        .bind(target_url)
+        .execute(pool)
+        .await
+        .expect("insert email endpoint");
+
+    let event_id = Uuid::new_v4();
+    sqlx::query(
+        r"
+        INSERT INTO webhook_events (
+            id, endpoint_id, provider, headers, payload, status, attempts, received_at,
+            event_type
+        )
+        VALUES (?, ?, 'stripe', '{}', ?, 'pending', 0, ?, 'invoice.paid')
+        ",
+    )
+    .bind(event_id.to_string())
+    .bind(endpoint_id.to_string())
+    .bind(payload)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await
+    .expect("insert event");
+    event_id
+}
+
+/// A plain-text SMTP server that accepts every recipient except
+/// `bounce@` (550) and `busy@` (450), and records each message it queues.
+async fn fake_smtp_server() -> (u16, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let queued = messages.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let queued = queued.clone();
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+                write.write_all(b"220 fake ESMTP\r\n").await.unwrap();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let command = line.to_ascii_uppercase();
+                    let reply: &[u8] = if command.starts_with("RCPT TO:<BOUNCE@") {
+                        b"550 5.1.1 no such user\r\n"
+                    } else if command.starts_with("RCPT TO:<BUSY@") {
+                        b"450 4.2.1 mailbox busy, try later\r\n"
+                    } else if command == "DATA" {
+                        write.write_all(b"354 go ahead\r\n").await.unwrap();
+                        let mut message = Vec::new();
+                        while let Ok(Some(line)) = lines.next_line().await {
+                            if line == "." {
+                                break;
+                            }
+                            message.push(line);
+                        }
+                        queued.lock().unwrap().push(message.join("\n"));
+                        b"250 2.0.0 queued as 1\r\n"
+                    } else if command == "QUIT" {
+                        write.write_all(b"221 bye\r\n").await.unwrap();
+                        break;
+                    } else {
+                        b"250 ok\r\n"
+                    };
+                    write.write_all(reply).await.unwrap();
+                }
+            });
+        }
+    });
+    (port, messages)
+}
+
+fn smtp_settings(port: u16) -> SmtpSettings {
+    SmtpSettings {
+        host: "127.0.0.1".to_string(),
+        port,
+        tls: SmtpTls::None,
+        username: None,
+        password: None,
+        from_address: "receiver@example.com".to_string(),
+    }
+}
+
+async fn event_state(pool: &SqlitePool, event_id: Uuid) -> (String, i64, Option<String>) {
+    sqlx::query_as("SELECT status, attempts, last_error FROM webhook_events WHERE id = ?")
+        .bind(event_id.to_string())
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn email_events_are_rendered_and_sent_through_smtp() {
+    let db = setup_db().await;
+    let (port, messages) = fake_smtp_server().await;
+    let config = DispatcherConfig {
+        smtp: Some(smtp_settings(port)),
+        ..DispatcherConfig::default()
+    };
+    let sender = EmailSender::new(&smtp_settings(port)).unwrap();
+    let event_id = seed_email_event(
+        &db.pool,
+        "mailto:ops@example.com,billing@example.com",
+        r#"{"id":"evt_1","type":"invoice.paid"}"#,
+    )
+    .await;
+
+    assert!(send_due_email(&db.pool, &config, &sender).await.unwrap());
+    assert!(!send_due_email(&db.pool, &config, &sender).await.unwrap());
+
+    assert_eq!(
+        event_state(&db.pool, event_id).await,
+        ("delivered".to_string(), 1, None)
+    );
+    let (worker_id, response_status, request_body): (String, Option<i64>, String) =
+        sqlx::query_as(
+            "SELECT worker_id, response_status, request_body FROM webhook_attempt_logs WHERE event_id = ?",
+        )
+        .bind(event_id.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(worker_id, EMAIL_WORKER_ID);
+    assert_eq!(response_status, Some(250));
+    assert!(
+        request_body.contains("\"type\": \"invoice.paid\""),
+        "{request_body}"
+    );
+
+    let messages = messages.lock().unwrap();
+    assert_eq!(messages.len(), 1);
+    let message = &messages[0];
+    assert!(
+        message.contains("Subject: stripe webhook: invoice.paid"),
+        "{message}"
+    );
+    assert!(
+        message.contains("To: ops@example.com, billing@example.com"),
+        "{message}"
+    );
+    assert!(
+        message.contains(&format!("{DELIVERY_ID_HEADER}: {event_id}")),
+        "{message}"
+    );
+    assert!(message.contains("\"id\": \"evt_1\""), "{message}");
+}
+
+#[tokio::test]
+async fn smtp_rejections_retry_or_dead_letter_the_event() {
+    let db = setup_db().await;
+    let (port, messages) = fake_smtp_server().await;
+    let config = DispatcherConfig {
+        smtp: Some(smtp_settings(port)),
+        ..DispatcherConfig::default()
+    };
+    let sender = EmailSender::new(&smtp_settings(port)).unwrap();
+    let busy = seed_email_event(&db.pool, "mailto:busy@example.com", "{}").await;
+    let bounced = seed_email_event(&db.pool, "mailto:bounce@example.com", "{}").await;
+    let invalid = seed_email_event(&db.pool, "mailto:not-an-address", "{}").await;
+
+    while send_due_email(&db.pool, &config, &sender).await.unwrap() {}
+
+    let (status, attempts, last_error) = event_state(&db.pool, busy).await;
+    assert_eq!((status.as_str(), attempts), ("pending", 1));
+    assert!(last_error.unwrap().contains("mailbox busy"));
+    let (status, attempts, last_error) = event_state(&db.pool, bounced).await;
+    assert_eq!((status.as_str(), attempts), ("dead", 1));
+    assert!(last_error.unwrap().contains("no such user"));
+    let (status, _, last_error) = event_state(&db.pool, invalid).await;
+    assert_eq!(status, "dead");
+    assert!(last_error.unwrap().contains("invalid recipient"));
+    assert!(messages.lock().unwrap().is_empty());
+}
+
+#[test]
+fn smtp_settings_are_read_from_a_config_file() {
+    let secrets_dir = TempDir::new().unwrap();
+    fs::write(secrets_dir.path().join("smtp-password"), "hunter2\n").unwrap();
+    let secrets = SecretStore::default().with_provider(
+        "file",
+        FileProvider {
+            base_dir: Some(secrets_dir.path().to_path_buf()),
+        },
+    );
+    let read = |contents: &str| {
+        let file = NamedTempFile::new().unwrap();
+        fs::write(file.path(), contents).unwrap();
+        smtp_settings_from_file(file.path(), &secrets)
+    };
+
+    let settings = read(
+        r#"{
+            "host": "smtp.example.com",
+            "port": 2525,
+            "username": "receiver",
+            "password_secret_id": "file:smtp-password",
+            "from": "receiver@example.com"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        settings,
+        SmtpSettings {
+            host: "smtp.example.com".to_string(),
+            port: 2525,
+            tls: SmtpTls::StartTls,
+            username: Some("receiver".to_string()),
+            password: Some("hunter2".to_string()),
+            from_address: "receiver@example.com".to_string(),
+        }
+    );
+    assert!(!format!("{settings:?}").contains("hunter2"));
+
+    for (contents, problem) in [
+        (r#"{"from": "receiver@example.com"}"#, "missing host"),
+        (
+            r#"{"host": "smtp.example.com", "from": "receiver@example.com", "sender": "x"}"#,
+            "unknown key",
+        ),
+        (
+            r#"{"host": "smtp.example.com", "from": "receiver@example.com", "username": "r",
+                "password": "p", "password_secret_id": "file:smtp-password"}"#,
+            "password given twice",
+        ),
+        (
+            r#"{"host": "smtp.example.com", "from": "receiver@example.com", "username": "r",
+                "password_secret_id": "file:missing"}"#,
+            "unresolvable secret",
+        ),
+        (r#"["smtp.example.com"]"#, "not an object"),
+    ] {
+        assert!(read(contents).is_err(), "{problem} should be rejected");
+    }
+}
@@ -4,13 +4,41 @@
     clippy::needless_raw_string_hashes
 )]
 
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use chrono::{Duration, Utc};
+use chrono::{Duration, Timelike, Utc};
 use receiver::{
-    dispatcher::{DispatcherConfig, lease_events, report_delivery},
-    types::{LeaseRequest, ReportAttempt, ReportOutcome, ReportRequest, WebhookEventStatus},
+    dispatcher::{
+        CronSchedule, DELIVERY_ATTEMPT_HEADER, DELIVERY_EVENT_ID_HEADER, DELIVERY_ID_HEADER,
+        DispatcherConfig, INJECTED_FAILURE_MESSAGE, LeaseBenchConfig, SmtpSettings, SmtpTls,
+        StoreError, deliverable_from, expire_events, inject_faults, lease_email_events,
+        lease_endpoint_checks, lease_events, maintenance_window_end, next_delivery_window_start,
+        record_endpoint_check, record_shadow_attempt, renew_lease, report_delivery,
+        retry_delay_secs, run_lease_bench, run_selftest, smtp_settings_from_vars, validate_backoff,
+    },
+    inspector::{
+        AnomalyConfig, DeadEventTarget, EndpointScope, QueuedEventFilter, attempt_buckets,
+        count_events_to_reprioritize, create_endpoint_group, detect_anomalies,
+        get_endpoint_backoff, get_endpoint_health, get_event_lineage, list_attempts,
+        list_endpoint_revisions, list_shadow_attempts, render_anomaly_metrics, render_slo_metrics,
+        render_tls_metrics, replay_event, replay_group, reprioritize_events_batch, run_doctor,
+        set_delivery_windows, set_dispatch_paused, set_endpoint_attempt_log_sampling,
+        set_endpoint_backoff, set_endpoint_canary, set_endpoint_check, set_endpoint_connect_policy,
+        set_endpoint_group, set_endpoint_redirect_policy, set_endpoint_region, set_endpoint_shadow,
+        set_endpoint_slo, set_endpoint_target, set_endpoint_timeouts, set_fault_injection,
+        set_group_paused, slo_stats, tls_expiries,
+    },
+    types::{
+        AddressFamily, AnomalyMetric, AttemptTiming, BackoffStrategy, CheckLeaseRequest,
+        CheckReportRequest, ConnectPolicy, DeliveryWindow, EndpointCheckMethod,
+        EndpointRevisionChange, EndpointTargetKind, LeaseRequest, LeasedEvent, MaintenanceWindow,
+        RedirectMode, RedirectPolicy, RegionMode, RenewRequest, ReplayAttemptBudget, ReportAttempt,
+        ReportOutcome, ReportRequest, ShadowReportRequest, WebhookAttemptErrorKind,
+        WebhookEventStatus,
+    },
 };
+use sha2::{Digest, Sha256};
 use sqlx::{
     Connection, SqliteConnection, SqlitePool,
     sqlite::{SqliteConnectOptions, SqlitePoolOptions},
@@ -97,6 +125,16 @@ async fn seed_endpoint(pool: &SqlitePool) -> Uuid {
     id
 }
 
+/// The dispatcher's per-purpose sampling hash: the first 8 bytes of
+/// SHA-256(purpose ‖ event ID).
+fn sample_bucket(purpose: &str, event_id: Uuid) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(purpose.as_bytes())
+        .chain_update(event_id.as_bytes())
+        .finalize();
+    u64::from_be_bytes(digest[..8].try_into().unwrap())
+}
+
 async fn seed_event(
     pool: &SqlitePool,
     endpoint_id: Uuid,
@@ -224,9 +262,13 @@ async fn lease_eligibility_filter() {
         limit: 50,
         lease_ms: 30_000,
         worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
     };
 
-    let events = lease_events(&pool, &req).await.expect("lease events");
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events");
 
     let returned_ids: HashSet<Uuid> = events.iter().map(|event| event.event.id).collect();
     let expected_ids: HashSet<Uuid> = [eligible_pending, eligible_requeued].into_iter().collect();
@@ -260,15 +302,226 @@ async fn target_url_join() {
         limit: 10,
         lease_ms: 30_000,
         worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
     };
 
-    let events = lease_events(&pool, &req).await.expect("lease events");
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events");
 
     assert_eq!(events.len(), 1, "should lease exactly one event");
     assert_eq!(
         events[0].target_url, known_target_url,
         "leased event should include correct target_url from endpoints table"
     );
+    assert_eq!(
+        events[0].target_kind,
+        EndpointTargetKind::Http,
+        "endpoints default to http delivery"
+    );
+}
+
+#[tokio::test]
+async fn amqp_target_kind_is_dead_lettered() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let amqp = Uuid::new_v4();
+    sqlx::query("INSERT INTO endpoints (id, target_url, target_kind) VALUES (?, ?, 'amqp')")
+        .bind(amqp.to_string())
+        .bind("amqp://broker.internal:5672/%2f?queue=orders")
+        .execute(&pool)
+        .await
+        .expect("insert amqp endpoint");
+    let queued_id = seed_event(&pool, amqp, "pending", None, None, None).await;
+
+    let http = seed_endpoint(&pool).await;
+    let http_id = seed_event(&pool, http, "pending", None, None, None).await;
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events");
+    assert_eq!(events.len(), 1, "no worker can publish to AMQP yet");
+    assert_eq!(events[0].event.id, http_id);
+
+    let (status, next_attempt_at, last_error): (String, Option<String>, Option<String>) =
+        sqlx::query_as(
+            "SELECT status, next_attempt_at, last_error FROM webhook_events WHERE id = ?",
+        )
+        .bind(queued_id.to_string())
+        .fetch_one(&pool)
+        .await
+        .expect("fetch status");
+    assert_eq!(status, "dead");
+    assert_eq!(next_attempt_at, None);
+    assert_eq!(
+        last_error.as_deref(),
+        Some("target kind amqp is not supported")
+    );
+}
+
+fn smtp_settings() -> SmtpSettings {
+    SmtpSettings {
+        host: "smtp.example.com".to_string(),
+        port: 587,
+        tls: SmtpTls::StartTls,
+        username: Some("receiver".to_string()),
+        password: Some("hunter2".to_string()),
+        from_address: "receiver@example.com".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn email_targets_are_only_leased_to_the_email_sender() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let email = Uuid::new_v4();
+    sqlx::query("INSERT INTO endpoints (id, target_url, target_kind) VALUES (?, ?, 'email')")
+        .bind(email.to_string())
+        .bind("mailto:ops@example.com")
+        .execute(&pool)
+        .await
+        .expect("insert email endpoint");
+    let email_id = seed_event(&pool, email, "pending", None, None, None).await;
+    let http = seed_endpoint(&pool).await;
+    let http_id = seed_event(&pool, http, "pending", None, None, None).await;
+
+    let config = DispatcherConfig {
+        smtp: Some(smtp_settings()),
+        ..DispatcherConfig::default()
+    };
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let events = lease_events(&pool, &config, &req)
+        .await
+        .expect("lease for a worker");
+    assert_eq!(events.len(), 1, "workers never get email targets");
+    assert_eq!(events[0].event.id, http_id);
+    let leased = serde_json::to_string(&events[0]).expect("serialize lease");
+    assert!(!leased.contains("hunter2"));
+
+    let events = lease_email_events(&pool, &config, &req)
+        .await
+        .expect("lease for the email sender");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event.id, email_id);
+    assert_eq!(events[0].target_kind, EndpointTargetKind::Email);
+    assert_eq!(events[0].target_url, "mailto:ops@example.com");
+}
+
+#[test]
+fn smtp_settings_are_validated() {
+    let parse = |vars: &[(&str, &str)]| {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect();
+        smtp_settings_from_vars(|name| vars.get(name).cloned())
+    };
+
+    assert_eq!(parse(&[]), Ok(None));
+    assert_eq!(
+        parse(&[
+            ("RECEIVER_SMTP_HOST", "smtp.example.com"),
+            ("RECEIVER_SMTP_USERNAME", "receiver"),
+            ("RECEIVER_SMTP_PASSWORD", "hunter2"),
+            ("RECEIVER_SMTP_FROM", " receiver@example.com "),
+        ]),
+        Ok(Some(smtp_settings()))
+    );
+    let smtps = parse(&[
+        ("RECEIVER_SMTP_HOST", "smtp.example.com"),
+        ("RECEIVER_SMTP_TLS", "tls"),
+        ("RECEIVER_SMTP_FROM", "receiver@example.com"),
+    ])
+    .expect("valid")
+    .expect("configured");
+    assert_eq!((smtps.tls, smtps.port), (SmtpTls::Tls, 465));
+    let relay = parse(&[
+        ("RECEIVER_SMTP_HOST", "relay.internal"),
+        ("RECEIVER_SMTP_TLS", "none"),
+        ("RECEIVER_SMTP_PORT", "2525"),
+        ("RECEIVER_SMTP_FROM", "receiver@example.com"),
+    ])
+    .expect("valid")
+    .expect("configured");
+    assert_eq!((relay.tls, relay.port), (SmtpTls::None, 2525));
+    assert_eq!(relay.username, None);
+
+    for (vars, problem) in [
+        (
+            &[("RECEIVER_SMTP_FROM", "receiver@example.com")][..],
+            "settings without a host",
+        ),
+        (
+            &[("RECEIVER_SMTP_HOST", "smtp.example.com")][..],
+            "no from address",
+        ),
+        (
+            &[
+                ("RECEIVER_SMTP_HOST", "smtp://smtp.example.com"),
+                ("RECEIVER_SMTP_FROM", "receiver@example.com"),
+            ][..],
+            "host with a scheme",
+        ),
+        (
+            &[
+                ("RECEIVER_SMTP_HOST", "smtp.example.com"),
+                ("RECEIVER_SMTP_TLS", "ssl"),
+                ("RECEIVER_SMTP_FROM", "receiver@example.com"),
+            ][..],
+            "unknown TLS mode",
+        ),
+        (
+            &[
+                ("RECEIVER_SMTP_HOST", "smtp.example.com"),
+                ("RECEIVER_SMTP_PORT", "0"),
+                ("RECEIVER_SMTP_FROM", "receiver@example.com"),
+            ][..],
+            "port zero",
+        ),
+        (
+            &[
+                ("RECEIVER_SMTP_HOST", "smtp.example.com"),
+                ("RECEIVER_SMTP_USERNAME", "receiver"),
+                ("RECEIVER_SMTP_FROM", "receiver@example.com"),
+            ][..],
+            "username without password",
+        ),
+        (
+            &[
+                ("RECEIVER_SMTP_HOST", "smtp.example.com"),
+                ("RECEIVER_SMTP_TLS", "none"),
+                ("RECEIVER_SMTP_USERNAME", "receiver"),
+                ("RECEIVER_SMTP_PASSWORD", "hunter2"),
+                ("RECEIVER_SMTP_FROM", "receiver@example.com"),
+            ][..],
+            "credentials in the clear",
+        ),
+        (
+            &[
+                ("RECEIVER_SMTP_HOST", "smtp.example.com"),
+                ("RECEIVER_SMTP_FROM", "Receiver <receiver@example.com>"),
+            ][..],
+            "from address with a display name",
+        ),
+    ] {
+        assert!(parse(vars).is_err(), "{problem} should be rejected");
+    }
 }
 
 #[tokio::test]
@@ -294,9 +547,13 @@ async fn expired_lease_recovery() {
         limit: 10,
         lease_ms: 30_000,
         worker_id: "worker-new".to_string(),
+        region: None,
+        endpoint_id: None,
     };
 
-    let events = lease_events(&pool, &req).await.expect("lease events");
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events");
 
     assert_eq!(events.len(), 1);
     let leased = &events[0];
@@ -333,11 +590,15 @@ async fn lease_exclusivity_no_duplicate_leases() {
         limit: 6,
         lease_ms: 30_000,
         worker_id: "worker-a".to_string(),
+        region: None,
+        endpoint_id: None,
     };
     let req_b = LeaseRequest {
         limit: 6,
         lease_ms: 30_000,
         worker_id: "worker-b".to_string(),
+        region: None,
+        endpoint_id: None,
     };
 
     let barrier_a = barrier.clone();
@@ -346,11 +607,15 @@ async fn lease_exclusivity_no_duplicate_leases() {
     let (events_a, events_b) = tokio::join!(
         async {
             barrier_a.wait().await;
-            lease_events(&pool, &req_a).await.expect("lease events a")
+            lease_events(&pool, &DispatcherConfig::default(), &req_a)
+                .await
+                .expect("lease events a")
         },
         async {
             barrier_b.wait().await;
-            lease_events(&pool, &req_b).await.expect("lease events b")
+            lease_events(&pool, &DispatcherConfig::default(), &req_b)
+                .await
+                .expect("lease events b")
         }
     );
 
@@ -387,9 +652,13 @@ async fn circuit_gating() {
         limit: 50,
         lease_ms: 30_000,
         worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
     };
 
-    let events = lease_events(&pool, &req).await.expect("lease events");
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events");
 
     assert!(
         events.is_empty(),
@@ -411,7 +680,7 @@ async fn circuit_gating() {
     .await
     .expect("update circuit state");
 
-    let events = lease_events(&pool, &req)
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
         .await
         .expect("lease events second call");
 
@@ -464,6 +733,8 @@ async fn report_happy_path_delivered() {
     // Stage 3: Build ReportRequest
     let report_req = ReportRequest {
         worker_id: "test-worker".to_string(),
+        worker_version: None,
+        worker_region: None,
         event_id,
         outcome: ReportOutcome::Delivered,
         retryable: true,
@@ -481,6 +752,9 @@ async fn report_happy_path_delivered() {
             response_body: Some(r#"{"status":"ok"}"#.to_string()),
             error_kind: None,
             error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
         },
     };
 
@@ -625,6 +899,8 @@ async fn report_retry_path() {
 
     let report_req = ReportRequest {
         worker_id: "test-worker".to_string(),
+        worker_version: None,
+        worker_region: None,
         event_id,
         outcome: ReportOutcome::Retry,
         retryable: true,
@@ -639,6 +915,9 @@ async fn report_retry_path() {
             response_body: Some("Service Unavailable".to_string()),
             error_kind: None,
             error_message: Some("Connection timed out".to_string()),
+            final_url: None,
+            peer_address: None,
+            timing: None,
         },
     };
 
@@ -766,6 +1045,8 @@ async fn report_lease_ownership_conflict_wrong_worker() {
 
     let report_req = ReportRequest {
         worker_id: "wrong-worker".to_string(),
+        worker_version: None,
+        worker_region: None,
         event_id,
         outcome: ReportOutcome::Delivered,
         retryable: true,
@@ -780,6 +1061,9 @@ async fn report_lease_ownership_conflict_wrong_worker() {
             response_body: None,
             error_kind: None,
             error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
         },
     };
 
@@ -837,6 +1121,8 @@ async fn report_lease_ownership_conflict_expired_lease() {
 
     let report_req = ReportRequest {
         worker_id: "test-worker".to_string(),
+        worker_version: None,
+        worker_region: None,
         event_id,
         outcome: ReportOutcome::Delivered,
         retryable: true,
@@ -851,6 +1137,9 @@ async fn report_lease_ownership_conflict_expired_lease() {
             response_body: None,
             error_kind: None,
             error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
         },
     };
 
@@ -917,6 +1206,8 @@ async fn report_max_attempts_forces_dead() {
 
     let report_req = ReportRequest {
         worker_id: "test-worker".to_string(),
+        worker_version: None,
+        worker_region: None,
         event_id,
         outcome: ReportOutcome::Retry,
         retryable: true,
@@ -931,6 +1222,9 @@ async fn report_max_attempts_forces_dead() {
             response_body: Some("Service Unavailable".to_string()),
             error_kind: None,
             error_message: Some("Connection timed out".to_string()),
+            final_url: None,
+            peer_address: None,
+            timing: None,
         },
     };
 
@@ -992,6 +1286,8 @@ async fn report_under_max_attempts_preserves_retry() {
 
     let report_req = ReportRequest {
         worker_id: "test-worker".to_string(),
+        worker_version: None,
+        worker_region: None,
         event_id,
         outcome: ReportOutcome::Retry,
         retryable: true,
@@ -1006,6 +1302,9 @@ async fn report_under_max_attempts_preserves_retry() {
             response_body: None,
             error_kind: None,
             error_message: Some("Server error".to_string()),
+            final_url: None,
+            peer_address: None,
+            timing: None,
         },
     };
 
@@ -1058,6 +1357,8 @@ async fn report_max_attempts_overrides_delivered() {
 
     let report_req = ReportRequest {
         worker_id: "test-worker".to_string(),
+        worker_version: None,
+        worker_region: None,
         event_id,
         outcome: ReportOutcome::Delivered,
         retryable: false,
@@ -1072,6 +1373,9 @@ async fn report_max_attempts_overrides_delivered() {
             response_body: Some(r#"{"ok":true}"#.to_string()),
             error_kind: None,
             error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
         },
     };
 
@@ -1127,6 +1431,8 @@ async fn report_final_outcome_returned_in_result() {
     let config = DispatcherConfig::default();
     let report_req = ReportRequest {
         worker_id: "worker".to_string(),
+        worker_version: None,
+        worker_region: None,
         event_id,
         outcome: ReportOutcome::Delivered,
         retryable: false,
@@ -1141,6 +1447,9 @@ async fn report_final_outcome_returned_in_result() {
             response_body: None,
             error_kind: None,
             error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
         },
     };
 
@@ -1154,3 +1463,2929 @@ async fn report_final_outcome_returned_in_result() {
         "final_outcome should match reported outcome"
     );
 }
+
+#[tokio::test]
+async fn global_pause_halts_leasing_until_resumed() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let endpoint_id = seed_endpoint(&pool).await;
+    let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+
+    let paused = set_dispatch_paused(&pool, true).await.expect("pause");
+    assert!(paused.paused);
+    assert!(paused.updated_at.is_some());
+
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease while paused");
+    assert!(events.is_empty(), "paused dispatch should lease nothing");
+
+    let status: String = sqlx::query_scalar("SELECT status FROM webhook_events WHERE id = ?")
+        .bind(event_id.to_string())
+        .fetch_one(&pool)
+        .await
+        .expect("fetch status");
+    assert_eq!(status, "pending", "paused events stay pending");
+
+    let resumed = set_dispatch_paused(&pool, false).await.expect("resume");
+    assert!(!resumed.paused);
+
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease after resume");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event.id, event_id);
+}
+
+#[tokio::test]
+async fn maintenance_window_defers_endpoint_events() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let endpoint_id = seed_endpoint(&pool).await;
+    let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+
+    let now = Utc::now();
+    let minute_of_day = i64::from(now.time().num_seconds_from_midnight() / 60);
+    sqlx::query(
+        "INSERT INTO endpoint_maintenance_windows \
+        (id, endpoint_id, day_of_week, start_minute, duration_minutes) \
+        VALUES (?, ?, NULL, ?, 60)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(endpoint_id.to_string())
+    .bind((minute_of_day - 10).rem_euclid(24 * 60))
+    .execute(&pool)
+    .await
+    .expect("insert maintenance window");
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events");
+    assert!(
+        events.is_empty(),
+        "endpoint in maintenance should be skipped"
+    );
+
+    let next_attempt_at: Option<String> =
+        sqlx::query_scalar("SELECT next_attempt_at FROM webhook_events WHERE id = ?")
+            .bind(event_id.to_string())
+            .fetch_one(&pool)
+            .await
+            .expect("fetch next_attempt_at");
+    let next_attempt_at = chrono::DateTime::parse_from_rfc3339(
+        next_attempt_at
+            .as_deref()
+            .expect("next_attempt_at pushed past window"),
+    )
+    .expect("valid timestamp");
+    let remaining = next_attempt_at.with_timezone(&Utc) - now;
+    assert!(remaining > Duration::minutes(48) && remaining <= Duration::minutes(51));
+}
+
+#[test]
+fn maintenance_window_end_merges_chained_and_weekly_windows() {
+    let at = |value: &str| {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .expect("valid timestamp")
+            .with_timezone(&Utc)
+    };
+    let windows = vec![
+        // Daily 23:00-01:00, spanning midnight.
+        MaintenanceWindow {
+            day_of_week: None,
+            start_minute: 23 * 60,
+            duration_minutes: 120,
+        },
+        // Mondays 01:00-03:00, chained onto the daily window.
+        MaintenanceWindow {
+            day_of_week: Some(0),
+            start_minute: 60,
+            duration_minutes: 120,
+        },
+    ];
+
+    // 2026-01-05 is a Monday.
+    assert_eq!(
+        maintenance_window_end(&windows, at("2026-01-05T00:30:00Z")),
+        Some(at("2026-01-05T03:00:00Z"))
+    );
+    assert_eq!(
+        maintenance_window_end(&windows, at("2026-01-06T00:30:00Z")),
+        Some(at("2026-01-06T01:00:00Z"))
+    );
+    assert_eq!(
+        maintenance_window_end(&windows, at("2026-01-06T12:00:00Z")),
+        None
+    );
+}
+
+#[tokio::test]
+async fn events_outside_delivery_windows_wait_for_the_next_window() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let endpoint_id = seed_endpoint(&pool).await;
+    let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+
+    // UTC+02:00, with the only window opening two local hours from now.
+    let now = Utc::now();
+    let local_minute = i64::from(now.time().num_seconds_from_midnight() / 60) + 120;
+    set_delivery_windows(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        120,
+        &[DeliveryWindow {
+            day_of_week: None,
+            start_minute: (local_minute + 120).rem_euclid(24 * 60),
+            duration_minutes: 60,
+        }],
+    )
+    .await
+    .expect("set delivery windows");
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events");
+    assert!(events.is_empty(), "endpoint outside its window is skipped");
+
+    let next_attempt_at: Option<String> =
+        sqlx::query_scalar("SELECT next_attempt_at FROM webhook_events WHERE id = ?")
+            .bind(event_id.to_string())
+            .fetch_one(&pool)
+            .await
+            .expect("fetch next_attempt_at");
+    let next_attempt_at = chrono::DateTime::parse_from_rfc3339(
+        next_attempt_at
+            .as_deref()
+            .expect("next_attempt_at moved to window start"),
+    )
+    .expect("valid timestamp");
+    let wait = next_attempt_at.with_timezone(&Utc) - now;
+    assert!(wait > Duration::minutes(118) && wait <= Duration::minutes(120));
+
+    set_delivery_windows(&pool, &EndpointScope::All, endpoint_id, 0, &[])
+        .await
+        .expect("clear delivery windows");
+    sqlx::query("UPDATE webhook_events SET next_attempt_at = NULL WHERE id = ?")
+        .bind(event_id.to_string())
+        .execute(&pool)
+        .await
+        .expect("make event due");
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events");
+    assert_eq!(events.len(), 1);
+}
+
+#[test]
+fn delivery_windows_follow_local_business_hours() {
+    let at = |value: &str| {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .expect("valid timestamp")
+            .with_timezone(&Utc)
+    };
+    // Weekdays 09:00-17:00 at UTC-05:00.
+    let business_hours: Vec<DeliveryWindow> = (0..5)
+        .map(|day| DeliveryWindow {
+            day_of_week: Some(day),
+            start_minute: 9 * 60,
+            duration_minutes: 8 * 60,
+        })
+        .collect();
+
+    // 2026-01-09 is a Friday; 23:00Z is 18:00 local.
+    assert_eq!(
+        next_delivery_window_start(&business_hours, -300, at("2026-01-09T23:00:00Z")),
+        Some(at("2026-01-12T14:00:00Z"))
+    );
+    assert_eq!(
+        next_delivery_window_start(&business_hours, -300, at("2026-01-12T15:00:00Z")),
+        None
+    );
+    assert_eq!(
+        next_delivery_window_start(&[], -300, at("2026-01-10T12:00:00Z")),
+        None
+    );
+
+    // Maintenance covering the first hour of the window pushes delivery on.
+    let maintenance = vec![MaintenanceWindow {
+        day_of_week: None,
+        start_minute: 14 * 60,
+        duration_minutes: 60,
+    }];
+    assert_eq!(
+        deliverable_from(
+            &maintenance,
+            &business_hours,
+            -300,
+            at("2026-01-09T23:00:00Z")
+        ),
+        Some(at("2026-01-12T15:00:00Z"))
+    );
+}
+
+#[tokio::test]
+async fn paused_group_is_skipped_by_lease() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let grouped = seed_endpoint(&pool).await;
+    let ungrouped = seed_endpoint(&pool).await;
+    let group = create_endpoint_group(&pool, "customer-a", None)
+        .await
+        .expect("create group");
+    set_endpoint_group(&pool, grouped, Some(group.id))
+        .await
+        .expect("assign group");
+    set_group_paused(&pool, group.id, true)
+        .await
+        .expect("pause group");
+
+    seed_event(&pool, grouped, "pending", None, None, None).await;
+    let free_event = seed_event(&pool, ungrouped, "pending", None, None, None).await;
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event.id, free_event);
+
+    set_group_paused(&pool, group.id, false)
+        .await
+        .expect("resume group");
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease after resume");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event.endpoint_id, grouped);
+}
+
+#[tokio::test]
+async fn drip_replays_are_not_leased_before_they_are_due() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let endpoint_id = seed_endpoint(&pool).await;
+    let group = create_endpoint_group(&pool, "customer-a", None)
+        .await
+        .expect("create group");
+    set_endpoint_group(&pool, endpoint_id, Some(group.id))
+        .await
+        .expect("assign group");
+    for _ in 0..20 {
+        seed_event(&pool, endpoint_id, "dead", None, None, None).await;
+    }
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    // A worker keeps leasing while the replay creates its events, taking
+    // the connection between the replay's transactions.
+    let done = AtomicBool::new(false);
+    let replay = async {
+        let result = replay_group(&pool, group.id, false, false, Some(1), None, None).await;
+        done.store(true, Ordering::SeqCst);
+        result
+    };
+    let lease = async {
+        let mut leased = Vec::new();
+        loop {
+            let finished = done.load(Ordering::SeqCst);
+            let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+                .await
+                .expect("lease events");
+            leased.extend(events.into_iter().map(|leased| leased.event.id));
+            if finished {
+                break leased;
+            }
+            tokio::task::yield_now().await;
+        }
+    };
+    let (replayed, leased) = tokio::join!(replay, lease);
+    let replayed = replayed.expect("replay group").replayed_event_ids;
+    assert_eq!(replayed.len(), 20);
+    // Only the first copy is due right away; the rest wait a minute each.
+    assert_eq!(leased, vec![replayed[0]]);
+}
+
+#[tokio::test]
+async fn reprioritized_events_are_leased_first() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let endpoint_id = seed_endpoint(&pool).await;
+    let mut events = Vec::new();
+    for (received_at, event_type) in [
+        ("2024-01-01T00:00:00Z", "customer.created"),
+        ("2024-01-01T00:00:01Z", "invoice.paid"),
+        ("2024-01-01T00:00:02Z", "invoice.voided"),
+    ] {
+        let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+        sqlx::query("UPDATE webhook_events SET received_at = ?, event_type = ? WHERE id = ?")
+            .bind(received_at)
+            .bind(event_type)
+            .bind(event_id.to_string())
+            .execute(&pool)
+            .await
+            .expect("set event type");
+        events.push(event_id);
+    }
+
+    let filter = QueuedEventFilter {
+        target: DeadEventTarget::Endpoint(endpoint_id),
+        provider: None,
+        event_type: Some("invoice.*".to_string()),
+        received_since: None,
+        received_before: None,
+    };
+    assert_eq!(
+        count_events_to_reprioritize(&pool, &filter, 10)
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        reprioritize_events_batch(&pool, &filter, 10, 1)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        reprioritize_events_batch(&pool, &filter, 10, 500)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        count_events_to_reprioritize(&pool, &filter, 10)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let req = LeaseRequest {
+        limit: 1,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let mut leased = Vec::new();
+    for _ in 0..3 {
+        let batch = lease_events(&pool, &DispatcherConfig::default(), &req)
+            .await
+            .expect("lease events");
+        leased.extend(batch.into_iter().map(|leased| leased.event.id));
+    }
+    assert_eq!(leased, [events[1], events[2], events[0]]);
+}
+
+#[tokio::test]
+async fn group_rate_limit_caps_leases_per_minute() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let first = seed_endpoint(&pool).await;
+    let second = seed_endpoint(&pool).await;
+    let group = create_endpoint_group(&pool, "customer-a", Some(3))
+        .await
+        .expect("create group");
+    for endpoint_id in [first, second] {
+        set_endpoint_group(&pool, endpoint_id, Some(group.id))
+            .await
+            .expect("assign group");
+        for _ in 0..2 {
+            seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+        }
+    }
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events");
+    assert_eq!(events.len(), 3, "group budget caps the lease");
+
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("second lease");
+    assert!(
+        events.is_empty(),
+        "in-flight deliveries count against the budget"
+    );
+}
+
+#[tokio::test]
+async fn expired_events_are_not_leased_and_get_swept() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let endpoint_id = seed_endpoint(&pool).await;
+    let stale = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    let fresh = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    let in_flight_expiry = (Utc::now() + Duration::seconds(30)).to_rfc3339();
+    let leased = seed_event(
+        &pool,
+        endpoint_id,
+        "in_flight",
+        None,
+        Some(&in_flight_expiry),
+        Some("worker-0"),
+    )
+    .await;
+
+    let past = (Utc::now() - Duration::minutes(1)).to_rfc3339();
+    let future = (Utc::now() + Duration::hours(1)).to_rfc3339();
+    for (id, expires_at) in [(stale, &past), (fresh, &future), (leased, &past)] {
+        sqlx::query("UPDATE webhook_events SET expires_at = ? WHERE id = ?")
+            .bind(expires_at)
+            .bind(id.to_string())
+            .execute(&pool)
+            .await
+            .expect("set expires_at");
+    }
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event.id, fresh);
+    assert_eq!(events[0].event.expires_at.as_deref(), Some(future.as_str()));
+
+    let expired = expire_events(&pool).await.expect("expire events");
+    assert_eq!(expired, 1, "only queued events are expired");
+
+    let status_of = |id: Uuid| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query_scalar::<_, String>("SELECT status FROM webhook_events WHERE id = ?")
+                .bind(id.to_string())
+                .fetch_one(&pool)
+                .await
+                .expect("fetch status")
+        }
+    };
+    assert_eq!(status_of(stale).await, "expired");
+    assert_eq!(status_of(leased).await, "in_flight");
+}
+
+#[tokio::test]
+async fn report_returns_endpoint_stats() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+
+    let now = Utc::now();
+    let lease_expires_at = (now + Duration::hours(1)).to_rfc3339();
+    let event_id = seed_event(
+        &pool,
+        endpoint_id,
+        "in_flight",
+        None,
+        Some(&lease_expires_at),
+        Some("test-worker"),
+    )
+    .await;
+
+    let report_req = ReportRequest {
+        worker_id: "test-worker".to_string(),
+        worker_version: None,
+        worker_region: None,
+        event_id,
+        outcome: ReportOutcome::Retry,
+        retryable: true,
+        next_attempt_at: None,
+        attempt: ReportAttempt {
+            started_at: (now - Duration::seconds(1)).to_rfc3339(),
+            finished_at: now.to_rfc3339(),
+            request_headers: BTreeMap::new(),
+            request_body: "{}".to_string(),
+            response_status: Some(503),
+            response_headers: None,
+            response_body: None,
+            error_kind: None,
+            error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
+        },
+    };
+    let config = DispatcherConfig {
+        circuit_failure_threshold: 1,
+        max_attempts: 5,
+        ..Default::default()
+    };
+
+    let result = report_delivery(&pool, &config, &report_req)
+        .await
+        .expect("report delivery");
+
+    assert_eq!(result.final_outcome, ReportOutcome::Retry);
+    assert_eq!(result.endpoint_stats.consecutive_failures, 1);
+    assert_eq!(result.endpoint_stats.attempts_remaining, 4);
+    let cooldown = result
+        .endpoint_stats
+        .circuit_cooldown_remaining_ms
+        .expect("circuit should be open");
+    assert!(cooldown > 0 && cooldown <= i64::try_from(config.circuit_cooldown_base_ms).unwrap());
+
+    let transitions: Vec<String> =
+        sqlx::query_scalar("SELECT state FROM target_circuit_transitions WHERE endpoint_id = ?")
+            .bind(endpoint_id.to_string())
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(transitions, ["open"]);
+}
+
+#[tokio::test]
+async fn fault_injection_turns_delivered_report_into_retry() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let healthy_endpoint_id = seed_endpoint(&pool).await;
+
+    let now = Utc::now();
+    let lease_expires_at = (now + Duration::hours(1)).to_rfc3339();
+    let faulty = seed_event_with_attempts(
+        &pool,
+        endpoint_id,
+        "in_flight",
+        None,
+        Some(&lease_expires_at),
+        Some("test-worker"),
+        0,
+    )
+    .await;
+    let healthy = seed_event_with_attempts(
+        &pool,
+        healthy_endpoint_id,
+        "in_flight",
+        None,
+        Some(&lease_expires_at),
+        Some("test-worker"),
+        0,
+    )
+    .await;
+    set_fault_injection(&pool, &EndpointScope::All, endpoint_id, 1.0, 0)
+        .await
+        .unwrap();
+
+    let delivered = |event_id| ReportRequest {
+        worker_id: "test-worker".to_string(),
+        worker_version: None,
+        worker_region: None,
+        event_id,
+        outcome: ReportOutcome::Delivered,
+        retryable: false,
+        next_attempt_at: None,
+        attempt: ReportAttempt {
+            started_at: now.to_rfc3339(),
+            finished_at: now.to_rfc3339(),
+            request_headers: BTreeMap::new(),
+            request_body: "{}".to_string(),
+            response_status: Some(200),
+            response_headers: None,
+            response_body: Some("ok".to_string()),
+            error_kind: None,
+            error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
+        },
+    };
+    let config = DispatcherConfig::default();
+
+    let mut req = delivered(faulty);
+    inject_faults(&pool, &mut req).await.unwrap();
+    assert_eq!(req.outcome, ReportOutcome::Retry);
+    assert_eq!(
+        req.attempt.error_message.as_deref(),
+        Some(INJECTED_FAILURE_MESSAGE)
+    );
+    let result = report_delivery(&pool, &config, &req).await.unwrap();
+    assert_eq!(result.final_outcome, ReportOutcome::Retry);
+    assert_eq!(result.endpoint_stats.consecutive_failures, 1);
+
+    let mut req = delivered(healthy);
+    inject_faults(&pool, &mut req).await.unwrap();
+    assert_eq!(req.outcome, ReportOutcome::Delivered);
+    let result = report_delivery(&pool, &config, &req).await.unwrap();
+    assert_eq!(result.final_outcome, ReportOutcome::Delivered);
+}
+
+#[tokio::test]
+async fn retries_follow_the_endpoint_backoff_strategy() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let intervals_endpoint = seed_endpoint(&pool).await;
+    let linear_endpoint = seed_endpoint(&pool).await;
+    let default_endpoint = seed_endpoint(&pool).await;
+
+    let intervals = BackoffStrategy::Intervals {
+        intervals: "1m,5m,30m,2h".to_string(),
+    };
+    assert!(validate_backoff(&intervals).is_ok());
+    for invalid in ["", "5x", "0m", "1m,,5m", "8d"] {
+        let strategy = BackoffStrategy::Intervals {
+            intervals: invalid.to_string(),
+        };
+        assert!(validate_backoff(&strategy).is_err(), "{invalid:?}");
+    }
+    assert!(
+        validate_backoff(&BackoffStrategy::Linear {
+            step_secs: 120,
+            max_secs: 60,
+        })
+        .is_err()
+    );
+    assert_eq!(retry_delay_secs(&intervals, 9, Utc::now()), Ok(7200));
+
+    let linear = BackoffStrategy::Linear {
+        step_secs: 30,
+        max_secs: 3600,
+    };
+    set_endpoint_backoff(
+        &pool,
+        &EndpointScope::All,
+        intervals_endpoint,
+        Some(&intervals),
+        "test",
+    )
+    .await
+    .unwrap();
+    set_endpoint_backoff(
+        &pool,
+        &EndpointScope::All,
+        linear_endpoint,
+        Some(&linear),
+        "test",
+    )
+    .await
+    .unwrap();
+    let stored = get_endpoint_backoff(&pool, &EndpointScope::All, intervals_endpoint)
+        .await
+        .unwrap();
+    assert_eq!(stored.strategy, Some(intervals));
+
+    let now = Utc::now();
+    let lease_expires_at = (now + Duration::hours(1)).to_rfc3339();
+    let config = DispatcherConfig::default();
+    for (endpoint_id, expected_delay) in [
+        (intervals_endpoint, Duration::minutes(5)),
+        (linear_endpoint, Duration::seconds(60)),
+        (default_endpoint, Duration::seconds(2)),
+    ] {
+        let event_id = seed_event_with_attempts(
+            &pool,
+            endpoint_id,
+            "in_flight",
+            None,
+            Some(&lease_expires_at),
+            Some("test-worker"),
+            1,
+        )
+        .await;
+        let req = ReportRequest {
+            worker_id: "test-worker".to_string(),
+            worker_version: None,
+            worker_region: None,
+            event_id,
+            outcome: ReportOutcome::Retry,
+            retryable: true,
+            next_attempt_at: None,
+            attempt: ReportAttempt {
+                started_at: now.to_rfc3339(),
+                finished_at: now.to_rfc3339(),
+                request_headers: BTreeMap::new(),
+                request_body: "{}".to_string(),
+                response_status: Some(503),
+                response_headers: None,
+                response_body: None,
+                error_kind: None,
+                error_message: None,
+                final_url: None,
+                peer_address: None,
+                timing: None,
+            },
+        };
+        let before = Utc::now();
+        report_delivery(&pool, &config, &req).await.unwrap();
+        let next_attempt_at: String =
+            sqlx::query_scalar("SELECT next_attempt_at FROM webhook_events WHERE id = ?")
+                .bind(event_id.to_string())
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let delay = chrono::DateTime::parse_from_rfc3339(&next_attempt_at)
+            .unwrap()
+            .with_timezone(&Utc)
+            - before;
+        assert!(
+            (delay - expected_delay).num_seconds().abs() <= 1,
+            "{endpoint_id}: {delay}"
+        );
+    }
+}
+
+#[test]
+fn cron_schedules_fire_at_matching_minutes() {
+    let at = |value: &str| {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    };
+    let next = |expression: &str, from: &str| {
+        CronSchedule::parse(expression)
+            .unwrap()
+            .next_after(at(from))
+            .map(|fire| fire.to_rfc3339())
+    };
+
+    // 2026-03-04 is a Wednesday.
+    assert_eq!(
+        next("@hourly", "2026-03-04T10:00:00Z").as_deref(),
+        Some("2026-03-04T11:00:00+00:00")
+    );
+    assert_eq!(
+        next("*/15 9-17 * * 1-5", "2026-03-04T17:50:30Z").as_deref(),
+        Some("2026-03-05T09:00:00+00:00")
+    );
+    assert_eq!(
+        next("30 6 * * 7", "2026-03-04T10:00:00Z").as_deref(),
+        Some("2026-03-08T06:30:00+00:00")
+    );
+    // Either restricted day field matches: the 1st or any Friday.
+    assert_eq!(
+        next("0 0 1 * 5", "2026-03-04T10:00:00Z").as_deref(),
+        Some("2026-03-06T00:00:00+00:00")
+    );
+    assert_eq!(
+        next("0 12 29 2 *", "2026-03-04T10:00:00Z").as_deref(),
+        Some("2028-02-29T12:00:00+00:00")
+    );
+    assert_eq!(next("0 0 30 2 *", "2026-03-04T10:00:00Z"), None);
+
+    for invalid in [
+        "* * * *",
+        "60 * * * *",
+        "*/0 * * * *",
+        "5-1 * * * *",
+        "a * * * *",
+    ] {
+        assert!(CronSchedule::parse(invalid).is_err(), "{invalid:?}");
+    }
+    let never = BackoffStrategy::Cron {
+        expression: "0 0 30 2 *".to_string(),
+    };
+    assert!(validate_backoff(&never).is_err());
+}
+
+#[tokio::test]
+async fn cron_backoff_retries_at_the_next_firing() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    set_endpoint_backoff(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        Some(&BackoffStrategy::Cron {
+            expression: "0 * * * *".to_string(),
+        }),
+        "test",
+    )
+    .await
+    .unwrap();
+
+    let now = Utc::now();
+    let lease_expires_at = (now + Duration::hours(1)).to_rfc3339();
+    let event_id = seed_event_with_attempts(
+        &pool,
+        endpoint_id,
+        "in_flight",
+        None,
+        Some(&lease_expires_at),
+        Some("test-worker"),
+        0,
+    )
+    .await;
+    let req = ReportRequest {
+        worker_id: "test-worker".to_string(),
+        worker_version: None,
+        worker_region: None,
+        event_id,
+        outcome: ReportOutcome::Retry,
+        retryable: true,
+        next_attempt_at: None,
+        attempt: ReportAttempt {
+            started_at: now.to_rfc3339(),
+            finished_at: now.to_rfc3339(),
+            request_headers: BTreeMap::new(),
+            request_body: "{}".to_string(),
+            response_status: Some(503),
+            response_headers: None,
+            response_body: None,
+            error_kind: None,
+            error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
+        },
+    };
+    let before = Utc::now();
+    report_delivery(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .unwrap();
+
+    let next_attempt_at: String =
+        sqlx::query_scalar("SELECT next_attempt_at FROM webhook_events WHERE id = ?")
+            .bind(event_id.to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    let next_attempt_at = chrono::DateTime::parse_from_rfc3339(&next_attempt_at)
+        .unwrap()
+        .with_timezone(&Utc);
+    assert_eq!((next_attempt_at.minute(), next_attempt_at.second()), (0, 0));
+    assert!(next_attempt_at > before);
+    assert!(next_attempt_at <= before + Duration::hours(1));
+}
+
+#[tokio::test]
+async fn lease_bench_delivers_every_seeded_event() {
+    let test_db = setup_db_shared(4).await;
+    let pool = test_db.pool;
+
+    let config = LeaseBenchConfig {
+        events: 40,
+        workers: 3,
+        batch_size: 7,
+        lease_ms: 30_000,
+    };
+    let report = run_lease_bench(&pool, &config).await.unwrap();
+    assert_eq!(report.delivered, 40);
+
+    let delivered: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM webhook_events WHERE status = 'delivered'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(delivered, 40);
+}
+
+#[tokio::test]
+async fn selftest_runs_the_pipeline_without_touching_other_events() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let endpoint_id = seed_endpoint(&pool).await;
+    let bystander = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+
+    let report = run_selftest(&pool, &DispatcherConfig::default()).await;
+    assert!(report.passed, "{report:?}");
+    let names: Vec<&str> = report.steps.iter().map(|step| step.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "setup", "ingest", "lease", "deliver", "report", "verify", "cleanup"
+        ]
+    );
+
+    let status: String = sqlx::query_scalar("SELECT status FROM webhook_events WHERE id = ?")
+        .bind(bystander.to_string())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "pending");
+
+    let leftovers: i64 = sqlx::query_scalar(
+        "SELECT (SELECT COUNT(*) FROM endpoints WHERE id = ?1) \
+            + (SELECT COUNT(*) FROM webhook_events WHERE endpoint_id = ?1) \
+            + (SELECT COUNT(*) FROM delivery_stats_hourly WHERE endpoint_id = ?1)",
+    )
+    .bind(report.endpoint_id.to_string())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(leftovers, 0);
+
+    set_dispatch_paused(&pool, true).await.unwrap();
+    let report = run_selftest(&pool, &DispatcherConfig::default()).await;
+    assert!(!report.passed);
+    let lease = report
+        .steps
+        .iter()
+        .find(|step| step.name == "lease")
+        .unwrap();
+    assert!(!lease.passed);
+    assert!(
+        report
+            .steps
+            .last()
+            .is_some_and(|step| step.name == "cleanup" && step.passed)
+    );
+}
+
+#[tokio::test]
+async fn lease_merges_throttled_group_and_ungrouped_events_by_age() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+
+    let throttled = seed_endpoint(&pool).await;
+    let ungrouped = seed_endpoint(&pool).await;
+    let group = create_endpoint_group(&pool, "throttled", Some(2))
+        .await
+        .expect("create group");
+    set_endpoint_group(&pool, throttled, Some(group.id))
+        .await
+        .expect("assign group");
+
+    let mut throttled_ids = Vec::new();
+    for _ in 0..3 {
+        throttled_ids.push(seed_event(&pool, throttled, "pending", None, None, None).await);
+    }
+    let mut ungrouped_ids = Vec::new();
+    for _ in 0..3 {
+        ungrouped_ids.push(seed_event(&pool, ungrouped, "pending", None, None, None).await);
+    }
+
+    let req = LeaseRequest {
+        limit: 4,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let leased: HashSet<Uuid> = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease events")
+        .iter()
+        .map(|leased| leased.event.id)
+        .collect();
+
+    let expected: HashSet<Uuid> = throttled_ids[..2]
+        .iter()
+        .chain(&ungrouped_ids[..2])
+        .copied()
+        .collect();
+    assert_eq!(
+        leased, expected,
+        "oldest events first, throttled group capped at its budget"
+    );
+}
+
+#[tokio::test]
+async fn starvation_guard_boosts_old_due_events_but_keeps_backoff() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+
+    let now = Utc::now();
+    let far_future = (now + Duration::days(1)).to_rfc3339();
+    let deferred = seed_event_with_attempts(
+        &pool,
+        endpoint_id,
+        "pending",
+        Some(&far_future),
+        None,
+        None,
+        3,
+    )
+    .await;
+    let old = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    for id in [deferred, old] {
+        sqlx::query("UPDATE webhook_events SET received_at = ? WHERE id = ?")
+            .bind((now - Duration::hours(2)).to_rfc3339())
+            .bind(id.to_string())
+            .execute(&pool)
+            .await
+            .expect("age event");
+    }
+    let urgent = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    sqlx::query("UPDATE webhook_events SET priority = 1 WHERE id = ?")
+        .bind(urgent.to_string())
+        .execute(&pool)
+        .await
+        .expect("prioritize event");
+
+    let req = LeaseRequest {
+        limit: 1,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let events = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease without guard");
+    assert_eq!(
+        events[0].event.id, urgent,
+        "priority wins without the guard"
+    );
+    sqlx::query(
+        "UPDATE webhook_events SET status = 'pending', lease_expires_at = NULL, \
+            leased_by = NULL WHERE id = ?",
+    )
+    .bind(urgent.to_string())
+    .execute(&pool)
+    .await
+    .expect("release lease");
+
+    // Two hours at a one-hour wait outranks one priority level.
+    let guarded = DispatcherConfig {
+        starvation_max_wait_ms: Some(60 * 60 * 1000),
+        ..Default::default()
+    };
+    let events = lease_events(&pool, &guarded, &req)
+        .await
+        .expect("lease with guard");
+    assert_eq!(events[0].event.id, old);
+
+    let req = LeaseRequest { limit: 10, ..req };
+    let events = lease_events(&pool, &guarded, &req)
+        .await
+        .expect("lease the rest");
+    let leased: Vec<Uuid> = events.iter().map(|leased| leased.event.id).collect();
+    assert_eq!(leased, [urgent], "retries still wait for next_attempt_at");
+}
+
+#[tokio::test]
+async fn failures_only_capture_drops_successful_response_bodies() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+
+    let now = Utc::now();
+    let lease_expires_at = (now + Duration::hours(1)).to_rfc3339();
+    let config = DispatcherConfig {
+        success_response_sample_percent: 0,
+        ..Default::default()
+    };
+
+    let mut captured = Vec::new();
+    for outcome in [ReportOutcome::Delivered, ReportOutcome::Retry] {
+        let event_id = seed_event(
+            &pool,
+            endpoint_id,
+            "in_flight",
+            None,
+            Some(&lease_expires_at),
+            Some("test-worker"),
+        )
+        .await;
+        let report_req = ReportRequest {
+            worker_id: "test-worker".to_string(),
+            worker_version: None,
+            worker_region: None,
+            event_id,
+            outcome,
+            retryable: true,
+            next_attempt_at: None,
+            attempt: ReportAttempt {
+                started_at: now.to_rfc3339(),
+                finished_at: now.to_rfc3339(),
+                request_headers: BTreeMap::new(),
+                request_body: "{}".to_string(),
+                response_status: Some(if outcome == ReportOutcome::Delivered {
+                    200
+                } else {
+                    503
+                }),
+                response_headers: None,
+                response_body: Some("body".to_string()),
+                error_kind: None,
+                error_message: None,
+                final_url: None,
+                peer_address: None,
+                timing: None,
+            },
+        };
+        report_delivery(&pool, &config, &report_req)
+            .await
+            .expect("report delivery");
+
+        captured.push(
+            sqlx::query_as::<_, (Option<String>, Option<String>)>(
+                "SELECT response_body, response_capture FROM webhook_attempt_logs WHERE event_id = ?",
+            )
+            .bind(event_id.to_string())
+            .fetch_one(&pool)
+            .await
+            .expect("attempt log should exist"),
+        );
+    }
+
+    assert_eq!(captured[0], (None, Some("dropped".to_string())));
+    assert_eq!(
+        captured[1],
+        (Some("body".to_string()), Some("failure".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn renew_extends_only_the_owners_live_lease() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let now = Utc::now();
+
+    let live = (now + Duration::seconds(30)).to_rfc3339();
+    let event_id = seed_event(
+        &pool,
+        endpoint_id,
+        "in_flight",
+        None,
+        Some(&live),
+        Some("worker-a"),
+    )
+    .await;
+    let expired = (now - Duration::seconds(1)).to_rfc3339();
+    let expired_id = seed_event(
+        &pool,
+        endpoint_id,
+        "in_flight",
+        None,
+        Some(&expired),
+        Some("worker-a"),
+    )
+    .await;
+
+    let renew = |worker_id: &str, event_id| RenewRequest {
+        worker_id: worker_id.to_string(),
+        event_id,
+        lease_ms: 600_000,
+    };
+
+    let lease_expires_at = renew_lease(&pool, &renew("worker-a", event_id))
+        .await
+        .expect("renew live lease");
+    let extended = chrono::DateTime::parse_from_rfc3339(&lease_expires_at).unwrap();
+    assert!(extended > now + Duration::minutes(9));
+
+    let stored: String =
+        sqlx::query_scalar("SELECT lease_expires_at FROM webhook_events WHERE id = ?")
+            .bind(event_id.to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(stored, lease_expires_at);
+
+    for (req, expected) in [
+        (renew("worker-b", event_id), "lease_not_owned"),
+        (renew("worker-a", expired_id), "lease_expired"),
+        (renew("worker-a", Uuid::new_v4()), "lease_missing"),
+    ] {
+        let result = renew_lease(&pool, &req).await;
+        assert!(
+            matches!(&result, Err(StoreError::Conflict(message)) if message == expected),
+            "expected {expected} conflict, got {result:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn attempts_record_the_endpoint_revision_they_were_leased_against() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+
+    let revision = set_endpoint_target(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        "https://new.example.com/webhook",
+        "admin:abc",
+    )
+    .await
+    .expect("set target");
+    assert_eq!(revision.revision, 2);
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let leased = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease");
+    assert_eq!(leased[0].target_url, "https://new.example.com/webhook");
+    assert_eq!(leased[0].target_revision, 2);
+
+    // A later change must not rewrite where the leased attempt went.
+    set_endpoint_target(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        "https://newer.example.com/webhook",
+        "admin:abc",
+    )
+    .await
+    .expect("set target again");
+
+    let now = Utc::now().to_rfc3339();
+    let report = ReportRequest {
+        worker_id: "worker-1".to_string(),
+        worker_version: None,
+        worker_region: None,
+        event_id,
+        outcome: ReportOutcome::Delivered,
+        retryable: false,
+        next_attempt_at: None,
+        attempt: ReportAttempt {
+            started_at: now.clone(),
+            finished_at: now,
+            request_headers: BTreeMap::new(),
+            request_body: "{}".to_string(),
+            response_status: Some(200),
+            response_headers: None,
+            response_body: None,
+            error_kind: None,
+            error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
+        },
+    };
+    report_delivery(&pool, &DispatcherConfig::default(), &report)
+        .await
+        .expect("report");
+
+    let attempts = list_attempts(&pool, &EndpointScope::All, event_id)
+        .await
+        .expect("list attempts");
+    assert_eq!(attempts.attempts[0].target_revision, Some(2));
+
+    let history = list_endpoint_revisions(&pool, &EndpointScope::All, endpoint_id)
+        .await
+        .expect("list revisions");
+    let summary: Vec<(i64, &str, Option<&str>)> = history
+        .revisions
+        .iter()
+        .map(|r| (r.revision, r.target_url.as_str(), r.changed_by.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (3, "https://newer.example.com/webhook", Some("admin:abc")),
+            (2, "https://new.example.com/webhook", Some("admin:abc")),
+            (1, "https://example.com/webhook", None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn endpoint_revisions_diff_delivery_settings() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+
+    set_endpoint_timeouts(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        Some(5_000),
+        None,
+        "admin:abc",
+    )
+    .await
+    .expect("set timeouts");
+    // Unchanged settings do not make a revision.
+    set_endpoint_timeouts(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        Some(5_000),
+        None,
+        "admin:abc",
+    )
+    .await
+    .expect("set timeouts again");
+    let policy = RedirectPolicy {
+        mode: RedirectMode::SameHost,
+        max_redirects: 3,
+    };
+    set_endpoint_redirect_policy(&pool, &EndpointScope::All, endpoint_id, policy, "ops:1")
+        .await
+        .expect("set redirects");
+    let revision = set_endpoint_target(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        "https://new.example.com/webhook",
+        "ops:1",
+    )
+    .await
+    .expect("set target");
+    assert_eq!(revision.revision, 4);
+    assert_eq!(
+        revision.changes,
+        [EndpointRevisionChange {
+            field: "target_url".to_string(),
+            from: Some("https://example.com/webhook".to_string()),
+            to: Some("https://new.example.com/webhook".to_string()),
+        }]
+    );
+
+    let history = list_endpoint_revisions(&pool, &EndpointScope::All, endpoint_id)
+        .await
+        .expect("list revisions");
+    let changes: Vec<_> = history
+        .revisions
+        .iter()
+        .map(|r| {
+            let fields = r
+                .changes
+                .iter()
+                .map(|c| (c.field.as_str(), c.from.as_deref(), c.to.as_deref()))
+                .collect::<Vec<_>>();
+            (r.revision, r.changed_by.as_deref(), fields)
+        })
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (
+                4,
+                Some("ops:1"),
+                vec![(
+                    "target_url",
+                    Some("https://example.com/webhook"),
+                    Some("https://new.example.com/webhook")
+                )]
+            ),
+            (
+                3,
+                Some("ops:1"),
+                vec![
+                    ("redirect_max", Some("0"), Some("3")),
+                    ("redirect_mode", Some("none"), Some("same_host")),
+                ]
+            ),
+            (
+                2,
+                Some("admin:abc"),
+                vec![("request_timeout_ms", None, Some("5000"))]
+            ),
+            (1, None, vec![]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn canary_split_routes_events_by_id_and_records_the_target() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    for _ in 0..20 {
+        seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    }
+
+    let canary = set_endpoint_canary(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        Some("https://canary.example.com/webhook"),
+        30,
+    )
+    .await
+    .expect("set canary");
+    assert_eq!(canary.canary_percent, 30);
+
+    let req = LeaseRequest {
+        limit: 50,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let leased = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease");
+    assert_eq!(leased.len(), 20);
+    for event in &leased {
+        let expected_canary = sample_bucket("canary", event.event.id) % 100 < 30;
+        assert_eq!(event.canary, expected_canary);
+        let expected_url = if expected_canary {
+            "https://canary.example.com/webhook"
+        } else {
+            "https://example.com/webhook"
+        };
+        assert_eq!(event.target_url, expected_url);
+    }
+
+    // Turning the canary off does not move events already leased.
+    let cleared = set_endpoint_canary(&pool, &EndpointScope::All, endpoint_id, None, 30)
+        .await
+        .expect("clear canary");
+    assert_eq!(cleared.canary_percent, 0);
+
+    for event in &leased {
+        let now = Utc::now().to_rfc3339();
+        let report = ReportRequest {
+            worker_id: "worker-1".to_string(),
+            worker_version: None,
+            worker_region: None,
+            event_id: event.event.id,
+            outcome: ReportOutcome::Delivered,
+            retryable: false,
+            next_attempt_at: None,
+            attempt: ReportAttempt {
+                started_at: now.clone(),
+                finished_at: now,
+                request_headers: BTreeMap::new(),
+                request_body: "{}".to_string(),
+                response_status: Some(200),
+                response_headers: None,
+                response_body: None,
+                error_kind: None,
+                error_message: None,
+                final_url: None,
+                peer_address: None,
+                timing: None,
+            },
+        };
+        report_delivery(&pool, &DispatcherConfig::default(), &report)
+            .await
+            .expect("report");
+
+        let attempts = list_attempts(&pool, &EndpointScope::All, event.event.id)
+            .await
+            .expect("list attempts");
+        assert_eq!(
+            attempts.attempts[0].target_url.as_deref(),
+            Some(event.target_url.as_str())
+        );
+    }
+}
+
+#[tokio::test]
+async fn canary_routing_and_sampling_decisions_are_independent() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    for _ in 0..200 {
+        seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    }
+    set_endpoint_canary(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        Some("https://canary.example.com/webhook"),
+        50,
+    )
+    .await
+    .expect("set canary");
+    set_endpoint_attempt_log_sampling(&pool, &EndpointScope::All, endpoint_id, Some(2))
+        .await
+        .expect("set sampling");
+    let config = DispatcherConfig {
+        success_response_sample_percent: 50,
+        ..DispatcherConfig::default()
+    };
+
+    let req = LeaseRequest {
+        limit: 500,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let leased = lease_events(&pool, &config, &req).await.expect("lease");
+    assert_eq!(leased.len(), 200);
+
+    // Counts of (canary, response kept, attempt logged).
+    let mut combinations: BTreeMap<(bool, bool, bool), usize> = BTreeMap::new();
+    for event in &leased {
+        let now = Utc::now().to_rfc3339();
+        let report = ReportRequest {
+            worker_id: "worker-1".to_string(),
+            worker_version: None,
+            worker_region: None,
+            event_id: event.event.id,
+            outcome: ReportOutcome::Delivered,
+            retryable: false,
+            next_attempt_at: None,
+            attempt: ReportAttempt {
+                started_at: now.clone(),
+                finished_at: now,
+                request_headers: BTreeMap::new(),
+                request_body: "{}".to_string(),
+                response_status: Some(200),
+                response_headers: None,
+                response_body: Some("ok".to_string()),
+                error_kind: None,
+                error_message: None,
+                final_url: None,
+                peer_address: None,
+                timing: None,
+            },
+        };
+        report_delivery(&pool, &config, &report)
+            .await
+            .expect("report");
+
+        let response_kept = sample_bucket("response_capture", event.event.id) % 100 < 50;
+        let listed = list_attempts(&pool, &EndpointScope::All, event.event.id)
+            .await
+            .expect("list attempts");
+        if let Some(attempt) = listed.attempts.first() {
+            assert_eq!(attempt.response_body.is_some(), response_kept);
+        }
+        *combinations
+            .entry((event.canary, response_kept, !listed.attempts.is_empty()))
+            .or_default() += 1;
+    }
+
+    // With one shared hash, canary events would be exactly the captured
+    // ones. Independent halves put about 25 events in each of the eight
+    // combinations; 5 is over four standard deviations below that.
+    assert_eq!(combinations.len(), 8, "{combinations:?}");
+    assert!(
+        combinations.values().all(|&count| count >= 5),
+        "{combinations:?}"
+    );
+}
+
+#[tokio::test]
+async fn shadow_attempts_are_logged_without_touching_the_event() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+
+    set_endpoint_shadow(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        Some("https://shadow.example.com/webhook"),
+    )
+    .await
+    .expect("set shadow");
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let leased = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease");
+    assert_eq!(
+        leased[0].shadow_target_url.as_deref(),
+        Some("https://shadow.example.com/webhook")
+    );
+
+    let now = Utc::now().to_rfc3339();
+    let shadow = ShadowReportRequest {
+        worker_id: "worker-1".to_string(),
+        event_id,
+        target_url: "https://shadow.example.com/webhook".to_string(),
+        attempt: ReportAttempt {
+            started_at: now.clone(),
+            finished_at: now,
+            request_headers: BTreeMap::new(),
+            request_body: "{}".to_string(),
+            response_status: Some(500),
+            response_headers: None,
+            response_body: Some("boom".to_string()),
+            error_kind: None,
+            error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
+        },
+    };
+    record_shadow_attempt(&pool, &shadow)
+        .await
+        .expect("record shadow attempt");
+
+    let (status, attempts): (String, i64) =
+        sqlx::query_as("SELECT status, attempts FROM webhook_events WHERE id = ?")
+            .bind(event_id.to_string())
+            .fetch_one(&pool)
+            .await
+            .expect("fetch event");
+    assert_eq!(status, "in_flight");
+    assert_eq!(attempts, 0);
+    let circuits: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM target_circuit_states")
+        .fetch_one(&pool)
+        .await
+        .expect("count circuits");
+    assert_eq!(circuits, 0);
+    let primary = list_attempts(&pool, &EndpointScope::All, event_id)
+        .await
+        .expect("list attempts");
+    assert!(primary.attempts.is_empty());
+
+    let shadows = list_shadow_attempts(&pool, &EndpointScope::All, event_id)
+        .await
+        .expect("list shadow attempts");
+    assert_eq!(shadows.attempts.len(), 1);
+    assert_eq!(shadows.attempts[0].response_status, Some(500));
+    assert_eq!(shadows.attempts[0].response_body.as_deref(), Some("boom"));
+}
+
+#[tokio::test]
+async fn slo_stats_count_late_deliveries_and_overdue_events() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    set_endpoint_slo(&pool, &EndpointScope::All, endpoint_id, Some((60, 95.0)))
+        .await
+        .expect("set slo");
+
+    let on_time = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    let late = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    let overdue = seed_event(&pool, endpoint_id, "paused", None, None, None).await;
+    let five_minutes_ago = (Utc::now() - Duration::minutes(5)).to_rfc3339();
+    for event_id in [late, overdue] {
+        sqlx::query("UPDATE webhook_events SET received_at = ? WHERE id = ?")
+            .bind(&five_minutes_ago)
+            .bind(event_id.to_string())
+            .execute(&pool)
+            .await
+            .expect("backdate event");
+    }
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let leased = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease");
+    assert_eq!(leased.len(), 2);
+    for event_id in [on_time, late] {
+        let now = Utc::now().to_rfc3339();
+        let report = ReportRequest {
+            worker_id: "worker-1".to_string(),
+            worker_version: None,
+            worker_region: None,
+            event_id,
+            outcome: ReportOutcome::Delivered,
+            retryable: false,
+            next_attempt_at: None,
+            attempt: ReportAttempt {
+                started_at: now.clone(),
+                finished_at: now,
+                request_headers: BTreeMap::new(),
+                request_body: "{}".to_string(),
+                response_status: Some(200),
+                response_headers: None,
+                response_body: None,
+                error_kind: None,
+                error_message: None,
+                final_url: None,
+                peer_address: None,
+                timing: None,
+            },
+        };
+        report_delivery(&pool, &DispatcherConfig::default(), &report)
+            .await
+            .expect("report");
+    }
+
+    let stats = slo_stats(&pool, &EndpointScope::All, 60)
+        .await
+        .expect("slo stats");
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].delivered, 2);
+    assert_eq!(stats[0].delivered_within_target, 1);
+    assert_eq!(stats[0].overdue, 1);
+    assert_eq!(stats[0].attainment_percent, Some(50.0));
+    assert!(!stats[0].met);
+    assert_eq!(stats[0].breaches_total, 1);
+
+    let metrics = render_slo_metrics(&stats);
+    assert!(metrics.contains(&format!(
+        "receiver_slo_breaches_total{{endpoint_id=\"{endpoint_id}\"}} 1\n"
+    )));
+    assert!(metrics.contains(&format!(
+        "receiver_slo_attainment_ratio{{endpoint_id=\"{endpoint_id}\"}} 0.5\n"
+    )));
+}
+
+#[tokio::test]
+async fn reports_maintain_hourly_rollups_for_attempt_buckets() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let delivered = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    let retried = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: Some(endpoint_id.to_string()),
+    };
+    let leased = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease");
+    assert_eq!(leased.len(), 2);
+    for (event_id, status) in [(delivered, 200), (retried, 503)] {
+        let finished_at = Utc::now();
+        let report = ReportRequest {
+            worker_id: "worker-1".to_string(),
+            worker_version: None,
+            worker_region: None,
+            event_id,
+            outcome: if status == 200 {
+                ReportOutcome::Delivered
+            } else {
+                ReportOutcome::Retry
+            },
+            retryable: status != 200,
+            next_attempt_at: (status != 200)
+                .then(|| (finished_at + Duration::minutes(5)).to_rfc3339()),
+            attempt: ReportAttempt {
+                started_at: (finished_at - Duration::milliseconds(250)).to_rfc3339(),
+                finished_at: finished_at.to_rfc3339(),
+                request_headers: BTreeMap::new(),
+                request_body: "{}".to_string(),
+                response_status: Some(status),
+                response_headers: None,
+                response_body: None,
+                error_kind: None,
+                error_message: None,
+                final_url: None,
+                peer_address: None,
+                timing: None,
+            },
+        };
+        report_delivery(&pool, &DispatcherConfig::default(), &report)
+            .await
+            .expect("report");
+    }
+
+    // Hour-sized buckets read the rollups, not the attempt logs.
+    sqlx::query("DELETE FROM webhook_attempt_logs")
+        .execute(&pool)
+        .await
+        .expect("drop attempt logs");
+    let buckets = attempt_buckets(&pool, &EndpointScope::All, 60, 3)
+        .await
+        .expect("attempt buckets");
+    assert_eq!(buckets.len(), 1);
+    assert_eq!(buckets[0].endpoint_id, endpoint_id);
+    assert_eq!(buckets[0].bucket, 3);
+    assert_eq!(buckets[0].attempts, 2);
+    assert_eq!(buckets[0].failures, 1);
+    assert!((buckets[0].avg_latency_ms - 250.0).abs() < 1.0);
+
+    let (delivered_count, dead): (i64, i64) =
+        sqlx::query_as("SELECT SUM(delivered), SUM(dead) FROM delivery_stats_hourly")
+            .fetch_one(&pool)
+            .await
+            .expect("rollup totals");
+    assert_eq!((delivered_count, dead), (1, 0));
+}
+
+#[tokio::test]
+async fn sampled_successes_are_counted_but_only_one_in_n_is_logged() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    set_endpoint_attempt_log_sampling(&pool, &EndpointScope::All, endpoint_id, Some(2))
+        .await
+        .expect("set sampling");
+    let mut delivered = Vec::new();
+    for _ in 0..8 {
+        delivered.push(seed_event(&pool, endpoint_id, "pending", None, None, None).await);
+    }
+    let retried = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+
+    let req = LeaseRequest {
+        limit: 20,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: Some(endpoint_id.to_string()),
+    };
+    let leased = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease");
+    assert_eq!(leased.len(), 9);
+    for event_id in delivered.iter().copied().chain([retried]) {
+        let success = event_id != retried;
+        let report = ReportRequest {
+            worker_id: "worker-1".to_string(),
+            worker_version: None,
+            worker_region: None,
+            event_id,
+            outcome: if success {
+                ReportOutcome::Delivered
+            } else {
+                ReportOutcome::Retry
+            },
+            retryable: !success,
+            next_attempt_at: (!success).then(|| (Utc::now() + Duration::minutes(5)).to_rfc3339()),
+            attempt: ReportAttempt {
+                started_at: Utc::now().to_rfc3339(),
+                finished_at: Utc::now().to_rfc3339(),
+                request_headers: BTreeMap::new(),
+                request_body: "{}".to_string(),
+                response_status: Some(if success { 200 } else { 503 }),
+                response_headers: None,
+                response_body: None,
+                error_kind: None,
+                error_message: None,
+                final_url: None,
+                peer_address: None,
+                timing: None,
+            },
+        };
+        report_delivery(&pool, &DispatcherConfig::default(), &report)
+            .await
+            .expect("report");
+    }
+
+    for event_id in delivered {
+        let kept = sample_bucket("attempt_log", event_id).is_multiple_of(2);
+        let listed = list_attempts(&pool, &EndpointScope::All, event_id)
+            .await
+            .expect("list attempts");
+        assert_eq!(listed.attempts.len(), usize::from(kept));
+        if kept {
+            assert_eq!(listed.attempts[0].sample_rate, Some(2));
+            assert_eq!(listed.unlogged_attempts, 0);
+        } else {
+            assert_eq!(listed.unlogged_attempts, 1);
+            assert_eq!(listed.unlogged_sample_rate, Some(2));
+        }
+    }
+
+    // Failures are always logged.
+    let listed = list_attempts(&pool, &EndpointScope::All, retried)
+        .await
+        .expect("list attempts");
+    assert_eq!(listed.attempts.len(), 1);
+    assert_eq!(listed.attempts[0].sample_rate, None);
+    assert_eq!(listed.unlogged_attempts, 0);
+
+    // Dropped attempts still count, so the doctor sees no mismatch.
+    let report = run_doctor(&pool, false).await.expect("doctor");
+    assert!(report.issues.is_empty(), "{:?}", report.issues);
+    let attempted: i64 = sqlx::query_scalar("SELECT SUM(attempts) FROM delivery_stats_hourly")
+        .fetch_one(&pool)
+        .await
+        .expect("rollup attempts");
+    assert_eq!(attempted, 9);
+}
+
+#[tokio::test]
+async fn lease_hands_out_endpoint_request_timeout() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let tuned = seed_endpoint(&pool).await;
+    let untuned = seed_endpoint(&pool).await;
+    seed_event(&pool, tuned, "pending", None, None, None).await;
+    seed_event(&pool, untuned, "pending", None, None, None).await;
+    set_endpoint_timeouts(
+        &pool,
+        &EndpointScope::All,
+        tuned,
+        Some(5_000),
+        None,
+        "admin:abc",
+    )
+    .await
+    .expect("set timeouts");
+    let config = DispatcherConfig {
+        default_request_timeout_ms: 12_000,
+        ..Default::default()
+    };
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+
+    let leased = lease_events(&pool, &config, &req).await.expect("lease");
+    assert_eq!(leased.len(), 2);
+    for leased in leased {
+        let expected = if leased.event.endpoint_id == tuned {
+            5_000
+        } else {
+            12_000
+        };
+        assert_eq!(leased.request_timeout_ms, expected);
+        assert_eq!(leased.delivery_budget_remaining_ms, None);
+    }
+}
+
+#[tokio::test]
+async fn exhausted_delivery_budget_marks_retry_dead() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    set_endpoint_timeouts(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        None,
+        Some(600),
+        "admin:abc",
+    )
+    .await
+    .expect("set timeouts");
+    let req = LeaseRequest {
+        limit: 1,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let retry_report = |started_at: String| ReportRequest {
+        worker_id: "worker-1".to_string(),
+        worker_version: None,
+        worker_region: None,
+        event_id,
+        outcome: ReportOutcome::Retry,
+        retryable: true,
+        next_attempt_at: None,
+        attempt: ReportAttempt {
+            started_at,
+            finished_at: Utc::now().to_rfc3339(),
+            request_headers: BTreeMap::new(),
+            request_body: "{}".to_string(),
+            response_status: Some(503),
+            response_headers: None,
+            response_body: None,
+            error_kind: None,
+            error_message: Some("unavailable".to_string()),
+            final_url: None,
+            peer_address: None,
+            timing: None,
+        },
+    };
+
+    let leased = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("first lease");
+    assert_eq!(leased[0].delivery_budget_remaining_ms, Some(600_000));
+    let two_minutes_ago = (Utc::now() - Duration::minutes(2)).to_rfc3339();
+    let result = report_delivery(
+        &pool,
+        &DispatcherConfig::default(),
+        &retry_report(two_minutes_ago),
+    )
+    .await
+    .expect("first report");
+    assert_eq!(result.final_outcome, ReportOutcome::Retry);
+
+    sqlx::query("UPDATE webhook_events SET next_attempt_at = NULL WHERE id = ?")
+        .bind(event_id.to_string())
+        .execute(&pool)
+        .await
+        .expect("make event due");
+    let leased = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("second lease");
+    let remaining = leased[0].delivery_budget_remaining_ms.expect("budget");
+    assert!((470_000..=480_000).contains(&remaining));
+
+    set_endpoint_timeouts(
+        &pool,
+        &EndpointScope::All,
+        endpoint_id,
+        None,
+        Some(60),
+        "admin:abc",
+    )
+    .await
+    .expect("shrink budget");
+    let result = report_delivery(
+        &pool,
+        &DispatcherConfig::default(),
+        &retry_report(Utc::now().to_rfc3339()),
+    )
+    .await
+    .expect("second report");
+    assert_eq!(result.final_outcome, ReportOutcome::Dead);
+    let last_error: String =
+        sqlx::query_scalar("SELECT last_error FROM webhook_events WHERE id = ?")
+            .bind(event_id.to_string())
+            .fetch_one(&pool)
+            .await
+            .expect("load event");
+    assert_eq!(last_error, "delivery_budget_exhausted (60s): unavailable");
+}
+
+#[tokio::test]
+async fn failure_spike_in_latest_bucket_is_flagged_as_anomaly() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let spiking = seed_endpoint(&pool).await;
+    let steady = seed_endpoint(&pool).await;
+    let config = AnomalyConfig {
+        baseline_buckets: 6,
+        z_threshold: 3.0,
+        min_attempts: 10,
+    };
+
+    for endpoint_id in [spiking, steady] {
+        let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+        for bucket in 0..=config.baseline_buckets {
+            let finished_at = Utc::now() - Duration::minutes((6 - bucket) * 10 + 5);
+            let started_at = finished_at - Duration::milliseconds(100);
+            let failures = if endpoint_id == spiking && bucket == 6 {
+                8
+            } else {
+                1
+            };
+            for attempt in 0..10 {
+                sqlx::query(
+                    "INSERT INTO webhook_attempt_logs \
+                     (id, event_id, attempt_no, started_at, finished_at, request_headers, \
+                      request_body, response_status) \
+                     VALUES (?, ?, ?, ?, ?, '{}', '{}', ?)",
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(event_id.to_string())
+                .bind(bucket * 10 + attempt)
+                .bind(started_at.to_rfc3339())
+                .bind(finished_at.to_rfc3339())
+                .bind(if attempt < failures { 500 } else { 200 })
+                .execute(&pool)
+                .await
+                .expect("insert attempt log");
+            }
+        }
+    }
+
+    let buckets = attempt_buckets(&pool, &EndpointScope::All, 10, config.baseline_buckets)
+        .await
+        .expect("attempt buckets");
+    assert_eq!(buckets.len(), 14);
+    let anomalies = detect_anomalies(&buckets, config);
+    assert_eq!(anomalies.len(), 1);
+    let anomaly = &anomalies[0];
+    assert_eq!(anomaly.endpoint_id, spiking);
+    assert_eq!(anomaly.metric, AnomalyMetric::FailureRate);
+    assert_eq!(anomaly.attempts, 10);
+    assert!((anomaly.current - 0.8).abs() < 1e-9);
+    assert!((anomaly.baseline_mean - 0.1).abs() < 1e-9);
+    assert!(anomaly.z_score > 10.0);
+
+    let scoped = attempt_buckets(
+        &pool,
+        &EndpointScope::Endpoints(vec![steady]),
+        10,
+        config.baseline_buckets,
+    )
+    .await
+    .expect("scoped attempt buckets");
+    assert!(detect_anomalies(&scoped, config).is_empty());
+
+    let metrics = render_anomaly_metrics(&anomalies);
+    assert!(metrics.contains(&format!(
+        "receiver_anomaly_z_score{{endpoint_id=\"{spiking}\",metric=\"failure_rate\"}} "
+    )));
+}
+
+#[tokio::test]
+async fn carried_over_replay_budget_exhausts_across_the_chain() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let config = DispatcherConfig::default();
+    let original_id = seed_event_with_attempts(
+        &pool,
+        endpoint_id,
+        "dead",
+        None,
+        None,
+        None,
+        i64::from(config.max_attempts) - 1,
+    )
+    .await;
+
+    let reset = replay_event(
+        &pool,
+        &EndpointScope::All,
+        original_id,
+        false,
+        false,
+        ReplayAttemptBudget::Reset,
+        None,
+    )
+    .await
+    .expect("reset replay");
+    assert_eq!(reset.event.attempts, 0);
+    sqlx::query("UPDATE webhook_events SET status = 'delivered' WHERE id = ?")
+        .bind(reset.event.id.to_string())
+        .execute(&pool)
+        .await
+        .expect("deliver reset replay");
+
+    let carried = replay_event(
+        &pool,
+        &EndpointScope::All,
+        original_id,
+        false,
+        false,
+        ReplayAttemptBudget::CarryOver,
+        None,
+    )
+    .await
+    .expect("carry-over replay");
+    assert_eq!(carried.event.attempts, i64::from(config.max_attempts) - 1);
+
+    // The startup doctor must not reset the carried budget. The seeded
+    // original has attempts without logs, so only the replay is checked.
+    let doctor = run_doctor(&pool, true).await.expect("doctor");
+    assert!(
+        doctor
+            .issues
+            .iter()
+            .all(|issue| issue.event_id != carried.event.id),
+        "{:?}",
+        doctor.issues
+    );
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let leased = lease_events(&pool, &config, &req).await.expect("lease");
+    assert_eq!(leased.len(), 1);
+    assert_eq!(leased[0].event.id, carried.event.id);
+    let now = Utc::now().to_rfc3339();
+    let report = report_delivery(
+        &pool,
+        &config,
+        &ReportRequest {
+            worker_id: "worker-1".to_string(),
+            worker_version: None,
+            worker_region: None,
+            event_id: carried.event.id,
+            outcome: ReportOutcome::Retry,
+            retryable: true,
+            next_attempt_at: None,
+            attempt: ReportAttempt {
+                started_at: now.clone(),
+                finished_at: now,
+                request_headers: BTreeMap::new(),
+                request_body: "{}".to_string(),
+                response_status: Some(503),
+                response_headers: None,
+                response_body: None,
+                error_kind: Some(WebhookAttemptErrorKind::InvalidResponse),
+                error_message: Some("unavailable".to_string()),
+                final_url: None,
+                peer_address: None,
+                timing: None,
+            },
+        },
+    )
+    .await
+    .expect("report");
+    assert_eq!(report.endpoint_stats.attempts_remaining, 0);
+
+    let lineage = get_event_lineage(&pool, &EndpointScope::All, carried.event.id)
+        .await
+        .expect("lineage");
+    assert_eq!(lineage.root_event_id, original_id);
+    assert_eq!(lineage.events.len(), 3);
+    assert_eq!(lineage.events[0].id, original_id);
+    assert_eq!(lineage.events[0].depth, 0);
+    assert!(lineage.events[1..].iter().all(|event| event.depth == 1));
+    let carried_entry = lineage
+        .events
+        .iter()
+        .find(|event| event.id == carried.event.id)
+        .expect("carried replay in lineage");
+    assert_eq!(carried_entry.status, WebhookEventStatus::Dead);
+    assert_eq!(carried_entry.attempts, i64::from(config.max_attempts));
+    assert_eq!(carried_entry.attempts_made, 1);
+    assert_eq!(lineage.cumulative_attempts, 1);
+
+    let scoped = get_event_lineage(
+        &pool,
+        &EndpointScope::Endpoints(vec![Uuid::new_v4()]),
+        carried.event.id,
+    )
+    .await;
+    assert!(matches!(
+        scoped,
+        Err(receiver::inspector::StoreError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn carried_over_replay_delivered_on_last_attempt_stays_delivered() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let config = DispatcherConfig::default();
+    let original_id = seed_event_with_attempts(
+        &pool,
+        endpoint_id,
+        "dead",
+        None,
+        None,
+        None,
+        i64::from(config.max_attempts) - 1,
+    )
+    .await;
+    let carried = replay_event(
+        &pool,
+        &EndpointScope::All,
+        original_id,
+        false,
+        false,
+        ReplayAttemptBudget::CarryOver,
+        None,
+    )
+    .await
+    .expect("carry-over replay");
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let leased = lease_events(&pool, &config, &req).await.expect("lease");
+    assert_eq!(leased.len(), 1);
+    let now = Utc::now().to_rfc3339();
+    let report = report_delivery(
+        &pool,
+        &config,
+        &ReportRequest {
+            worker_id: "worker-1".to_string(),
+            worker_version: None,
+            worker_region: None,
+            event_id: carried.event.id,
+            outcome: ReportOutcome::Delivered,
+            retryable: false,
+            next_attempt_at: None,
+            attempt: ReportAttempt {
+                started_at: now.clone(),
+                finished_at: now,
+                request_headers: BTreeMap::new(),
+                request_body: "{}".to_string(),
+                response_status: Some(200),
+                response_headers: None,
+                response_body: None,
+                error_kind: None,
+                error_message: None,
+                final_url: None,
+                peer_address: None,
+                timing: None,
+            },
+        },
+    )
+    .await
+    .expect("report");
+    assert_eq!(report.final_outcome, ReportOutcome::Delivered);
+
+    let (status, attempts): (String, i64) =
+        sqlx::query_as("SELECT status, attempts FROM webhook_events WHERE id = ?")
+            .bind(carried.event.id.to_string())
+            .fetch_one(&pool)
+            .await
+            .expect("replay row");
+    assert_eq!(status, "delivered");
+    assert_eq!(attempts, i64::from(config.max_attempts));
+}
+
+#[tokio::test]
+async fn delivery_id_is_shared_across_a_replay_chain() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let original_id = seed_event(&pool, endpoint_id, "dead", None, None, None).await;
+
+    let first = replay_event(
+        &pool,
+        &EndpointScope::All,
+        original_id,
+        false,
+        false,
+        ReplayAttemptBudget::Reset,
+        None,
+    )
+    .await
+    .expect("replay original");
+    sqlx::query("UPDATE webhook_events SET status = 'dead' WHERE id = ?")
+        .bind(first.event.id.to_string())
+        .execute(&pool)
+        .await
+        .expect("mark replay dead");
+    let second = replay_event(
+        &pool,
+        &EndpointScope::All,
+        first.event.id,
+        false,
+        false,
+        ReplayAttemptBudget::Reset,
+        None,
+    )
+    .await
+    .expect("replay replay");
+
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let leased = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease");
+    assert_eq!(leased.len(), 1);
+    let headers = &leased[0].delivery_headers;
+    assert_eq!(headers[DELIVERY_ID_HEADER], original_id.to_string());
+    assert_eq!(
+        headers[DELIVERY_EVENT_ID_HEADER],
+        second.event.id.to_string()
+    );
+    assert_eq!(headers[DELIVERY_ATTEMPT_HEADER], "1");
+
+    seed_event_with_attempts(&pool, endpoint_id, "pending", None, None, None, 2).await;
+    let leased = lease_events(&pool, &DispatcherConfig::default(), &req)
+        .await
+        .expect("lease retry");
+    assert_eq!(leased[0].delivery_headers[DELIVERY_ATTEMPT_HEADER], "3");
+    assert_eq!(
+        leased[0].delivery_headers[DELIVERY_ID_HEADER],
+        leased[0].event.id.to_string()
+    );
+}
+
+#[tokio::test]
+async fn endpoint_checks_are_leased_once_per_interval_and_surface_in_health() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let active = seed_endpoint(&pool).await;
+    let idle = seed_endpoint(&pool).await;
+    let amqp = seed_endpoint(&pool).await;
+    seed_event(&pool, active, "delivered", None, None, None).await;
+    seed_event(&pool, amqp, "pending", None, None, None).await;
+    sqlx::query("UPDATE endpoints SET target_kind = 'amqp' WHERE id = ?")
+        .bind(amqp.to_string())
+        .execute(&pool)
+        .await
+        .expect("set target kind");
+    let config = DispatcherConfig::default();
+    let req = CheckLeaseRequest {
+        worker_id: "worker-1".to_string(),
+        limit: 10,
+    };
+
+    let checks = lease_endpoint_checks(&pool, &config, &req)
+        .await
+        .expect("lease checks");
+    assert_eq!(checks.len(), 1);
+    assert_eq!(checks[0].endpoint_id, active);
+    assert_eq!(checks[0].method, EndpointCheckMethod::Head);
+    assert_eq!(checks[0].url, "https://example.com/webhook");
+    assert_eq!(
+        checks[0].request_timeout_ms,
+        config.default_request_timeout_ms
+    );
+    assert!(
+        lease_endpoint_checks(&pool, &config, &req)
+            .await
+            .expect("lease checks again")
+            .is_empty()
+    );
+
+    let started_at = Utc::now() - Duration::seconds(10);
+    let report = |offset_seconds: i64, response_status: Option<i64>| CheckReportRequest {
+        worker_id: "worker-1".to_string(),
+        endpoint_id: active,
+        url: "https://example.com/webhook".to_string(),
+        started_at: (started_at + Duration::seconds(offset_seconds)).to_rfc3339(),
+        finished_at: (started_at + Duration::seconds(offset_seconds) + Duration::milliseconds(120))
+            .to_rfc3339(),
+        response_status,
+        error_message: response_status
+            .is_none()
+            .then(|| "connection refused".to_string()),
+        tls_expires_at: response_status.map(|_| "2031-01-01T00:00:00Z".to_string()),
+    };
+    record_endpoint_check(&pool, &report(0, Some(405)))
+        .await
+        .expect("record reachable check");
+    record_endpoint_check(&pool, &report(5, None))
+        .await
+        .expect("record failed check");
+
+    let health = get_endpoint_health(&pool, &EndpointScope::All, active, 14)
+        .await
+        .expect("health");
+    let latest = health.latest_check.expect("latest check");
+    assert!(!latest.reachable);
+    assert_eq!(latest.response_status, None);
+    assert_eq!(latest.latency_ms, 120);
+    assert_eq!(latest.error_message.as_deref(), Some("connection refused"));
+    let last_reachable_at = health.last_reachable_at.expect("last reachable");
+    assert!(last_reachable_at < latest.checked_at);
+    assert!(health.circuit.is_none());
+
+    let idle_health = get_endpoint_health(&pool, &EndpointScope::All, idle, 14)
+        .await
+        .expect("idle health");
+    assert!(idle_health.latest_check.is_none());
+    assert!(idle_health.last_reachable_at.is_none());
+
+    set_endpoint_check(
+        &pool,
+        &EndpointScope::All,
+        active,
+        EndpointCheckMethod::Options,
+        Some("https://example.com/ping"),
+        14,
+    )
+    .await
+    .expect("set check");
+    let checks = lease_endpoint_checks(&pool, &config, &req)
+        .await
+        .expect("lease reconfigured check");
+    assert_eq!(checks.len(), 1);
+    assert_eq!(checks[0].method, EndpointCheckMethod::Options);
+    assert_eq!(checks[0].url, "https://example.com/ping");
+}
+
+#[tokio::test]
+async fn expiring_certificates_are_flagged_from_the_latest_check() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let expiring = seed_endpoint(&pool).await;
+    let healthy = seed_endpoint(&pool).await;
+    let now = Utc::now();
+    let report = |endpoint_id: Uuid, offset_seconds: i64, tls_expires_in_days: Option<i64>| {
+        let at = now - Duration::minutes(10) + Duration::seconds(offset_seconds);
+        CheckReportRequest {
+            worker_id: "worker-1".to_string(),
+            endpoint_id,
+            url: "https://example.com/webhook".to_string(),
+            started_at: at.to_rfc3339(),
+            finished_at: at.to_rfc3339(),
+            response_status: tls_expires_in_days.map(|_| 200),
+            error_message: tls_expires_in_days
+                .is_none()
+                .then(|| "connection reset".to_string()),
+            tls_expires_at: tls_expires_in_days
+                .map(|days| (now + Duration::days(days) + Duration::hours(1)).to_rfc3339()),
+        }
+    };
+    // The renewed certificate from the second check is the one that counts,
+    // and a later failed probe without a certificate does not hide it.
+    for req in [
+        report(expiring, 0, Some(90)),
+        report(expiring, 1, Some(5)),
+        report(expiring, 2, None),
+        report(healthy, 0, Some(60)),
+    ] {
+        record_endpoint_check(&pool, &req)
+            .await
+            .expect("record check");
+    }
+
+    let expiries = tls_expiries(&pool, &EndpointScope::All)
+        .await
+        .expect("tls expiries");
+    assert_eq!(expiries.len(), 2);
+    assert_eq!(expiries[0].endpoint_id, expiring);
+    assert_eq!(expiries[0].days_remaining, 5);
+    assert_eq!(expiries[1].endpoint_id, healthy);
+    assert_eq!(expiries[1].days_remaining, 60);
+
+    let scoped = tls_expiries(&pool, &EndpointScope::Endpoints(vec![healthy]))
+        .await
+        .expect("scoped tls expiries");
+    assert_eq!(scoped.len(), 1);
+    assert_eq!(scoped[0].endpoint_id, healthy);
+
+    let health = get_endpoint_health(&pool, &EndpointScope::All, expiring, 14)
+        .await
+        .expect("expiring health");
+    assert!(health.tls_expiring);
+    assert!(!health.latest_check.expect("latest check").reachable);
+    let health = get_endpoint_health(&pool, &EndpointScope::All, healthy, 14)
+        .await
+        .expect("healthy health");
+    assert!(!health.tls_expiring);
+
+    let metrics = render_tls_metrics(&expiries, 14);
+    assert!(metrics.contains(&format!(
+        "receiver_endpoint_tls_expiry_days{{endpoint_id=\"{expiring}\"}} 5\n"
+    )));
+    assert!(metrics.contains(&format!(
+        "receiver_endpoint_tls_expiring{{endpoint_id=\"{expiring}\"}} 1\n"
+    )));
+    assert!(metrics.contains(&format!(
+        "receiver_endpoint_tls_expiring{{endpoint_id=\"{healthy}\"}} 0\n"
+    )));
+}
+
+#[tokio::test]
+async fn attempt_error_kinds_round_trip_through_the_store() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    let kinds = [
+        WebhookAttemptErrorKind::Timeout,
+        WebhookAttemptErrorKind::Dns,
+        WebhookAttemptErrorKind::Tls,
+        WebhookAttemptErrorKind::ConnectionRefused,
+        WebhookAttemptErrorKind::TooManyRedirects,
+        WebhookAttemptErrorKind::PayloadTooLarge,
+        WebhookAttemptErrorKind::Network,
+        WebhookAttemptErrorKind::InvalidResponse,
+        WebhookAttemptErrorKind::Unexpected,
+    ];
+    let base = Utc::now() - Duration::minutes(5);
+
+    for (offset, kind) in (0_i64..).zip(kinds) {
+        let at = (base + Duration::seconds(offset)).to_rfc3339();
+        let shadow = ShadowReportRequest {
+            worker_id: "worker-1".to_string(),
+            event_id,
+            target_url: "https://shadow.example.com/webhook".to_string(),
+            attempt: ReportAttempt {
+                started_at: at.clone(),
+                finished_at: at,
+                request_headers: BTreeMap::new(),
+                request_body: "{}".to_string(),
+                response_status: None,
+                response_headers: None,
+                response_body: None,
+                error_kind: Some(kind),
+                error_message: Some("failed".to_string()),
+                final_url: None,
+                peer_address: None,
+                timing: None,
+            },
+        };
+        record_shadow_attempt(&pool, &shadow)
+            .await
+            .expect("record shadow attempt");
+    }
+
+    let shadows = list_shadow_attempts(&pool, &EndpointScope::All, event_id)
+        .await
+        .expect("list shadow attempts");
+    let mut stored: Vec<_> = shadows
+        .attempts
+        .iter()
+        .map(|attempt| (attempt.started_at.clone(), attempt.error_kind))
+        .collect();
+    stored.sort_by(|a, b| a.0.cmp(&b.0));
+    let stored: Vec<_> = stored.into_iter().map(|(_, kind)| kind).collect();
+    assert_eq!(stored, kinds.map(Some).to_vec());
+}
+
+#[tokio::test]
+async fn redirect_policy_is_leased_and_final_url_is_logged() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    let config = DispatcherConfig::default();
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+
+    let events = lease_events(&pool, &config, &req)
+        .await
+        .expect("lease events");
+    assert_eq!(
+        events[0].redirect_policy,
+        RedirectPolicy {
+            mode: RedirectMode::None,
+            max_redirects: 0,
+        }
+    );
+
+    let policy = RedirectPolicy {
+        mode: RedirectMode::SameHost,
+        max_redirects: 3,
+    };
+    let stored =
+        set_endpoint_redirect_policy(&pool, &EndpointScope::All, endpoint_id, policy, "admin:abc")
+            .await
+            .expect("set redirect policy");
+    assert_eq!(stored.policy, policy);
+    let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+
+    let events = lease_events(&pool, &config, &req)
+        .await
+        .expect("lease events");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].redirect_policy, policy);
+
+    let now = Utc::now().to_rfc3339();
+    let report = ReportRequest {
+        worker_id: "worker-1".to_string(),
+        worker_version: None,
+        worker_region: None,
+        event_id,
+        outcome: ReportOutcome::Delivered,
+        retryable: true,
+        next_attempt_at: None,
+        attempt: ReportAttempt {
+            started_at: now.clone(),
+            finished_at: now,
+            request_headers: BTreeMap::new(),
+            request_body: "{}".to_string(),
+            response_status: Some(200),
+            response_headers: None,
+            response_body: None,
+            error_kind: None,
+            error_message: None,
+            final_url: Some("https://example.com/webhook/v2".to_string()),
+            peer_address: None,
+            timing: None,
+        },
+    };
+    report_delivery(&pool, &config, &report)
+        .await
+        .expect("report");
+
+    let attempts = list_attempts(&pool, &EndpointScope::All, event_id)
+        .await
+        .expect("list attempts");
+    assert_eq!(
+        attempts.attempts[0].final_url.as_deref(),
+        Some("https://example.com/webhook/v2")
+    );
+}
+
+#[tokio::test]
+async fn connect_policy_is_leased_and_peer_address_is_logged() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let policy = ConnectPolicy {
+        address_family: AddressFamily::PreferIpv6,
+        happy_eyeballs_delay_ms: None,
+    };
+    let stored =
+        set_endpoint_connect_policy(&pool, &EndpointScope::All, endpoint_id, policy, "admin:abc")
+            .await
+            .expect("set connect policy");
+    assert_eq!(stored.policy, policy);
+    let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    let config = DispatcherConfig::default();
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+
+    let events = lease_events(&pool, &config, &req)
+        .await
+        .expect("lease events");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].connect_policy, policy);
+
+    let now = Utc::now().to_rfc3339();
+    let report = ReportRequest {
+        worker_id: "worker-1".to_string(),
+        worker_version: None,
+        worker_region: None,
+        event_id,
+        outcome: ReportOutcome::Retry,
+        retryable: true,
+        next_attempt_at: None,
+        attempt: ReportAttempt {
+            started_at: now.clone(),
+            finished_at: now,
+            request_headers: BTreeMap::new(),
+            request_body: "{}".to_string(),
+            response_status: None,
+            response_headers: None,
+            response_body: None,
+            error_kind: Some(WebhookAttemptErrorKind::Timeout),
+            error_message: Some("connect timed out".to_string()),
+            final_url: None,
+            peer_address: Some("[2001:db8::1]:443".to_string()),
+            timing: None,
+        },
+    };
+    report_delivery(&pool, &config, &report)
+        .await
+        .expect("report");
+
+    let attempts = list_attempts(&pool, &EndpointScope::All, event_id)
+        .await
+        .expect("list attempts");
+    assert_eq!(
+        attempts.attempts[0].peer_address.as_deref(),
+        Some("[2001:db8::1]:443")
+    );
+}
+
+#[tokio::test]
+async fn attempt_timings_and_worker_are_logged_when_reported() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let endpoint_id = seed_endpoint(&pool).await;
+    let event_id = seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    let config = DispatcherConfig::default();
+    let req = LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let timing = AttemptTiming {
+        dns_ms: Some(12),
+        connect_ms: Some(30),
+        tls_ms: None,
+        ttfb_ms: Some(1_450),
+        total_ms: Some(1_480),
+    };
+
+    for timing in [None, Some(timing)] {
+        let events = lease_events(&pool, &config, &req)
+            .await
+            .expect("lease events");
+        assert_eq!(events.len(), 1);
+        let now = Utc::now().to_rfc3339();
+        let report = ReportRequest {
+            worker_id: "worker-1".to_string(),
+            worker_version: Some("2.1.0".to_string()),
+            worker_region: Some("us-east-1".to_string()),
+            event_id,
+            outcome: ReportOutcome::Retry,
+            retryable: true,
+            next_attempt_at: Some(Utc::now().to_rfc3339()),
+            attempt: ReportAttempt {
+                started_at: now.clone(),
+                finished_at: now,
+                request_headers: BTreeMap::new(),
+                request_body: "{}".to_string(),
+                response_status: Some(503),
+                response_headers: None,
+                response_body: None,
+                error_kind: None,
+                error_message: None,
+                final_url: None,
+                peer_address: None,
+                timing,
+            },
+        };
+        report_delivery(&pool, &config, &report)
+            .await
+            .expect("report");
+    }
+
+    let attempts = list_attempts(&pool, &EndpointScope::All, event_id)
+        .await
+        .expect("list attempts");
+    let mut logged: Vec<_> = attempts
+        .attempts
+        .iter()
+        .map(|attempt| (attempt.attempt_no, attempt.timing))
+        .collect();
+    logged.sort_by_key(|(attempt_no, _)| *attempt_no);
+    assert_eq!(logged, vec![(1, None), (2, Some(timing))]);
+    assert!(attempts.attempts.iter().all(|attempt| {
+        attempt.worker_id.as_deref() == Some("worker-1")
+            && attempt.worker_version.as_deref() == Some("2.1.0")
+            && attempt.worker_region.as_deref() == Some("us-east-1")
+    }));
+}
+
+#[tokio::test]
+async fn region_pinned_endpoints_go_to_workers_of_their_region() {
+    let test_db = setup_db_shared(1).await;
+    let pool = test_db.pool;
+    let required = seed_endpoint(&pool).await;
+    let preferred = seed_endpoint(&pool).await;
+    let anywhere = seed_endpoint(&pool).await;
+    for (endpoint_id, mode) in [
+        (required, RegionMode::Require),
+        (preferred, RegionMode::Prefer),
+    ] {
+        let stored = set_endpoint_region(
+            &pool,
+            &EndpointScope::All,
+            endpoint_id,
+            Some("eu"),
+            mode,
+            "admin:abc",
+        )
+        .await
+        .expect("set region");
+        assert_eq!(stored.region.as_deref(), Some("eu"));
+    }
+    let config = DispatcherConfig::default();
+    let lease = |region: &str| LeaseRequest {
+        limit: 10,
+        lease_ms: 30_000,
+        worker_id: format!("worker-{region}"),
+        region: Some(region.to_string()),
+        endpoint_id: None,
+    };
+    let leased_endpoints = |events: Vec<LeasedEvent>| {
+        let mut ids: Vec<Uuid> = events.iter().map(|e| e.event.endpoint_id).collect();
+        ids.sort();
+        ids
+    };
+
+    for endpoint_id in [required, preferred, anywhere] {
+        seed_event(&pool, endpoint_id, "pending", None, None, None).await;
+    }
+    let events = lease_events(&pool, &config, &lease("us"))
+        .await
+        .expect("lease events");
+    assert_eq!(leased_endpoints(events), vec![anywhere]);
+    let events = lease_events(&pool, &config, &lease("eu"))
+        .await
+        .expect("lease events");
+    let mut expected = vec![required, preferred];
+    expected.sort();
+    assert_eq!(leased_endpoints(events), expected);
+
+    // Once due for longer than the fallback delay, only the preferring
+    // endpoint's event may leave its region.
+    let due_since = (Utc::now() - Duration::minutes(1)).to_rfc3339();
+    for endpoint_id in [required, preferred] {
+        seed_event(&pool, endpoint_id, "pending", Some(&due_since), None, None).await;
+    }
+    let events = lease_events(&pool, &config, &lease("us"))
+        .await
+        .expect("lease events");
+    assert_eq!(leased_endpoints(events), vec![preferred]);
+}
@@ -0,0 +1,265 @@
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    Router,
+    body::Bytes,
+    http::{Method, StatusCode, Uri, header::ETAG},
+};
+use chrono::{NaiveDate, TimeZone, Utc};
+use object_store::{aws::AmazonS3Builder, prefix::PrefixStore};
+use receiver::{
+    export::{
+        ExportScheduleConfig, ExportSink, export_day, export_path, get_export_status, open_sink,
+        run_scheduled_export,
+    },
+    inspector::render_export_metrics,
+    types::ExportFormat,
+};
+use sqlx::{
+    Connection, SqliteConnection, SqlitePool,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+use tempfile::NamedTempFile;
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+struct TestDb {
+    pool: SqlitePool,
+    _db_file: NamedTempFile,
+}
+
+async fn setup_db() -> TestDb {
+    let db_file = NamedTempFile::new().expect("create temp sqlite file");
+    let options = SqliteConnectOptions::new()
+        .filename(db_file.path())
+        .create_if_missing(true);
+
+    let mut conn = SqliteConnection::connect_with(&options)
+        .await
+        .expect("connect sqlite for migrations");
+    run_migrations(&mut conn).await.expect("run migrations");
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .expect("connect sqlite file");
+
+    TestDb {
+        pool,
+        _db_file: db_file,
+    }
+}
+
+async fn run_migrations(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    let mut entries: Vec<_> = fs::read_dir("migrations")
+        .map_err(sqlx::Error::Io)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().and_then(|ext| ext.to_str()) == Some("sql"))
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let contents = fs::read_to_string(entry.path()).map_err(sqlx::Error::Io)?;
+        for statement in contents.split(';') {
+            let statement = statement.trim();
+            if !statement.is_empty() {
+                sqlx::query(statement).execute(&mut *conn).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn seed_event(pool: &SqlitePool, received_at: &str) -> Uuid {
+    let endpoint_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO endpoints (id, target_url) VALUES (?, 'https://example.com/hook')")
+        .bind(endpoint_id.to_string())
+        .execute(pool)
+        .await
+        .expect("insert endpoint");
+    let event_id = Uuid::new_v4();
+    sqlx::query(
+        r"
+        INSERT INTO webhook_events (
+            id, endpoint_id, provider, headers, payload, status, attempts, received_at
+        )
+        VALUES (?, ?, 'stripe', '{}', '{}', 'delivered', 1, ?)
+        ",
+    )
+    .bind(event_id.to_string())
+    .bind(endpoint_id.to_string())
+    .bind(received_at)
+    .execute(pool)
+    .await
+    .expect("insert event");
+    event_id
+}
+
+fn day(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+#[tokio::test]
+async fn scheduled_export_backfills_every_missed_day() {
+    let db = setup_db().await;
+    seed_event(&db.pool, "2024-03-01T12:00:00Z").await;
+    seed_event(&db.pool, "2024-03-03T12:00:00Z").await;
+    let dir = tempfile::tempdir().unwrap();
+    let config = ExportScheduleConfig {
+        sink: Some(ExportSink::Dir(dir.path().to_path_buf())),
+        hour_utc: 2,
+        ..ExportScheduleConfig::default()
+    };
+    let sink = open_sink(config.sink.as_ref().unwrap()).unwrap();
+
+    // Before the hour the previous day is not due yet.
+    let before_hour = Utc.with_ymd_and_hms(2024, 3, 2, 1, 0, 0).unwrap();
+    let exported = run_scheduled_export(&db.pool, sink.as_ref(), &config, before_hour)
+        .await
+        .unwrap();
+    assert!(exported.is_empty());
+    assert_eq!(
+        get_export_status(&db.pool).await.unwrap().last_exported_day,
+        None
+    );
+
+    let after_hour = Utc.with_ymd_and_hms(2024, 3, 2, 3, 0, 0).unwrap();
+    let exported = run_scheduled_export(&db.pool, sink.as_ref(), &config, after_hour)
+        .await
+        .unwrap();
+    assert_eq!(exported, vec![day("2024-03-01")]);
+
+    // Three days later every day since the last export is written.
+    let later = Utc.with_ymd_and_hms(2024, 3, 5, 3, 0, 0).unwrap();
+    let exported = run_scheduled_export(&db.pool, sink.as_ref(), &config, later)
+        .await
+        .unwrap();
+    assert_eq!(
+        exported,
+        vec![day("2024-03-02"), day("2024-03-03"), day("2024-03-04")]
+    );
+    for (exported_day, events) in [
+        ("2024-03-01", 1),
+        ("2024-03-02", 0),
+        ("2024-03-03", 1),
+        ("2024-03-04", 0),
+    ] {
+        let path = export_path(dir.path(), day(exported_day), ExportFormat::Ndjson);
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), events, "{exported_day}");
+    }
+    let status = get_export_status(&db.pool).await.unwrap();
+    assert_eq!(status.last_exported_day.as_deref(), Some("2024-03-04"));
+    assert_eq!(status.consecutive_failures, 0);
+    assert!(
+        run_scheduled_export(&db.pool, sink.as_ref(), &config, later)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn scheduled_export_failures_are_recorded_and_retried() {
+    let db = setup_db().await;
+    let dir = tempfile::tempdir().unwrap();
+    let config = ExportScheduleConfig {
+        sink: Some(ExportSink::Dir(dir.path().to_path_buf())),
+        hour_utc: 0,
+        ..ExportScheduleConfig::default()
+    };
+    let sink = open_sink(config.sink.as_ref().unwrap()).unwrap();
+    let first = Utc.with_ymd_and_hms(2024, 3, 2, 3, 0, 0).unwrap();
+    run_scheduled_export(&db.pool, sink.as_ref(), &config, first)
+        .await
+        .unwrap();
+
+    // A file where the 2024-03-02 partition directory belongs fails that
+    // day, and the days after it wait.
+    fs::write(dir.path().join("dt=2024-03-02"), "").unwrap();
+    let later = Utc.with_ymd_and_hms(2024, 3, 4, 3, 0, 0).unwrap();
+    for attempt in 1..=2 {
+        assert!(
+            run_scheduled_export(&db.pool, sink.as_ref(), &config, later)
+                .await
+                .is_err()
+        );
+        let status = get_export_status(&db.pool).await.unwrap();
+        assert_eq!(status.last_exported_day.as_deref(), Some("2024-03-01"));
+        assert_eq!(status.consecutive_failures, attempt);
+        assert!(
+            status
+                .last_error
+                .as_deref()
+                .unwrap()
+                .contains("export of 2024-03-02 failed"),
+            "{status:?}"
+        );
+    }
+    let metrics = render_export_metrics(&get_export_status(&db.pool).await.unwrap());
+    assert!(metrics.contains("receiver_scheduled_export_consecutive_failures 2\n"));
+    assert!(metrics.contains("receiver_scheduled_export_last_day_timestamp_seconds 1709251200\n"));
+
+    fs::remove_file(dir.path().join("dt=2024-03-02")).unwrap();
+    let exported = run_scheduled_export(&db.pool, sink.as_ref(), &config, later)
+        .await
+        .unwrap();
+    assert_eq!(exported, vec![day("2024-03-02"), day("2024-03-03")]);
+    let status = get_export_status(&db.pool).await.unwrap();
+    assert_eq!(status.consecutive_failures, 0);
+    assert!(status.last_error.is_some());
+}
+
+#[tokio::test]
+async fn export_day_puts_the_partition_into_an_s3_bucket() {
+    let db = setup_db().await;
+    let event_id = seed_event(&db.pool, "2024-03-01T12:00:00Z").await;
+
+    let uploads = Arc::new(Mutex::new(Vec::new()));
+    let recorded = uploads.clone();
+    let app = Router::new().fallback(move |method: Method, uri: Uri, body: Bytes| {
+        let recorded = recorded.clone();
+        async move {
+            recorded
+                .lock()
+                .unwrap()
+                .push((method, uri.path().to_string(), body));
+            (StatusCode::OK, [(ETAG, "\"etag\"")])
+        }
+    });
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let bucket = AmazonS3Builder::new()
+        .with_endpoint(format!("http://{addr}"))
+        .with_allow_http(true)
+        .with_region("us-east-1")
+        .with_bucket_name("warehouse")
+        .with_access_key_id("key")
+        .with_secret_access_key("secret")
+        .build()
+        .unwrap();
+    let sink = PrefixStore::new(bucket, "receiver/events");
+
+    let key = export_day(&db.pool, &sink, ExportFormat::Ndjson, day("2024-03-01"))
+        .await
+        .unwrap();
+    assert_eq!(key, "dt=2024-03-01/events.ndjson");
+
+    let uploads = uploads.lock().unwrap();
+    assert_eq!(uploads.len(), 1);
+    let (method, path, body) = &uploads[0];
+    assert_eq!(method, Method::PUT);
+    assert_eq!(
+        path,
+        "/warehouse/receiver/events/dt%3D2024-03-01/events.ndjson"
+    );
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.contains(&event_id.to_string()), "{body}");
+}
@@ -0,0 +1,2099 @@
+#![allow(
+    clippy::expect_used,
+    clippy::unwrap_used,
+    clippy::needless_raw_string_hashes
+)]
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+    routing::{get, post},
+};
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use flate2::{Compression, write::GzEncoder};
+use hmac::{Hmac, Mac};
+use http_body_util::BodyExt;
+use receiver::{
+    dispatcher::{DispatcherConfig, lease_events, report_delivery},
+    handlers::ingest::{delivery_receipt_handler, ingest_health_handler, ingest_source_handler},
+    ingest::{
+        IngestConfig, IngestJournal, IngestQueue, ingest_backpressured, replay_journal,
+        scrub_payload,
+    },
+    inspector::{
+        EndpointScope, ListEventsParams, ScrubScope, UsageParams, create_endpoint_group,
+        delete_event, find_missing_provider_events, get_event, get_group_quota, group_quotas,
+        list_endpoint_secret_ids, list_events, provider_ingest_stats, render_quota_metrics,
+        replay_event, rotate_endpoint_secret, search_customer_events, set_endpoint_group,
+        set_group_quota, set_payload_schema, set_scrub_rules, usage_rollups,
+    },
+    jobs::{
+        StoreError as JobStoreError, cancel_job, create_reconcile_job, fail_interrupted_jobs,
+        get_job, get_reconcile_report, lease_reconcile_runs, record_reconcile_result,
+    },
+    secrets::{FileProvider, SecretError, SecretStore},
+    snapshot::{SnapshotError, restore_snapshot, write_snapshot},
+    state::AppState,
+    types::{
+        ApiErrorCode, ApiErrorResponse, DeliveryReceipt, IngestHealth, IngestHealthStatus,
+        IngestResponse, JobKind, JobStatus, LeaseRequest, PayloadEncoding, PayloadIntegrity,
+        ReconcileLeaseRequest, ReconcileResultRequest, ReplayAttemptBudget, ReportAttempt,
+        ReportOutcome, ReportRequest, ScrubAction, ScrubRule, StartReconcileRequest,
+        WebhookEventStatus,
+    },
+};
+use sha2::{Digest, Sha256};
+use sqlx::{
+    Connection, SqliteConnection, SqlitePool,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+use std::fs;
+use std::io::Write;
+use tempfile::NamedTempFile;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+struct TestDb {
+    pool: SqlitePool,
+    _db_file: NamedTempFile,
+}
+
+async fn setup_db() -> TestDb {
+    let db_file = NamedTempFile::new().expect("create temp sqlite file");
+    let options = SqliteConnectOptions::new()
+        .filename(db_file.path())
+        .create_if_missing(true)
+        .busy_timeout(std::time::Duration::from_millis(500));
+
+    let mut conn = SqliteConnection::connect_with(&options)
+        .await
+        .expect("connect sqlite for migrations");
+    sqlx::query("PRAGMA foreign_keys = ON;")
+        .execute(&mut conn)
+        .await
+        .expect("enable foreign keys");
+    run_migrations(&mut conn).await.expect("run migrations");
+    conn.close().await.expect("close migration conn");
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .after_connect(|conn, _| {
+            Box::pin(async move {
+                sqlx::query("PRAGMA foreign_keys = ON;")
+                    .execute(conn)
+                    .await?;
+                Ok(())
+            })
+        })
+        .connect_with(options)
+        .await
+        .expect("connect sqlite");
+
+    TestDb {
+        pool,
+        _db_file: db_file,
+    }
+}
+
+async fn run_migrations(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    let mut entries: Vec<_> = fs::read_dir("migrations")
+        .map_err(sqlx::Error::Io)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("sql"))
+        .collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let contents = fs::read_to_string(entry.path()).map_err(sqlx::Error::Io)?;
+        for stmt in contents.split(';') {
+            let stmt = stmt.trim();
+            if !stmt.is_empty() {
+                sqlx::query(stmt).execute(&mut *conn).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn seed_source(pool: &SqlitePool, slug: &str, provider: &str, secret: &str) -> Uuid {
+    let endpoint_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO endpoints (id, target_url) VALUES (?, ?)")
+        .bind(endpoint_id.to_string())
+        .bind("https://example.com/hook")
+        .execute(pool)
+        .await
+        .expect("insert endpoint");
+
+    let source_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO sources (id, slug, provider, endpoint_id, secret, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(source_id.to_string())
+    .bind(slug)
+    .bind(provider)
+    .bind(endpoint_id.to_string())
+    .bind(secret)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await
+    .expect("insert source");
+
+    source_id
+}
+
+fn build_app(pool: SqlitePool) -> Router {
+    build_app_with_queue(pool, None)
+}
+
+fn build_app_with_queue(pool: SqlitePool, ingest_queue: Option<IngestQueue>) -> Router {
+    build_app_with_config(pool, IngestConfig::default(), ingest_queue)
+}
+
+fn build_app_with_config(
+    pool: SqlitePool,
+    ingest: IngestConfig,
+    ingest_queue: Option<IngestQueue>,
+) -> Router {
+    let state = AppState {
+        pool,
+        dispatcher: DispatcherConfig::default(),
+        inspector_api_token: None,
+        inspector_scoped_tokens: Vec::new(),
+        inspector_oidc: None,
+        share_links: None,
+        bundle_signing_key: None,
+        audit_reads: false,
+        ingest,
+        ingest_queue,
+        secrets: SecretStore::default(),
+        job_dir: None,
+    };
+    Router::new()
+        .route("/ingest/s/:source_slug", post(ingest_source_handler))
+        .route(
+            "/ingest/:endpoint_id/events/:provider_event_id/status",
+            get(delivery_receipt_handler),
+        )
+        .route("/health", get(ingest_health_handler))
+        .with_state(state)
+}
+
+fn sign(secret: &str, parts: &[&[u8]]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    for part in parts {
+        mac.update(part);
+    }
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn ingest_request(slug: &str, header: (&str, String), body: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/ingest/s/{slug}"))
+        .header("content-type", "application/json")
+        .header(header.0, header.1)
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn ingest_with_valid_signature_records_source() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "acme-billing", "acme", "s3cret").await;
+    let app = build_app(db.pool.clone());
+
+    let body = r#"{"type":"invoice.paid"}"#;
+    let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+    let response = app
+        .oneshot(ingest_request(
+            "acme-billing",
+            ("x-webhook-signature", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+
+    let event = get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+        .await
+        .expect("get_event")
+        .event;
+    assert_eq!(event.source_id, Some(source_id));
+    assert_eq!(event.provider, "acme");
+    assert_eq!(event.payload, body);
+    assert_eq!(event.status, WebhookEventStatus::Pending);
+    assert_eq!(
+        event.headers.get("content-type").map(String::as_str),
+        Some("application/json")
+    );
+
+    let listed = list_events(
+        &db.pool,
+        &EndpointScope::All,
+        &ListEventsParams {
+            limit: 50,
+            before: None,
+            status: None,
+            endpoint_id: None,
+            source_id: Some(source_id),
+            group_id: None,
+            provider: None,
+            stuck_after_minutes: None,
+            schema_invalid: None,
+            is_replay: None,
+            has_replays: None,
+            include_last_attempt: false,
+            updated_since: None,
+            worker_version: None,
+            fields: None,
+            metadata: Vec::new(),
+        },
+    )
+    .await
+    .expect("list_events");
+    assert_eq!(listed.events.len(), 1);
+    assert_eq!(listed.events[0].event.id, ingested.event_id);
+
+    let other = list_events(
+        &db.pool,
+        &EndpointScope::All,
+        &ListEventsParams {
+            limit: 50,
+            before: None,
+            status: None,
+            endpoint_id: None,
+            source_id: Some(Uuid::new_v4()),
+            group_id: None,
+            provider: None,
+            stuck_after_minutes: None,
+            schema_invalid: None,
+            is_replay: None,
+            has_replays: None,
+            include_last_attempt: false,
+            updated_since: None,
+            worker_version: None,
+            fields: None,
+            metadata: Vec::new(),
+        },
+    )
+    .await
+    .expect("list_events");
+    assert!(other.events.is_empty());
+}
+
+#[tokio::test]
+async fn ingest_with_wrong_signature_returns_401() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "acme-billing", "acme", "s3cret").await;
+    let app = build_app(db.pool.clone());
+
+    let body = r#"{"type":"invoice.paid"}"#;
+    let signature = format!("sha256={}", sign("wrong", &[body.as_bytes()]));
+    let response = app
+        .oneshot(ingest_request(
+            "acme-billing",
+            ("x-webhook-signature", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhook_events")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn ingest_unknown_source_returns_404() {
+    let db = setup_db().await;
+    let app = build_app(db.pool.clone());
+
+    let response = app
+        .oneshot(ingest_request(
+            "missing",
+            ("x-webhook-signature", "sha256=00".to_string()),
+            "{}",
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn ingest_github_signature() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "gh", "github", "gh-secret").await;
+    let app = build_app(db.pool.clone());
+
+    let body = r#"{"action":"opened"}"#;
+    let signature = format!("sha256={}", sign("gh-secret", &[body.as_bytes()]));
+    let response = app
+        .oneshot(ingest_request(
+            "gh",
+            ("x-hub-signature-256", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn ingest_extracts_provider_metadata_for_filtering() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "gh", "github", "gh-secret").await;
+
+    let mut event_ids = Vec::new();
+    for repository in ["acme/api", "acme/web"] {
+        let body = format!(
+            r#"{{"action":"opened","repository":{{"full_name":"{repository}"}},"sender":{{"login":"octocat"}}}}"#
+        );
+        let signature = format!("sha256={}", sign("gh-secret", &[body.as_bytes()]));
+        let response = build_app(db.pool.clone())
+            .oneshot(ingest_request(
+                "gh",
+                ("x-hub-signature-256", signature),
+                &body,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+        event_ids.push(ingested.event_id);
+    }
+
+    let stored = get_event(&db.pool, &EndpointScope::All, event_ids[0])
+        .await
+        .unwrap();
+    let metadata = stored.metadata.expect("metadata extracted");
+    assert_eq!(metadata["repository"], "acme/api");
+    assert_eq!(metadata["sender"], "octocat");
+    assert!(!metadata.contains_key("organization"));
+
+    let mut params = ListEventsParams {
+        limit: 50,
+        before: None,
+        status: None,
+        endpoint_id: None,
+        source_id: Some(source_id),
+        group_id: None,
+        provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: vec![("repository".to_string(), "acme/web".to_string())],
+    };
+    let listed = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .unwrap();
+    assert_eq!(listed.events.len(), 1);
+    assert_eq!(listed.events[0].event.id, event_ids[1]);
+
+    params
+        .metadata
+        .push(("sender".to_string(), "someone-else".to_string()));
+    let listed = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .unwrap();
+    assert!(listed.events.is_empty());
+}
+
+#[tokio::test]
+async fn delivery_receipt_reports_status_to_the_source() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "gh-receipts", "github", "gh-secret").await;
+    let endpoint_id: String = sqlx::query_scalar("SELECT endpoint_id FROM sources WHERE id = ?")
+        .bind(source_id.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+
+    let body = r#"{"action":"opened"}"#;
+    let signature = format!("sha256={}", sign("gh-secret", &[body.as_bytes()]));
+    let request = Request::builder()
+        .method("POST")
+        .uri("/ingest/s/gh-receipts")
+        .header("content-type", "application/json")
+        .header("x-hub-signature-256", signature)
+        .header("x-github-delivery", "delivery-1")
+        .body(Body::from(body))
+        .unwrap();
+    let response = build_app(db.pool.clone()).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let receipt_request = |provider_event_id: &str, secret: &str| {
+        Request::builder()
+            .uri(format!(
+                "/ingest/{endpoint_id}/events/{provider_event_id}/status"
+            ))
+            .header("authorization", format!("Bearer {secret}"))
+            .body(Body::empty())
+            .unwrap()
+    };
+    let fetch_receipt = || async {
+        let response = build_app(db.pool.clone())
+            .oneshot(receipt_request("delivery-1", "gh-secret"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice::<DeliveryReceipt>(&bytes).unwrap()
+    };
+
+    let response = build_app(db.pool.clone())
+        .oneshot(receipt_request("delivery-1", "wrong-secret"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = build_app(db.pool.clone())
+        .oneshot(receipt_request("delivery-2", "gh-secret"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let receipt = fetch_receipt().await;
+    assert_eq!(receipt.status, WebhookEventStatus::Pending);
+    assert!(!receipt.delivered);
+    assert_eq!(receipt.attempts, 0);
+
+    let config = DispatcherConfig::default();
+    let lease = LeaseRequest {
+        limit: 1,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: Some(endpoint_id.clone()),
+    };
+    let leased = lease_events(&db.pool, &config, &lease).await.unwrap();
+    let now = Utc::now().to_rfc3339();
+    let report = ReportRequest {
+        worker_id: "worker-1".to_string(),
+        worker_version: None,
+        worker_region: None,
+        event_id: leased[0].event.id,
+        outcome: ReportOutcome::Delivered,
+        retryable: false,
+        next_attempt_at: None,
+        attempt: ReportAttempt {
+            started_at: now.clone(),
+            finished_at: now.clone(),
+            request_headers: BTreeMap::new(),
+            request_body: body.to_string(),
+            response_status: Some(200),
+            response_headers: None,
+            response_body: None,
+            error_kind: None,
+            error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
+        },
+    };
+    report_delivery(&db.pool, &config, &report).await.unwrap();
+
+    let receipt = fetch_receipt().await;
+    assert_eq!(receipt.event_id, leased[0].event.id);
+    assert_eq!(receipt.status, WebhookEventStatus::Delivered);
+    assert!(receipt.delivered);
+    assert_eq!(receipt.delivered_at.as_deref(), Some(now.as_str()));
+    assert_eq!(receipt.attempts, 1);
+}
+
+#[tokio::test]
+async fn ingest_stripe_signature_checks_timestamp_tolerance() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "stripe", "stripe", "whsec_test").await;
+    let body = r#"{"id":"evt_1"}"#;
+
+    let fresh = Utc::now().timestamp();
+    let fresh_sig = sign(
+        "whsec_test",
+        &[format!("{fresh}.").as_bytes(), body.as_bytes()],
+    );
+    let response = build_app(db.pool.clone())
+        .oneshot(ingest_request(
+            "stripe",
+            ("stripe-signature", format!("t={fresh},v1={fresh_sig}")),
+            body,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+    let event = get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+        .await
+        .unwrap()
+        .event;
+    assert_eq!(event.provider_event_id.as_deref(), Some("evt_1"));
+
+    let stale = fresh - 3600;
+    let stale_sig = sign(
+        "whsec_test",
+        &[format!("{stale}.").as_bytes(), body.as_bytes()],
+    );
+    let response = build_app(db.pool.clone())
+        .oneshot(ingest_request(
+            "stripe",
+            ("stripe-signature", format!("t={stale},v1={stale_sig}")),
+            body,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn reconcile_reports_provider_events_never_ingested() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "gh", "github", "gh-secret").await;
+
+    for delivery_id in ["d-1", "d-3"] {
+        let body = r#"{"action":"opened"}"#;
+        let signature = format!("sha256={}", sign("gh-secret", &[body.as_bytes()]));
+        let request = Request::builder()
+            .method("POST")
+            .uri("/ingest/s/gh")
+            .header("x-hub-signature-256", signature)
+            .header("x-github-delivery", delivery_id)
+            .body(Body::from(body))
+            .unwrap();
+        let response = build_app(db.pool.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let provider_ids: Vec<String> = ["d-1", "d-2", "d-3", "d-4"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let missing =
+        find_missing_provider_events(&db.pool, &EndpointScope::All, "github", &provider_ids)
+            .await
+            .expect("reconcile");
+    assert_eq!(missing, vec!["d-2".to_string(), "d-4".to_string()]);
+
+    let other_provider =
+        find_missing_provider_events(&db.pool, &EndpointScope::All, "stripe", &provider_ids)
+            .await
+            .expect("reconcile");
+    assert_eq!(other_provider.len(), 4);
+}
+
+async fn ingest_github_delivery(pool: &SqlitePool, delivery_id: &str) {
+    let body = r#"{"action":"opened"}"#;
+    let signature = format!("sha256={}", sign("gh-secret", &[body.as_bytes()]));
+    let request = Request::builder()
+        .method("POST")
+        .uri("/ingest/s/gh")
+        .header("x-hub-signature-256", signature)
+        .header("x-github-delivery", delivery_id)
+        .body(Body::from(body))
+        .unwrap();
+    let response = build_app(pool.clone()).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+fn reconcile_window(source_id: Uuid, fetch_missing: bool) -> StartReconcileRequest {
+    StartReconcileRequest {
+        source_id,
+        since: "2026-01-01T00:00:00Z".to_string(),
+        until: "2026-01-02T00:00:00Z".to_string(),
+        fetch_missing,
+    }
+}
+
+fn reconcile_lease(worker_id: &str) -> ReconcileLeaseRequest {
+    ReconcileLeaseRequest {
+        worker_id: worker_id.to_string(),
+        limit: 10,
+        lease_ms: 60_000,
+    }
+}
+
+#[tokio::test]
+async fn reconcile_job_reports_missing_events_until_redelivered() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "gh", "github", "gh-secret").await;
+    ingest_github_delivery(&db.pool, "d-1").await;
+
+    let job = create_reconcile_job(&db.pool, "ops", &reconcile_window(source_id, true))
+        .await
+        .expect("create reconcile job");
+    assert_eq!(job.kind, JobKind::Reconcile);
+    assert_eq!(job.status, JobStatus::Queued);
+
+    let tasks = lease_reconcile_runs(&db.pool, &reconcile_lease("worker-a"))
+        .await
+        .expect("lease");
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].job_id, job.id);
+    assert_eq!(tasks[0].source_slug, "gh");
+    assert_eq!(tasks[0].provider, "github");
+    assert!(tasks[0].fetch_missing);
+    let again = lease_reconcile_runs(&db.pool, &reconcile_lease("worker-b"))
+        .await
+        .expect("lease again");
+    assert!(
+        again.is_empty(),
+        "a leased reconciliation is not handed out twice"
+    );
+
+    let mut result = ReconcileResultRequest {
+        worker_id: "worker-b".to_string(),
+        job_id: job.id,
+        provider_event_ids: ["d-1", "d-2", "d-2", "d-3"]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        error: None,
+    };
+    let err = record_reconcile_result(&db.pool, &result)
+        .await
+        .expect_err("only the leasing worker reports");
+    assert!(matches!(err, JobStoreError::Conflict(ref code) if code == "lease_not_owned"));
+
+    result.worker_id = "worker-a".to_string();
+    let recorded = record_reconcile_result(&db.pool, &result)
+        .await
+        .expect("record result");
+    assert_eq!(recorded.checked, 3);
+    assert_eq!(recorded.missing, vec!["d-2".to_string(), "d-3".to_string()]);
+    assert!(recorded.fetch_missing);
+
+    let report = get_reconcile_report(&db.pool, job.id)
+        .await
+        .expect("report");
+    assert_eq!(report.job.status, JobStatus::Completed);
+    assert_eq!((report.job.total, report.job.processed), (3, 3));
+    assert_eq!(report.source_id, source_id);
+    assert_eq!(report.since, "2026-01-01T00:00:00Z");
+    let ids: Vec<&str> = report
+        .missing
+        .iter()
+        .map(|missing| missing.provider_event_id.as_str())
+        .collect();
+    assert_eq!(ids, ["d-2", "d-3"]);
+    assert!(
+        report
+            .missing
+            .iter()
+            .all(|missing| missing.event_id.is_none())
+    );
+
+    // The worker asks GitHub to redeliver d-2, which arrives signed as usual.
+    ingest_github_delivery(&db.pool, "d-2").await;
+    let report = get_reconcile_report(&db.pool, job.id)
+        .await
+        .expect("report after redelivery");
+    assert!(report.missing[0].event_id.is_some());
+    assert!(report.missing[1].event_id.is_none());
+}
+
+#[tokio::test]
+async fn reconcile_jobs_outlive_restarts_and_record_worker_errors() {
+    let db = setup_db().await;
+    let github = seed_source(&db.pool, "gh", "github", "gh-secret").await;
+    let acme = seed_source(&db.pool, "acme", "acme", "s3cret").await;
+
+    let err = create_reconcile_job(&db.pool, "ops", &reconcile_window(acme, false))
+        .await
+        .expect_err("acme has no deliveries API");
+    assert!(matches!(err, JobStoreError::Conflict(_)));
+
+    let cancelled = create_reconcile_job(&db.pool, "ops", &reconcile_window(github, false))
+        .await
+        .expect("create job to cancel");
+    let cancelled = cancel_job(&db.pool, cancelled.id).await.expect("cancel");
+    assert_eq!(cancelled.status, JobStatus::Cancelled);
+
+    let job = create_reconcile_job(&db.pool, "ops", &reconcile_window(github, false))
+        .await
+        .expect("create job");
+    fail_interrupted_jobs(&db.pool)
+        .await
+        .expect("close interrupted jobs");
+    let job = get_job(&db.pool, job.id).await.expect("job");
+    assert_eq!(job.status, JobStatus::Queued, "workers run reconciliations");
+
+    let tasks = lease_reconcile_runs(&db.pool, &reconcile_lease("worker-a"))
+        .await
+        .expect("lease");
+    let leased: Vec<Uuid> = tasks.iter().map(|task| task.job_id).collect();
+    assert_eq!(leased, vec![job.id]);
+
+    record_reconcile_result(
+        &db.pool,
+        &ReconcileResultRequest {
+            worker_id: "worker-a".to_string(),
+            job_id: job.id,
+            provider_event_ids: Vec::new(),
+            error: Some("github returned 401".to_string()),
+        },
+    )
+    .await
+    .expect("record error");
+    let report = get_reconcile_report(&db.pool, job.id)
+        .await
+        .expect("report");
+    assert_eq!(report.job.status, JobStatus::Failed);
+    assert_eq!(report.job.error.as_deref(), Some("github returned 401"));
+    assert!(report.missing.is_empty());
+}
+
+async fn set_ingest_mode(pool: &SqlitePool, source_id: Uuid, mode: &str) {
+    sqlx::query(
+        "UPDATE endpoints SET ingest_mode = ? WHERE id = (SELECT endpoint_id FROM sources WHERE id = ?)",
+    )
+    .bind(mode)
+    .bind(source_id.to_string())
+    .execute(pool)
+    .await
+    .expect("update ingest mode");
+}
+
+#[tokio::test]
+async fn fast_ack_returns_202_and_persists_in_background() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "fast", "acme", "s3cret").await;
+    set_ingest_mode(&db.pool, source_id, "fast_ack").await;
+    let queue = IngestQueue::spawn(db.pool.clone(), 8, None);
+
+    let body = r#"{"type":"order.created"}"#;
+    let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+    let response = build_app_with_queue(db.pool.clone(), Some(queue))
+        .oneshot(ingest_request(
+            "fast",
+            ("x-webhook-signature", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+
+    let mut persisted = None;
+    for _ in 0..50 {
+        if let Ok(found) = get_event(&db.pool, &EndpointScope::All, ingested.event_id).await {
+            persisted = Some(found.event);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let event = persisted.expect("fast-ack event should be persisted by the writer");
+    assert_eq!(event.payload, body);
+    assert_eq!(event.source_id, Some(source_id));
+}
+
+#[tokio::test]
+async fn fast_ack_without_queue_persists_synchronously() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "fast", "acme", "s3cret").await;
+    set_ingest_mode(&db.pool, source_id, "fast_ack").await;
+
+    let body = "{}";
+    let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+    let response = build_app(db.pool.clone())
+        .oneshot(ingest_request(
+            "fast",
+            ("x-webhook-signature", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+    get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+        .await
+        .expect("event persisted before response");
+}
+
+#[tokio::test]
+async fn fast_ack_journal_is_truncated_once_persisted() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "fast", "acme", "s3cret").await;
+    set_ingest_mode(&db.pool, source_id, "fast_ack").await;
+    let journal_file = NamedTempFile::new().unwrap();
+    let journal = IngestJournal::open(journal_file.path()).unwrap();
+    let queue = IngestQueue::spawn(db.pool.clone(), 8, Some(journal));
+
+    let body = "{}";
+    let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+    let response = build_app_with_queue(db.pool.clone(), Some(queue))
+        .oneshot(ingest_request(
+            "fast",
+            ("x-webhook-signature", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+
+    let mut truncated = false;
+    for _ in 0..50 {
+        if get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+            .await
+            .is_ok()
+            && fs::metadata(journal_file.path()).unwrap().len() == 0
+        {
+            truncated = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(
+        truncated,
+        "journal should be emptied after the event is stored"
+    );
+}
+
+#[tokio::test]
+async fn fast_ack_writes_are_retried_until_the_database_recovers() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "fast", "acme", "s3cret").await;
+    set_ingest_mode(&db.pool, source_id, "fast_ack").await;
+    let journal_file = NamedTempFile::new().unwrap();
+    let journal = IngestJournal::open(journal_file.path()).unwrap();
+    let queue = IngestQueue::spawn(db.pool.clone(), 8, Some(journal));
+    sqlx::query(
+        "CREATE TRIGGER fail_event_inserts BEFORE INSERT ON webhook_events \
+        BEGIN SELECT RAISE(ABORT, 'database is unavailable'); END",
+    )
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    let body = "{}";
+    let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+    let response = build_app_with_queue(db.pool.clone(), Some(queue))
+        .oneshot(ingest_request(
+            "fast",
+            ("x-webhook-signature", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+
+    // Well past the time the writer used to give up after.
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    assert!(
+        get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+            .await
+            .is_err()
+    );
+    assert!(fs::metadata(journal_file.path()).unwrap().len() > 0);
+
+    sqlx::query("DROP TRIGGER fail_event_inserts")
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let mut stored = false;
+    for _ in 0..100 {
+        if get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+            .await
+            .is_ok()
+            && fs::metadata(journal_file.path()).unwrap().len() == 0
+        {
+            stored = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert!(stored, "the event should be stored once inserts succeed");
+}
+
+#[tokio::test]
+async fn replay_journal_recovers_unpersisted_events() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "fast", "acme", "s3cret").await;
+    let endpoint_id: String = sqlx::query_scalar("SELECT endpoint_id FROM sources WHERE id = ?")
+        .bind(source_id.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    let event_id = Uuid::new_v4();
+    let record = serde_json::json!({
+        "id": event_id,
+        "endpoint_id": endpoint_id,
+        "source_id": source_id.to_string(),
+        "provider": "acme",
+        "provider_event_id": "evt_1",
+        "headers": {"content-type": "application/json"},
+        "payload": "{}",
+        "received_at": "2026-01-01T00:00:00Z",
+    });
+    let journal_file = NamedTempFile::new().unwrap();
+    fs::write(journal_file.path(), format!("{record}\n{{\"id\":\"torn")).unwrap();
+
+    let replayed = replay_journal(&db.pool, journal_file.path()).await.unwrap();
+    assert_eq!(replayed, 1);
+    assert_eq!(fs::metadata(journal_file.path()).unwrap().len(), 0);
+
+    let event = get_event(&db.pool, &EndpointScope::All, event_id)
+        .await
+        .unwrap()
+        .event;
+    assert_eq!(event.source_id, Some(source_id));
+    assert_eq!(event.provider_event_id.as_deref(), Some("evt_1"));
+
+    fs::write(journal_file.path(), format!("{record}\n")).unwrap();
+    let replayed = replay_journal(&db.pool, journal_file.path()).await.unwrap();
+    assert_eq!(replayed, 1);
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhook_events")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn replay_journal_missing_file_is_noop() {
+    let db = setup_db().await;
+    let dir = tempfile::tempdir().unwrap();
+    let replayed = replay_journal(&db.pool, dir.path().join("absent.journal"))
+        .await
+        .unwrap();
+    assert_eq!(replayed, 0);
+}
+
+#[tokio::test]
+async fn endpoint_default_ttl_sets_event_expiry() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "ttl", "acme", "s3cret").await;
+    sqlx::query(
+        "UPDATE endpoints SET default_ttl_seconds = 3600 \
+        WHERE id = (SELECT endpoint_id FROM sources WHERE slug = 'ttl')",
+    )
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    let body = "{}";
+    let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+    let response = build_app(db.pool.clone())
+        .oneshot(ingest_request(
+            "ttl",
+            ("x-webhook-signature", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+
+    let event = get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+        .await
+        .unwrap()
+        .event;
+    let received_at = chrono::DateTime::parse_from_rfc3339(&event.received_at).unwrap();
+    let expires_at =
+        chrono::DateTime::parse_from_rfc3339(event.expires_at.as_deref().unwrap()).unwrap();
+    assert_eq!((expires_at - received_at).num_seconds(), 3600);
+}
+
+#[test]
+fn scrub_payload_applies_paths_wildcards_and_arrays() {
+    let rules = vec![
+        ScrubRule {
+            path: "card.number".to_string(),
+            action: ScrubAction::Remove,
+        },
+        ScrubRule {
+            path: "customers.email".to_string(),
+            action: ScrubAction::MaskEmail,
+        },
+        ScrubRule {
+            path: "metadata.*".to_string(),
+            action: ScrubAction::Mask,
+        },
+    ];
+    let payload = r#"{"card":{"number":"4242","brand":"visa"},"customers":[{"email":"jane@example.com"},{"email":"x"}],"metadata":{"a":"1","b":2}}"#;
+
+    let scrubbed: serde_json::Value =
+        serde_json::from_str(&scrub_payload(payload, &rules)).unwrap();
+
+    assert_eq!(
+        scrubbed,
+        serde_json::json!({
+            "card": {"brand": "visa"},
+            "customers": [{"email": "j***@example.com"}, {"email": "[REDACTED]"}],
+            "metadata": {"a": "[REDACTED]", "b": "[REDACTED]"},
+        })
+    );
+    assert_eq!(scrub_payload("not json", &rules), "not json");
+}
+
+#[tokio::test]
+async fn ingest_scrubs_payload_and_records_rule_version() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "scrubbed", "acme", "s3cret").await;
+    let provider_scope = ScrubScope::Provider("acme".to_string());
+    set_scrub_rules(
+        &db.pool,
+        &EndpointScope::All,
+        &provider_scope,
+        &[ScrubRule {
+            path: "email".to_string(),
+            action: ScrubAction::Mask,
+        }],
+    )
+    .await
+    .unwrap();
+    let ruleset = set_scrub_rules(
+        &db.pool,
+        &EndpointScope::All,
+        &provider_scope,
+        &[ScrubRule {
+            path: "card.number".to_string(),
+            action: ScrubAction::Remove,
+        }],
+    )
+    .await
+    .unwrap();
+    assert_eq!(ruleset.version, 2);
+
+    let body =
+        r#"{"id":"evt_1","card":{"number":"4242424242424242","last4":"4242"},"email":"a@b.c"}"#;
+    let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+    let response = build_app(db.pool.clone())
+        .oneshot(ingest_request(
+            "scrubbed",
+            ("x-webhook-signature", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+
+    let event = get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+        .await
+        .unwrap()
+        .event;
+    assert!(!event.payload.contains("4242424242424242"));
+    assert!(
+        event.payload.contains("a@b.c"),
+        "only the current rules apply"
+    );
+    assert_eq!(event.scrub_ruleset_id, Some(ruleset.id));
+    assert_eq!(event.scrub_rule_version, Some(2));
+}
+
+#[tokio::test]
+async fn scrubbed_fields_are_left_out_of_provider_metadata() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "gh", "github", "gh-secret").await;
+    set_scrub_rules(
+        &db.pool,
+        &EndpointScope::All,
+        &ScrubScope::Provider("github".to_string()),
+        &[ScrubRule {
+            path: "organization.login".to_string(),
+            action: ScrubAction::Remove,
+        }],
+    )
+    .await
+    .unwrap();
+
+    let body = r#"{"action":"opened","repository":{"full_name":"acme/api"},"organization":{"login":"acme"},"sender":{"login":"octocat"}}"#;
+    let signature = format!("sha256={}", sign("gh-secret", &[body.as_bytes()]));
+    let response = build_app(db.pool.clone())
+        .oneshot(ingest_request(
+            "gh",
+            ("x-hub-signature-256", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+
+    let stored = get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+        .await
+        .unwrap();
+    let metadata = stored.metadata.expect("metadata extracted");
+    assert_eq!(metadata["repository"], "acme/api");
+    assert_eq!(metadata["sender"], "octocat");
+    assert!(!metadata.contains_key("organization"));
+    let found = search_customer_events(&db.pool, &EndpointScope::All, "acme", 50)
+        .await
+        .unwrap();
+    assert!(found.events.is_empty());
+}
+
+#[tokio::test]
+async fn endpoint_signing_secret_id_replaces_plaintext_secret() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "vaulted", "acme", "plaintext").await;
+    let mut secret_file = NamedTempFile::new().unwrap();
+    writeln!(secret_file, "rotated-secret").unwrap();
+    sqlx::query(
+        "UPDATE endpoints SET signing_secret_id = ? \
+        WHERE id = (SELECT endpoint_id FROM sources WHERE slug = 'vaulted')",
+    )
+    .bind(format!("file:{}", secret_file.path().display()))
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    let body = "{}";
+    let stale = format!("sha256={}", sign("plaintext", &[body.as_bytes()]));
+    let response = build_app(db.pool.clone())
+        .oneshot(ingest_request(
+            "vaulted",
+            ("x-webhook-signature", stale),
+            body,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let signature = format!("sha256={}", sign("rotated-secret", &[body.as_bytes()]));
+    let response = build_app(db.pool.clone())
+        .oneshot(ingest_request(
+            "vaulted",
+            ("x-webhook-signature", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn rotated_endpoint_secret_stays_valid_until_its_expiry() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "rotating", "acme", "plaintext").await;
+    let endpoint_id: String =
+        sqlx::query_scalar("SELECT endpoint_id FROM sources WHERE slug = 'rotating'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    let endpoint_id = Uuid::parse_str(&endpoint_id).unwrap();
+    let mut secret_ids = Vec::new();
+    let mut secret_files = Vec::new();
+    for secret in ["first-secret", "second-secret", "third-secret"] {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "{secret}").unwrap();
+        secret_ids.push(format!("file:{}", file.path().display()));
+        secret_files.push(file);
+    }
+    let status_for = |secret: &str| {
+        let body = "{}";
+        let signature = format!("sha256={}", sign(secret, &[body.as_bytes()]));
+        let request = ingest_request("rotating", ("x-webhook-signature", signature), body);
+        let app = build_app(db.pool.clone());
+        async move { app.oneshot(request).await.unwrap().status() }
+    };
+    let future = (Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+    let past = (Utc::now() - chrono::Duration::hours(1)).to_rfc3339();
+
+    let secrets = rotate_endpoint_secret(
+        &db.pool,
+        &EndpointScope::All,
+        endpoint_id,
+        &secret_ids[0],
+        None,
+        &future,
+    )
+    .await
+    .unwrap();
+    assert!(secrets.secondary_secret_id.is_none());
+    assert_eq!(status_for("plaintext").await, StatusCode::UNAUTHORIZED);
+    assert_eq!(status_for("first-secret").await, StatusCode::OK);
+
+    let secrets = rotate_endpoint_secret(
+        &db.pool,
+        &EndpointScope::All,
+        endpoint_id,
+        &secret_ids[1],
+        None,
+        &future,
+    )
+    .await
+    .unwrap();
+    assert_eq!(secrets.secondary_secret_id.as_ref(), Some(&secret_ids[0]));
+    assert_eq!(status_for("first-secret").await, StatusCode::OK);
+    assert_eq!(status_for("second-secret").await, StatusCode::OK);
+    let mut stored = vec![
+        (endpoint_id.to_string(), secret_ids[0].clone()),
+        (endpoint_id.to_string(), secret_ids[1].clone()),
+    ];
+    stored.sort();
+    assert_eq!(list_endpoint_secret_ids(&db.pool).await.unwrap(), stored);
+
+    rotate_endpoint_secret(
+        &db.pool,
+        &EndpointScope::All,
+        endpoint_id,
+        &secret_ids[2],
+        None,
+        &past,
+    )
+    .await
+    .unwrap();
+    assert_eq!(status_for("first-secret").await, StatusCode::UNAUTHORIZED);
+    assert_eq!(status_for("second-secret").await, StatusCode::UNAUTHORIZED);
+    assert_eq!(status_for("third-secret").await, StatusCode::OK);
+
+    sqlx::query("UPDATE endpoints SET signing_secret_expires_at = ? WHERE id = ?")
+        .bind(&past)
+        .bind(endpoint_id.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(status_for("third-secret").await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn provider_stats_count_rejections_and_payload_sizes() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "acme-main", "acme", "acme-secret").await;
+    seed_source(&db.pool, "other-main", "other", "other-secret").await;
+    let send = |slug: &'static str, secret: &str, body: &'static str| {
+        let signature = format!("sha256={}", sign(secret, &[body.as_bytes()]));
+        let request = ingest_request(slug, ("x-webhook-signature", signature), body);
+        let app = build_app(db.pool.clone());
+        async move { app.oneshot(request).await.unwrap().status() }
+    };
+
+    assert_eq!(send("acme-main", "acme-secret", "{}").await, StatusCode::OK);
+    assert_eq!(
+        send("acme-main", "acme-secret", r#"{"a":"1234567890"}"#).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        send("acme-main", "wrong", "{}").await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        send("other-main", "wrong", "{}").await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    let stats = provider_ingest_stats(&db.pool, 60).await.unwrap();
+    assert_eq!(stats.len(), 2);
+    let acme = &stats[0];
+    assert_eq!(acme.provider, "acme");
+    assert_eq!(acme.accepted, 2);
+    assert_eq!(acme.rejected_signature, 1);
+    assert_eq!(acme.rejected_size, 0);
+    assert_eq!(acme.payload_bytes_p50, Some(2));
+    assert_eq!(acme.payload_bytes_p99, Some(18));
+    assert_eq!(acme.payload_bytes_max, Some(18));
+    let other = &stats[1];
+    assert_eq!(other.provider, "other");
+    assert_eq!(other.accepted, 0);
+    assert_eq!(other.rejected_signature, 1);
+    assert_eq!(other.payload_bytes_max, None);
+}
+
+#[test]
+fn secret_store_resolves_registered_schemes_only() {
+    struct Fixed;
+
+    impl receiver::secrets::SecretProvider for Fixed {
+        fn resolve(&self, key: &str) -> Result<String, SecretError> {
+            Ok(format!("value-for-{key}"))
+        }
+    }
+
+    let store = SecretStore::default().with_provider("vault", Fixed);
+    assert_eq!(
+        store.resolve("vault:kv/stripe#signing").unwrap(),
+        "value-for-kv/stripe#signing"
+    );
+
+    let store = SecretStore::default();
+    assert!(matches!(
+        store.resolve("vault:kv/stripe"),
+        Err(SecretError::UnknownScheme(_))
+    ));
+    assert!(matches!(
+        store.check_scheme("aws-sm:prod/stripe"),
+        Err(SecretError::UnknownScheme(_))
+    ));
+    assert!(
+        store
+            .check_scheme("env:RECEIVER_TEST_SECRET_THAT_IS_NOT_SET")
+            .is_ok()
+    );
+    assert!(matches!(
+        store.resolve("gcp:stripe"),
+        Err(SecretError::UnknownScheme(_))
+    ));
+    assert!(matches!(
+        store.resolve("no-scheme"),
+        Err(SecretError::InvalidId(_))
+    ));
+    assert!(matches!(
+        store.resolve("env:RECEIVER_TEST_SECRET_THAT_IS_NOT_SET"),
+        Err(SecretError::NotFound(_))
+    ));
+}
+
+#[test]
+fn file_secrets_stay_inside_the_base_dir() {
+    let root = tempfile::tempdir().unwrap();
+    let base = root.path().join("secrets");
+    std::fs::create_dir_all(base.join("stripe")).unwrap();
+    std::fs::write(base.join("stripe/signing"), "whsec_1\n").unwrap();
+    std::fs::write(root.path().join("outside"), "leaked").unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(root.path().join("outside"), base.join("link")).unwrap();
+    let store = SecretStore::default().with_provider(
+        "file",
+        FileProvider {
+            base_dir: Some(base.clone()),
+        },
+    );
+
+    assert_eq!(store.resolve("file:stripe/signing").unwrap(), "whsec_1");
+    assert_eq!(store.resolve("file:./stripe/signing").unwrap(), "whsec_1");
+    let escapes = [
+        "file:../outside".to_string(),
+        "file:stripe/../../outside".to_string(),
+        format!("file:{}", root.path().join("outside").display()),
+        #[cfg(unix)]
+        "file:link".to_string(),
+    ];
+    for secret_id in escapes {
+        assert!(
+            matches!(
+                store.resolve(&secret_id),
+                Err(SecretError::OutsideBaseDir(_))
+            ),
+            "{secret_id} should be refused"
+        );
+    }
+    assert!(matches!(
+        store.resolve("file:stripe/missing"),
+        Err(SecretError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn payload_schema_violations_are_recorded_and_filterable() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "typed", "acme", "s3cret").await;
+    let schema = r#"{"type":"object","required":["id","amount"],"properties":{"amount":{"type":"integer","minimum":0}}}"#;
+    set_payload_schema(&db.pool, "acme", "invoice.paid", schema, false)
+        .await
+        .unwrap();
+
+    let mut ingested_ids = Vec::new();
+    for body in [
+        r#"{"type":"invoice.paid","id":"in_1","amount":100}"#,
+        r#"{"type":"invoice.paid","amount":-5}"#,
+        r#"{"type":"invoice.voided"}"#,
+    ] {
+        let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+        let response = build_app(db.pool.clone())
+            .oneshot(ingest_request(
+                "typed",
+                ("x-webhook-signature", signature),
+                body,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+        ingested_ids.push(ingested.event_id);
+    }
+
+    let valid = get_event(&db.pool, &EndpointScope::All, ingested_ids[0])
+        .await
+        .unwrap()
+        .event;
+    assert_eq!(valid.event_type.as_deref(), Some("invoice.paid"));
+    assert_eq!(valid.schema_errors, None);
+    let invalid = get_event(&db.pool, &EndpointScope::All, ingested_ids[1])
+        .await
+        .unwrap()
+        .event;
+    assert_eq!(
+        invalid.schema_errors,
+        Some(vec![
+            "$: missing required property id".to_string(),
+            "$.amount: less than 0".to_string(),
+        ])
+    );
+
+    let listed = list_events(
+        &db.pool,
+        &EndpointScope::All,
+        &ListEventsParams {
+            limit: 50,
+            before: None,
+            status: None,
+            endpoint_id: None,
+            source_id: None,
+            group_id: None,
+            provider: None,
+            stuck_after_minutes: None,
+            schema_invalid: Some(true),
+            is_replay: None,
+            has_replays: None,
+            include_last_attempt: false,
+            updated_since: None,
+            worker_version: None,
+            fields: None,
+            metadata: Vec::new(),
+        },
+    )
+    .await
+    .unwrap();
+    let listed_ids: Vec<Uuid> = listed.events.iter().map(|item| item.event.id).collect();
+    assert_eq!(listed_ids, vec![ingested_ids[1]]);
+
+    set_payload_schema(&db.pool, "acme", "invoice.paid", schema, true)
+        .await
+        .unwrap();
+    let body = r#"{"type":"invoice.paid"}"#;
+    let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+    let response = build_app(db.pool.clone())
+        .oneshot(ingest_request(
+            "typed",
+            ("x-webhook-signature", signature),
+            body,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn binary_payloads_are_stored_as_blobs_and_returned_base64() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "binary", "acme", "s3cret").await;
+    let body: &[u8] = &[0x1f, 0x8b, 0x08, 0x00, 0xff, 0xfe];
+    let signature = format!("sha256={}", sign("s3cret", &[body]));
+    let request = Request::builder()
+        .method("POST")
+        .uri("/ingest/s/binary")
+        .header("content-type", "application/octet-stream")
+        .header("x-webhook-signature", signature)
+        .body(Body::from(body.to_vec()))
+        .unwrap();
+    let response = build_app(db.pool.clone()).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+
+    let stored_type: String =
+        sqlx::query_scalar("SELECT typeof(payload) FROM webhook_events WHERE id = ?")
+            .bind(ingested.event_id.to_string())
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert_eq!(stored_type, "blob");
+
+    let event = get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+        .await
+        .unwrap()
+        .event;
+    assert_eq!(event.payload_encoding, PayloadEncoding::Base64);
+    assert_eq!(event.payload, "H4sIAP/+");
+
+    let replayed = replay_event(
+        &db.pool,
+        &EndpointScope::All,
+        ingested.event_id,
+        false,
+        false,
+        ReplayAttemptBudget::Reset,
+        None,
+    )
+    .await
+    .unwrap();
+    let replayed = get_event(&db.pool, &EndpointScope::All, replayed.event.id)
+        .await
+        .unwrap()
+        .event;
+    assert_eq!(replayed.payload_encoding, PayloadEncoding::Base64);
+    assert_eq!(replayed.payload, event.payload);
+
+    let mut archive = Vec::new();
+    write_snapshot(&db.pool, &mut archive).await.unwrap();
+    let target = setup_db().await;
+    restore_snapshot(&target.pool, std::io::Cursor::new(&archive))
+        .await
+        .unwrap();
+    let restored = get_event(&target.pool, &EndpointScope::All, ingested.event_id)
+        .await
+        .unwrap()
+        .event;
+    assert_eq!(restored.payload, event.payload);
+}
+
+#[tokio::test]
+async fn payload_hash_detects_tampered_payloads() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "hashed", "acme", "s3cret").await;
+    let body = br#"{"amount":100}"#;
+    let signature = format!("sha256={}", sign("s3cret", &[body]));
+    let request = Request::builder()
+        .method("POST")
+        .uri("/ingest/s/hashed")
+        .header("content-type", "application/json")
+        .header("x-webhook-signature", signature)
+        .body(Body::from(body.to_vec()))
+        .unwrap();
+    let response = build_app(db.pool.clone()).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+
+    let found = get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        found.event.payload_sha256.as_deref(),
+        Some(hex::encode(Sha256::digest(body)).as_str())
+    );
+    assert_eq!(found.payload_integrity, PayloadIntegrity::Verified);
+
+    sqlx::query("UPDATE webhook_events SET payload = '{\"amount\":1}' WHERE id = ?")
+        .bind(ingested.event_id.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let found = get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+        .await
+        .unwrap();
+    assert_eq!(found.payload_integrity, PayloadIntegrity::Mismatch);
+
+    let mut archive = Vec::new();
+    write_snapshot(&db.pool, &mut archive).await.unwrap();
+    let target = setup_db().await;
+    let err = restore_snapshot(&target.pool, std::io::Cursor::new(&archive))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SnapshotError::Parse(ref message) if message.contains("payload_sha256")));
+}
+
+#[tokio::test]
+async fn gzip_bodies_are_decompressed_before_verification() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "zipped", "acme", "s3cret").await;
+    let body = r#"{"type":"invoice.paid","id":"evt_gz"}"#;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+    let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+    let request = Request::builder()
+        .method("POST")
+        .uri("/ingest/s/zipped")
+        .header("content-type", "application/json")
+        .header("content-encoding", "gzip")
+        .header("x-webhook-signature", signature)
+        .body(Body::from(compressed))
+        .unwrap();
+    let response = build_app(db.pool.clone()).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+
+    let event = get_event(&db.pool, &EndpointScope::All, ingested.event_id)
+        .await
+        .unwrap()
+        .event;
+    assert_eq!(event.payload, body);
+    assert!(!event.headers.contains_key("content-encoding"));
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(&vec![b' '; IngestConfig::default().max_body_bytes + 1])
+        .unwrap();
+    let bomb = encoder.finish().unwrap();
+    let request = Request::builder()
+        .method("POST")
+        .uri("/ingest/s/zipped")
+        .header("content-encoding", "gzip")
+        .header("x-webhook-signature", "sha256=00")
+        .body(Body::from(bomb))
+        .unwrap();
+    let response = build_app(db.pool.clone()).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn raw_and_decompressed_bodies_share_one_size_limit() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "large", "acme", "s3cret").await;
+    let limit = 4096;
+    let padded = |len: usize| {
+        let prefix = r#"{"type":"invoice.paid","pad":""#;
+        let mut body = prefix.to_string();
+        body.push_str(&"x".repeat(len - prefix.len() - 2));
+        body.push_str(r#""}"#);
+        body
+    };
+    let send = |body: &str, gzip: bool| {
+        let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/ingest/s/large")
+            .header("content-type", "application/json")
+            .header("x-webhook-signature", signature);
+        let bytes = if gzip {
+            request = request.header("content-encoding", "gzip");
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(body.as_bytes()).unwrap();
+            encoder.finish().unwrap()
+        } else {
+            body.as_bytes().to_vec()
+        };
+        let config = IngestConfig {
+            max_body_bytes: limit,
+            ..IngestConfig::default()
+        };
+        let app = build_app_with_config(db.pool.clone(), config, None);
+        let request = request.body(Body::from(bytes)).unwrap();
+        async move { app.oneshot(request).await.unwrap().status() }
+    };
+
+    let at_limit = padded(limit);
+    assert_eq!(send(&at_limit, false).await, StatusCode::OK);
+    assert_eq!(send(&at_limit, true).await, StatusCode::OK);
+    let over_limit = padded(limit + 1);
+    assert_eq!(
+        send(&over_limit, false).await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    assert_eq!(send(&over_limit, true).await, StatusCode::PAYLOAD_TOO_LARGE);
+
+    // Both rejections count, whichever form went over the limit.
+    let stats = provider_ingest_stats(&db.pool, 60).await.unwrap();
+    assert_eq!((stats[0].accepted, stats[0].rejected_size), (2, 2));
+}
+
+#[tokio::test]
+async fn usage_rollups_count_ingest_attempts_and_stored_bytes() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "acme-billing", "acme", "s3cret").await;
+    let app = build_app(db.pool.clone());
+
+    let body = r#"{"type":"invoice.paid"}"#;
+    let mut event_ids = Vec::new();
+    for _ in 0..2 {
+        let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+        let response = app
+            .clone()
+            .oneshot(ingest_request(
+                "acme-billing",
+                ("x-webhook-signature", signature),
+                body,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+        event_ids.push(ingested.event_id);
+    }
+    let endpoint_id = get_event(&db.pool, &EndpointScope::All, event_ids[0])
+        .await
+        .expect("get_event")
+        .event
+        .endpoint_id;
+
+    let config = DispatcherConfig::default();
+    let lease = LeaseRequest {
+        limit: 1,
+        lease_ms: 30_000,
+        worker_id: "worker-1".to_string(),
+        region: None,
+        endpoint_id: None,
+    };
+    let leased = lease_events(&db.pool, &config, &lease)
+        .await
+        .expect("lease events");
+    let now = Utc::now().to_rfc3339();
+    let report = ReportRequest {
+        worker_id: "worker-1".to_string(),
+        worker_version: None,
+        worker_region: None,
+        event_id: leased[0].event.id,
+        outcome: ReportOutcome::Delivered,
+        retryable: true,
+        next_attempt_at: None,
+        attempt: ReportAttempt {
+            started_at: now.clone(),
+            finished_at: now,
+            request_headers: BTreeMap::new(),
+            request_body: body.to_string(),
+            response_status: Some(200),
+            response_headers: None,
+            response_body: Some("ok".to_string()),
+            error_kind: None,
+            error_message: None,
+            final_url: None,
+            peer_address: None,
+            timing: None,
+        },
+    };
+    report_delivery(&db.pool, &config, &report)
+        .await
+        .expect("report delivery");
+
+    let today = Utc::now().date_naive().to_string();
+    let params = UsageParams {
+        from: today.clone(),
+        to: today.clone(),
+        endpoint_id: Some(endpoint_id),
+        group_id: None,
+    };
+    let rollups = usage_rollups(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("usage rollups");
+    assert_eq!(rollups.len(), 1);
+    assert_eq!(rollups[0].day, today);
+    assert_eq!(rollups[0].events_ingested, 2);
+    assert_eq!(rollups[0].deliveries_attempted, 1);
+    assert_eq!(rollups[0].bytes_stored, (3 * body.len() + 2) as i64);
+
+    let other_scope = EndpointScope::Endpoints(vec![Uuid::new_v4()]);
+    let hidden = usage_rollups(&db.pool, &other_scope, &params)
+        .await
+        .expect("usage rollups");
+    assert!(hidden.is_empty());
+}
+
+#[tokio::test]
+async fn ingest_over_group_quota_is_rate_limited_and_reported() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "acme-billing", "acme", "s3cret").await;
+    let endpoint_id: String = sqlx::query_scalar("SELECT endpoint_id FROM sources WHERE id = ?")
+        .bind(source_id.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .expect("source endpoint");
+    let group = create_endpoint_group(&db.pool, "acme", None)
+        .await
+        .expect("create group");
+    set_endpoint_group(
+        &db.pool,
+        Uuid::parse_str(&endpoint_id).unwrap(),
+        Some(group.id),
+    )
+    .await
+    .expect("assign group");
+    set_group_quota(&db.pool, group.id, Some(1), None)
+        .await
+        .expect("set quota");
+    let app = build_app(db.pool.clone());
+
+    let body = r#"{"type":"invoice.paid"}"#;
+    let mut statuses = Vec::new();
+    for _ in 0..2 {
+        let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+        let response = app
+            .clone()
+            .oneshot(ingest_request(
+                "acme-billing",
+                ("x-webhook-signature", signature),
+                body,
+            ))
+            .await
+            .unwrap();
+        statuses.push(response.status());
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let bytes = response.into_body().collect().await.unwrap().to_bytes();
+            let error: ApiErrorResponse = serde_json::from_slice(&bytes).unwrap();
+            assert_eq!(error.code, ApiErrorCode::RateLimited);
+        }
+    }
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::TOO_MANY_REQUESTS]);
+
+    let events: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhook_events")
+        .fetch_one(&db.pool)
+        .await
+        .expect("count events");
+    assert_eq!(events, 1);
+
+    let quota = get_group_quota(&db.pool, group.id)
+        .await
+        .expect("group quota");
+    assert_eq!(quota.events_today, 1);
+    assert_eq!(quota.stored_bytes, body.len() as i64);
+    assert_eq!(quota.rejections_total, 1);
+    assert!(quota.exceeded_today);
+
+    let metrics = render_quota_metrics(&group_quotas(&db.pool, None).await.expect("quotas"));
+    let group_id = group.id;
+    assert!(metrics.contains(&format!(
+        "receiver_group_quota_rejections_total{{group_id=\"{group_id}\"}} 1\n"
+    )));
+    assert!(metrics.contains(&format!(
+        "receiver_group_quota_exceeded{{group_id=\"{group_id}\"}} 1\n"
+    )));
+}
+
+#[tokio::test]
+async fn storage_quota_tracks_stored_replayed_and_deleted_payloads() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "acme-billing", "acme", "s3cret").await;
+    let endpoint_id: String = sqlx::query_scalar("SELECT endpoint_id FROM sources WHERE id = ?")
+        .bind(source_id.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .expect("source endpoint");
+    let group = create_endpoint_group(&db.pool, "acme", None)
+        .await
+        .expect("create group");
+    set_endpoint_group(
+        &db.pool,
+        Uuid::parse_str(&endpoint_id).unwrap(),
+        Some(group.id),
+    )
+    .await
+    .expect("assign group");
+    let body = r#"{"type":"invoice.paid"}"#;
+    let len = body.len() as i64;
+    set_group_quota(&db.pool, group.id, None, Some(2 * len))
+        .await
+        .expect("set quota");
+    let app = build_app(db.pool.clone());
+    let ingest = || {
+        let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+        let request = ingest_request("acme-billing", ("x-webhook-signature", signature), body);
+        let app = app.clone();
+        async move { app.oneshot(request).await.unwrap() }
+    };
+    let stored_bytes = || async {
+        get_group_quota(&db.pool, group.id)
+            .await
+            .expect("group quota")
+            .stored_bytes
+    };
+
+    let response = ingest().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(stored_bytes().await, len);
+
+    let replayed = replay_event(
+        &db.pool,
+        &EndpointScope::All,
+        ingested.event_id,
+        false,
+        false,
+        ReplayAttemptBudget::Reset,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(stored_bytes().await, 2 * len);
+    assert_eq!(ingest().await.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    delete_event(&db.pool, &EndpointScope::All, replayed.event.id, false)
+        .await
+        .unwrap();
+    assert_eq!(stored_bytes().await, len);
+    delete_event(&db.pool, &EndpointScope::All, replayed.event.id, true)
+        .await
+        .unwrap();
+    assert_eq!(stored_bytes().await, len);
+    assert_eq!(ingest().await.status(), StatusCode::OK);
+    assert_eq!(stored_bytes().await, 2 * len);
+}
+
+#[tokio::test]
+async fn deterministic_event_ids_store_repeated_provider_events_once() {
+    let db = setup_db().await;
+    let source_id = seed_source(&db.pool, "acme-billing", "acme", "s3cret").await;
+    let config = IngestConfig {
+        deterministic_event_ids: true,
+        ..IngestConfig::default()
+    };
+    let body = r#"{"type":"invoice.paid"}"#;
+    let mut event_ids = Vec::new();
+    // A restarted or second instance gets the same ID.
+    for _ in 0..2 {
+        let app = build_app_with_config(db.pool.clone(), config.clone(), None);
+        let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+        let request = Request::builder()
+            .method("POST")
+            .uri("/ingest/s/acme-billing")
+            .header("content-type", "application/json")
+            .header("x-webhook-signature", signature)
+            .header("webhook-id", "msg_123")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+        event_ids.push(ingested.event_id);
+    }
+    assert_eq!(event_ids[0], event_ids[1]);
+    assert_eq!(event_ids[0].get_version_num(), 5);
+
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhook_events WHERE source_id = ?")
+        .bind(source_id.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 1);
+}
+
+#[tokio::test]
+async fn ingest_drops_credential_headers_and_honours_an_allowlist() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "acme-billing", "acme", "s3cret").await;
+    let body = r#"{"type":"invoice.paid"}"#;
+    let ingest = |config: IngestConfig| {
+        let app = build_app_with_config(db.pool.clone(), config, None);
+        let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+        let request = Request::builder()
+            .method("POST")
+            .uri("/ingest/s/acme-billing")
+            .header("content-type", "application/json")
+            .header("authorization", "Bearer provider-token")
+            .header("cookie", "session=abc")
+            .header("x-webhook-signature", signature)
+            .body(Body::from(body))
+            .unwrap();
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let bytes = response.into_body().collect().await.unwrap().to_bytes();
+            let ingested: IngestResponse = serde_json::from_slice(&bytes).unwrap();
+            ingested.event_id
+        }
+    };
+
+    let event_id = ingest(IngestConfig::default()).await;
+    let stored = get_event(&db.pool, &EndpointScope::All, event_id)
+        .await
+        .unwrap();
+    assert!(!stored.event.headers.contains_key("authorization"));
+    assert!(!stored.event.headers.contains_key("cookie"));
+    assert!(stored.event.headers.contains_key("x-webhook-signature"));
+    assert_eq!(stored.dropped_headers, ["authorization", "cookie"]);
+
+    let event_id = ingest(IngestConfig {
+        header_allowlist: Some(vec!["content-type".to_string(), "cookie".to_string()]),
+        ..IngestConfig::default()
+    })
+    .await;
+    let stored = get_event(&db.pool, &EndpointScope::All, event_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        stored.event.headers.keys().collect::<Vec<_>>(),
+        ["content-type", "cookie"]
+    );
+    assert!(
+        stored
+            .dropped_headers
+            .contains(&"x-webhook-signature".to_string())
+    );
+    assert!(
+        stored
+            .dropped_headers
+            .contains(&"authorization".to_string())
+    );
+}
+
+#[tokio::test]
+async fn ingest_backlog_over_threshold_answers_retry_after() {
+    let db = setup_db().await;
+    seed_source(&db.pool, "acme-billing", "acme", "s3cret").await;
+    let config = IngestConfig {
+        max_pending_events: Some(1),
+        backpressure_retry_after_secs: 45,
+        ..IngestConfig::default()
+    };
+    let app = build_app_with_config(db.pool.clone(), config, None);
+
+    let body = r#"{"type":"invoice.paid"}"#;
+    let mut responses = Vec::new();
+    for _ in 0..2 {
+        let signature = format!("sha256={}", sign("s3cret", &[body.as_bytes()]));
+        let response = app
+            .clone()
+            .oneshot(ingest_request(
+                "acme-billing",
+                ("x-webhook-signature", signature),
+                body,
+            ))
+            .await
+            .unwrap();
+        responses.push(response);
+    }
+    assert_eq!(responses[0].status(), StatusCode::OK);
+    assert_eq!(responses[1].status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(responses[1].headers()["retry-after"], "45");
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/health")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let health: IngestHealth = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(health.status, IngestHealthStatus::Backpressure);
+    assert_eq!(health.pending_events, 1);
+    assert_eq!(health.max_pending_events, Some(1));
+    assert_eq!(health.max_db_bytes, None);
+    assert!(health.db_bytes > 0);
+
+    for (max_pending_events, max_db_bytes, backpressured) in [
+        (Some(1), None, true),
+        (Some(2), None, false),
+        (None, None, false),
+        (Some(2), Some(health.db_bytes), true),
+        (None, Some(health.db_bytes + 1), false),
+    ] {
+        let config = IngestConfig {
+            max_pending_events,
+            max_db_bytes,
+            ..IngestConfig::default()
+        };
+        assert_eq!(
+            ingest_backpressured(&db.pool, &config).await.unwrap(),
+            backpressured,
+            "{max_pending_events:?} {max_db_bytes:?}"
+        );
+    }
+}
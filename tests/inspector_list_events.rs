@@ -6,10 +6,23 @@
 
 use std::collections::BTreeMap;
 
-use chrono::{Duration, Utc};
+use chrono::{Duration, NaiveDate, SecondsFormat, Utc};
 use receiver::{
-    inspector::{ListEventsParams, StoreError, get_event, list_events},
-    types::WebhookEventStatus,
+    dispatcher::archive_attempt_logs,
+    export::{ExportSink, export_day, export_path, open_sink},
+    inspector::{
+        CSV_COLUMNS, DeadEventTarget, DeadEventWindow, EndpointScope, ExportEventsParams,
+        ListEventsParams, StoreError, close_circuit, create_endpoint_group, delete_event,
+        export_events, finish_operation, get_attempt_request, get_event, get_event_lineage,
+        list_attempts, list_events, list_operations, render_csv, render_curl, render_ndjson,
+        replay_dead_window, replay_event, replay_group, run_doctor, search_customer_events,
+        set_endpoint_group, set_endpoint_profile, start_operation, storage_report,
+        summarize_errors,
+    },
+    types::{
+        DoctorIssueKind, EventListField, ExportFormat, OperationKind, OperationStatus,
+        PayloadEncoding, ReplayAttemptBudget, WebhookAttemptErrorKind, WebhookEventStatus,
+    },
 };
 use sqlx::{
     Connection, SqliteConnection, SqlitePool,
@@ -163,10 +176,23 @@ async fn list_events_returns_empty_when_no_events() {
         before: None,
         status: None,
         endpoint_id: None,
+        source_id: None,
+        group_id: None,
         provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
     };
 
-    let result = list_events(&db.pool, &params).await.expect("list_events");
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
 
     assert!(result.events.is_empty());
     assert!(result.next_before.is_none());
@@ -184,10 +210,23 @@ async fn list_events_returns_summary_without_payload() {
         before: None,
         status: None,
         endpoint_id: None,
+        source_id: None,
+        group_id: None,
         provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
     };
 
-    let result = list_events(&db.pool, &params).await.expect("list_events");
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
 
     assert_eq!(result.events.len(), 1);
     let item = &result.events[0];
@@ -224,10 +263,23 @@ async fn list_events_includes_replayed_from_event_id() {
         before: None,
         status: None,
         endpoint_id: None,
+        source_id: None,
+        group_id: None,
         provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
     };
 
-    let result = list_events(&db.pool, &params).await.expect("list_events");
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
 
     let source = result
         .events
@@ -244,6 +296,154 @@ async fn list_events_includes_replayed_from_event_id() {
     assert_eq!(replayed.event.replayed_from_event_id, Some(source_id));
 }
 
+#[tokio::test]
+async fn list_events_filters_by_replay_status() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let base = Utc::now();
+    let replayed_source =
+        seed_event(&db.pool, endpoint_id, "stripe", "dead", &base.to_rfc3339()).await;
+    let untouched = seed_event(
+        &db.pool,
+        endpoint_id,
+        "stripe",
+        "delivered",
+        &(base + Duration::seconds(1)).to_rfc3339(),
+    )
+    .await;
+    let replay_id = seed_event_with_replay(
+        &db.pool,
+        endpoint_id,
+        "stripe",
+        "pending",
+        &(base + Duration::seconds(2)).to_rfc3339(),
+        Some(replayed_source),
+    )
+    .await;
+
+    let list = |is_replay: Option<bool>, has_replays: Option<bool>| {
+        let params = ListEventsParams {
+            limit: 50,
+            before: None,
+            status: None,
+            endpoint_id: None,
+            source_id: None,
+            group_id: None,
+            provider: None,
+            stuck_after_minutes: None,
+            schema_invalid: None,
+            is_replay,
+            has_replays,
+            include_last_attempt: false,
+            updated_since: None,
+            worker_version: None,
+            fields: None,
+            metadata: Vec::new(),
+        };
+        let pool = db.pool.clone();
+        async move {
+            list_events(&pool, &EndpointScope::All, &params)
+                .await
+                .expect("list_events")
+                .events
+                .into_iter()
+                .map(|item| item.event.id)
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(list(Some(true), None).await, vec![replay_id]);
+    assert_eq!(
+        list(Some(false), None).await,
+        vec![untouched, replayed_source]
+    );
+    assert_eq!(list(None, Some(true)).await, vec![replayed_source]);
+    assert_eq!(list(None, Some(false)).await, vec![replay_id, untouched]);
+    assert_eq!(list(Some(false), Some(false)).await, vec![untouched]);
+}
+
+#[tokio::test]
+async fn list_events_includes_last_attempt_when_requested() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let base = Utc::now();
+    let retried = seed_event(
+        &db.pool,
+        endpoint_id,
+        "stripe",
+        "requeued",
+        &base.to_rfc3339(),
+    )
+    .await;
+    let untried = seed_event(
+        &db.pool,
+        endpoint_id,
+        "stripe",
+        "pending",
+        &(base + Duration::seconds(1)).to_rfc3339(),
+    )
+    .await;
+    for (attempt_no, finished_at, status, error_kind) in [
+        (1, "2024-01-01T00:00:01Z", None, Some("timeout")),
+        (2, "2024-01-01T00:01:00Z", Some(503), None),
+    ] {
+        sqlx::query(
+            "INSERT INTO webhook_attempt_logs \
+            (id, event_id, attempt_no, started_at, finished_at, request_headers, request_body, \
+             response_status, error_kind) \
+            VALUES (?, ?, ?, ?, ?, '{}', '{}', ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(retried.to_string())
+        .bind(attempt_no)
+        .bind(finished_at)
+        .bind(finished_at)
+        .bind(status)
+        .bind(error_kind)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    }
+
+    let mut params = ListEventsParams {
+        limit: 50,
+        before: None,
+        status: None,
+        endpoint_id: None,
+        source_id: None,
+        group_id: None,
+        provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
+    };
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .unwrap();
+    assert!(result.events.iter().all(|item| item.last_attempt.is_none()));
+
+    params.include_last_attempt = true;
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .unwrap();
+    assert_eq!(result.events[0].event.id, untried);
+    assert!(result.events[0].last_attempt.is_none());
+    assert_eq!(result.events[1].event.id, retried);
+    let last = result.events[1]
+        .last_attempt
+        .as_ref()
+        .expect("last attempt");
+    assert_eq!(last.response_status, Some(503));
+    assert!(last.error_kind.is_none());
+    assert_eq!(last.finished_at, "2024-01-01T00:01:00Z");
+}
+
 #[tokio::test]
 async fn list_events_joins_target_url() {
     let db = setup_db().await;
@@ -257,10 +457,23 @@ async fn list_events_joins_target_url() {
         before: None,
         status: None,
         endpoint_id: None,
+        source_id: None,
+        group_id: None,
         provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
     };
 
-    let result = list_events(&db.pool, &params).await.expect("list_events");
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
 
     assert_eq!(result.events[0].target_url, target);
 }
@@ -279,10 +492,23 @@ async fn list_events_joins_circuit_state() {
         before: None,
         status: None,
         endpoint_id: None,
+        source_id: None,
+        group_id: None,
         provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
     };
 
-    let result = list_events(&db.pool, &params).await.expect("list_events");
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
 
     let circuit = result.events[0].circuit.as_ref().expect("circuit present");
     assert_eq!(circuit.endpoint_id, endpoint_id);
@@ -290,6 +516,217 @@ async fn list_events_joins_circuit_state() {
     assert!(circuit.open_until.is_some());
 }
 
+#[tokio::test]
+async fn list_events_skips_joins_for_unrequested_fields() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let group_id = create_endpoint_group(&db.pool, "payments", None)
+        .await
+        .unwrap()
+        .id;
+    set_endpoint_group(&db.pool, endpoint_id, Some(group_id))
+        .await
+        .unwrap();
+    let now = Utc::now().to_rfc3339();
+    let open_until = (Utc::now() + Duration::hours(1)).to_rfc3339();
+    let event_id = seed_event(&db.pool, endpoint_id, "stripe", "pending", &now).await;
+    seed_circuit_state(&db.pool, endpoint_id, "open", Some(&open_until)).await;
+
+    let mut params = ListEventsParams {
+        limit: 50,
+        before: None,
+        status: None,
+        endpoint_id: None,
+        source_id: None,
+        group_id: None,
+        provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: Some(vec![EventListField::Id, EventListField::Status]),
+        metadata: Vec::new(),
+    };
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
+    assert_eq!(result.events[0].event.id, event_id);
+    assert!(result.events[0].target_url.is_empty());
+    assert!(result.events[0].circuit.is_none());
+    assert!(result.events[0].event.payload_preview.is_none());
+
+    // Filtering by group still needs the endpoint join.
+    params.group_id = Some(group_id);
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events by group");
+    assert_eq!(result.events.len(), 1);
+    assert!(result.events[0].circuit.is_none());
+
+    params.fields = Some(vec![EventListField::Circuit]);
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events with circuit");
+    assert!(result.events[0].circuit.is_some());
+}
+
+#[tokio::test]
+async fn list_events_previews_payloads() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let now = Utc::now().to_rfc3339();
+    let small = seed_event(&db.pool, endpoint_id, "stripe", "pending", &now).await;
+    let large_payload = format!(r#"{{"data":"{}"}}"#, "é".repeat(300));
+    let large = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO webhook_events (
+            id, endpoint_id, provider, headers, payload, status, attempts, received_at
+        ) VALUES (?, ?, 'github', ?, ?, 'pending', 0, ?)
+        "#,
+    )
+    .bind(large.to_string())
+    .bind(endpoint_id.to_string())
+    .bind(r#"{"content-type":"Application/JSON; charset=utf-8"}"#)
+    .bind(&large_payload)
+    .bind(&now)
+    .execute(&db.pool)
+    .await
+    .expect("insert large event");
+    let text = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO webhook_events (
+            id, endpoint_id, provider, headers, payload, status, attempts, received_at
+        ) VALUES (?, ?, 'github', '{}', 'not json', 'pending', 0, ?)
+        "#,
+    )
+    .bind(text.to_string())
+    .bind(endpoint_id.to_string())
+    .bind(&now)
+    .execute(&db.pool)
+    .await
+    .expect("insert text event");
+
+    let params = ListEventsParams {
+        limit: 50,
+        before: None,
+        status: None,
+        endpoint_id: None,
+        source_id: None,
+        group_id: None,
+        provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: Some(vec![EventListField::Id, EventListField::PayloadPreview]),
+        metadata: Vec::new(),
+    };
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
+    let preview = |event_id| {
+        result
+            .events
+            .iter()
+            .find(|item| item.event.id == event_id)
+            .and_then(|item| item.event.payload_preview.clone())
+            .expect("payload preview")
+    };
+
+    let small = preview(small);
+    assert_eq!(small.snippet, r#"{"secret":"data"}"#);
+    assert!(!small.truncated);
+    assert!(small.is_json);
+    assert_eq!(small.content_type, "application/json");
+    assert_eq!(small.size_bytes, 17);
+
+    let large = preview(large);
+    assert_eq!(large.snippet.chars().count(), 200);
+    assert!(large_payload.starts_with(&large.snippet));
+    assert!(large.truncated);
+    assert!(large.is_json);
+    assert_eq!(large.content_type, "application/json");
+    assert_eq!(large.size_bytes, large_payload.len() as i64);
+
+    let text = preview(text);
+    assert!(!text.is_json);
+    assert_eq!(text.content_type, "text/plain");
+    assert_eq!(text.encoding, PayloadEncoding::Utf8);
+}
+
+#[tokio::test]
+async fn search_finds_customer_activity_across_providers() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let seed = |provider: &'static str, metadata: &'static str, received_at: &'static str| {
+        let pool = db.pool.clone();
+        async move {
+            let id = Uuid::new_v4();
+            sqlx::query(
+                r#"
+                INSERT INTO webhook_events (
+                    id, endpoint_id, provider, headers, payload, status, attempts,
+                    received_at, metadata
+                ) VALUES (?, ?, ?, '{}', '{}', 'pending', 0, ?, ?)
+                "#,
+            )
+            .bind(id.to_string())
+            .bind(endpoint_id.to_string())
+            .bind(provider)
+            .bind(received_at)
+            .bind(metadata)
+            .execute(&pool)
+            .await
+            .expect("insert event");
+            id
+        }
+    };
+    let stripe = seed("stripe", r#"{"customer_id":"123"}"#, "2024-01-01T00:00:00Z").await;
+    let shopify = seed(
+        "shopify",
+        r#"{"customer_id":"123","shop_domain":"acme.myshopify.com"}"#,
+        "2024-01-02T00:00:00Z",
+    )
+    .await;
+    seed("github", r#"{"sender":"123"}"#, "2024-01-03T00:00:00Z").await;
+    seed("stripe", r#"{"customer_id":"456"}"#, "2024-01-04T00:00:00Z").await;
+
+    let result = search_customer_events(&db.pool, &EndpointScope::All, "123", 50)
+        .await
+        .expect("search");
+    assert_eq!(result.customer_ref, "123");
+    let found: Vec<_> = result
+        .events
+        .iter()
+        .map(|found| (found.event.id, found.matched_field.as_str()))
+        .collect();
+    assert_eq!(found, [(shopify, "customer_id"), (stripe, "customer_id")]);
+
+    let by_shop = search_customer_events(&db.pool, &EndpointScope::All, "acme.myshopify.com", 50)
+        .await
+        .expect("search by shop");
+    assert_eq!(by_shop.events.len(), 1);
+    assert_eq!(by_shop.events[0].matched_field, "shop_domain");
+
+    let scoped = search_customer_events(
+        &db.pool,
+        &EndpointScope::Endpoints(vec![Uuid::new_v4()]),
+        "123",
+        50,
+    )
+    .await
+    .expect("scoped search");
+    assert!(scoped.events.is_empty());
+}
+
 #[tokio::test]
 async fn list_events_circuit_none_when_missing() {
     let db = setup_db().await;
@@ -302,10 +739,23 @@ async fn list_events_circuit_none_when_missing() {
         before: None,
         status: None,
         endpoint_id: None,
+        source_id: None,
+        group_id: None,
         provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
     };
 
-    let result = list_events(&db.pool, &params).await.expect("list_events");
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
 
     assert!(result.events[0].circuit.is_none());
 }
@@ -345,10 +795,23 @@ async fn list_events_filters_by_status() {
         before: None,
         status: Some(WebhookEventStatus::Delivered),
         endpoint_id: None,
+        source_id: None,
+        group_id: None,
         provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
     };
 
-    let result = list_events(&db.pool, &params).await.expect("list_events");
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
 
     assert_eq!(result.events.len(), 1);
     assert_eq!(result.events[0].event.status, WebhookEventStatus::Delivered);
@@ -368,10 +831,23 @@ async fn list_events_filters_by_endpoint_id() {
         before: None,
         status: None,
         endpoint_id: Some(endpoint_a),
+        source_id: None,
+        group_id: None,
         provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
     };
 
-    let result = list_events(&db.pool, &params).await.expect("list_events");
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
 
     assert_eq!(result.events.len(), 1);
     assert_eq!(result.events[0].event.endpoint_id, endpoint_a);
@@ -404,10 +880,23 @@ async fn list_events_filters_by_provider() {
         before: None,
         status: None,
         endpoint_id: None,
+        source_id: None,
+        group_id: None,
         provider: Some("github".to_string()),
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
     };
 
-    let result = list_events(&db.pool, &params).await.expect("list_events");
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
 
     assert_eq!(result.events.len(), 1);
     assert_eq!(result.events[0].event.provider, "github");
@@ -428,10 +917,23 @@ async fn list_events_respects_limit() {
         before: None,
         status: None,
         endpoint_id: None,
+        source_id: None,
+        group_id: None,
         provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
     };
 
-    let result = list_events(&db.pool, &params).await.expect("list_events");
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
 
     assert_eq!(result.events.len(), 3);
     assert!(result.next_before.is_some());
@@ -451,12 +953,24 @@ async fn list_events_cursor_pagination() {
 
     let first_page = list_events(
         &db.pool,
+        &EndpointScope::All,
         &ListEventsParams {
             limit: 2,
             before: None,
             status: None,
             endpoint_id: None,
+            source_id: None,
+            group_id: None,
             provider: None,
+            stuck_after_minutes: None,
+            schema_invalid: None,
+            is_replay: None,
+            has_replays: None,
+            include_last_attempt: false,
+            updated_since: None,
+            worker_version: None,
+            fields: None,
+            metadata: Vec::new(),
         },
     )
     .await
@@ -470,12 +984,24 @@ async fn list_events_cursor_pagination() {
 
     let second_page = list_events(
         &db.pool,
+        &EndpointScope::All,
         &ListEventsParams {
             limit: 2,
             before: Some(cursor),
             status: None,
             endpoint_id: None,
+            source_id: None,
+            group_id: None,
             provider: None,
+            stuck_after_minutes: None,
+            schema_invalid: None,
+            is_replay: None,
+            has_replays: None,
+            include_last_attempt: false,
+            updated_since: None,
+            worker_version: None,
+            fields: None,
+            metadata: Vec::new(),
         },
     )
     .await
@@ -521,10 +1047,23 @@ async fn list_events_ordering_desc() {
         before: None,
         status: None,
         endpoint_id: None,
+        source_id: None,
+        group_id: None,
         provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
     };
 
-    let result = list_events(&db.pool, &params).await.expect("list_events");
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
 
     assert_eq!(result.events[0].event.id, newest);
     assert_eq!(result.events[1].event.id, middle);
@@ -543,12 +1082,24 @@ async fn list_events_cursor_stability() {
 
     let first_run = list_events(
         &db.pool,
+        &EndpointScope::All,
         &ListEventsParams {
             limit: 2,
             before: None,
             status: None,
             endpoint_id: None,
+            source_id: None,
+            group_id: None,
             provider: None,
+            stuck_after_minutes: None,
+            schema_invalid: None,
+            is_replay: None,
+            has_replays: None,
+            include_last_attempt: false,
+            updated_since: None,
+            worker_version: None,
+            fields: None,
+            metadata: Vec::new(),
         },
     )
     .await
@@ -558,12 +1109,24 @@ async fn list_events_cursor_stability() {
 
     let second_run = list_events(
         &db.pool,
+        &EndpointScope::All,
         &ListEventsParams {
             limit: 2,
             before: Some(cursor.clone()),
             status: None,
             endpoint_id: None,
+            source_id: None,
+            group_id: None,
             provider: None,
+            stuck_after_minutes: None,
+            schema_invalid: None,
+            is_replay: None,
+            has_replays: None,
+            include_last_attempt: false,
+            updated_since: None,
+            worker_version: None,
+            fields: None,
+            metadata: Vec::new(),
         },
     )
     .await
@@ -571,12 +1134,24 @@ async fn list_events_cursor_stability() {
 
     let third_run = list_events(
         &db.pool,
+        &EndpointScope::All,
         &ListEventsParams {
             limit: 2,
             before: Some(cursor),
             status: None,
             endpoint_id: None,
+            source_id: None,
+            group_id: None,
             provider: None,
+            stuck_after_minutes: None,
+            schema_invalid: None,
+            is_replay: None,
+            has_replays: None,
+            include_last_attempt: false,
+            updated_since: None,
+            worker_version: None,
+            fields: None,
+            metadata: Vec::new(),
         },
     )
     .await
@@ -599,7 +1174,9 @@ async fn get_event_returns_full_payload() {
     let now = Utc::now().to_rfc3339();
     let event_id = seed_event(&db.pool, endpoint_id, "stripe", "pending", &now).await;
 
-    let result = get_event(&db.pool, event_id).await.expect("get_event");
+    let result = get_event(&db.pool, &EndpointScope::All, event_id)
+        .await
+        .expect("get_event");
 
     assert_eq!(result.event.id, event_id);
     assert_eq!(result.event.provider, "stripe");
@@ -608,6 +1185,81 @@ async fn get_event_returns_full_payload() {
     assert!(result.event.replayed_from_event_id.is_none());
 }
 
+#[tokio::test]
+async fn events_carry_endpoint_name_and_description() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let now = Utc::now().to_rfc3339();
+    let event_id = seed_event(&db.pool, endpoint_id, "stripe", "pending", &now).await;
+
+    let result = get_event(&db.pool, &EndpointScope::All, event_id)
+        .await
+        .unwrap();
+    assert!(result.endpoint_name.is_none());
+
+    let profile = set_endpoint_profile(
+        &db.pool,
+        &EndpointScope::All,
+        endpoint_id,
+        Some("Billing service"),
+        Some("Invoices and payment status updates"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(profile.name.as_deref(), Some("Billing service"));
+
+    let result = get_event(&db.pool, &EndpointScope::All, event_id)
+        .await
+        .unwrap();
+    assert_eq!(result.endpoint_name.as_deref(), Some("Billing service"));
+    assert_eq!(
+        result.endpoint_description.as_deref(),
+        Some("Invoices and payment status updates")
+    );
+
+    let mut params = ListEventsParams {
+        limit: 50,
+        before: None,
+        status: None,
+        endpoint_id: None,
+        source_id: None,
+        group_id: None,
+        provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: Some(vec![EventListField::EndpointName]),
+        metadata: Vec::new(),
+    };
+    let listed = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .unwrap();
+    assert_eq!(
+        listed.events[0].endpoint_name.as_deref(),
+        Some("Billing service")
+    );
+
+    params.fields = Some(vec![EventListField::Id]);
+    let listed = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .unwrap();
+    assert!(listed.events[0].endpoint_name.is_none());
+
+    let missing = set_endpoint_profile(
+        &db.pool,
+        &EndpointScope::All,
+        Uuid::new_v4(),
+        Some("Nobody"),
+        None,
+    )
+    .await;
+    assert!(matches!(missing, Err(StoreError::NotFound(_))));
+}
+
 #[tokio::test]
 async fn get_event_replayed_from_event_id_set() {
     let db = setup_db().await;
@@ -632,7 +1284,9 @@ async fn get_event_replayed_from_event_id_set() {
     )
     .await;
 
-    let result = get_event(&db.pool, replay_id).await.expect("get_event");
+    let result = get_event(&db.pool, &EndpointScope::All, replay_id)
+        .await
+        .expect("get_event");
 
     assert_eq!(result.event.replayed_from_event_id, Some(source_id));
 }
@@ -642,7 +1296,7 @@ async fn get_event_not_found() {
     let db = setup_db().await;
     let non_existent_id = Uuid::new_v4();
 
-    let result = get_event(&db.pool, non_existent_id).await;
+    let result = get_event(&db.pool, &EndpointScope::All, non_existent_id).await;
 
     assert!(matches!(result, Err(StoreError::NotFound(_))));
 }
@@ -656,7 +1310,9 @@ async fn get_event_includes_circuit_state() {
     let event_id = seed_event(&db.pool, endpoint_id, "stripe", "pending", &now).await;
     seed_circuit_state(&db.pool, endpoint_id, "open", Some(&open_until)).await;
 
-    let result = get_event(&db.pool, event_id).await.expect("get_event");
+    let result = get_event(&db.pool, &EndpointScope::All, event_id)
+        .await
+        .expect("get_event");
 
     let circuit = result.circuit.expect("circuit present");
     assert_eq!(circuit.endpoint_id, endpoint_id);
@@ -671,7 +1327,1303 @@ async fn get_event_circuit_none_when_missing() {
     let now = Utc::now().to_rfc3339();
     let event_id = seed_event(&db.pool, endpoint_id, "stripe", "pending", &now).await;
 
-    let result = get_event(&db.pool, event_id).await.expect("get_event");
+    let result = get_event(&db.pool, &EndpointScope::All, event_id)
+        .await
+        .expect("get_event");
 
     assert!(result.circuit.is_none());
 }
+
+#[tokio::test]
+async fn list_events_filters_by_group_id() {
+    let db = setup_db().await;
+    let grouped = seed_endpoint(&db.pool, "https://a.example.com/hook").await;
+    let ungrouped = seed_endpoint(&db.pool, "https://b.example.com/hook").await;
+    let group = create_endpoint_group(&db.pool, "customer-a", None)
+        .await
+        .expect("create group");
+    set_endpoint_group(&db.pool, grouped, Some(group.id))
+        .await
+        .expect("assign group");
+
+    let now = Utc::now().to_rfc3339();
+    let grouped_event = seed_event(&db.pool, grouped, "stripe", "pending", &now).await;
+    seed_event(&db.pool, ungrouped, "stripe", "pending", &now).await;
+
+    let params = ListEventsParams {
+        limit: 50,
+        before: None,
+        status: None,
+        endpoint_id: None,
+        source_id: None,
+        group_id: Some(group.id),
+        provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
+    };
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
+
+    assert_eq!(result.events.len(), 1);
+    assert_eq!(result.events[0].event.id, grouped_event);
+}
+
+#[tokio::test]
+async fn endpoint_group_names_are_unique() {
+    let db = setup_db().await;
+    create_endpoint_group(&db.pool, "customer-a", Some(10))
+        .await
+        .expect("create group");
+    let err = create_endpoint_group(&db.pool, "customer-a", None)
+        .await
+        .expect_err("duplicate name");
+    assert!(matches!(err, StoreError::Conflict(ref message) if message == "group_name_taken"));
+}
+
+#[tokio::test]
+async fn replay_group_replays_dead_events_of_group_endpoints() {
+    let db = setup_db().await;
+    let first = seed_endpoint(&db.pool, "https://a.example.com/hook").await;
+    let second = seed_endpoint(&db.pool, "https://b.example.com/hook").await;
+    let outside = seed_endpoint(&db.pool, "https://c.example.com/hook").await;
+    let group = create_endpoint_group(&db.pool, "customer-a", None)
+        .await
+        .expect("create group");
+    for endpoint_id in [first, second] {
+        set_endpoint_group(&db.pool, endpoint_id, Some(group.id))
+            .await
+            .expect("assign group");
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let dead_first = seed_event(&db.pool, first, "stripe", "dead", &now).await;
+    let dead_second = seed_event(&db.pool, second, "stripe", "dead", &now).await;
+    seed_event(&db.pool, first, "stripe", "delivered", &now).await;
+    seed_event(&db.pool, outside, "stripe", "dead", &now).await;
+
+    let replayed = replay_group(&db.pool, group.id, false, false, None, None, None)
+        .await
+        .expect("replay group")
+        .replayed_event_ids;
+    assert_eq!(replayed.len(), 2);
+
+    let mut sources = Vec::new();
+    for id in replayed {
+        let event = get_event(&db.pool, &EndpointScope::All, id)
+            .await
+            .expect("replayed event")
+            .event;
+        assert_eq!(event.status, WebhookEventStatus::Pending);
+        sources.push(event.replayed_from_event_id.expect("replay source"));
+    }
+    sources.sort();
+    let mut expected = vec![dead_first, dead_second];
+    expected.sort();
+    assert_eq!(sources, expected);
+}
+
+#[tokio::test]
+async fn closing_a_circuit_replays_dead_events_from_the_outage_window() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://a.example.com/hook").await;
+    let open_until = (Utc::now() + Duration::hours(1)).to_rfc3339();
+    seed_circuit_state(&db.pool, endpoint_id, "open", Some(&open_until)).await;
+    let outage_start = Utc::now() - Duration::hours(2);
+    let ts = |minutes: i64| {
+        (outage_start + Duration::minutes(minutes)).to_rfc3339_opts(SecondsFormat::Secs, true)
+    };
+    seed_event(&db.pool, endpoint_id, "stripe", "dead", &ts(-30)).await;
+    let first = seed_event(&db.pool, endpoint_id, "stripe", "dead", &ts(10)).await;
+    let second = seed_event(&db.pool, endpoint_id, "stripe", "dead", &ts(20)).await;
+    seed_event(&db.pool, endpoint_id, "stripe", "dead", &ts(30)).await;
+    seed_event(&db.pool, endpoint_id, "stripe", "delivered", &ts(15)).await;
+
+    close_circuit(&db.pool, &EndpointScope::All, endpoint_id)
+        .await
+        .expect("close circuit");
+    let event = get_event(&db.pool, &EndpointScope::All, first)
+        .await
+        .expect("get event");
+    let circuit = event.circuit.expect("circuit row");
+    assert_eq!(circuit.state, receiver::types::TargetCircuitStatus::Closed);
+    assert_eq!(circuit.consecutive_failures, 0);
+
+    let window = DeadEventWindow {
+        received_since: ts(0),
+        received_before: Some(ts(60)),
+        limit: 2,
+        drip_rate_per_minute: None,
+    };
+    let replayed = replay_dead_window(
+        &db.pool,
+        DeadEventTarget::Endpoint(endpoint_id),
+        &window,
+        None,
+        None,
+    )
+    .await
+    .expect("replay window")
+    .replayed_event_ids;
+    let mut sources = Vec::new();
+    for id in &replayed {
+        let event = get_event(&db.pool, &EndpointScope::All, *id)
+            .await
+            .expect("replayed event")
+            .event;
+        sources.push(event.replayed_from_event_id.expect("replay source"));
+    }
+    assert_eq!(sources, vec![first, second]);
+
+    let again = replay_dead_window(
+        &db.pool,
+        DeadEventTarget::Endpoint(endpoint_id),
+        &window,
+        None,
+        None,
+    )
+    .await
+    .expect("replay window again");
+    assert!(again.replayed_event_ids.is_empty());
+}
+
+#[tokio::test]
+async fn replay_group_drip_staggers_next_attempt_at() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://a.example.com/hook").await;
+    let group = create_endpoint_group(&db.pool, "customer-a", None)
+        .await
+        .expect("create group");
+    set_endpoint_group(&db.pool, endpoint_id, Some(group.id))
+        .await
+        .expect("assign group");
+    let now = Utc::now();
+    for offset in 0..3 {
+        let received_at = (now - Duration::minutes(10 - offset)).to_rfc3339();
+        seed_event(&db.pool, endpoint_id, "stripe", "dead", &received_at).await;
+    }
+
+    let replayed = replay_group(&db.pool, group.id, false, false, Some(2), None, None)
+        .await
+        .expect("replay group")
+        .replayed_event_ids;
+    assert_eq!(replayed.len(), 3);
+
+    let mut due = Vec::new();
+    for id in replayed {
+        let event = get_event(&db.pool, &EndpointScope::All, id)
+            .await
+            .expect("replayed event")
+            .event;
+        let next_attempt_at = event.next_attempt_at.expect("staggered");
+        due.push(chrono::DateTime::parse_from_rfc3339(&next_attempt_at).unwrap());
+    }
+    assert_eq!(due[1] - due[0], Duration::seconds(30));
+    assert_eq!(due[2] - due[1], Duration::seconds(30));
+}
+
+#[tokio::test]
+async fn outage_window_replay_drip_inserts_events_already_staggered() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://a.example.com/hook").await;
+    let now = Utc::now();
+    for offset in 0..3 {
+        let received_at = (now - Duration::minutes(10 - offset)).to_rfc3339();
+        seed_event(&db.pool, endpoint_id, "stripe", "dead", &received_at).await;
+    }
+
+    let window = DeadEventWindow {
+        received_since: (now - Duration::hours(1)).to_rfc3339_opts(SecondsFormat::Secs, true),
+        received_before: None,
+        limit: 10,
+        drip_rate_per_minute: Some(4),
+    };
+    let replayed = replay_dead_window(
+        &db.pool,
+        DeadEventTarget::Endpoint(endpoint_id),
+        &window,
+        None,
+        None,
+    )
+    .await
+    .expect("replay window")
+    .replayed_event_ids;
+    assert_eq!(replayed.len(), 3);
+
+    let mut due = Vec::new();
+    for id in replayed {
+        let event = get_event(&db.pool, &EndpointScope::All, id)
+            .await
+            .expect("replayed event")
+            .event;
+        let next_attempt_at = event.next_attempt_at.expect("staggered");
+        due.push(chrono::DateTime::parse_from_rfc3339(&next_attempt_at).unwrap());
+    }
+    assert_eq!(due[1] - due[0], Duration::seconds(15));
+    assert_eq!(due[2] - due[1], Duration::seconds(15));
+}
+
+async fn seed_attempt(
+    pool: &SqlitePool,
+    event_id: Uuid,
+    finished_at: &str,
+    response_status: Option<i64>,
+    error_kind: Option<&str>,
+) {
+    sqlx::query(
+        "INSERT INTO webhook_attempt_logs \
+        (id, event_id, attempt_no, started_at, finished_at, request_headers, request_body, \
+         response_status, error_kind) \
+        VALUES (?, ?, 1, ?, ?, '{}', '{}', ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(event_id.to_string())
+    .bind(finished_at)
+    .bind(finished_at)
+    .bind(response_status)
+    .bind(error_kind)
+    .execute(pool)
+    .await
+    .expect("insert attempt");
+}
+
+#[tokio::test]
+async fn summarize_errors_groups_failures_in_window() {
+    let db = setup_db().await;
+    let first = seed_endpoint(&db.pool, "https://a.example.com/hook").await;
+    let second = seed_endpoint(&db.pool, "https://b.example.com/hook").await;
+    let now = Utc::now();
+    let recent = (now - Duration::minutes(5)).to_rfc3339();
+    let old = (now - Duration::hours(3)).to_rfc3339();
+    let first_event = seed_event(&db.pool, first, "stripe", "pending", &recent).await;
+    let second_event = seed_event(&db.pool, second, "stripe", "pending", &recent).await;
+
+    seed_attempt(&db.pool, first_event, &recent, Some(503), None).await;
+    seed_attempt(&db.pool, first_event, &recent, Some(500), None).await;
+    seed_attempt(&db.pool, first_event, &recent, None, Some("timeout")).await;
+    seed_attempt(&db.pool, first_event, &recent, Some(200), None).await;
+    seed_attempt(&db.pool, first_event, &old, Some(502), None).await;
+    seed_attempt(&db.pool, second_event, &recent, Some(401), None).await;
+
+    let since = (now - Duration::hours(1)).to_rfc3339();
+    let buckets = summarize_errors(&db.pool, &EndpointScope::All, &since, None)
+        .await
+        .expect("summarize errors");
+
+    assert_eq!(buckets.len(), 3);
+    assert_eq!(buckets[0].endpoint_id, first);
+    assert_eq!(buckets[0].status_class.as_deref(), Some("5xx"));
+    assert_eq!(buckets[0].count, 2);
+    assert!(buckets.iter().any(|bucket| bucket.endpoint_id == first
+        && matches!(bucket.error_kind, Some(WebhookAttemptErrorKind::Timeout))
+        && bucket.status_class.is_none()));
+    assert!(buckets.iter().any(|bucket| bucket.endpoint_id == second
+        && bucket.status_class.as_deref() == Some("4xx")
+        && bucket.count == 1));
+
+    let only_second = summarize_errors(&db.pool, &EndpointScope::All, &since, Some(second))
+        .await
+        .expect("summarize errors for endpoint");
+    assert_eq!(only_second.len(), 1);
+    assert_eq!(only_second[0].target_url, "https://b.example.com/hook");
+}
+
+#[tokio::test]
+async fn list_events_filters_stuck_in_flight() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let now = Utc::now();
+    let received = now.to_rfc3339();
+
+    let expired_lease = seed_event(&db.pool, endpoint_id, "stripe", "in_flight", &received).await;
+    let long_running = seed_event(&db.pool, endpoint_id, "stripe", "in_flight", &received).await;
+    let healthy = seed_event(&db.pool, endpoint_id, "stripe", "in_flight", &received).await;
+    seed_event(&db.pool, endpoint_id, "stripe", "pending", &received).await;
+
+    let past = (now - Duration::minutes(1)).to_rfc3339();
+    let future = (now + Duration::hours(2)).to_rfc3339();
+    let long_ago = (now - Duration::minutes(30)).to_rfc3339();
+    let just_now = (now - Duration::minutes(1)).to_rfc3339();
+    for (id, lease_expires_at, leased_at) in [
+        (expired_lease, &past, &just_now),
+        (long_running, &future, &long_ago),
+        (healthy, &future, &just_now),
+    ] {
+        sqlx::query("UPDATE webhook_events SET lease_expires_at = ?, leased_at = ? WHERE id = ?")
+            .bind(lease_expires_at)
+            .bind(leased_at)
+            .bind(id.to_string())
+            .execute(&db.pool)
+            .await
+            .expect("set lease");
+    }
+
+    let params = ListEventsParams {
+        limit: 50,
+        before: None,
+        status: None,
+        endpoint_id: None,
+        source_id: None,
+        group_id: None,
+        provider: None,
+        stuck_after_minutes: Some(15),
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
+    };
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
+
+    let mut ids: Vec<Uuid> = result.events.iter().map(|item| item.event.id).collect();
+    ids.sort();
+    let mut expected = vec![expired_lease, long_running];
+    expected.sort();
+    assert_eq!(ids, expected);
+}
+
+#[tokio::test]
+async fn soft_delete_hides_event() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let now = Utc::now().to_rfc3339();
+    let event_id = seed_event(&db.pool, endpoint_id, "stripe", "delivered", &now).await;
+
+    let deleted = delete_event(&db.pool, &EndpointScope::All, event_id, false)
+        .await
+        .expect("soft delete");
+    assert!(deleted.erased_at.is_none());
+
+    let err = get_event(&db.pool, &EndpointScope::All, event_id)
+        .await
+        .expect_err("hidden");
+    assert!(matches!(err, StoreError::NotFound(_)));
+    let params = ListEventsParams {
+        limit: 50,
+        before: None,
+        status: None,
+        endpoint_id: None,
+        source_id: None,
+        group_id: None,
+        provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
+    };
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
+    assert!(result.events.is_empty());
+
+    let payload: String = sqlx::query_scalar("SELECT payload FROM webhook_events WHERE id = ?")
+        .bind(event_id.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(payload, r#"{"secret":"data"}"#, "soft delete keeps data");
+}
+
+#[tokio::test]
+async fn hard_delete_scrubs_payload_and_attempt_bodies() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let now = Utc::now().to_rfc3339();
+    let event_id = seed_event(&db.pool, endpoint_id, "stripe", "dead", &now).await;
+    seed_attempt(&db.pool, event_id, &now, Some(500), None).await;
+    seed_attempt(&db.pool, event_id, &now, None, Some("timeout")).await;
+
+    let soft = delete_event(&db.pool, &EndpointScope::All, event_id, false)
+        .await
+        .expect("soft delete");
+    let erased = delete_event(&db.pool, &EndpointScope::All, event_id, true)
+        .await
+        .expect("hard delete");
+    assert_eq!(erased.deleted_at, soft.deleted_at);
+    assert!(erased.erased_at.is_some());
+
+    let (payload, headers): (String, String) =
+        sqlx::query_as("SELECT payload, headers FROM webhook_events WHERE id = ?")
+            .bind(event_id.to_string())
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert_eq!(payload, "");
+    assert_eq!(headers, "{}");
+
+    let attempts: Vec<(String, Option<i64>)> = sqlx::query_as(
+        "SELECT request_body, response_status FROM webhook_attempt_logs WHERE event_id = ?",
+    )
+    .bind(event_id.to_string())
+    .fetch_all(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(attempts.len(), 2, "attempt rows are kept");
+    assert!(attempts.iter().all(|(body, _)| body.is_empty()));
+    assert!(attempts.iter().any(|(_, status)| *status == Some(500)));
+}
+
+#[tokio::test]
+async fn hard_delete_drops_metadata_and_payload_event_type() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let now = Utc::now().to_rfc3339();
+    let stripe = seed_event(&db.pool, endpoint_id, "stripe", "delivered", &now).await;
+    let github = seed_event(&db.pool, endpoint_id, "github", "delivered", &now).await;
+    for (event_id, event_type, metadata) in [
+        (stripe, "invoice.paid", r#"{"customer_id":"cus_123"}"#),
+        (github, "push", r#"{"organization":"cus_123"}"#),
+    ] {
+        sqlx::query("UPDATE webhook_events SET event_type = ?, metadata = ? WHERE id = ?")
+            .bind(event_type)
+            .bind(metadata)
+            .bind(event_id.to_string())
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+    let found = search_customer_events(&db.pool, &EndpointScope::All, "cus_123", 50)
+        .await
+        .expect("search");
+    assert_eq!(found.events.len(), 2);
+
+    for event_id in [stripe, github] {
+        delete_event(&db.pool, &EndpointScope::All, event_id, true)
+            .await
+            .expect("hard delete");
+    }
+    let erased: Vec<(String, Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT provider, event_type, metadata FROM webhook_events ORDER BY provider",
+    )
+    .fetch_all(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        erased,
+        [
+            ("github".to_string(), Some("push".to_string()), None),
+            ("stripe".to_string(), None, None),
+        ],
+        "GitHub sends the event type in a header, so it is not payload data"
+    );
+
+    // Even if the events were brought back, nothing is left to match.
+    sqlx::query("UPDATE webhook_events SET deleted_at = NULL")
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let found = search_customer_events(&db.pool, &EndpointScope::All, "cus_123", 50)
+        .await
+        .expect("search after erase");
+    assert!(found.events.is_empty());
+}
+
+#[tokio::test]
+async fn doctor_reports_and_repairs_impossible_states() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let now = Utc::now();
+    let received = now.to_rfc3339();
+
+    let leaseless = seed_event(&db.pool, endpoint_id, "stripe", "in_flight", &received).await;
+    let scheduled = seed_event(&db.pool, endpoint_id, "stripe", "delivered", &received).await;
+    let miscounted = seed_event(&db.pool, endpoint_id, "stripe", "pending", &received).await;
+    let healthy = seed_event(&db.pool, endpoint_id, "stripe", "delivered", &received).await;
+
+    sqlx::query("UPDATE webhook_events SET next_attempt_at = ?, attempts = 1 WHERE id = ?")
+        .bind((now + Duration::days(1)).to_rfc3339())
+        .bind(scheduled.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    for (id, attempts) in [(miscounted, 3), (healthy, 1)] {
+        sqlx::query("UPDATE webhook_events SET attempts = ? WHERE id = ?")
+            .bind(attempts)
+            .bind(id.to_string())
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+    for id in [scheduled, miscounted, healthy] {
+        seed_attempt(&db.pool, id, &received, Some(200), None).await;
+    }
+
+    let report = run_doctor(&db.pool, false).await.unwrap();
+    assert!(!report.repaired);
+    let mut found: Vec<(Uuid, DoctorIssueKind)> = report
+        .issues
+        .iter()
+        .map(|issue| (issue.event_id, issue.kind))
+        .collect();
+    found.sort_by_key(|(id, _)| *id);
+    let mut expected = vec![
+        (leaseless, DoctorIssueKind::InFlightWithoutLease),
+        (scheduled, DoctorIssueKind::DeliveredWithNextAttempt),
+        (miscounted, DoctorIssueKind::AttemptCountMismatch),
+    ];
+    expected.sort_by_key(|(id, _)| *id);
+    assert_eq!(found, expected);
+    assert_eq!(
+        get_event(&db.pool, &EndpointScope::All, leaseless)
+            .await
+            .unwrap()
+            .event
+            .status,
+        WebhookEventStatus::InFlight,
+        "report-only runs change nothing"
+    );
+
+    let report = run_doctor(&db.pool, true).await.unwrap();
+    assert!(report.repaired);
+    assert_eq!(report.issues.len(), 3);
+
+    let event = get_event(&db.pool, &EndpointScope::All, leaseless)
+        .await
+        .unwrap()
+        .event;
+    assert_eq!(event.status, WebhookEventStatus::Requeued);
+    let event = get_event(&db.pool, &EndpointScope::All, scheduled)
+        .await
+        .unwrap()
+        .event;
+    assert_eq!(event.status, WebhookEventStatus::Delivered);
+    assert_eq!(event.next_attempt_at, None);
+    let event = get_event(&db.pool, &EndpointScope::All, miscounted)
+        .await
+        .unwrap()
+        .event;
+    assert_eq!(event.attempts, 1);
+
+    let report = run_doctor(&db.pool, false).await.unwrap();
+    assert!(report.issues.is_empty());
+}
+
+#[tokio::test]
+async fn archived_attempt_logs_remain_visible_to_inspector() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let now = Utc::now();
+    let event_id = seed_event(&db.pool, endpoint_id, "stripe", "dead", &now.to_rfc3339()).await;
+    sqlx::query("UPDATE webhook_events SET attempts = 2 WHERE id = ?")
+        .bind(event_id.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    seed_attempt(
+        &db.pool,
+        event_id,
+        &(now - Duration::days(60)).to_rfc3339(),
+        Some(500),
+        None,
+    )
+    .await;
+    seed_attempt(&db.pool, event_id, &now.to_rfc3339(), Some(500), None).await;
+
+    let before = (now - Duration::days(30)).to_rfc3339();
+    assert_eq!(archive_attempt_logs(&db.pool, &before).await.unwrap(), 1);
+    assert_eq!(archive_attempt_logs(&db.pool, &before).await.unwrap(), 0);
+
+    let hot: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhook_attempt_logs")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(hot, 1);
+
+    let attempts = list_attempts(&db.pool, &EndpointScope::All, event_id)
+        .await
+        .unwrap()
+        .attempts;
+    assert_eq!(attempts.len(), 2, "archived attempts are still listed");
+    assert!(run_doctor(&db.pool, false).await.unwrap().issues.is_empty());
+
+    delete_event(&db.pool, &EndpointScope::All, event_id, true)
+        .await
+        .unwrap();
+    let bodies: Vec<String> =
+        sqlx::query_scalar("SELECT request_body FROM webhook_attempt_logs_all WHERE event_id = ?")
+            .bind(event_id.to_string())
+            .fetch_all(&db.pool)
+            .await
+            .unwrap();
+    assert_eq!(bodies, vec![String::new(), String::new()]);
+}
+
+#[tokio::test]
+async fn attempt_request_renders_as_masked_curl() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let event_id = seed_event(
+        &db.pool,
+        endpoint_id,
+        "stripe",
+        "dead",
+        "2024-01-01T00:00:00Z",
+    )
+    .await;
+    let attempt_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO webhook_attempt_logs \
+        (id, event_id, attempt_no, started_at, finished_at, request_headers, request_body) \
+        VALUES (?, ?, 1, '2024-01-01T00:00:01Z', '2024-01-01T00:00:02Z', ?, ?)",
+    )
+    .bind(attempt_id.to_string())
+    .bind(event_id.to_string())
+    .bind(r#"{"content-type":"application/json","x-webhook-signature":"v1=abc"}"#)
+    .bind(r#"{"note":"it's"}"#)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    let request = get_attempt_request(&db.pool, &EndpointScope::All, attempt_id)
+        .await
+        .unwrap();
+    assert_eq!(request.event_id, event_id);
+    assert_eq!(request.target_url, "https://example.com/hook");
+
+    let masked = render_curl(
+        &request.target_url,
+        &request.request_headers,
+        &request.request_body,
+        false,
+    );
+    assert_eq!(masked.masked_headers, vec!["x-webhook-signature"]);
+    assert_eq!(
+        masked.command,
+        "curl -X POST 'https://example.com/hook' \\\n  \
+         -H 'content-type: application/json' \\\n  \
+         -H 'x-webhook-signature: [REDACTED]' \\\n  \
+         --data-raw '{\"note\":\"it'\\''s\"}'"
+    );
+
+    let revealed = render_curl(
+        &request.target_url,
+        &request.request_headers,
+        &request.request_body,
+        true,
+    );
+    assert!(revealed.masked_headers.is_empty());
+    assert!(revealed.command.contains("x-webhook-signature: v1=abc"));
+
+    let other_endpoint = seed_endpoint(&db.pool, "https://other.example.com/hook").await;
+    let scoped = EndpointScope::Endpoints(vec![other_endpoint]);
+    assert!(matches!(
+        get_attempt_request(&db.pool, &scoped, attempt_id).await,
+        Err(StoreError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn storage_report_sizes_tables_and_projects_growth() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let now = Utc::now();
+    let event_id = seed_event(
+        &db.pool,
+        endpoint_id,
+        "stripe",
+        "delivered",
+        &now.to_rfc3339(),
+    )
+    .await;
+    seed_attempt(
+        &db.pool,
+        event_id,
+        &(now - Duration::days(10)).to_rfc3339(),
+        Some(200),
+        None,
+    )
+    .await;
+    seed_attempt(&db.pool, event_id, &now.to_rfc3339(), Some(200), None).await;
+    sqlx::query("INSERT INTO usage_rollups (endpoint_id, day, bytes_stored) VALUES (?, ?, ?)")
+        .bind(endpoint_id.to_string())
+        .bind((now - Duration::days(1)).format("%Y-%m-%d").to_string())
+        .bind(7_000_i64)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+    let report = storage_report(&db.pool, 7, Some(1_000_000)).await.unwrap();
+    assert!(report.db_bytes > 0);
+    assert!(report.file_bytes.is_some());
+    let events = report
+        .tables
+        .iter()
+        .find(|table| table.name == "webhook_events")
+        .expect("events table");
+    assert_eq!(events.rows, 1);
+    assert!(events.bytes > 0);
+    assert!(
+        report
+            .tables
+            .windows(2)
+            .all(|pair| pair[0].bytes >= pair[1].bytes)
+    );
+
+    let projection = report.projection;
+    assert_eq!(projection.attempt_logs_due_for_archive, 1);
+    assert_eq!(projection.daily_growth_bytes, 1_000);
+    assert_eq!(
+        projection.days_until_full,
+        Some((1_000_000 - report.db_bytes).max(0) / 1_000)
+    );
+}
+
+#[tokio::test]
+async fn list_events_filters_by_updated_since() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let received = Utc::now().to_rfc3339();
+    let repaired = seed_event(&db.pool, endpoint_id, "stripe", "in_flight", &received).await;
+    let untouched = seed_event(&db.pool, endpoint_id, "stripe", "pending", &received).await;
+    sqlx::query("UPDATE webhook_events SET updated_at = '2020-01-01T00:00:00Z'")
+        .execute(&db.pool)
+        .await
+        .expect("backdate updated_at");
+
+    run_doctor(&db.pool, true).await.expect("run_doctor");
+
+    let since = (Utc::now() - Duration::minutes(1)).to_rfc3339_opts(SecondsFormat::Secs, true);
+    let params = ListEventsParams {
+        limit: 50,
+        before: None,
+        status: None,
+        endpoint_id: None,
+        source_id: None,
+        group_id: None,
+        provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: Some(since.clone()),
+        worker_version: None,
+        fields: None,
+        metadata: Vec::new(),
+    };
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
+
+    let ids: Vec<Uuid> = result.events.iter().map(|item| item.event.id).collect();
+    assert_eq!(ids, vec![repaired]);
+    assert!(
+        result.events[0]
+            .event
+            .updated_at
+            .as_deref()
+            .is_some_and(|updated_at| updated_at >= since.as_str())
+    );
+
+    let untouched = get_event(&db.pool, &EndpointScope::All, untouched)
+        .await
+        .expect("get_event");
+    assert_eq!(
+        untouched.event.updated_at.as_deref(),
+        Some("2020-01-01T00:00:00Z")
+    );
+}
+
+#[tokio::test]
+async fn replay_skip_if_pending_refuses_duplicate_copies() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let group = create_endpoint_group(&db.pool, "customer-a", None)
+        .await
+        .expect("create group");
+    set_endpoint_group(&db.pool, endpoint_id, Some(group.id))
+        .await
+        .expect("assign group");
+    let now = Utc::now().to_rfc3339();
+    let dead = seed_event(&db.pool, endpoint_id, "stripe", "dead", &now).await;
+
+    let first = replay_group(&db.pool, group.id, false, true, None, None, None)
+        .await
+        .expect("first group replay")
+        .replayed_event_ids;
+    assert_eq!(first.len(), 1);
+    let again = replay_group(&db.pool, group.id, false, true, None, None, None)
+        .await
+        .expect("second group replay");
+    assert!(again.replayed_event_ids.is_empty());
+
+    let result = replay_event(
+        &db.pool,
+        &EndpointScope::All,
+        dead,
+        false,
+        true,
+        ReplayAttemptBudget::Reset,
+        None,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(StoreError::Conflict(reason)) if reason == "pending_replay_exists"
+    ));
+
+    sqlx::query("UPDATE webhook_events SET status = 'delivered' WHERE id = ?")
+        .bind(first[0].to_string())
+        .execute(&db.pool)
+        .await
+        .expect("deliver replay");
+    replay_event(
+        &db.pool,
+        &EndpointScope::All,
+        dead,
+        false,
+        true,
+        ReplayAttemptBudget::Reset,
+        None,
+    )
+    .await
+    .expect("replay once the copy is delivered");
+}
+
+#[tokio::test]
+async fn replay_loop_is_refused_until_forced_or_delivered() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let now = Utc::now().to_rfc3339();
+    let original = seed_event(&db.pool, endpoint_id, "stripe", "dead", &now).await;
+    let replay = |event_id, loop_limit| {
+        replay_event(
+            &db.pool,
+            &EndpointScope::All,
+            event_id,
+            false,
+            false,
+            ReplayAttemptBudget::Reset,
+            loop_limit,
+        )
+    };
+    let mark = |event_id: Uuid, status: &'static str| {
+        sqlx::query("UPDATE webhook_events SET status = ? WHERE id = ?")
+            .bind(status)
+            .bind(event_id.to_string())
+            .execute(&db.pool)
+    };
+
+    let mut latest = original;
+    for _ in 0..2 {
+        latest = replay(latest, Some(2)).await.expect("replay").event.id;
+        mark(latest, "dead").await.expect("mark replay dead");
+    }
+    let refused = replay(latest, Some(2)).await;
+    assert!(matches!(
+        refused,
+        Err(StoreError::Conflict(reason)) if reason == "replay_loop_detected"
+    ));
+    let refused_from_root = replay(original, Some(2)).await;
+    assert!(matches!(
+        refused_from_root,
+        Err(StoreError::Conflict(reason)) if reason == "replay_loop_detected"
+    ));
+
+    let forced = replay(latest, None).await.expect("forced replay").event.id;
+    mark(forced, "delivered")
+        .await
+        .expect("deliver forced replay");
+    replay(forced, Some(2))
+        .await
+        .expect("replay once the chain has delivered");
+}
+
+#[tokio::test]
+async fn deleted_replays_leave_the_lineage_and_the_loop_count() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let now = Utc::now().to_rfc3339();
+    let original = seed_event(&db.pool, endpoint_id, "stripe", "dead", &now).await;
+    let replay = |event_id, loop_limit| {
+        replay_event(
+            &db.pool,
+            &EndpointScope::All,
+            event_id,
+            false,
+            false,
+            ReplayAttemptBudget::Reset,
+            loop_limit,
+        )
+    };
+
+    let first = replay(original, None).await.expect("first replay").event.id;
+    let second = replay(original, None)
+        .await
+        .expect("second replay")
+        .event
+        .id;
+    seed_attempt(&db.pool, first, &now, Some(500), None).await;
+    seed_attempt(&db.pool, second, &now, Some(500), None).await;
+    let refused = replay(original, Some(2)).await;
+    assert!(matches!(
+        refused,
+        Err(StoreError::Conflict(reason)) if reason == "replay_loop_detected"
+    ));
+
+    delete_event(&db.pool, &EndpointScope::All, first, false)
+        .await
+        .expect("delete replay");
+    let lineage = get_event_lineage(&db.pool, &EndpointScope::All, second)
+        .await
+        .expect("lineage");
+    let ids: Vec<Uuid> = lineage.events.iter().map(|event| event.id).collect();
+    assert_eq!(ids, vec![original, second]);
+    assert_eq!(lineage.cumulative_attempts, 1);
+    replay(original, Some(2))
+        .await
+        .expect("replay once a copy is deleted");
+}
+
+#[tokio::test]
+async fn group_replay_operation_tracks_progress() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let group = create_endpoint_group(&db.pool, "customer-a", None)
+        .await
+        .expect("create group");
+    set_endpoint_group(&db.pool, endpoint_id, Some(group.id))
+        .await
+        .expect("assign group");
+    let now = Utc::now().to_rfc3339();
+    let already_replayed = seed_event(&db.pool, endpoint_id, "stripe", "dead", &now).await;
+    seed_event(&db.pool, endpoint_id, "stripe", "dead", &now).await;
+    replay_event(
+        &db.pool,
+        &EndpointScope::All,
+        already_replayed,
+        false,
+        false,
+        ReplayAttemptBudget::Reset,
+        None,
+    )
+    .await
+    .expect("replay one event");
+
+    let target = DeadEventTarget::Group(group.id);
+    let operation_id = start_operation(&db.pool, OperationKind::GroupReplay, target, "ops:1")
+        .await
+        .expect("start operation");
+    let running = list_operations(&db.pool).await.expect("list operations");
+    assert_eq!(running[0].status, OperationStatus::Running);
+
+    let result = replay_group(
+        &db.pool,
+        group.id,
+        false,
+        true,
+        None,
+        None,
+        Some(operation_id),
+    )
+    .await;
+    finish_operation(&db.pool, operation_id, &result)
+        .await
+        .expect("finish operation");
+    let replayed = result.expect("group replay").replayed_event_ids;
+    assert_eq!(replayed.len(), 1);
+
+    let operations = list_operations(&db.pool).await.expect("list operations");
+    assert_eq!(operations.len(), 1);
+    let operation = &operations[0];
+    assert_eq!(operation.id, operation_id);
+    assert_eq!(operation.status, OperationStatus::Completed);
+    assert_eq!(operation.actor, "ops:1");
+    assert_eq!(operation.group_id, Some(group.id));
+    assert_eq!((operation.processed, operation.total), (2, 2));
+    assert_eq!(operation.event_ids, replayed);
+    assert!(operation.failures.is_empty());
+    assert!(operation.finished_at.is_some());
+}
+
+#[tokio::test]
+async fn group_replay_reports_chains_over_the_loop_limit() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let group = create_endpoint_group(&db.pool, "customer-a", None)
+        .await
+        .expect("create group");
+    set_endpoint_group(&db.pool, endpoint_id, Some(group.id))
+        .await
+        .expect("assign group");
+    let now = Utc::now().to_rfc3339();
+    let looping = seed_event(&db.pool, endpoint_id, "stripe", "dead", &now).await;
+    let fresh = seed_event(&db.pool, endpoint_id, "stripe", "dead", &now).await;
+    let copy = replay_event(
+        &db.pool,
+        &EndpointScope::All,
+        looping,
+        false,
+        false,
+        ReplayAttemptBudget::Reset,
+        None,
+    )
+    .await
+    .expect("replay once")
+    .event
+    .id;
+    sqlx::query("UPDATE webhook_events SET status = 'dead' WHERE id = ?")
+        .bind(copy.to_string())
+        .execute(&db.pool)
+        .await
+        .expect("mark replay dead");
+
+    let target = DeadEventTarget::Group(group.id);
+    let operation_id = start_operation(&db.pool, OperationKind::GroupReplay, target, "ops:1")
+        .await
+        .expect("start operation");
+    let result = replay_group(
+        &db.pool,
+        group.id,
+        false,
+        false,
+        None,
+        Some(1),
+        Some(operation_id),
+    )
+    .await;
+    finish_operation(&db.pool, operation_id, &result)
+        .await
+        .expect("finish operation");
+    let replayed = result.expect("group replay");
+    assert_eq!(replayed.replayed_event_ids.len(), 1);
+    let mut failed: Vec<Uuid> = replayed.failures.iter().map(|f| f.event_id).collect();
+    failed.sort();
+    let mut expected = vec![looping, copy];
+    expected.sort();
+    assert_eq!(failed, expected);
+    assert!(
+        replayed
+            .failures
+            .iter()
+            .all(|f| f.reason == "replay_loop_detected")
+    );
+    let created = get_event(
+        &db.pool,
+        &EndpointScope::All,
+        replayed.replayed_event_ids[0],
+    )
+    .await
+    .expect("replayed event")
+    .event;
+    assert_eq!(created.replayed_from_event_id, Some(fresh));
+
+    let operations = list_operations(&db.pool).await.expect("list operations");
+    let mut recorded: Vec<Uuid> = operations[0].failures.iter().map(|f| f.event_id).collect();
+    recorded.sort();
+    assert_eq!(recorded, expected);
+    assert_eq!((operations[0].processed, operations[0].total), (3, 3));
+}
+
+#[tokio::test]
+async fn export_events_flattens_attempts_into_csv_rows() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/webhook").await;
+    let retried = seed_event(
+        &db.pool,
+        endpoint_id,
+        "stripe",
+        "requeued",
+        "2024-01-01T00:00:00Z",
+    )
+    .await;
+    let untouched = seed_event(
+        &db.pool,
+        endpoint_id,
+        "github",
+        "pending",
+        "2024-01-02T00:00:00Z",
+    )
+    .await;
+    seed_event(
+        &db.pool,
+        endpoint_id,
+        "stripe",
+        "pending",
+        "2024-01-03T00:00:00Z",
+    )
+    .await;
+    sqlx::query("UPDATE webhook_events SET last_error = 'bad \"gateway\", retrying' WHERE id = ?")
+        .bind(retried.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    for (attempt_no, started_at, finished_at, status) in [
+        (1, "2024-01-01T00:00:01Z", "2024-01-01T00:00:01.250Z", 500),
+        (2, "2024-01-01T00:01:00Z", "2024-01-01T00:01:00.500Z", 502),
+    ] {
+        sqlx::query(
+            "INSERT INTO webhook_attempt_logs \
+            (id, event_id, attempt_no, started_at, finished_at, request_headers, request_body, \
+             response_status) \
+            VALUES (?, ?, ?, ?, ?, '{}', '{}', ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(retried.to_string())
+        .bind(attempt_no)
+        .bind(started_at)
+        .bind(finished_at)
+        .bind(status)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    }
+
+    let params = ExportEventsParams {
+        received_before: Some("2024-01-03T00:00:00Z".to_string()),
+        ..ExportEventsParams::default()
+    };
+    let records = export_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .unwrap();
+    let ids: Vec<Uuid> = records.iter().map(|record| record.id).collect();
+    assert_eq!(ids, vec![retried, untouched]);
+    assert_eq!(records[0].last_response_status, Some(502));
+    assert_eq!(records[0].last_duration_ms, Some(500));
+    assert_eq!(records[0].total_duration_ms, Some(750));
+    assert_eq!(records[1].last_duration_ms, None);
+
+    let csv = render_csv(&records);
+    let lines: Vec<&str> = csv.split("\r\n").collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], CSV_COLUMNS.join(","));
+    assert_eq!(
+        lines[1],
+        format!(
+            "{retried},{endpoint_id},stripe,,,requeued,0,2024-01-01T00:00:00Z,,\
+             2024-01-01T00:01:00Z,502,500,750,\"bad \"\"gateway\"\", retrying\""
+        )
+    );
+    assert_eq!(
+        lines[2],
+        format!("{untouched},{endpoint_id},github,,,pending,0,2024-01-02T00:00:00Z,,,,,,")
+    );
+    assert_eq!(lines[3], "");
+
+    let ndjson = render_ndjson(&records).unwrap();
+    assert_eq!(ndjson.lines().count(), 2);
+}
+
+#[tokio::test]
+async fn export_day_writes_a_date_partitioned_file() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/webhook").await;
+    let late = seed_event(
+        &db.pool,
+        endpoint_id,
+        "stripe",
+        "delivered",
+        "2024-01-01T23:59:59Z",
+    )
+    .await;
+    let early = seed_event(
+        &db.pool,
+        endpoint_id,
+        "stripe",
+        "dead",
+        "2024-01-01T00:00:00Z",
+    )
+    .await;
+    seed_event(
+        &db.pool,
+        endpoint_id,
+        "stripe",
+        "pending",
+        "2024-01-02T00:00:00Z",
+    )
+    .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let sink = open_sink(&ExportSink::Dir(dir.path().to_path_buf())).unwrap();
+    let day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    let key = export_day(&db.pool, sink.as_ref(), ExportFormat::Csv, day)
+        .await
+        .unwrap();
+    assert_eq!(key, "dt=2024-01-01/events.csv");
+    let path = export_path(dir.path(), day, ExportFormat::Csv);
+    assert_eq!(path, dir.path().join("dt=2024-01-01").join("events.csv"));
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let ids: Vec<&str> = contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split(',').next())
+        .collect();
+    assert_eq!(ids, vec![early.to_string(), late.to_string()]);
+    assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn list_events_filters_by_worker_version() {
+    let db = setup_db().await;
+    let endpoint_id = seed_endpoint(&db.pool, "https://example.com/hook").await;
+    let received = Utc::now().to_rfc3339();
+    let bad_build = seed_event(&db.pool, endpoint_id, "stripe", "pending", &received).await;
+    let good_build = seed_event(&db.pool, endpoint_id, "stripe", "pending", &received).await;
+    seed_event(&db.pool, endpoint_id, "stripe", "pending", &received).await;
+    for (event_id, worker_version) in [(bad_build, "1.4.0-rc1"), (good_build, "1.3.2")] {
+        sqlx::query(
+            "INSERT INTO webhook_attempt_logs \
+            (id, event_id, attempt_no, started_at, finished_at, request_headers, request_body, \
+             worker_id, worker_version, worker_region) \
+            VALUES (?, ?, 1, ?, ?, '{}', '{}', 'worker-1', ?, 'eu-west-1')",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(event_id.to_string())
+        .bind(&received)
+        .bind(&received)
+        .bind(worker_version)
+        .execute(&db.pool)
+        .await
+        .expect("insert attempt");
+    }
+
+    let params = ListEventsParams {
+        limit: 50,
+        before: None,
+        status: None,
+        endpoint_id: None,
+        source_id: None,
+        group_id: None,
+        provider: None,
+        stuck_after_minutes: None,
+        schema_invalid: None,
+        is_replay: None,
+        has_replays: None,
+        include_last_attempt: false,
+        updated_since: None,
+        worker_version: Some("1.4.0-rc1".to_string()),
+        fields: None,
+        metadata: Vec::new(),
+    };
+    let result = list_events(&db.pool, &EndpointScope::All, &params)
+        .await
+        .expect("list_events");
+    let ids: Vec<Uuid> = result.events.iter().map(|item| item.event.id).collect();
+    assert_eq!(ids, vec![bad_build]);
+
+    let attempts = list_attempts(&db.pool, &EndpointScope::All, bad_build)
+        .await
+        .expect("list_attempts");
+    let attempt = &attempts.attempts[0];
+    assert_eq!(attempt.worker_id.as_deref(), Some("worker-1"));
+    assert_eq!(attempt.worker_version.as_deref(), Some("1.4.0-rc1"));
+    assert_eq!(attempt.worker_region.as_deref(), Some("eu-west-1"));
+}
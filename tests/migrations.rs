@@ -0,0 +1,248 @@
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use receiver::jobs::{create_job, get_job, spawn_backfill_job};
+use receiver::migrate::{
+    BackfillError, MIGRATOR, MigrationError, contract_backfill, find_backfill, get_backfill,
+    repair_migrations, run_migrations,
+};
+use receiver::types::{JobKind, JobStatus};
+use sqlx::SqlitePool;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use tempfile::NamedTempFile;
+use uuid::Uuid;
+
+async fn connect(db_file: &NamedTempFile) -> SqlitePool {
+    let options = SqliteConnectOptions::new()
+        .filename(db_file.path())
+        .create_if_missing(true)
+        .busy_timeout(std::time::Duration::from_secs(5));
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .expect("connect sqlite")
+}
+
+async fn index_exists(pool: &SqlitePool, name: &str) -> bool {
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'index' AND name = ?)",
+    )
+    .bind(name)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn concurrent_startups_both_migrate() {
+    let db_file = NamedTempFile::new().expect("create temp sqlite file");
+    let first = connect(&db_file).await;
+    let second = connect(&db_file).await;
+
+    let (a, b) = tokio::join!(run_migrations(&first), run_migrations(&second));
+    a.unwrap();
+    b.unwrap();
+
+    let recorded: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM _sqlx_migrations WHERE success")
+        .fetch_one(&first)
+        .await
+        .unwrap();
+    assert_eq!(recorded, i64::try_from(MIGRATOR.iter().count()).unwrap());
+}
+
+#[tokio::test]
+async fn repair_finishes_partially_applied_migration() {
+    let db_file = NamedTempFile::new().expect("create temp sqlite file");
+    let pool = connect(&db_file).await;
+    run_migrations(&pool).await.unwrap();
+
+    // Leave 0050 as a crash would: recorded unfinished with its last index
+    // missing.
+    sqlx::query("DROP INDEX idx_jobs_status")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE _sqlx_migrations SET success = FALSE WHERE version = 50")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let err = run_migrations(&pool).await.unwrap_err();
+    assert!(matches!(err, MigrationError::Dirty { version: 50, .. }));
+    assert!(err.to_string().contains("receiver migrate --repair"));
+
+    let report = repair_migrations(&pool).await.unwrap();
+    assert_eq!(report.repaired.len(), 1);
+    assert_eq!(report.repaired[0].version, 50);
+    assert_eq!(report.repaired[0].skipped_statements, 3);
+    assert!(report.applied.is_empty());
+    assert!(index_exists(&pool, "idx_jobs_status").await);
+
+    let report = run_migrations(&pool).await.unwrap();
+    assert!(report.applied.is_empty());
+}
+
+async fn insert_event(
+    pool: &SqlitePool,
+    replayed_from: Option<Uuid>,
+    event_type: Option<&str>,
+) -> Uuid {
+    let event_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO webhook_events \
+            (id, endpoint_id, replayed_from_event_id, provider, headers, payload, status, attempts, \
+            received_at, event_type) \
+        VALUES (?, 'endpoint', ?, 'stripe', '{}', '{}', 'dead', 1, '2024-01-01T00:00:00Z', ?)",
+    )
+    .bind(event_id.to_string())
+    .bind(replayed_from.map(|id| id.to_string()))
+    .bind(event_type)
+    .execute(pool)
+    .await
+    .unwrap();
+    event_id
+}
+
+#[tokio::test]
+async fn backfill_fills_expanded_column_before_contract() {
+    let db_file = NamedTempFile::new().expect("create temp sqlite file");
+    let pool = connect(&db_file).await;
+    run_migrations(&pool).await.unwrap();
+    sqlx::query("INSERT INTO endpoints (id, target_url) VALUES ('endpoint', 'http://localhost')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let original = insert_event(&pool, None, Some("invoice.paid")).await;
+    let replay = insert_event(&pool, Some(original), None).await;
+
+    let backfill = find_backfill("replay_event_type").unwrap();
+    let err = contract_backfill(&pool, backfill).await.unwrap_err();
+    assert!(matches!(err, BackfillError::Conflict(ref code) if code == "backfill_incomplete"));
+
+    let job = create_job(&pool, JobKind::Backfill, "admin", None)
+        .await
+        .unwrap();
+    spawn_backfill_job(pool.clone(), job.id, backfill)
+        .await
+        .unwrap();
+    let job = get_job(&pool, job.id).await.unwrap();
+    assert_eq!(job.status, JobStatus::Completed);
+    assert_eq!(job.processed, job.total);
+
+    let event_type: Option<String> =
+        sqlx::query_scalar("SELECT event_type FROM webhook_events WHERE id = ?")
+            .bind(replay.to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(event_type.as_deref(), Some("invoice.paid"));
+    let state = get_backfill(&pool, backfill).await.unwrap();
+    assert_eq!(state.job_id, Some(job.id));
+    assert!(state.completed_at.is_some());
+
+    let contracted = contract_backfill(&pool, backfill).await.unwrap();
+    assert!(contracted.contracted_at.is_some());
+    let err = contract_backfill(&pool, backfill).await.unwrap_err();
+    assert!(matches!(err, BackfillError::Conflict(ref code) if code == "backfill_contracted"));
+}
+
+#[tokio::test]
+async fn priority_backfill_contract_adds_index() {
+    let db_file = NamedTempFile::new().expect("create temp sqlite file");
+    let pool = connect(&db_file).await;
+    run_migrations(&pool).await.unwrap();
+    sqlx::query("INSERT INTO endpoints (id, target_url) VALUES ('endpoint', 'http://localhost')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    for _ in 0..3 {
+        insert_event(&pool, None, None).await;
+    }
+
+    let backfill = find_backfill("event_priority").unwrap();
+    let job = create_job(&pool, JobKind::Backfill, "admin", None)
+        .await
+        .unwrap();
+    spawn_backfill_job(pool.clone(), job.id, backfill)
+        .await
+        .unwrap();
+    let unset: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM webhook_events WHERE priority IS NULL")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(unset, 0);
+
+    contract_backfill(&pool, backfill).await.unwrap();
+    assert!(index_exists(&pool, "idx_webhook_events_priority").await);
+}
+
+#[tokio::test]
+async fn derived_backfills_extract_from_stored_events() {
+    let db_file = NamedTempFile::new().expect("create temp sqlite file");
+    let pool = connect(&db_file).await;
+    run_migrations(&pool).await.unwrap();
+    sqlx::query("INSERT INTO endpoints (id, target_url) VALUES ('endpoint', 'http://localhost')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let rows = [
+        (
+            "stripe",
+            "{}",
+            r#"{"id":"evt_1","type":"charge.succeeded"}"#,
+        ),
+        (
+            "github",
+            r#"{"x-github-delivery":"delivery-1","x-github-event":"push"}"#,
+            "{}",
+        ),
+    ];
+    for (provider, headers, payload) in rows {
+        sqlx::query(
+            "INSERT INTO webhook_events \
+                (id, endpoint_id, provider, headers, payload, status, attempts, received_at) \
+            VALUES (?, 'endpoint', ?, ?, ?, 'delivered', 1, '2024-01-01T00:00:00Z')",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(provider)
+        .bind(headers)
+        .bind(payload)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    for name in ["event_type", "provider_event_id"] {
+        let job = create_job(&pool, JobKind::Backfill, "admin", None)
+            .await
+            .unwrap();
+        spawn_backfill_job(pool.clone(), job.id, find_backfill(name).unwrap())
+            .await
+            .unwrap();
+        let job = get_job(&pool, job.id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Completed, "{name}: {:?}", job.error);
+    }
+
+    let derived: Vec<(String, Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT provider, event_type, provider_event_id FROM webhook_events ORDER BY provider",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        derived,
+        vec![
+            (
+                "github".to_string(),
+                Some("push".to_string()),
+                Some("delivery-1".to_string())
+            ),
+            (
+                "stripe".to_string(),
+                Some("charge.succeeded".to_string()),
+                Some("evt_1".to_string())
+            ),
+        ]
+    );
+}
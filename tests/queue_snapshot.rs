@@ -0,0 +1,216 @@
+#![allow(
+    clippy::expect_used,
+    clippy::unwrap_used,
+    clippy::needless_raw_string_hashes
+)]
+
+use std::io::Cursor;
+
+use chrono::Utc;
+use receiver::{
+    inspector::{EndpointScope, create_endpoint_group, list_attempts, set_endpoint_group},
+    snapshot::{restore_snapshot, write_snapshot},
+};
+use sqlx::{
+    Connection, SqliteConnection, SqlitePool,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+use std::fs;
+use tempfile::NamedTempFile;
+use uuid::Uuid;
+
+struct TestDb {
+    pool: SqlitePool,
+    _db_file: NamedTempFile,
+}
+
+async fn setup_db() -> TestDb {
+    let db_file = NamedTempFile::new().expect("create temp sqlite file");
+    let options = SqliteConnectOptions::new()
+        .filename(db_file.path())
+        .create_if_missing(true)
+        .busy_timeout(std::time::Duration::from_millis(500));
+
+    let mut conn = SqliteConnection::connect_with(&options)
+        .await
+        .expect("connect sqlite for migrations");
+    sqlx::query("PRAGMA foreign_keys = ON;")
+        .execute(&mut conn)
+        .await
+        .expect("enable foreign keys");
+    run_migrations(&mut conn).await.expect("run migrations");
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .after_connect(|conn, _| {
+            Box::pin(async move {
+                sqlx::query("PRAGMA foreign_keys = ON;")
+                    .execute(conn)
+                    .await?;
+                Ok(())
+            })
+        })
+        .connect_with(options)
+        .await
+        .expect("connect sqlite");
+
+    TestDb {
+        pool,
+        _db_file: db_file,
+    }
+}
+
+async fn run_migrations(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    let mut entries: Vec<_> = fs::read_dir("migrations")
+        .map_err(sqlx::Error::Io)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("sql"))
+        .collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let contents = fs::read_to_string(entry.path()).map_err(sqlx::Error::Io)?;
+        for stmt in contents.split(';') {
+            let stmt = stmt.trim();
+            if !stmt.is_empty() {
+                sqlx::query(stmt).execute(&mut *conn).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn seed_event(pool: &SqlitePool, endpoint_id: Uuid, status: &str) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO webhook_events (
+            id, endpoint_id, provider, headers, payload, status, attempts, received_at
+        ) VALUES (?, ?, 'stripe', '{}', '{"n":1}', ?, 1, ?)
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(endpoint_id.to_string())
+    .bind(status)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await
+    .expect("insert event");
+
+    sqlx::query(
+        "INSERT INTO webhook_attempt_logs \
+        (id, event_id, attempt_no, started_at, finished_at, request_headers, request_body, \
+         response_status) \
+        VALUES (?, ?, 1, ?, ?, '{}', '{}', 503)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(id.to_string())
+    .bind(Utc::now().to_rfc3339())
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await
+    .expect("insert attempt");
+    id
+}
+
+async fn count(pool: &SqlitePool, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn snapshot_round_trips_queued_and_dead_events() {
+    let source = setup_db().await;
+    let endpoint_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO endpoints (id, target_url, default_ttl_seconds) VALUES (?, ?, 60)")
+        .bind(endpoint_id.to_string())
+        .bind("https://example.com/hook")
+        .execute(&source.pool)
+        .await
+        .unwrap();
+    let group = create_endpoint_group(&source.pool, "customer-a", Some(10))
+        .await
+        .unwrap();
+    set_endpoint_group(&source.pool, endpoint_id, Some(group.id))
+        .await
+        .unwrap();
+    let source_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO sources (id, slug, provider, endpoint_id, secret, created_at) \
+        VALUES (?, 'stripe-main', 'stripe', ?, 's3cret', ?)",
+    )
+    .bind(source_id.to_string())
+    .bind(endpoint_id.to_string())
+    .bind(Utc::now().to_rfc3339())
+    .execute(&source.pool)
+    .await
+    .unwrap();
+    let pending = seed_event(&source.pool, endpoint_id, "pending").await;
+    sqlx::query("UPDATE webhook_events SET source_id = ? WHERE id = ?")
+        .bind(source_id.to_string())
+        .bind(pending.to_string())
+        .execute(&source.pool)
+        .await
+        .unwrap();
+    let dead = seed_event(&source.pool, endpoint_id, "dead").await;
+    seed_event(&source.pool, endpoint_id, "delivered").await;
+
+    let mut archive = Vec::new();
+    let written = write_snapshot(&source.pool, &mut archive).await.unwrap();
+    assert_eq!(written.endpoint_groups, 1);
+    assert_eq!(written.endpoints, 1);
+    assert_eq!(written.sources, 1);
+    assert_eq!(
+        written.webhook_events, 2,
+        "delivered events are not carried"
+    );
+    assert_eq!(written.webhook_attempt_logs, 2);
+
+    let target = setup_db().await;
+    let restored = restore_snapshot(&target.pool, Cursor::new(&archive))
+        .await
+        .unwrap();
+    assert_eq!(restored.webhook_events, 2);
+    assert_eq!(count(&target.pool, "webhook_attempt_logs").await, 2);
+
+    let (group_id, ttl): (Option<String>, Option<i64>) =
+        sqlx::query_as("SELECT group_id, default_ttl_seconds FROM endpoints WHERE id = ?")
+            .bind(endpoint_id.to_string())
+            .fetch_one(&target.pool)
+            .await
+            .unwrap();
+    assert_eq!(group_id, Some(group.id.to_string()));
+    assert_eq!(ttl, Some(60));
+    for event_id in [pending, dead] {
+        let attempts = list_attempts(&target.pool, &EndpointScope::All, event_id)
+            .await
+            .unwrap();
+        assert_eq!(attempts.attempts.len(), 1);
+    }
+
+    let again = restore_snapshot(&target.pool, Cursor::new(&archive))
+        .await
+        .unwrap();
+    assert_eq!(again.webhook_events, 0, "restoring twice is idempotent");
+    assert_eq!(count(&target.pool, "webhook_events").await, 2);
+}
+
+#[tokio::test]
+async fn restore_rejects_unknown_columns() {
+    let db = setup_db().await;
+    let archive = concat!(
+        r#"{"format":"receiver-snapshot","version":1,"created_at":"2026-01-01T00:00:00Z"}"#,
+        "\n",
+        r#"{"table":"endpoints","row":{"id":"e1","target_url":"x","evil); DROP TABLE endpoints; --":1}}"#,
+        "\n",
+    );
+
+    assert!(
+        restore_snapshot(&db.pool, Cursor::new(archive))
+            .await
+            .is_err()
+    );
+    assert_eq!(count(&db.pool, "endpoints").await, 0);
+}